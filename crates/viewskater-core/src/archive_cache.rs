@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::io::Read;
 use std::collections::HashMap;
@@ -11,6 +11,8 @@ pub enum ArchiveType {
     Zip,
     Rar,
     SevenZ,
+    #[cfg(feature = "parquet")]
+    Parquet,
 }
 
 /// Archive cache that stores reusable archive instances per pane
@@ -87,6 +89,9 @@ impl ArchiveCache {
             ArchiveType::Zip => self.read_zip_file(&path, filename),
             ArchiveType::Rar => self.read_rar_file(&path, filename),
             ArchiveType::SevenZ => self.read_7z_file(&path, filename),
+            // Parquet rows are always preloaded, so a miss here means the row does not exist
+            #[cfg(feature = "parquet")]
+            ArchiveType::Parquet => Err(format!("Parquet row not found: {}", filename).into()),
         }
     }
     
@@ -95,7 +100,7 @@ impl ArchiveCache {
         // Get or create cached ZIP archive
         if self.zip_archive.is_none() {
             debug!("Creating new ZIP archive instance for {:?}", path);
-            let file = std::io::BufReader::new(std::fs::File::open(path)?);
+            let file = std::io::BufReader::new(std::fs::File::open(crate::paths::long_path(path))?);
             let zip_archive = zip::ZipArchive::new(file)?;
             self.zip_archive = Some(Arc::new(std::sync::Mutex::new(zip_archive)));
         }
@@ -111,8 +116,9 @@ impl ArchiveCache {
     
     /// Read a file from RAR archive using simple filename comparison
     /// Uses the contributor's straightforward approach - simple and intuitive
-    fn read_rar_file(&mut self, path: &PathBuf, filename: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-        let mut archive = unrar::Archive::new(path).open_for_processing()?;
+    fn read_rar_file(&mut self, path: &Path, filename: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let archive_path = crate::paths::long_path(path).into_owned();
+        let mut archive = unrar::Archive::new(&archive_path).open_for_processing()?;
         let buffer = Vec::new();
         
         while let Some(header) = archive.read_header()? {
@@ -139,7 +145,7 @@ impl ArchiveCache {
         // Get or create cached 7z archive
         if self.sevenz_archive.is_none() {
             debug!("Creating new 7z archive instance for {:?}", path);
-            let reader = sevenz_rust2::ArchiveReader::open(path, sevenz_rust2::Password::empty())?;
+            let reader = sevenz_rust2::ArchiveReader::open(crate::paths::long_path(path), sevenz_rust2::Password::empty())?;
             self.sevenz_archive = Some(Arc::new(std::sync::Mutex::new(reader)));
         }
         
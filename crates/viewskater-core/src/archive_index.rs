@@ -0,0 +1,48 @@
+//! Entry listing for ZIP and 7z archives
+//!
+//! Both return `(name, unpacked size)` pairs in archive order for the files `keep` accepts,
+//! which is the first pass of opening an archive. RAR listing goes through the unrar
+//! library's own file API and is not covered here.
+use std::io::{Read, Seek};
+
+pub fn zip_entries<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    keep: impl Fn(&str) -> bool,
+) -> zip::result::ZipResult<Vec<(String, u64)>> {
+    let mut entries = Vec::new();
+    for i in 0..archive.len() {
+        let file = archive.by_index(i)?;
+        if file.is_file() && keep(file.name()) {
+            entries.push((file.name().to_string(), file.size()));
+        }
+    }
+    Ok(entries)
+}
+
+pub fn sevenz_entries(archive: &sevenz_rust2::Archive, keep: impl Fn(&str) -> bool) -> Vec<(String, u64)> {
+    archive.files.iter()
+        .filter(|entry| !entry.is_directory && keep(entry.name()))
+        .map(|entry| (entry.name().to_string(), entry.size()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    #[test]
+    fn test_zip_entries() {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        writer.add_directory("photos/", options).unwrap();
+        writer.start_file("photos/a.jpg", options).unwrap();
+        writer.write_all(b"abc").unwrap();
+        writer.start_file("notes.txt", options).unwrap();
+        let bytes = writer.finish().unwrap().into_inner();
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let entries = zip_entries(&mut archive, |name| name.ends_with(".jpg")).unwrap();
+        assert_eq!(entries, vec![("photos/a.jpg".to_string(), 3)]);
+    }
+}
@@ -0,0 +1,5 @@
+/// COCO annotation formats
+///
+/// Parsing of COCO dataset JSON files and decoding of their RLE segmentation masks.
+pub mod parser;
+pub mod rle_decoder;
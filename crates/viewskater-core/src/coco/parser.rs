@@ -35,6 +35,8 @@ pub struct CocoAnnotation {
     pub area: f32,
     #[serde(default)]
     pub iscrowd: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,  // Detection confidence (prediction files only)
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -68,6 +70,7 @@ impl CocoDataset {
     }
 
     /// Parse COCO JSON from a string
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(content: &str) -> Result<Self, String> {
         serde_json::from_str(content)
             .map_err(|e| format!("Failed to parse COCO JSON: {}", e))
@@ -156,6 +159,10 @@ impl CocoDataset {
 
         // Group annotations by image
         for ann in &self.annotations {
+            // Unvalidated datasets can still hold malformed boxes
+            let &[x, y, width, height] = ann.bbox.as_slice() else {
+                continue;
+            };
             if let Some(image) = image_map.get(&ann.image_id) {
                 let category_name = category_map
                     .get(&ann.category_id)
@@ -164,15 +171,12 @@ impl CocoDataset {
 
                 let image_ann = ImageAnnotation {
                     id: ann.id,
-                    bbox: BoundingBox {
-                        x: ann.bbox[0],
-                        y: ann.bbox[1],
-                        width: ann.bbox[2],
-                        height: ann.bbox[3],
-                    },
+                    bbox: BoundingBox { x, y, width, height },
                     category_id: ann.category_id,
                     category_name,
                     segmentation: ann.segmentation.clone(),
+                    score: ann.score,
+                    area: if ann.area > 0.0 { ann.area } else { width * height },
                 };
 
                 map.entry(image.file_name.clone())
@@ -198,6 +202,8 @@ pub struct ImageAnnotation {
     pub category_id: u64,
     pub category_name: String,
     pub segmentation: Option<CocoSegmentation>,
+    pub score: Option<f32>,  // Confidence score, present for model predictions
+    pub area: f32,  // Segmentation area, falls back to bbox area when missing
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -213,6 +219,11 @@ impl BoundingBox {
     pub fn to_corners(self) -> (f32, f32, f32, f32) {
         (self.x, self.y, self.x + self.width, self.y + self.height)
     }
+
+    /// Check whether a point in image coordinates lies inside the box
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x <= self.x + self.width && y >= self.y && y <= self.y + self.height
+    }
 }
 
 #[cfg(test)]
@@ -261,4 +272,24 @@ mod tests {
         assert_eq!(dataset.annotations.len(), 1);
         assert_eq!(dataset.categories.len(), 1);
     }
+
+    #[test]
+    fn test_annotation_map_skips_malformed_bbox() {
+        let coco_json = r#"{
+            "images": [{"id": 1, "file_name": "test.jpg"}],
+            "annotations": [
+                {"id": 1, "image_id": 1, "category_id": 1, "bbox": [10.0]},
+                {"id": 2, "image_id": 1, "category_id": 1, "bbox": [0.0, 0.0, 4.0, 5.0]}
+            ],
+            "categories": [{"id": 1, "name": "person"}]
+        }"#;
+
+        // Without validate_and_clean first
+        let dataset = CocoDataset::from_str(coco_json).unwrap();
+        let map = dataset.build_image_annotation_map();
+        let annotations = &map["test.jpg"];
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].id, 2);
+        assert_eq!(annotations[0].area, 20.0);
+    }
 }
@@ -6,6 +6,9 @@
 /// IMPORTANT: COCO RLE uses COLUMN-MAJOR (Fortran) order!
 use crate::coco::parser::CocoRLE;
 
+/// Largest mask decoded; a corrupt `size` would otherwise ask for an enormous buffer
+const MAX_MASK_PIXELS: usize = 1 << 28;
+
 /// Decode RLE to binary mask
 /// COCO RLE uses column-major order (Fortran-style), meaning it fills column-by-column
 pub fn decode_rle(rle: &CocoRLE) -> Vec<u8> {
@@ -15,7 +18,9 @@ pub fn decode_rle(rle: &CocoRLE) -> Vec<u8> {
 
     let height = rle.size[0] as usize;
     let width = rle.size[1] as usize;
-    let total_pixels = height * width;
+    let Some(total_pixels) = height.checked_mul(width).filter(|&pixels| pixels <= MAX_MASK_PIXELS) else {
+        return Vec::new();
+    };
 
     // Create mask in row-major order for easier access
     let mut mask = vec![0u8; total_pixels];
@@ -58,7 +63,7 @@ pub fn decode_rle(rle: &CocoRLE) -> Vec<u8> {
 /// Find contours in a binary mask using a simple marching squares algorithm
 /// Returns a list of polygons (each polygon is a list of (x, y) coordinates)
 pub fn mask_to_polygons(mask: &[u8], width: usize, height: usize, simplify_epsilon: f32) -> Vec<Vec<(f32, f32)>> {
-    if mask.is_empty() || width == 0 || height == 0 {
+    if mask.is_empty() || width == 0 || height == 0 || width.checked_mul(height) != Some(mask.len()) {
         return Vec::new();
     }
 
@@ -307,6 +312,16 @@ mod tests {
         assert_eq!(mask[8], 0);
     }
 
+    #[test]
+    fn test_malformed_rle() {
+        let huge = CocoRLE { size: vec![u32::MAX, u32::MAX], counts: vec![1] };
+        assert!(decode_rle(&huge).is_empty());
+        // Counts running past the mask stop at its end
+        let overlong = CocoRLE { size: vec![2, 2], counts: vec![1, u32::MAX, 7] };
+        assert_eq!(decode_rle(&overlong), vec![0, 1, 1, 1]);
+        assert!(mask_to_polygons(&[1, 1], 3, 3, 0.5).is_empty());
+    }
+
     #[test]
     fn test_perpendicular_distance() {
         let point = (1.0, 1.0);
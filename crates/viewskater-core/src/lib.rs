@@ -0,0 +1,35 @@
+//! Loading machinery of the ViewSkater image viewer, without the GUI
+//!
+//! These are the parts of the viewer that find and read images, kept free of winit, iced
+//! and wgpu so other tools and tests can use them:
+//!
+//! - [`path_source::PathSource`] says where an image comes from: a plain file, an entry
+//!   inside an archive, or archive content already read into memory.
+//! - [`path_index::PathIndex`] holds the image list of an opened folder, paging the paths of
+//!   very large collections out to disk.
+//! - [`archive_cache::ArchiveCache`] keeps ZIP, RAR and 7z archives open between reads and
+//!   holds preloaded archive content; [`archive_index`] lists the entries of ZIP and 7z files.
+//! - [`reader`] reads the encoded bytes of any `PathSource`, with the file size.
+//! - [`warm_cache`] reads a folder on slow storage into memory ahead of time, and the
+//!   reader serves it from there.
+//! - [`load_limits`] bounds how many files are read and decoded at once.
+//! - [`exif_utils::decode_with_exif_orientation`] decodes image bytes upright.
+//! - [`paths::long_path`] is the form of a path to open files with on Windows.
+//! - [`coco`] (feature `coco`) parses COCO annotation files and decodes their RLE masks.
+//!
+//! The decoded image caches (`ImageCache` and its CPU/GPU variants) stay in the application:
+//! they hold wgpu textures and are driven by the panes' loading queues. Format decoders that
+//! depend on optional codecs and the viewer's source plugins also stay there and wrap
+//! [`reader`].
+
+pub mod archive_cache;
+pub mod archive_index;
+pub mod exif_utils;
+pub mod load_limits;
+pub mod path_index;
+pub mod path_source;
+pub mod paths;
+pub mod reader;
+pub mod warm_cache;
+#[cfg(feature = "coco")]
+pub mod coco;
@@ -0,0 +1,107 @@
+//! Limits on how many images are decoded and read from disk at once
+//!
+//! `decode_threads` bounds the blocking decode/upload jobs the loader runs in parallel;
+//! `max_concurrent_reads` bounds filesystem reads on top of that, so a spinning disk or a
+//! NAS can be kept to one or two streams while NVMe drives are given more. Both limits
+//! start at their defaults and can be changed at runtime with [`set_limits`]; a lowered
+//! limit takes effect as running jobs finish.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use once_cell::sync::Lazy;
+
+pub const DEFAULT_DECODE_THREADS: usize = 4;
+pub const DEFAULT_MAX_CONCURRENT_READS: usize = 4;
+
+pub static DECODE_SLOTS: Lazy<ConcurrencyLimit> = Lazy::new(|| ConcurrencyLimit::new(DEFAULT_DECODE_THREADS));
+pub static READ_SLOTS: Lazy<ConcurrencyLimit> = Lazy::new(|| ConcurrencyLimit::new(DEFAULT_MAX_CONCURRENT_READS));
+
+/// Counting limit whose size can change while slots are held
+#[derive(Debug)]
+pub struct ConcurrencyLimit {
+    limit: AtomicUsize,
+    in_use: AtomicUsize,
+}
+
+/// A held slot, released when dropped
+pub struct SlotGuard<'a> {
+    owner: &'a ConcurrencyLimit,
+}
+
+impl Drop for SlotGuard<'_> {
+    fn drop(&mut self) {
+        self.owner.in_use.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl ConcurrencyLimit {
+    pub fn new(limit: usize) -> Self {
+        Self { limit: AtomicUsize::new(limit.max(1)), in_use: AtomicUsize::new(0) }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit.load(Ordering::Acquire)
+    }
+
+    /// Change the limit; values below 1 are raised to 1
+    pub fn set_limit(&self, limit: usize) {
+        self.limit.store(limit.max(1), Ordering::Release);
+    }
+
+    /// Take a slot if one is free under the current limit
+    pub fn try_acquire(&self) -> Option<SlotGuard<'_>> {
+        let mut in_use = self.in_use.load(Ordering::Acquire);
+        loop {
+            if in_use >= self.limit() {
+                return None;
+            }
+            match self.in_use.compare_exchange_weak(in_use, in_use + 1, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return Some(SlotGuard { owner: self }),
+                Err(current) => in_use = current,
+            }
+        }
+    }
+
+    /// Wait for a slot
+    pub async fn acquire(&self) -> SlotGuard<'_> {
+        loop {
+            if let Some(guard) = self.try_acquire() {
+                return guard;
+            }
+            tokio::time::sleep(Duration::from_millis(2)).await;
+        }
+    }
+}
+
+/// Apply the `decode_threads` / `max_concurrent_reads` settings
+pub fn set_limits(decode_threads: usize, max_concurrent_reads: usize) {
+    DECODE_SLOTS.set_limit(decode_threads);
+    READ_SLOTS.set_limit(max_concurrent_reads);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_changes_apply_to_new_acquires() {
+        let limit = ConcurrencyLimit::new(2);
+        let first = limit.try_acquire().expect("first slot");
+        let second = limit.try_acquire().expect("second slot");
+        assert!(limit.try_acquire().is_none());
+
+        // Lowering the limit keeps held slots but blocks new ones until enough are released
+        limit.set_limit(1);
+        drop(first);
+        assert!(limit.try_acquire().is_none());
+        drop(second);
+        let third = limit.try_acquire().expect("slot after release");
+
+        // Raising it frees room immediately; zero is treated as one
+        limit.set_limit(3);
+        assert!(limit.try_acquire().is_some());
+        drop(third);
+        limit.set_limit(0);
+        assert_eq!(limit.limit(), 1);
+    }
+}
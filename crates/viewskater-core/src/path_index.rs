@@ -0,0 +1,470 @@
+//! Image list of an opened folder, paged out to disk for very large collections
+//!
+//! Lists up to [`PAGED_THRESHOLD`] images keep every [`PathSource`] in memory. A longer list
+//! of filesystem paths is written to a temporary page file instead, a page at a time as
+//! [`PathIndexBuilder`] is given them, keeping only the byte offset of each page of
+//! [`PAGE_LEN`] paths; a page is read back the first time one of its paths is asked for, and
+//! [`PathIndex::trim`] lets go of pages far from the current image again. A million-image
+//! folder then holds a few tens of thousands of paths rather than gigabytes of them.
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::{Index, Range};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use log::{error, info, warn};
+
+use crate::path_source::PathSource;
+
+/// Paths per page
+pub const PAGE_LEN: usize = 4096;
+/// Lists longer than this are paged out
+pub const PAGED_THRESHOLD: usize = 200_000;
+/// Pages on either side of the current one that `trim` keeps loaded
+const KEEP_PAGES: usize = 8;
+
+static NEXT_PAGE_FILE: AtomicUsize = AtomicUsize::new(0);
+
+/// Temporary file with the paths of a paged list, removed once no list uses it
+struct PageFile {
+    path: PathBuf,
+    file: Mutex<File>,
+    /// Start of each page in the file, then its end
+    offsets: Vec<u64>,
+}
+
+impl PageFile {
+    fn create() -> io::Result<Self> {
+        let path = std::env::temp_dir().join(format!(
+            "viewskater_paths_{}_{}.idx",
+            std::process::id(),
+            NEXT_PAGE_FILE.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = File::options().read(true).write(true).create(true).truncate(true).open(&path)?;
+        Ok(Self { path, file: Mutex::new(file), offsets: vec![0] })
+    }
+
+    /// Pages written so far
+    fn pages(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// Writes a page of paths as length-prefixed encoded bytes after the last one
+    ///
+    /// A page that fails to write isn't counted, so the ones before it stay readable.
+    fn append_page<'a>(&mut self, paths: impl Iterator<Item = &'a PathBuf>) -> io::Result<()> {
+        let mut bytes = Vec::new();
+        for entry in paths {
+            let encoded = entry.as_os_str().as_encoded_bytes();
+            bytes.extend_from_slice(&(encoded.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(encoded);
+        }
+        let end = self.offsets[self.pages()];
+        {
+            let mut file = self.file.lock().map_err(|_| io::Error::other("page file lock poisoned"))?;
+            file.seek(SeekFrom::Start(end))?;
+            file.write_all(&bytes)?;
+        }
+        self.offsets.push(end + bytes.len() as u64);
+        Ok(())
+    }
+
+    fn read_page(&self, page: usize) -> io::Result<Box<[PathSource]>> {
+        let (start, end) = (self.offsets[page], self.offsets[page + 1]);
+        let mut bytes = vec![0; (end - start) as usize];
+        {
+            let mut file = self.file.lock().map_err(|_| io::Error::other("page file lock poisoned"))?;
+            file.seek(SeekFrom::Start(start))?;
+            file.read_exact(&mut bytes)?;
+        }
+
+        let mut paths = Vec::with_capacity(PAGE_LEN);
+        let mut rest = bytes.as_slice();
+        while let Some((len, tail)) = rest.split_first_chunk::<4>() {
+            let len = u32::from_le_bytes(*len) as usize;
+            let encoded = tail.get(..len).ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+            // SAFETY: written by `write` from `as_encoded_bytes` in this same process
+            let os_str = unsafe { std::ffi::OsStr::from_encoded_bytes_unchecked(encoded) };
+            paths.push(PathSource::Filesystem(PathBuf::from(os_str)));
+            rest = &tail[len..];
+        }
+        Ok(paths.into_boxed_slice())
+    }
+}
+
+impl Drop for PageFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Image paths of a pane, indexed like a slice
+#[derive(Clone, Default)]
+pub struct PathIndex {
+    len: usize,
+    pages: Vec<OnceLock<Box<[PathSource]>>>,
+    /// Where unloaded pages are read from; `None` when every page is in memory
+    store: Option<Arc<PageFile>>,
+}
+
+impl PathIndex {
+    /// Keeps every path in memory, whatever the length
+    pub fn new(paths: Vec<PathSource>) -> Self {
+        let len = paths.len();
+        let mut pages = Vec::with_capacity(len.div_ceil(PAGE_LEN));
+        let mut paths = paths.into_iter();
+        while pages.len() * PAGE_LEN < len {
+            pages.push(OnceLock::from(paths.by_ref().take(PAGE_LEN).collect::<Box<[_]>>()));
+        }
+        Self { len, pages, store: None }
+    }
+
+    /// Filesystem paths, paged out when there are more than `PAGED_THRESHOLD`
+    pub fn from_filesystem_paths(paths: Vec<PathBuf>) -> Self {
+        let mut builder = PathIndexBuilder::new();
+        paths.into_iter().for_each(|path| builder.push(path));
+        builder.finish()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_paged(&self) -> bool {
+        self.store.is_some()
+    }
+
+    fn page(&self, page: usize) -> &[PathSource] {
+        self.pages[page].get_or_init(|| self.read_page(page))
+    }
+
+    fn read_page(&self, page: usize) -> Box<[PathSource]> {
+        let Some(store) = &self.store else {
+            return Box::default();
+        };
+        store.read_page(page).unwrap_or_else(|e| {
+            // Empty paths fail to load like any unreadable file, keeping the indices valid
+            error!("Failed to read page {} of {}: {}", page, store.path.display(), e);
+            let len = PAGE_LEN.min(self.len - page * PAGE_LEN);
+            vec![PathSource::Filesystem(PathBuf::new()); len].into_boxed_slice()
+        })
+    }
+
+    pub fn get(&self, index: usize) -> Option<&PathSource> {
+        (index < self.len).then(|| &self.page(index / PAGE_LEN)[index % PAGE_LEN])
+    }
+
+    /// Every path in order, reading the pages not loaded yet
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &PathSource> + '_ {
+        (0..self.pages.len()).flat_map(move |page| self.page(page).iter())
+    }
+
+    pub fn to_vec(&self) -> Vec<PathSource> {
+        self.iter().cloned().collect()
+    }
+
+    /// Paths in `range`, in order
+    ///
+    /// Pages not loaded yet are read without being kept, so walking a paged list this way
+    /// holds one page at a time rather than loading all of it like `iter`.
+    pub fn range(&self, range: Range<usize>) -> impl DoubleEndedIterator<Item = PathSource> + '_ {
+        let (start, end) = (range.start.min(self.len), range.end.min(self.len));
+        let pages = if start < end { start / PAGE_LEN..(end - 1) / PAGE_LEN + 1 } else { 0..0 };
+        pages.flat_map(move |page| {
+            let first = page * PAGE_LEN;
+            let (from, to) = (start.max(first) - first, end.min(first + PAGE_LEN) - first);
+            let paths = match self.pages[page].get() {
+                Some(paths) => paths[from..to].to_vec(),
+                None => {
+                    let mut paths = self.read_page(page).into_vec();
+                    paths.truncate(to);
+                    paths.drain(..from);
+                    paths
+                }
+            };
+            paths.into_iter()
+        })
+    }
+
+    /// Every path but the one at `index` with its index, nearest first after it (or before
+    /// it when `forward` is false) and wrapping around the list; read like `range`
+    pub fn walk_from(&self, index: usize, forward: bool) -> Box<dyn Iterator<Item = (usize, PathSource)> + '_> {
+        let index = index.min(self.len);
+        let after = (index + 1).min(self.len);
+        if forward {
+            Box::new((after..self.len).zip(self.range(after..self.len)).chain((0..index).zip(self.range(0..index))))
+        } else {
+            Box::new((0..index).rev().zip(self.range(0..index).rev())
+                .chain((after..self.len).rev().zip(self.range(after..self.len).rev())))
+        }
+    }
+
+    /// Binary search over a list kept in the order `compare` follows
+    pub fn binary_search_by(&self, mut compare: impl FnMut(&PathSource) -> std::cmp::Ordering) -> Result<usize, usize> {
+        let (mut low, mut high) = (0, self.len);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match compare(&self[mid]) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => return Ok(mid),
+            }
+        }
+        Err(low)
+    }
+
+    /// Drops the loaded pages of a paged list that are far from `index`
+    pub fn trim(&mut self, index: usize) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        // Pages that couldn't be written out only exist in memory
+        let current = index / PAGE_LEN;
+        for (page, paths) in self.pages.iter_mut().enumerate().take(store.pages()) {
+            if page.abs_diff(current) > KEEP_PAGES {
+                paths.take();
+            }
+        }
+    }
+
+    /// Pages currently in memory
+    pub fn loaded_pages(&self) -> usize {
+        self.pages.iter().filter(|page| page.get().is_some()).count()
+    }
+}
+
+/// Builds a [`PathIndex`] from paths pushed in list order
+///
+/// Paths are kept in memory until there are more than [`PAGED_THRESHOLD`]; from then on every
+/// full page goes straight to the page file, so a listing never holds all of a huge folder.
+/// Should writing fail, the pages already written stay paged out and the rest stay in memory.
+pub struct PathIndexBuilder {
+    threshold: usize,
+    len: usize,
+    pages: Vec<OnceLock<Box<[PathSource]>>>,
+    /// Page being filled
+    current: Vec<PathBuf>,
+    store: Option<PageFile>,
+    /// Set once the page file couldn't be created or written
+    in_memory: bool,
+}
+
+impl PathIndexBuilder {
+    pub fn new() -> Self {
+        Self::with_threshold(PAGED_THRESHOLD)
+    }
+
+    /// Pages out once there are more than `threshold` paths, such as 0 for a sorted run of
+    /// a listing that is merged with others later
+    pub fn with_threshold(threshold: usize) -> Self {
+        Self {
+            threshold,
+            len: 0,
+            pages: Vec::new(),
+            current: Vec::new(),
+            store: None,
+            in_memory: false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push(&mut self, path: PathBuf) {
+        self.current.push(path);
+        self.len += 1;
+        if self.current.len() == PAGE_LEN {
+            self.finish_page();
+        }
+    }
+
+    fn finish_page(&mut self) {
+        if self.store.is_none() && !self.in_memory && self.len > self.threshold {
+            self.page_out();
+        }
+        let paths = std::mem::take(&mut self.current);
+        match &mut self.store {
+            Some(store) if !self.in_memory => match store.append_page(paths.iter()) {
+                Ok(()) => {
+                    self.pages.push(OnceLock::new());
+                    return;
+                }
+                Err(e) => self.stop_paging(e),
+            },
+            _ => {}
+        }
+        self.pages.push(OnceLock::from(paths.into_iter().map(PathSource::Filesystem).collect::<Box<[_]>>()));
+    }
+
+    /// Moves the pages held so far to a new page file
+    fn page_out(&mut self) {
+        let mut store = match PageFile::create() {
+            Ok(store) => store,
+            Err(e) => return self.stop_paging(e),
+        };
+        let written = self.pages.iter_mut().try_for_each(|page| {
+            if let Some(paths) = page.get() {
+                store.append_page(paths.iter().map(PathSource::path))?;
+            }
+            page.take();
+            Ok(())
+        });
+        self.store = Some(store);
+        if let Err(e) = written {
+            self.stop_paging(e);
+        }
+    }
+
+    fn stop_paging(&mut self, e: io::Error) {
+        warn!("Failed to page out the image list, keeping the rest of it in memory: {}", e);
+        self.in_memory = true;
+    }
+
+    pub fn finish(mut self) -> PathIndex {
+        if !self.current.is_empty() {
+            self.finish_page();
+        }
+        let store = self.store.map(Arc::new);
+        if let Some(store) = store.as_ref().filter(|_| !self.in_memory) {
+            info!("Paged {} paths out to {}", self.len, store.path.display());
+        }
+        PathIndex { len: self.len, pages: self.pages, store }
+    }
+}
+
+impl Default for PathIndexBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Index<usize> for PathIndex {
+    type Output = PathSource;
+
+    fn index(&self, index: usize) -> &PathSource {
+        self.get(index).unwrap_or_else(|| panic!("index {} out of range for {} paths", index, self.len))
+    }
+}
+
+impl From<Vec<PathSource>> for PathIndex {
+    fn from(paths: Vec<PathSource>) -> Self {
+        Self::new(paths)
+    }
+}
+
+impl fmt::Debug for PathIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PathIndex")
+            .field("len", &self.len)
+            .field("paged", &self.is_paged())
+            .field("loaded_pages", &self.loaded_pages())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbered(count: usize) -> Vec<PathBuf> {
+        (0..count).map(|i| PathBuf::from(format!("/photos/img_{:07}.jpg", i))).collect()
+    }
+
+    #[test]
+    fn test_in_memory_index() {
+        let index = PathIndex::new(numbered(PAGE_LEN + 3).into_iter().map(PathSource::Filesystem).collect());
+        assert!(!index.is_paged());
+        assert_eq!(index.len(), PAGE_LEN + 3);
+        assert_eq!(index[PAGE_LEN + 2].path(), &PathBuf::from(format!("/photos/img_{:07}.jpg", PAGE_LEN + 2)));
+        assert!(index.get(PAGE_LEN + 3).is_none());
+        assert_eq!(index.iter().count(), PAGE_LEN + 3);
+        assert!(PathIndex::default().is_empty());
+    }
+
+    #[test]
+    fn test_range_and_walk() {
+        let paths = numbered(PAGE_LEN * 2 + 5);
+        let index = PathIndex::new(paths.iter().cloned().map(PathSource::Filesystem).collect());
+        let range = PAGE_LEN - 2..PAGE_LEN * 2 + 1;
+        assert!(index.range(range.clone()).map(|source| source.path().clone()).eq(paths[range].iter().cloned()));
+        assert!(index.range(PAGE_LEN * 2 + 3..usize::MAX).rev().map(|source| source.path().clone()).eq(paths[PAGE_LEN * 2 + 3..].iter().rev().cloned()));
+        assert_eq!(index.range(5..5).count(), 0);
+
+        let small = PathIndex::new(numbered(5).into_iter().map(PathSource::Filesystem).collect());
+        let walk = |current: usize, forward: bool| small.walk_from(current, forward).map(|(i, _)| i).collect::<Vec<_>>();
+        assert_eq!(walk(1, true), vec![2, 3, 4, 0]);
+        assert_eq!(walk(1, false), vec![0, 4, 3, 2]);
+        assert_eq!(walk(4, true), vec![0, 1, 2, 3]);
+        assert!(small.walk_from(2, true).all(|(i, source)| source.path() == small[i].path()));
+        assert_eq!(PathIndex::default().walk_from(0, true).count(), 0);
+    }
+
+    #[test]
+    fn test_paged_index_loads_and_trims_pages() {
+        let paths = numbered(PAGED_THRESHOLD + 1);
+        let mut index = PathIndex::from_filesystem_paths(paths.clone());
+        assert!(index.is_paged());
+        assert_eq!(index.loaded_pages(), 0);
+        // Walking a range reads pages without keeping them
+        assert_eq!(index.range(10..PAGE_LEN * 3).count(), PAGE_LEN * 3 - 10);
+        assert_eq!(index.loaded_pages(), 0);
+
+        let last = paths.len() - 1;
+        assert_eq!(index[last].path(), &paths[last]);
+        assert_eq!(index[7].path(), &paths[7]);
+        assert_eq!(index.loaded_pages(), 2);
+        assert_eq!(index.binary_search_by(|source| source.path().cmp(&paths[123_456])), Ok(123_456));
+
+        index.trim(last);
+        assert_eq!(index.loaded_pages(), 1);
+        // Trimmed pages read back the same
+        assert_eq!(index[7].path(), &paths[7]);
+
+        // The page file goes with the last list using it
+        let file = index.store.as_ref().unwrap().path.clone();
+        let copy = index.clone();
+        drop(index);
+        assert!(file.exists());
+        drop(copy);
+        assert!(!file.exists());
+    }
+
+    #[test]
+    fn test_builder_pages_out_while_pushing() {
+        let mut builder = PathIndexBuilder::new();
+        for path in numbered(PAGED_THRESHOLD + PAGE_LEN + 1) {
+            builder.push(path);
+            if builder.len() == PAGED_THRESHOLD + PAGE_LEN {
+                // Past the threshold only the page being filled is held
+                assert!(builder.store.is_some());
+                assert!(builder.pages.iter().all(|page| page.get().is_none()));
+            }
+        }
+        let index = builder.finish();
+        assert!(index.is_paged());
+        assert_eq!(index.len(), PAGED_THRESHOLD + PAGE_LEN + 1);
+        assert_eq!(index[PAGED_THRESHOLD + PAGE_LEN].path(), &PathBuf::from(format!("/photos/img_{:07}.jpg", PAGED_THRESHOLD + PAGE_LEN)));
+        assert_eq!(index[3].path(), &PathBuf::from("/photos/img_0000003.jpg"));
+
+        let mut small = PathIndexBuilder::new();
+        small.push(PathBuf::from("a.jpg"));
+        let small = small.finish();
+        assert!(!small.is_paged());
+        assert_eq!(small[0].path(), &PathBuf::from("a.jpg"));
+
+        let mut run = PathIndexBuilder::with_threshold(0);
+        run.push(PathBuf::from("a.jpg"));
+        assert!(run.finish().is_paged());
+    }
+}
@@ -0,0 +1,38 @@
+//! Where an image is read from
+
+use std::path::PathBuf;
+
+/// PathSource enum for type-safe image loading with performance optimization
+#[derive(Clone, Debug)]
+pub enum PathSource {
+    /// Regular filesystem file - direct filesystem I/O
+    Filesystem(PathBuf),
+    /// Archive internal path - requires archive reading
+    Archive(PathBuf),
+    /// Preloaded archive content - available in ArchiveCache HashMap
+    Preloaded(PathBuf),
+}
+
+impl PathSource {
+    /// Get the underlying PathBuf for any variant
+    pub fn path(&self) -> &PathBuf {
+        match self {
+            PathSource::Filesystem(path) => path,
+            PathSource::Archive(path) => path,
+            PathSource::Preloaded(path) => path,
+        }
+    }
+    /// Get filename for display/sorting purposes
+    pub fn file_name(&self) -> std::borrow::Cow<'_, str> {
+        match self {
+            PathSource::Filesystem(_) => {
+                self.path().file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+            },
+            _ => {
+                std::borrow::Cow::from(self.path().display().to_string())
+            }
+        }
+    }
+}
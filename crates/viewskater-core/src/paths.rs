@@ -0,0 +1,57 @@
+//! Paths as the platform wants them for opening files
+
+use std::borrow::Cow;
+use std::path::Path;
+#[cfg(windows)]
+use std::path::PathBuf;
+
+/// Extended-length form of `path` on Windows (`\\?\C:\...` or `\\?\UNC\server\share\...`),
+/// which lifts the 260 character limit and lets network shares be opened reliably. Paths are
+/// only converted where files are opened, so the plain form is what gets displayed and stored.
+/// Other platforms get the path back unchanged.
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    #[cfg(windows)]
+    if let Some(extended) = path.to_str().and_then(extended_length_path) {
+        return Cow::Owned(PathBuf::from(extended));
+    }
+    Cow::Borrowed(path)
+}
+
+/// `\\?\` form of an absolute Windows path, or `None` for relative paths, paths already in
+/// device form, and paths with `.`, `..` or empty components (which the prefix stops Windows
+/// from normalizing)
+#[cfg_attr(not(windows), allow(dead_code))]
+fn extended_length_path(path: &str) -> Option<String> {
+    let path = path.replace('/', "\\");
+    if path.starts_with(r"\\?\") || path.starts_with(r"\\.\") {
+        return None;
+    }
+    let (prefix, rest) = if let Some(share) = path.strip_prefix(r"\\") {
+        (r"\\?\UNC\", share)
+    } else if path.len() >= 3 && path.as_bytes()[0].is_ascii_alphabetic() && path[1..].starts_with(":\\") {
+        (r"\\?\", path.as_str())
+    } else {
+        return None;
+    };
+    let components = rest.trim_end_matches('\\').split('\\').skip(1);
+    if components.clone().any(|c| c.is_empty() || c == "." || c == "..") {
+        return None;
+    }
+    Some(format!("{}{}", prefix, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extended_length_path() {
+        assert_eq!(extended_length_path(r"C:\data\img.png").as_deref(), Some(r"\\?\C:\data\img.png"));
+        assert_eq!(extended_length_path("C:/data/img.png").as_deref(), Some(r"\\?\C:\data\img.png"));
+        assert_eq!(extended_length_path(r"\\nas\share\set").as_deref(), Some(r"\\?\UNC\nas\share\set"));
+        assert_eq!(extended_length_path(r"\\?\C:\data"), None);
+        assert_eq!(extended_length_path(r"data\img.png"), None);
+        assert_eq!(extended_length_path(r"C:\data\..\img.png"), None);
+        assert_eq!(extended_length_path("/home/user/img.png"), None);
+    }
+}
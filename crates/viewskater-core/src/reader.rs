@@ -0,0 +1,133 @@
+//! Reading the encoded bytes of an image from wherever its [`PathSource`] points
+//!
+//! Filesystem files are served from the [`crate::warm_cache`] when their folder was read
+//! ahead, and otherwise read directly (memory-mapped above 1 MB). Archive entries and
+//! preloaded archive content go through the pane's [`ArchiveCache`].
+
+use std::fs::File;
+use std::io::{self, Read};
+use memmap2::Mmap;
+#[allow(unused_imports)]
+use log::{debug, warn};
+
+use crate::archive_cache::ArchiveCache;
+use crate::path_source::PathSource;
+use crate::paths::long_path;
+
+/// Files above this size are memory-mapped instead of read
+const MMAP_THRESHOLD: u64 = 1_048_576;
+
+/// Raw bytes of an image
+///
+/// `archive_cache` is required for archive and preloaded content.
+pub fn read_bytes(path_source: &PathSource, archive_cache: Option<&mut ArchiveCache>) -> io::Result<Vec<u8>> {
+    read_bytes_with_size(path_source, archive_cache).map(|(bytes, _)| bytes)
+}
+
+/// Raw bytes of an image and its size in bytes
+pub fn read_bytes_with_size(path_source: &PathSource, archive_cache: Option<&mut ArchiveCache>) -> io::Result<(Vec<u8>, u64)> {
+    match path_source {
+        PathSource::Filesystem(path) => {
+            if let Some(bytes) = crate::warm_cache::get(path) {
+                debug!("Using preloaded folder data for: {}", path.display());
+                let file_size = bytes.len() as u64;
+                return Ok((bytes, file_size));
+            }
+
+            let long = long_path(path);
+            if !long.exists() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Filesystem file not found: {}", path.display())
+                ));
+            }
+
+            let mut file = File::open(&long)?;
+            let file_size = file.metadata()?.len();
+
+            // Memory-map large files; for smaller ones regular reading is often faster
+            if file_size > MMAP_THRESHOLD {
+                let mmap = unsafe { Mmap::map(&file)? };
+                let bytes = mmap.to_vec();
+                debug!("Read {} bytes from filesystem using mmap: {}", bytes.len(), path.display());
+                Ok((bytes, file_size))
+            } else {
+                let mut buffer = Vec::with_capacity(file_size as usize);
+                file.read_to_end(&mut buffer)?;
+                debug!("Read {} bytes from filesystem: {}", buffer.len(), path.display());
+                Ok((buffer, file_size))
+            }
+        },
+
+        PathSource::Preloaded(path) => {
+            let cache = archive_cache.ok_or_else(|| io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Archive cache required for preloaded content"
+            ))?;
+
+            let path_str = path.to_string_lossy();
+            match cache.get_preloaded_data(&path_str) {
+                Some(data) => {
+                    debug!("Using preloaded data for: {}", path_str);
+                    Ok((data.to_vec(), data.len() as u64))
+                }
+                None => Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Preloaded data not found: {}", path_str)
+                )),
+            }
+        },
+
+        PathSource::Archive(path) => {
+            let cache = archive_cache.ok_or_else(|| io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Archive cache required for archive content"
+            ))?;
+
+            let path_str = path.to_string_lossy();
+            debug!("Reading from archive: {}", path_str);
+            let bytes = cache.read_from_archive(&path_str)
+                .map_err(|e| io::Error::other(format!("Failed to read from archive: {}", e)))?;
+            let file_size = bytes.len() as u64;
+            Ok((bytes, file_size))
+        }
+    }
+}
+
+/// Size of an image in bytes, or 0 when it cannot be found
+///
+/// Filesystem files only have their metadata read; archive entries are read from the cache.
+pub fn file_size(path_source: &PathSource, archive_cache: Option<&mut ArchiveCache>) -> u64 {
+    match path_source {
+        PathSource::Filesystem(path) => {
+            std::fs::metadata(long_path(path)).map(|m| m.len()).unwrap_or(0)
+        },
+        PathSource::Preloaded(path) => archive_cache
+            .and_then(|cache| cache.get_preloaded_data(&path.to_string_lossy()).map(|data| data.len() as u64))
+            .unwrap_or(0),
+        PathSource::Archive(path) => archive_cache
+            .and_then(|cache| cache.read_from_archive(&path.to_string_lossy()).ok().map(|bytes| bytes.len() as u64))
+            .unwrap_or(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_reads_files_and_reports_missing_ones() {
+        let path = std::env::temp_dir().join(format!("viewskater-core-reader-{}.bin", std::process::id()));
+        std::fs::write(&path, b"image bytes").unwrap();
+        let source = PathSource::Filesystem(path.clone());
+        assert_eq!(read_bytes_with_size(&source, None).unwrap(), (b"image bytes".to_vec(), 11));
+        assert_eq!(file_size(&source, None), 11);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read_bytes(&source, None).unwrap_err().kind(), io::ErrorKind::NotFound);
+        let archived = PathSource::Archive(PathBuf::from("set.zip/a.png"));
+        assert_eq!(read_bytes(&archived, None).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(file_size(&archived, None), 0);
+    }
+}
@@ -0,0 +1,120 @@
+//! Encoded bytes of a folder read ahead into memory
+//!
+//! Meant for folders on slow network storage: [`warm_up`] reads every file once, in list
+//! order, and [`crate::reader`] (and the application's slider loader) serve those paths
+//! from memory afterwards. Only one folder is kept; warming another one replaces it.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use once_cell::sync::Lazy;
+#[allow(unused_imports)]
+use log::{debug, info, warn};
+
+use crate::path_index::PathIndex;
+use crate::path_source::PathSource;
+
+static WARM_FILES: Lazy<Mutex<WarmStore>> = Lazy::new(|| Mutex::new(WarmStore::default()));
+
+#[derive(Default)]
+struct WarmStore {
+    directory: Option<String>,
+    files: HashMap<PathBuf, Arc<Vec<u8>>>,
+    bytes: usize,
+}
+
+/// Updates sent from a warm-up worker
+#[derive(Debug, Clone)]
+pub enum WarmUpEvent {
+    /// (files done, total files)
+    Progress(usize, usize),
+    Finished(Result<WarmUpSummary, String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WarmUpSummary {
+    pub files: usize,
+    pub bytes: usize,
+    pub cancelled: bool,
+    /// Stopped early because the memory limit was reached
+    pub truncated: bool,
+}
+
+/// Bytes of a warmed file, if present
+pub fn get(path: &Path) -> Option<Vec<u8>> {
+    let store = WARM_FILES.lock().ok()?;
+    store.files.get(path).map(|bytes| bytes.as_ref().clone())
+}
+
+/// Directory currently held in memory and its size in bytes
+pub fn current() -> Option<(String, usize)> {
+    let store = WARM_FILES.lock().ok()?;
+    store.directory.clone().map(|directory| (directory, store.bytes))
+}
+
+pub fn clear() {
+    if let Ok(mut store) = WARM_FILES.lock() {
+        *store = WarmStore::default();
+    }
+}
+
+/// Half of the currently available system memory
+pub fn default_memory_limit() -> usize {
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+    (system.available_memory() / 2) as usize
+}
+
+/// Read `paths` into memory in order, skipping non-filesystem entries
+///
+/// Deterministic: files are read strictly in list order and reading stops at the first
+/// file that would exceed `memory_limit`, so the same folder and limit always end up with
+/// the same warmed prefix. Checks `cancel` between files.
+pub fn warm_up(
+    directory: &str,
+    paths: &PathIndex,
+    memory_limit: usize,
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<WarmUpSummary, String> {
+    {
+        let mut store = WARM_FILES.lock().map_err(|_| "Warm cache lock poisoned".to_string())?;
+        if store.directory.as_deref() != Some(directory) {
+            *store = WarmStore { directory: Some(directory.to_string()), ..WarmStore::default() };
+        }
+    }
+
+    let total = paths.len();
+    let mut summary = WarmUpSummary { files: 0, bytes: 0, cancelled: false, truncated: false };
+    for (i, path_source) in paths.iter().enumerate() {
+        if cancel.load(Ordering::Relaxed) {
+            summary.cancelled = true;
+            break;
+        }
+        let PathSource::Filesystem(path) = path_source else {
+            progress(i + 1, total);
+            continue;
+        };
+
+        let already_warm = WARM_FILES.lock().map(|store| store.files.contains_key(path)).unwrap_or(false);
+        if !already_warm {
+            let bytes = std::fs::read(path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            let mut store = WARM_FILES.lock().map_err(|_| "Warm cache lock poisoned".to_string())?;
+            if store.bytes + bytes.len() > memory_limit {
+                warn!("Preload stopped at {} of {} files: memory limit of {} bytes reached", i, total, memory_limit);
+                summary.truncated = true;
+                break;
+            }
+            store.bytes += bytes.len();
+            summary.bytes += bytes.len();
+            store.files.insert(path.clone(), Arc::new(bytes));
+        }
+        summary.files += 1;
+        progress(i + 1, total);
+    }
+
+    info!("Preloaded {} file(s), {} bytes of {}", summary.files, summary.bytes, directory);
+    Ok(summary)
+}
@@ -0,0 +1,15 @@
+//! ZIP and 7z listing, as done when an archive is opened
+#![no_main]
+
+use std::io::Cursor;
+use libfuzzer_sys::fuzz_target;
+use viewskater_core::archive_index::{sevenz_entries, zip_entries};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(mut archive) = zip::ZipArchive::new(Cursor::new(data)) {
+        let _ = zip_entries(&mut archive, |_| true);
+    }
+    if let Ok(archive) = sevenz_rust2::Archive::read(&mut Cursor::new(data), &sevenz_rust2::Password::empty()) {
+        let _ = sevenz_entries(&archive, |_| true);
+    }
+});
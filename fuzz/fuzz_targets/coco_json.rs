@@ -0,0 +1,33 @@
+//! COCO annotation files, from detection through parsing to the masks drawn for them
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use viewskater_core::coco::parser::{CocoDataset, CocoSegmentation};
+use viewskater_core::coco::rle_decoder::{decode_rle, mask_to_polygons};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(content) = std::str::from_utf8(data) else {
+        return;
+    };
+    let _ = CocoDataset::is_coco_format(content);
+    let Ok(mut dataset) = CocoDataset::from_str(content) else {
+        return;
+    };
+    // Mapping must not rely on validate_and_clean having run
+    let _ = dataset.build_image_annotation_map();
+    let _ = dataset.validate_and_clean();
+    for annotations in dataset.build_image_annotation_map().values() {
+        for annotation in annotations {
+            if let Some(CocoSegmentation::Rle(rle)) = &annotation.segmentation {
+                let mask = decode_rle(rle);
+                // Contour tracing on huge masks only slows the fuzzer down
+                if mask.len() > 1 << 20 {
+                    continue;
+                }
+                if let [height, width] = rle.size[..] {
+                    let _ = mask_to_polygons(&mask, width as usize, height as usize, 1.0);
+                }
+            }
+        }
+    }
+});
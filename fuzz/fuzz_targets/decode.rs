@@ -0,0 +1,10 @@
+//! Image bytes through the EXIF-aware decoder; the format is guessed from the content
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use viewskater_core::exif_utils::{decode_with_exif_orientation, get_orientation_aware_dimensions};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = get_orientation_aware_dimensions(data);
+    let _ = decode_with_exif_orientation(data);
+});
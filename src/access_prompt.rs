@@ -0,0 +1,133 @@
+/// Dialog for folders the macOS sandbox keeps closed
+///
+/// A folder reached only through a file opened with "Open With", or whose saved permission (a
+/// security-scoped bookmark) no longer resolves, cannot be listed: the pane then shows just
+/// the opened file, or stays empty. Instead of leaving the reason to the crash-debug log, a
+/// dialog names the folder, says why its saved permission failed if it had one, and offers
+/// "Grant Access…", which opens the system folder panel on that folder and opens the path
+/// again once access is given.
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use iced_winit::core::{Element, Length, Alignment};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, column, container, text, button, Space};
+use log::debug;
+use once_cell::sync::Lazy;
+
+use crate::app::{DataViewer, Message};
+use crate::macos_file_access::macos_file_handler;
+
+#[derive(Debug, Clone)]
+pub enum AccessPromptMessage {
+    /// Ask for access to the folder and open the path again
+    GrantAccess,
+    Dismiss,
+}
+
+impl From<AccessPromptMessage> for Message {
+    fn from(msg: AccessPromptMessage) -> Self {
+        Message::AccessPromptAction(msg)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DeniedFolder {
+    /// File or folder that was opened
+    path: PathBuf,
+    folder: PathBuf,
+    pane_index: usize,
+    reason: String,
+}
+
+// Set from the pane while it loads, which has no access to the app state
+static DENIED: Lazy<Mutex<Option<DeniedFolder>>> = Lazy::new(|| Mutex::new(None));
+
+/// Shows the dialog for `folder`, which could not be listed when `path` was opened in pane
+/// `pane_index`
+pub fn report(path: &Path, folder: &Path, pane_index: usize) {
+    let reason = macos_file_handler::resolve_failure(&folder.to_string_lossy())
+        .unwrap_or_else(|| "ViewSkater has not been given access to this folder yet.".to_string());
+    debug!("No access to {}: {}", folder.display(), reason);
+    if let Ok(mut denied) = DENIED.lock() {
+        *denied = Some(DeniedFolder {
+            path: path.to_path_buf(),
+            folder: folder.to_path_buf(),
+            pane_index,
+            reason,
+        });
+    }
+}
+
+pub fn handle_access_prompt_message(app: &mut DataViewer, msg: AccessPromptMessage) -> Task<Message> {
+    let Some(denied) = DENIED.lock().ok().and_then(|mut denied| denied.take()) else {
+        return Task::none();
+    };
+    match msg {
+        AccessPromptMessage::GrantAccess => {
+            if macos_file_handler::request_directory_access_with_optimized_dialog(&denied.folder.to_string_lossy()) {
+                app.initialize_dir_path_sync(&denied.path, denied.pane_index)
+            } else {
+                Task::none()
+            }
+        }
+        AccessPromptMessage::Dismiss => Task::none(),
+    }
+}
+
+pub fn dialog<'a>() -> Option<Element<'a, Message, WinitTheme, Renderer>> {
+    let denied = DENIED.lock().ok()?.clone()?;
+    let name = denied.folder.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| denied.folder.display().to_string());
+    let shown = if denied.path != denied.folder {
+        "Until then, only the opened file is shown."
+    } else {
+        "Until then, its images cannot be shown."
+    };
+
+    let content = column![
+        text(format!("No access to \"{}\"", name))
+            .size(18)
+            .font(iced_winit::core::Font {
+                family: iced_winit::core::font::Family::Name("Roboto"),
+                weight: iced_winit::core::font::Weight::Bold,
+                stretch: iced_winit::core::font::Stretch::Normal,
+                style: iced_winit::core::font::Style::Normal,
+            }),
+        text(format!(
+            "{}\n\n{}\n\nGrant access to the folder to browse all of its images. {}",
+            denied.folder.display(), denied.reason, shown
+        )).size(13),
+        row![
+            Space::with_width(Length::Fill),
+            button(text("Not Now"))
+                .padding([3, 10])
+                .on_press(AccessPromptMessage::Dismiss.into()),
+            button(text("Grant Access…"))
+                .padding([3, 10])
+                .on_press(AccessPromptMessage::GrantAccess.into()),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    ]
+    .spacing(10)
+    .width(Length::Fixed(420.0));
+
+    Some(container(content)
+        .padding(15)
+        .style(|theme: &WinitTheme| {
+            iced_widget::container::Style {
+                background: Some(theme.extended_palette().background.base.color.into()),
+                text_color: Some(theme.extended_palette().primary.weak.text),
+                border: iced_winit::core::Border {
+                    color: theme.extended_palette().background.strong.color,
+                    width: 1.0,
+                    radius: iced_winit::core::border::Radius::from(8.0),
+                },
+                ..Default::default()
+            }
+        })
+        .into())
+}
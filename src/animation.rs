@@ -0,0 +1,277 @@
+/// Playback of animated GIF and APNG files
+///
+/// The cache holds an animated file's first frame like any other image. Once such a file is
+/// shown, all of its frames are decoded on a worker thread and uploaded as textures of their
+/// own, and the image shader draws whichever frame is due as their delays pass, so the rest
+/// of the app still sees a single texture per image. A bar above the footer pauses playback
+/// and steps through the frames one at a time.
+///
+/// Frames are matched to the texture of the image they belong to and dropped once it is no
+/// longer shown.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::{wgpu, Renderer};
+use iced_wgpu::engine::CompressionStrategy;
+use iced_widget::{row, container, text, button};
+use once_cell::sync::Lazy;
+use log::{info, warn};
+
+use crate::app::{DataViewer, Message};
+use crate::archive_cache::ArchiveCache;
+use crate::cache::img_cache::PathSource;
+use crate::file_io::AnimationFrame;
+use crate::pane::Pane;
+
+/// RGBA bytes the frames of one file may take; longer animations are cut short
+const MAX_ANIMATION_BYTES: usize = 512 * 1024 * 1024;
+/// Browsers show frames with a delay this short or shorter for `DEFAULT_DELAY` instead
+const MIN_DELAY: Duration = Duration::from_millis(10);
+const DEFAULT_DELAY: Duration = Duration::from_millis(100);
+
+type TextureKey = wgpu::Id<wgpu::Texture>;
+
+#[derive(Debug)]
+struct Playback {
+    frames: Vec<(Arc<wgpu::Texture>, Duration)>,
+    current: usize,
+    /// When the current frame started showing
+    frame_started: Instant,
+}
+
+impl Playback {
+    fn step(&mut self, forward: bool) {
+        let count = self.frames.len();
+        self.current = if forward { (self.current + 1) % count } else { (self.current + count - 1) % count };
+        self.frame_started = Instant::now();
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    playing: bool,
+    /// `None` while the frames are decoded, and for files that turn out to be still images
+    animations: HashMap<TextureKey, Option<Playback>>,
+}
+
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State {
+    playing: true,
+    animations: HashMap::new(),
+}));
+
+#[derive(Debug, Clone)]
+pub enum AnimationMessage {
+    Decoded(TextureKey, Result<Option<Vec<AnimationFrame>>, String>),
+    TogglePlay,
+    StepBackward,
+    StepForward,
+}
+
+impl From<AnimationMessage> for Message {
+    fn from(msg: AnimationMessage) -> Self {
+        Message::AnimationAction(msg)
+    }
+}
+
+fn frame_delay(delay: Duration) -> Duration {
+    if delay <= MIN_DELAY { DEFAULT_DELAY } else { delay }
+}
+
+/// Frame showing `elapsed` after frame `current` started, and how long it has shown by then
+fn advance(delays: &[Duration], current: usize, elapsed: Duration) -> (usize, Duration) {
+    let cycle: Duration = delays.iter().sum();
+    if cycle.is_zero() {
+        return (current, elapsed);
+    }
+    // Whole loops end where they started
+    let mut elapsed = Duration::from_nanos((elapsed.as_nanos() % cycle.as_nanos()) as u64);
+    let mut index = current;
+    while elapsed >= delays[index] {
+        elapsed -= delays[index];
+        index = (index + 1) % delays.len();
+    }
+    (index, elapsed)
+}
+
+/// Texture of the frame due now when `texture` is the image of an animation
+///
+/// Called by the image shader on every redraw, which is what moves playback along.
+pub fn current_frame(texture: &wgpu::Texture) -> Option<Arc<wgpu::Texture>> {
+    let mut state = STATE.lock().ok()?;
+    let playing = state.playing;
+    let playback = state.animations.get_mut(&texture.global_id())?.as_mut()?;
+    if playing {
+        let delays: Vec<Duration> = playback.frames.iter().map(|(_, delay)| *delay).collect();
+        let (index, shown_for) = advance(&delays, playback.current, playback.frame_started.elapsed());
+        playback.current = index;
+        playback.frame_started = Instant::now() - shown_for;
+    }
+    Some(Arc::clone(&playback.frames[playback.current].0))
+}
+
+/// Whether a shown animation is playing, which needs a redraw every frame
+pub fn is_playing() -> bool {
+    STATE.lock().is_ok_and(|state| state.playing && state.animations.values().any(Option::is_some))
+}
+
+fn is_animation_candidate(source: &PathSource) -> bool {
+    source.path().extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gif") || ext.eq_ignore_ascii_case("png"))
+}
+
+fn decode_file(source: &PathSource, archive_cache: Option<&Mutex<ArchiveCache>>) -> Result<Option<Vec<AnimationFrame>>, String> {
+    let bytes = match archive_cache {
+        Some(archive_cache) => {
+            let mut guard = archive_cache.lock().map_err(|e| e.to_string())?;
+            crate::file_io::read_image_bytes(source, Some(&mut *guard))
+        }
+        None => crate::file_io::read_image_bytes(source, None),
+    }
+    .map_err(|e| e.to_string())?;
+    Ok(crate::file_io::decode_animation(&bytes, MAX_ANIMATION_BYTES))
+}
+
+/// Starts decoding the frames of GIFs and PNGs newly shown in the panes, and forgets the
+/// animations no longer shown
+pub fn request(panes: &[Pane]) -> Option<Task<Message>> {
+    let mut state = STATE.lock().ok()?;
+    let shown: Vec<(TextureKey, &Pane)> = panes.iter()
+        .filter(|pane| pane.dir_loaded)
+        .filter_map(|pane| Some((pane.scene.as_ref()?.get_texture()?.global_id(), pane)))
+        .collect();
+    state.animations.retain(|key, _| shown.iter().any(|(shown_key, _)| shown_key == key));
+
+    let mut tasks = Vec::new();
+    for (key, pane) in shown {
+        if state.animations.contains_key(&key) {
+            continue;
+        }
+        let Some(source) = pane.img_cache.image_paths.get(pane.img_cache.current_index).cloned() else {
+            continue;
+        };
+        state.animations.insert(key, None);
+        if !is_animation_candidate(&source) {
+            continue;
+        }
+        let archive_cache = pane.has_compressed_file.then(|| Arc::clone(&pane.archive_cache));
+        tasks.push(Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || decode_file(&source, archive_cache.as_deref()))
+                    .await
+                    .unwrap_or_else(|e| Err(e.to_string()))
+            },
+            move |result| AnimationMessage::Decoded(key, result).into()
+        ));
+    }
+    (!tasks.is_empty()).then(|| Task::batch(tasks))
+}
+
+/// Pause and move every shown animation one frame
+fn step_all(state: &mut State, forward: bool) {
+    state.playing = false;
+    for playback in state.animations.values_mut().flatten() {
+        playback.step(forward);
+    }
+}
+
+pub fn handle_animation_message(app: &mut DataViewer, msg: AnimationMessage) -> Task<Message> {
+    let Ok(mut state) = STATE.lock() else {
+        return Task::none();
+    };
+    match msg {
+        AnimationMessage::Decoded(key, result) => match result {
+            Ok(Some(frames)) => {
+                // Ignore frames of an image that went away while they were decoded
+                let Some(entry) = state.animations.get_mut(&key) else {
+                    return Task::none();
+                };
+                info!("Playing animation of {} frames", frames.len());
+                let frames = frames.into_iter().map(|frame| {
+                    let (width, height) = frame.image.dimensions();
+                    let texture = crate::cache::cache_utils::create_and_upload_texture(
+                        &app.device, &app.queue, frame.image.as_raw(), width, height, CompressionStrategy::None);
+                    (Arc::new(texture), frame_delay(frame.delay))
+                }).collect();
+                *entry = Some(Playback { frames, current: 0, frame_started: Instant::now() });
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to decode animation: {}", e),
+        },
+        AnimationMessage::TogglePlay => {
+            state.playing = !state.playing;
+            // Resume from the start of the frame that was paused on
+            for playback in state.animations.values_mut().flatten() {
+                playback.frame_started = Instant::now();
+            }
+        }
+        AnimationMessage::StepBackward => step_all(&mut state, false),
+        AnimationMessage::StepForward => step_all(&mut state, true),
+    }
+    Task::none()
+}
+
+/// Play/pause and frame step buttons while an animation is shown, or nothing
+///
+/// The frame number is only shown while paused, since the view isn't rebuilt as frames
+/// change during playback.
+pub fn view<'a>() -> Element<'a, Message, WinitTheme, Renderer> {
+    let status = STATE.lock().ok().and_then(|state| {
+        let playback = state.animations.values().flatten().next()?;
+        Some((state.playing, playback.current, playback.frames.len()))
+    });
+    let Some((playing, current, count)) = status else {
+        return container(text("")).height(0).into();
+    };
+
+    let label_style = |_theme: &WinitTheme| iced_widget::text::Style {
+        color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+    };
+    let label = if playing {
+        format!("Animation, {} frames", count)
+    } else {
+        format!("Frame {} / {}", current + 1, count)
+    };
+    let control = |label: &'static str, msg: AnimationMessage| {
+        button(text(label).size(11))
+            .padding([1, 6])
+            .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+            .on_press(msg.into())
+    };
+
+    let content = row![
+        text(label).size(11).style(label_style),
+        control("<", AnimationMessage::StepBackward),
+        control(if playing { "Pause" } else { "Play" }, AnimationMessage::TogglePlay),
+        control(">", AnimationMessage::StepForward),
+    ]
+    .spacing(8)
+    .align_y(Alignment::Center);
+
+    container(content)
+        .padding([2, 8])
+        .width(Length::Fill)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advance_through_frames() {
+        let ms = Duration::from_millis;
+        let delays = [ms(100), ms(50), ms(200)];
+        assert_eq!(advance(&delays, 0, ms(40)), (0, ms(40)));
+        assert_eq!(advance(&delays, 0, ms(120)), (1, ms(20)));
+        assert_eq!(advance(&delays, 1, ms(260)), (0, ms(10)));
+        // Whole loops are skipped
+        assert_eq!(advance(&delays, 2, ms(350 * 4 + 30)), (2, ms(30)));
+
+        assert_eq!(frame_delay(Duration::ZERO), DEFAULT_DELAY);
+        assert_eq!(frame_delay(ms(40)), ms(40));
+    }
+}
@@ -8,7 +8,8 @@ mod settings_widget;
 use iced_core::Length;
 use iced_core::alignment::Horizontal;
 // Re-exports
-pub use message::{Message, DirectoryEnumResult, DirectoryEnumError};
+pub use message::{Message, NavigationMessage, PaneMessage, OverlayMessage, SettingsMessage, IoMessage};
+pub use message::{DirectoryEnumResult, DirectoryEnumError, ImagesLoadedResult, ListingEvent};
 pub use settings_widget::{RuntimeSettings, SettingsWidget};
 
 #[warn(unused_imports)]
@@ -54,7 +55,9 @@ use iced_winit::core::{Color, Element};
 
 use crate::navigation_keyboard::{move_right_all, move_left_all};
 use crate::cache::img_cache::CacheStrategy;
+use crate::cache::source_strategy::{self, SourceKind, SourceStrategies};
 use crate::menu::PaneLayout;
+use crate::widgets::shader::stereo_scene::StereoMode;
 use crate::pane::{self, Pane};
 use crate::settings::WindowState;
 use crate::ui;
@@ -74,14 +77,16 @@ use std::sync::mpsc::{Sender, Receiver};
 static APP_UPDATE_STATS: Lazy<Mutex<TimingStats>> = Lazy::new(|| {
     Mutex::new(TimingStats::new("App Update"))
 });
+/// Ids of directory listings, so a pane ignores one it has since been reopened over
+static NEXT_LISTING_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
 
 pub struct DataViewer {
     pub background_color: Color,//debug
     pub title: String,
     pub directory_path: Option<String>,
     pub current_image_index: usize,
-    pub slider_value: u16,                              // for master slider
-    pub prev_slider_value: u16,                         // for master slider
+    pub slider_value: u32,                              // for master slider
+    pub prev_slider_value: u32,                         // for master slider
     pub divider_position: Option<u16>,
     pub is_slider_dual: bool,
     pub show_footer: bool,
@@ -104,6 +109,8 @@ pub struct DataViewer {
     pub backend: wgpu::Backend,
     pub show_fps: bool,
     pub compression_strategy: CompressionStrategy,
+    pub source_strategies: SourceStrategies,           // Cache/compression strategy per kind of source
+    pub active_source: SourceKind,                     // Kind of the source opened last
     pub renderer_request_sender: Sender<RendererRequest>,
     pub is_horizontal_split: bool,
     pub file_receiver: Receiver<String>,
@@ -119,15 +126,37 @@ pub struct DataViewer {
     pub(crate) ctrl_pressed: bool,                                 // Flag to save ctrl/cmd(macOS) press state
     pub use_binary_size: bool,                          // Use binary (KiB/MiB) vs decimal (KB/MB) for file sizes
     pub spinner_location: crate::settings::SpinnerLocation,  // Where to show loading spinner
+    pub menu_layout: crate::menu_layout::MenuLayout,    // Menu bar mode and hidden menus
     pub window_width: f32,                              // Current window width for responsive layout
+    pub overview: crate::widgets::overview_strip::OverviewStrip,  // Proxies around the current index above the slider
+    pub timeline: crate::widgets::timeline_strip::TimelineStrip,  // Capture-date groups above the slider
+    pub bursts: crate::burst_stacks::BurstStacks,  // Burst stacks of the followed pane's folder
+    pub sidebar: crate::widgets::sidebar::Sidebar,      // Info/plugin/label/log sections beside the image
+    pub stereo_mode: StereoMode,                        // Stereo composition of the dual-pane pair
+    pub blend_compare: bool,                            // Draw pane 2 over pane 1 as an onion skin
+    pub blend_opacity: f32,                             // Onion skin opacity of pane 2 (0.0 - 1.0)
     #[cfg(feature = "selection")]
     pub selection_manager: SelectionManager,            // Manages image selections/exclusions
     #[cfg(feature = "coco")]
     pub annotation_manager: crate::coco::annotation_manager::AnnotationManager,  // Manages COCO annotations
+    #[cfg(feature = "embeddings")]
+    pub embedding_manager: crate::embeddings::EmbeddingManager,  // Embeddings for similar-image navigation
+    #[cfg(feature = "coco")]
+    pub coco_discovery: crate::coco::discovery::AnnotationDiscovery,  // Annotation files offered for opened folders
+    #[cfg(feature = "coco")]
+    pub category_remap: crate::coco::category_remap::CategoryRemapState,  // Category rename/merge/delete dialog
     #[cfg(feature = "coco")]
     pub coco_disable_simplification: bool,              // COCO: Disable polygon simplification for RLE masks
     #[cfg(feature = "coco")]
     pub coco_mask_render_mode: crate::settings::CocoMaskRenderMode,  // COCO: Mask rendering mode (Polygon or Pixel)
+    #[cfg(feature = "coco")]
+    pub coco_show_labels: bool,                         // COCO: Draw category/score labels on bboxes
+    #[cfg(feature = "coco")]
+    pub coco_label_size: f32,                           // COCO: Label font size in pixels
+    #[cfg(feature = "coco")]
+    pub coco_crop_padding: f32,                         // COCO: Crop export padding (percent of bbox)
+    #[cfg(feature = "coco")]
+    pub coco_crop_size: u32,                            // COCO: Crop export longest side (0 = original)
     pub window_size: PhysicalSize<u32>,
     pub maximized_size: Option<PhysicalSize<u32>>,  // Tracks size when maximized (for X11 un-maximize detection)
     pub window_position: PhysicalPosition<i32>,
@@ -135,6 +164,32 @@ pub struct DataViewer {
     pub position_before_transition: PhysicalPosition<i32>,  // Backup for Windows maximize fix
     pub last_monitor: Option<iced_winit::winit::monitor::MonitorHandle>, // Track position when not in windowed mode with multiple monitors
     pub show_success_save_modal: bool,
+    pub sequence_export: crate::sequence_export_modal::SequenceExportState,  // Animation export dialog
+    pub metadata_editor: crate::metadata_editor::MetadataEditorState,        // XMP metadata dialog
+    pub folder_preload: crate::folder_preload::FolderPreload,  // Progress of "Preload Folder into Memory"
+    pub comparison_report: crate::comparison_report::ComparisonReport,  // Progress of "Export Comparison Report"
+    pub folder_stats: crate::folder_stats::FolderStats,         // Skipped-files notice after opening a folder
+    pub show_usage_stats: bool,                                  // Help > Usage Statistics panel
+    pub check_for_updates: bool,                                 // Look for a newer release at startup
+    pub confirm_delete: bool,                                    // Ask before moving files to the trash
+    #[cfg(feature = "update-check")]
+    pub update_check: crate::update_check::UpdateCheck,         // Update banner and release notes
+    #[cfg(feature = "gps-map")]
+    pub gps_map: crate::gps_map::GpsMap,                        // Pins of geotagged images in the sidebar
+    #[cfg(feature = "inference")]
+    pub inference: crate::inference::InferenceState,            // ONNX model and its predictions
+    pub follow_mode: crate::follow_mode::FollowMode,            // `--follow` directory watch
+    pub metrics_panel: crate::widgets::metrics_panel::MetricsPanel,  // Per-step metrics plot from a CSV
+    pub plugins: crate::plugins::PluginManager,                 // External metadata-panel plugins
+    pub favorites: crate::favorites::Favorites,                 // Favorite images across folders
+    pub playlist_drag: crate::playlist::PlaylistDrag,           // Row being dragged in the Playlist section
+    pub session_restore: crate::session::SessionRestore,       // Images and views of an opened session still to show
+    pub file_hashes: crate::file_hashes::FileHashes,            // Hashes copied from the current file
+    pub file_delete: crate::file_delete::FileDelete,            // Trash confirmation and progress
+    pub viewed_images: crate::viewed_images::ViewedImages,      // Images seen per folder, for resuming a review
+    pub navigation_history: crate::navigation_history::NavigationHistory,  // Back/forward stack of jumps
+    #[cfg(feature = "scripting")]
+    pub script_manager: crate::scripting::ScriptManager,        // User scripts bound to hotkeys
     pub show_failure_save_modal: Option<String>,
 }
 
@@ -167,7 +222,11 @@ impl DataViewer {
         // Load user settings from YAML file
         let settings = UserSettings::load(settings_path);
         let cache_strategy = settings.get_cache_strategy();
-        let compression_strategy = settings.get_compression_strategy();
+        let compression_strategy = if crate::safe_mode::is_enabled() {
+            CompressionStrategy::None
+        } else {
+            settings.get_compression_strategy()
+        };
 
         info!("Initializing DataViewer with settings:");
         info!("  show_fps: {}", settings.show_fps);
@@ -180,6 +239,18 @@ impl DataViewer {
         info!("  cache_strategy: {:?}", cache_strategy);
         info!("  compression_strategy: {:?}", compression_strategy);
         info!("  is_slider_dual: {}", settings.is_slider_dual);
+        info!("  follow_symlinks: {}", settings.follow_symlinks);
+        info!("  load_for_display: {}", settings.load_for_display);
+
+        crate::file_io::set_follow_symlinks(settings.follow_symlinks);
+        crate::cache::load_limits::set_limits(settings.decode_threads, settings.max_concurrent_reads);
+        crate::display_decode::set_enabled(settings.load_for_display);
+        crate::display_decode::set_viewport(settings.window_width, settings.window_height, 1.0);
+
+        #[cfg(feature = "update-check")]
+        if settings.check_for_updates && !crate::safe_mode::is_enabled() {
+            crate::update_check::request();
+        }
 
         Self {
             title: String::from("ViewSkater"),
@@ -210,6 +281,8 @@ impl DataViewer {
             cache_strategy,
             show_fps: settings.show_fps,
             compression_strategy,
+            source_strategies: settings.get_source_strategies((cache_strategy, compression_strategy)),
+            active_source: SourceKind::Local,
             renderer_request_sender,
             is_horizontal_split: settings.is_horizontal_split,
             file_receiver,
@@ -225,15 +298,37 @@ impl DataViewer {
             ctrl_pressed: false,
             use_binary_size: settings.use_binary_size,
             spinner_location: settings.spinner_location,
+            menu_layout: crate::menu_layout::MenuLayout::new(&settings),
             window_width: settings.window_width as f32,
+            overview: Default::default(),
+            timeline: Default::default(),
+            bursts: Default::default(),
+            sidebar: crate::widgets::sidebar::Sidebar::load(),
+            stereo_mode: StereoMode::Off,
+            blend_compare: false,
+            blend_opacity: 0.5,
             #[cfg(feature = "selection")]
             selection_manager: SelectionManager::new(),
             #[cfg(feature = "coco")]
             annotation_manager: crate::coco::annotation_manager::AnnotationManager::new(),
+            #[cfg(feature = "embeddings")]
+            embedding_manager: crate::embeddings::EmbeddingManager::new(),
+            #[cfg(feature = "coco")]
+            coco_discovery: crate::coco::discovery::AnnotationDiscovery::load(),
+            #[cfg(feature = "coco")]
+            category_remap: Default::default(),
             #[cfg(feature = "coco")]
             coco_disable_simplification: settings.coco_disable_simplification,
             #[cfg(feature = "coco")]
             coco_mask_render_mode: settings.coco_mask_render_mode,
+            #[cfg(feature = "coco")]
+            coco_show_labels: settings.coco_show_labels,
+            #[cfg(feature = "coco")]
+            coco_label_size: settings.coco_label_size,
+            #[cfg(feature = "coco")]
+            coco_crop_padding: settings.coco_crop_padding,
+            #[cfg(feature = "coco")]
+            coco_crop_size: settings.coco_crop_size,
             window_position: PhysicalPosition { x: crate::config::CONFIG.window_position_x, y: crate::config::CONFIG.window_position_y },
             last_windowed_position: PhysicalPosition { x: crate::config::CONFIG.window_position_x, y: crate::config::CONFIG.window_position_y },
             position_before_transition: PhysicalPosition { x: crate::config::CONFIG.window_position_x, y: crate::config::CONFIG.window_position_y },
@@ -242,6 +337,35 @@ impl DataViewer {
             maximized_size: None,
             last_monitor: None,
             show_success_save_modal: false,
+            sequence_export: Default::default(),
+            metadata_editor: Default::default(),
+            folder_preload: Default::default(),
+            comparison_report: Default::default(),
+            folder_stats: Default::default(),
+            show_usage_stats: false,
+            check_for_updates: settings.check_for_updates,
+            confirm_delete: settings.confirm_delete,
+            #[cfg(feature = "update-check")]
+            update_check: Default::default(),
+            #[cfg(feature = "gps-map")]
+            gps_map: Default::default(),
+            #[cfg(feature = "inference")]
+            inference: Default::default(),
+            follow_mode: Default::default(),
+            metrics_panel: Default::default(),
+            plugins: crate::plugins::PluginManager::load(),
+            favorites: crate::favorites::Favorites::load(),
+            playlist_drag: Default::default(),
+            session_restore: Default::default(),
+            file_hashes: Default::default(),
+            file_delete: Default::default(),
+            viewed_images: crate::viewed_images::ViewedImages {
+                persist: settings.persist_viewed,
+                ..Default::default()
+            },
+            navigation_history: Default::default(),
+            #[cfg(feature = "scripting")]
+            script_manager: crate::scripting::ScriptManager::load(),
             show_failure_save_modal: None,
         }
     }
@@ -276,6 +400,54 @@ impl DataViewer {
         }
     }
 
+    /// Jump a pane directly to the image at `new_pos`, reloading the cache window around it
+    /// With a single shared slider, every loaded pane follows (clamped to its own image count)
+    pub(crate) fn jump_to_image(&mut self, pane_index: usize, new_pos: usize) -> Task<Message> {
+        self.use_slider_image_for_render = false;
+        for pane in self.panes.iter_mut() {
+            pane.slider_image_position = None;
+        }
+
+        let follow_all = !self.is_slider_dual || self.pane_layout == PaneLayout::SinglePane;
+        let mut operations = Vec::new();
+        for (idx, pane) in self.panes.iter_mut().enumerate() {
+            if !pane.dir_loaded || (idx != pane_index && !follow_all) {
+                continue;
+            }
+            let Some(last_index) = pane.img_cache.image_paths.len().checked_sub(1) else {
+                continue;
+            };
+            let pos = if follow_all {
+                crate::navigation_slider::pane_position(idx, new_pos, last_index + 1)
+            } else {
+                new_pos
+            }.min(last_index);
+            if pane.img_cache.current_index != pos {
+                pane.slider_value = pos as u32;
+                if idx != 1 || crate::navigation_slider::pane_b_offset() == 0 {
+                    self.slider_value = pos as u32;
+                }
+                operations.push((idx as isize, pos));
+            }
+        }
+
+        let mut tasks = Vec::new();
+        for (pane_idx, pos) in operations {
+            tasks.push(crate::navigation_slider::load_remaining_images(
+                &self.device,
+                &self.queue,
+                self.is_gpu_supported,
+                self.cache_strategy,
+                self.compression_strategy,
+                &mut self.panes,
+                &mut self.loading_status,
+                pane_idx,
+                pos,
+            ));
+        }
+        Task::batch(tasks)
+    }
+
     /// Start loading neighbor images after directory initialization completes
     /// Sets last_opened_pane, loads selection state, and kicks off async neighbor loading
     fn start_neighbor_loading(&mut self, pane_index: usize) -> Task<Message> {
@@ -288,6 +460,16 @@ impl DataViewer {
             }
         }
 
+        // A progressively shown initial image is decoded alongside its neighbors
+        let progressive_task = self.panes[pane_index].progressive.as_ref()
+            .map_or_else(Task::none, |upload| crate::cache::progressive::decode_task(pane_index, upload));
+
+        // A window restored from the recent directories cache needs no loading
+        if crate::cache::dir_cache::is_window_loaded(&self.panes[pane_index].img_cache) {
+            debug!("start_neighbor_loading: window of pane {} already loaded", pane_index);
+            return progressive_task;
+        }
+
         // Set loading timer for spinner display during neighbor loading
         // The first image is already displayed, now we load the rest in background
         if let Some(pane) = self.panes.get_mut(pane_index) {
@@ -308,7 +490,7 @@ impl DataViewer {
             current_index,
         );
 
-        load_task
+        Task::batch([progressive_task, load_task])
     }
 
     pub fn reset_state(&mut self, pane_index: isize) {
@@ -348,6 +530,7 @@ impl DataViewer {
 
     pub(crate) fn initialize_dir_path(&mut self, path: &PathBuf, pane_index: usize) -> Task<Message> {
         debug!("last_opened_pane: {}", self.last_opened_pane);
+        self.apply_source_strategy(path);
 
         // Check if this is a compressed file - use sync path for archives
         if path.extension().is_some_and(|ex| {
@@ -362,18 +545,21 @@ impl DataViewer {
         self.panes[pane_index].slider_image_position = None;
         self.panes[pane_index].slider_scene = None;
 
-        // Dispatch async directory enumeration (Issue #73 - NFS performance fix)
+        // List the directory in the background (Issue #73 - NFS performance fix)
         // Note: Loading spinner will be shown during neighbor loading phase (after first image displays)
-        let path_clone = path.clone();
-        Task::perform(
-            crate::file_io::enumerate_directory_async(path_clone),
-            move |result| Message::DirectoryEnumerated(result, pane_index)
+        let listing_id = NEXT_LISTING_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        self.panes[pane_index].listing_id = listing_id;
+        Task::run(
+            crate::file_io::list_directory_in_background(path.clone()),
+            move |event| Message::Io(IoMessage::DirectoryListed(pane_index, listing_id, event))
         )
     }
 
     /// Sync initialization path for compressed files (zip/rar/7z)
-    /// Archives are typically local so sync loading is acceptable
-    fn initialize_dir_path_sync(&mut self, path: &PathBuf, pane_index: usize) -> Task<Message> {
+    /// Archives are typically local so sync loading is acceptable. On macOS it also opens
+    /// folders the sandbox keeps from the background listing, as it reads them through their
+    /// security-scoped bookmark.
+    pub(crate) fn initialize_dir_path_sync(&mut self, path: &PathBuf, pane_index: usize) -> Task<Message> {
         debug!("Sync initialization for compressed file: {}", path.display());
 
         self.ensure_pane_exists(pane_index);
@@ -411,8 +597,40 @@ impl DataViewer {
         self.start_neighbor_loading(pane_index)
     }
 
+    /// Open an explicit list of image files (`--stdin-list`, favorites) instead of a directory
+    ///
+    /// The parent of the first file stands in as the directory path.
+    pub(crate) fn open_path_list(
+        &mut self,
+        paths: Vec<PathBuf>,
+        pane_index: usize,
+        initial_index: usize,
+        source: crate::playlist::PlaylistSource,
+    ) -> Task<Message> {
+        let Some(directory_path) = paths.first().and_then(|p| p.parent()).map(|d| d.to_string_lossy().to_string()) else {
+            return Task::none();
+        };
+
+        self.ensure_pane_exists(pane_index);
+        self.reset_state(pane_index as isize);
+        self.panes[pane_index].slider_image = None;
+        self.panes[pane_index].slider_image_position = None;
+        self.panes[pane_index].slider_scene = None;
+
+        let result = crate::app::message::DirectoryEnumResult {
+            initial_index: initial_index.min(paths.len() - 1),
+            file_paths: crate::cache::img_cache::PathIndex::from_filesystem_paths(paths),
+            directory_path,
+            skipped: Default::default(),
+            raw_pairs: Default::default(),
+        };
+        let task = self.complete_dir_initialization(result, pane_index);
+        self.panes[pane_index].playlist = Some(source);
+        task
+    }
+
     /// Complete directory initialization after async enumeration
-    /// Called when DirectoryEnumerated message arrives
+    /// Called when a DirectoryListed message brings a partial or complete listing
     pub(crate) fn complete_dir_initialization(
         &mut self,
         result: crate::app::message::DirectoryEnumResult,
@@ -426,6 +644,7 @@ impl DataViewer {
         let cache_size = self.cache_size;
 
         let pane = &mut self.panes[pane_index];
+        pane.raw_pairs = result.raw_pairs;
 
         // Initialize pane with pre-enumerated paths (loads first image synchronously)
         pane.initialize_with_paths(
@@ -519,17 +738,28 @@ impl DataViewer {
             }
 
             let panes_refs: Vec<&mut pane::Pane> = self.panes.iter_mut().collect();
-            self.slider_value = pane::get_master_slider_value(&panes_refs, &self.pane_layout, self.is_slider_dual, self.last_opened_pane as usize) as u16;
+            self.slider_value = pane::get_master_slider_value(&panes_refs, &self.pane_layout, self.is_slider_dual, self.last_opened_pane as usize) as u32;
         } else {
             // Single to dual slider: give slider.value to each slider
             for pane in self.panes.iter_mut() {
-                pane.slider_value = pane.img_cache.current_index as u16;
+                pane.slider_value = pane.img_cache.current_index as u32;
                 pane.is_selected = pane.is_selected_cache;
             }
         }
         self.is_slider_dual = !self.is_slider_dual;
     }
 
+    /// Switch to dual pane with the synced slider, so pairs are matched by index
+    pub(crate) fn ensure_paired_panes(&mut self) {
+        if self.pane_layout != PaneLayout::DualPane {
+            info!("Composed views need a pair of panes, switching to dual pane");
+            self.toggle_pane_layout(PaneLayout::DualPane);
+        }
+        if self.is_slider_dual {
+            self.toggle_slider_type();
+        }
+    }
+
     pub(crate) fn toggle_pane_layout(&mut self, pane_layout: PaneLayout) {
         match pane_layout {
             PaneLayout::SinglePane => {
@@ -540,7 +770,7 @@ impl DataViewer {
                 if self.pane_layout == PaneLayout::DualPane {
                     // Reset the slider value to the first pane's current index
                     let panes_refs: Vec<&mut pane::Pane> = self.panes.iter_mut().collect();
-                    self.slider_value = pane::get_master_slider_value(&panes_refs, &pane_layout, self.is_slider_dual, self.last_opened_pane as usize) as u16;
+                    self.slider_value = pane::get_master_slider_value(&panes_refs, &pane_layout, self.is_slider_dual, self.last_opened_pane as usize) as u32;
                     self.panes[0].is_selected = true;
                 }
             }
@@ -599,6 +829,30 @@ impl DataViewer {
         self.panes.iter().any(|pane| pane.loading_started_at.is_some())
     }
 
+    /// Index of the pane that pane-specific actions apply to: the first pane in single-pane
+    /// mode, otherwise the last one opened
+    pub(crate) fn active_pane_index(&self) -> usize {
+        if self.pane_layout == PaneLayout::SinglePane { 0 } else { self.last_opened_pane.max(0) as usize }
+    }
+
+    /// Whether a dialog with text fields is open, so keys typed into them aren't shortcuts
+    pub(crate) fn text_input_dialog_open(&self) -> bool {
+        #[cfg(feature = "coco")]
+        if self.category_remap.visible {
+            return true;
+        }
+        self.metadata_editor.visible || self.settings.is_visible()
+    }
+
+    /// File shown in the active pane, when it is a plain file
+    pub(crate) fn current_file(&self) -> Option<PathBuf> {
+        let pane = self.panes.get(self.active_pane_index()).filter(|pane| pane.dir_loaded)?;
+        match pane.img_cache.image_paths.get(pane.img_cache.current_index)? {
+            crate::cache::img_cache::PathSource::Filesystem(path) => Some(path.clone()),
+            _ => None,
+        }
+    }
+
     pub(crate) fn update_cache_strategy(&mut self, strategy: CacheStrategy) {
         debug!("Changing cache strategy from {:?} to {:?}", self.cache_strategy, strategy);
         self.cache_strategy = strategy;
@@ -636,11 +890,28 @@ impl DataViewer {
                         archive_cache_size,
                         archive_warning_threshold_mb,
                     );
+                    crate::cache::progressive::finish_blocking(&mut pane.progressive, &self.queue);
                 }
             }
         }
     }
 
+    /// Switches to the strategies configured for the kind of source `path` opens
+    fn apply_source_strategy(&mut self, path: &std::path::Path) {
+        let kind = source_strategy::source_kind(path);
+        self.active_source = kind;
+        let (cache_strategy, compression_strategy) = self.source_strategies.resolve(kind);
+        let compression_strategy = if crate::safe_mode::is_enabled() { CompressionStrategy::None } else { compression_strategy };
+        if compression_strategy != self.compression_strategy {
+            info!("Using {:?} compression for {}", compression_strategy, kind.label());
+            self.update_compression_strategy(compression_strategy);
+        }
+        if cache_strategy != self.cache_strategy {
+            info!("Using {:?} cache for {}", cache_strategy, kind.label());
+            self.update_cache_strategy(cache_strategy);
+        }
+    }
+
     pub(crate) fn update_compression_strategy(&mut self, strategy: CompressionStrategy) {
         if self.compression_strategy != strategy {
             self.compression_strategy = strategy;
@@ -688,6 +959,7 @@ impl DataViewer {
                                 archive_cache_size,
                                 archive_warning_threshold_mb,
                             );
+                            crate::cache::progressive::finish_blocking(&mut pane.progressive, &self.queue);
                         }
                     }
                 }
@@ -695,6 +967,31 @@ impl DataViewer {
         }
     }
 
+    fn overview_thumbnail_task(&mut self) -> Option<Task<Message>> {
+        if !self.overview.expanded {
+            return None;
+        }
+        let pane_index = crate::widgets::overview_strip::active_pane(&self.pane_layout, self.is_slider_dual)?;
+        let pane = self.panes.get(pane_index)?;
+        let current = crate::widgets::overview_strip::display_index(pane, self.use_slider_image_for_render);
+        Some(self.overview.request_thumbnails(pane, pane_index, current))
+    }
+
+    fn timeline_task(&mut self) -> Option<Task<Message>> {
+        let pane_index = crate::widgets::overview_strip::active_pane(&self.pane_layout, self.is_slider_dual)?;
+        let pane = self.panes.get(pane_index)?;
+        self.timeline.request(pane, pane_index)
+    }
+
+    /// Filesystem path of the image shown in the first pane, for metadata plugins
+    pub(crate) fn plugin_image_path(&self) -> Option<PathBuf> {
+        let pane = self.panes.first().filter(|pane| pane.dir_loaded)?;
+        match pane.img_cache.image_paths.get(pane.img_cache.current_index)? {
+            crate::cache::img_cache::PathSource::Filesystem(path) => Some(path.clone()),
+            _ => None,
+        }
+    }
+
     pub(crate) fn toggle_split_orientation(&mut self) {
         self.is_horizontal_split = !self.is_horizontal_split;
     }
@@ -711,6 +1008,10 @@ impl iced_winit::runtime::Program for DataViewer {
         let mut cli_tasks: Vec<Task<Message>> = Vec::new();
         while let Ok(path) = self.file_receiver.try_recv() {
             println!("Processing file path in main thread: {}", path);
+            if crate::session::is_session_file(std::path::Path::new(&path)) {
+                cli_tasks.push(Task::done(crate::session::SessionMessage::OpenFrom(PathBuf::from(path)).into()));
+                continue;
+            }
             // Reset state and initialize the directory path
             self.reset_state(-1);
             println!("State reset complete, initializing directory path");
@@ -718,12 +1019,111 @@ impl iced_winit::runtime::Program for DataViewer {
             cli_tasks.push(init_task);
             println!("Directory path initialization task queued");
         }
+        if let Some(base) = crate::stdin_input::take_list_request() {
+            cli_tasks.push(crate::stdin_input::start(base));
+        }
+        if let Some(directory) = crate::follow_mode::take_request() {
+            self.reset_state(-1);
+            cli_tasks.push(crate::follow_mode::start(self, directory));
+        }
+        if let Some(config) = crate::triggers::take_request() {
+            cli_tasks.push(crate::triggers::start(config));
+        }
+        if let Some(path) = crate::settings_watch::take_request() {
+            cli_tasks.push(crate::settings_watch::start(path));
+        }
+        if let Some(name) = crate::profiles::take_request() {
+            cli_tasks.push(Task::done(crate::profiles::ProfileMessage::Select(name).into()));
+        }
+        #[cfg(feature = "update-check")]
+        if crate::update_check::take_request() {
+            cli_tasks.push(crate::update_check::start());
+        }
 
         let _update_start = Instant::now();
 
         // Route message to handler
         let task = message_handlers::handle_message(self, message);
 
+        // Decode overview proxies for the position reached by this message
+        let task = match self.overview_thumbnail_task() {
+            Some(overview_task) => Task::batch([task, overview_task]),
+            None => task,
+        };
+
+        // Read capture dates of a newly shown folder while the timeline is open
+        let task = match self.timeline_task() {
+            Some(timeline_task) => Task::batch([task, timeline_task]),
+            None => task,
+        };
+
+        // Find burst stacks in a newly shown folder while stacking is on
+        let task = match crate::burst_stacks::BurstStacks::request(self) {
+            Some(burst_task) => Task::batch([task, burst_task]),
+            None => task,
+        };
+
+        // Run metadata plugins once per image shown
+        let plugin_path = self.plugin_image_path();
+        let task = match self.plugins.request(plugin_path.as_deref()) {
+            Some(plugin_task) => Task::batch([task, plugin_task]),
+            None => task,
+        };
+
+        // Scan a newly shown folder for GPS tags while the sidebar is open
+        #[cfg(feature = "gps-map")]
+        let task = match crate::gps_map::request(self) {
+            Some(map_task) => Task::batch([task, map_task]),
+            None => task,
+        };
+
+        // Offer or load annotation files for a newly shown folder
+        #[cfg(feature = "coco")]
+        let task = match crate::coco::discovery::AnnotationDiscovery::request(self) {
+            Some(discovery_task) => Task::batch([task, discovery_task]),
+            None => task,
+        };
+
+        // Run the loaded model on a newly shown image while auto-run is on
+        #[cfg(feature = "inference")]
+        let task = match crate::inference::request(self) {
+            Some(inference_task) => Task::batch([task, inference_task]),
+            None => task,
+        };
+
+        // Estimate white balance gains for newly shown images while the preview is on
+        let task = match crate::white_balance::request(&self.panes) {
+            Some(white_balance_task) => Task::batch([task, white_balance_task]),
+            None => task,
+        };
+
+        // Decode the frames of newly shown animated images
+        let task = match crate::animation::request(&self.panes) {
+            Some(animation_task) => Task::batch([task, animation_task]),
+            None => task,
+        };
+
+        // Decode reduced images in full once they are zoomed past 1:1
+        let task = match crate::display_decode::request(self) {
+            Some(display_task) => Task::batch([task, display_task]),
+            None => task,
+        };
+
+        // Bring the panes of an opened session to their saved image and view
+        let task = match crate::session::restore_pending(self) {
+            Some(session_task) => Task::batch([task, session_task]),
+            None => task,
+        };
+
+        // Mark the images now shown as viewed
+        self.viewed_images.record(&self.panes);
+
+        // Drop the path pages of very large folders that are far from the current image again
+        for pane in self.panes.iter_mut() {
+            let current_index = pane.img_cache.current_index;
+            pane.img_cache.image_paths.trim(current_index);
+        }
+
         // Handle replay mode logic
         if let Some(replay_action) = self.update_replay_mode() {
             if let Some(replay_task) = self.process_replay_action(replay_action) {
@@ -811,15 +1211,51 @@ impl iced_winit::runtime::Program for DataViewer {
     fn view(&self) -> Element<'_, Message, WinitTheme, Renderer> {
         let content = ui::build_ui(self);
 
+        #[cfg(feature = "coco")]
+        if self.category_remap.visible {
+            let remap_content = crate::coco::category_remap::view_category_remap(&self.category_remap);
+            return widgets::modal::modal(content, remap_content, crate::coco::category_remap::CategoryRemapMessage::Hide.into());
+        }
+
+        #[cfg(feature = "inference")]
+        if let Some(preview) = crate::inference::upscale::view(&self.inference) {
+            return widgets::modal::modal(content, preview, crate::inference::InferenceMessage::SetUpscalePreview(false).into());
+        }
+        #[cfg(feature = "inference")]
+        if let Some(preview) = crate::inference::cutout::view(&self.inference) {
+            return widgets::modal::modal(content, preview, crate::inference::InferenceMessage::SetCutoutPreview(false).into());
+        }
+
+        if let Some(dialog) = crate::file_delete::dialog(&self.file_delete) {
+            return widgets::modal::modal(content, dialog, crate::file_delete::DeleteMessage::Cancel.into());
+        }
+
+        #[cfg(target_os = "macos")]
+        if let Some(dialog) = crate::access_prompt::dialog() {
+            return widgets::modal::modal(content, dialog, crate::access_prompt::AccessPromptMessage::Dismiss.into());
+        }
+
         if self.show_success_save_modal {
-            let modal_content = Self::save_result_modal("File saved", None, Message::HideSuccessSaveModal);
-            modal::modal(content, modal_content, Message::HideSuccessSaveModal)
+            let modal_content = Self::save_result_modal("File saved", None, Message::Overlay(OverlayMessage::HideSuccessSaveModal));
+            modal::modal(content, modal_content, Message::Overlay(OverlayMessage::HideSuccessSaveModal))
         } else if let Some(ref error_message) = self.show_failure_save_modal {
-            let modal_content = Self::save_result_modal("Error saving file", Some(format!("Message: {error_message}")), Message::HideFailureSaveModal);
-            modal::modal(content, modal_content, Message::HideFailureSaveModal)
+            let modal_content = Self::save_result_modal("Error saving file", Some(format!("Message: {error_message}")), Message::Overlay(OverlayMessage::HideFailureSaveModal));
+            modal::modal(content, modal_content, Message::Overlay(OverlayMessage::HideFailureSaveModal))
+        } else if self.plugins.show_manager {
+            let manager_content = crate::plugin_manager_modal::view_plugin_manager_modal(self);
+            widgets::modal::modal(content, manager_content, crate::plugins::PluginMessage::HideManager.into())
+        } else if self.sequence_export.visible {
+            let export_content = crate::sequence_export_modal::view_sequence_export_modal(self);
+            widgets::modal::modal(content, export_content, crate::sequence_export_modal::SequenceExportMessage::Hide.into())
+        } else if self.metadata_editor.visible {
+            let editor_content = crate::metadata_editor::view_metadata_editor(self);
+            widgets::modal::modal(content, editor_content, crate::metadata_editor::MetadataEditorMessage::Hide.into())
         } else if self.settings.is_visible() {
             let options_content = crate::settings_modal::view_settings_modal(self);
-            widgets::modal::modal(content, options_content, Message::HideOptions)
+            widgets::modal::modal(content, options_content, Message::Overlay(OverlayMessage::HideOptions))
+        } else if self.show_usage_stats {
+            let stats_content = crate::usage_stats::view_usage_stats(self);
+            widgets::modal::modal(content, stats_content, crate::usage_stats::UsageStatsMessage::Hide.into())
         } else if self.show_about {
             // Build the info column dynamically to avoid empty text widgets
             let mut info_column = column![
@@ -891,9 +1327,9 @@ impl iced_winit::runtime::Program for DataViewer {
                     ..Default::default()
                 }
             })
-            .on_press(Message::OpenWebLink(
+            .on_press(Message::Overlay(OverlayMessage::OpenWebLink(
                 "https://github.com/ggand0/viewskater".to_string(),
-            )));
+            ))));
 
             info_column = info_column.spacing(4);
 
@@ -926,7 +1362,7 @@ impl iced_winit::runtime::Program for DataViewer {
                 }
             });
 
-            widgets::modal::modal(content, about_content, Message::HideAbout)
+            widgets::modal::modal(content, about_content, Message::Overlay(OverlayMessage::HideAbout))
         } else {
             content.into()
         }
@@ -1,8 +1,8 @@
-use log::debug;
+use log::{debug, info};
 use iced_core::keyboard::{self, Key, key::Named};
 use iced_winit::runtime::Task;
 
-use crate::app::{DataViewer, Message};
+use crate::app::{DataViewer, Message, IoMessage, PaneMessage, SettingsMessage};
 use crate::menu::PaneLayout;
 use crate::file_io;
 use crate::navigation_keyboard::{move_right_all, move_left_all};
@@ -16,9 +16,27 @@ fn is_platform_modifier(modifiers: &keyboard::Modifiers) -> bool {
     return modifiers.control(); // Use Control key on other platforms
 }
 
+/// Keys a focused text field uses itself, for typing, moving the cursor and deleting
+fn is_text_editing_key(key: &keyboard::Key) -> bool {
+    matches!(
+        key.as_ref(),
+        Key::Character(_)
+            | Key::Named(Named::Space | Named::ArrowLeft | Named::ArrowRight | Named::ArrowUp | Named::ArrowDown)
+            // Delete and Cmd+Backspace would otherwise trash the current image
+            | Key::Named(Named::Delete | Named::Backspace)
+    )
+}
+
 impl DataViewer {
     pub(crate) fn handle_key_pressed_event(&mut self, key: &keyboard::Key, modifiers: keyboard::Modifiers) -> Vec<Task<Message>> {
         let mut tasks = Vec::new();
+        self.menu_layout.on_key_pressed(key);
+
+        // While a dialog's text field may have focus, only quitting stays a shortcut
+        let is_quit = matches!(key.as_ref(), Key::Character("q")) && is_platform_modifier(&modifiers);
+        if self.text_input_dialog_open() && is_text_editing_key(key) && !is_quit {
+            return tasks;
+        }
 
         match key.as_ref() {
             Key::Named(Named::Tab) => {
@@ -49,7 +67,7 @@ impl DataViewer {
                 if modifiers.shift() && modifiers.alt() {
                     debug!("Key1 Shift+Alt pressed");
                     tasks.push(Task::perform(file_io::pick_file(), move |result| {
-                        Message::FolderOpened(result, 0)
+                        Message::Io(IoMessage::FolderOpened(result, 0))
                     }));
                 }
 
@@ -57,7 +75,7 @@ impl DataViewer {
                 else if modifiers.alt() {
                     debug!("Key1 Alt pressed");
                     tasks.push(Task::perform(file_io::pick_folder(), move |result| {
-                        Message::FolderOpened(result, 0)
+                        Message::Io(IoMessage::FolderOpened(result, 0))
                     }));
                 }
 
@@ -76,7 +94,7 @@ impl DataViewer {
                 if self.pane_layout == PaneLayout::DualPane && modifiers.shift() && modifiers.alt() {
                     debug!("Key2 Shift+Alt pressed");
                     tasks.push(Task::perform(file_io::pick_file(), move |result| {
-                        Message::FolderOpened(result, 1)
+                        Message::Io(IoMessage::FolderOpened(result, 1))
                     }));
                 }
 
@@ -84,7 +102,7 @@ impl DataViewer {
                 else if self.pane_layout == PaneLayout::DualPane && modifiers.alt() {
                     debug!("Key2 Alt pressed");
                     tasks.push(Task::perform(file_io::pick_folder(), move |result| {
-                        Message::FolderOpened(result, 1)
+                        Message::Io(IoMessage::FolderOpened(result, 1))
                     }));
                 }
 
@@ -109,7 +127,7 @@ impl DataViewer {
             Key::Character("q") => {
                 // Terminate the app
                 if is_platform_modifier(&modifiers) {
-                    tasks.push(Task::done(Message::SaveWindowState));
+                    tasks.push(Task::done(Message::Settings(SettingsMessage::SaveWindowState)));
                     tasks.push(Task::done(Message::Quit));
                 }
             }
@@ -119,7 +137,7 @@ impl DataViewer {
                         debug!("Save file with platform_modifier+s");
                         if self.panes[0].current_image.len() > 0 {
                             tasks.push(Task::perform(file_io::pick_save_file(), move |result| {
-                                Message::ReadySaveImage(result)
+                                Message::Io(IoMessage::ReadySaveImage(result))
                             }));
                         }
                 }
@@ -139,18 +157,25 @@ impl DataViewer {
                     if modifiers.shift() {
                         debug!("Opening folder with platform_modifier+shift+o");
                         tasks.push(Task::perform(file_io::pick_folder(), move |result| {
-                            Message::FolderOpened(result, pane_index)
+                            Message::Io(IoMessage::FolderOpened(result, pane_index))
                         }));
                     } else {
                         // Otherwise open file
                         debug!("Opening file with platform_modifier+o");
                         tasks.push(Task::perform(file_io::pick_file(), move |result| {
-                            Message::FolderOpened(result, pane_index)
+                            Message::Io(IoMessage::FolderOpened(result, pane_index))
                         }));
                     }
                 }
             }
 
+            Key::Named(Named::ArrowLeft) if modifiers.alt() => {
+                tasks.push(Task::done(crate::navigation_history::HistoryMessage::Back.into()));
+            }
+            Key::Named(Named::ArrowRight) if modifiers.alt() => {
+                tasks.push(Task::done(crate::navigation_history::HistoryMessage::Forward.into()));
+            }
+
             Key::Named(Named::ArrowLeft) | Key::Character("a") => {
                 // Check for first image navigation with platform modifier or Fn key
                 if is_platform_modifier(&modifiers) {
@@ -170,8 +195,8 @@ impl DataViewer {
                             // Navigate to the first image (index 0)
                             if pane.img_cache.current_index > 0 {
                                 let new_pos = 0;
-                                pane.slider_value = new_pos as u16;
-                                self.slider_value = new_pos as u16;
+                                pane.slider_value = new_pos as u32;
+                                self.slider_value = new_pos as u32;
 
                                 // Save the operation for later execution
                                 operations.push((idx as isize, new_pos));
@@ -179,6 +204,10 @@ impl DataViewer {
                         }
                     }
 
+                    if let Some(&(pane_idx, new_pos)) = operations.first() {
+                        crate::navigation_history::record_jump(self, pane_idx as usize, new_pos);
+                    }
+
                     // Now execute all operations after the loop is complete
                     for (pane_idx, new_pos) in operations {
                         tasks.push(crate::navigation_slider::load_remaining_images(
@@ -263,8 +292,8 @@ impl DataViewer {
                             if let Some(last_index) = pane.img_cache.image_paths.len().checked_sub(1) {
                                 if pane.img_cache.current_index < last_index {
                                     let new_pos = last_index;
-                                    pane.slider_value = new_pos as u16;
-                                    self.slider_value = new_pos as u16;
+                                    pane.slider_value = new_pos as u32;
+                                    self.slider_value = new_pos as u32;
 
                                     // Save the operation for later execution
                                     operations.push((idx as isize, new_pos));
@@ -273,6 +302,10 @@ impl DataViewer {
                         }
                     }
 
+                    if let Some(&(pane_idx, new_pos)) = operations.first() {
+                        crate::navigation_history::record_jump(self, pane_idx as usize, new_pos);
+                    }
+
                     // Now execute all operations after the loop is complete
                     for (pane_idx, new_pos) in operations {
                         tasks.push(crate::navigation_slider::load_remaining_images(
@@ -337,11 +370,92 @@ impl DataViewer {
                 }
             }
 
+            Key::Character("l") if !is_platform_modifier(&modifiers) => {
+                tasks.push(Task::done(Message::Pane(PaneMessage::ToggleZoomLock(!crate::zoom_lock::is_active()))));
+            }
+
+            Key::Character("y") | Key::Character("Y") if is_platform_modifier(&modifiers) => {
+                let msg = if modifiers.shift() {
+                    crate::soft_proof::SoftProofMessage::ToggleGamutWarning
+                } else {
+                    crate::soft_proof::SoftProofMessage::ToggleProofColors
+                };
+                tasks.push(Task::done(msg.into()));
+            }
+
+            Key::Character("z") if !is_platform_modifier(&modifiers) => {
+                let enabled = !crate::widgets::shader::clipping_warning::is_enabled();
+                tasks.push(Task::done(Message::Pane(PaneMessage::ToggleClippingWarning(enabled))));
+            }
+
+            Key::Character("n") if !is_platform_modifier(&modifiers) => {
+                tasks.push(Task::done(crate::noise_analysis::NoiseMessage::Measure.into()));
+            }
+
+            Key::Character("t") if !is_platform_modifier(&modifiers) => {
+                tasks.push(Task::done(crate::auto_rotate::RotateMessage::Suggest.into()));
+            }
+
+            Key::Character("r") if !is_platform_modifier(&modifiers) => {
+                tasks.push(Task::done(Message::Io(IoMessage::SwapRawPair)));
+            }
+
+            // Burst keys; without stacks they fall through to the COCO and script bindings
+            Key::Character("v") if !is_platform_modifier(&modifiers) && self.bursts.is_active() => {
+                tasks.push(Task::done(crate::burst_stacks::BurstMessage::Flip.into()));
+            }
+
+            Key::Character("k") if !is_platform_modifier(&modifiers) && self.bursts.is_active() => {
+                tasks.push(Task::done(crate::burst_stacks::BurstMessage::Pick.into()));
+            }
+
+            Key::Character("[") if !is_platform_modifier(&modifiers) && self.bursts.is_active() => {
+                tasks.push(Task::done(crate::burst_stacks::BurstMessage::Step(false).into()));
+            }
+
+            Key::Character("]") if !is_platform_modifier(&modifiers) && self.bursts.is_active() => {
+                tasks.push(Task::done(crate::burst_stacks::BurstMessage::Step(true).into()));
+            }
+
+            Key::Character("*") => {
+                tasks.push(Task::done(crate::favorites::FavoritesMessage::ToggleCurrent.into()));
+            }
+
+            Key::Character("#") => {
+                tasks.push(Task::done(crate::file_hashes::HashMessage::Copy(crate::file_hashes::HashKind::Sha256).into()));
+            }
+
+            Key::Character("j") if !is_platform_modifier(&modifiers) => {
+                tasks.push(Task::done(crate::viewed_images::ViewedMessage::JumpToFirstUnviewed.into()));
+            }
+
+            // Cmd+Backspace is the Finder shortcut for "Move to Trash"
+            Key::Named(Named::Delete) => {
+                tasks.push(Task::done(crate::file_delete::DeleteMessage::DeleteCurrent.into()));
+            }
+            Key::Named(Named::Backspace) if cfg!(target_os = "macos") && is_platform_modifier(&modifiers) => {
+                tasks.push(Task::done(crate::file_delete::DeleteMessage::DeleteCurrent.into()));
+            }
+
+            Key::Character("i") if !is_platform_modifier(&modifiers) => {
+                let visible = !self.sidebar.is_visible();
+                tasks.push(Task::done(crate::widgets::sidebar::SidebarMessage::SetVisible(visible).into()));
+            }
+
             Key::Named(Named::F3)  => {
                 self.show_fps = !self.show_fps;
                 debug!("Toggled debug FPS display: {}", self.show_fps);
             }
 
+            Key::Character("p") if self.follow_mode.is_active() => {
+                tasks.push(Task::done(crate::follow_mode::FollowMessage::TogglePause.into()));
+            }
+
+            Key::Named(Named::F4) => {
+                let gamma_space = crate::utils::color::toggle_gamma_space_scaling();
+                info!("Image scaling now done in {} space", if gamma_space { "gamma (debug comparison)" } else { "linear" });
+            }
+
             Key::Named(Named::Super) => {
                 #[cfg(target_os = "macos")] {
                     self.set_ctrl_pressed(true);
@@ -364,6 +478,17 @@ impl DataViewer {
                     tasks.push(task);
                 }
 
+                // Check if embeddings module wants to handle this key
+                #[cfg(feature = "embeddings")]
+                if let Some(task) = crate::widgets::similarity_widget::handle_keyboard_event(
+                    key,
+                    modifiers,
+                    &self.pane_layout,
+                    self.last_opened_pane,
+                ) {
+                    tasks.push(task);
+                }
+
                 // Check if COCO module wants to handle this key
                 #[cfg(feature = "coco")]
                 if let Some(task) = crate::coco::widget::handle_keyboard_event(
@@ -374,6 +499,12 @@ impl DataViewer {
                 ) {
                     tasks.push(task);
                 }
+
+                // Run a user script bound to this key
+                #[cfg(feature = "scripting")]
+                if let Some(task) = crate::scripting::handle_keyboard_event(&self.script_manager, key, modifiers) {
+                    tasks.push(task);
+                }
             }
         }
 
@@ -383,6 +514,7 @@ impl DataViewer {
     pub(crate) fn handle_key_released_event(&mut self, key_code: &keyboard::Key, _modifiers: keyboard::Modifiers) -> Vec<Task<Message>> {
         #[allow(unused_mut)]
         let mut tasks = Vec::new();
+        self.menu_layout.on_key_released(key_code);
 
         match key_code.as_ref() {
             Key::Named(Named::Tab) => {
@@ -419,4 +551,34 @@ impl DataViewer {
 
         tasks
     }
+
+    /// One step forward or back in all panes, as a plain arrow key press does
+    ///
+    /// For navigation that does not come from the keyboard (scripts, external triggers).
+    pub(crate) fn step_all(&mut self, forward: bool) -> Task<Message> {
+        // Stop skating the other way, dropping its queued loads
+        if forward && self.skate_left {
+            self.loading_status.reset_load_previous_queue_items();
+        } else if !forward && self.skate_right {
+            self.loading_status.reset_load_next_queue_items();
+        }
+        self.skate_left = false;
+        self.skate_right = false;
+        self.use_slider_image_for_render = false;
+        for pane in self.panes.iter_mut() {
+            pane.slider_image_position = None;
+        }
+        let navigate = if forward { move_right_all } else { move_left_all };
+        navigate(
+            &self.device,
+            &self.queue,
+            self.cache_strategy,
+            self.compression_strategy,
+            &mut self.panes,
+            &mut self.loading_status,
+            &mut self.slider_value,
+            &self.pane_layout,
+            self.is_slider_dual,
+            self.last_opened_pane as usize)
+    }
 }
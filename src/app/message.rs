@@ -6,16 +6,23 @@ use iced_core::Color;
 use iced_winit::winit::dpi::{PhysicalPosition, PhysicalSize};
 
 use crate::cache::img_cache::{CachedData, CacheStrategy, ImageMetadata, LoadOperation};
+use crate::cache::progressive::ProgressiveEvent;
+use crate::stdin_input::StdinListEvent;
 use crate::menu::PaneLayout;
+use crate::widgets::shader::stereo_scene::StereoMode;
 use crate::file_io;
 use iced_wgpu::engine::CompressionStrategy;
 
 /// Result of async directory enumeration
 #[derive(Debug, Clone)]
 pub struct DirectoryEnumResult {
-    pub file_paths: Vec<PathBuf>,
+    pub file_paths: crate::cache::img_cache::PathIndex,
     pub directory_path: String,
     pub initial_index: usize,
+    /// Non-image files left out of `file_paths`
+    pub skipped: crate::folder_stats::SkippedFiles,
+    /// Left-out member of each RAW+JPEG pair, keyed by the listed one
+    pub raw_pairs: std::collections::HashMap<PathBuf, PathBuf>,
 }
 
 /// Error type for async directory enumeration
@@ -23,96 +30,251 @@ pub struct DirectoryEnumResult {
 pub enum DirectoryEnumError {
     NoImagesFound,
     DirectoryError(String),
+    /// The folder of this path may not be read, as in the macOS sandbox
+    AccessDenied(PathBuf),
     NotFound,
 }
 
+/// Progress of a background directory listing
+#[derive(Debug, Clone)]
+pub enum ListingEvent {
+    /// First images of a large folder, sorted, so it can be opened before the rest is listed
+    Partial(DirectoryEnumResult),
+    /// Images found so far
+    Progress(usize),
+    Complete(Result<DirectoryEnumResult, DirectoryEnumError>),
+}
+
 /// Result type for slider image widget loading: (pane_idx, position, handle, dimensions, file_size)
 pub type SliderImageWidgetResult = Result<(usize, usize, Handle, (u32, u32), u64), (usize, usize)>;
 
 /// Result type for batch image loading: (cached_data, metadata, load_operation)
 pub type ImagesLoadedResult = Result<(Vec<Option<CachedData>>, Vec<Option<ImageMetadata>>, Option<LoadOperation>), std::io::ErrorKind>;
 
+/// Top-level application message
+///
+/// Core messages are grouped by domain into the nested enums below, each routed to its own
+/// handler module under `message_handlers/`; feature modules bring their own `*Action` enums.
 #[derive(Debug, Clone)]
 pub enum Message {
     Debug(String),
     Nothing,
-    ShowAbout,
-    HideAbout,
-    ShowOptions,
-    HideOptions,
-    SaveWindowState,
-    SaveSettings,
-    ClearSettingsStatus,
-    SettingsTabSelected(usize),
-    ShowLogs,
-    OpenSettingsDir,
-    ExportDebugLogs,
-    ExportAllLogs,
-    OpenWebLink(String),
     // Note: Changed from font::Error to () since the error is never used
     #[allow(dead_code)]
     FontLoaded(Result<(), ()>),
-    OpenFolder(usize),
-    OpenFile(usize),
-    FileDropped(isize, String),
-    Close,
     Quit,
     ReplayKeepAlive,
-    FolderOpened(Result<String, file_io::Error>, usize),
-    DirectoryEnumerated(Result<DirectoryEnumResult, DirectoryEnumError>, usize),
-    SliderChanged(isize, u16),
-    SliderReleased(isize, u16),
-    #[allow(dead_code)]
-    SliderImageLoaded(Result<(usize, CachedData), usize>),
-    SliderImageWidgetLoaded(SliderImageWidgetResult),
     Event(Event),
-    ImagesLoaded(ImagesLoadedResult),
+    #[allow(dead_code)]
+    BackgroundColorChanged(Color),
+    #[allow(dead_code)]
+    TimerTick,
+    Navigation(NavigationMessage),
+    Pane(PaneMessage),
+    Overlay(OverlayMessage),
+    Settings(SettingsMessage),
+    Io(IoMessage),
+    #[cfg(feature = "selection")]
+    SelectionAction(crate::widgets::selection_widget::SelectionMessage),
+    #[cfg(feature = "coco")]
+    CocoAction(crate::coco::widget::CocoMessage),
+    #[cfg(feature = "embeddings")]
+    SimilarityAction(crate::widgets::similarity_widget::SimilarityMessage),
+    SequenceExportAction(crate::sequence_export_modal::SequenceExportMessage),
+    MetadataEditorAction(crate::metadata_editor::MetadataEditorMessage),
+    OverviewAction(crate::widgets::overview_strip::OverviewMessage),
+    TimelineAction(crate::widgets::timeline_strip::TimelineMessage),
+    SidebarAction(crate::widgets::sidebar::SidebarMessage),
+    PreloadAction(crate::folder_preload::PreloadMessage),
+    BulkLoadAction(crate::bulk_load::BulkLoadMessage),
+    AnimationAction(crate::animation::AnimationMessage),
+    DisplayDecodeAction(crate::display_decode::DisplayDecodeMessage),
+    ReportAction(crate::comparison_report::ReportMessage),
+    FolderStatsAction(crate::folder_stats::FolderStatsMessage),
+    UsageStatsAction(crate::usage_stats::UsageStatsMessage),
+    #[cfg(feature = "update-check")]
+    UpdateAction(crate::update_check::UpdateMessage),
+    #[cfg(feature = "gps-map")]
+    MapAction(crate::gps_map::MapMessage),
+    FollowAction(crate::follow_mode::FollowMessage),
+    MetricsAction(crate::widgets::metrics_panel::MetricsMessage),
+    PluginAction(crate::plugins::PluginMessage),
+    UserShaderAction(crate::widgets::shader::user_shader::UserShaderMessage),
+    LutAction(crate::widgets::lut_panel::LutMessage),
+    ExposureAction(crate::widgets::exposure_panel::ExposureMessage),
+    WindowLevelAction(crate::widgets::window_level::WindowLevelMessage),
+    DisplayProfileAction(crate::display_profile::DisplayProfileMessage),
+    SoftProofAction(crate::soft_proof::SoftProofMessage),
+    WhiteBalanceAction(crate::white_balance::WhiteBalanceMessage),
+    NoiseAction(crate::noise_analysis::NoiseMessage),
+    BurstAction(crate::burst_stacks::BurstMessage),
+    RotateAction(crate::auto_rotate::RotateMessage),
+    ChannelDiffAction(crate::channel_diff::ChannelDiffMessage),
+    #[cfg(feature = "coco")]
+    CocoDiscoveryAction(crate::coco::discovery::DiscoveryMessage),
+    #[cfg(feature = "coco")]
+    CategoryRemapAction(crate::coco::category_remap::CategoryRemapMessage),
+    #[cfg(feature = "coco")]
+    OverlayExportAction(crate::coco::overlay_export::OverlayExportMessage),
+    #[cfg(feature = "inference")]
+    InferenceAction(crate::inference::InferenceMessage),
+    TriggerAction(crate::triggers::TriggerAction),
+    ProfileAction(crate::profiles::ProfileMessage),
+    FavoritesAction(crate::favorites::FavoritesMessage),
+    PlaylistAction(crate::playlist::PlaylistMessage),
+    SessionAction(crate::session::SessionMessage),
+    HashAction(crate::file_hashes::HashMessage),
+    DeleteAction(crate::file_delete::DeleteMessage),
+    #[cfg(target_os = "macos")]
+    AccessPromptAction(crate::access_prompt::AccessPromptMessage),
+    #[cfg(target_os = "macos")]
+    PermissionsAction(crate::bookmark_permissions::PermissionsMessage),
+    ViewedAction(crate::viewed_images::ViewedMessage),
+    MenuLayoutAction(crate::menu_layout::MenuLayoutMessage),
+    HistoryAction(crate::navigation_history::HistoryMessage),
+    #[cfg(feature = "scripting")]
+    ScriptAction(crate::scripting::ScriptMessage),
+}
+
+/// Moving through the images of the panes
+#[derive(Debug, Clone)]
+pub enum NavigationMessage {
+    SliderChanged(isize, u32),
+    SliderReleased(isize, u32),
+    JumpToImage(usize, usize),  // (pane_index, image index)
+    SetPaneOffset(isize),       // Frames pane B runs ahead of the shared slider
+}
+
+/// Layout and display options of the panes and the window they sit in
+#[derive(Debug, Clone)]
+pub enum PaneMessage {
     OnSplitResize(u16),
     ResetSplit(u16),
     ToggleSliderType(bool),
     TogglePaneLayout(PaneLayout),
     ToggleFooter(bool),
     PaneSelected(usize, bool),
-    CopyFilename(usize),
-    CopyFilePath(usize),
-    CopyImage(usize),
-    #[allow(dead_code)]
-    BackgroundColorChanged(Color),
-    #[allow(dead_code)]
-    TimerTick,
     SetCacheStrategy(CacheStrategy),
     SetCompressionStrategy(CompressionStrategy),
     ToggleFpsDisplay(bool),
     ToggleSplitOrientation(bool),
     ToggleSyncedZoom(bool),
+    SetStereoMode(StereoMode),
+    ToggleBlendCompare(bool),
+    SetBlendOpacity(f32),
     ToggleMouseWheelZoom(bool),
+    ToggleZoomLock(bool),
+    ToggleClippingWarning(bool),
     ToggleCopyButtons(bool),
     ToggleMetadataDisplay(bool),
     ToggleNearestNeighborFilter(bool),
+    ToggleRawPairing(bool),
     SetSpinnerLocation(crate::settings::SpinnerLocation),
     #[cfg(feature = "coco")]
     ToggleCocoSimplification(bool),
     #[cfg(feature = "coco")]
     SetCocoMaskRenderMode(crate::settings::CocoMaskRenderMode),
+    #[cfg(feature = "coco")]
+    ToggleCocoLabels(bool),
+    #[cfg(feature = "coco")]
+    SetCocoLabelSize(f32),
+    #[cfg(feature = "coco")]
+    SetCocoCropPadding(f32),
+    #[cfg(feature = "coco")]
+    SetCocoCropSize(u32),
     ToggleFullScreen(bool),
     CursorOnTop(bool),
     CursorOnMenu(bool),
     CursorOnFooter(bool),
-    #[cfg(feature = "selection")]
-    SelectionAction(crate::widgets::selection_widget::SelectionMessage),
-    #[cfg(feature = "coco")]
-    CocoAction(crate::coco::widget::CocoMessage),
-    // Advanced settings input
-    AdvancedSettingChanged(String, String),  // (field_name, value)
-    ResetAdvancedSettings,
     // Window resize
     WindowResized(f32, PhysicalSize<u32>, bool), // (new width, window size, is window maximized)
     PositionChanged(
         PhysicalPosition<i32>,
         Option<iced_winit::winit::monitor::MonitorHandle>,
     ), // (window position, current monitor)
-    RequestSaveImage,
-    ReadySaveImage(Result<PathBuf, file_io::Error>),
+}
+
+/// Dialogs and modals shown over the panes, and the links and folders they open
+#[derive(Debug, Clone)]
+pub enum OverlayMessage {
+    ShowAbout,
+    HideAbout,
+    ShowOptions,
+    HideOptions,
+    ShowLogs,
+    OpenSettingsDir,
+    ExportDebugLogs,
+    ExportAllLogs,
+    OpenWebLink(String),
     HideSuccessSaveModal,
     HideFailureSaveModal,
 }
+
+/// The settings dialog and the settings file
+#[derive(Debug, Clone)]
+pub enum SettingsMessage {
+    SaveWindowState,
+    SaveSettings,
+    ClearSettingsStatus,
+    SettingsTabSelected(usize),
+    // Advanced settings input
+    AdvancedSettingChanged(String, String),  // (field_name, value)
+    ResetAdvancedSettings,
+    SettingsFileChanged,
+}
+
+/// Opening, listing, loading and saving files
+#[derive(Debug, Clone)]
+pub enum IoMessage {
+    OpenFolder(usize),
+    OpenFile(usize),
+    FileDropped(isize, String),
+    Close,
+    FolderOpened(Result<String, file_io::Error>, usize),
+    DirectoryListed(usize, u64, ListingEvent),  // (pane_index, listing id, event)
+    CopyFilename(usize),
+    CopyFilePath(usize),
+    CopyImage(usize),
+    /// Show the other file of the current RAW+JPEG pair
+    SwapRawPair,
+    ImagesLoaded(ImagesLoadedResult),
+    PrefetchFiltered(usize, Vec<usize>),  // (pane_index, filtered neighbor indices)
+    FilteredPrefetchLoaded(usize, Vec<(usize, PathBuf)>, ImagesLoadedResult),
+    ProgressiveDecode(usize, u64, ProgressiveEvent),  // (pane_index, upload id, event)
+    StdinList(StdinListEvent),
+    #[allow(dead_code)]
+    SliderImageLoaded(Result<(usize, CachedData), usize>),
+    SliderImageWidgetLoaded(SliderImageWidgetResult),
+    RequestSaveImage,
+    ReadySaveImage(Result<PathBuf, file_io::Error>),
+}
+
+impl From<NavigationMessage> for Message {
+    fn from(msg: NavigationMessage) -> Self {
+        Message::Navigation(msg)
+    }
+}
+
+impl From<PaneMessage> for Message {
+    fn from(msg: PaneMessage) -> Self {
+        Message::Pane(msg)
+    }
+}
+
+impl From<OverlayMessage> for Message {
+    fn from(msg: OverlayMessage) -> Self {
+        Message::Overlay(msg)
+    }
+}
+
+impl From<SettingsMessage> for Message {
+    fn from(msg: SettingsMessage) -> Self {
+        Message::Settings(msg)
+    }
+}
+
+impl From<IoMessage> for Message {
+    fn from(msg: IoMessage) -> Self {
+        Message::Io(msg)
+    }
+}
@@ -1,30 +1,22 @@
 // Comprehensive message handler module that routes different message categories
 // This significantly reduces the size of app.rs update() method
+//
+// Core messages are split by domain into the submodules below, each matching its own
+// message enum exhaustively; feature `*Action` messages go to their feature modules.
 
-use std::path::PathBuf;
-use std::sync::Arc;
-use log::{info, warn, error, debug};
+mod io;
+mod navigation;
+mod overlay;
+mod pane;
+mod settings;
+
+use log::debug;
 use iced_winit::runtime::Task;
-use iced_wgpu::engine::CompressionStrategy;
 use iced_core::Event;
 
-use iced_runtime::clipboard;
-
 use crate::app::{DataViewer, Message};
-use crate::cache::img_cache::{CacheStrategy, CachedData, LoadOperation};
-use crate::exif_utils::decode_with_exif_orientation;
-use crate::settings::{UserSettings, WindowState};
-use crate::utils::save::extract_gpu_image;
-use crate::{file_io, window_state::get_window_visible};
-use crate::loading_handler;
-use crate::navigation_slider;
 use crate::navigation_keyboard::{move_left_all, move_right_all};
 use crate::menu::PaneLayout;
-use crate::pane::{IMAGE_RENDER_TIMES, IMAGE_RENDER_FPS};
-use crate::widgets::shader::{scene::Scene, cpu_scene::CpuScene};
-
-#[allow(unused_imports)]
-use std::time::Instant;
 
 /// Main entry point for handling all messages
 /// Routes messages to appropriate handler functions
@@ -46,7 +38,10 @@ pub fn handle_message(app: &mut DataViewer, message: Message) -> Task<Message> {
             Task::none()
         }
         Message::Quit => {
-            let _ = handle_save_window_state(app);
+            let _ = settings::handle_save_window_state(app);
+            app.viewed_images.save();
+            crate::utils::frame_stats::write_report();
+            crate::stdin_input::cleanup();
             std::process::exit(0);
         }
         Message::ReplayKeepAlive => {
@@ -57,780 +52,238 @@ pub fn handle_message(app: &mut DataViewer, message: Message) -> Task<Message> {
             Task::none()
         }
 
-        // UI state messages (About, Options, Logs)
-        Message::ShowLogs | Message::OpenSettingsDir | Message::ExportDebugLogs |
-        Message::ExportAllLogs | Message::ShowAbout | Message::HideAbout |
-        Message::ShowOptions | Message::HideOptions | Message::OpenWebLink(_) => {
-            handle_ui_messages(app, message)
-        }
+        // Core messages, by domain
+        Message::Navigation(msg) => navigation::handle_navigation_message(app, msg),
+        Message::Pane(msg) => pane::handle_pane_message(app, msg),
+        Message::Overlay(msg) => overlay::handle_overlay_message(app, msg),
+        Message::Settings(msg) => settings::handle_settings_message(app, msg),
+        Message::Io(msg) => io::handle_io_message(app, msg),
 
-        // Settings messages
-        Message::SaveWindowState | Message::SaveSettings | Message::ClearSettingsStatus |
-        Message::SettingsTabSelected(_) | Message::AdvancedSettingChanged(_, _) |
-        Message::ResetAdvancedSettings => {
-            handle_settings_messages(app, message)
+        Message::SequenceExportAction(msg) => {
+            crate::sequence_export_modal::handle_sequence_export_message(app, msg)
         }
 
-        // File operation messages
-        Message::OpenFolder(_) | Message::OpenFile(_) | Message::FileDropped(_, _) |
-        Message::Close | Message::FolderOpened(_, _) | Message::DirectoryEnumerated(_, _) |
-        Message::CopyFilename(_) | Message::CopyFilePath(_) | Message::CopyImage(_) => {
-            handle_file_messages(app, message)
+        Message::MetadataEditorAction(msg) => {
+            crate::metadata_editor::handle_metadata_editor_message(app, msg)
         }
 
-        // Image loading messages
-        Message::ImagesLoaded(_) | Message::SliderImageWidgetLoaded(_) | Message::SliderImageLoaded(_) => {
-            handle_image_loading_messages(app, message)
+        Message::PreloadAction(msg) => {
+            crate::folder_preload::handle_preload_message(app, msg)
         }
 
-        // Slider and navigation messages
-        Message::SliderChanged(_, _) | Message::SliderReleased(_, _) => {
-            handle_slider_messages(app, message)
-        }
-        
-        Message::RequestSaveImage | Message::ReadySaveImage(_) => handle_save_image(app, message),
-
-        // Toggle and UI control messages
-        Message::OnSplitResize(_) | Message::ResetSplit(_) | Message::ToggleSliderType(_) |
-        Message::TogglePaneLayout(_) | Message::ToggleFooter(_) | Message::ToggleSyncedZoom(_) |
-        Message::ToggleMouseWheelZoom(_) | Message::ToggleCopyButtons(_) | Message::ToggleMetadataDisplay(_) | Message::ToggleNearestNeighborFilter(_) |
-        Message::SetSpinnerLocation(_) |
-        Message::ToggleFullScreen(_) | Message::ToggleFpsDisplay(_) | Message::ToggleSplitOrientation(_) |
-        Message::CursorOnTop(_) | Message::CursorOnMenu(_) | Message::CursorOnFooter(_) |
-        Message::PaneSelected(_, _) | Message::SetCacheStrategy(_) | Message::SetCompressionStrategy(_) |
-        Message::WindowResized(_, _, _) | Message::PositionChanged(_, _)
-        | Message::HideSuccessSaveModal
-        | Message::HideFailureSaveModal =>
-        {
-
-            handle_toggle_messages(app, message)
+        Message::BulkLoadAction(msg) => {
+            crate::bulk_load::handle_bulk_load_message(msg)
         }
 
-        #[cfg(feature = "coco")]
-        Message::ToggleCocoSimplification(_) => {
-            handle_toggle_messages(app, message)
+        Message::AnimationAction(msg) => {
+            crate::animation::handle_animation_message(app, msg)
         }
 
-        #[cfg(feature = "coco")]
-        Message::SetCocoMaskRenderMode(_) => {
-            handle_toggle_messages(app, message)
+        Message::DisplayDecodeAction(msg) => {
+            crate::display_decode::handle_display_decode_message(app, msg)
         }
 
-        // Event messages (mouse, keyboard, file drops)
-        Message::Event(event) => {
-            handle_event_messages(app, event)
+        #[cfg(feature = "update-check")]
+        Message::UpdateAction(msg) => {
+            crate::update_check::handle_update_message(app, msg)
         }
 
-        // Feature-specific messages
-        #[cfg(feature = "selection")]
-        Message::SelectionAction(msg) => {
-            crate::widgets::selection_widget::handle_selection_message(
-                msg,
-                &app.panes,
-                &mut app.selection_manager,
-            )
+        #[cfg(feature = "gps-map")]
+        Message::MapAction(msg) => {
+            crate::gps_map::handle_map_message(app, msg)
         }
 
-        #[cfg(feature = "coco")]
-        Message::CocoAction(coco_msg) => {
-            crate::coco::widget::handle_coco_message(
-                coco_msg,
-                &mut app.panes,
-                &mut app.annotation_manager,
-            )
+        Message::FolderStatsAction(msg) => {
+            crate::folder_stats::handle_folder_stats_message(&mut app.folder_stats, msg)
         }
-    }
-}
 
-/// Routes UI state messages (About, Options, Logs, etc.)
-pub fn handle_ui_messages(app: &mut DataViewer, message: Message) -> Task<Message> {
-    match message {
-        Message::ShowLogs => {
-            let app_name = "viewskater";
-            let log_dir_path = crate::logging::get_log_directory(app_name);
-            let _ = std::fs::create_dir_all(log_dir_path.clone());
-            crate::logging::open_in_file_explorer(log_dir_path.to_string_lossy().as_ref());
-            Task::none()
+        Message::UsageStatsAction(msg) => {
+            crate::usage_stats::handle_usage_stats_message(app, msg)
         }
-        Message::OpenSettingsDir => {
-            let settings_path = UserSettings::settings_path();
-            if let Some(settings_dir) = settings_path.parent() {
-                let _ = std::fs::create_dir_all(settings_dir);
-                crate::logging::open_in_file_explorer(settings_dir.to_string_lossy().as_ref());
-            }
-            Task::none()
-        }
-        Message::ExportDebugLogs => {
-            let app_name = "viewskater";
-            if let Some(log_buffer) = crate::get_shared_log_buffer() {
-                crate::logging::export_and_open_debug_logs(app_name, log_buffer);
-            } else {
-                warn!("Log buffer not available for export");
-            }
-            Task::none()
+
+        Message::ReportAction(msg) => {
+            crate::comparison_report::handle_report_message(app, msg)
         }
-        Message::ExportAllLogs => {
-            handle_export_all_logs();
-            Task::none()
+
+        Message::FollowAction(msg) => {
+            crate::follow_mode::handle_follow_message(app, msg)
         }
-        Message::ShowAbout => {
-            app.show_about = true;
-            Task::perform(async {
-                std::thread::sleep(std::time::Duration::from_millis(5));
-            }, |_| Message::Nothing)
+
+        Message::MetricsAction(msg) => {
+            crate::widgets::metrics_panel::handle_metrics_message(msg, &app.panes, &mut app.metrics_panel)
         }
-        Message::HideAbout => {
-            app.show_about = false;
-            Task::none()
+
+        Message::PluginAction(msg) => {
+            crate::plugins::handle_plugin_message(&mut app.plugins, msg)
         }
-        Message::ShowOptions => {
-            app.settings.show();
-            Task::perform(async {
-                std::thread::sleep(std::time::Duration::from_millis(5));
-            }, |_| Message::Nothing)
+
+        Message::UserShaderAction(msg) => {
+            crate::widgets::shader::user_shader::handle_user_shader_message(msg)
         }
-        Message::HideOptions => {
-            app.settings.hide();
-            Task::none()
+
+        Message::LutAction(msg) => {
+            crate::widgets::lut_panel::handle_lut_message(msg)
         }
-        Message::OpenWebLink(url) => {
-            if let Err(e) = webbrowser::open(&url) {
-                warn!("Failed to open link: {}, error: {:?}", url, e);
-            }
-            Task::none()
+
+        Message::ExposureAction(msg) => {
+            crate::widgets::exposure_panel::handle_exposure_message(msg)
         }
-        _ => Task::none()
-    }
-}
 
-/// Routes settings-related messages
-pub fn handle_settings_messages(app: &mut DataViewer, message: Message) -> Task<Message> {
-    match message {
-        Message::SaveWindowState => handle_save_window_state(app),
-        Message::SaveSettings => handle_save_settings(app),
-        Message::ClearSettingsStatus => {
-            app.settings.clear_save_status();
-            Task::none()
+        Message::WindowLevelAction(msg) => {
+            crate::widgets::window_level::handle_window_level_message(msg)
         }
-        Message::SettingsTabSelected(index) => {
-            app.settings.set_active_tab(index);
-            Task::none()
+
+        Message::DisplayProfileAction(msg) => {
+            crate::display_profile::handle_display_profile_message(msg)
         }
-        Message::AdvancedSettingChanged(field_name, value) => {
-            app.settings.set_advanced_input(field_name, value);
-            Task::none()
+
+        Message::SoftProofAction(msg) => {
+            crate::soft_proof::handle_soft_proof_message(msg)
         }
-        Message::ResetAdvancedSettings => {
-            handle_reset_advanced_settings(app);
-            Task::none()
+
+        Message::WhiteBalanceAction(msg) => {
+            crate::white_balance::handle_white_balance_message(msg)
         }
-        _ => Task::none()
-    }
-}
 
-/// Routes file operation messages
-pub fn handle_file_messages(app: &mut DataViewer, message: Message) -> Task<Message> {
-    match message {
-        Message::OpenFolder(pane_index) => {
-            Task::perform(file_io::pick_folder(), move |result| {
-                Message::FolderOpened(result, pane_index)
-            })
+        Message::NoiseAction(msg) => {
+            crate::noise_analysis::handle_noise_message(app, msg)
         }
-        Message::OpenFile(pane_index) => {
-            Task::perform(file_io::pick_file(), move |result| {
-                Message::FolderOpened(result, pane_index)
-            })
+
+        Message::MenuLayoutAction(msg) => {
+            crate::menu_layout::handle_menu_layout_message(app, msg)
         }
-        Message::FileDropped(pane_index, dropped_path) => {
-            handle_file_dropped(app, pane_index, dropped_path)
+
+        Message::BurstAction(msg) => {
+            crate::burst_stacks::handle_burst_message(app, msg)
         }
-        Message::Close => {
-            app.reset_state(-1);
-            debug!("directory_path: {:?}", app.directory_path);
-            debug!("self.current_image_index: {}", app.current_image_index);
-            for pane in app.panes.iter_mut() {
-                let img_cache = &mut pane.img_cache;
-                debug!("img_cache.current_index: {}", img_cache.current_index);
-                debug!("img_cache.image_paths.len(): {}", img_cache.image_paths.len());
-            }
-            Task::none()
+
+        Message::RotateAction(msg) => {
+            crate::auto_rotate::handle_rotate_message(app, msg)
         }
-        Message::FolderOpened(result, pane_index) => {
-            match result {
-                Ok(dir) => {
-                    debug!("Folder opened: {}", dir);
-                    if pane_index > 0 && app.pane_layout == PaneLayout::SinglePane {
-                        debug!("Ignoring request to open folder in pane {} while in single-pane mode", pane_index);
-                        Task::none()
-                    } else {
-                        app.initialize_dir_path(&PathBuf::from(dir), pane_index)
-                    }
-                }
-                Err(err) => {
-                    debug!("Folder open failed: {:?}", err);
-                    Task::none()
-                }
-            }
+
+        Message::ChannelDiffAction(msg) => {
+            crate::channel_diff::handle_channel_diff_message(app, msg)
         }
-        Message::DirectoryEnumerated(result, pane_index) => {
-            use crate::app::DirectoryEnumError;
-            match result {
-                Ok(enum_result) => {
-                    debug!("Directory enumerated: {} images found", enum_result.file_paths.len());
-                    app.complete_dir_initialization(enum_result, pane_index)
-                }
-                Err(DirectoryEnumError::NoImagesFound) => {
-                    error!("No supported images found in directory");
-                    Task::none()
-                }
-                Err(DirectoryEnumError::DirectoryError(e)) => {
-                    error!("Directory enumeration error: {}", e);
-                    Task::none()
-                }
-                Err(DirectoryEnumError::NotFound) => {
-                    error!("Path not found");
-                    Task::none()
-                }
-            }
+
+        #[cfg(feature = "coco")]
+        Message::CocoDiscoveryAction(msg) => {
+            crate::coco::discovery::handle_discovery_message(app, msg)
         }
-        Message::CopyFilename(pane_index) => {
-            let path = &app.panes[pane_index].img_cache.image_paths[app.panes[pane_index].img_cache.current_index];
-            let filename_str = path.file_name().to_string();
-            if let Some(filename) = file_io::get_filename(&filename_str) {
-                debug!("Copying filename to clipboard: {}", filename);
-                return clipboard::write(filename);
-            }
-            Task::none()
+        #[cfg(feature = "coco")]
+        Message::CategoryRemapAction(msg) => {
+            crate::coco::category_remap::handle_category_remap_message(app, msg)
         }
-        Message::CopyFilePath(pane_index) => {
-            let path = &app.panes[pane_index].img_cache.image_paths[app.panes[pane_index].img_cache.current_index];
-            let img_path = path.file_name().to_string();
-            if let Some(dir_path) = app.panes[pane_index].directory_path.as_ref() {
-                let full_path = PathBuf::from(dir_path).join(img_path);
-                debug!("Copying full path to clipboard: {}", full_path.display());
-                return clipboard::write(full_path.to_string_lossy().to_string());
-            }
-            Task::none()
+        #[cfg(feature = "coco")]
+        Message::OverlayExportAction(msg) => {
+            crate::coco::overlay_export::handle_overlay_export_message(app, msg)
         }
-        Message::CopyImage(pane_index) => {
-            let cache = &app.panes[pane_index].img_cache;
-            // Try CPU cache first, fall back to reading from disk for GPU-cached images
-            let bytes = if let Ok(cached_data) = cache.get_current_image() {
-                cached_data.as_vec().ok()
-            } else {
-                None
-            };
-            let bytes = bytes.or_else(|| {
-                let path_source = &cache.image_paths[cache.current_index];
-                match path_source {
-                    crate::cache::img_cache::PathSource::Filesystem(path) => {
-                        std::fs::read(path).ok()
-                    }
-                    _ => {
-                        error!("Cannot copy image: archive images require CPU cache");
-                        None
-                    }
-                }
-            });
-            if let Some(bytes) = bytes {
-                std::thread::spawn(move || {
-                    match image::load_from_memory(&bytes) {
-                        Ok(img) => {
-                            let rgba = img.to_rgba8();
-                            let (w, h) = rgba.dimensions();
-                            let img_data = arboard::ImageData {
-                                width: w as usize,
-                                height: h as usize,
-                                bytes: std::borrow::Cow::Owned(rgba.into_raw()),
-                            };
-                            match arboard::Clipboard::new() {
-                                Ok(mut clip) => {
-                                    if let Err(e) = clip.set_image(img_data) {
-                                        error!("Failed to copy image to clipboard: {}", e);
-                                    } else {
-                                        debug!("Image copied to clipboard ({}x{})", w, h);
-                                    }
-                                }
-                                Err(e) => error!("Failed to open clipboard: {}", e),
-                            }
-                        }
-                        Err(e) => error!("Failed to decode image for clipboard: {}", e),
-                    }
-                });
-            }
-            Task::none()
+        #[cfg(feature = "inference")]
+        Message::InferenceAction(msg) => {
+            crate::inference::handle_inference_message(app, msg)
         }
-        _ => Task::none()
-    }
-}
 
-/// Routes image loading messages
-pub fn handle_image_loading_messages(app: &mut DataViewer, message: Message) -> Task<Message> {
-    match message {
-        Message::ImagesLoaded(result) => {
-            debug!("ImagesLoaded");
-            match result {
-                Ok((image_data, metadata, operation)) => {
-                    if let Some(op) = operation {
-                        let cloned_op = op.clone();
-                        match op {
-                            LoadOperation::LoadNext((ref pane_indices, ref target_indices))
-                            | LoadOperation::LoadPrevious((ref pane_indices, ref target_indices))
-                            | LoadOperation::ShiftNext((ref pane_indices, ref target_indices))
-                            | LoadOperation::ShiftPrevious((ref pane_indices, ref target_indices)) => {
-                                let operation_type = cloned_op.operation_type();
-
-                                loading_handler::handle_load_operation_all(
-                                    &mut app.panes,
-                                    &mut app.loading_status,
-                                    pane_indices,
-                                    target_indices,
-                                    &image_data,
-                                    &metadata,
-                                    &cloned_op,
-                                    operation_type,
-                                );
-
-                                // Clear loading timer for the panes that completed
-                                // (clear per-pane, not based on global queue state)
-                                for &pane_idx in pane_indices {
-                                    if let Some(pane) = app.panes.get_mut(pane_idx) {
-                                        pane.loading_started_at = None;
-                                    }
-                                }
-                            }
-                            LoadOperation::LoadPos((pane_index, target_indices_and_cache)) => {
-                                loading_handler::handle_load_pos_operation(
-                                    &mut app.panes,
-                                    &mut app.loading_status,
-                                    pane_index,
-                                    &target_indices_and_cache,
-                                    &image_data,
-                                    &metadata,
-                                );
-
-                                // Clear loading timer for this pane
-                                if let Some(pane) = app.panes.get_mut(pane_index) {
-                                    pane.loading_started_at = None;
-                                }
-
-                                // Signal replay controller that initial load is complete
-                                if let Some(ref mut replay_controller) = app.replay_controller {
-                                    if matches!(replay_controller.state, crate::replay::ReplayState::WaitingForReady { .. }) {
-                                        debug!("LoadPos complete - signaling replay controller that app is ready to navigate");
-
-                                        // Set image count for slider mode navigation
-                                        if let Some(pane) = app.panes.get(pane_index) {
-                                            replay_controller.set_image_count(pane.img_cache.image_paths.len());
-                                        }
-
-                                        // Reset FPS trackers right before navigation starts
-                                        // This ensures no stale data from image loading contaminates metrics
-                                        if let Ok(mut fps) = crate::CURRENT_FPS.lock() { *fps = 0.0; }
-                                        if let Ok(mut fps) = IMAGE_RENDER_FPS.lock() { *fps = 0.0; }
-                                        if let Ok(mut times) = crate::FRAME_TIMES.lock() { times.clear(); }
-                                        if let Ok(mut times) = IMAGE_RENDER_TIMES.lock() { times.clear(); }
-                                        iced_wgpu::reset_image_fps();
-
-                                        replay_controller.on_ready_to_navigate();
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-                Err(err) => {
-                    debug!("Image load failed: {:?}", err);
-                }
-            }
-            Task::none()
-        }
-        Message::SliderImageWidgetLoaded(result) => {
-            match result {
-                Ok((pane_idx, pos, handle, dimensions, file_size)) => {
-                    crate::track_async_delivery();
-
-                    if let Some(pane) = app.panes.get_mut(pane_idx) {
-                        pane.slider_image = Some(handle);
-                        pane.slider_image_dimensions = Some(dimensions);
-                        pane.slider_image_position = Some(pos);
-                        // Update metadata for footer display during slider dragging
-                        pane.current_image_metadata = Some(crate::cache::img_cache::ImageMetadata::new(
-                            dimensions.0, dimensions.1, file_size
-                        ));
-                        // BUGFIX: Don't update current_index here! It causes desyncs when stale slider images
-                        // load after slider release. The slider position is tracked in slider_image_position instead.
-                        // pane.img_cache.current_index = pos;
-
-                        debug!("Slider image loaded for pane {} at position {} with dimensions {:?}", pane_idx, pos, dimensions);
-                    } else {
-                        warn!("SliderImageWidgetLoaded: Invalid pane index {}", pane_idx);
-                    }
-                },
-                Err((pane_idx, pos)) => {
-                    warn!("SLIDER: Failed to load image widget for pane {} at position {}", pane_idx, pos);
-                }
-            }
-            Task::none()
+        Message::TriggerAction(action) => {
+            crate::triggers::handle_trigger_action(app, action)
         }
-        Message::SliderImageLoaded(result) => {
-            match result {
-                Ok((pos, cached_data)) => {
-                    let pane = &mut app.panes[0];
-
-                    if let CachedData::Cpu(bytes) = &cached_data {
-                        debug!("SliderImageLoaded: loaded data: {:?}", bytes.len());
-
-                        pane.current_image = CachedData::Cpu(bytes.clone());
-                        pane.current_image_index = Some(pos);
-                        pane.slider_scene = Some(Scene::CpuScene(CpuScene::new(
-                            bytes.clone(), true)));
-
-                        if let Some(device) = &pane.device {
-                            if let Some(queue) = &pane.queue {
-                                if let Some(scene) = &mut pane.slider_scene {
-                                    scene.ensure_texture(device, queue, pane.pane_id);
-                                }
-                            }
-                        }
-                    }
-                },
-                Err(pos) => {
-                    warn!("SLIDER: Failed to load image for position {}", pos);
-                }
-            }
-            Task::none()
-        }
-        _ => Task::none()
-    }
-}
-
-/// Routes slider and navigation messages
-pub fn handle_slider_messages(app: &mut DataViewer, message: Message) -> Task<Message> {
-    match message {
-        Message::SliderChanged(pane_index, value) => {
-            app.is_slider_moving = true;
-            app.use_slider_image_for_render = true;
-            app.last_slider_update = Instant::now();
-
-            // Reset COCO zoom state when slider starts moving
-            #[cfg(feature = "coco")]
-            {
-                if pane_index == -1 {
-                    // Reset all panes
-                    for pane in app.panes.iter_mut() {
-                        pane.zoom_scale = 1.0;
-                        pane.zoom_offset = iced_core::Vector::default();
-                    }
-                } else {
-                    // Reset specific pane
-                    if let Some(pane) = app.panes.get_mut(pane_index as usize) {
-                        pane.zoom_scale = 1.0;
-                        pane.zoom_offset = iced_core::Vector::default();
-                    }
-                }
-            }
-
-            let use_async = true;
 
-            #[cfg(target_os = "linux")]
-            let use_throttle = true;
-            #[cfg(not(target_os = "linux"))]
-            let use_throttle = false;
+        Message::ProfileAction(msg) => settings::handle_profile_message(app, msg),
 
-            if pane_index == -1 {
-                app.prev_slider_value = app.slider_value;
-                app.slider_value = value;
-
-                if app.panes[0].slider_image.is_none() {
-                    for pane in app.panes.iter_mut() {
-                        pane.slider_scene = None;
-                    }
-                }
-            } else {
-                let pane_index_usize = pane_index as usize;
-
-                if app.is_slider_dual && app.pane_layout == PaneLayout::DualPane {
-                    for idx in 0..app.panes.len() {
-                        if idx != pane_index_usize {
-                            app.panes[idx].slider_image = None;
-                            app.panes[idx].slider_image_position = None;
-                        }
-                    }
-                }
-
-                let pane = &mut app.panes[pane_index_usize];
-                pane.prev_slider_value = pane.slider_value;
-                pane.slider_value = value;
-
-                if pane.slider_image.is_none() {
-                    pane.slider_scene = None;
-                }
-            }
-
-            navigation_slider::update_pos(
-                &mut app.panes,
-                pane_index,
-                value as usize,
-                use_async,
-                use_throttle,
-            )
+        Message::HistoryAction(msg) => {
+            crate::navigation_history::handle_history_message(app, msg)
         }
-        Message::SliderReleased(pane_index, value) => {
-            debug!("SLIDER_DEBUG: SliderReleased event received");
-            app.is_slider_moving = false;
-
-            let final_image_fps = iced_wgpu::get_image_fps();
-            let upload_timestamps = iced_wgpu::get_image_upload_timestamps();
-
-            if !upload_timestamps.is_empty() {
-                if let Ok(mut render_times) = IMAGE_RENDER_TIMES.lock() {
-                    *render_times = upload_timestamps.into_iter().collect();
-
-                    if let Ok(mut fps) = IMAGE_RENDER_FPS.lock() {
-                        *fps = final_image_fps as f32;
-                        debug!("SLIDER_DEBUG: Synced image fps tracking, final FPS: {:.1}", final_image_fps);
-                    }
-                }
-            }
 
-            // Use the position of the currently displayed slider_image if available,
-            // otherwise fall back to the slider value
-            let pos = if pane_index >= 0 {
-                app.panes.get(pane_index as usize)
-                    .and_then(|pane| pane.slider_image_position)
-                    .unwrap_or(value as usize)
-            } else {
-                // For pane_index == -1 (all panes), use slider_image_position from pane 0
-                app.panes.first()
-                    .and_then(|pane| pane.slider_image_position)
-                    .unwrap_or(value as usize)
-            };
-
-            debug!("SliderReleased: Using position {} (slider_image_position) instead of slider value {}", pos, value);
-
-            navigation_slider::load_remaining_images(
-                &app.device,
-                &app.queue,
-                app.is_gpu_supported,
-                app.cache_strategy,
-                app.compression_strategy,
-                &mut app.panes,
-                &mut app.loading_status,
-                pane_index,
-                pos)
+        Message::FavoritesAction(msg) => {
+            crate::favorites::handle_favorites_message(app, msg)
         }
-        _ => Task::none()
-    }
-}
 
-/// Routes toggle and UI control messages
-pub fn handle_toggle_messages(app: &mut DataViewer, message: Message) -> Task<Message> {
-    match message {
-        Message::OnSplitResize(position) => {
-            app.divider_position = Some(position);
-            Task::none()
-        }
-        Message::ResetSplit(_position) => {
-            app.divider_position = None;
-            Task::none()
-        }
-        Message::ToggleSliderType(_bool) => {
-            app.toggle_slider_type();
-            Task::none()
+        Message::PlaylistAction(msg) => {
+            crate::playlist::handle_playlist_message(app, msg)
         }
-        Message::TogglePaneLayout(pane_layout) => {
-            app.toggle_pane_layout(pane_layout);
-            Task::none()
-        }
-        Message::ToggleFooter(_bool) => {
-            app.toggle_footer();
-            Task::none()
-        }
-        Message::ToggleSyncedZoom(enabled) => {
-            app.synced_zoom = enabled;
-            Task::none()
-        }
-        Message::ToggleMouseWheelZoom(enabled) => {
-            app.mouse_wheel_zoom = enabled;
-            for pane in app.panes.iter_mut() {
-                pane.mouse_wheel_zoom = enabled;
-            }
-            Task::none()
+
+        Message::SessionAction(msg) => {
+            crate::session::handle_session_message(app, msg)
         }
-        Message::ToggleCopyButtons(enabled) => {
-            app.show_copy_buttons = enabled;
-            Task::none()
+
+        Message::HashAction(msg) => {
+            crate::file_hashes::handle_hash_message(app, msg)
         }
-        Message::ToggleMetadataDisplay(enabled) => {
-            app.show_metadata = enabled;
-            Task::none()
+
+        Message::DeleteAction(msg) => {
+            crate::file_delete::handle_delete_message(app, msg)
         }
 
-        Message::HideSuccessSaveModal => {
-            app.toggle_success_save_modal();
+        #[cfg(target_os = "macos")]
+        Message::AccessPromptAction(msg) => {
+            crate::access_prompt::handle_access_prompt_message(app, msg)
+        }
 
-            Task::none()
+        #[cfg(target_os = "macos")]
+        Message::PermissionsAction(msg) => {
+            crate::bookmark_permissions::handle_permissions_message(app, msg)
         }
-        Message::HideFailureSaveModal => {
-            app.set_failure_save_modal(None);
-            Task::none()
+
+        Message::ViewedAction(msg) => {
+            crate::viewed_images::handle_viewed_message(app, msg)
         }
 
+        #[cfg(feature = "scripting")]
+        Message::ScriptAction(msg) => {
+            crate::scripting::handle_script_message(app, msg)
+        }
 
-        Message::ToggleNearestNeighborFilter(enabled) => {
-            debug!("ToggleNearestNeighborFilter: setting to {}", enabled);
-            app.nearest_neighbor_filter = enabled;
+        Message::OverviewAction(msg) => {
+            crate::widgets::overview_strip::handle_overview_message(msg, &mut app.overview)
+        }
 
-            // Force reload of current directories to apply the new filter immediately
-            let mut tasks = Vec::new();
-            for pane_index in 0..app.panes.len() {
-                if let Some(dir_path) = app.panes[pane_index].directory_path.clone() {
-                    debug!("Reloading directory for pane {}: {:?}", pane_index, dir_path);
-                    tasks.push(app.initialize_dir_path(&PathBuf::from(dir_path), pane_index));
-                }
-            }
+        Message::TimelineAction(msg) => {
+            crate::widgets::timeline_strip::handle_timeline_message(msg, &mut app.timeline)
+        }
 
-            Task::batch(tasks)
+        Message::SidebarAction(msg) => {
+            crate::widgets::sidebar::handle_sidebar_message(msg, &mut app.sidebar)
         }
-        Message::SetSpinnerLocation(location) => {
-            debug!("SetSpinnerLocation: setting to {:?}", location);
-            app.spinner_location = location;
-            Task::none()
+
+        // Event messages (mouse, keyboard, file drops)
+        Message::Event(event) => {
+            handle_event_messages(app, event)
         }
-        #[cfg(feature = "coco")]
-        Message::ToggleCocoSimplification(enabled) => {
-            app.coco_disable_simplification = enabled;
-            Task::none()
+
+        // Feature-specific messages
+        #[cfg(feature = "selection")]
+        Message::SelectionAction(msg) => {
+            crate::widgets::selection_widget::handle_selection_message(
+                msg,
+                &app.panes,
+                &mut app.selection_manager,
+            )
         }
+
         #[cfg(feature = "coco")]
-        Message::SetCocoMaskRenderMode(mode) => {
-            app.coco_mask_render_mode = mode;
-            Task::none()
-        }
-        Message::ToggleFullScreen(enabled) => {
-            if enabled {
-                app.window_state = WindowState::FullScreen;
-            } else {
-                app.window_state = WindowState::Window;
-            }
-            Task::none()
-        }
-        Message::ToggleFpsDisplay(value) => {
-            app.show_fps = value;
-            Task::none()
-        }
-        Message::ToggleSplitOrientation(_bool) => {
-            app.toggle_split_orientation();
-            Task::none()
-        }
-        Message::CursorOnTop(value) => {
-            app.cursor_on_top = value;
-            Task::none()
-        }
-        Message::CursorOnMenu(value) => {
-            app.cursor_on_menu = value;
-            Task::none()
-        }
-        Message::CursorOnFooter(value) => {
-            app.cursor_on_footer = value;
-            Task::none()
-        }
-        Message::PaneSelected(pane_index, is_selected) => {
-            app.panes[pane_index].is_selected = is_selected;
-            for (index, pane) in app.panes.iter_mut().enumerate() {
-                debug!("pane_index: {}, is_selected: {}", index, pane.is_selected);
-            }
-            Task::none()
-        }
-        Message::SetCacheStrategy(strategy) => {
-            app.update_cache_strategy(strategy);
-            Task::none()
-        }
-        Message::SetCompressionStrategy(strategy) => {
-            app.update_compression_strategy(strategy);
-            Task::none()
-        }
-        Message::WindowResized(width, size, is_maximized) => {
-            app.window_width = width;
-            app.window_size = size;
-
-            // Track the largest size seen while maximized (used by Linux X11 un-maximize workaround)
-            if is_maximized {
-                let should_update = app.maximized_size.map_or(true, |max_size| {
-                    size.width > max_size.width || size.height > max_size.height
-                });
-                if should_update {
-                    app.maximized_size = Some(size);
+        Message::CocoAction(coco_msg) => {
+            if let crate::coco::widget::CocoMessage::CropsExported(ref result) = coco_msg {
+                match result {
+                    Ok(summary) if summary.failed == 0 => app.toggle_success_save_modal(),
+                    Ok(summary) => app.set_failure_save_modal(Some(format!(
+                        "{} crop(s) could not be exported ({} saved to {})",
+                        summary.failed, summary.exported, summary.output_dir.display()))),
+                    Err(e) => app.set_failure_save_modal(Some(e.clone())),
                 }
             }
-
-            // macOS: use is_maximized (winit's isZoomed() wrapper), same as other platforms.
-            // isZoomed() may be unreliable mid-animation, but save_window_state_to_disk
-            // queries it authoritatively post-animation as a safety net.
-            #[cfg(target_os = "macos")]
-            match app.window_state {
-                WindowState::Window => {
-                    if is_maximized {
-                        app.window_state = WindowState::Maximized;
-                        app.last_windowed_position = app.position_before_transition;
-                    }
-                },
-                WindowState::Maximized => {
-                    if !is_maximized {
-                        app.window_state = WindowState::Window;
-                    }
-                },
-                _ => {},
-            }
-
-            // Windows/Linux: use winit's is_maximized() (reliable on these platforms)
-            #[cfg(not(target_os = "macos"))]
-            match app.window_state {
-                WindowState::Window => {
-                    if is_maximized {
-                        app.window_state = WindowState::Maximized;
-                        // Windows workaround: PositionChanged(0,0) fires before this event,
-                        // corrupting last_windowed_position. Restore from backup.
-                        app.last_windowed_position = app.position_before_transition;
-                    }
-                },
-                WindowState::Maximized => {
-                    if !is_maximized {
-                        // Primary detection: is_maximized() returned false (works on Windows/macOS)
-                        app.window_state = WindowState::Window;
-                    } else {
-                        // X11 workaround: is_maximized() returns stale true during un-maximize transition
-                        // On X11, maximized windows cannot be resized - any size change means un-maximize
-                        #[cfg(target_os = "linux")]
-                        {
-                            let size_changed = app.maximized_size.map_or(false, |max_size| size != max_size);
-                            if size_changed {
-                                app.window_state = WindowState::Window;
-                                app.maximized_size = None;
-                            }
-                        }
-                    }
-                },
-                _ => {},
-            }
-
-            Task::none()
+            crate::coco::widget::handle_coco_message(
+                coco_msg,
+                &mut app.panes,
+                &mut app.annotation_manager,
+            )
         }
-        Message::PositionChanged(position, monitor) => {
-            app.window_position = position;
-            let is_same_monitor = app.last_monitor == monitor;
-            // Only track last_windowed_position when in windowed state or moving across different monitors
-            // Save previous value first (Windows workaround: PositionChanged fires before WindowResized
-            // during maximize, so we need to be able to restore if transition is detected)
-            if app.window_state == WindowState::Window || !is_same_monitor {
-                app.position_before_transition = app.last_windowed_position;
-                app.last_windowed_position = position;
-                app.last_monitor = monitor;
-            }
-            Task::none()
+
+        #[cfg(feature = "embeddings")]
+        Message::SimilarityAction(msg) => {
+            crate::widgets::similarity_widget::handle_similarity_message(
+                msg,
+                &app.panes,
+                &mut app.embedding_manager,
+            )
         }
-        _ => Task::none()
     }
 }
 
@@ -838,7 +291,16 @@ pub fn handle_toggle_messages(app: &mut DataViewer, message: Message) -> Task<Me
 pub fn handle_event_messages(app: &mut DataViewer, event: Event) -> Task<Message> {
     match event {
         Event::Mouse(iced_core::mouse::Event::WheelScrolled { delta }) => {
-            if !app.ctrl_pressed && !app.mouse_wheel_zoom && !app.settings.is_visible() && !app.show_about {
+            // The category dialog scrolls its own list
+            #[cfg(feature = "coco")]
+            let category_remap_visible = app.category_remap.visible;
+            #[cfg(not(feature = "coco"))]
+            let category_remap_visible = false;
+            #[cfg(feature = "inference")]
+            let upscale_preview_visible = app.inference.upscale_preview_visible();
+            #[cfg(not(feature = "inference"))]
+            let upscale_preview_visible = false;
+            if !crate::widgets::shader::image_shader::wheel_zooms(app.mouse_wheel_zoom, app.ctrl_pressed) && !app.settings.is_visible() && !app.show_about && !app.show_usage_stats && !app.metadata_editor.visible && !category_remap_visible && !upscale_preview_visible && !app.file_delete.dialog_visible() {
                 match delta {
                     iced_core::mouse::ScrollDelta::Lines { y, .. }
                     | iced_core::mouse::ScrollDelta::Pixels { y, .. } => {
@@ -883,7 +345,7 @@ pub fn handle_event_messages(app: &mut DataViewer, event: Event) -> Task<Message
                     }
                 };
             } else {
-                // Mouse wheel with ctrl pressed or mouse_wheel_zoom enabled = zoom mode
+                // Mouse wheel in zoom mode (see `wheel_zooms`), or over a dialog
                 // Clear slider state to switch to ImageShader widget which handles zoom
                 if app.use_slider_image_for_render {
                     app.use_slider_image_for_render = false;
@@ -895,6 +357,13 @@ pub fn handle_event_messages(app: &mut DataViewer, event: Event) -> Task<Message
             Task::none()
         }
 
+        Event::Mouse(iced_core::mouse::Event::ButtonPressed(iced_core::mouse::Button::Back)) => {
+            crate::navigation_history::handle_history_message(app, crate::navigation_history::HistoryMessage::Back)
+        }
+        Event::Mouse(iced_core::mouse::Event::ButtonPressed(iced_core::mouse::Button::Forward)) => {
+            crate::navigation_history::handle_history_message(app, crate::navigation_history::HistoryMessage::Forward)
+        }
+
         Event::Keyboard(iced_core::keyboard::Event::KeyPressed { key, modifiers, .. }) => {
             debug!("KeyPressed - Key pressed: {:?}, modifiers: {:?}", key, modifiers);
             debug!("modifiers.shift(): {}", modifiers.shift());
@@ -933,6 +402,9 @@ pub fn handle_event_messages(app: &mut DataViewer, event: Event) -> Task<Message
 // ============================================================================
 
 fn handle_window_file_drop(app: &mut DataViewer, path: &std::path::Path) -> Task<Message> {
+    if crate::session::is_session_file(path) {
+        return Task::done(crate::session::SessionMessage::OpenFrom(path.to_path_buf()).into());
+    }
     if app.pane_layout != PaneLayout::SinglePane {
         return Task::none();
     }
@@ -944,7 +416,7 @@ fn handle_window_file_drop(app: &mut DataViewer, path: &std::path::Path) -> Task
         match std::fs::read_to_string(path) {
             Ok(content) => {
                 if crate::coco::parser::CocoDataset::is_coco_format(&content) {
-                    info!("✓ Detected COCO JSON file: {}", path.display());
+                    log::info!("✓ Detected COCO JSON file: {}", path.display());
                     return Task::done(Message::CocoAction(
                         crate::coco::widget::CocoMessage::LoadCocoFile(path.to_path_buf())
                     ));
@@ -953,7 +425,7 @@ fn handle_window_file_drop(app: &mut DataViewer, path: &std::path::Path) -> Task
                 }
             }
             Err(e) => {
-                warn!("Failed to read JSON file: {}", e);
+                log::warn!("Failed to read JSON file: {}", e);
             }
         }
     }
@@ -962,447 +434,3 @@ fn handle_window_file_drop(app: &mut DataViewer, path: &std::path::Path) -> Task
     debug!("File dropped: {:?}", path);
     app.initialize_dir_path(&path.to_path_buf(), 0)
 }
-
-fn handle_file_dropped(app: &mut DataViewer, pane_index: isize, dropped_path: String) -> Task<Message> {
-    let path = PathBuf::from(&dropped_path);
-
-    #[cfg(feature = "coco")]
-    debug!("COCO FEATURE IS ENABLED");
-    #[cfg(not(feature = "coco"))]
-    debug!("COCO FEATURE IS DISABLED");
-
-    #[cfg(feature = "coco")]
-    if path.extension().and_then(|s| s.to_str()) == Some("json") {
-        debug!("JSON file detected, checking if it's COCO format: {}", path.display());
-        match std::fs::read_to_string(&path) {
-            Ok(content) => {
-                if crate::coco::parser::CocoDataset::is_coco_format(&content) {
-                    info!("✓ Detected COCO JSON file: {}", path.display());
-                    return Task::none();
-                } else {
-                    debug!("JSON file is not COCO format, treating as regular file");
-                }
-            }
-            Err(e) => {
-                warn!("Failed to read JSON file: {}", e);
-            }
-        }
-    }
-
-    debug!("Message::FileDropped - Resetting state");
-    app.reset_state(pane_index);
-
-    debug!("File dropped: {:?}, pane_index: {}", dropped_path, pane_index);
-    debug!("self.dir_loaded, pane_index, last_opened_pane: {:?}, {}, {}",
-        app.panes[pane_index as usize].dir_loaded, pane_index, app.last_opened_pane);
-    app.initialize_dir_path(&path, pane_index as usize)
-}
-
-fn handle_save_settings(app: &mut DataViewer) -> Task<Message> {
-    let parse_value = |key: &str, _default: u64| -> Result<u64, String> {
-        app.settings.advanced_input
-            .get(key)
-            .ok_or_else(|| format!("Missing value for {}", key))?
-            .parse::<u64>()
-            .map_err(|_| format!("Invalid number for {}", key))
-    };
-
-    let cache_size = match parse_value("cache_size", 5) {
-        Ok(v) if v > 0 && v <= 100 => v as usize,
-        Ok(_) => {
-            app.settings.set_save_status(Some("Error: Cache size must be between 1 and 100".to_string()));
-            return Task::perform(async {
-                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            }, |_| Message::ClearSettingsStatus);
-        }
-        Err(e) => {
-            app.settings.set_save_status(Some(format!("Error parsing cache_size: {}", e)));
-            return Task::perform(async {
-                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            }, |_| Message::ClearSettingsStatus);
-        }
-    };
-
-    let max_loading_queue_size = match parse_value("max_loading_queue_size", 3) {
-        Ok(v) if v > 0 && v <= 50 => v as usize,
-        Ok(_) => {
-            app.settings.set_save_status(Some("Error: Max loading queue size must be between 1 and 50".to_string()));
-            return Task::perform(async {
-                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            }, |_| Message::ClearSettingsStatus);
-        }
-        Err(e) => {
-            app.settings.set_save_status(Some(format!("Error parsing max_loading_queue_size: {}", e)));
-            return Task::perform(async {
-                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            }, |_| Message::ClearSettingsStatus);
-        }
-    };
-
-    let max_being_loaded_queue_size = match parse_value("max_being_loaded_queue_size", 3) {
-        Ok(v) if v > 0 && v <= 50 => v as usize,
-        Ok(_) => {
-            app.settings.set_save_status(Some("Error: Max being loaded queue size must be between 1 and 50".to_string()));
-            return Task::perform(async {
-                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            }, |_| Message::ClearSettingsStatus);
-        }
-        Err(e) => {
-            app.settings.set_save_status(Some(format!("Error parsing max_being_loaded_queue_size: {}", e)));
-            return Task::perform(async {
-                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            }, |_| Message::ClearSettingsStatus);
-        }
-    };
-
-    let atlas_size = match parse_value("atlas_size", 2048) {
-        Ok(v) if (256..=8192).contains(&v) && v.is_power_of_two() => v as u32,
-        Ok(_) => {
-            app.settings.set_save_status(Some("Error: Atlas size must be a power of 2 between 256 and 8192".to_string()));
-            return Task::perform(async {
-                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            }, |_| Message::ClearSettingsStatus);
-        }
-        Err(e) => {
-            app.settings.set_save_status(Some(format!("Error parsing atlas_size: {}", e)));
-            return Task::perform(async {
-                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            }, |_| Message::ClearSettingsStatus);
-        }
-    };
-
-    let double_click_threshold_ms = match parse_value("double_click_threshold_ms", 250) {
-        Ok(v) if (50..=1000).contains(&v) => v as u16,
-        Ok(_) => {
-            app.settings.set_save_status(Some("Error: Double-click threshold must be between 50 and 1000 ms".to_string()));
-            return Task::perform(async {
-                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            }, |_| Message::ClearSettingsStatus);
-        }
-        Err(e) => {
-            app.settings.set_save_status(Some(format!("Error parsing double_click_threshold_ms: {}", e)));
-            return Task::perform(async {
-                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            }, |_| Message::ClearSettingsStatus);
-        }
-    };
-
-    let archive_cache_size = match parse_value("archive_cache_size", 200) {
-        Ok(v) if (10..=10000).contains(&v) => v,
-        Ok(_) => {
-            app.settings.set_save_status(Some("Error: Archive cache size must be between 10 and 10000 MB".to_string()));
-            return Task::perform(async {
-                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            }, |_| Message::ClearSettingsStatus);
-        }
-        Err(e) => {
-            app.settings.set_save_status(Some(format!("Error parsing archive_cache_size: {}", e)));
-            return Task::perform(async {
-                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            }, |_| Message::ClearSettingsStatus);
-        }
-    };
-
-    let archive_warning_threshold_mb = match parse_value("archive_warning_threshold_mb", 500) {
-        Ok(v) if (10..=10000).contains(&v) => v,
-        Ok(_) => {
-            app.settings.set_save_status(Some("Error: Archive warning threshold must be between 10 and 10000 MB".to_string()));
-            return Task::perform(async {
-                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            }, |_| Message::ClearSettingsStatus);
-        }
-        Err(e) => {
-            app.settings.set_save_status(Some(format!("Error parsing archive_warning_threshold_mb: {}", e)));
-            return Task::perform(async {
-                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            }, |_| Message::ClearSettingsStatus);
-        }
-    };
-
-    let settings = UserSettings {
-        show_fps: app.show_fps,
-        show_footer: app.show_footer,
-        is_horizontal_split: app.is_horizontal_split,
-        synced_zoom: app.synced_zoom,
-        mouse_wheel_zoom: app.mouse_wheel_zoom,
-        show_copy_buttons: app.show_copy_buttons,
-        show_metadata: app.show_metadata,
-        nearest_neighbor_filter: app.nearest_neighbor_filter,
-        cache_strategy: match app.cache_strategy {
-            CacheStrategy::Cpu => "cpu".to_string(),
-            CacheStrategy::Gpu => "gpu".to_string(),
-        },
-        compression_strategy: match app.compression_strategy {
-            CompressionStrategy::None => "none".to_string(),
-            CompressionStrategy::Bc1 => "bc1".to_string(),
-        },
-        is_slider_dual: app.is_slider_dual,
-        cache_size,
-        max_loading_queue_size,
-        max_being_loaded_queue_size,
-        window_width: app.window_size.width,
-        window_height: app.window_size.height,
-        atlas_size,
-        double_click_threshold_ms,
-        archive_cache_size,
-        archive_warning_threshold_mb,
-        #[cfg(feature = "coco")]
-        coco_disable_simplification: app.coco_disable_simplification,
-        #[cfg(not(feature = "coco"))]
-        coco_disable_simplification: false,
-        #[cfg(feature = "coco")]
-        coco_mask_render_mode: app.coco_mask_render_mode,
-        #[cfg(not(feature = "coco"))]
-        coco_mask_render_mode: crate::settings::CocoMaskRenderMode::default(),
-        use_binary_size: app.use_binary_size,
-        spinner_location: app.spinner_location,
-        window_state: app.window_state,
-        window_position_x: app.window_position.x,
-        window_position_y: app.window_position.y,
-    };
-
-    let old_settings = UserSettings::load(None);
-    let window_settings_changed = atlas_size != old_settings.atlas_size;
-
-    match settings.save() {
-        Ok(_) => {
-            info!("Settings saved successfully");
-
-            app.archive_cache_size = archive_cache_size * 1_048_576;
-            app.archive_warning_threshold_mb = archive_warning_threshold_mb;
-            info!("Archive settings applied immediately: cache_size={}MB, warning_threshold={}MB",
-                archive_cache_size, archive_warning_threshold_mb);
-
-            if cache_size != app.cache_size {
-                info!("Cache size changed from {} to {}, reloading all panes", app.cache_size, cache_size);
-                app.cache_size = cache_size;
-
-                let pane_file_lengths: Vec<usize> = app.panes.iter()
-                    .map(|p| p.img_cache.num_files)
-                    .collect();
-
-                let cache_size = app.cache_size;
-                let archive_cache_size = app.archive_cache_size;
-                let archive_warning_threshold_mb = app.archive_warning_threshold_mb;
-
-                for (i, pane) in app.panes.iter_mut().enumerate() {
-                    if let Some(dir_path) = &pane.directory_path.clone() {
-                        if pane.dir_loaded {
-                            let path = PathBuf::from(dir_path);
-
-                            let _ = pane.initialize_dir_path(
-                                &Arc::clone(&app.device),
-                                &Arc::clone(&app.queue),
-                                app.is_gpu_supported,
-                                app.cache_strategy,
-                                app.compression_strategy,
-                                &app.pane_layout,
-                                &pane_file_lengths,
-                                i,
-                                &path,
-                                app.is_slider_dual,
-                                &mut app.slider_value,
-                                cache_size,
-                                archive_cache_size,
-                                archive_warning_threshold_mb,
-                            );
-                        }
-                    }
-                }
-            }
-
-            if max_loading_queue_size != app.max_loading_queue_size || max_being_loaded_queue_size != app.max_being_loaded_queue_size {
-                info!("Queue size settings changed: max_loading_queue_size={}, max_being_loaded_queue_size={}", max_loading_queue_size, max_being_loaded_queue_size);
-                app.max_loading_queue_size = max_loading_queue_size;
-                app.max_being_loaded_queue_size = max_being_loaded_queue_size;
-
-                for pane in app.panes.iter_mut() {
-                    pane.max_loading_queue_size = max_loading_queue_size;
-                    pane.max_being_loaded_queue_size = max_being_loaded_queue_size;
-                }
-            }
-
-            if double_click_threshold_ms != app.double_click_threshold_ms {
-                info!("Double-click threshold changed from {} to {} ms", app.double_click_threshold_ms, double_click_threshold_ms);
-                app.double_click_threshold_ms = double_click_threshold_ms;
-            }
-
-            app.settings.set_save_status(Some(if window_settings_changed {
-                "Settings saved! Window settings require restart, other changes applied immediately.".to_string()
-            } else {
-                "Settings saved! All changes applied immediately.".to_string()
-            }));
-
-            Task::perform(async {
-                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            }, |_| Message::ClearSettingsStatus)
-        }
-        Err(e) => {
-            error!("Failed to save settings: {}", e);
-            app.settings.set_save_status(Some(format!("Error: {}", e)));
-
-            Task::perform(async {
-                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            }, |_| Message::ClearSettingsStatus)
-        }
-    }
-}
-
-fn handle_save_window_state(app: &mut DataViewer) -> Task<Message> {
-    let mut old_settings = UserSettings::load(None);
-    let tuple = get_window_visible(app.last_windowed_position, app.window_size,
-        app.last_monitor.clone());
-    // Prevents the saved position from being outside of the monitor
-    if !tuple.0 {
-        app.last_windowed_position = tuple.1;
-    }
-    // Use last_windowed_position to avoid saving maximized position (0,0) on Windows
-    old_settings.window_position_x = app.last_windowed_position.x;
-    old_settings.window_position_y = app.last_windowed_position.y;
-    if app.window_state == WindowState::Window {
-        old_settings.window_width = app.window_size.width;
-        old_settings.window_height = app.window_size.height;
-    }
-    old_settings.window_state = app.window_state;
-    if let Err(e) = old_settings.save() {
-        error!("Failed to save window state: {e}");
-    }
-    Task::none()
-}
-
-fn handle_reset_advanced_settings(app: &mut DataViewer) {
-    use crate::config;
-
-    app.show_fps = false;
-    app.show_footer = true;
-    app.is_horizontal_split = false;
-    app.synced_zoom = true;
-    app.mouse_wheel_zoom = false;
-    app.cache_strategy = CacheStrategy::Gpu;
-    app.compression_strategy = CompressionStrategy::None;
-    app.is_slider_dual = false;
-
-    app.settings.advanced_input.insert("cache_size".to_string(), config::DEFAULT_CACHE_SIZE.to_string());
-    app.settings.advanced_input.insert("max_loading_queue_size".to_string(), config::DEFAULT_MAX_LOADING_QUEUE_SIZE.to_string());
-    app.settings.advanced_input.insert("max_being_loaded_queue_size".to_string(), config::DEFAULT_MAX_BEING_LOADED_QUEUE_SIZE.to_string());
-    app.settings.advanced_input.insert("window_width".to_string(), config::DEFAULT_WINDOW_WIDTH.to_string());
-    app.settings.advanced_input.insert("window_height".to_string(), config::DEFAULT_WINDOW_HEIGHT.to_string());
-    app.settings.advanced_input.insert("atlas_size".to_string(), config::DEFAULT_ATLAS_SIZE.to_string());
-    app.settings.advanced_input.insert("double_click_threshold_ms".to_string(), config::DEFAULT_DOUBLE_CLICK_THRESHOLD_MS.to_string());
-    app.settings.advanced_input.insert("archive_cache_size".to_string(), config::DEFAULT_ARCHIVE_CACHE_SIZE.to_string());
-    app.settings.advanced_input.insert("archive_warning_threshold_mb".to_string(), config::DEFAULT_ARCHIVE_WARNING_THRESHOLD_MB.to_string());
-}
-
-fn handle_export_all_logs() {
-    println!("DEBUG: ExportAllLogs message received");
-    let app_name = "viewskater";
-    if let Some(log_buffer) = crate::get_shared_log_buffer() {
-        println!("DEBUG: Got log buffer, starting export...");
-        if let Some(stdout_buffer) = crate::get_shared_stdout_buffer() {
-            println!("DEBUG: Got stdout buffer, calling export_and_open_all_logs...");
-            crate::logging::export_and_open_all_logs(app_name, log_buffer, stdout_buffer);
-            println!("DEBUG: export_and_open_all_logs completed");
-        } else {
-            println!("DEBUG: Stdout buffer not available, exporting debug logs only");
-            match crate::logging::export_debug_logs(app_name, log_buffer) {
-                Ok(debug_log_path) => {
-                    println!("DEBUG: Export successful to: {}", debug_log_path.display());
-                    info!("Debug logs successfully exported to: {}", debug_log_path.display());
-                }
-                Err(e) => {
-                    println!("DEBUG: Export failed: {}", e);
-                    error!("Failed to export debug logs: {}", e);
-                    eprintln!("Failed to export debug logs: {}", e);
-                }
-            }
-        }
-        println!("DEBUG: Export operation completed");
-    } else {
-        println!("DEBUG: Log buffer not available");
-        warn!("Log buffer not available for export");
-    }
-    println!("DEBUG: ExportAllLogs handler finished");
-}
-
-pub fn handle_save_image(app: &mut DataViewer, message: Message) -> Task<Message> {
-    let current_image = &app.panes.first().as_ref().unwrap().current_image;
-
-    if current_image.len() == 0 {
-        Task::none()
-    } else {
-        match message {
-            Message::ReadySaveImage(result) => {
-                match result {
-                    Ok(path) => {
-                        let format = path
-                            .extension()
-                            .and_then(image::ImageFormat::from_extension);
-
-                        if let Some(format) = format {
-                            let (width, height) = current_image.dimensions();
-
-                            let save_result = match current_image {
-                                CachedData::Cpu(items) => {
-                                    match decode_with_exif_orientation(items) {
-                                        Ok(image) => image.save_with_format(path, format),
-                                        Err(e) => Err(std::io::Error::from(e).into()),
-                                    }
-                                },
-                                CachedData::Gpu(texture) => {
-                                    let texture = texture.clone();
-                                    let buf=  extract_gpu_image(app, &texture);
-
-                                    match format {
-                                        image::ImageFormat::Jpeg => {
-                                            let rgb: Vec<u8> = buf
-                                                .chunks_exact(4)
-                                                .flat_map(|p| [p[0], p[1], p[2]])
-                                                .collect();
-                                            image::save_buffer_with_format(&path, &rgb, width, height, image::ColorType::Rgb8, format)
-                                            }
-                                            _ => {
-                                            image::save_buffer_with_format(&path, &buf, width, height, image::ColorType::Rgba8, format)
-                                            }
-                                     }
-                                }
-                                CachedData::BC1(_texture) => {
-                                    app.set_failure_save_modal(Some("BC1 Saving is currently unsupported".into()));
-                                    return Task::none()
-                                },
-                            };
-
-                            match save_result {
-                                Ok(_) => app.toggle_success_save_modal(),
-                                Err(e) => app.set_failure_save_modal(Some(e.to_string())),
-                            }
-
-                            Task::none()
-                        } else {
-                            app.set_failure_save_modal(Some(
-                                "Wrong file extension, cannot determine format".into(),
-                            ));
-
-                            Task::none()
-                        }
-
-                    }
-
-                    Err(err) => {
-                        if let file_io::Error::InvalidExtension = err {
-                            app.set_failure_save_modal(Some("Error selecting save file - invalid extension".into()));
-                        }
-
-                        debug!("Save file select error: {:?}", err);
-                        Task::none()
-                    }
-                }
-            }
-
-            Message::RequestSaveImage => Task::perform(file_io::pick_save_file(), move |result| {
-                Message::ReadySaveImage(result)
-            }),
-
-            _ => Task::none(),
-        }
-    }
-}
@@ -0,0 +1,473 @@
+//! Opening folders and files, loading images into the panes, and saving them
+
+use std::path::PathBuf;
+use log::{debug, warn, error};
+use iced_winit::runtime::Task;
+use iced_runtime::clipboard;
+
+use crate::app::{DataViewer, Message, IoMessage};
+use crate::cache::img_cache::{CachedData, LoadOperation, LoadOperationType};
+use crate::exif_utils::decode_with_exif_orientation;
+use crate::utils::save::extract_gpu_image;
+use crate::file_io;
+use crate::loading_handler;
+use crate::menu::PaneLayout;
+use crate::pane::{IMAGE_RENDER_TIMES, IMAGE_RENDER_FPS};
+use crate::widgets::shader::{scene::Scene, cpu_scene::CpuScene};
+
+/// Routes file, image loading and save messages
+pub fn handle_io_message(app: &mut DataViewer, msg: IoMessage) -> Task<Message> {
+    match msg {
+        IoMessage::OpenFolder(pane_index) => {
+            Task::perform(file_io::pick_folder(), move |result| {
+                Message::Io(IoMessage::FolderOpened(result, pane_index))
+            })
+        }
+        IoMessage::OpenFile(pane_index) => {
+            Task::perform(file_io::pick_file(), move |result| {
+                Message::Io(IoMessage::FolderOpened(result, pane_index))
+            })
+        }
+        IoMessage::FileDropped(pane_index, dropped_path) => {
+            handle_file_dropped(app, pane_index, dropped_path)
+        }
+        IoMessage::Close => {
+            app.reset_state(-1);
+            debug!("directory_path: {:?}", app.directory_path);
+            debug!("self.current_image_index: {}", app.current_image_index);
+            for pane in app.panes.iter_mut() {
+                let img_cache = &mut pane.img_cache;
+                debug!("img_cache.current_index: {}", img_cache.current_index);
+                debug!("img_cache.image_paths.len(): {}", img_cache.image_paths.len());
+            }
+            Task::none()
+        }
+        IoMessage::FolderOpened(result, pane_index) => {
+            match result {
+                Ok(dir) => {
+                    debug!("Folder opened: {}", dir);
+                    if pane_index > 0 && app.pane_layout == PaneLayout::SinglePane {
+                        debug!("Ignoring request to open folder in pane {} while in single-pane mode", pane_index);
+                        Task::none()
+                    } else {
+                        app.initialize_dir_path(&PathBuf::from(dir), pane_index)
+                    }
+                }
+                Err(err) => {
+                    debug!("Folder open failed: {:?}", err);
+                    Task::none()
+                }
+            }
+        }
+        IoMessage::SwapRawPair => {
+            // Reopening the folder at the other file keeps that one in the listing
+            let pane_index = app.active_pane_index();
+            let other = app.panes.get(pane_index).and_then(|pane| {
+                let current = pane.img_cache.image_paths.get(pane.img_cache.current_index)?;
+                pane.raw_pairs.get(current.path()).cloned()
+            });
+            match other {
+                Some(other) => app.initialize_dir_path(&other, pane_index),
+                None => Task::none(),
+            }
+        }
+        IoMessage::DirectoryListed(pane_index, listing_id, event) => {
+            use crate::app::{DirectoryEnumError, ListingEvent};
+            if app.panes.get(pane_index).map(|pane| pane.listing_id) != Some(listing_id) {
+                debug!("Ignoring listing {} for pane {}, which has been reopened since", listing_id, pane_index);
+                return Task::none();
+            }
+            match event {
+                ListingEvent::Partial(enum_result) => {
+                    debug!("Directory still listing: opening on the first {} images", enum_result.file_paths.len());
+                    let found = enum_result.file_paths.len();
+                    let task = app.complete_dir_initialization(enum_result, pane_index);
+                    app.panes[pane_index].indexing = Some(found);
+                    task
+                }
+                ListingEvent::Progress(found) => {
+                    app.panes[pane_index].indexing = Some(found);
+                    Task::none()
+                }
+                ListingEvent::Complete(Ok(mut enum_result)) => {
+                    debug!("Directory enumerated: {} images found", enum_result.file_paths.len());
+                    // A folder opened on its first images stays on the image it shows now
+                    let pane = &mut app.panes[pane_index];
+                    if pane.indexing.take().is_some() {
+                        // Both listings are sorted, so a binary search finds it without reading every page
+                        if let Some(current) = pane.img_cache.image_paths.get(pane.img_cache.current_index) {
+                            if let Ok(index) = enum_result.file_paths
+                                .binary_search_by(|source| alphanumeric_sort::compare_path(source.path(), current.path()))
+                            {
+                                enum_result.initial_index = index;
+                            }
+                        }
+                    }
+                    let pane_label = (app.pane_layout == PaneLayout::DualPane).then(|| format!("Pane {}", pane_index + 1));
+                    let notice = app.folder_stats.report(pane_label.as_deref(), enum_result.file_paths.len(), &enum_result.skipped);
+                    Task::batch([app.complete_dir_initialization(enum_result, pane_index), notice])
+                }
+                ListingEvent::Complete(Err(e)) => {
+                    app.panes[pane_index].indexing = None;
+                    match e {
+                        DirectoryEnumError::NoImagesFound => error!("No supported images found in directory"),
+                        DirectoryEnumError::DirectoryError(e) => error!("Directory enumeration error: {}", e),
+                        // Read again through the stored bookmark, which asks for access if there is none
+                        #[cfg(target_os = "macos")]
+                        DirectoryEnumError::AccessDenied(path) => return app.initialize_dir_path_sync(&path, pane_index),
+                        #[cfg(not(target_os = "macos"))]
+                        DirectoryEnumError::AccessDenied(path) => error!("Access denied to the folder of {}", path.display()),
+                        DirectoryEnumError::NotFound => error!("Path not found"),
+                    }
+                    Task::none()
+                }
+            }
+        }
+        IoMessage::CopyFilename(pane_index) => {
+            let path = &app.panes[pane_index].img_cache.image_paths[app.panes[pane_index].img_cache.current_index];
+            let filename_str = path.file_name().to_string();
+            if let Some(filename) = file_io::get_filename(&filename_str) {
+                debug!("Copying filename to clipboard: {}", filename);
+                return clipboard::write(filename);
+            }
+            Task::none()
+        }
+        IoMessage::CopyFilePath(pane_index) => {
+            let path = &app.panes[pane_index].img_cache.image_paths[app.panes[pane_index].img_cache.current_index];
+            let img_path = path.file_name().to_string();
+            if let Some(dir_path) = app.panes[pane_index].directory_path.as_ref() {
+                let full_path = PathBuf::from(dir_path).join(img_path);
+                debug!("Copying full path to clipboard: {}", full_path.display());
+                return clipboard::write(full_path.to_string_lossy().to_string());
+            }
+            Task::none()
+        }
+        IoMessage::CopyImage(pane_index) => {
+            let cache = &app.panes[pane_index].img_cache;
+            // Try CPU cache first, fall back to reading from disk for GPU-cached images
+            let bytes = if let Ok(cached_data) = cache.get_current_image() {
+                cached_data.as_vec().ok()
+            } else {
+                None
+            };
+            let bytes = bytes.or_else(|| {
+                let path_source = &cache.image_paths[cache.current_index];
+                match path_source {
+                    crate::cache::img_cache::PathSource::Filesystem(path) => {
+                        std::fs::read(path).ok()
+                    }
+                    _ => {
+                        error!("Cannot copy image: archive images require CPU cache");
+                        None
+                    }
+                }
+            });
+            if let Some(bytes) = bytes {
+                std::thread::spawn(move || {
+                    match image::load_from_memory(&bytes) {
+                        Ok(img) => {
+                            let rgba = img.to_rgba8();
+                            let (w, h) = rgba.dimensions();
+                            let img_data = arboard::ImageData {
+                                width: w as usize,
+                                height: h as usize,
+                                bytes: std::borrow::Cow::Owned(rgba.into_raw()),
+                            };
+                            match arboard::Clipboard::new() {
+                                Ok(mut clip) => {
+                                    if let Err(e) = clip.set_image(img_data) {
+                                        error!("Failed to copy image to clipboard: {}", e);
+                                    } else {
+                                        debug!("Image copied to clipboard ({}x{})", w, h);
+                                    }
+                                }
+                                Err(e) => error!("Failed to open clipboard: {}", e),
+                            }
+                        }
+                        Err(e) => error!("Failed to decode image for clipboard: {}", e),
+                    }
+                });
+            }
+            Task::none()
+        }
+        IoMessage::ImagesLoaded(result) => {
+            debug!("ImagesLoaded");
+            match result {
+                Ok((image_data, metadata, operation)) => {
+                    if let Some(op) = operation {
+                        let cloned_op = op.clone();
+                        match op {
+                            LoadOperation::LoadNext((ref pane_indices, ref target_indices))
+                            | LoadOperation::LoadPrevious((ref pane_indices, ref target_indices))
+                            | LoadOperation::ShiftNext((ref pane_indices, ref target_indices))
+                            | LoadOperation::ShiftPrevious((ref pane_indices, ref target_indices)) => {
+                                let operation_type = cloned_op.operation_type();
+
+                                loading_handler::handle_load_operation_all(
+                                    &mut app.panes,
+                                    &mut app.loading_status,
+                                    pane_indices,
+                                    target_indices,
+                                    &image_data,
+                                    &metadata,
+                                    &cloned_op,
+                                    operation_type,
+                                );
+
+                                // Clear loading timer for the panes that completed
+                                // (clear per-pane, not based on global queue state)
+                                for &pane_idx in pane_indices {
+                                    if let Some(pane) = app.panes.get_mut(pane_idx) {
+                                        pane.loading_started_at = None;
+                                    }
+                                }
+                            }
+                            LoadOperation::LoadPos((pane_index, target_indices_and_cache)) => {
+                                loading_handler::handle_load_pos_operation(
+                                    &mut app.panes,
+                                    &mut app.loading_status,
+                                    pane_index,
+                                    &target_indices_and_cache,
+                                    &image_data,
+                                    &metadata,
+                                );
+
+                                // Clear loading timer for this pane
+                                if let Some(pane) = app.panes.get_mut(pane_index) {
+                                    pane.loading_started_at = None;
+                                }
+                                if !app.loading_status.is_operation_in_queues(LoadOperationType::LoadPos) {
+                                    crate::bulk_load::finish();
+                                }
+
+                                // Signal replay controller that initial load is complete
+                                if let Some(ref mut replay_controller) = app.replay_controller {
+                                    if matches!(replay_controller.state, crate::replay::ReplayState::WaitingForReady { .. }) {
+                                        debug!("LoadPos complete - signaling replay controller that app is ready to navigate");
+
+                                        // Set image count for slider mode navigation
+                                        if let Some(pane) = app.panes.get(pane_index) {
+                                            replay_controller.set_image_count(pane.img_cache.image_paths.len());
+                                        }
+
+                                        // Reset FPS trackers right before navigation starts
+                                        // This ensures no stale data from image loading contaminates metrics
+                                        if let Ok(mut fps) = crate::CURRENT_FPS.lock() { *fps = 0.0; }
+                                        if let Ok(mut fps) = IMAGE_RENDER_FPS.lock() { *fps = 0.0; }
+                                        if let Ok(mut times) = crate::FRAME_TIMES.lock() { times.clear(); }
+                                        if let Ok(mut times) = IMAGE_RENDER_TIMES.lock() { times.clear(); }
+                                        iced_wgpu::reset_image_fps();
+
+                                        replay_controller.on_ready_to_navigate();
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                Err(err) => {
+                    debug!("Image load failed: {:?}", err);
+                }
+            }
+            Task::none()
+        }
+        IoMessage::PrefetchFiltered(pane_index, targets) => {
+            loading_handler::prefetch_filtered(
+                &app.device,
+                &app.queue,
+                app.cache_strategy,
+                app.compression_strategy,
+                &mut app.panes,
+                pane_index,
+                &targets,
+            )
+        }
+        IoMessage::FilteredPrefetchLoaded(pane_index, targets, result) => {
+            loading_handler::handle_filtered_prefetch_loaded(&mut app.panes, pane_index, targets, result);
+            Task::none()
+        }
+        IoMessage::ProgressiveDecode(pane_index, id, event) => {
+            if let Some(pane) = app.panes.get_mut(pane_index) {
+                crate::cache::progressive::handle_event(&mut pane.progressive, id, event, &app.queue);
+            }
+            Task::none()
+        }
+        IoMessage::StdinList(event) => crate::stdin_input::handle_event(app, event),
+        IoMessage::SliderImageWidgetLoaded(result) => {
+            match result {
+                Ok((pane_idx, pos, handle, dimensions, file_size)) => {
+                    crate::track_async_delivery();
+
+                    if let Some(pane) = app.panes.get_mut(pane_idx) {
+                        pane.slider_image = Some(handle);
+                        pane.slider_image_dimensions = Some(dimensions);
+                        pane.slider_image_position = Some(pos);
+                        // Update metadata for footer display during slider dragging
+                        pane.current_image_metadata = Some(crate::cache::img_cache::ImageMetadata::new(
+                            dimensions.0, dimensions.1, file_size
+                        ));
+                        // BUGFIX: Don't update current_index here! It causes desyncs when stale slider images
+                        // load after slider release. The slider position is tracked in slider_image_position instead.
+                        // pane.img_cache.current_index = pos;
+
+                        debug!("Slider image loaded for pane {} at position {} with dimensions {:?}", pane_idx, pos, dimensions);
+                    } else {
+                        warn!("SliderImageWidgetLoaded: Invalid pane index {}", pane_idx);
+                    }
+                },
+                Err((pane_idx, pos)) => {
+                    warn!("SLIDER: Failed to load image widget for pane {} at position {}", pane_idx, pos);
+                }
+            }
+            Task::none()
+        }
+        IoMessage::SliderImageLoaded(result) => {
+            match result {
+                Ok((pos, cached_data)) => {
+                    let pane = &mut app.panes[0];
+
+                    if let CachedData::Cpu(bytes) = &cached_data {
+                        debug!("SliderImageLoaded: loaded data: {:?}", bytes.len());
+
+                        pane.current_image = CachedData::Cpu(bytes.clone());
+                        pane.current_image_index = Some(pos);
+                        pane.slider_scene = Some(Scene::CpuScene(CpuScene::new(
+                            bytes.clone(), true)));
+
+                        if let Some(device) = &pane.device {
+                            if let Some(queue) = &pane.queue {
+                                if let Some(scene) = &mut pane.slider_scene {
+                                    scene.ensure_texture(device, queue, pane.pane_id);
+                                }
+                            }
+                        }
+                    }
+                },
+                Err(pos) => {
+                    warn!("SLIDER: Failed to load image for position {}", pos);
+                }
+            }
+            Task::none()
+        }
+        IoMessage::RequestSaveImage => {
+            if app.panes[0].current_image.len() == 0 {
+                return Task::none();
+            }
+            Task::perform(file_io::pick_save_file(), move |result| {
+                Message::Io(IoMessage::ReadySaveImage(result))
+            })
+        }
+        IoMessage::ReadySaveImage(result) => save_image(app, result),
+    }
+}
+
+fn handle_file_dropped(app: &mut DataViewer, pane_index: isize, dropped_path: String) -> Task<Message> {
+    let path = PathBuf::from(&dropped_path);
+
+    if crate::session::is_session_file(&path) {
+        return Task::done(crate::session::SessionMessage::OpenFrom(path).into());
+    }
+
+    #[cfg(feature = "coco")]
+    debug!("COCO FEATURE IS ENABLED");
+    #[cfg(not(feature = "coco"))]
+    debug!("COCO FEATURE IS DISABLED");
+
+    #[cfg(feature = "coco")]
+    if path.extension().and_then(|s| s.to_str()) == Some("json") {
+        debug!("JSON file detected, checking if it's COCO format: {}", path.display());
+        match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                if crate::coco::parser::CocoDataset::is_coco_format(&content) {
+                    log::info!("✓ Detected COCO JSON file: {}", path.display());
+                    return Task::none();
+                } else {
+                    debug!("JSON file is not COCO format, treating as regular file");
+                }
+            }
+            Err(e) => {
+                warn!("Failed to read JSON file: {}", e);
+            }
+        }
+    }
+
+    debug!("IoMessage::FileDropped - Resetting state");
+    app.reset_state(pane_index);
+
+    debug!("File dropped: {:?}, pane_index: {}", dropped_path, pane_index);
+    debug!("self.dir_loaded, pane_index, last_opened_pane: {:?}, {}, {}",
+        app.panes[pane_index as usize].dir_loaded, pane_index, app.last_opened_pane);
+    app.initialize_dir_path(&path, pane_index as usize)
+}
+
+fn save_image(app: &mut DataViewer, result: Result<PathBuf, file_io::Error>) -> Task<Message> {
+    let current_image = &app.panes.first().as_ref().unwrap().current_image;
+    if current_image.len() == 0 {
+        return Task::none();
+    }
+
+    match result {
+        Ok(path) => {
+            let format = path
+                .extension()
+                .and_then(image::ImageFormat::from_extension);
+
+            if let Some(format) = format {
+                let (width, height) = current_image.dimensions();
+
+                let save_result = match current_image {
+                    CachedData::Cpu(items) => {
+                        match decode_with_exif_orientation(items) {
+                            Ok(image) => image.save_with_format(path, format),
+                            Err(e) => Err(std::io::Error::from(e).into()),
+                        }
+                    },
+                    CachedData::Gpu(texture) => {
+                        let texture = texture.clone();
+                        let buf=  extract_gpu_image(app, &texture);
+
+                        match format {
+                            image::ImageFormat::Jpeg => {
+                                let rgb: Vec<u8> = buf
+                                    .chunks_exact(4)
+                                    .flat_map(|p| [p[0], p[1], p[2]])
+                                    .collect();
+                                image::save_buffer_with_format(&path, &rgb, width, height, image::ColorType::Rgb8, format)
+                                }
+                                _ => {
+                                image::save_buffer_with_format(&path, &buf, width, height, image::ColorType::Rgba8, format)
+                                }
+                         }
+                    }
+                    CachedData::BC1(_texture) => {
+                        app.set_failure_save_modal(Some("BC1 Saving is currently unsupported".into()));
+                        return Task::none()
+                    },
+                };
+
+                match save_result {
+                    Ok(_) => app.toggle_success_save_modal(),
+                    Err(e) => app.set_failure_save_modal(Some(e.to_string())),
+                }
+
+                Task::none()
+            } else {
+                app.set_failure_save_modal(Some(
+                    "Wrong file extension, cannot determine format".into(),
+                ));
+
+                Task::none()
+            }
+
+        }
+
+        Err(err) => {
+            if let file_io::Error::InvalidExtension = err {
+                app.set_failure_save_modal(Some("Error selecting save file - invalid extension".into()));
+            }
+
+            debug!("Save file select error: {:?}", err);
+            Task::none()
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Slider, jump and pane offset navigation
+
+use std::time::Instant;
+use log::{debug, info};
+use iced_winit::runtime::Task;
+
+use crate::app::{DataViewer, Message, NavigationMessage};
+use crate::menu::PaneLayout;
+use crate::navigation_slider;
+use crate::pane::{IMAGE_RENDER_TIMES, IMAGE_RENDER_FPS};
+
+pub fn handle_navigation_message(app: &mut DataViewer, msg: NavigationMessage) -> Task<Message> {
+    match msg {
+        NavigationMessage::SliderChanged(pane_index, value) => {
+            app.is_slider_moving = true;
+            app.use_slider_image_for_render = true;
+            app.last_slider_update = Instant::now();
+
+            // Reset COCO zoom state when slider starts moving
+            #[cfg(feature = "coco")]
+            {
+                if pane_index == -1 {
+                    // Reset all panes
+                    for pane in app.panes.iter_mut() {
+                        pane.zoom_scale = 1.0;
+                        pane.zoom_offset = iced_core::Vector::default();
+                    }
+                } else {
+                    // Reset specific pane
+                    if let Some(pane) = app.panes.get_mut(pane_index as usize) {
+                        pane.zoom_scale = 1.0;
+                        pane.zoom_offset = iced_core::Vector::default();
+                    }
+                }
+            }
+
+            let use_async = true;
+
+            #[cfg(target_os = "linux")]
+            let use_throttle = true;
+            #[cfg(not(target_os = "linux"))]
+            let use_throttle = false;
+
+            if pane_index == -1 {
+                app.prev_slider_value = app.slider_value;
+                app.slider_value = value;
+
+                if app.panes[0].slider_image.is_none() {
+                    for pane in app.panes.iter_mut() {
+                        pane.slider_scene = None;
+                    }
+                }
+            } else {
+                let pane_index_usize = pane_index as usize;
+
+                if app.is_slider_dual && app.pane_layout == PaneLayout::DualPane {
+                    for idx in 0..app.panes.len() {
+                        if idx != pane_index_usize {
+                            app.panes[idx].slider_image = None;
+                            app.panes[idx].slider_image_position = None;
+                        }
+                    }
+                }
+
+                let pane = &mut app.panes[pane_index_usize];
+                pane.prev_slider_value = pane.slider_value;
+                pane.slider_value = value;
+
+                if pane.slider_image.is_none() {
+                    pane.slider_scene = None;
+                }
+            }
+
+            navigation_slider::update_pos(
+                &mut app.panes,
+                pane_index,
+                value as usize,
+                use_async,
+                use_throttle,
+            )
+        }
+        NavigationMessage::SliderReleased(pane_index, value) => {
+            debug!("SLIDER_DEBUG: SliderReleased event received");
+            app.is_slider_moving = false;
+
+            let final_image_fps = iced_wgpu::get_image_fps();
+            let upload_timestamps = iced_wgpu::get_image_upload_timestamps();
+
+            if !upload_timestamps.is_empty() {
+                if let Ok(mut render_times) = IMAGE_RENDER_TIMES.lock() {
+                    *render_times = upload_timestamps.into_iter().collect();
+
+                    if let Ok(mut fps) = IMAGE_RENDER_FPS.lock() {
+                        *fps = final_image_fps as f32;
+                        debug!("SLIDER_DEBUG: Synced image fps tracking, final FPS: {:.1}", final_image_fps);
+                    }
+                }
+            }
+
+            // Use the position of the currently displayed slider_image if available,
+            // otherwise fall back to the slider value
+            let pos = if pane_index >= 0 {
+                app.panes.get(pane_index as usize)
+                    .and_then(|pane| pane.slider_image_position)
+                    .unwrap_or(value as usize)
+            } else {
+                // For pane_index == -1 (all panes), use slider_image_position from pane 0
+                app.panes.first()
+                    .and_then(|pane| pane.slider_image_position)
+                    .unwrap_or(value as usize)
+            };
+
+            debug!("SliderReleased: Using position {} (slider_image_position) instead of slider value {}", pos, value);
+            crate::navigation_history::record_jump(app, pane_index.max(0) as usize, pos);
+
+            navigation_slider::load_remaining_images(
+                &app.device,
+                &app.queue,
+                app.is_gpu_supported,
+                app.cache_strategy,
+                app.compression_strategy,
+                &mut app.panes,
+                &mut app.loading_status,
+                pane_index,
+                pos)
+        }
+        NavigationMessage::JumpToImage(pane_index, pos) => {
+            crate::navigation_history::record_jump(app, pane_index, pos);
+            app.jump_to_image(pane_index, pos)
+        }
+        NavigationMessage::SetPaneOffset(offset) => {
+            info!("Pane B offset set to {}", offset);
+            navigation_slider::set_pane_b_offset(offset);
+            // Pane A stays put, pane B moves to the new offset
+            let pos = app.panes[0].img_cache.current_index;
+            app.jump_to_image(0, pos)
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! About, settings and save-result dialogs, logs and web links
+
+use log::{info, warn, error};
+use iced_winit::runtime::Task;
+
+use crate::app::{DataViewer, Message, OverlayMessage};
+use crate::settings::UserSettings;
+
+pub fn handle_overlay_message(app: &mut DataViewer, msg: OverlayMessage) -> Task<Message> {
+    match msg {
+        OverlayMessage::ShowLogs => {
+            let app_name = "viewskater";
+            let log_dir_path = crate::logging::get_log_directory(app_name);
+            let _ = std::fs::create_dir_all(log_dir_path.clone());
+            crate::logging::open_in_file_explorer(log_dir_path.to_string_lossy().as_ref());
+            Task::none()
+        }
+        OverlayMessage::OpenSettingsDir => {
+            let settings_path = UserSettings::settings_path();
+            if let Some(settings_dir) = settings_path.parent() {
+                let _ = std::fs::create_dir_all(settings_dir);
+                crate::logging::open_in_file_explorer(settings_dir.to_string_lossy().as_ref());
+            }
+            Task::none()
+        }
+        OverlayMessage::ExportDebugLogs => {
+            let app_name = "viewskater";
+            if let Some(log_buffer) = crate::get_shared_log_buffer() {
+                crate::logging::export_and_open_debug_logs(app_name, log_buffer);
+            } else {
+                warn!("Log buffer not available for export");
+            }
+            Task::none()
+        }
+        OverlayMessage::ExportAllLogs => {
+            handle_export_all_logs();
+            Task::none()
+        }
+        OverlayMessage::ShowAbout => {
+            app.show_about = true;
+            Task::perform(async {
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }, |_| Message::Nothing)
+        }
+        OverlayMessage::HideAbout => {
+            app.show_about = false;
+            Task::none()
+        }
+        OverlayMessage::ShowOptions => {
+            app.settings.show();
+            #[cfg(target_os = "macos")]
+            if app.settings.active_tab == crate::bookmark_permissions::PERMISSIONS_TAB {
+                crate::bookmark_permissions::refresh(app);
+            }
+            Task::perform(async {
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }, |_| Message::Nothing)
+        }
+        OverlayMessage::HideOptions => {
+            app.settings.hide();
+            Task::none()
+        }
+        OverlayMessage::OpenWebLink(url) => {
+            if let Err(e) = webbrowser::open(&url) {
+                warn!("Failed to open link: {}, error: {:?}", url, e);
+            }
+            Task::none()
+        }
+        OverlayMessage::HideSuccessSaveModal => {
+            app.toggle_success_save_modal();
+            Task::none()
+        }
+        OverlayMessage::HideFailureSaveModal => {
+            app.set_failure_save_modal(None);
+            Task::none()
+        }
+    }
+}
+
+fn handle_export_all_logs() {
+    println!("DEBUG: ExportAllLogs message received");
+    let app_name = "viewskater";
+    if let Some(log_buffer) = crate::get_shared_log_buffer() {
+        println!("DEBUG: Got log buffer, starting export...");
+        if let Some(stdout_buffer) = crate::get_shared_stdout_buffer() {
+            println!("DEBUG: Got stdout buffer, calling export_and_open_all_logs...");
+            crate::logging::export_and_open_all_logs(app_name, log_buffer, stdout_buffer);
+            println!("DEBUG: export_and_open_all_logs completed");
+        } else {
+            println!("DEBUG: Stdout buffer not available, exporting debug logs only");
+            match crate::logging::export_debug_logs(app_name, log_buffer) {
+                Ok(debug_log_path) => {
+                    println!("DEBUG: Export successful to: {}", debug_log_path.display());
+                    info!("Debug logs successfully exported to: {}", debug_log_path.display());
+                }
+                Err(e) => {
+                    println!("DEBUG: Export failed: {}", e);
+                    error!("Failed to export debug logs: {}", e);
+                    eprintln!("Failed to export debug logs: {}", e);
+                }
+            }
+        }
+        println!("DEBUG: Export operation completed");
+    } else {
+        println!("DEBUG: Log buffer not available");
+        warn!("Log buffer not available for export");
+    }
+    println!("DEBUG: ExportAllLogs handler finished");
+}
@@ -0,0 +1,283 @@
+//! Pane layout, display toggles and window geometry
+
+use std::path::PathBuf;
+use log::{debug, warn};
+use iced_winit::runtime::Task;
+use iced_wgpu::engine::CompressionStrategy;
+
+use crate::app::{DataViewer, Message, PaneMessage};
+use crate::settings::WindowState;
+use crate::widgets::shader::stereo_scene::StereoMode;
+
+pub fn handle_pane_message(app: &mut DataViewer, msg: PaneMessage) -> Task<Message> {
+    match msg {
+        PaneMessage::OnSplitResize(position) => {
+            app.divider_position = Some(position);
+            Task::none()
+        }
+        PaneMessage::ResetSplit(_position) => {
+            app.divider_position = None;
+            Task::none()
+        }
+        PaneMessage::ToggleSliderType(_bool) => {
+            app.toggle_slider_type();
+            Task::none()
+        }
+        PaneMessage::TogglePaneLayout(pane_layout) => {
+            app.toggle_pane_layout(pane_layout);
+            Task::none()
+        }
+        PaneMessage::ToggleFooter(_bool) => {
+            app.toggle_footer();
+            Task::none()
+        }
+        PaneMessage::ToggleSyncedZoom(enabled) => {
+            app.synced_zoom = enabled;
+            Task::none()
+        }
+        PaneMessage::SetStereoMode(mode) => {
+            app.stereo_mode = mode;
+            if mode != StereoMode::Off {
+                app.blend_compare = false;
+                app.ensure_paired_panes();
+            }
+            Task::none()
+        }
+        PaneMessage::ToggleBlendCompare(enabled) => {
+            app.blend_compare = enabled;
+            if enabled {
+                app.stereo_mode = StereoMode::Off;
+                app.ensure_paired_panes();
+            }
+            Task::none()
+        }
+        PaneMessage::SetBlendOpacity(opacity) => {
+            app.blend_opacity = opacity.clamp(0.0, 1.0);
+            Task::none()
+        }
+        PaneMessage::ToggleMouseWheelZoom(enabled) => {
+            app.mouse_wheel_zoom = enabled;
+            for pane in app.panes.iter_mut() {
+                pane.mouse_wheel_zoom = enabled;
+            }
+            Task::none()
+        }
+        PaneMessage::ToggleZoomLock(enabled) => {
+            if enabled != crate::zoom_lock::is_active() {
+                crate::zoom_lock::toggle();
+            }
+            Task::none()
+        }
+        PaneMessage::ToggleClippingWarning(enabled) => {
+            crate::widgets::shader::clipping_warning::set_enabled(enabled);
+            Task::none()
+        }
+        PaneMessage::ToggleCopyButtons(enabled) => {
+            app.show_copy_buttons = enabled;
+            Task::none()
+        }
+        PaneMessage::ToggleMetadataDisplay(enabled) => {
+            app.show_metadata = enabled;
+            Task::none()
+        }
+        PaneMessage::ToggleNearestNeighborFilter(enabled) => {
+            debug!("ToggleNearestNeighborFilter: setting to {}", enabled);
+            app.nearest_neighbor_filter = enabled;
+
+            // Force reload of current directories to apply the new filter immediately
+            let mut tasks = Vec::new();
+            for pane_index in 0..app.panes.len() {
+                if let Some(dir_path) = app.panes[pane_index].directory_path.clone() {
+                    debug!("Reloading directory for pane {}: {:?}", pane_index, dir_path);
+                    tasks.push(app.initialize_dir_path(&PathBuf::from(dir_path), pane_index));
+                }
+            }
+
+            Task::batch(tasks)
+        }
+        PaneMessage::ToggleRawPairing(enabled) => {
+            crate::raw_pairs::set_enabled(enabled);
+
+            // Relist open folders, staying on the image each pane shows
+            let mut tasks = Vec::new();
+            for pane_index in 0..app.panes.len() {
+                let pane = &app.panes[pane_index];
+                if !pane.dir_loaded || pane.has_compressed_file {
+                    continue;
+                }
+                if let Some(current) = pane.img_cache.image_paths.get(pane.img_cache.current_index) {
+                    let current = current.path().clone();
+                    tasks.push(app.initialize_dir_path(&current, pane_index));
+                }
+            }
+            Task::batch(tasks)
+        }
+        PaneMessage::SetSpinnerLocation(location) => {
+            debug!("SetSpinnerLocation: setting to {:?}", location);
+            app.spinner_location = location;
+            Task::none()
+        }
+        #[cfg(feature = "coco")]
+        PaneMessage::ToggleCocoSimplification(enabled) => {
+            app.coco_disable_simplification = enabled;
+            Task::none()
+        }
+        #[cfg(feature = "coco")]
+        PaneMessage::SetCocoMaskRenderMode(mode) => {
+            app.coco_mask_render_mode = mode;
+            Task::none()
+        }
+        #[cfg(feature = "coco")]
+        PaneMessage::ToggleCocoLabels(enabled) => {
+            app.coco_show_labels = enabled;
+            Task::none()
+        }
+        #[cfg(feature = "coco")]
+        PaneMessage::SetCocoLabelSize(size) => {
+            app.coco_label_size = size;
+            Task::none()
+        }
+        #[cfg(feature = "coco")]
+        PaneMessage::SetCocoCropPadding(padding) => {
+            app.coco_crop_padding = padding;
+            Task::none()
+        }
+        #[cfg(feature = "coco")]
+        PaneMessage::SetCocoCropSize(size) => {
+            app.coco_crop_size = size;
+            Task::none()
+        }
+        PaneMessage::ToggleFullScreen(enabled) => {
+            if enabled {
+                app.window_state = WindowState::FullScreen;
+            } else {
+                app.window_state = WindowState::Window;
+            }
+            Task::none()
+        }
+        PaneMessage::ToggleFpsDisplay(value) => {
+            app.show_fps = value;
+            Task::none()
+        }
+        PaneMessage::ToggleSplitOrientation(_bool) => {
+            app.toggle_split_orientation();
+            Task::none()
+        }
+        PaneMessage::CursorOnTop(value) => {
+            app.cursor_on_top = value;
+            Task::none()
+        }
+        PaneMessage::CursorOnMenu(value) => {
+            app.cursor_on_menu = value;
+            Task::none()
+        }
+        PaneMessage::CursorOnFooter(value) => {
+            app.cursor_on_footer = value;
+            Task::none()
+        }
+        PaneMessage::PaneSelected(pane_index, is_selected) => {
+            app.panes[pane_index].is_selected = is_selected;
+            for (index, pane) in app.panes.iter_mut().enumerate() {
+                debug!("pane_index: {}, is_selected: {}", index, pane.is_selected);
+            }
+            Task::none()
+        }
+        PaneMessage::SetCacheStrategy(strategy) => {
+            app.source_strategies.set_cache(app.active_source, strategy);
+            app.update_cache_strategy(strategy);
+            Task::none()
+        }
+        PaneMessage::SetCompressionStrategy(strategy) => {
+            if crate::safe_mode::is_enabled() && !matches!(strategy, CompressionStrategy::None) {
+                warn!("Compression stays off in safe mode");
+                return Task::none();
+            }
+            app.source_strategies.set_compression(app.active_source, strategy);
+            app.update_compression_strategy(strategy);
+            Task::none()
+        }
+        PaneMessage::WindowResized(width, size, is_maximized) => {
+            app.window_width = width;
+            app.window_size = size;
+            crate::display_decode::set_viewport(size.width, size.height, size.width as f32 / width);
+
+            // Track the largest size seen while maximized (used by Linux X11 un-maximize workaround)
+            if is_maximized {
+                let should_update = app.maximized_size.map_or(true, |max_size| {
+                    size.width > max_size.width || size.height > max_size.height
+                });
+                if should_update {
+                    app.maximized_size = Some(size);
+                }
+            }
+
+            // macOS: use is_maximized (winit's isZoomed() wrapper), same as other platforms.
+            // isZoomed() may be unreliable mid-animation, but save_window_state_to_disk
+            // queries it authoritatively post-animation as a safety net.
+            #[cfg(target_os = "macos")]
+            match app.window_state {
+                WindowState::Window => {
+                    if is_maximized {
+                        app.window_state = WindowState::Maximized;
+                        app.last_windowed_position = app.position_before_transition;
+                    }
+                },
+                WindowState::Maximized => {
+                    if !is_maximized {
+                        app.window_state = WindowState::Window;
+                    }
+                },
+                _ => {},
+            }
+
+            // Windows/Linux: use winit's is_maximized() (reliable on these platforms)
+            #[cfg(not(target_os = "macos"))]
+            match app.window_state {
+                WindowState::Window => {
+                    if is_maximized {
+                        app.window_state = WindowState::Maximized;
+                        // Windows workaround: PositionChanged(0,0) fires before this event,
+                        // corrupting last_windowed_position. Restore from backup.
+                        app.last_windowed_position = app.position_before_transition;
+                    }
+                },
+                WindowState::Maximized => {
+                    if !is_maximized {
+                        // Primary detection: is_maximized() returned false (works on Windows/macOS)
+                        app.window_state = WindowState::Window;
+                    } else {
+                        // X11 workaround: is_maximized() returns stale true during un-maximize transition
+                        // On X11, maximized windows cannot be resized - any size change means un-maximize
+                        #[cfg(target_os = "linux")]
+                        {
+                            let size_changed = app.maximized_size.map_or(false, |max_size| size != max_size);
+                            if size_changed {
+                                app.window_state = WindowState::Window;
+                                app.maximized_size = None;
+                            }
+                        }
+                    }
+                },
+                _ => {},
+            }
+
+            Task::none()
+        }
+        PaneMessage::PositionChanged(position, monitor) => {
+            app.window_position = position;
+            let is_same_monitor = app.last_monitor == monitor;
+            if !is_same_monitor {
+                crate::display_profile::select_for_monitor(monitor.as_ref().and_then(|m| m.name()));
+            }
+            // Only track last_windowed_position when in windowed state or moving across different monitors
+            // Save previous value first (Windows workaround: PositionChanged fires before WindowResized
+            // during maximize, so we need to be able to restore if transition is detected)
+            if app.window_state == WindowState::Window || !is_same_monitor {
+                app.position_before_transition = app.last_windowed_position;
+                app.last_windowed_position = position;
+                app.last_monitor = monitor;
+            }
+            Task::none()
+        }
+    }
+}
@@ -0,0 +1,545 @@
+//! Settings dialog, settings file and profile changes, and saving the window state
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use log::{info, error};
+use iced_winit::runtime::Task;
+use iced_wgpu::engine::CompressionStrategy;
+
+use crate::app::{DataViewer, Message, PaneMessage, SettingsMessage};
+use crate::cache::img_cache::CacheStrategy;
+use crate::cache::source_strategy::{self, SourceStrategies};
+use crate::settings::{UserSettings, WindowState};
+use crate::window_state::get_window_visible;
+
+pub fn handle_settings_message(app: &mut DataViewer, msg: SettingsMessage) -> Task<Message> {
+    match msg {
+        SettingsMessage::SaveWindowState => handle_save_window_state(app),
+        SettingsMessage::SaveSettings => handle_save_settings(app),
+        SettingsMessage::ClearSettingsStatus => {
+            app.settings.clear_save_status();
+            Task::none()
+        }
+        SettingsMessage::SettingsTabSelected(index) => {
+            app.settings.set_active_tab(index);
+            #[cfg(target_os = "macos")]
+            if index == crate::bookmark_permissions::PERMISSIONS_TAB {
+                crate::bookmark_permissions::refresh(app);
+            }
+            Task::none()
+        }
+        SettingsMessage::AdvancedSettingChanged(field_name, value) => {
+            app.settings.set_advanced_input(field_name, value);
+            Task::none()
+        }
+        SettingsMessage::ResetAdvancedSettings => {
+            handle_reset_advanced_settings(app);
+            Task::none()
+        }
+        SettingsMessage::SettingsFileChanged => {
+            match crate::settings_watch::read_watched() {
+                Some(settings) => apply_user_settings(app, crate::profiles::with_active_profile(settings)),
+                None => Task::none(),
+            }
+        }
+    }
+}
+
+fn handle_save_settings(app: &mut DataViewer) -> Task<Message> {
+    let parse_value = |key: &str, _default: u64| -> Result<u64, String> {
+        app.settings.advanced_input
+            .get(key)
+            .ok_or_else(|| format!("Missing value for {}", key))?
+            .parse::<u64>()
+            .map_err(|_| format!("Invalid number for {}", key))
+    };
+
+    let cache_size = match parse_value("cache_size", 5) {
+        Ok(v) if v > 0 && v <= 100 => v as usize,
+        Ok(_) => {
+            app.settings.set_save_status(Some("Error: Cache size must be between 1 and 100".to_string()));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+        Err(e) => {
+            app.settings.set_save_status(Some(format!("Error parsing cache_size: {}", e)));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+    };
+
+    let max_loading_queue_size = match parse_value("max_loading_queue_size", 3) {
+        Ok(v) if v > 0 && v <= 50 => v as usize,
+        Ok(_) => {
+            app.settings.set_save_status(Some("Error: Max loading queue size must be between 1 and 50".to_string()));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+        Err(e) => {
+            app.settings.set_save_status(Some(format!("Error parsing max_loading_queue_size: {}", e)));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+    };
+
+    let max_being_loaded_queue_size = match parse_value("max_being_loaded_queue_size", 3) {
+        Ok(v) if v > 0 && v <= 50 => v as usize,
+        Ok(_) => {
+            app.settings.set_save_status(Some("Error: Max being loaded queue size must be between 1 and 50".to_string()));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+        Err(e) => {
+            app.settings.set_save_status(Some(format!("Error parsing max_being_loaded_queue_size: {}", e)));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+    };
+
+    let atlas_size = match parse_value("atlas_size", 2048) {
+        Ok(v) if (256..=8192).contains(&v) && v.is_power_of_two() => v as u32,
+        Ok(_) => {
+            app.settings.set_save_status(Some("Error: Atlas size must be a power of 2 between 256 and 8192".to_string()));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+        Err(e) => {
+            app.settings.set_save_status(Some(format!("Error parsing atlas_size: {}", e)));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+    };
+
+    let double_click_threshold_ms = match parse_value("double_click_threshold_ms", 250) {
+        Ok(v) if (50..=1000).contains(&v) => v as u16,
+        Ok(_) => {
+            app.settings.set_save_status(Some("Error: Double-click threshold must be between 50 and 1000 ms".to_string()));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+        Err(e) => {
+            app.settings.set_save_status(Some(format!("Error parsing double_click_threshold_ms: {}", e)));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+    };
+
+    let archive_cache_size = match parse_value("archive_cache_size", 200) {
+        Ok(v) if (10..=10000).contains(&v) => v,
+        Ok(_) => {
+            app.settings.set_save_status(Some("Error: Archive cache size must be between 10 and 10000 MB".to_string()));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+        Err(e) => {
+            app.settings.set_save_status(Some(format!("Error parsing archive_cache_size: {}", e)));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+    };
+
+    let archive_warning_threshold_mb = match parse_value("archive_warning_threshold_mb", 500) {
+        Ok(v) if (10..=10000).contains(&v) => v,
+        Ok(_) => {
+            app.settings.set_save_status(Some("Error: Archive warning threshold must be between 10 and 10000 MB".to_string()));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+        Err(e) => {
+            app.settings.set_save_status(Some(format!("Error parsing archive_warning_threshold_mb: {}", e)));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+    };
+
+    let directory_cache_mb = match parse_value("directory_cache_mb", 512) {
+        Ok(v) if v <= 65536 => v,
+        Ok(_) => {
+            app.settings.set_save_status(Some("Error: Recent directories cache must be between 0 and 65536 MB".to_string()));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+        Err(e) => {
+            app.settings.set_save_status(Some(format!("Error parsing directory_cache_mb: {}", e)));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+    };
+
+    let decode_threads = match parse_value("decode_threads", 4) {
+        Ok(v) if (1..=64).contains(&v) => v as usize,
+        Ok(_) => {
+            app.settings.set_save_status(Some("Error: Decode threads must be between 1 and 64".to_string()));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+        Err(e) => {
+            app.settings.set_save_status(Some(format!("Error parsing decode_threads: {}", e)));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+    };
+
+    let max_concurrent_reads = match parse_value("max_concurrent_reads", 4) {
+        Ok(v) if (1..=64).contains(&v) => v as usize,
+        Ok(_) => {
+            app.settings.set_save_status(Some("Error: Max concurrent file reads must be between 1 and 64".to_string()));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+        Err(e) => {
+            app.settings.set_save_status(Some(format!("Error parsing max_concurrent_reads: {}", e)));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+    };
+
+    let load_timeout_secs = match parse_value("load_timeout_secs", 30) {
+        Ok(v) if v <= 3600 => v,
+        Ok(_) => {
+            app.settings.set_save_status(Some("Error: Load timeout must be between 0 and 3600 seconds".to_string()));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+        Err(e) => {
+            app.settings.set_save_status(Some(format!("Error parsing load_timeout_secs: {}", e)));
+            return Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus));
+        }
+    };
+
+    let settings = UserSettings {
+        show_fps: app.show_fps,
+        show_footer: app.show_footer,
+        is_horizontal_split: app.is_horizontal_split,
+        synced_zoom: app.synced_zoom,
+        mouse_wheel_zoom: app.mouse_wheel_zoom,
+        show_copy_buttons: app.show_copy_buttons,
+        show_metadata: app.show_metadata,
+        nearest_neighbor_filter: app.nearest_neighbor_filter,
+        cache_strategy: match app.source_strategies.local.0 {
+            CacheStrategy::Cpu => "cpu".to_string(),
+            CacheStrategy::Gpu => "gpu".to_string(),
+        },
+        compression_strategy: match app.source_strategies.local.1 {
+            CompressionStrategy::None => "none".to_string(),
+            CompressionStrategy::Bc1 => "bc1".to_string(),
+        },
+        archive_cache_strategy: source_strategy::cache_strategy_name(app.source_strategies.archive.cache).to_string(),
+        archive_compression_strategy: source_strategy::compression_strategy_name(app.source_strategies.archive.compression).to_string(),
+        remote_cache_strategy: source_strategy::cache_strategy_name(app.source_strategies.remote.cache).to_string(),
+        remote_compression_strategy: source_strategy::compression_strategy_name(app.source_strategies.remote.compression).to_string(),
+        is_slider_dual: app.is_slider_dual,
+        cache_size,
+        max_loading_queue_size,
+        max_being_loaded_queue_size,
+        window_width: app.window_size.width,
+        window_height: app.window_size.height,
+        atlas_size,
+        double_click_threshold_ms,
+        archive_cache_size,
+        archive_warning_threshold_mb,
+        directory_cache_mb,
+        decode_threads,
+        max_concurrent_reads,
+        load_timeout_secs,
+        follow_symlinks: crate::file_io::follows_symlinks(),
+        check_for_updates: app.check_for_updates,
+        confirm_delete: app.confirm_delete,
+        persist_viewed: app.viewed_images.persist,
+        load_for_display: crate::display_decode::is_enabled(),
+        convert_icc_profiles: crate::config::CONFIG.convert_icc_profiles,
+        #[cfg(feature = "coco")]
+        coco_disable_simplification: app.coco_disable_simplification,
+        #[cfg(not(feature = "coco"))]
+        coco_disable_simplification: false,
+        #[cfg(feature = "coco")]
+        coco_mask_render_mode: app.coco_mask_render_mode,
+        #[cfg(not(feature = "coco"))]
+        coco_mask_render_mode: crate::settings::CocoMaskRenderMode::default(),
+        #[cfg(feature = "coco")]
+        coco_show_labels: app.coco_show_labels,
+        #[cfg(not(feature = "coco"))]
+        coco_show_labels: true,
+        #[cfg(feature = "coco")]
+        coco_label_size: app.coco_label_size,
+        #[cfg(not(feature = "coco"))]
+        coco_label_size: 13.0,
+        #[cfg(feature = "coco")]
+        coco_crop_padding: app.coco_crop_padding,
+        #[cfg(not(feature = "coco"))]
+        coco_crop_padding: 0.0,
+        #[cfg(feature = "coco")]
+        coco_crop_size: app.coco_crop_size,
+        #[cfg(not(feature = "coco"))]
+        coco_crop_size: 0,
+        use_binary_size: app.use_binary_size,
+        spinner_location: app.spinner_location,
+        menu_bar_mode: app.menu_layout.mode,
+        hidden_menus: app.menu_layout.hidden.clone(),
+        window_state: app.window_state,
+        window_position_x: app.window_position.x,
+        window_position_y: app.window_position.y,
+    };
+
+    let old_settings = UserSettings::load(None);
+    let window_settings_changed = atlas_size != old_settings.atlas_size;
+
+    match settings.save() {
+        Ok(_) => {
+            info!("Settings saved successfully");
+
+            app.archive_cache_size = archive_cache_size * 1_048_576;
+            app.archive_warning_threshold_mb = archive_warning_threshold_mb;
+            info!("Archive settings applied immediately: cache_size={}MB, warning_threshold={}MB",
+                archive_cache_size, archive_warning_threshold_mb);
+            crate::cache::dir_cache::set_budget_mb(directory_cache_mb);
+            crate::cache::load_limits::set_limits(decode_threads, max_concurrent_reads);
+            crate::cache::load_watchdog::set_load_timeout(load_timeout_secs);
+
+            apply_cache_size(app, cache_size);
+            apply_queue_sizes(app, max_loading_queue_size, max_being_loaded_queue_size);
+
+            if double_click_threshold_ms != app.double_click_threshold_ms {
+                info!("Double-click threshold changed from {} to {} ms", app.double_click_threshold_ms, double_click_threshold_ms);
+                app.double_click_threshold_ms = double_click_threshold_ms;
+            }
+
+            app.settings.set_save_status(Some(if window_settings_changed {
+                "Settings saved! Window settings require restart, other changes applied immediately.".to_string()
+            } else {
+                "Settings saved! All changes applied immediately.".to_string()
+            }));
+
+            Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus))
+        }
+        Err(e) => {
+            error!("Failed to save settings: {}", e);
+            app.settings.set_save_status(Some(format!("Error: {}", e)));
+
+            Task::perform(async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            }, |_| Message::Settings(SettingsMessage::ClearSettingsStatus))
+        }
+    }
+}
+
+/// Use a new cache window size, reloading loaded panes when it changed
+pub(crate) fn apply_cache_size(app: &mut DataViewer, cache_size: usize) {
+    if cache_size == app.cache_size {
+        return;
+    }
+    info!("Cache size changed from {} to {}, reloading all panes", app.cache_size, cache_size);
+    app.cache_size = cache_size;
+
+    let pane_file_lengths: Vec<usize> = app.panes.iter()
+        .map(|p| p.img_cache.num_files)
+        .collect();
+
+    let cache_size = app.cache_size;
+    let archive_cache_size = app.archive_cache_size;
+    let archive_warning_threshold_mb = app.archive_warning_threshold_mb;
+
+    for (i, pane) in app.panes.iter_mut().enumerate() {
+        if let Some(dir_path) = &pane.directory_path.clone() {
+            if pane.dir_loaded {
+                let path = PathBuf::from(dir_path);
+
+                let _ = pane.initialize_dir_path(
+                    &Arc::clone(&app.device),
+                    &Arc::clone(&app.queue),
+                    app.is_gpu_supported,
+                    app.cache_strategy,
+                    app.compression_strategy,
+                    &app.pane_layout,
+                    &pane_file_lengths,
+                    i,
+                    &path,
+                    app.is_slider_dual,
+                    &mut app.slider_value,
+                    cache_size,
+                    archive_cache_size,
+                    archive_warning_threshold_mb,
+                );
+                crate::cache::progressive::finish_blocking(&mut pane.progressive, &app.queue);
+            }
+        }
+    }
+}
+
+pub(crate) fn apply_queue_sizes(app: &mut DataViewer, max_loading_queue_size: usize, max_being_loaded_queue_size: usize) {
+    if max_loading_queue_size == app.max_loading_queue_size && max_being_loaded_queue_size == app.max_being_loaded_queue_size {
+        return;
+    }
+    info!("Queue size settings changed: max_loading_queue_size={}, max_being_loaded_queue_size={}", max_loading_queue_size, max_being_loaded_queue_size);
+    app.max_loading_queue_size = max_loading_queue_size;
+    app.max_being_loaded_queue_size = max_being_loaded_queue_size;
+
+    for pane in app.panes.iter_mut() {
+        pane.max_loading_queue_size = max_loading_queue_size;
+        pane.max_being_loaded_queue_size = max_being_loaded_queue_size;
+    }
+}
+
+/// Apply settings read while the app is running, from an edited file or a profile
+///
+/// Display toggles go through their usual messages; cache strategy, compression, atlas size
+/// and window geometry are left for the next start, as with the settings dialog.
+fn apply_user_settings(app: &mut DataViewer, settings: UserSettings) -> Task<Message> {
+    let mut messages = Vec::new();
+    if settings.show_fps != app.show_fps {
+        messages.push(PaneMessage::ToggleFpsDisplay(settings.show_fps));
+    }
+    if settings.show_footer != app.show_footer {
+        messages.push(PaneMessage::ToggleFooter(settings.show_footer));
+    }
+    if settings.is_horizontal_split != app.is_horizontal_split {
+        messages.push(PaneMessage::ToggleSplitOrientation(settings.is_horizontal_split));
+    }
+    if settings.synced_zoom != app.synced_zoom {
+        messages.push(PaneMessage::ToggleSyncedZoom(settings.synced_zoom));
+    }
+    if settings.mouse_wheel_zoom != app.mouse_wheel_zoom {
+        messages.push(PaneMessage::ToggleMouseWheelZoom(settings.mouse_wheel_zoom));
+    }
+    if settings.show_copy_buttons != app.show_copy_buttons {
+        messages.push(PaneMessage::ToggleCopyButtons(settings.show_copy_buttons));
+    }
+    if settings.show_metadata != app.show_metadata {
+        messages.push(PaneMessage::ToggleMetadataDisplay(settings.show_metadata));
+    }
+    if settings.spinner_location != app.spinner_location {
+        messages.push(PaneMessage::SetSpinnerLocation(settings.spinner_location));
+    }
+    if settings.nearest_neighbor_filter != app.nearest_neighbor_filter {
+        messages.push(PaneMessage::ToggleNearestNeighborFilter(settings.nearest_neighbor_filter));
+    }
+    let tasks: Vec<Task<Message>> = messages.into_iter()
+        .map(|message| super::pane::handle_pane_message(app, message))
+        .collect();
+
+    app.use_binary_size = settings.use_binary_size;
+    app.archive_cache_size = settings.archive_cache_size * 1_048_576;
+    app.archive_warning_threshold_mb = settings.archive_warning_threshold_mb;
+    app.double_click_threshold_ms = settings.double_click_threshold_ms;
+    crate::cache::dir_cache::set_budget_mb(settings.directory_cache_mb);
+    crate::cache::load_limits::set_limits(settings.decode_threads, settings.max_concurrent_reads);
+    crate::cache::load_watchdog::set_load_timeout(settings.load_timeout_secs);
+    crate::file_io::set_follow_symlinks(settings.follow_symlinks);
+    app.check_for_updates = settings.check_for_updates;
+    app.confirm_delete = settings.confirm_delete;
+    app.viewed_images.persist = settings.persist_viewed;
+    app.menu_layout.apply(&settings);
+    crate::display_decode::set_enabled(settings.load_for_display);
+    // Only consulted when a source is opened, so these apply from the next one
+    let local = app.source_strategies.local;
+    app.source_strategies = settings.get_source_strategies(local);
+    apply_cache_size(app, settings.cache_size);
+    apply_queue_sizes(app, settings.max_loading_queue_size, settings.max_being_loaded_queue_size);
+
+    // Keep the settings dialog in step with the file
+    app.settings.advanced_input = crate::app::SettingsWidget::new(&settings).advanced_input;
+
+    Task::batch(tasks)
+}
+
+pub(super) fn handle_profile_message(app: &mut DataViewer, msg: crate::profiles::ProfileMessage) -> Task<Message> {
+    let base = crate::settings_watch::read_watched().unwrap_or_else(|| UserSettings::load(None));
+    match msg {
+        crate::profiles::ProfileMessage::Select(name) => {
+            let Some(profile) = crate::profiles::load(&name) else {
+                return Task::none();
+            };
+            let Some(settings) = profile.apply_to(&base) else {
+                return Task::none();
+            };
+            info!("Switching to profile {}", name);
+            crate::profiles::set_active(Some(name));
+            if let Some(pane_layout) = profile.pane_layout {
+                if pane_layout != app.pane_layout {
+                    app.toggle_pane_layout(pane_layout);
+                }
+            }
+            apply_user_settings(app, settings)
+        }
+        crate::profiles::ProfileMessage::Clear => {
+            info!("Leaving profile {:?}", crate::profiles::active_name());
+            crate::profiles::set_active(None);
+            apply_user_settings(app, base)
+        }
+    }
+}
+
+pub(super) fn handle_save_window_state(app: &mut DataViewer) -> Task<Message> {
+    let mut old_settings = UserSettings::load(None);
+    let tuple = get_window_visible(app.last_windowed_position, app.window_size,
+        app.last_monitor.clone());
+    // Prevents the saved position from being outside of the monitor
+    if !tuple.0 {
+        app.last_windowed_position = tuple.1;
+    }
+    // Use last_windowed_position to avoid saving maximized position (0,0) on Windows
+    old_settings.window_position_x = app.last_windowed_position.x;
+    old_settings.window_position_y = app.last_windowed_position.y;
+    if app.window_state == WindowState::Window {
+        old_settings.window_width = app.window_size.width;
+        old_settings.window_height = app.window_size.height;
+    }
+    old_settings.window_state = app.window_state;
+    if let Err(e) = old_settings.save() {
+        error!("Failed to save window state: {e}");
+    }
+    Task::none()
+}
+
+fn handle_reset_advanced_settings(app: &mut DataViewer) {
+    use crate::config;
+
+    app.show_fps = false;
+    app.show_footer = true;
+    app.is_horizontal_split = false;
+    app.synced_zoom = true;
+    app.mouse_wheel_zoom = false;
+    app.cache_strategy = CacheStrategy::Gpu;
+    app.compression_strategy = CompressionStrategy::None;
+    app.source_strategies = SourceStrategies::new(CacheStrategy::Gpu, CompressionStrategy::None);
+    app.is_slider_dual = false;
+
+    app.settings.advanced_input.insert("cache_size".to_string(), config::DEFAULT_CACHE_SIZE.to_string());
+    app.settings.advanced_input.insert("max_loading_queue_size".to_string(), config::DEFAULT_MAX_LOADING_QUEUE_SIZE.to_string());
+    app.settings.advanced_input.insert("max_being_loaded_queue_size".to_string(), config::DEFAULT_MAX_BEING_LOADED_QUEUE_SIZE.to_string());
+    app.settings.advanced_input.insert("window_width".to_string(), config::DEFAULT_WINDOW_WIDTH.to_string());
+    app.settings.advanced_input.insert("window_height".to_string(), config::DEFAULT_WINDOW_HEIGHT.to_string());
+    app.settings.advanced_input.insert("atlas_size".to_string(), config::DEFAULT_ATLAS_SIZE.to_string());
+    app.settings.advanced_input.insert("double_click_threshold_ms".to_string(), config::DEFAULT_DOUBLE_CLICK_THRESHOLD_MS.to_string());
+    app.settings.advanced_input.insert("archive_cache_size".to_string(), config::DEFAULT_ARCHIVE_CACHE_SIZE.to_string());
+    app.settings.advanced_input.insert("archive_warning_threshold_mb".to_string(), config::DEFAULT_ARCHIVE_WARNING_THRESHOLD_MB.to_string());
+    app.settings.advanced_input.insert("directory_cache_mb".to_string(), config::DEFAULT_DIRECTORY_CACHE_MB.to_string());
+    app.settings.advanced_input.insert("decode_threads".to_string(), config::DEFAULT_DECODE_THREADS.to_string());
+    app.settings.advanced_input.insert("max_concurrent_reads".to_string(), config::DEFAULT_MAX_CONCURRENT_READS.to_string());
+    app.settings.advanced_input.insert("load_timeout_secs".to_string(), config::DEFAULT_LOAD_TIMEOUT_SECS.to_string());
+}
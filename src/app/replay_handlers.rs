@@ -6,7 +6,7 @@ use std::time::Duration;
 use iced_winit::runtime::Task;
 use log::{debug, info, warn};
 
-use super::{DataViewer, Message};
+use super::{DataViewer, Message, NavigationMessage};
 
 /// Reset all FPS counters and timing history for fresh measurements
 fn reset_fps_trackers() {
@@ -213,7 +213,7 @@ impl DataViewer {
                 // Slider mode navigation: send SliderChanged message to simulate slider drag
                 debug!("Slider navigate to position {}", position);
                 // Use pane index -1 to affect the selected pane (same as global slider)
-                Some(Task::done(Message::SliderChanged(-1, position)))
+                Some(Task::done(Message::Navigation(NavigationMessage::SliderChanged(-1, position))))
             }
             crate::replay::ReplayAction::SliderStartNavigatingLeft => {
                 reset_fps_trackers();
@@ -225,6 +225,7 @@ impl DataViewer {
                 if let Some(ref controller) = self.replay_controller {
                     if controller.config.auto_exit {
                         info!("Auto-exit enabled, exiting application");
+                        crate::utils::frame_stats::write_report();
                         std::process::exit(0);
                     }
                 }
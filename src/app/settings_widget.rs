@@ -41,6 +41,8 @@ pub struct SettingsWidget {
     pub active_tab: usize,                              // Which tab is selected
     pub advanced_input: HashMap<String, String>,        // Text input state for advanced settings
     pub runtime_settings: RuntimeSettings,              // Runtime-configurable settings
+    #[cfg(target_os = "macos")]
+    pub stored_bookmarks: Vec<crate::macos_file_access::macos_file_handler::StoredBookmark>, // Listed on the Permissions tab
 }
 
 impl SettingsWidget {
@@ -56,6 +58,10 @@ impl SettingsWidget {
         advanced_input.insert("double_click_threshold_ms".to_string(), settings.double_click_threshold_ms.to_string());
         advanced_input.insert("archive_cache_size".to_string(), settings.archive_cache_size.to_string());
         advanced_input.insert("archive_warning_threshold_mb".to_string(), settings.archive_warning_threshold_mb.to_string());
+        advanced_input.insert("directory_cache_mb".to_string(), settings.directory_cache_mb.to_string());
+        advanced_input.insert("decode_threads".to_string(), settings.decode_threads.to_string());
+        advanced_input.insert("max_concurrent_reads".to_string(), settings.max_concurrent_reads.to_string());
+        advanced_input.insert("load_timeout_secs".to_string(), settings.load_timeout_secs.to_string());
 
         Self {
             show_options: false,
@@ -63,6 +69,8 @@ impl SettingsWidget {
             active_tab: 0,
             advanced_input,
             runtime_settings: RuntimeSettings::from_user_settings(settings),
+            #[cfg(target_os = "macos")]
+            stored_bookmarks: Vec::new(),
         }
     }
 
@@ -0,0 +1,73 @@
+/// Base directories for settings, data and logs, with portable mode
+///
+/// Drop-in replacements for the `dirs` functions the app uses. In portable mode (started
+/// with `--portable`, or with a `portable.txt` next to the executable) they point into a
+/// `viewskater-data` folder next to the executable instead, so nothing is written to the
+/// user's profile, e.g. when running from a USB stick.
+use std::path::{Path, PathBuf};
+use once_cell::sync::Lazy;
+
+const PORTABLE_FLAG: &str = "--portable";
+const PORTABLE_SENTINEL: &str = "portable.txt";
+const PORTABLE_DIR_NAME: &str = "viewskater-data";
+
+/// Decided once, from the raw arguments, so it holds before logging and settings are set up
+static PORTABLE_ROOT: Lazy<Option<PathBuf>> = Lazy::new(|| {
+    let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    portable_root(&exe_dir, std::env::args_os().skip(1).any(|arg| arg == PORTABLE_FLAG))
+});
+
+fn portable_root(exe_dir: &Path, flag: bool) -> Option<PathBuf> {
+    (flag || exe_dir.join(PORTABLE_SENTINEL).is_file()).then(|| exe_dir.join(PORTABLE_DIR_NAME))
+}
+
+/// Folder holding all files in portable mode
+pub fn portable_root_dir() -> Option<&'static Path> {
+    PORTABLE_ROOT.as_deref()
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    match PORTABLE_ROOT.as_ref() {
+        Some(root) => Some(root.join("config")),
+        None => dirs::config_dir(),
+    }
+}
+
+pub fn data_dir() -> Option<PathBuf> {
+    match PORTABLE_ROOT.as_ref() {
+        Some(root) => Some(root.join("data")),
+        None => dirs::data_dir(),
+    }
+}
+
+pub fn data_local_dir() -> Option<PathBuf> {
+    match PORTABLE_ROOT.as_ref() {
+        Some(root) => Some(root.join("data")),
+        None => dirs::data_local_dir(),
+    }
+}
+
+pub fn cache_dir() -> Option<PathBuf> {
+    match PORTABLE_ROOT.as_ref() {
+        Some(root) => Some(root.join("cache")),
+        None => dirs::cache_dir(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_portable_root_from_flag_or_sentinel() {
+        let dir = std::env::temp_dir().join(format!("viewskater_portable_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(portable_root(&dir, false), None);
+        assert_eq!(portable_root(&dir, true), Some(dir.join(PORTABLE_DIR_NAME)));
+
+        std::fs::write(dir.join(PORTABLE_SENTINEL), b"").unwrap();
+        assert_eq!(portable_root(&dir, false), Some(dir.join(PORTABLE_DIR_NAME)));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,606 @@
+/// Rotation suggestions for scanned documents
+///
+/// Scanners and phone scan apps often save pages sideways or upside down. Press T and the
+/// current image is checked for text lines: lines run along the direction whose ink profile
+/// alternates most between dense rows and empty gaps, and a line is upright when more of its
+/// ink sits above the band of lowercase letters (ascenders, capitals) than below it
+/// (descenders). The suggested turn is shown right away, with Keep and Undo in a bar under
+/// the image, and can be run over the whole folder.
+///
+/// Rotations are kept in memory for the session only. Files on disk are never rewritten;
+/// a rotated image is decoded, turned and handed to the caches as a PNG instead. The PNGs
+/// are kept per turn, so flipping a suggestion back and forth or coming back to a rotated
+/// image after it left the image cache doesn't decode and encode it again.
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Cursor};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, container, text, button, Row};
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use image::DynamicImage;
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+
+use crate::app::{DataViewer, Message};
+use crate::cache::img_cache::PathSource;
+
+/// Longest side images are shrunk to before looking for text lines
+const DETECT_SIZE: u32 = 1200;
+/// How much more the line profile must alternate than the cross profile
+const LINE_RATIO: f64 = 2.0;
+/// Smallest share of ink above or below the letter band that decides upright vs upside down
+const MIN_SKEW: f64 = 0.15;
+/// Runs of inked rows shorter than this are specks, not text lines
+const MIN_LINE_HEIGHT: usize = 4;
+/// Encoded bytes of rotated images kept around
+const ROTATED_CACHE_BYTES: usize = 256 * 1024 * 1024;
+
+/// Quarter turns clockwise applied to each image, on top of its EXIF orientation
+static ROTATIONS: Lazy<Mutex<HashMap<PathBuf, u8>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone)]
+enum Status {
+    Detecting,
+    /// A rotation was suggested for the current image and is being previewed
+    Suggested(u8),
+    /// The image is upright, or no text lines were found
+    Upright,
+    Unsure,
+    BatchRunning,
+    BatchDone { rotated: usize, total: usize },
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+struct Bar {
+    status: Status,
+    /// Rotations the last change replaced, restored by Undo
+    undo: Vec<(PathBuf, u8)>,
+}
+
+/// Image, quarter turns and its modification time, so an edited file isn't served stale
+type RotatedKey = (PathBuf, u8, Option<SystemTime>);
+
+/// Rotated PNGs, least recently used first, within a byte budget
+#[derive(Debug, Default)]
+struct RotatedCache {
+    entries: VecDeque<(RotatedKey, Vec<u8>)>,
+    bytes: usize,
+}
+
+impl RotatedCache {
+    fn get(&mut self, key: &RotatedKey) -> Option<Vec<u8>> {
+        let position = self.entries.iter().position(|(entry_key, _)| entry_key == key)?;
+        let entry = self.entries.remove(position)?;
+        let bytes = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(bytes)
+    }
+
+    fn insert(&mut self, key: RotatedKey, bytes: Vec<u8>, budget: usize) {
+        if bytes.len() > budget {
+            return;
+        }
+        self.bytes += bytes.len();
+        self.entries.push_back((key, bytes));
+        while self.bytes > budget {
+            let Some((_, evicted)) = self.entries.pop_front() else { break };
+            self.bytes -= evicted.len();
+        }
+    }
+}
+
+static ROTATED: Lazy<Mutex<RotatedCache>> = Lazy::new(|| Mutex::new(RotatedCache::default()));
+
+static BAR: Lazy<Mutex<Option<Bar>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone)]
+pub enum RotateMessage {
+    Suggest,
+    /// Image and suggested quarter turns, None when unsure
+    Detected(PathBuf, Result<Option<u8>, String>),
+    Keep,
+    Undo,
+    ApplyToFolder,
+    /// Suggestions for every image of the folder that had one
+    FolderDetected(Vec<(PathBuf, u8)>, usize),
+    Close,
+}
+
+impl From<RotateMessage> for Message {
+    fn from(msg: RotateMessage) -> Self {
+        Message::RotateAction(msg)
+    }
+}
+
+/// Quarter turns clockwise set for `path`
+pub fn rotation(path: &Path) -> u8 {
+    ROTATIONS.lock().ok().and_then(|rotations| rotations.get(path).copied()).unwrap_or(0)
+}
+
+fn set_rotation(path: &Path, turns: u8) {
+    if let Ok(mut rotations) = ROTATIONS.lock() {
+        match turns % 4 {
+            0 => rotations.remove(path),
+            turns => rotations.insert(path.to_path_buf(), turns),
+        };
+    }
+}
+
+fn rotate(image: DynamicImage, turns: u8) -> DynamicImage {
+    match turns % 4 {
+        1 => image.rotate90(),
+        2 => image.rotate180(),
+        3 => image.rotate270(),
+        _ => image,
+    }
+}
+
+/// Reads an image with a rotation set and returns it turned, encoded as PNG
+pub fn read_rotated(path: &Path) -> io::Result<Vec<u8>> {
+    let turns = rotation(path);
+    let modified = std::fs::metadata(crate::file_io::long_path(path)).and_then(|m| m.modified()).ok();
+    let key = (path.to_path_buf(), turns % 4, modified);
+    if let Some(bytes) = ROTATED.lock().ok().and_then(|mut cache| cache.get(&key)) {
+        return Ok(bytes);
+    }
+
+    let bytes = if crate::utils::raw_preview::is_raw(path) {
+        crate::utils::raw_preview::read(path)?
+    } else {
+        std::fs::read(crate::file_io::long_path(path))?
+    };
+    let image = crate::file_io::decode_image_from_bytes(&bytes)
+        .map_err(|kind| io::Error::new(kind, format!("Failed to decode {}", path.display())))?;
+    // PNG has no float samples
+    let image = match image {
+        DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => DynamicImage::ImageRgba16(image.to_rgba16()),
+        image => image,
+    };
+
+    let mut out = Vec::new();
+    rotate(image, turns)
+        .write_with_encoder(PngEncoder::new_with_quality(Cursor::new(&mut out), CompressionType::Fast, FilterType::NoFilter))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    if let Ok(mut cache) = ROTATED.lock() {
+        cache.insert(key, out.clone(), ROTATED_CACHE_BYTES);
+    }
+    Ok(out)
+}
+
+/// Row-major map of inked pixels
+#[derive(Debug, Clone, PartialEq)]
+struct Bitmap {
+    width: usize,
+    height: usize,
+    ink: Vec<bool>,
+}
+
+impl Bitmap {
+    fn rotate_cw(&self) -> Bitmap {
+        let (width, height) = (self.height, self.width);
+        let ink = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| self.ink[(self.height - 1 - x) * self.width + y])
+            .collect();
+        Bitmap { width, height, ink }
+    }
+
+    fn row_counts(&self) -> Vec<usize> {
+        self.ink.chunks_exact(self.width.max(1)).map(|row| row.iter().filter(|&&i| i).count()).collect()
+    }
+
+    fn column_counts(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.width];
+        for row in self.ink.chunks_exact(self.width.max(1)) {
+            for (count, &ink) in counts.iter_mut().zip(row) {
+                *count += ink as usize;
+            }
+        }
+        counts
+    }
+}
+
+/// Threshold splitting dark ink from light paper, by Otsu's method
+fn otsu(histogram: &[usize; 256]) -> u8 {
+    let total: usize = histogram.iter().sum();
+    let weighted: f64 = histogram.iter().enumerate().map(|(v, &n)| v as f64 * n as f64).sum();
+    let (mut below, mut below_sum) = (0usize, 0.0f64);
+    let (mut best, mut best_variance) = (0u8, 0.0f64);
+    for (value, &count) in histogram.iter().enumerate() {
+        below += count;
+        below_sum += value as f64 * count as f64;
+        let above = total - below;
+        if below == 0 || above == 0 {
+            continue;
+        }
+        let mean_below = below_sum / below as f64;
+        let mean_above = (weighted - below_sum) / above as f64;
+        let variance = below as f64 * above as f64 * (mean_below - mean_above).powi(2);
+        if variance > best_variance {
+            best_variance = variance;
+            best = value as u8;
+        }
+    }
+    best
+}
+
+fn ink_map(image: &DynamicImage) -> Bitmap {
+    let gray = image.thumbnail(DETECT_SIZE, DETECT_SIZE).to_luma8();
+    let mut histogram = [0usize; 256];
+    for pixel in gray.pixels() {
+        histogram[pixel.0[0] as usize] += 1;
+    }
+    let threshold = otsu(&histogram);
+    Bitmap {
+        width: gray.width() as usize,
+        height: gray.height() as usize,
+        ink: gray.pixels().map(|p| p.0[0] <= threshold).collect(),
+    }
+}
+
+/// Squared coefficient of variation of a profile over its inked span
+fn alternation(counts: &[usize]) -> f64 {
+    let Some(first) = counts.iter().position(|&c| c > 0) else {
+        return 0.0;
+    };
+    let last = counts.iter().rposition(|&c| c > 0).unwrap_or(first);
+    let span = &counts[first..=last];
+    let mean = span.iter().sum::<usize>() as f64 / span.len() as f64;
+    let variance = span.iter().map(|&c| (c as f64 - mean).powi(2)).sum::<f64>() / span.len() as f64;
+    variance / (mean * mean)
+}
+
+/// Ink above minus ink below the letter band of each text line, over their sum;
+/// positive for upright text
+fn upright_skew(bitmap: &Bitmap) -> f64 {
+    let counts = bitmap.row_counts();
+    let (mut above, mut below) = (0usize, 0usize);
+    let mut row = 0;
+    while row < counts.len() {
+        if counts[row] == 0 {
+            row += 1;
+            continue;
+        }
+        let top = row;
+        while row < counts.len() && counts[row] > 0 {
+            row += 1;
+        }
+        let line = &counts[top..row];
+        if line.len() < MIN_LINE_HEIGHT {
+            continue;
+        }
+        let peak = line.iter().copied().max().unwrap_or(0);
+        let band_top = line.iter().position(|&c| c * 2 >= peak).unwrap_or(0);
+        let band_bottom = line.iter().rposition(|&c| c * 2 >= peak).unwrap_or(line.len() - 1);
+        above += line[..band_top].iter().sum::<usize>();
+        below += line[band_bottom + 1..].iter().sum::<usize>();
+    }
+    if above + below == 0 {
+        return 0.0;
+    }
+    (above as f64 - below as f64) / (above + below) as f64
+}
+
+/// Quarter turns clockwise that make the text of `bitmap` upright, None when unsure
+fn detect(bitmap: &Bitmap) -> Option<u8> {
+    let inked = bitmap.ink.iter().filter(|&&i| i).count();
+    // Blank pages and photos, where most of the frame comes out as ink
+    if inked == 0 || inked * 2 > bitmap.ink.len() {
+        return None;
+    }
+    let rows = alternation(&bitmap.row_counts());
+    let columns = alternation(&bitmap.column_counts());
+    let (turns, horizontal) = if rows > columns * LINE_RATIO {
+        (0, bitmap.clone())
+    } else if columns > rows * LINE_RATIO {
+        (1, bitmap.rotate_cw())
+    } else {
+        return None;
+    };
+    let skew = upright_skew(&horizontal);
+    if skew.abs() < MIN_SKEW {
+        return None;
+    }
+    Some(if skew > 0.0 { turns } else { turns + 2 })
+}
+
+/// Quarter turns clockwise that make a scanned page upright, None when unsure
+pub fn suggest_turns(image: &DynamicImage) -> Option<u8> {
+    detect(&ink_map(image))
+}
+
+fn filesystem_path(source: &PathSource) -> Option<&PathBuf> {
+    match source {
+        PathSource::Filesystem(path) => Some(path),
+        _ => None,
+    }
+}
+
+fn set_status(status: Status, undo: Option<Vec<(PathBuf, u8)>>) {
+    if let Ok(mut bar) = BAR.lock() {
+        let undo = undo.or_else(|| bar.as_ref().map(|bar| bar.undo.clone())).unwrap_or_default();
+        *bar = Some(Bar { status, undo });
+    }
+}
+
+/// Relist the folders showing `path` so its images are decoded again with their new rotation
+fn reload(app: &mut DataViewer, path: &Path) -> Task<Message> {
+    let mut tasks = Vec::new();
+    for pane_index in 0..app.panes.len() {
+        let pane = &mut app.panes[pane_index];
+        if !pane.dir_loaded || !pane.img_cache.image_paths.iter().any(|source| source.path() == path) {
+            continue;
+        }
+        let Some(current) = pane.img_cache.image_paths.get(pane.img_cache.current_index) else {
+            continue;
+        };
+        let current = current.path().clone();
+        // Emptied first, so the decoded window isn't parked and handed back unrotated
+        pane.img_cache.clear_cache();
+        tasks.push(app.initialize_dir_path(&current, pane_index));
+    }
+    Task::batch(tasks)
+}
+
+pub fn handle_rotate_message(app: &mut DataViewer, msg: RotateMessage) -> Task<Message> {
+    let pane_index = app.active_pane_index();
+    let current = app.panes.get(pane_index)
+        .filter(|pane| pane.dir_loaded)
+        .and_then(|pane| pane.img_cache.image_paths.get(pane.img_cache.current_index))
+        .cloned();
+
+    match msg {
+        RotateMessage::Suggest => {
+            let Some(source) = current else {
+                return Task::none();
+            };
+            let Some(path) = filesystem_path(&source).cloned() else {
+                set_status(Status::Failed("Only images in folders can be rotated".to_string()), Some(Vec::new()));
+                return Task::none();
+            };
+            set_status(Status::Detecting, Some(Vec::new()));
+            Task::perform(
+                async move {
+                    let result = tokio::task::spawn_blocking(move || {
+                        crate::file_io::decode_path_source(&source, None).map(|image| suggest_turns(&image))
+                    })
+                    .await
+                    .unwrap_or_else(|e| Err(e.to_string()));
+                    (path, result)
+                },
+                |(path, result)| RotateMessage::Detected(path, result).into()
+            )
+        }
+        RotateMessage::Detected(path, result) => {
+            // Closed while detecting
+            if BAR.lock().map(|bar| bar.is_none()).unwrap_or(true) {
+                return Task::none();
+            }
+            match result {
+                Ok(Some(turns)) if turns != 0 => {
+                    // Detection ran on the image as shown, so the turn adds to any set before
+                    let previous = rotation(&path);
+                    set_rotation(&path, previous + turns);
+                    set_status(Status::Suggested(turns), Some(vec![(path.clone(), previous)]));
+                    reload(app, &path)
+                }
+                Ok(Some(_)) => {
+                    set_status(Status::Upright, None);
+                    Task::none()
+                }
+                Ok(None) => {
+                    set_status(Status::Unsure, None);
+                    Task::none()
+                }
+                Err(e) => {
+                    set_status(Status::Failed(e), None);
+                    Task::none()
+                }
+            }
+        }
+        RotateMessage::Keep | RotateMessage::Close => {
+            if let Ok(mut bar) = BAR.lock() {
+                *bar = None;
+            }
+            Task::none()
+        }
+        RotateMessage::Undo => {
+            let undo = BAR.lock().ok()
+                .and_then(|mut bar| bar.take())
+                .map(|bar| bar.undo)
+                .unwrap_or_default();
+            for (path, turns) in &undo {
+                set_rotation(path, *turns);
+            }
+            match undo.first() {
+                Some((path, _)) => reload(app, path),
+                None => Task::none(),
+            }
+        }
+        RotateMessage::ApplyToFolder => {
+            let Some(pane) = app.panes.get(pane_index).filter(|pane| pane.dir_loaded && !pane.has_compressed_file) else {
+                return Task::none();
+            };
+            let paths: Vec<PathBuf> = pane.img_cache.image_paths.iter()
+                .filter_map(filesystem_path)
+                .cloned()
+                .collect();
+            // The current image's suggestion is already applied; undoing it goes with the batch
+            let undo = BAR.lock().ok()
+                .and_then(|bar| bar.as_ref().map(|bar| bar.undo.clone()))
+                .unwrap_or_default();
+            set_status(Status::BatchRunning, Some(undo));
+            let total = paths.len();
+            Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || {
+                        paths.into_par_iter()
+                            .filter_map(|path| {
+                                let source = PathSource::Filesystem(path.clone());
+                                let image = crate::file_io::decode_path_source(&source, None).ok()?;
+                                suggest_turns(&image).filter(|&turns| turns != 0).map(|turns| (path, turns))
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .await
+                    .unwrap_or_default()
+                },
+                move |found| RotateMessage::FolderDetected(found, total).into()
+            )
+        }
+        RotateMessage::FolderDetected(found, total) => {
+            let Some(mut undo) = BAR.lock().ok().and_then(|bar| bar.as_ref().map(|bar| bar.undo.clone())) else {
+                return Task::none();
+            };
+            for (path, turns) in &found {
+                let previous = rotation(path);
+                if !undo.iter().any(|(undone, _)| undone == path) {
+                    undo.push((path.clone(), previous));
+                }
+                set_rotation(path, previous + turns);
+            }
+            set_status(Status::BatchDone { rotated: found.len(), total }, Some(undo));
+            match found.first() {
+                Some((path, _)) => reload(app, path),
+                None => Task::none(),
+            }
+        }
+    }
+}
+
+fn turn_text(turns: u8) -> &'static str {
+    match turns % 4 {
+        1 => "90° clockwise",
+        2 => "180°",
+        3 => "90° counterclockwise",
+        _ => "none",
+    }
+}
+
+/// Suggestion and its actions while the rotation bar is open, or nothing
+pub fn view<'a>() -> Element<'a, Message, WinitTheme, Renderer> {
+    let Some(bar) = BAR.lock().ok().and_then(|bar| bar.clone()) else {
+        return container(text("")).height(0).into();
+    };
+
+    let action = |label: &'a str, msg: RotateMessage| {
+        button(text(label).size(11))
+            .padding([1, 6])
+            .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+            .on_press(msg.into())
+    };
+
+    let (line, actions): (String, Vec<(&str, RotateMessage)>) = match bar.status {
+        Status::Detecting => ("Looking for text lines...".to_string(), vec![]),
+        Status::Suggested(turns) => (
+            format!("Rotated {} (suggested)", turn_text(turns)),
+            vec![("Keep", RotateMessage::Keep), ("Undo", RotateMessage::Undo), ("Apply to Folder", RotateMessage::ApplyToFolder)],
+        ),
+        Status::Upright => (
+            "Already upright".to_string(),
+            vec![("Apply to Folder", RotateMessage::ApplyToFolder)],
+        ),
+        Status::Unsure => ("No clear text direction found; left as is".to_string(), vec![]),
+        Status::BatchRunning => ("Checking every image in the folder...".to_string(), vec![]),
+        Status::BatchDone { rotated, total } => (
+            format!("Rotated {} of {} images", rotated, total),
+            if bar.undo.is_empty() { vec![] } else { vec![("Undo", RotateMessage::Undo)] },
+        ),
+        Status::Failed(e) => (e, vec![]),
+    };
+
+    let buttons = actions.into_iter()
+        .fold(Row::new().spacing(10), |buttons, (label, msg)| buttons.push(action(label, msg)));
+
+    container(
+        row![
+            text("Rotation").size(13),
+            container(text(line).size(13)).width(Length::Fill),
+            buttons,
+            action("Close", RotateMessage::Close),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center)
+    )
+    .padding([4, 8])
+    .width(Length::Fill)
+    .style(|_theme: &WinitTheme| container::Style {
+        text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+        ..container::Style::default()
+    })
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A page of text lines: a letter band with ascenders above it on every sixth column
+    /// and descenders below it on every eleventh
+    fn page() -> Bitmap {
+        let (width, height) = (200, 120);
+        let mut ink = vec![false; width * height];
+        for line_top in (10..100).step_by(16) {
+            for x in 20..180 {
+                let ascender = x % 6 == 0;
+                let descender = x % 11 == 0;
+                let rows = (line_top + 3..line_top + 8)
+                    .chain(if ascender { line_top..line_top + 3 } else { 0..0 })
+                    .chain(if descender { line_top + 8..line_top + 10 } else { 0..0 });
+                for y in rows {
+                    ink[y * width + x] = x % 3 != 0 || y < line_top + 3;
+                }
+            }
+        }
+        Bitmap { width, height, ink }
+    }
+
+    #[test]
+    fn test_detect_quarter_turns() {
+        let upright = page();
+        assert_eq!(detect(&upright), Some(0));
+        // A page that arrived turned clockwise n times needs 4 - n more
+        let mut turned = upright.clone();
+        for arrived in 1..4u8 {
+            turned = turned.rotate_cw();
+            assert_eq!(detect(&turned), Some(4 - arrived), "arrived turned {} times", arrived);
+        }
+        assert_eq!(turned.rotate_cw(), upright);
+    }
+
+    #[test]
+    fn test_detect_unsure() {
+        let blank = Bitmap { width: 50, height: 50, ink: vec![false; 2500] };
+        assert_eq!(detect(&blank), None);
+        // A checkerboard has no text lines in either direction
+        let checker = Bitmap {
+            width: 40,
+            height: 40,
+            ink: (0..1600).map(|i| (i % 40 / 5 + i / 40 / 5) % 2 == 0).collect(),
+        };
+        assert_eq!(detect(&checker), None);
+    }
+
+    #[test]
+    fn test_rotated_cache_evicts_least_recent() {
+        let key = |turns: u8| (PathBuf::from("page.jpg"), turns, None);
+        let mut cache = RotatedCache::default();
+        cache.insert(key(1), vec![1; 40], 100);
+        cache.insert(key(2), vec![2; 40], 100);
+        assert_eq!(cache.get(&key(1)), Some(vec![1; 40]));
+        // Turn 2 is now the least recently used
+        cache.insert(key(3), vec![3; 40], 100);
+        assert_eq!(cache.get(&key(2)), None);
+        assert_eq!(cache.get(&key(1)), Some(vec![1; 40]));
+        assert_eq!(cache.bytes, 80);
+        // Larger than the whole budget
+        cache.insert(key(0), vec![0; 200], 100);
+        assert_eq!(cache.get(&key(0)), None);
+    }
+}
@@ -0,0 +1,143 @@
+/// Permissions page of the settings on macOS
+///
+/// Lists the folders ViewSkater keeps security-scoped bookmarks for: when each was granted,
+/// when it last resolved and whether it works this session. "Revoke" deletes a grant that is
+/// no longer wanted, and "Re-grant…" asks for the folder again through the system folder
+/// panel, which replaces a broken bookmark with a fresh one.
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::font::Font;
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, column, container, text, button, Space, scrollable};
+use log::debug;
+
+use crate::app::{DataViewer, Message};
+use crate::macos_file_access::macos_file_handler::{self, BookmarkStatus, StoredBookmark};
+
+/// Tab of the settings modal the page is shown in
+pub const PERMISSIONS_TAB: usize = 3;
+
+#[derive(Debug, Clone)]
+pub enum PermissionsMessage {
+    Refresh,
+    /// Delete the bookmark of a folder
+    Revoke(String),
+    /// Ask for a folder again and store a new bookmark
+    Regrant(String),
+}
+
+impl From<PermissionsMessage> for Message {
+    fn from(msg: PermissionsMessage) -> Self {
+        Message::PermissionsAction(msg)
+    }
+}
+
+/// Reads the stored bookmarks again, as they are listed from UserDefaults only when asked
+pub fn refresh(app: &mut DataViewer) {
+    app.settings.stored_bookmarks = macos_file_handler::list_stored_bookmarks();
+}
+
+pub fn handle_permissions_message(app: &mut DataViewer, msg: PermissionsMessage) -> Task<Message> {
+    match msg {
+        PermissionsMessage::Refresh => {}
+        PermissionsMessage::Revoke(path) => macos_file_handler::remove_stored_bookmark(&path),
+        PermissionsMessage::Regrant(path) => {
+            if !macos_file_handler::request_directory_access_with_optimized_dialog(&path) {
+                debug!("Access to {} was not granted again", path);
+            }
+        }
+    }
+    refresh(app);
+    Task::none()
+}
+
+/// Local date and time of a Unix time, or "unknown"
+fn format_time(time: Option<i64>) -> String {
+    time.and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn bookmark_row(bookmark: &StoredBookmark) -> Element<'_, Message, WinitTheme, Renderer> {
+    let (status, color) = match &bookmark.status {
+        BookmarkStatus::Active => ("Active".to_string(), Color::from_rgb(0.4, 0.8, 0.4)),
+        BookmarkStatus::Unused => ("Not used yet this session".to_string(), Color::from_rgb(0.7, 0.7, 0.7)),
+        BookmarkStatus::Broken(reason) => (format!("Broken: {}", reason), Color::from_rgb(0.9, 0.4, 0.4)),
+    };
+    let dates = format!(
+        "Granted {}  ·  Last resolved {}",
+        format_time(bookmark.created), format_time(bookmark.last_resolved)
+    );
+
+    row![
+        column![
+            text(bookmark.path.as_str()).size(13),
+            text(dates).size(11).style(|theme: &WinitTheme| {
+                iced_widget::text::Style {
+                    color: Some(theme.extended_palette().background.weak.color)
+                }
+            }),
+            text(status).size(11).style(move |_theme: &WinitTheme| {
+                iced_widget::text::Style { color: Some(color) }
+            }),
+        ]
+        .spacing(1)
+        .width(Length::Fill),
+        button(text("Re-grant…").size(12))
+            .padding([2, 8])
+            .on_press(PermissionsMessage::Regrant(bookmark.path.clone()).into()),
+        button(text("Revoke").size(12))
+            .padding([2, 8])
+            .on_press(PermissionsMessage::Revoke(bookmark.path.clone()).into()),
+    ]
+    .spacing(8)
+    .align_y(Alignment::Center)
+    .into()
+}
+
+/// Permissions tab content: stored folder grants
+pub fn view_permissions_tab<'a>(viewer: &'a DataViewer) -> Element<'a, Message, WinitTheme, Renderer> {
+    let bookmarks = &viewer.settings.stored_bookmarks;
+    let mut list = column![].spacing(8);
+    if bookmarks.is_empty() {
+        list = list.push(text("No folder permissions are stored.").size(13));
+    }
+    for bookmark in bookmarks {
+        list = list.push(bookmark_row(bookmark));
+    }
+
+    let content = column![
+        row![
+            text("Folder Permissions").size(16)
+                .font(Font {
+                    family: iced_winit::core::font::Family::Name("Roboto"),
+                    weight: iced_winit::core::font::Weight::Medium,
+                    stretch: iced_winit::core::font::Stretch::Normal,
+                    style: iced_winit::core::font::Style::Normal,
+                }),
+            Space::with_width(Length::Fill),
+            button(text("Refresh").size(12))
+                .padding([2, 8])
+                .on_press(PermissionsMessage::Refresh.into()),
+        ]
+        .align_y(Alignment::Center),
+        text("Folders ViewSkater may read in the macOS sandbox. Revoke grants you no longer need, or re-grant ones that stopped working.")
+            .size(12)
+            .style(|theme: &WinitTheme| {
+                iced_widget::text::Style {
+                    color: Some(theme.extended_palette().background.weak.color)
+                }
+            }),
+        Space::with_height(5),
+        list,
+    ]
+    .spacing(3);
+
+    scrollable(
+        container(content)
+            .padding([5, 10])
+    )
+    .height(Length::Fill)
+    .into()
+}
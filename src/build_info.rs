@@ -95,6 +95,12 @@ impl BuildInfo {
         #[cfg(feature = "jp2")]
         features.push("jp2");
 
+        #[cfg(feature = "heif")]
+        features.push("heif");
+
+        #[cfg(feature = "jxl")]
+        features.push("jxl");
+
         if features.is_empty() {
             "none".to_string()
         } else {
@@ -0,0 +1,159 @@
+/// Progress of the cache window reload after a slider jump
+///
+/// `navigation_slider::load_remaining_images` queues one `LoadPos` job per pane, each
+/// loading the whole window around the new position. On slow storage that can take a while
+/// with nothing to show for it, so the jobs count their images here and a bar above the
+/// footer shows how far along they are, an estimate of the time left and a button to cancel.
+/// Cancelled images are skipped and left empty; they load when navigated onto.
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, container, text, button, progress_bar};
+use once_cell::sync::Lazy;
+use log::info;
+
+use crate::app::Message;
+
+/// Bulk loads finishing sooner than this never show the bar
+const SHOW_AFTER: Duration = Duration::from_millis(500);
+
+static CURRENT: Lazy<Mutex<Option<Arc<BulkLoad>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Image counters shared by the jobs of one bulk load
+#[derive(Debug)]
+pub struct BulkLoad {
+    total: AtomicUsize,
+    done: AtomicUsize,
+    cancelled: AtomicBool,
+    started: Instant,
+}
+
+impl BulkLoad {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Count one image of a job as loaded, failed or skipped
+    pub fn finish_image(&self) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Start tracking a new bulk load, replacing the previous one
+pub fn begin() {
+    if let Ok(mut current) = CURRENT.lock() {
+        *current = Some(Arc::new(BulkLoad {
+            total: AtomicUsize::new(0),
+            done: AtomicUsize::new(0),
+            cancelled: AtomicBool::new(false),
+            started: Instant::now(),
+        }));
+    }
+}
+
+/// Add the images of a job queued for the current bulk load
+pub fn add_images(count: usize) {
+    if let Some(bulk_load) = current() {
+        bulk_load.total.fetch_add(count, Ordering::Relaxed);
+    }
+}
+
+pub fn current() -> Option<Arc<BulkLoad>> {
+    CURRENT.lock().ok().and_then(|current| current.clone())
+}
+
+/// Stop tracking once no job of the bulk load is queued anymore
+pub fn finish() {
+    if let Ok(mut current) = CURRENT.lock() {
+        if let Some(bulk_load) = current.take() {
+            info!("Bulk load finished: {} / {} images in {:?}{}",
+                bulk_load.done.load(Ordering::Relaxed), bulk_load.total.load(Ordering::Relaxed),
+                bulk_load.started.elapsed(), if bulk_load.is_cancelled() { " (cancelled)" } else { "" });
+        }
+    }
+}
+
+/// Time left at the rate so far, once there is a rate to go by
+fn estimate_remaining(elapsed: Duration, done: usize, total: usize) -> Option<Duration> {
+    if done == 0 {
+        return None;
+    }
+    Some(elapsed.mul_f64(total.saturating_sub(done) as f64 / done as f64))
+}
+
+#[derive(Debug, Clone)]
+pub enum BulkLoadMessage {
+    Cancel,
+}
+
+impl From<BulkLoadMessage> for Message {
+    fn from(msg: BulkLoadMessage) -> Self {
+        Message::BulkLoadAction(msg)
+    }
+}
+
+pub fn handle_bulk_load_message(msg: BulkLoadMessage) -> Task<Message> {
+    match msg {
+        BulkLoadMessage::Cancel => {
+            if let Some(bulk_load) = current() {
+                bulk_load.cancelled.store(true, Ordering::Relaxed);
+            }
+            Task::none()
+        }
+    }
+}
+
+/// Progress bar of a running bulk load, or nothing
+///
+/// The counters change without a message; the view is rebuilt every frame while a pane
+/// is loading, which keeps the bar current.
+pub fn view<'a>() -> Element<'a, Message, WinitTheme, Renderer> {
+    let Some(bulk_load) = current().filter(|b| !b.is_cancelled() && b.started.elapsed() >= SHOW_AFTER) else {
+        return container(text("")).height(0).into();
+    };
+    let total = bulk_load.total.load(Ordering::Relaxed);
+    let done = bulk_load.done.load(Ordering::Relaxed).min(total);
+
+    let label_style = |_theme: &WinitTheme| iced_widget::text::Style {
+        color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+    };
+    let label = match estimate_remaining(bulk_load.started.elapsed(), done, total) {
+        Some(remaining) => format!("Loading {} / {}, ~{}s left", done, total, remaining.as_secs() + 1),
+        None => format!("Loading {} / {}", done, total),
+    };
+
+    let content = row![
+        text(label).size(11).style(label_style),
+        progress_bar(0.0..=total.max(1) as f32, done as f32)
+            .height(Length::Fixed(8.0))
+            .width(Length::Fill),
+        button(text("Cancel").size(11))
+            .padding([1, 6])
+            .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+            .on_press(BulkLoadMessage::Cancel.into()),
+    ]
+    .spacing(8)
+    .align_y(Alignment::Center);
+
+    container(content)
+        .padding([2, 8])
+        .width(Length::Fill)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_remaining() {
+        let elapsed = Duration::from_secs(4);
+        assert_eq!(estimate_remaining(elapsed, 0, 10), None);
+        assert_eq!(estimate_remaining(elapsed, 2, 10), Some(Duration::from_secs(16)));
+        assert_eq!(estimate_remaining(elapsed, 10, 10), Some(Duration::ZERO));
+    }
+}
@@ -0,0 +1,369 @@
+/// Burst stacks for culling
+///
+/// Consecutive images taken within a second of each other (by EXIF capture time) form a
+/// stack. While the current image is in one, a bar under the image shows its place in the
+/// stack and, expanded, a button per frame. V flips back to the frame shown before, so two
+/// frames can be compared A-vs-B in place; K picks the current frame by giving its XMP
+/// sidecar the "Pick" label and taking the label off the rest of the stack. [ and ] step over
+/// whole stacks. Those keys only act while the folder has stacks; otherwise they go to the
+/// annotation and script bindings as before. Capture times are read in the background once per folder.
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::PathBuf;
+use chrono::NaiveDateTime;
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, container, text, button, scrollable, Row};
+use log::{info, error};
+use rayon::prelude::*;
+
+use crate::app::{DataViewer, Message, NavigationMessage};
+use crate::cache::img_cache::PathSource;
+use crate::utils::xmp;
+use crate::widgets::overview_strip;
+use crate::widgets::timeline_strip::FolderKey;
+
+/// Largest gap between frames of one burst
+const MAX_GAP_MILLIS: i64 = 1000;
+/// XMP label given to the picked frame of a stack
+const PICK_LABEL: &str = "Pick";
+
+#[derive(Debug, Clone)]
+pub enum BurstMessage {
+    SetEnabled(bool),
+    SetExpanded(bool),
+    /// Stacks and picked images of a folder
+    Scanned(FolderKey, Vec<Range<usize>>, HashSet<usize>),
+    /// Go back to the frame shown before the current one
+    Flip,
+    Pick,
+    Picked(FolderKey, Result<usize, String>),
+    /// Jump to the next or previous stack or single image
+    Step(bool),
+}
+
+impl From<BurstMessage> for Message {
+    fn from(msg: BurstMessage) -> Self {
+        Message::BurstAction(msg)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct BurstStacks {
+    pub enabled: bool,
+    pub expanded: bool,
+    key: Option<FolderKey>,
+    scanning: bool,
+    stacks: Vec<Range<usize>>,
+    picks: HashSet<usize>,
+    /// Image shown on the last update, and the frame of the same stack shown before it
+    last_index: Option<usize>,
+    previous: Option<usize>,
+    status: Option<String>,
+}
+
+/// Runs of at least two consecutive images whose capture times are at most `max_gap` apart
+pub fn find_bursts(times: &[Option<NaiveDateTime>], max_gap_millis: i64) -> Vec<Range<usize>> {
+    let mut stacks = Vec::new();
+    let mut start = 0;
+    for index in 1..=times.len() {
+        let continues = index < times.len() && match (times[index - 1], times[index]) {
+            (Some(a), Some(b)) => (b - a).num_milliseconds().abs() <= max_gap_millis,
+            _ => false,
+        };
+        if !continues {
+            if index - start >= 2 {
+                stacks.push(start..index);
+            }
+            start = index;
+        }
+    }
+    stacks
+}
+
+/// Index of the stack holding `index`
+fn stack_of(stacks: &[Range<usize>], index: usize) -> Option<usize> {
+    let position = stacks.partition_point(|stack| stack.end <= index);
+    stacks.get(position).filter(|stack| stack.contains(&index)).map(|_| position)
+}
+
+/// First image of the next (or previous) stack or single image from `current`
+pub fn step(stacks: &[Range<usize>], current: usize, len: usize, forward: bool) -> Option<usize> {
+    let here = stack_of(stacks, current).map(|s| stacks[s].clone()).unwrap_or(current..current + 1);
+    if forward {
+        (here.end < len).then_some(here.end)
+    } else {
+        let before = here.start.checked_sub(1)?;
+        Some(stack_of(stacks, before).map(|s| stacks[s].start).unwrap_or(before))
+    }
+}
+
+fn filesystem_path(source: &PathSource) -> Option<&PathBuf> {
+    match source {
+        PathSource::Filesystem(path) => Some(path),
+        _ => None,
+    }
+}
+
+impl BurstStacks {
+    /// Whether stacks were found in the shown folder, so V, K, [ and ] act on them
+    pub fn is_active(&self) -> bool {
+        self.enabled && !self.stacks.is_empty()
+    }
+
+    fn current_stack(&self, index: usize) -> Option<Range<usize>> {
+        stack_of(&self.stacks, index).map(|s| self.stacks[s].clone())
+    }
+
+    /// Start reading capture times for a newly shown folder, and follow the shown image
+    pub fn request(app: &mut DataViewer) -> Option<Task<Message>> {
+        if !app.bursts.enabled {
+            return None;
+        }
+        let pane_index = overview_strip::active_pane(&app.pane_layout, app.is_slider_dual)?;
+        let pane = app.panes.get(pane_index).filter(|pane| pane.dir_loaded && !pane.has_compressed_file)?;
+        let current = pane.img_cache.current_index;
+        let bursts = &mut app.bursts;
+
+        if bursts.last_index != Some(current) {
+            let stack = |index| stack_of(&bursts.stacks, index);
+            let same_stack = bursts.last_index.is_some_and(|last| stack(last).is_some() && stack(last) == stack(current));
+            bursts.previous = if same_stack { bursts.last_index } else { None };
+            bursts.last_index = Some(current);
+        }
+
+        let key = (pane_index, pane.directory_path.clone()?, pane.img_cache.image_paths.len());
+        if bursts.key.as_ref() == Some(&key) {
+            return None;
+        }
+        *bursts = BurstStacks {
+            enabled: true,
+            expanded: bursts.expanded,
+            key: Some(key.clone()),
+            scanning: true,
+            last_index: Some(current),
+            ..BurstStacks::default()
+        };
+
+        let paths = pane.img_cache.image_paths.clone();
+        Some(Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    let paths = paths.to_vec();
+                    let times: Vec<Option<NaiveDateTime>> = paths.par_iter()
+                        .map(|source| crate::utils::exif_tags::read_capture_date(filesystem_path(source)?))
+                        .collect();
+                    let stacks = find_bursts(&times, MAX_GAP_MILLIS);
+                    let picks = stacks.iter()
+                        .flat_map(|stack| stack.clone())
+                        .filter(|&index| {
+                            filesystem_path(&paths[index])
+                                .is_some_and(|path| xmp::read_sidecar(path).label.as_deref() == Some(PICK_LABEL))
+                        })
+                        .collect::<HashSet<usize>>();
+                    (stacks, picks)
+                })
+                .await
+                .unwrap_or_default()
+            },
+            move |(stacks, picks)| BurstMessage::Scanned(key.clone(), stacks, picks).into()
+        ))
+    }
+}
+
+pub fn handle_burst_message(app: &mut DataViewer, msg: BurstMessage) -> Task<Message> {
+    let pane_index = overview_strip::active_pane(&app.pane_layout, app.is_slider_dual);
+    match msg {
+        BurstMessage::SetEnabled(enabled) => {
+            // Scanned again when turned back on, in case picks changed meanwhile
+            app.bursts = BurstStacks { enabled, expanded: app.bursts.expanded, ..BurstStacks::default() };
+            Task::none()
+        }
+        BurstMessage::SetExpanded(expanded) => {
+            app.bursts.expanded = expanded;
+            Task::none()
+        }
+        BurstMessage::Scanned(key, stacks, picks) => {
+            if app.bursts.key.as_ref() == Some(&key) {
+                info!("Found {} burst stacks", stacks.len());
+                app.bursts.stacks = stacks;
+                app.bursts.picks = picks;
+                app.bursts.scanning = false;
+            }
+            Task::none()
+        }
+        BurstMessage::Flip => {
+            match (pane_index, app.bursts.previous) {
+                (Some(pane_index), Some(previous)) => Task::done(Message::Navigation(NavigationMessage::JumpToImage(pane_index, previous))),
+                _ => Task::none(),
+            }
+        }
+        BurstMessage::Step(forward) => {
+            let Some(pane_index) = pane_index.filter(|&index| app.panes.get(index).is_some_and(|pane| pane.dir_loaded)) else {
+                return Task::none();
+            };
+            let pane = &app.panes[pane_index];
+            match step(&app.bursts.stacks, pane.img_cache.current_index, pane.img_cache.image_paths.len(), forward) {
+                Some(index) => Task::done(Message::Navigation(NavigationMessage::JumpToImage(pane_index, index))),
+                None => Task::none(),
+            }
+        }
+        BurstMessage::Pick => {
+            let (Some(pane), Some(key)) = (pane_index.and_then(|index| app.panes.get(index)), app.bursts.key.clone()) else {
+                return Task::none();
+            };
+            let winner = pane.img_cache.current_index;
+            let Some(stack) = app.bursts.current_stack(winner) else {
+                return Task::none();
+            };
+            let members: Vec<(usize, PathBuf)> = stack
+                .filter_map(|index| Some((index, filesystem_path(pane.img_cache.image_paths.get(index)?)?.clone())))
+                .collect();
+            Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || -> Result<usize, String> {
+                        for (index, path) in &members {
+                            let mut fields = xmp::read_sidecar(path);
+                            let is_pick = fields.label.as_deref() == Some(PICK_LABEL);
+                            if *index == winner {
+                                fields.label = Some(PICK_LABEL.to_string());
+                            } else if is_pick {
+                                fields.label = None;
+                            } else {
+                                continue;
+                            }
+                            xmp::write_sidecar(path, &fields)?;
+                        }
+                        Ok(winner)
+                    })
+                    .await
+                    .unwrap_or_else(|e| Err(e.to_string()))
+                },
+                move |result| BurstMessage::Picked(key.clone(), result).into()
+            )
+        }
+        BurstMessage::Picked(key, result) => {
+            let bursts = &mut app.bursts;
+            if bursts.key.as_ref() != Some(&key) {
+                return Task::none();
+            }
+            match result {
+                Ok(winner) => {
+                    if let Some(stack) = bursts.current_stack(winner) {
+                        bursts.picks.retain(|index| !stack.contains(index));
+                        bursts.picks.insert(winner);
+                    }
+                    bursts.status = None;
+                }
+                Err(e) => {
+                    error!("Failed to write pick: {}", e);
+                    bursts.status = Some(format!("Error: {}", e));
+                }
+            }
+            Task::none()
+        }
+    }
+}
+
+/// Stack of the current image, or a zero-height container outside stacks
+pub fn view<'a>(app: &'a DataViewer) -> Element<'a, Message, WinitTheme, Renderer> {
+    let bursts = &app.bursts;
+    let empty = || -> Element<'a, Message, WinitTheme, Renderer> { container(text("")).height(0).into() };
+    let Some(pane_index) = overview_strip::active_pane(&app.pane_layout, app.is_slider_dual) else {
+        return empty();
+    };
+    if !bursts.enabled || bursts.scanning || bursts.key.as_ref().map(|key| key.0) != Some(pane_index) {
+        return empty();
+    }
+    let current = overview_strip::display_index(&app.panes[pane_index], app.use_slider_image_for_render);
+    let Some(stack) = bursts.current_stack(current) else {
+        return empty();
+    };
+
+    let labeled = |label: &'static str| -> iced_widget::Button<'a, Message, WinitTheme, Renderer> {
+        button(text(label).size(11))
+            .padding([1, 6])
+            .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+    };
+    let picked = stack.clone().find(|index| bursts.picks.contains(index));
+    let mut summary = format!("Burst: frame {} of {}", current - stack.start + 1, stack.len());
+    if let Some(picked) = picked {
+        summary.push_str(&format!(", pick {}", picked - stack.start + 1));
+    }
+    if let Some(previous) = bursts.previous {
+        summary.push_str(&format!("   V flips with {}", previous - stack.start + 1));
+    }
+
+    let frames: Element<'a, Message, WinitTheme, Renderer> = if bursts.expanded {
+        let buttons = stack.clone().fold(Row::new().spacing(4), |frames, index| {
+            let is_current = index == current;
+            let mark = if bursts.picks.contains(&index) { " *" } else { "" };
+            frames.push(
+                button(text(format!("{}{}", index - stack.start + 1, mark)).size(11))
+                    .padding([1, 6])
+                    .style(move |theme: &WinitTheme, status| {
+                        let mut style = crate::menu::button_style(theme, status, "labeled");
+                        if is_current {
+                            style.border.color = theme.extended_palette().primary.strong.color;
+                            style.border.width = 1.0;
+                        }
+                        style
+                    })
+                    .on_press(Message::Navigation(NavigationMessage::JumpToImage(pane_index, index)))
+            )
+        });
+        scrollable(buttons)
+            .direction(scrollable::Direction::Horizontal(scrollable::Scrollbar::new().width(4).scroller_width(4)))
+            .width(Length::Fill)
+            .into()
+    } else {
+        container(text("")).width(Length::Fill).into()
+    };
+
+    container(
+        row![
+            text(summary).size(13),
+            frames,
+            text(bursts.status.clone().unwrap_or_default()).size(12),
+            labeled(if bursts.expanded { "Collapse" } else { "Expand" })
+                .on_press(BurstMessage::SetExpanded(!bursts.expanded).into()),
+            labeled("Pick (K)").on_press(BurstMessage::Pick.into()),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center)
+    )
+    .padding([4, 8])
+    .width(Length::Fill)
+    .style(|_theme: &WinitTheme| container::Style {
+        text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+        ..container::Style::default()
+    })
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_bursts() {
+        let base = NaiveDateTime::parse_from_str("2024-06-12 18:30:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let at = |millis: i64| Some(base + chrono::Duration::milliseconds(millis));
+        let times = [at(0), at(5000), at(5200), at(5400), None, at(5600), at(9000), at(9900)];
+        assert_eq!(find_bursts(&times, MAX_GAP_MILLIS), vec![1..4, 6..8]);
+    }
+
+    #[test]
+    fn test_step_over_stacks() {
+        let stacks = vec![1..4, 6..8];
+        assert_eq!(step(&stacks, 0, 10, true), Some(1));
+        assert_eq!(step(&stacks, 2, 10, true), Some(4));
+        assert_eq!(step(&stacks, 5, 10, true), Some(6));
+        assert_eq!(step(&stacks, 8, 10, false), Some(6));
+        assert_eq!(step(&stacks, 4, 10, false), Some(1));
+        assert_eq!(step(&stacks, 7, 8, true), None);
+        assert_eq!(step(&stacks, 0, 10, false), None);
+    }
+}
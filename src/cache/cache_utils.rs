@@ -35,15 +35,17 @@ pub fn check_and_resize_if_oversized(img: DynamicImage) -> DynamicImage {
     }
 }
 
+/// Loads an image at its full size; GPU loads fit it with `texture_tiles::fit_to_device`
+pub fn decode_original_image(path_source: &crate::cache::img_cache::PathSource, archive_cache: Option<&mut crate::archive_cache::ArchiveCache>) -> Result<DynamicImage, io::Error> {
+    // Use PathSource-aware unified function
+    let bytes = crate::file_io::read_image_bytes(path_source, archive_cache)?;
+    crate::file_io::decode_image_from_bytes(&bytes)
+        .map_err(|e| io::Error::new(e, format!("Failed to read image from PathSource: {}", path_source.file_name())))
+}
+
 /// Loads an image with safety resizing for oversized images (>8192px)
 pub fn load_original_image(path_source: &crate::cache::img_cache::PathSource, archive_cache: Option<&mut crate::archive_cache::ArchiveCache>) -> Result<DynamicImage, io::Error> {
-    let img = {
-        // Use PathSource-aware unified function
-        let bytes = crate::file_io::read_image_bytes(path_source, archive_cache)?;
-        crate::file_io::decode_image_from_bytes(&bytes)
-            .map_err(|e| io::Error::new(e, format!("Failed to read image from PathSource: {}", path_source.file_name())))?
-    };
-    Ok(check_and_resize_if_oversized(img))
+    Ok(check_and_resize_if_oversized(decode_original_image(path_source, archive_cache)?))
 }
 
 /// Loads and resizes an image to target dimensions, then applies safety size check
@@ -58,7 +60,7 @@ pub fn load_and_resize_image(path_source: &crate::cache::img_cache::PathSource,
     info!("Resizing image: {}x{} -> {}x{}", original_width, original_height, target_width, target_height);
 
     // First resize to target dimensions
-    let resized_img = img.resize_exact(target_width, target_height, image::imageops::FilterType::Triangle);
+    let resized_img = crate::utils::color::resize(&img, target_width, target_height, image::imageops::FilterType::Triangle);
 
     // Then apply safety check for oversized images
     Ok(check_and_resize_if_oversized(resized_img))
@@ -177,6 +179,36 @@ pub fn upload_uncompressed_texture(
     );
 }
 
+/// Uploads full-width RGBA rows into part of an existing texture, starting at row `y`
+pub fn upload_texture_rows(
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    rows_rgba: &[u8],
+    width: u32,
+    y: u32,
+    rows: u32,
+) {
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d { x: 0, y, z: 0 },
+            aspect: wgpu::TextureAspect::All,
+        },
+        rows_rgba,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(width * 4),
+            rows_per_image: None,
+        },
+        wgpu::Extent3d {
+            width,
+            height: rows,
+            depth_or_array_layers: 1,
+        },
+    );
+}
+
 /// Uploads compressed image data to a texture
 pub fn upload_compressed_texture(
     queue: &wgpu::Queue,
@@ -254,6 +286,9 @@ pub fn create_and_upload_texture(
     height: u32,
     compression_strategy: CompressionStrategy,
 ) -> wgpu::Texture {
+    if let Some(grid) = crate::cache::texture_tiles::grid_for_device(device, width, height) {
+        return crate::cache::texture_tiles::create_tiled_texture(device, queue, image_data, grid);
+    }
     let use_compression = should_use_compression(width, height, compression_strategy);
 
     let texture = create_gpu_texture(device, width, height, compression_strategy);
@@ -289,7 +324,7 @@ pub fn load_image_resized_sync(
     let img = if is_slider_move {
         load_and_resize_image(img_path, 1280, 720, archive_cache)?
     } else {
-        load_original_image(img_path, archive_cache)?
+        crate::cache::texture_tiles::fit_to_device(decode_original_image(img_path, archive_cache)?, device)
     };
     let (image_bytes, width, height) = convert_image_to_rgba(&img);
 
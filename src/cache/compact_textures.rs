@@ -0,0 +1,250 @@
+/// Single-channel textures for grayscale and indexed images
+///
+/// Segmentation masks and scans are mostly 8-bit grayscale or palette PNGs, which used to be
+/// expanded to RGBA before upload and took four times the memory they need. Such images are
+/// now kept as `R8Unorm` textures: a grayscale one holds its sRGB-encoded gray levels, an
+/// indexed one its palette indices, with the palette in a 256x1 texture of its own that
+/// `texture.wgsl` looks the indices up in. Indices are never filtered; each sample reads the
+/// nearest texel.
+///
+/// Code that reads a texture's pixels back goes through [`to_rgba`]. The comparison
+/// pipelines sample the texture as it is, so for these images they only see the red channel.
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex, Weak};
+use image::{DynamicImage, GrayImage};
+use iced_wgpu::wgpu;
+use once_cell::sync::Lazy;
+use crate::cache::{gray16_textures, hdr_textures};
+
+/// Value of `pixel_format` in `texture.wgsl`'s filter options
+pub const FORMAT_RGBA: u32 = 0;
+pub const FORMAT_GRAY: u32 = 1;
+pub const FORMAT_INDEXED: u32 = 2;
+/// `Rgba16Float` texture of an HDR image (see `hdr_textures`)
+pub const FORMAT_HDR: u32 = 3;
+/// `Rg8Unorm` texture of a 16-bit grayscale image (see `gray16_textures`)
+pub const FORMAT_GRAY16: u32 = 4;
+
+/// RGBA colors of an indexed image, 256 entries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette(Vec<[u8; 4]>);
+
+impl Palette {
+    /// Palette from a PNG's `PLTE` RGB triples and `tRNS` alphas; missing entries are opaque black
+    fn from_png(rgb: &[u8], alpha: Option<&[u8]>) -> Self {
+        let entries = (0..256).map(|i| match rgb.get(i * 3..i * 3 + 3) {
+            Some(color) => [color[0], color[1], color[2], alpha.and_then(|alpha| alpha.get(i)).copied().unwrap_or(255)],
+            None => [0, 0, 0, 255],
+        });
+        Self(entries.collect())
+    }
+
+    /// RGBA image of the colors the indices of `image` point to
+    pub fn expand(&self, indices: &GrayImage) -> DynamicImage {
+        let pixels = indices.as_raw().iter().flat_map(|&index| self.0[index as usize]).collect();
+        DynamicImage::ImageRgba8(image::RgbaImage::from_raw(indices.width(), indices.height(), pixels)
+            .expect("one color per index"))
+    }
+}
+
+struct IndexedTexture {
+    image: Weak<wgpu::Texture>,
+    palette: Palette,
+    palette_texture: wgpu::Texture,
+}
+
+static PALETTES: Lazy<Mutex<HashMap<wgpu::Id<wgpu::Texture>, IndexedTexture>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Indices of a row of `width` pixels packed `bit_depth` bits each, most significant first
+fn unpack_row(row: &[u8], width: usize, bit_depth: u8) -> impl Iterator<Item = u8> + '_ {
+    let per_byte = 8 / bit_depth as usize;
+    let mask = ((1u16 << bit_depth) - 1) as u8;
+    (0..width).map(move |x| {
+        let shift = 8 - bit_depth as usize * (x % per_byte + 1);
+        (row[x / per_byte] >> shift) & mask
+    })
+}
+
+/// Decodes an indexed PNG to its palette indices, without expanding them to colors
+pub fn decode_indexed_png(bytes: &[u8]) -> Option<(GrayImage, Palette)> {
+    // Color type byte of the IHDR chunk, right after the signature
+    if !bytes.starts_with(b"\x89PNG\r\n\x1a\n") || bytes.get(25) != Some(&3) {
+        return None;
+    }
+    let mut decoder = png::Decoder::new(Cursor::new(bytes));
+    decoder.set_transformations(png::Transformations::IDENTITY);
+    let mut reader = decoder.read_info().ok()?;
+    let info = reader.info();
+    let palette = Palette::from_png(info.palette.as_deref()?, info.trns.as_deref());
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buffer).ok()?;
+
+    let bit_depth = frame.bit_depth as u8;
+    let width = frame.width as usize;
+    let indices: Vec<u8> = buffer[..frame.buffer_size()]
+        .chunks(frame.line_size)
+        .flat_map(|row| unpack_row(row, width, bit_depth))
+        .collect();
+    Some((GrayImage::from_raw(frame.width, frame.height, indices)?, palette))
+}
+
+/// `R8Unorm` or `Rgba8UnormSrgb` texture holding `texels`
+fn create_plain_texture(device: &wgpu::Device, queue: &wgpu::Queue, label: &str, format: wgpu::TextureFormat, width: u32, height: u32, texels: &[u8]) -> wgpu::Texture {
+    let bytes_per_texel = if format == wgpu::TextureFormat::R8Unorm { 1 } else { 4 };
+    let size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    queue.write_texture(
+        texture.as_image_copy(),
+        texels,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(width * bytes_per_texel),
+            rows_per_image: None,
+        },
+        size,
+    );
+    texture
+}
+
+/// Single-channel texture for an 8-bit grayscale image, or the indices of an indexed one,
+/// or None when the image has more channels or needs more than one texture
+pub fn create_texture(device: &wgpu::Device, queue: &wgpu::Queue, image: &DynamicImage, palette: Option<&Palette>) -> Option<Arc<wgpu::Texture>> {
+    let DynamicImage::ImageLuma8(gray) = image else {
+        return None;
+    };
+    let (width, height) = gray.dimensions();
+    let max_dimension = device.limits().max_texture_dimension_2d;
+    if width > max_dimension || height > max_dimension {
+        return None;
+    }
+    let texture = Arc::new(create_plain_texture(
+        device, queue, "CompactTexture", wgpu::TextureFormat::R8Unorm, width, height, gray.as_raw()));
+
+    if let Some(palette) = palette {
+        let texels: Vec<u8> = palette.0.iter().flatten().copied().collect();
+        let palette_texture = create_plain_texture(
+            device, queue, "PaletteTexture", wgpu::TextureFormat::Rgba8UnormSrgb, 256, 1, &texels);
+        if let Ok(mut palettes) = PALETTES.lock() {
+            palettes.retain(|_, indexed| indexed.image.strong_count() > 0);
+            palettes.insert(texture.global_id(), IndexedTexture {
+                image: Arc::downgrade(&texture),
+                palette: palette.clone(),
+                palette_texture,
+            });
+        }
+    }
+    Some(texture)
+}
+
+/// `pixel_format` of a texture for `texture.wgsl`
+pub fn pixel_format(texture: &wgpu::Texture) -> u32 {
+    if hdr_textures::is_hdr(texture) {
+        return FORMAT_HDR;
+    }
+    if gray16_textures::is_gray16(texture) {
+        return FORMAT_GRAY16;
+    }
+    if texture.format() != wgpu::TextureFormat::R8Unorm {
+        return FORMAT_RGBA;
+    }
+    match PALETTES.lock() {
+        Ok(palettes) if palettes.contains_key(&texture.global_id()) => FORMAT_INDEXED,
+        _ => FORMAT_GRAY,
+    }
+}
+
+/// View of the palette of an indexed texture; other textures get a placeholder, since the
+/// binding always needs one
+pub fn palette_view(device: &wgpu::Device, texture: &wgpu::Texture) -> wgpu::TextureView {
+    if let Some(indexed) = PALETTES.lock().ok().as_ref().and_then(|palettes| palettes.get(&texture.global_id())) {
+        return indexed.palette_texture.create_view(&wgpu::TextureViewDescriptor::default());
+    }
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("PalettePlaceholder"),
+        size: wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    })
+    .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+/// Bytes per texel of a texture read back from the GPU
+pub fn bytes_per_texel(texture: &wgpu::Texture) -> u32 {
+    match texture.format() {
+        wgpu::TextureFormat::R8Unorm => 1,
+        gray16_textures::FORMAT => 2,
+        hdr_textures::FORMAT => 8,
+        _ => 4,
+    }
+}
+
+/// RGBA rows of a texture's texels read back from the GPU, tightly packed
+pub fn to_rgba(texture: &wgpu::Texture, texels: Vec<u8>) -> Vec<u8> {
+    match pixel_format(texture) {
+        FORMAT_GRAY => texels.iter().flat_map(|&gray| [gray, gray, gray, 255]).collect(),
+        FORMAT_INDEXED => {
+            let palettes = PALETTES.lock().ok();
+            let palette = palettes.as_ref().and_then(|palettes| palettes.get(&texture.global_id()));
+            match palette {
+                Some(indexed) => texels.iter().flat_map(|&index| indexed.palette.0[index as usize]).collect(),
+                None => texels.iter().flat_map(|&index| [index, index, index, 255]).collect(),
+            }
+        }
+        FORMAT_HDR => hdr_textures::to_rgba(&texels),
+        FORMAT_GRAY16 => gray16_textures::to_rgba(texture, &texels),
+        _ => texels,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unpack_row() {
+        assert_eq!(unpack_row(&[0b1011_0001], 5, 1).collect::<Vec<_>>(), [1, 0, 1, 1, 0]);
+        assert_eq!(unpack_row(&[0b1101_0010, 0b1100_0000], 5, 2).collect::<Vec<_>>(), [3, 1, 0, 2, 3]);
+        assert_eq!(unpack_row(&[0xA5, 0x30], 3, 4).collect::<Vec<_>>(), [10, 5, 3]);
+        assert_eq!(unpack_row(&[7, 200], 2, 8).collect::<Vec<_>>(), [7, 200]);
+    }
+
+    #[test]
+    fn test_decode_indexed_png() {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut bytes, 3, 2);
+            encoder.set_color(png::ColorType::Indexed);
+            encoder.set_depth(png::BitDepth::Two);
+            encoder.set_palette(vec![0, 0, 0, 255, 0, 0, 0, 0, 255]);
+            encoder.set_trns(vec![255, 128]);
+            let mut writer = encoder.write_header().unwrap();
+            // Rows of indices 0 1 2 and 2 1 0, padded to a byte
+            writer.write_image_data(&[0b0001_1000, 0b1001_0000]).unwrap();
+        }
+
+        let (indices, palette) = decode_indexed_png(&bytes).unwrap();
+        assert_eq!(indices.as_raw(), &[0, 1, 2, 2, 1, 0]);
+        let DynamicImage::ImageRgba8(colors) = palette.expand(&indices) else { unreachable!() };
+        assert_eq!(colors.get_pixel(1, 0).0, [255, 0, 0, 128]);
+        assert_eq!(colors.get_pixel(0, 1).0, [0, 0, 255, 255]);
+
+        // An RGB PNG is left to the regular decoder
+        let mut rgb = Vec::new();
+        image::RgbImage::new(2, 2).write_to(&mut Cursor::new(&mut rgb), image::ImageFormat::Png).unwrap();
+        assert!(decode_indexed_png(&rgb).is_none());
+    }
+}
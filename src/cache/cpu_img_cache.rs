@@ -18,7 +18,7 @@ impl ImageCacheBackend for CpuImageCache {
     fn load_image(
         &self,
         index: usize,
-        image_paths: &[crate::cache::img_cache::PathSource],
+        image_paths: &crate::cache::img_cache::PathIndex,
         #[allow(unused_variables)] compression_strategy: CompressionStrategy,
         archive_cache: Option<&mut crate::archive_cache::ArchiveCache>
     ) -> Result<CachedData, io::Error> {
@@ -33,7 +33,7 @@ impl ImageCacheBackend for CpuImageCache {
     #[allow(clippy::needless_option_as_deref)]
     fn load_single_image(
         &mut self,
-        image_paths: &[crate::cache::img_cache::PathSource],
+        image_paths: &crate::cache::img_cache::PathIndex,
         cache_count: usize,
         current_index: usize,
         cached_data: &mut Vec<Option<CachedData>>,
@@ -69,6 +69,7 @@ impl ImageCacheBackend for CpuImageCache {
                         .with_guessed_format()
                         .ok()
                         .and_then(|r| r.into_dimensions().ok())
+                        .or_else(|| crate::utils::layered::dimensions(&bytes))
                         .unwrap_or((0, 0));
                     cached_data[cache_slot] = Some(CachedData::Cpu(bytes));
                     cached_metadata[cache_slot] = Some(ImageMetadata::new(width, height, file_size));
@@ -93,7 +94,7 @@ impl ImageCacheBackend for CpuImageCache {
     #[allow(dead_code)]
     fn load_initial_images(
         &mut self,
-        image_paths: &[crate::cache::img_cache::PathSource],
+        image_paths: &crate::cache::img_cache::PathIndex,
         cache_count: usize,
         current_index: usize,
         cached_data: &mut Vec<Option<CachedData>>,
@@ -136,6 +137,7 @@ impl ImageCacheBackend for CpuImageCache {
                             .with_guessed_format()
                             .ok()
                             .and_then(|r| r.into_dimensions().ok())
+                            .or_else(|| crate::utils::layered::dimensions(&bytes))
                             .unwrap_or((0, 0));
                         cached_data[i] = Some(CachedData::Cpu(bytes));
                         cached_metadata[i] = Some(ImageMetadata::new(width, height, file_size));
@@ -0,0 +1,227 @@
+/// Decoded images of recently closed directories
+///
+/// When a pane switches to another directory its cache window is normally thrown away.
+/// The window is parked here instead, in a process-wide LRU bounded by
+/// `directory_cache_mb`, and handed back when the same directory is opened again with the
+/// same file list, so toggling between two folders does not decode everything again.
+use std::collections::HashMap;
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use iced_wgpu::engine::CompressionStrategy;
+#[allow(unused_imports)]
+use log::{debug, info};
+
+use crate::cache::img_cache::{CachedData, CacheStrategy, ImageCache, ImageMetadata};
+use crate::config::CONFIG;
+
+pub static RECENT_DIRECTORIES: Lazy<Mutex<DirectoryLru>> = Lazy::new(|| {
+    Mutex::new(DirectoryLru::new(CONFIG.directory_cache_mb as usize * 1024 * 1024))
+});
+
+/// Cache window contents of one directory, keyed by image index
+pub struct DirectorySnapshot {
+    image_paths: Vec<std::path::PathBuf>,
+    images: HashMap<usize, (CachedData, Option<ImageMetadata>)>,
+    bytes: usize,
+}
+
+impl DirectorySnapshot {
+    /// Collect the loaded slots of a cache window, or None if nothing is loaded
+    pub fn from_cache(img_cache: &ImageCache) -> Option<Self> {
+        // Keeping the file list of a paged folder would hold every path in memory again
+        if img_cache.image_paths.is_paged() {
+            return None;
+        }
+        let mut images = HashMap::new();
+        for ((data, metadata), &index) in img_cache.cached_data.iter()
+            .zip(img_cache.cached_metadata.iter())
+            .zip(img_cache.cached_image_indices.iter())
+        {
+            if let (Some(data), true) = (data, index >= 0) {
+                images.insert(index as usize, (data.clone(), metadata.clone()));
+            }
+        }
+        if images.is_empty() {
+            return None;
+        }
+        let bytes = images.values().map(|(data, _)| data.len()).sum();
+        Some(Self {
+            image_paths: img_cache.image_paths.iter().map(|p| p.path().clone()).collect(),
+            images,
+            bytes,
+        })
+    }
+}
+
+pub struct DirectoryLru {
+    entries: Vec<(String, DirectorySnapshot)>,  // Least recently used first
+    budget_bytes: usize,
+}
+
+impl DirectoryLru {
+    pub fn new(budget_bytes: usize) -> Self {
+        Self { entries: Vec::new(), budget_bytes }
+    }
+
+    fn total_bytes(&self) -> usize {
+        self.entries.iter().map(|(_, snapshot)| snapshot.bytes).sum()
+    }
+
+    fn evict_over_budget(&mut self) {
+        while self.total_bytes() > self.budget_bytes {
+            let (evicted, _) = self.entries.remove(0);
+            debug!("DirectoryLru: evicted {}", evicted);
+        }
+    }
+
+    pub fn set_budget(&mut self, budget_bytes: usize) {
+        self.budget_bytes = budget_bytes;
+        self.evict_over_budget();
+    }
+
+    /// Park a directory's window, evicting the least recently used ones over budget
+    pub fn insert(&mut self, directory: &str, snapshot: DirectorySnapshot) {
+        self.entries.retain(|(key, _)| key != directory);
+        if snapshot.bytes > self.budget_bytes {
+            debug!("DirectoryLru: {} ({} bytes) exceeds the budget, not kept", directory, snapshot.bytes);
+            return;
+        }
+        self.entries.push((directory.to_string(), snapshot));
+        self.evict_over_budget();
+    }
+
+    /// Remove and return the snapshot of `directory` if its file list is unchanged
+    pub fn take(&mut self, directory: &str, img_cache: &ImageCache) -> Option<DirectorySnapshot> {
+        let pos = self.entries.iter().position(|(key, _)| key == directory)?;
+        let (_, snapshot) = self.entries.remove(pos);
+        let unchanged = snapshot.image_paths.len() == img_cache.image_paths.len()
+            && snapshot.image_paths.iter().zip(img_cache.image_paths.iter()).all(|(a, b)| a == b.path());
+        unchanged.then_some(snapshot)
+    }
+}
+
+/// Slot of `current_index` in a window of `2 * cache_count + 1`, as placed by `load_single_image`
+pub fn window_slot(current_index: usize, cache_count: usize, num_files: usize) -> usize {
+    if current_index <= cache_count {
+        current_index
+    } else if current_index > (num_files - 1) - cache_count {
+        cache_count + (cache_count - (num_files - 1 - current_index))
+    } else {
+        cache_count
+    }
+}
+
+fn matches_strategy(data: &CachedData, cache_strategy: CacheStrategy, compression_strategy: CompressionStrategy) -> bool {
+    match data {
+        CachedData::Cpu(_) => cache_strategy == CacheStrategy::Cpu,
+        CachedData::Gpu(_) => cache_strategy == CacheStrategy::Gpu && matches!(compression_strategy, CompressionStrategy::None),
+        CachedData::BC1(_) => cache_strategy == CacheStrategy::Gpu && matches!(compression_strategy, CompressionStrategy::Bc1),
+    }
+}
+
+/// Apply a changed `directory_cache_mb` setting
+pub fn set_budget_mb(budget_mb: u64) {
+    if let Ok(mut lru) = RECENT_DIRECTORIES.lock() {
+        lru.set_budget(budget_mb as usize * 1024 * 1024);
+    }
+}
+
+/// Park the window of a pane that is leaving `directory`
+pub fn remember(directory: &str, img_cache: &ImageCache) {
+    if let Some(snapshot) = DirectorySnapshot::from_cache(img_cache) {
+        debug!("Keeping {} decoded images ({} bytes) of {}", snapshot.images.len(), snapshot.bytes, directory);
+        if let Ok(mut lru) = RECENT_DIRECTORIES.lock() {
+            lru.insert(directory, snapshot);
+        }
+    }
+}
+
+/// Fill a freshly created window from a parked snapshot of `directory`
+///
+/// Sets `current_offset` like `load_single_image` would. Returns true when the image at
+/// `current_index` was restored, so the synchronous initial load can be skipped.
+pub fn restore(directory: &str, img_cache: &mut ImageCache, cache_strategy: CacheStrategy) -> bool {
+    let snapshot = match RECENT_DIRECTORIES.lock() {
+        Ok(mut lru) => lru.take(directory, img_cache),
+        Err(_) => None,
+    };
+    let Some(mut snapshot) = snapshot else {
+        return false;
+    };
+
+    let cache_count = img_cache.cache_count;
+    let slot = window_slot(img_cache.current_index, cache_count, img_cache.image_paths.len());
+    let first = img_cache.current_index - slot;
+    let mut restored = 0;
+    for s in 0..img_cache.cached_data.len() {
+        let index = first + s;
+        let Some((data, metadata)) = snapshot.images.remove(&index) else {
+            continue;
+        };
+        if !matches_strategy(&data, cache_strategy, img_cache.compression_strategy) {
+            continue;
+        }
+        img_cache.cached_data[s] = Some(data);
+        img_cache.cached_metadata[s] = metadata;
+        img_cache.cached_image_indices[s] = index as isize;
+        restored += 1;
+    }
+    img_cache.current_offset = slot as isize - cache_count as isize;
+
+    info!("Restored {} cached images of {}", restored, directory);
+    img_cache.cached_image_indices[slot] == img_cache.current_index as isize
+}
+
+/// Whether every slot of the window that maps to an image already holds it
+pub fn is_window_loaded(img_cache: &ImageCache) -> bool {
+    let num_files = img_cache.image_paths.len();
+    if num_files == 0 {
+        return false;
+    }
+    let slot = window_slot(img_cache.current_index, img_cache.cache_count, num_files);
+    let first = img_cache.current_index - slot;
+    (0..img_cache.cached_data.len())
+        .filter(|s| first + s < num_files)
+        .all(|s| img_cache.cached_data[s].is_some() && img_cache.cached_image_indices[s] == (first + s) as isize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(paths: &[&str], bytes: usize) -> DirectorySnapshot {
+        let mut images = HashMap::new();
+        images.insert(0, (CachedData::Cpu(vec![0; bytes]), None));
+        DirectorySnapshot {
+            image_paths: paths.iter().map(std::path::PathBuf::from).collect(),
+            images,
+            bytes,
+        }
+    }
+
+    #[test]
+    fn test_lru_evicts_oldest_over_budget() {
+        let mut lru = DirectoryLru::new(100);
+        lru.insert("a", snapshot(&["a/1.png"], 40));
+        lru.insert("b", snapshot(&["b/1.png"], 40));
+        // Re-inserting refreshes "a", so "b" is the oldest when "c" arrives
+        lru.insert("a", snapshot(&["a/1.png"], 40));
+        lru.insert("c", snapshot(&["c/1.png"], 40));
+        let keys: Vec<&str> = lru.entries.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["a", "c"]);
+
+        // A single directory larger than the budget is not kept
+        lru.insert("d", snapshot(&["d/1.png"], 200));
+        assert!(lru.entries.iter().all(|(k, _)| k != "d"));
+    }
+
+    #[test]
+    fn test_window_slot_matches_single_image_placement() {
+        // cache_count 2, window of 5 over 10 files
+        assert_eq!(window_slot(0, 2, 10), 0);
+        assert_eq!(window_slot(2, 2, 10), 2);
+        assert_eq!(window_slot(5, 2, 10), 2);
+        assert_eq!(window_slot(8, 2, 10), 3);
+        assert_eq!(window_slot(9, 2, 10), 4);
+    }
+}
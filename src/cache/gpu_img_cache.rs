@@ -25,20 +25,29 @@ impl ImageCacheBackend for GpuImageCache {
     fn load_image(
         &self,
         index: usize,
-        image_paths: &[crate::cache::img_cache::PathSource],
+        image_paths: &crate::cache::img_cache::PathIndex,
         compression_strategy: CompressionStrategy,
         archive_cache: Option<&mut crate::archive_cache::ArchiveCache>
     ) -> Result<CachedData, io::Error> {
         if let Some(path_source) = image_paths.get(index) {
-            // Use the safe load_original_image function to prevent crashes with oversized images
-            let img = crate::cache::cache_utils::load_original_image(path_source, archive_cache).map_err(|e| {
-                io::Error::new(io::ErrorKind::InvalidData, format!("Failed to open image: {}", e))
-            })?;
+            // Images larger than the device allows are tiled, or downscaled if even that fails
+            let img = match crate::startup_image::take(path_source) {
+                Some(img) => img,
+                None => crate::cache::cache_utils::decode_original_image(path_source, archive_cache).map_err(|e| {
+                    io::Error::new(io::ErrorKind::InvalidData, format!("Failed to open image: {}", e))
+                })?,
+            };
+            let img = crate::cache::texture_tiles::fit_to_device(img, &self.device);
 
             let rgba_image = img.to_rgba8();
             let (width, height) = img.dimensions();
             let rgba_data = rgba_image.into_raw();
 
+            if let Some(grid) = crate::cache::texture_tiles::grid_for_device(&self.device, width, height) {
+                let texture = crate::cache::texture_tiles::create_tiled_texture(&self.device, &self.queue, &rgba_data, grid);
+                return Ok(CachedData::Gpu(texture.into()));
+            }
+
             // Use our utility function to determine if compression should be used
             let use_compression = crate::cache::cache_utils::should_use_compression(
                 width, height, compression_strategy
@@ -77,7 +86,7 @@ impl ImageCacheBackend for GpuImageCache {
     #[allow(clippy::needless_option_as_deref)]
     fn load_single_image(
         &mut self,
-        image_paths: &[crate::cache::img_cache::PathSource],
+        image_paths: &crate::cache::img_cache::PathIndex,
         cache_count: usize,
         current_index: usize,
         cached_data: &mut Vec<Option<CachedData>>,
@@ -113,8 +122,7 @@ impl ImageCacheBackend for GpuImageCache {
                     // Get dimensions from the loaded texture
                     let (width, height) = match &image {
                         CachedData::Gpu(texture) | CachedData::BC1(texture) => {
-                            let size = texture.size();
-                            (size.width, size.height)
+                            crate::cache::texture_tiles::image_size(texture)
                         },
                         CachedData::Cpu(_) => (0, 0), // Shouldn't happen in GPU cache
                     };
@@ -141,7 +149,7 @@ impl ImageCacheBackend for GpuImageCache {
     #[allow(dead_code)]
     fn load_initial_images(
         &mut self,
-        image_paths: &[crate::cache::img_cache::PathSource],
+        image_paths: &crate::cache::img_cache::PathIndex,
         cache_count: usize,
         current_index: usize,
         cached_data: &mut Vec<Option<CachedData>>,
@@ -184,8 +192,7 @@ impl ImageCacheBackend for GpuImageCache {
                         // Get dimensions from the loaded texture
                         let (width, height) = match &image {
                             CachedData::Gpu(texture) | CachedData::BC1(texture) => {
-                                let size = texture.size();
-                                (size.width, size.height)
+                                crate::cache::texture_tiles::image_size(texture)
                             },
                             CachedData::Cpu(_) => (0, 0), // Shouldn't happen in GPU cache
                         };
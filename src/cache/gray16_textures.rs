@@ -0,0 +1,147 @@
+/// Textures for 16-bit grayscale images
+///
+/// Microscopy frames and depth maps are mostly 16-bit single-channel PNGs or TIFFs whose
+/// values span a small part of that range, so shown as they are they look flat or black.
+/// They are uploaded at full precision, the high byte of each value in the red channel of an
+/// `Rg8Unorm` texture and the low byte in green; filtering the two channels and putting them
+/// back together is the same as filtering the 16-bit values. `texture.wgsl` shows the values
+/// of the window set in the pane footer (`widgets::window_level`) from black to white. The
+/// window starts out at the darkest and brightest values in the image.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use image::DynamicImage;
+use iced_wgpu::wgpu;
+use once_cell::sync::Lazy;
+
+pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg8Unorm;
+
+/// Value range of a 16-bit grayscale image and the part of it shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Levels {
+    /// Darkest and brightest values in the image
+    pub range: (u16, u16),
+    /// Values shown as black and as white
+    pub window: (u16, u16),
+}
+
+struct Gray16Texture {
+    image: Weak<wgpu::Texture>,
+    levels: Levels,
+}
+
+static LEVELS: Lazy<Mutex<HashMap<wgpu::Id<wgpu::Texture>, Gray16Texture>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn is_gray16(texture: &wgpu::Texture) -> bool {
+    texture.format() == FORMAT
+}
+
+/// Darkest and brightest of `values`, at least one apart so a window over them has a width
+fn value_range(values: &[u16]) -> (u16, u16) {
+    let (min, max) = values.iter().fold((u16::MAX, 0), |(min, max), &value| (min.min(value), max.max(value)));
+    if min >= max {
+        let min = min.min(u16::MAX - 1);
+        return (min, min + 1);
+    }
+    (min, max)
+}
+
+/// `Rg8Unorm` texture of a 16-bit grayscale image, or None for other images and ones larger
+/// than the device allows
+pub fn create_texture(device: &wgpu::Device, queue: &wgpu::Queue, image: &DynamicImage) -> Option<Arc<wgpu::Texture>> {
+    let DynamicImage::ImageLuma16(gray) = image else {
+        return None;
+    };
+    let (width, height) = gray.dimensions();
+    let max_dimension = device.limits().max_texture_dimension_2d;
+    if width > max_dimension || height > max_dimension {
+        return None;
+    }
+    let texels: Vec<u8> = gray.as_raw().iter().flat_map(|value| value.to_be_bytes()).collect();
+
+    let size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+    let texture = Arc::new(device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Gray16Texture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: FORMAT,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    }));
+    queue.write_texture(
+        texture.as_image_copy(),
+        &texels,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(width * 2),
+            rows_per_image: None,
+        },
+        size,
+    );
+
+    let range = value_range(gray.as_raw());
+    if let Ok(mut levels) = LEVELS.lock() {
+        levels.retain(|_, gray16| gray16.image.strong_count() > 0);
+        levels.insert(texture.global_id(), Gray16Texture {
+            image: Arc::downgrade(&texture),
+            levels: Levels { range, window: range },
+        });
+    }
+    Some(texture)
+}
+
+pub fn levels(key: wgpu::Id<wgpu::Texture>) -> Option<Levels> {
+    LEVELS.lock().ok()?.get(&key).map(|gray16| gray16.levels)
+}
+
+/// Sets the window of a texture, keeping its black point below its white point
+pub fn set_window(key: wgpu::Id<wgpu::Texture>, low: u16, high: u16) {
+    if let Some(gray16) = LEVELS.lock().ok().as_mut().and_then(|levels| levels.get_mut(&key)) {
+        let low = low.min(u16::MAX - 1);
+        gray16.levels.window = (low, high.max(low + 1));
+    }
+}
+
+/// Window of a texture as fractions of the 16-bit range, as `texture.wgsl` takes it
+pub fn shader_window(texture: &wgpu::Texture) -> [f32; 2] {
+    let (low, high) = levels(texture.global_id()).map_or((0, u16::MAX), |levels| levels.window);
+    [low as f32 / u16::MAX as f32, high as f32 / u16::MAX as f32]
+}
+
+/// Gray level `value` is shown with, as `texture.wgsl` maps it
+fn apply_window(value: u16, (low, high): (u16, u16)) -> u8 {
+    let span = high.saturating_sub(low).max(1) as f32;
+    (value.saturating_sub(low) as f32 / span * 255.0).min(255.0).round() as u8
+}
+
+/// RGBA texels of `Rg8Unorm` texels read back from the GPU, at the texture's window
+pub fn to_rgba(texture: &wgpu::Texture, texels: &[u8]) -> Vec<u8> {
+    let window = levels(texture.global_id()).map_or((0, u16::MAX), |levels| levels.window);
+    texels.chunks_exact(2)
+        .flat_map(|texel| {
+            let gray = apply_window(u16::from_be_bytes([texel[0], texel[1]]), window);
+            [gray, gray, gray, 255]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window() {
+        assert_eq!(value_range(&[900, 120, 4000]), (120, 4000));
+        // A flat image still gets a window one value wide
+        assert_eq!(value_range(&[7, 7]), (7, 8));
+        assert_eq!(value_range(&[u16::MAX]), (u16::MAX - 1, u16::MAX));
+
+        assert_eq!(apply_window(100, (100, 300)), 0);
+        assert_eq!(apply_window(200, (100, 300)), 128);
+        assert_eq!(apply_window(300, (100, 300)), 255);
+        // Values outside the window clip to black and white
+        assert_eq!(apply_window(50, (100, 300)), 0);
+        assert_eq!(apply_window(60000, (100, 300)), 255);
+    }
+}
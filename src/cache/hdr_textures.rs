@@ -0,0 +1,112 @@
+/// Floating-point textures for HDR images
+///
+/// OpenEXR and Radiance HDR files hold linear light, often well past 1.0. Instead of being
+/// clamped to 8 bits they are uploaded as `Rgba16Float` textures, and `texture.wgsl` scales
+/// them by the exposure and gamma of the exposure bar (`widgets::exposure_panel`) before the
+/// usual sRGB output. Images too large for one texture are tone-mapped to 8 bits at load time
+/// with the exposure set then.
+use std::sync::Arc;
+use image::{DynamicImage, RgbaImage};
+use iced_wgpu::wgpu;
+use crate::utils::lut::{f16_to_f32, f32_to_f16};
+
+pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Gamma the sRGB output curve approximates; a gamma setting of this leaves it unchanged
+pub const DISPLAY_GAMMA: f32 = 2.2;
+
+pub fn is_float_image(image: &DynamicImage) -> bool {
+    matches!(image, DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_))
+}
+
+pub fn is_hdr(texture: &wgpu::Texture) -> bool {
+    texture.format() == FORMAT
+}
+
+/// Linear value after `stops` of exposure and the `gamma` setting, as `texture.wgsl` does it
+pub fn expose(value: f32, stops: f32, gamma: f32) -> f32 {
+    (value * stops.exp2()).max(0.0).powf(DISPLAY_GAMMA / gamma)
+}
+
+/// `Rgba16Float` texture of a float image, or None for other images and ones larger than
+/// the device allows
+pub fn create_texture(device: &wgpu::Device, queue: &wgpu::Queue, image: &DynamicImage) -> Option<Arc<wgpu::Texture>> {
+    if !is_float_image(image) {
+        return None;
+    }
+    let (width, height) = (image.width(), image.height());
+    let max_dimension = device.limits().max_texture_dimension_2d;
+    if width > max_dimension || height > max_dimension {
+        return None;
+    }
+    let texels: Vec<u8> = image.to_rgba32f().as_raw().iter()
+        .flat_map(|&value| f32_to_f16(value).to_le_bytes())
+        .collect();
+
+    let size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("HdrTexture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: FORMAT,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    queue.write_texture(
+        texture.as_image_copy(),
+        &texels,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(width * 8),
+            rows_per_image: None,
+        },
+        size,
+    );
+    Some(Arc::new(texture))
+}
+
+fn to_srgb_pixel([r, g, b, a]: [f32; 4], stops: f32, gamma: f32) -> [u8; 4] {
+    let encode = |value: f32| crate::utils::color::encode_srgb(expose(value, stops, gamma));
+    [encode(r), encode(g), encode(b), (a.clamp(0.0, 1.0) * 255.0).round() as u8]
+}
+
+/// 8-bit sRGB image of a float image at the current exposure
+pub fn tone_map(image: &DynamicImage) -> DynamicImage {
+    let (stops, gamma) = crate::widgets::exposure_panel::current();
+    let linear = image.to_rgba32f();
+    let pixels = linear.pixels().flat_map(|pixel| to_srgb_pixel(pixel.0, stops, gamma)).collect();
+    DynamicImage::ImageRgba8(RgbaImage::from_raw(linear.width(), linear.height(), pixels).expect("four bytes per pixel"))
+}
+
+/// 8-bit sRGB RGBA texels of `Rgba16Float` texels read back from the GPU, at the current
+/// exposure
+pub fn to_rgba(texels: &[u8]) -> Vec<u8> {
+    let (stops, gamma) = crate::widgets::exposure_panel::current();
+    texels.chunks_exact(8)
+        .flat_map(|texel| {
+            let channel = |i: usize| f16_to_f32(u16::from_le_bytes([texel[i * 2], texel[i * 2 + 1]]));
+            to_srgb_pixel([channel(0), channel(1), channel(2), channel(3)], stops, gamma)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expose() {
+        assert_eq!(expose(0.25, 2.0, DISPLAY_GAMMA), 1.0);
+        assert_eq!(expose(4.0, -2.0, DISPLAY_GAMMA), 1.0);
+        assert_eq!(expose(-1.0, 0.0, DISPLAY_GAMMA), 0.0);
+        // A higher gamma brightens the midtones
+        assert!(expose(0.2, 0.0, 4.4) > 0.2);
+        assert_eq!(to_srgb_pixel([4.0, 1.0, 0.0, 0.5], 0.0, DISPLAY_GAMMA), [255, 255, 0, 128]);
+
+        // Read back at the default exposure
+        let texels: Vec<u8> = [0.25f32, 8.0, 0.0, 1.0].iter().flat_map(|&value| f32_to_f16(value).to_le_bytes()).collect();
+        assert_eq!(to_rgba(&texels), [crate::utils::color::encode_srgb(0.25), 255, 0, 255]);
+    }
+}
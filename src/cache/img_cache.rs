@@ -4,7 +4,6 @@ use std::time::Instant;
 #[allow(unused_imports)]
 use log::{debug, info, warn, error};
 
-use std::path::PathBuf;
 use std::io;
 use std::collections::VecDeque;
 use std::sync::Arc;
@@ -13,7 +12,7 @@ use iced_wgpu::wgpu;
 
 use crate::file_io::{empty_async_block_vec};
 use crate::loading_status::LoadingStatus;
-use crate::app::Message;
+use crate::app::{Message, IoMessage};
 use crate::pane::Pane;
 use crate::pane;
 use crate::cache::cpu_img_cache::CpuImageCache;
@@ -21,6 +20,8 @@ use crate::cache::gpu_img_cache::GpuImageCache;
 use crate::file_io;
 use iced_wgpu::engine::CompressionStrategy;
 
+pub use viewskater_core::path_source::PathSource;
+pub use viewskater_core::path_index::PathIndex;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum LoadOperation {
@@ -40,6 +41,13 @@ pub enum LoadOperationType {
     LoadPos,
 }
 
+/// Lane a load runs in; background loads step aside while interactive ones are in flight
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum LoadPriority {
+    Interactive,    // Next/previous image for keyboard navigation
+    Background,     // Window population after slider moves, prefetch
+}
+
 impl LoadOperation {
     pub fn operation_type(&self) -> LoadOperationType {
         match self {
@@ -50,6 +58,13 @@ impl LoadOperation {
             LoadOperation::LoadPos(..) => LoadOperationType::LoadPos,
         }
     }
+
+    pub fn priority(&self) -> LoadPriority {
+        match self {
+            LoadOperation::LoadPos(..) => LoadPriority::Background,
+            _ => LoadPriority::Interactive,
+        }
+    }
 }
 
 #[allow(dead_code)]
@@ -134,8 +149,7 @@ impl CachedData {
                 // Use EXIF-aware dimensions to account for orientation
                 crate::exif_utils::get_orientation_aware_dimensions(data)
             },
-            CachedData::Gpu(texture) => (texture.width(), texture.height()),
-            CachedData::BC1(texture) => (texture.width(), texture.height()),
+            CachedData::Gpu(texture) | CachedData::BC1(texture) => crate::cache::texture_tiles::image_size(texture),
         }
     }
 
@@ -157,7 +171,8 @@ impl CachedData {
             CachedData::Gpu(texture) => {
                 let width = texture.width();
                 let height = texture.height();
-                4 * (width as usize) * (height as usize) // 4 bytes per pixel (RGBA8)
+                let layers = texture.depth_or_array_layers();  // Tiles of an oversized image
+                4 * (width as usize) * (height as usize) * (layers as usize) // 4 bytes per pixel (RGBA8)
             }
             CachedData::BC1(texture) => {
                 // BC1 uses 8 bytes per 4x4 block, which is 0.5 bytes per pixel
@@ -196,47 +211,12 @@ impl CachedData {
         }
     }
 }
-/// PathSource enum for type-safe image loading with performance optimization
-#[derive(Clone, Debug)]
-pub enum PathSource {
-    /// Regular filesystem file - direct filesystem I/O
-    Filesystem(PathBuf),
-    /// Archive internal path - requires archive reading
-    Archive(PathBuf),
-    /// Preloaded archive content - available in ArchiveCache HashMap
-    Preloaded(PathBuf),
-}
-
-impl PathSource {
-    /// Get the underlying PathBuf for any variant
-    pub fn path(&self) -> &PathBuf {
-        match self {
-            PathSource::Filesystem(path) => path,
-            PathSource::Archive(path) => path,
-            PathSource::Preloaded(path) => path,
-        }
-    }
-    /// Get filename for display/sorting purposes
-    pub fn file_name(&self) -> std::borrow::Cow<'_, str> {
-        match self {
-            PathSource::Filesystem(_) => {
-                self.path().file_name()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-            },
-            _ => {
-                std::borrow::Cow::from(self.path().display().to_string())
-            }
-        }
-    }
-}
-
 #[allow(dead_code)]
 pub trait ImageCacheBackend {
     fn load_image(
         &self,
         index: usize,
-        image_paths: &[PathSource],
+        image_paths: &PathIndex,
         compression_strategy: CompressionStrategy,
         archive_cache: Option<&mut crate::archive_cache::ArchiveCache>
     ) -> Result<CachedData, io::Error>;
@@ -244,7 +224,7 @@ pub trait ImageCacheBackend {
     #[allow(clippy::too_many_arguments)]
     fn load_single_image(
         &mut self,
-        image_paths: &[PathSource],
+        image_paths: &PathIndex,
         cache_count: usize,
         current_index: usize,
         cached_data: &mut Vec<Option<CachedData>>,
@@ -259,7 +239,7 @@ pub trait ImageCacheBackend {
     #[allow(clippy::too_many_arguments)]
     fn load_initial_images(
         &mut self,
-        image_paths: &[PathSource],
+        image_paths: &PathIndex,
         cache_count: usize,
         current_index: usize,
         cached_data: &mut Vec<Option<CachedData>>,
@@ -287,7 +267,7 @@ pub trait ImageCacheBackend {
 
 
 pub struct ImageCache {
-    pub image_paths: Vec<PathSource>,
+    pub image_paths: PathIndex,
     pub num_files: usize,
     pub current_index: usize,
     pub current_offset: isize,
@@ -307,7 +287,7 @@ pub struct ImageCache {
 impl Default for ImageCache {
     fn default() -> Self {
         ImageCache {
-            image_paths: Vec::new(),
+            image_paths: PathIndex::default(),
             num_files: 0,
             current_index: 0,
             current_offset: 0,
@@ -329,7 +309,7 @@ impl Default for ImageCache {
 #[allow(dead_code)]
 impl ImageCache {
     pub fn new(
-        image_paths: &[PathSource],
+        image_paths: impl Into<PathIndex>,
         cache_count: usize,
         cache_strategy: CacheStrategy,
         compression_strategy: CompressionStrategy,
@@ -337,6 +317,7 @@ impl ImageCache {
         device: Option<Arc<wgpu::Device>>,
         queue: Option<Arc<wgpu::Queue>>,
     ) -> Self {
+        let image_paths = image_paths.into();
         let cache_size = cache_count * 2 + 1;
         let mut cached_data = Vec::new();
         let mut cached_metadata = Vec::new();
@@ -347,8 +328,8 @@ impl ImageCache {
 
         // Initialize the image cache with the basic structure
         let mut image_cache = ImageCache {
-            image_paths: image_paths.to_owned(),
             num_files: image_paths.len(),
+            image_paths,
             current_index: initial_index,
             current_offset: 0,
             cache_count,
@@ -541,7 +522,7 @@ impl ImageCache {
         self.cached_metadata.clear();
         self.cached_image_indices.clear();
         self.cache_states.clear();
-        self.image_paths.clear();
+        self.image_paths = PathIndex::default();
         self.num_files = 0;
         self.current_index = 0;
         self.current_offset = 0;
@@ -604,6 +585,36 @@ impl ImageCache {
         }
     }
 
+    /// Moves the window onto the next image when it is cached, returning it to show
+    pub fn step_next(&mut self) -> Option<CachedData> {
+        let next_slot = usize::try_from(self.get_next_cache_index()).ok()?;
+        // Clone the cached image to release the borrow before the indices move
+        let data = self.get_image_by_index(next_slot).ok()?.clone();
+
+        self.current_offset += 1;
+        if self.current_index < self.image_paths.len() - 1 {
+            self.current_index += 1;
+        }
+        Some(data)
+    }
+
+    /// Moves the window onto the previous image when it is cached; stays put around the edges
+    pub fn step_prev(&mut self) -> Option<CachedData> {
+        let current_slot = self.cache_count as isize + self.current_offset;
+        if current_slot <= 0 || !self.is_some_at_index(current_slot as usize)
+            || !self.is_image_index_within_bounds(current_slot - 1) {
+            return None;
+        }
+        let data = self.get_image_by_index(current_slot as usize - 1).ok()?.clone();
+
+        self.current_offset -= 1;
+        assert!(self.current_offset >= -(self.cache_count as isize)); // Check against actual cache size, not static CONFIG
+        if self.current_index > 0 {
+            self.current_index -= 1;
+        }
+        Some(data)
+    }
+
     pub fn shift_cache_right(
         &mut self, new_item: Option<CachedData>, new_metadata: Option<ImageMetadata>,
     ) {
@@ -918,7 +929,7 @@ pub fn load_images_by_operation_slider(
                 ).await
             };
 
-            Task::perform(images_loading_task, Message::ImagesLoaded)
+            Task::perform(images_loading_task, |result| Message::Io(IoMessage::ImagesLoaded(result)))
         } else {
             Task::none()
         }
@@ -970,8 +981,11 @@ pub fn load_images_by_indices(
         let queue_clone = Arc::clone(queue);
 
         debug!("Task::perform started for {:?}", operation);
+        let interactive_guard = (operation.priority() == LoadPriority::Interactive)
+            .then(crate::loading_status::InteractiveLoadGuard::new);
         Task::perform(
             async move {
+                let _interactive_guard = interactive_guard;
                 let result = file_io::load_images_async(
                     paths,
                     cache_strategy,
@@ -983,7 +997,7 @@ pub fn load_images_by_indices(
                 ).await;
                 result
             },
-            Message::ImagesLoaded,
+            |result| Message::Io(IoMessage::ImagesLoaded(result)),
         )
 
     } else {
@@ -1030,11 +1044,11 @@ pub fn load_images_by_operation(
                 }
                 LoadOperation::ShiftNext((ref _pane_indices, ref _target_indicies)) => {
                     let empty_async_block = empty_async_block_vec(operation, panes.len());
-                    Task::perform(empty_async_block, Message::ImagesLoaded)
+                    Task::perform(empty_async_block, |result| Message::Io(IoMessage::ImagesLoaded(result)))
                 }
                 LoadOperation::ShiftPrevious((ref _pane_indices,  ref _target_indicies)) => {
                     let empty_async_block = empty_async_block_vec(operation, panes.len());
-                    Task::perform(empty_async_block, Message::ImagesLoaded)
+                    Task::perform(empty_async_block, |result| Message::Io(IoMessage::ImagesLoaded(result)))
                 }
                 LoadOperation::LoadPos((ref _pane_indices, _target_indices_and_cache)) => {
                     Task::none()
@@ -1096,3 +1110,158 @@ pub fn load_all_images_in_queue(
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use proptest::prelude::*;
+    use crate::archive_cache::ArchiveCache;
+
+    /// Content of image `index`, so a slot can be checked against the index it claims
+    fn image_bytes(index: usize) -> Vec<u8> {
+        format!("image {}", index).into_bytes()
+    }
+
+    /// One pane's cache window over an in-memory folder, driven the way keyboard
+    /// navigation drives it with every load finishing before the next key press
+    struct Window {
+        cache: ImageCache,
+        archive: ArchiveCache,
+    }
+
+    impl Window {
+        /// Like opening the folder at `index`
+        fn open(num_files: usize, cache_count: usize, index: usize) -> Self {
+            let mut archive = ArchiveCache::new();
+            let paths: Vec<PathSource> = (0..num_files).map(|i| {
+                let name = format!("{:04}.png", i);
+                archive.add_preloaded_data(name.clone(), image_bytes(i));
+                PathSource::Preloaded(PathBuf::from(name))
+            }).collect();
+            let mut cache = ImageCache::new(paths, cache_count, CacheStrategy::Cpu, CompressionStrategy::None, index, None, None);
+            cache.load_initial_images(Some(&mut archive)).unwrap();
+            Self { cache, archive }
+        }
+
+        fn load(&mut self, index: usize) -> Option<CachedData> {
+            Some(self.cache.load_image(index, Some(&mut self.archive)).unwrap())
+        }
+
+        /// LoadNext as `load_next_images_all` enqueues it, or nothing for a ShiftNext
+        fn load_next(&mut self) {
+            let target = self.cache.get_next_image_to_load();
+            if target >= self.cache.num_files || self.cache.current_offset < 0
+                || self.cache.is_operation_blocking(LoadOperationType::LoadNext) {
+                return;
+            }
+            let data = self.load(target);
+            self.cache.move_next(data, None, target as isize).unwrap();
+        }
+
+        /// LoadPrevious as `load_prev_images_all` enqueues it, or nothing for a ShiftPrevious
+        fn load_prev(&mut self) {
+            let target = self.cache.current_index as isize - self.cache.cache_count as isize - self.cache.current_offset - 1;
+            if target < 0 || self.cache.is_operation_blocking(LoadOperationType::LoadPrevious) {
+                return;
+            }
+            let data = self.load(target as usize);
+            self.cache.move_prev(data, None, target).unwrap();
+        }
+
+        fn next(&mut self) {
+            if self.cache.current_index + 1 >= self.cache.num_files {
+                return;
+            }
+            if self.cache.step_next().is_none() {
+                // A miss loads the next image, which the following key press shows
+                self.load_next();
+                assert!(self.cache.step_next().is_some(), "Next image still missing after loading it");
+            }
+            self.load_next();
+        }
+
+        fn prev(&mut self) {
+            if self.cache.current_index == 0 {
+                return;
+            }
+            if self.cache.step_prev().is_none() {
+                self.load_prev();
+                assert!(self.cache.step_prev().is_some(), "Previous image still missing after loading it");
+            }
+            self.load_prev();
+        }
+
+        /// Slots line up with the window around `expected`, and hold the images they name
+        fn check(&self, expected: usize) {
+            let cache = &self.cache;
+            let cache_count = cache.cache_count as isize;
+            assert_eq!(cache.current_index, expected);
+            assert!((-cache_count..=cache_count).contains(&cache.current_offset), "Offset {} out of range", cache.current_offset);
+            assert_eq!(cache.cached_data.len(), cache.cache_count * 2 + 1);
+            assert_eq!(cache.cached_image_indices.len(), cache.cached_data.len());
+
+            let first = expected as isize - cache_count - cache.current_offset;
+            for (slot, data) in cache.cached_data.iter().enumerate() {
+                let index = first + slot as isize;
+                if (0..cache.num_files as isize).contains(&index) {
+                    assert_eq!(cache.cached_image_indices[slot], index, "Slot {} of {:?}", slot, cache.cached_image_indices);
+                    match data {
+                        Some(CachedData::Cpu(bytes)) => assert_eq!(*bytes, image_bytes(index as usize), "Slot {} holds another image", slot),
+                        _ => panic!("Slot {} for image {} is empty", slot, index),
+                    }
+                } else {
+                    assert!(data.is_none(), "Slot {} outside the folder holds an image", slot);
+                }
+            }
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Next,
+        Prev,
+        /// Target taken modulo the folder size
+        Jump(usize),
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            4 => Just(Op::Next),
+            4 => Just(Op::Prev),
+            1 => any::<usize>().prop_map(Op::Jump),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_window_follows_navigation(
+            num_files in 1usize..40,
+            cache_count in 1usize..6,
+            start in any::<usize>(),
+            ops in prop::collection::vec(op(), 0..200),
+        ) {
+            let mut expected = start % num_files;
+            let mut window = Window::open(num_files, cache_count, expected);
+            window.check(expected);
+
+            for op in ops {
+                match op {
+                    Op::Next => {
+                        window.next();
+                        expected = (expected + 1).min(num_files - 1);
+                    }
+                    Op::Prev => {
+                        window.prev();
+                        expected = expected.saturating_sub(1);
+                    }
+                    Op::Jump(target) => {
+                        expected = target % num_files;
+                        window = Window::open(num_files, cache_count, expected);
+                    }
+                }
+                window.check(expected);
+            }
+        }
+    }
+}
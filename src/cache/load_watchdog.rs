@@ -0,0 +1,157 @@
+/// Timeout and retry for image loads that stop making progress
+///
+/// A read from a stalled network mount or a hung archive can block forever, which used to
+/// leave the pane waiting with its spinner running. Each image load is given
+/// `load_timeout_secs`; one that runs over is logged, dropped and started again once, and if
+/// the retry also runs over the slot gets an error placeholder so navigation can go on.
+/// The timer starts once the load holds its decode slot. A read stuck in the kernel can't be
+/// interrupted: its blocking thread is abandoned, keeps its decode slot until it returns and
+/// whatever it returns is discarded.
+use std::future::Future;
+use std::io::Cursor;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use iced_wgpu::engine::CompressionStrategy;
+use iced_wgpu::wgpu;
+use image::{ImageFormat, Rgba, RgbaImage};
+use once_cell::sync::Lazy;
+use log::{error, warn};
+
+use crate::cache::cache_utils;
+use crate::cache::img_cache::{CacheStrategy, CachedData, ImageMetadata};
+use crate::config::CONFIG;
+
+/// Attempts after the first one times out
+pub const LOAD_RETRIES: usize = 1;
+const PLACEHOLDER_WIDTH: u32 = 480;
+const PLACEHOLDER_HEIGHT: u32 = 320;
+
+static LOAD_TIMEOUT_SECS: Lazy<AtomicU64> = Lazy::new(|| AtomicU64::new(CONFIG.load_timeout_secs));
+
+/// Apply a changed `load_timeout_secs` setting; 0 turns the watchdog off
+pub fn set_load_timeout(secs: u64) {
+    LOAD_TIMEOUT_SECS.store(secs, Ordering::Release);
+}
+
+pub fn load_timeout() -> Option<Duration> {
+    match LOAD_TIMEOUT_SECS.load(Ordering::Acquire) {
+        0 => None,
+        secs => Some(Duration::from_secs(secs)),
+    }
+}
+
+/// Runs the load `start` resolves to with `timeout`, starting it again up to `retries` times
+/// when it runs over
+///
+/// `start` waits for whatever slot the load needs, so the timer only runs once it has one.
+/// Returns `None` once every attempt has timed out. `label` names the load in the log.
+pub async fn with_retry<T, Start, Load>(
+    label: &str,
+    timeout: Duration,
+    retries: usize,
+    mut start: impl FnMut() -> Start,
+) -> Option<T>
+where
+    Start: Future<Output = Load>,
+    Load: Future<Output = T>,
+{
+    for tries in 0..=retries {
+        let load = start().await;
+        match tokio::time::timeout(timeout, load).await {
+            Ok(result) => return Some(result),
+            Err(_) if tries < retries => warn!("Loading {} did not finish within {:?}, abandoning it and retrying", label, timeout),
+            Err(_) => error!("Loading {} did not finish within {:?} after {} retries, abandoning it", label, timeout, retries),
+        }
+    }
+    None
+}
+
+/// Dark frame crossed out in red, shown in place of an image that never loaded
+fn placeholder_image() -> RgbaImage {
+    let (width, height) = (PLACEHOLDER_WIDTH as i64, PLACEHOLDER_HEIGHT as i64);
+    // Pixels within this distance of either diagonal, scaled by the width
+    let thickness = 4 * width;
+    RgbaImage::from_fn(PLACEHOLDER_WIDTH, PLACEHOLDER_HEIGHT, |x, y| {
+        let (x, y) = (x as i64, y as i64);
+        let on_diagonal = (x * height - y * width).abs() < thickness
+            || ((width - 1 - x) * height - y * width).abs() < thickness;
+        if on_diagonal { Rgba([200, 60, 60, 255]) } else { Rgba([40, 40, 40, 255]) }
+    })
+}
+
+/// Error placeholder as cached data for `cache_strategy`
+pub fn placeholder(
+    cache_strategy: CacheStrategy,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+) -> Option<(CachedData, ImageMetadata)> {
+    let image = placeholder_image();
+    let data = match cache_strategy {
+        CacheStrategy::Cpu => {
+            let mut bytes = Vec::new();
+            image.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png).ok()?;
+            CachedData::Cpu(bytes)
+        }
+        CacheStrategy::Gpu => {
+            let texture = cache_utils::create_gpu_texture(device, PLACEHOLDER_WIDTH, PLACEHOLDER_HEIGHT, CompressionStrategy::None);
+            cache_utils::upload_uncompressed_texture(queue, &texture, image.as_raw(), PLACEHOLDER_WIDTH, PLACEHOLDER_HEIGHT);
+            CachedData::Gpu(std::sync::Arc::new(texture))
+        }
+    };
+    Some((data, ImageMetadata::new(PLACEHOLDER_WIDTH, PLACEHOLDER_HEIGHT, 0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    // Each attempt resolves to the load it starts
+    #[allow(clippy::async_yields_async)]
+    #[tokio::test]
+    async fn test_with_retry() {
+        let timeout = Duration::from_millis(20);
+
+        // The first attempt hangs, the retry finishes
+        let calls = Cell::new(0);
+        let result = with_retry("stalled", timeout, 1, || {
+            calls.set(calls.get() + 1);
+            let stalled = calls.get() == 1;
+            async move {
+                async move {
+                    if stalled {
+                        std::future::pending::<()>().await;
+                    }
+                    7
+                }
+            }
+        }).await;
+        assert_eq!((result, calls.get()), (Some(7), 2));
+
+        // Every attempt hangs
+        let calls = Cell::new(0);
+        let result = with_retry("stuck", timeout, 1, || {
+            calls.set(calls.get() + 1);
+            async { std::future::pending::<u8>() }
+        }).await;
+        assert_eq!((result, calls.get()), (None, 2));
+
+        // Waiting for a slot doesn't count against the timeout
+        let result = with_retry("queued", timeout, 0, || async {
+            tokio::time::sleep(timeout * 3).await;
+            async { 5 }
+        }).await;
+        assert_eq!(result, Some(5));
+    }
+
+    #[test]
+    fn test_placeholder_is_crossed_out() {
+        let image = placeholder_image();
+        assert_eq!(image.dimensions(), (PLACEHOLDER_WIDTH, PLACEHOLDER_HEIGHT));
+        let red = Rgba([200, 60, 60, 255]);
+        assert_eq!(*image.get_pixel(0, 0), red);
+        assert_eq!(*image.get_pixel(PLACEHOLDER_WIDTH - 1, 0), red);
+        assert_eq!(*image.get_pixel(PLACEHOLDER_WIDTH / 2, PLACEHOLDER_HEIGHT / 2), red);
+        assert_ne!(*image.get_pixel(PLACEHOLDER_WIDTH / 2, 10), red);
+    }
+}
@@ -3,4 +3,15 @@ pub mod cpu_img_cache;
 pub mod gpu_img_cache;
 pub mod cache_utils;
 pub mod texture_cache;
-pub mod compression;
\ No newline at end of file
+pub mod compression;
+pub mod dir_cache;
+pub mod load_watchdog;
+pub mod progressive;
+pub mod texture_tiles;
+pub mod source_strategy;
+pub mod compact_textures;
+pub mod hdr_textures;
+pub mod gray16_textures;
+
+// Shared with other tools through the viewskater-core crate
+pub use viewskater_core::{load_limits, warm_cache};
@@ -0,0 +1,269 @@
+/// Progressive display of large PNGs
+///
+/// The initial image of a pane is normally decoded in full before anything is shown. For
+/// large or Adam7-interlaced PNGs on the uncompressed GPU path, a blank texture is put in
+/// the cache slot instead and a worker decodes the file, sending bands of rows that are
+/// copied into that texture as they arrive. Interlaced files refine pass by pass; plain
+/// ones fill in top to bottom. The worker waits while `PENDING_BANDS` bands are queued, so
+/// a slow UI holds back the decode rather than piling up copies of the image.
+///
+/// Progressive JPEG is out of scope: neither the `image` decoder nor the other JPEG
+/// decoders in the dependency tree expose individual scans, so JPEGs always load in full.
+use std::io::{BufReader, Cursor, Read};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use iced_wgpu::wgpu;
+use iced_wgpu::engine::CompressionStrategy;
+use iced_winit::runtime::Task;
+#[allow(unused_imports)]
+use log::{debug, info, warn, error};
+
+use crate::app::{Message, IoMessage};
+use crate::cache::cache_utils;
+use crate::cache::dir_cache::window_slot;
+use crate::cache::img_cache::{CachedData, CacheStrategy, ImageCache, ImageMetadata, PathSource};
+
+/// Non-interlaced PNGs below this many pixels decode fast enough to load in one go
+const PROGRESSIVE_MIN_PIXELS: u64 = 12_000_000;
+/// Rows per band sent to the texture
+const BAND_ROWS: u32 = 128;
+/// Bands the worker may queue ahead of the UI
+const PENDING_BANDS: usize = 8;
+const MAX_TEXTURE_SIZE: u32 = 8192;
+
+/// Adam7 passes as (x start, y start, x step, y step)
+const ADAM7_PASSES: [(u32, u32, u32, u32); 7] = [
+    (0, 0, 8, 8), (4, 0, 8, 8), (0, 4, 4, 8), (2, 0, 4, 4),
+    (0, 2, 2, 4), (1, 0, 2, 2), (0, 1, 1, 2),
+];
+/// Block each pixel of a pass covers until later passes fill in the gaps
+const ADAM7_BLOCKS: [(u32, u32); 7] = [(8, 8), (4, 8), (4, 4), (2, 4), (2, 2), (1, 2), (1, 1)];
+
+static NEXT_UPLOAD_ID: AtomicU64 = AtomicU64::new(1);
+
+/// A texture being filled in by a decode worker
+pub struct ProgressiveUpload {
+    pub id: u64,
+    pub path: PathBuf,
+    pub texture: Arc<wgpu::Texture>,
+    pub width: u32,
+}
+
+#[derive(Debug, Clone)]
+pub enum ProgressiveEvent {
+    /// Full-width RGBA rows starting at `y`
+    Band { y: u32, rows: u32, rgba: Vec<u8> },
+    Finished(Result<(), String>),
+}
+
+/// Width and height of a PNG worth showing progressively, from its header
+pub fn png_candidate(source: impl Read) -> Option<(u32, u32)> {
+    let reader = png::Decoder::new(source).read_info().ok()?;
+    let info = reader.info();
+    let (width, height) = (info.width, info.height);
+    if width == 0 || height == 0 || width > MAX_TEXTURE_SIZE || height > MAX_TEXTURE_SIZE {
+        return None;
+    }
+    let large = width as u64 * height as u64 >= PROGRESSIVE_MIN_PIXELS;
+    (info.interlaced || large).then_some((width, height))
+}
+
+/// Width and height of each non-empty Adam7 pass, with its pass index
+pub fn adam7_pass_sizes(width: u32, height: u32) -> Vec<(usize, u32, u32)> {
+    let span = |size: u32, start: u32, step: u32| if size > start { (size - start).div_ceil(step) } else { 0 };
+    ADAM7_PASSES.iter().enumerate()
+        .map(|(pass, &(x0, y0, dx, dy))| (pass, span(width, x0, dx), span(height, y0, dy)))
+        .filter(|&(_, w, h)| w > 0 && h > 0)
+        .collect()
+}
+
+/// Convert one row of 8-bit samples to RGBA
+pub fn row_to_rgba(row: &[u8], color_type: png::ColorType) -> Vec<u8> {
+    match color_type {
+        png::ColorType::Rgba => row.to_vec(),
+        png::ColorType::Rgb => row.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        png::ColorType::GrayscaleAlpha => row.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        // Indexed is expanded by the decoder, so only grayscale is left
+        _ => row.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+    }
+}
+
+/// Decode a PNG, calling `emit(y, rows, rgba)` as parts of the image become available
+///
+/// Emits bands of about `BAND_ROWS` rows. Interlaced images emit each pass as it decodes,
+/// from its first row to the last one it reached, with each decoded pixel covering the block
+/// that later passes refine.
+pub fn decode_png_bands(bytes: &[u8], mut emit: impl FnMut(u32, u32, Vec<u8>)) -> Result<(), String> {
+    let mut decoder = png::Decoder::new(Cursor::new(bytes));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let (width, height, interlaced) = {
+        let info = reader.info();
+        (info.width, info.height, info.interlaced)
+    };
+    let (color_type, _) = reader.output_color_type();
+    let stride = width as usize * 4;
+
+    if !interlaced {
+        let mut band = Vec::with_capacity(stride * BAND_ROWS as usize);
+        let mut band_start = 0;
+        for y in 0..height {
+            let row = reader.next_row().map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("PNG ended at row {} of {}", y, height))?;
+            band.extend_from_slice(&row_to_rgba(row.data(), color_type));
+            if y + 1 - band_start == BAND_ROWS || y + 1 == height {
+                emit(band_start, y + 1 - band_start, std::mem::take(&mut band));
+                band_start = y + 1;
+            }
+        }
+        return Ok(());
+    }
+
+    let mut canvas = vec![0u8; stride * height as usize];
+    for (pass, _, pass_height) in adam7_pass_sizes(width, height) {
+        let (x0, y0, dx, dy) = ADAM7_PASSES[pass];
+        let (block_w, block_h) = ADAM7_BLOCKS[pass];
+        let mut band_start = y0;
+        for line in 0..pass_height {
+            let row = reader.next_row().map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("PNG ended in pass {}", pass + 1))?;
+            let rgba = row_to_rgba(row.data(), color_type);
+            let y = y0 + line * dy;
+            for (i, pixel) in rgba.chunks_exact(4).enumerate() {
+                let x = x0 + i as u32 * dx;
+                for by in y..(y + block_h).min(height) {
+                    let start = by as usize * stride + x as usize * 4;
+                    let end = by as usize * stride + (x + block_w).min(width) as usize * 4;
+                    for target in canvas[start..end].chunks_exact_mut(4) {
+                        target.copy_from_slice(pixel);
+                    }
+                }
+            }
+            let reached = (y + block_h).min(height);
+            if reached - band_start >= BAND_ROWS || line + 1 == pass_height {
+                let rows = &canvas[band_start as usize * stride..reached as usize * stride];
+                emit(band_start, reached - band_start, rows.to_vec());
+                band_start = reached;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Put a blank texture for the current image in the cache window
+///
+/// Used in place of `load_single_image`. Returns None when the image is not a
+/// progressive candidate or the cache cannot take a plain GPU texture.
+pub fn begin(img_cache: &mut ImageCache, cache_strategy: CacheStrategy, device: &wgpu::Device) -> Option<ProgressiveUpload> {
+    if cache_strategy != CacheStrategy::Gpu || !matches!(img_cache.compression_strategy, CompressionStrategy::None) {
+        return None;
+    }
+    let Some(PathSource::Filesystem(path)) = img_cache.image_paths.get(img_cache.current_index) else {
+        return None;
+    };
+    let path = path.clone();
+    if !path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("png")) {
+        return None;
+    }
+    // Only the header is read here; the worker reads the whole file
+    let file_size = std::fs::metadata(&path).ok()?.len();
+    let (width, height) = match crate::cache::warm_cache::get(&path) {
+        Some(bytes) => png_candidate(Cursor::new(bytes))?,
+        None => png_candidate(BufReader::new(std::fs::File::open(&path).ok()?))?,
+    };
+
+    let texture = Arc::new(cache_utils::create_gpu_texture(device, width, height, CompressionStrategy::None));
+    let slot = window_slot(img_cache.current_index, img_cache.cache_count, img_cache.image_paths.len());
+    img_cache.cached_data[slot] = Some(CachedData::Gpu(Arc::clone(&texture)));
+    img_cache.cached_metadata[slot] = Some(ImageMetadata::new(width, height, file_size));
+    img_cache.cached_image_indices[slot] = img_cache.current_index as isize;
+    img_cache.current_offset = slot as isize - img_cache.cache_count as isize;
+
+    info!("Showing {} ({}x{}) progressively", path.display(), width, height);
+    Some(ProgressiveUpload {
+        id: NEXT_UPLOAD_ID.fetch_add(1, Ordering::Relaxed),
+        path,
+        texture,
+        width,
+    })
+}
+
+/// Decode the file of `upload` on a worker thread, streaming bands back as messages
+pub fn decode_task(pane_index: usize, upload: &ProgressiveUpload) -> Task<Message> {
+    let (id, path) = (upload.id, upload.path.clone());
+    let (mut sender, receiver) = futures::channel::mpsc::channel(PENDING_BANDS);
+    std::thread::spawn(move || {
+        // Blocks while the queue is full; returns at once when the pane has dropped the receiver
+        let mut send = |event: ProgressiveEvent| {
+            let _ = futures::executor::block_on(futures::SinkExt::send(&mut sender, event));
+        };
+        let result = crate::cache::warm_cache::get(&path)
+            .map_or_else(|| std::fs::read(&path), Ok)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+            .and_then(|bytes| decode_png_bands(&bytes, |y, rows, rgba| {
+                send(ProgressiveEvent::Band { y, rows, rgba });
+            }));
+        send(ProgressiveEvent::Finished(result));
+    });
+    Task::run(receiver, move |event| Message::Io(IoMessage::ProgressiveDecode(pane_index, id, event)))
+}
+
+/// Decode and upload on the calling thread, for reloads that do not run the worker task
+pub fn finish_blocking(upload: &mut Option<ProgressiveUpload>, queue: &wgpu::Queue) {
+    let Some(current) = upload.take() else {
+        return;
+    };
+    let result = crate::cache::warm_cache::get(&current.path)
+        .map_or_else(|| std::fs::read(&current.path), Ok)
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| decode_png_bands(&bytes, |y, rows, rgba| {
+            cache_utils::upload_texture_rows(queue, &current.texture, &rgba, current.width, y, rows);
+        }));
+    if let Err(e) = result {
+        error!("Decoding {} failed: {}", current.path.display(), e);
+    }
+}
+
+/// Copy a band into the texture, or drop the upload once decoding is done
+pub fn handle_event(upload: &mut Option<ProgressiveUpload>, id: u64, event: ProgressiveEvent, queue: &wgpu::Queue) {
+    let Some(current) = upload.as_ref().filter(|current| current.id == id) else {
+        return;     // The pane moved on to another image
+    };
+    match event {
+        ProgressiveEvent::Band { y, rows, rgba } => {
+            cache_utils::upload_texture_rows(queue, &current.texture, &rgba, current.width, y, rows);
+        }
+        ProgressiveEvent::Finished(result) => {
+            if let Err(e) = result {
+                error!("Progressive decode of {} failed: {}", current.path.display(), e);
+            }
+            *upload = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adam7_pass_sizes() {
+        // A full 8x8 block has 1, 1, 2, 4, 8, 16 and 32 pixels per pass
+        let pixels: Vec<u32> = adam7_pass_sizes(8, 8).iter().map(|&(_, w, h)| w * h).collect();
+        assert_eq!(pixels, vec![1, 1, 2, 4, 8, 16, 32]);
+
+        // Tiny images skip the passes that start past their edges
+        let passes: Vec<usize> = adam7_pass_sizes(1, 1).iter().map(|&(pass, _, _)| pass).collect();
+        assert_eq!(passes, vec![0]);
+        let passes: Vec<usize> = adam7_pass_sizes(3, 2).iter().map(|&(pass, _, _)| pass).collect();
+        assert_eq!(passes, vec![0, 3, 5, 6]);
+    }
+
+    #[test]
+    fn test_row_to_rgba() {
+        assert_eq!(row_to_rgba(&[1, 2, 3], png::ColorType::Rgb), vec![1, 2, 3, 255]);
+        assert_eq!(row_to_rgba(&[9, 128], png::ColorType::GrayscaleAlpha), vec![9, 9, 9, 128]);
+        assert_eq!(row_to_rgba(&[7, 8], png::ColorType::Grayscale), vec![7, 7, 7, 255, 8, 8, 8, 255]);
+    }
+}
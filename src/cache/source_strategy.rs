@@ -0,0 +1,235 @@
+/// Cache strategy by the kind of source images come from
+///
+/// Local folders use `cache_strategy` and `compression_strategy`. Archives and folders on
+/// network filesystems can override either with the `archive_*` and `remote_*` settings:
+/// archives are slow to seek and do well fully cached in RAM, while a local NVMe drive keeps
+/// up with GPU-resident textures. The renderer works with one strategy at a time, so opening
+/// a source of another kind switches the app-wide strategy, for both panes.
+///
+/// Network filesystems are recognized from the mount table on Linux, `statfs` on
+/// macOS and UNC paths on Windows.
+use std::path::Path;
+use iced_wgpu::engine::CompressionStrategy;
+use log::warn;
+
+use crate::cache::img_cache::CacheStrategy;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SourceKind {
+    #[default]
+    Local,
+    Archive,
+    Remote,
+}
+
+impl SourceKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            SourceKind::Local => "local folders",
+            SourceKind::Archive => "archives",
+            SourceKind::Remote => "network folders",
+        }
+    }
+}
+
+/// Strategies an archive or remote source uses instead of the local ones, where set
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct StrategyOverride {
+    pub cache: Option<CacheStrategy>,
+    pub compression: Option<CompressionStrategy>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SourceStrategies {
+    pub local: (CacheStrategy, CompressionStrategy),
+    pub archive: StrategyOverride,
+    pub remote: StrategyOverride,
+}
+
+impl SourceStrategies {
+    pub fn new(cache: CacheStrategy, compression: CompressionStrategy) -> Self {
+        Self {
+            local: (cache, compression),
+            archive: StrategyOverride::default(),
+            remote: StrategyOverride::default(),
+        }
+    }
+
+    pub fn resolve(&self, kind: SourceKind) -> (CacheStrategy, CompressionStrategy) {
+        let (cache, compression) = self.local;
+        let overrides = match kind {
+            SourceKind::Local => return self.local,
+            SourceKind::Archive => &self.archive,
+            SourceKind::Remote => &self.remote,
+        };
+        (overrides.cache.unwrap_or(cache), overrides.compression.unwrap_or(compression))
+    }
+
+    /// Makes `strategy` the one for `kind` from now on
+    pub fn set_cache(&mut self, kind: SourceKind, strategy: CacheStrategy) {
+        match kind {
+            SourceKind::Local => self.local.0 = strategy,
+            SourceKind::Archive => self.archive.cache = Some(strategy),
+            SourceKind::Remote => self.remote.cache = Some(strategy),
+        }
+    }
+
+    pub fn set_compression(&mut self, kind: SourceKind, strategy: CompressionStrategy) {
+        match kind {
+            SourceKind::Local => self.local.1 = strategy,
+            SourceKind::Archive => self.archive.compression = Some(strategy),
+            SourceKind::Remote => self.remote.compression = Some(strategy),
+        }
+    }
+}
+
+/// Override from a settings value; empty means "same as local folders"
+pub fn parse_cache_strategy(value: &str) -> Option<CacheStrategy> {
+    match value.to_lowercase().as_str() {
+        "" => None,
+        "cpu" => Some(CacheStrategy::Cpu),
+        "gpu" => Some(CacheStrategy::Gpu),
+        _ => {
+            warn!("Unknown cache strategy '{}', using the local folder one", value);
+            None
+        }
+    }
+}
+
+pub fn parse_compression_strategy(value: &str) -> Option<CompressionStrategy> {
+    match value.to_lowercase().as_str() {
+        "" => None,
+        "none" => Some(CompressionStrategy::None),
+        "bc1" => Some(CompressionStrategy::Bc1),
+        _ => {
+            warn!("Unknown compression strategy '{}', using the local folder one", value);
+            None
+        }
+    }
+}
+
+pub fn cache_strategy_name(strategy: Option<CacheStrategy>) -> &'static str {
+    match strategy {
+        None => "",
+        Some(CacheStrategy::Cpu) => "cpu",
+        Some(CacheStrategy::Gpu) => "gpu",
+    }
+}
+
+pub fn compression_strategy_name(strategy: Option<CompressionStrategy>) -> &'static str {
+    match strategy {
+        None => "",
+        Some(CompressionStrategy::None) => "none",
+        Some(CompressionStrategy::Bc1) => "bc1",
+    }
+}
+
+/// Filesystem types treated as network storage
+const REMOTE_FILESYSTEMS: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb3", "smbfs", "afpfs", "webdav", "davfs", "9p", "afs", "ceph",
+    "glusterfs", "lustre", "fuse.sshfs", "fuse.rclone", "fuse.glusterfs",
+];
+
+/// Type of the filesystem holding `path`, from `/proc/self/mounts` lines
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn mount_fs_type<'a>(mounts: &'a str, path: &Path) -> Option<&'a str> {
+    mounts.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = fields.nth(1)?.replace("\\040", " ").replace("\\011", "\t").replace("\\134", "\\");
+            Some((mount_point, fields.next()?))
+        })
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.len())
+        .map(|(_, fs_type)| fs_type)
+}
+
+/// `\\server\share` or `\\?\UNC\server\share`, but not the `\\?\C:` or `\\.\` device forms
+#[cfg_attr(not(windows), allow(dead_code))]
+fn is_unc_path(path: &str) -> bool {
+    path.starts_with("\\\\?\\UNC\\")
+        || (path.starts_with("\\\\") && !path.starts_with("\\\\?\\") && !path.starts_with("\\\\.\\"))
+}
+
+#[cfg(target_os = "linux")]
+fn is_remote(path: &Path) -> bool {
+    std::fs::read_to_string("/proc/self/mounts").ok()
+        .and_then(|mounts| mount_fs_type(&mounts, path).map(|fs_type| REMOTE_FILESYSTEMS.contains(&fs_type)))
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "macos")]
+fn is_remote(path: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+    let Ok(c_path) = std::ffi::CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return false;
+    }
+    let fs_type = unsafe { std::ffi::CStr::from_ptr(stat.f_fstypename.as_ptr()) };
+    REMOTE_FILESYSTEMS.contains(&fs_type.to_string_lossy().as_ref())
+}
+
+#[cfg(windows)]
+fn is_remote(path: &Path) -> bool {
+    path.to_str().is_some_and(is_unc_path)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn is_remote(_path: &Path) -> bool {
+    false
+}
+
+/// Kind of source `path` (a folder, image or archive) opens
+pub fn source_kind(path: &Path) -> SourceKind {
+    let is_archive = path.extension().is_some_and(|ext| {
+        crate::file_io::ALLOWED_COMPRESSED_FILES.contains(&ext.to_ascii_lowercase().to_str().unwrap_or(""))
+    });
+    if is_archive {
+        SourceKind::Archive
+    } else if is_remote(path) {
+        SourceKind::Remote
+    } else {
+        SourceKind::Local
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_overrides() {
+        let mut strategies = SourceStrategies::new(CacheStrategy::Gpu, CompressionStrategy::None);
+        strategies.archive.cache = Some(CacheStrategy::Cpu);
+        assert_eq!(strategies.resolve(SourceKind::Archive), (CacheStrategy::Cpu, CompressionStrategy::None));
+        assert_eq!(strategies.resolve(SourceKind::Remote), (CacheStrategy::Gpu, CompressionStrategy::None));
+
+        strategies.set_compression(SourceKind::Remote, CompressionStrategy::Bc1);
+        strategies.set_cache(SourceKind::Local, CacheStrategy::Cpu);
+        assert_eq!(strategies.resolve(SourceKind::Remote), (CacheStrategy::Cpu, CompressionStrategy::Bc1));
+        assert_eq!(strategies.resolve(SourceKind::Local), (CacheStrategy::Cpu, CompressionStrategy::None));
+    }
+
+    #[test]
+    fn test_mount_fs_type() {
+        let mounts = "/dev/nvme0n1p2 / ext4 rw,relatime 0 0\n\
+                      server:/export /mnt/photos nfs4 rw 0 0\n\
+                      //nas/share /mnt/my\\040share cifs rw 0 0\n";
+        assert_eq!(mount_fs_type(mounts, Path::new("/home/user/pictures")), Some("ext4"));
+        assert_eq!(mount_fs_type(mounts, Path::new("/mnt/photos/2024/a.jpg")), Some("nfs4"));
+        assert_eq!(mount_fs_type(mounts, Path::new("/mnt/my share/a.jpg")), Some("cifs"));
+        // Mount points match whole components only
+        assert_eq!(mount_fs_type(mounts, Path::new("/mnt/photos2")), Some("ext4"));
+    }
+
+    #[test]
+    fn test_is_unc_path() {
+        assert!(is_unc_path("\\\\nas\\share\\photos"));
+        assert!(is_unc_path("\\\\?\\UNC\\nas\\share"));
+        assert!(!is_unc_path("\\\\?\\C:\\photos"));
+        assert!(!is_unc_path("C:\\photos"));
+    }
+}
@@ -64,7 +64,7 @@ impl TextureCache {
         debug!("TextureCache: Creating new texture (hash: {})", hash);
 
         let load_start = Instant::now();
-        match crate::exif_utils::decode_with_exif_orientation(image_bytes) {
+        match crate::file_io::decode_image_from_bytes(image_bytes) {
             Ok(img) => {
                 let load_time = load_start.elapsed();
                 debug!("TextureCache: Loaded image in {:?}", load_time);
@@ -0,0 +1,234 @@
+/// Textures for images larger than the GPU's texture size limit
+///
+/// Such an image used to be downscaled to fit, losing most of a panorama's detail. Instead it
+/// is split into a grid of tiles, each a layer of one array texture, and `texture.wgsl` picks
+/// the layer and the position within it for every sample. Neighbouring tiles overlap by
+/// [`TILE_MARGIN`] texels, so bilinear taps next to a tile edge read real neighbours rather
+/// than a clamped edge, and no seams show.
+///
+/// The grid of a tiled texture is looked up by its id; code that reads a texture's size goes
+/// through [`image_size`]. The comparison pipelines sample a plain 2D view, so for a tiled
+/// image they only see its first tile.
+use std::collections::HashMap;
+use std::sync::Mutex;
+use image::DynamicImage;
+use iced_wgpu::wgpu;
+use once_cell::sync::Lazy;
+use log::{info, warn};
+
+/// Texels each tile repeats from its neighbours on every side
+pub const TILE_MARGIN: u32 = 1;
+
+static GRIDS: Lazy<Mutex<HashMap<wgpu::Id<wgpu::Texture>, TileGrid>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Layout of an image over the layers of a tiled texture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileGrid {
+    pub width: u32,
+    pub height: u32,
+    pub columns: u32,
+    pub rows: u32,
+    /// Size of each tile, margins included
+    pub tile_width: u32,
+    pub tile_height: u32,
+}
+
+/// Tiles along one axis and their size, margins included
+fn split_axis(length: u32, max_dimension: u32) -> (u32, u32) {
+    let count = length.div_ceil(max_dimension - 2 * TILE_MARGIN);
+    (count, length.div_ceil(count) + 2 * TILE_MARGIN)
+}
+
+impl TileGrid {
+    /// Grid for a `width` x `height` image, or None when it fits one texture or would need
+    /// more than `max_layers` tiles
+    pub fn new(width: u32, height: u32, max_dimension: u32, max_layers: u32) -> Option<Self> {
+        if (width <= max_dimension && height <= max_dimension) || max_dimension <= 2 * TILE_MARGIN {
+            return None;
+        }
+        let (columns, tile_width) = split_axis(width, max_dimension);
+        let (rows, tile_height) = split_axis(height, max_dimension);
+        (columns * rows <= max_layers).then_some(Self { width, height, columns, rows, tile_width, tile_height })
+    }
+
+    pub fn layers(&self) -> u32 {
+        self.columns * self.rows
+    }
+
+    /// Image pixels between the start of one tile and the next, along x and y
+    pub fn stride(&self) -> (u32, u32) {
+        (self.tile_width - 2 * TILE_MARGIN, self.tile_height - 2 * TILE_MARGIN)
+    }
+
+    /// Image pixel shown at texel (`x`, `y`) of tile `layer`; texels past the image edge
+    /// repeat it
+    fn source_pixel(&self, layer: u32, x: u32, y: u32) -> (u32, u32) {
+        let (stride_x, stride_y) = self.stride();
+        let (column, row) = (layer % self.columns, layer / self.columns);
+        let source_x = (column * stride_x + x).saturating_sub(TILE_MARGIN).min(self.width - 1);
+        let source_y = (row * stride_y + y).saturating_sub(TILE_MARGIN).min(self.height - 1);
+        (source_x, source_y)
+    }
+
+    /// RGBA texels of tile `layer`, cut from the image's tightly packed RGBA rows
+    pub fn tile_pixels(&self, rgba: &[u8], layer: u32) -> Vec<u8> {
+        let mut tile = Vec::with_capacity((self.tile_width * self.tile_height * 4) as usize);
+        for y in 0..self.tile_height {
+            for x in 0..self.tile_width {
+                let (source_x, source_y) = self.source_pixel(layer, x, y);
+                let offset = ((source_y * self.width + source_x) * 4) as usize;
+                tile.extend_from_slice(&rgba[offset..offset + 4]);
+            }
+        }
+        tile
+    }
+
+    /// Image RGBA rows put back together from the tiles of every layer, packed in order
+    pub fn assemble(&self, tiles: &[u8]) -> Vec<u8> {
+        let (stride_x, stride_y) = self.stride();
+        let tile_len = (self.tile_width * self.tile_height * 4) as usize;
+        let mut rgba = vec![0; (self.width * self.height * 4) as usize];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (column, row) = (x / stride_x, y / stride_y);
+                let layer = (row * self.columns + column) as usize;
+                let (tile_x, tile_y) = (x - column * stride_x + TILE_MARGIN, y - row * stride_y + TILE_MARGIN);
+                let from = layer * tile_len + ((tile_y * self.tile_width + tile_x) * 4) as usize;
+                let to = ((y * self.width + x) * 4) as usize;
+                rgba[to..to + 4].copy_from_slice(&tiles[from..from + 4]);
+            }
+        }
+        rgba
+    }
+}
+
+/// Grid of a texture made by `create_tiled_texture`, or None for a plain one
+pub fn grid_of(texture: &wgpu::Texture) -> Option<TileGrid> {
+    if texture.depth_or_array_layers() <= 1 {
+        return None;
+    }
+    GRIDS.lock().ok()?.get(&texture.global_id()).copied()
+}
+
+/// Size of the image a texture holds, whether tiled or not
+pub fn image_size(texture: &wgpu::Texture) -> (u32, u32) {
+    match grid_of(texture) {
+        Some(grid) => (grid.width, grid.height),
+        None => (texture.width(), texture.height()),
+    }
+}
+
+/// Grid for an image on `device`, or None when it fits a single texture
+pub fn grid_for_device(device: &wgpu::Device, width: u32, height: u32) -> Option<TileGrid> {
+    let limits = device.limits();
+    TileGrid::new(width, height, limits.max_texture_dimension_2d, limits.max_texture_array_layers)
+}
+
+/// Keep an image at full size when `device` can show it, in one texture or tiled; downscale
+/// it like the CPU path does otherwise
+pub fn fit_to_device(image: DynamicImage, device: &wgpu::Device) -> DynamicImage {
+    let (width, height) = (image.width(), image.height());
+    let max_dimension = device.limits().max_texture_dimension_2d;
+    if (width <= max_dimension && height <= max_dimension) || grid_for_device(device, width, height).is_some() {
+        return image;
+    }
+    warn!("Image {}x{} needs more tiles than the GPU allows", width, height);
+    crate::cache::cache_utils::check_and_resize_if_oversized(image)
+}
+
+/// Uploads RGBA rows as a tiled array texture with a layer per tile
+pub fn create_tiled_texture(device: &wgpu::Device, queue: &wgpu::Queue, rgba: &[u8], grid: TileGrid) -> wgpu::Texture {
+    info!("Splitting {}x{} image into {}x{} tiles of {}x{}",
+        grid.width, grid.height, grid.columns, grid.rows, grid.tile_width, grid.tile_height);
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("TiledTexture"),
+        size: wgpu::Extent3d {
+            width: grid.tile_width,
+            height: grid.tile_height,
+            depth_or_array_layers: grid.layers(),
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+
+    for layer in 0..grid.layers() {
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x: 0, y: 0, z: layer },
+                aspect: wgpu::TextureAspect::All,
+            },
+            &grid.tile_pixels(rgba, layer),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(grid.tile_width * 4),
+                rows_per_image: None,
+            },
+            wgpu::Extent3d {
+                width: grid.tile_width,
+                height: grid.tile_height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    if let Ok(mut grids) = GRIDS.lock() {
+        grids.insert(texture.global_id(), grid);
+    }
+    texture
+}
+
+/// 2D view of a texture's first layer, for pipelines that don't know about tiles
+pub fn first_layer_view(texture: &wgpu::Texture) -> wgpu::TextureView {
+    texture.create_view(&wgpu::TextureViewDescriptor {
+        dimension: Some(wgpu::TextureViewDimension::D2),
+        array_layer_count: Some(1),
+        ..Default::default()
+    })
+}
+
+/// Array view over every tile of a texture; a plain texture is a single tile
+pub fn tiles_view(texture: &wgpu::Texture) -> wgpu::TextureView {
+    texture.create_view(&wgpu::TextureViewDescriptor {
+        dimension: Some(wgpu::TextureViewDimension::D2Array),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_fits_device_limits() {
+        assert_eq!(TileGrid::new(8192, 4000, 8192, 256), None);
+
+        let grid = TileGrid::new(20_000, 5_000, 8192, 256).unwrap();
+        assert_eq!((grid.columns, grid.rows), (3, 1));
+        assert!(grid.tile_width <= 8192 && grid.tile_height <= 8192);
+        assert!(grid.columns * grid.stride().0 >= 20_000 && grid.rows * grid.stride().1 >= 5_000);
+
+        // More tiles than layers
+        assert_eq!(TileGrid::new(200_000, 200_000, 8192, 256), None);
+    }
+
+    #[test]
+    fn test_tiles_round_trip() {
+        let grid = TileGrid::new(11, 7, 6, 16).unwrap();
+        assert_eq!((grid.columns, grid.rows), (3, 2));
+        let rgba: Vec<u8> = (0..11 * 7).flat_map(|i| [i as u8, (i * 3) as u8, 7, 255]).collect();
+        let tiles: Vec<u8> = (0..grid.layers()).flat_map(|layer| grid.tile_pixels(&rgba, layer)).collect();
+        assert_eq!(grid.assemble(&tiles), rgba);
+
+        // The margin repeats the neighbouring tile's edge
+        let (stride_x, _) = grid.stride();
+        let second = grid.tile_pixels(&rgba, 1);
+        let left_neighbour = (stride_x - 1) as usize * 4;
+        assert_eq!(&second[..4], &rgba[left_neighbour..left_neighbour + 4]);
+    }
+}
@@ -0,0 +1,203 @@
+/// Per-channel difference of the two panes over the visible region
+///
+/// While both panes show images of the same size, each pane's footer gets the mean and max
+/// absolute R/G/B difference between the two images over the part that pane is showing, in
+/// 0-255 levels. The difference is rendered on the GPU from the textures already on screen,
+/// sampled on a grid of at most `MAX_SAMPLES` per side and read back, so it follows panning
+/// and zooming without decoding anything; max is the largest difference among those samples.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use iced_wgpu::wgpu;
+use iced_winit::runtime::Task;
+use once_cell::sync::Lazy;
+
+use crate::app::{DataViewer, Message};
+use crate::cache::texture_tiles::grid_of;
+use crate::menu::PaneLayout;
+
+const MAX_PANES: usize = 2;
+/// Longest side of the sample grid
+const MAX_SAMPLES: u32 = 256;
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Mean and max absolute difference per channel, in 0-255 levels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelDiff {
+    pub mean: [f32; 3],
+    pub max: [u8; 3],
+}
+
+/// Textures and region a difference was computed for: texture addresses, and the region in
+/// thousandths of the image size
+type DiffKey = (usize, usize, [u32; 4]);
+
+static RESULTS: Lazy<Mutex<[Option<(DiffKey, Option<ChannelDiff>)>; MAX_PANES]>> =
+    Lazy::new(|| Mutex::new([None; MAX_PANES]));
+
+#[derive(Debug, Clone)]
+pub enum ChannelDiffMessage {
+    SetEnabled(bool),
+    /// Recompute for panes whose view changed since the last frame
+    Refresh,
+}
+
+impl From<ChannelDiffMessage> for Message {
+    fn from(msg: ChannelDiffMessage) -> Self {
+        Message::ChannelDiffAction(msg)
+    }
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Mean and max of each channel of row-major RGBA difference pixels
+pub fn summarize(rgba: &[u8]) -> ChannelDiff {
+    let mut sum = [0u64; 3];
+    let mut max = [0u8; 3];
+    let mut count = 0u64;
+    for pixel in rgba.chunks_exact(4) {
+        for c in 0..3 {
+            sum[c] += pixel[c] as u64;
+            max[c] = max[c].max(pixel[c]);
+        }
+        count += 1;
+    }
+    let mean = sum.map(|s| s as f32 / count.max(1) as f32);
+    ChannelDiff { mean, max }
+}
+
+/// Sample grid for `region` of an image of `image_size`: the region's aspect ratio, at most
+/// `MAX_SAMPLES` per side and never more samples than the region has pixels
+pub fn sample_size(region: [f32; 4], image_size: (u32, u32)) -> (u32, u32) {
+    let width = ((region[2] - region[0]) * image_size.0 as f32).max(0.0);
+    let height = ((region[3] - region[1]) * image_size.1 as f32).max(0.0);
+    if width < 1.0 || height < 1.0 {
+        return (0, 0);
+    }
+    let scale = (MAX_SAMPLES as f32 / width.max(height)).min(1.0);
+    let fit = |length: f32| ((length * scale).round() as u32).max(1);
+    (fit(width), fit(height))
+}
+
+/// The two pane textures to compare, when both panes show untiled images of the same size
+fn textures(app: &DataViewer) -> Option<(Arc<wgpu::Texture>, Arc<wgpu::Texture>)> {
+    if !is_enabled() || app.pane_layout != PaneLayout::DualPane || app.panes.len() < MAX_PANES {
+        return None;
+    }
+    let texture = |index: usize| {
+        let pane = &app.panes[index];
+        pane.dir_loaded.then(|| pane.scene.as_ref()?.get_texture().cloned()).flatten()
+    };
+    let (first, second) = (texture(0)?, texture(1)?);
+    // The diff pipeline samples a single layer, which is only one tile of a tiled image
+    let tiled = grid_of(&first).is_some() || grid_of(&second).is_some();
+    (!tiled && first.size() == second.size()).then_some((first, second))
+}
+
+fn key(textures: &(Arc<wgpu::Texture>, Arc<wgpu::Texture>), region: [f32; 4]) -> DiffKey {
+    (
+        Arc::as_ptr(&textures.0) as usize,
+        Arc::as_ptr(&textures.1) as usize,
+        region.map(|fraction| (fraction * 1000.0).round() as u32),
+    )
+}
+
+fn region(pane_index: usize) -> [f32; 4] {
+    crate::noise_analysis::visible_region(pane_index).unwrap_or([0.0, 0.0, 1.0, 1.0])
+}
+
+/// Whether a pane's view changed since its difference was computed; checked after each frame
+pub fn needs_refresh(app: &DataViewer) -> bool {
+    let Ok(results) = RESULTS.lock() else {
+        return false;
+    };
+    match textures(app) {
+        Some(textures) => (0..MAX_PANES).any(|pane_index| {
+            results[pane_index].as_ref().map(|(k, _)| *k) != Some(key(&textures, region(pane_index)))
+        }),
+        None => results.iter().any(Option::is_some),
+    }
+}
+
+fn refresh(app: &DataViewer) {
+    let textures = textures(app);
+    let Ok(mut results) = RESULTS.lock() else {
+        return;
+    };
+    let Some(textures) = textures else {
+        *results = [None; MAX_PANES];
+        return;
+    };
+    let size = textures.0.size();
+    for (pane_index, result) in results.iter_mut().enumerate() {
+        let region = region(pane_index);
+        let key = key(&textures, region);
+        if result.as_ref().map(|(k, _)| *k) == Some(key) {
+            continue;
+        }
+        // Kept even when the readback fails, so a failing pane isn't retried every frame
+        let diff = crate::widgets::shader::diff_readback::read_difference(
+            &app.device,
+            &app.queue,
+            &textures.0,
+            &textures.1,
+            region,
+            sample_size(region, (size.width, size.height)),
+        )
+        .map(|pixels| summarize(&pixels));
+        *result = Some((key, diff));
+    }
+}
+
+pub fn handle_channel_diff_message(app: &mut DataViewer, msg: ChannelDiffMessage) -> Task<Message> {
+    match msg {
+        ChannelDiffMessage::SetEnabled(enabled) => {
+            ENABLED.store(enabled, Ordering::Relaxed);
+            refresh(app);
+        }
+        ChannelDiffMessage::Refresh => refresh(app),
+    }
+    Task::none()
+}
+
+/// `metadata` with the pane's difference appended, for its footer
+pub fn with_footer_text(metadata: Option<String>, pane_index: usize) -> Option<String> {
+    let diff = RESULTS.lock().ok()
+        .and_then(|results| results.get(pane_index).copied().flatten())
+        .and_then(|(_, diff)| diff);
+    let Some(diff) = diff else {
+        return metadata;
+    };
+    let text = format!(
+        "diff R {:.1}/{}  G {:.1}/{}  B {:.1}/{}",
+        diff.mean[0], diff.max[0], diff.mean[1], diff.max[1], diff.mean[2], diff.max[2]
+    );
+    Some(match metadata {
+        Some(metadata) => format!("{}  {}", metadata, text),
+        None => text,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize() {
+        let rgba = [10, 0, 255, 255, 20, 4, 1, 255];
+        let diff = summarize(&rgba);
+        assert_eq!(diff.mean, [15.0, 2.0, 128.0]);
+        assert_eq!(diff.max, [20, 4, 255]);
+    }
+
+    #[test]
+    fn test_sample_size() {
+        // Zoomed out: the whole 4000x3000 image is capped at 256 on the long side
+        assert_eq!(sample_size([0.0, 0.0, 1.0, 1.0], (4000, 3000)), (256, 192));
+        // Zoomed in on 100x50 pixels: one sample per pixel
+        assert_eq!(sample_size([0.5, 0.5, 0.525, 0.5 + 50.0 / 3000.0], (4000, 3000)), (100, 50));
+        assert_eq!(sample_size([0.5, 0.5, 0.5, 0.6], (4000, 3000)), (0, 0));
+    }
+}
@@ -7,6 +7,7 @@ use std::path::PathBuf;
 use log::{info, warn};
 
 use super::parser::{CocoDataset, ImageAnnotation};
+use super::crop_export::CropJob;
 
 /// Manages COCO annotations for the current session
 pub struct AnnotationManager {
@@ -15,6 +16,65 @@ pub struct AnnotationManager {
 
     /// Path to the currently loaded COCO JSON file
     current_json_path: Option<PathBuf>,
+
+    /// Active filter for jump-to-annotation navigation
+    jump_filter: Option<AnnotationFilter>,
+
+    /// Which annotations the overlay draws
+    overlay_filter: OverlayFilter,
+}
+
+/// Which images jump-to-annotation navigation should stop at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationFilter {
+    /// Images with at least one annotation of this category id
+    Category(u64),
+    /// Images with no annotations at all
+    Unannotated,
+    /// Images with an annotation the overlay filter flags
+    Flagged,
+}
+
+/// Boxes within this many pixels of an image edge count as touching it
+const BORDER_MARGIN: f32 = 1.0;
+
+/// Overlay filter for auditing label noise: when active, only annotations that are tiny or
+/// touch the image border are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct OverlayFilter {
+    /// Flag boxes whose area is below this many square pixels
+    pub max_area: Option<f32>,
+    /// Flag boxes touching an image edge
+    pub touching_border: bool,
+}
+
+impl OverlayFilter {
+    pub fn is_active(&self) -> bool {
+        self.max_area.is_some() || self.touching_border
+    }
+
+    /// Whether an annotation meets any enabled criterion; image edges are only checked when
+    /// the image size is known
+    pub fn flags(&self, annotation: &ImageAnnotation, image_size: (u32, u32)) -> bool {
+        let bbox = &annotation.bbox;
+        let tiny = self.max_area.is_some_and(|max_area| bbox.width * bbox.height < max_area);
+        let (width, height) = (image_size.0 as f32, image_size.1 as f32);
+        let touching = self.touching_border && width > 0.0 && height > 0.0 && (
+            bbox.x <= BORDER_MARGIN
+                || bbox.y <= BORDER_MARGIN
+                || bbox.x + bbox.width >= width - BORDER_MARGIN
+                || bbox.y + bbox.height >= height - BORDER_MARGIN
+        );
+        tiny || touching
+    }
+
+    /// The annotations to draw: all of them while the filter is off
+    pub fn apply(&self, annotations: &[ImageAnnotation], image_size: (u32, u32)) -> Vec<ImageAnnotation> {
+        annotations.iter()
+            .filter(|annotation| !self.is_active() || self.flags(annotation, image_size))
+            .cloned()
+            .collect()
+    }
 }
 
 /// A loaded COCO dataset with its associated directory
@@ -23,7 +83,6 @@ struct LoadedDataset {
     dataset: CocoDataset,
 
     /// Image directory associated with this dataset
-    #[allow(dead_code)]
     image_directory: PathBuf,
 
     /// Cached lookup map: filename -> annotations
@@ -31,6 +90,9 @@ struct LoadedDataset {
 
     /// Set of image IDs that had invalid annotations
     images_with_invalid_annos: std::collections::HashSet<u64>,
+
+    /// Image sizes from the JSON, by filename
+    image_sizes: HashMap<String, (u32, u32)>,
 }
 
 impl AnnotationManager {
@@ -39,6 +101,8 @@ impl AnnotationManager {
         Self {
             current_dataset: None,
             current_json_path: None,
+            jump_filter: None,
+            overlay_filter: OverlayFilter::default(),
         }
     }
 
@@ -106,12 +170,16 @@ impl AnnotationManager {
 
         // Build the annotation lookup map
         let annotation_map = dataset.build_image_annotation_map();
+        let image_sizes = dataset.images.iter()
+            .map(|image| (image.file_name.clone(), (image.width, image.height)))
+            .collect();
 
         self.current_dataset = Some(LoadedDataset {
             dataset,
             image_directory,
             annotation_map,
             images_with_invalid_annos: images_with_invalid,
+            image_sizes,
         });
         self.current_json_path = Some(json_path);
 
@@ -240,10 +308,101 @@ impl AnnotationManager {
         false
     }
 
+    /// Categories in the loaded dataset as (id, name), sorted by id
+    pub fn categories(&self) -> Vec<(u64, String)> {
+        let mut categories: Vec<(u64, String)> = self.current_dataset.as_ref()
+            .map(|ds| ds.dataset.categories.iter().map(|c| (c.id, c.name.clone())).collect())
+            .unwrap_or_default();
+        categories.sort_by_key(|(id, _)| *id);
+        categories
+    }
+
+    /// Number of annotations of each category in the loaded dataset
+    pub fn category_counts(&self) -> HashMap<u64, usize> {
+        let mut counts = HashMap::new();
+        if let Some(ds) = &self.current_dataset {
+            for annotation in &ds.dataset.annotations {
+                *counts.entry(annotation.category_id).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// Look up a category name by id
+    pub fn category_name(&self, category_id: u64) -> Option<&str> {
+        self.current_dataset.as_ref()
+            .and_then(|ds| ds.dataset.categories.iter().find(|c| c.id == category_id))
+            .map(|c| c.name.as_str())
+    }
+
+    /// Get the active jump filter
+    pub fn jump_filter(&self) -> Option<AnnotationFilter> {
+        self.jump_filter
+    }
+
+    /// Set the active jump filter
+    pub fn set_jump_filter(&mut self, filter: Option<AnnotationFilter>) {
+        self.jump_filter = filter;
+    }
+
+    /// Get the active overlay filter
+    pub fn overlay_filter(&self) -> OverlayFilter {
+        self.overlay_filter
+    }
+
+    /// Set the active overlay filter
+    pub fn set_overlay_filter(&mut self, filter: OverlayFilter) {
+        self.overlay_filter = filter;
+    }
+
+    /// Check whether an image (by filename) passes the given filter
+    pub fn image_matches(&self, filename: &str, filter: AnnotationFilter) -> bool {
+        let annotations = self.get_annotations(filename);
+        match filter {
+            AnnotationFilter::Category(category_id) => annotations
+                .is_some_and(|annos| annos.iter().any(|a| a.category_id == category_id)),
+            AnnotationFilter::Unannotated => !annotations.is_some_and(|annos| !annos.is_empty()),
+            AnnotationFilter::Flagged => {
+                let image_size = self.current_dataset.as_ref()
+                    .and_then(|ds| ds.image_sizes.get(filename).copied())
+                    .unwrap_or((0, 0));
+                self.overlay_filter.is_active() && annotations
+                    .is_some_and(|annos| annos.iter().any(|a| self.overlay_filter.flags(a, image_size)))
+            }
+        }
+    }
+
+    /// Build crop export jobs for every annotated image, or only for `ids` within one image
+    pub fn crop_jobs(&self, only: Option<(&str, &[u64])>) -> Vec<CropJob> {
+        let Some(ds) = &self.current_dataset else {
+            return Vec::new();
+        };
+
+        match only {
+            Some((filename, ids)) => ds.annotation_map.get(filename)
+                .map(|annos| CropJob {
+                    image_path: ds.image_directory.join(filename),
+                    annotations: annos.iter().filter(|a| ids.contains(&a.id)).cloned().collect(),
+                })
+                .filter(|job| !job.annotations.is_empty())
+                .into_iter()
+                .collect(),
+            None => ds.annotation_map.iter()
+                .filter(|(_, annos)| !annos.is_empty())
+                .map(|(filename, annos)| CropJob {
+                    image_path: ds.image_directory.join(filename),
+                    annotations: annos.clone(),
+                })
+                .collect(),
+        }
+    }
+
     /// Clear the currently loaded dataset
     pub fn clear(&mut self) {
         self.current_dataset = None;
         self.current_json_path = None;
+        self.jump_filter = None;
+        self.overlay_filter = OverlayFilter::default();
         info!("Cleared COCO annotations");
     }
 }
@@ -273,4 +432,39 @@ mod tests {
         assert!(!manager.has_annotations());
         assert!(manager.get_annotations("test.jpg").is_none());
     }
+
+    #[test]
+    fn test_unannotated_filter_without_dataset() {
+        let manager = AnnotationManager::new();
+        assert!(manager.image_matches("test.jpg", AnnotationFilter::Unannotated));
+        assert!(!manager.image_matches("test.jpg", AnnotationFilter::Category(1)));
+    }
+
+    #[test]
+    fn test_overlay_filter_flags() {
+        use super::super::parser::BoundingBox;
+        let annotation = |x: f32, y: f32, width: f32, height: f32| ImageAnnotation {
+            id: 1,
+            bbox: BoundingBox { x, y, width, height },
+            category_id: 1,
+            category_name: "person".to_string(),
+            segmentation: None,
+            score: None,
+            area: width * height,
+        };
+        let tiny = OverlayFilter { max_area: Some(16.0 * 16.0), touching_border: false };
+        assert!(tiny.flags(&annotation(50.0, 50.0, 10.0, 20.0), (100, 100)));
+        assert!(!tiny.flags(&annotation(50.0, 50.0, 16.0, 16.0), (100, 100)));
+
+        let border = OverlayFilter { max_area: None, touching_border: true };
+        assert!(border.flags(&annotation(0.5, 40.0, 20.0, 20.0), (100, 100)));
+        assert!(border.flags(&annotation(40.0, 40.0, 20.0, 59.5), (100, 100)));
+        assert!(!border.flags(&annotation(40.0, 40.0, 20.0, 20.0), (100, 100)));
+        // Edges unknown without an image size
+        assert!(!border.flags(&annotation(40.0, 40.0, 20.0, 59.5), (0, 0)));
+
+        let boxes = [annotation(0.0, 0.0, 50.0, 50.0), annotation(40.0, 40.0, 20.0, 20.0)];
+        assert_eq!(OverlayFilter::default().apply(&boxes, (100, 100)).len(), 2);
+        assert_eq!(border.apply(&boxes, (100, 100)).len(), 1);
+    }
 }
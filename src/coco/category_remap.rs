@@ -0,0 +1,536 @@
+/// Category rename, merge and delete dialog for the loaded COCO file
+///
+/// Each category of the loaded file gets a name field and a delete switch. Giving a category
+/// the name of another one merges them: the annotations of both end up in the category that
+/// kept its name (or the one with the lowest id), and the other category is dropped. Deleting
+/// a category removes its annotations too. The dialog previews how many annotations each
+/// change touches, and "Save As..." writes the result to a new JSON file, leaving the opened
+/// one as it was, then shows the new file's annotations.
+///
+/// The file is rewritten as JSON values rather than through `CocoDataset`, so fields the
+/// viewer doesn't read (`info`, `licenses`, extra keys on annotations) are kept.
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::font::Font;
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, column, container, text, button, text_input, scrollable, Space, Column};
+use log::{info, error};
+use serde_json::Value;
+
+use crate::app::{DataViewer, Message};
+use super::widget::CocoMessage;
+
+/// What happens to one category
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Rename(String),
+    /// Annotations move to this category and the category is dropped
+    MergeInto(u64),
+    /// The category and its annotations are dropped
+    Delete,
+}
+
+/// Name field and delete switch of one category row
+#[derive(Debug, Clone)]
+pub struct CategoryEdit {
+    pub id: u64,
+    pub name: String,
+    pub delete: bool,
+}
+
+/// Counts of what a remap changes
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Summary {
+    pub renamed: usize,
+    pub removed_categories: usize,
+    /// Annotations moved to another category
+    pub merged: usize,
+    /// Annotations dropped with a deleted category
+    pub deleted: usize,
+}
+
+/// Outcome of each changed category, from the original `(id, name)` list and the edited rows
+pub fn plan(original: &[(u64, String)], edits: &[CategoryEdit]) -> Result<BTreeMap<u64, Outcome>, String> {
+    let original_names: HashMap<u64, &str> = original.iter().map(|(id, name)| (*id, name.as_str())).collect();
+    let kept_name = |edit: &CategoryEdit| original_names.get(&edit.id) == Some(&edit.name.trim());
+
+    let kept: Vec<&CategoryEdit> = edits.iter().filter(|edit| !edit.delete).collect();
+    if let Some(edit) = kept.iter().find(|edit| edit.name.trim().is_empty()) {
+        let name = original_names.get(&edit.id).copied().unwrap_or_default();
+        return Err(format!("Category \"{}\" needs a name", name));
+    }
+
+    // Categories sharing a name merge into the one that kept it, else into the lowest id
+    let mut survivors: HashMap<&str, u64> = HashMap::new();
+    for edit in kept.iter().filter(|edit| kept_name(edit)) {
+        survivors.entry(edit.name.trim()).or_insert(edit.id);
+    }
+    for edit in &kept {
+        survivors.entry(edit.name.trim()).or_insert(edit.id);
+    }
+
+    let mut outcomes = BTreeMap::new();
+    for edit in edits {
+        let outcome = if edit.delete {
+            Outcome::Delete
+        } else {
+            match survivors[edit.name.trim()] {
+                survivor if survivor != edit.id => Outcome::MergeInto(survivor),
+                _ if kept_name(edit) => continue,
+                _ => Outcome::Rename(edit.name.trim().to_string()),
+            }
+        };
+        outcomes.insert(edit.id, outcome);
+    }
+    Ok(outcomes)
+}
+
+/// What applying `outcomes` changes, given the annotation count of each category
+pub fn preview(outcomes: &BTreeMap<u64, Outcome>, counts: &HashMap<u64, usize>) -> Summary {
+    let mut summary = Summary::default();
+    for (id, outcome) in outcomes {
+        let count = counts.get(id).copied().unwrap_or(0);
+        match outcome {
+            Outcome::Rename(_) => summary.renamed += 1,
+            Outcome::MergeInto(_) => {
+                summary.removed_categories += 1;
+                summary.merged += count;
+            }
+            Outcome::Delete => {
+                summary.removed_categories += 1;
+                summary.deleted += count;
+            }
+        }
+    }
+    summary
+}
+
+/// Rewrites the categories and annotations of a parsed COCO file
+pub fn apply(json: &mut Value, outcomes: &BTreeMap<u64, Outcome>) -> Result<Summary, String> {
+    let root = json.as_object_mut().ok_or("Not a COCO file: the top level isn't an object")?;
+    let mut summary = Summary::default();
+
+    if let Some(Value::Array(annotations)) = root.get_mut("annotations") {
+        annotations.retain_mut(|annotation| {
+            let Some(id) = annotation.get("category_id").and_then(Value::as_u64) else {
+                return true;
+            };
+            match outcomes.get(&id) {
+                Some(Outcome::Delete) => {
+                    summary.deleted += 1;
+                    false
+                }
+                Some(Outcome::MergeInto(target)) => {
+                    annotation["category_id"] = Value::from(*target);
+                    summary.merged += 1;
+                    true
+                }
+                _ => true,
+            }
+        });
+    }
+
+    let Some(Value::Array(categories)) = root.get_mut("categories") else {
+        return Err("Not a COCO file: no categories".to_string());
+    };
+    categories.retain_mut(|category| {
+        let Some(id) = category.get("id").and_then(Value::as_u64) else {
+            return true;
+        };
+        match outcomes.get(&id) {
+            Some(Outcome::Delete | Outcome::MergeInto(_)) => {
+                summary.removed_categories += 1;
+                false
+            }
+            Some(Outcome::Rename(name)) => {
+                category["name"] = Value::from(name.as_str());
+                summary.renamed += 1;
+                true
+            }
+            None => true,
+        }
+    });
+    Ok(summary)
+}
+
+fn remap_file(source: &Path, output: &Path, outcomes: &BTreeMap<u64, Outcome>) -> Result<Summary, String> {
+    let contents = std::fs::read(source).map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+    let mut json: Value = serde_json::from_slice(&contents).map_err(|e| format!("Failed to parse JSON: {}", e))?;
+    let summary = apply(&mut json, outcomes)?;
+    let bytes = serde_json::to_vec(&json).map_err(|e| e.to_string())?;
+    std::fs::write(output, bytes).map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+    Ok(summary)
+}
+
+fn describe(summary: &Summary) -> String {
+    let mut parts = Vec::new();
+    if summary.renamed > 0 {
+        parts.push(format!("{} renamed", summary.renamed));
+    }
+    if summary.removed_categories > 0 {
+        parts.push(format!("{} categories removed", summary.removed_categories));
+    }
+    if summary.merged > 0 {
+        parts.push(format!("{} annotations moved", summary.merged));
+    }
+    if summary.deleted > 0 {
+        parts.push(format!("{} annotations deleted", summary.deleted));
+    }
+    if parts.is_empty() {
+        "No changes".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CategoryRemapState {
+    pub visible: bool,
+    /// `(id, name)` of the loaded file's categories, by id
+    original: Vec<(u64, String)>,
+    edits: Vec<CategoryEdit>,
+    counts: HashMap<u64, usize>,
+    saving: bool,
+    status: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum CategoryRemapMessage {
+    Show,
+    Hide,
+    SetName(usize, String),
+    ToggleDelete(usize),
+    Reset,
+    /// Pick the output file
+    Save,
+    SaveTo(PathBuf),
+    Saved(Result<(PathBuf, Summary), String>),
+}
+
+impl From<CategoryRemapMessage> for Message {
+    fn from(msg: CategoryRemapMessage) -> Self {
+        Message::CategoryRemapAction(msg)
+    }
+}
+
+impl CategoryRemapState {
+    fn reset(&mut self) {
+        self.edits = self.original.iter()
+            .map(|(id, name)| CategoryEdit { id: *id, name: name.clone(), delete: false })
+            .collect();
+        self.status = None;
+    }
+}
+
+pub fn handle_category_remap_message(app: &mut DataViewer, msg: CategoryRemapMessage) -> Task<Message> {
+    let state = &mut app.category_remap;
+    match msg {
+        CategoryRemapMessage::Show => {
+            if !app.annotation_manager.has_annotations() {
+                return Task::none();
+            }
+            *state = CategoryRemapState {
+                visible: true,
+                original: app.annotation_manager.categories(),
+                counts: app.annotation_manager.category_counts(),
+                ..CategoryRemapState::default()
+            };
+            state.reset();
+            Task::none()
+        }
+        CategoryRemapMessage::Hide => {
+            state.visible = false;
+            Task::none()
+        }
+        CategoryRemapMessage::SetName(row, name) => {
+            if let Some(edit) = state.edits.get_mut(row) {
+                edit.name = name;
+            }
+            Task::none()
+        }
+        CategoryRemapMessage::ToggleDelete(row) => {
+            if let Some(edit) = state.edits.get_mut(row) {
+                edit.delete = !edit.delete;
+            }
+            Task::none()
+        }
+        CategoryRemapMessage::Reset => {
+            state.reset();
+            Task::none()
+        }
+        CategoryRemapMessage::Save => {
+            let Some(source) = app.annotation_manager.get_json_path().cloned() else {
+                return Task::none();
+            };
+            if state.saving {
+                return Task::none();
+            }
+            if let Err(e) = plan(&state.original, &state.edits) {
+                state.status = Some(format!("Error: {}", e));
+                return Task::none();
+            }
+            let stem = source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            let directory = source.parent().map(Path::to_path_buf).unwrap_or_default();
+            Task::perform(
+                async move {
+                    rfd::AsyncFileDialog::new()
+                        .set_title("Save remapped annotations")
+                        .set_directory(directory)
+                        .set_file_name(format!("{}_remapped.json", stem))
+                        .add_filter("JSON", &["json"])
+                        .save_file()
+                        .await
+                },
+                |file_handle| match file_handle {
+                    Some(file) => CategoryRemapMessage::SaveTo(file.path().to_path_buf()).into(),
+                    None => Message::Nothing,
+                }
+            )
+        }
+        CategoryRemapMessage::SaveTo(output) => {
+            let Some(source) = app.annotation_manager.get_json_path().cloned() else {
+                return Task::none();
+            };
+            if output == source {
+                state.status = Some("Error: pick a new file, the opened one is left as it is".to_string());
+                return Task::none();
+            }
+            let outcomes = match plan(&state.original, &state.edits) {
+                Ok(outcomes) => outcomes,
+                Err(e) => {
+                    state.status = Some(format!("Error: {}", e));
+                    return Task::none();
+                }
+            };
+            state.saving = true;
+            state.status = None;
+            Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || {
+                        remap_file(&source, &output, &outcomes).map(|summary| (output, summary))
+                    })
+                    .await
+                    .unwrap_or_else(|e| Err(e.to_string()))
+                },
+                |result| CategoryRemapMessage::Saved(result).into()
+            )
+        }
+        CategoryRemapMessage::Saved(result) => {
+            state.saving = false;
+            match result {
+                Ok((output, summary)) => {
+                    info!("Saved remapped annotations to {}: {}", output.display(), describe(&summary));
+                    state.visible = false;
+                    // Show the saved file, for the same folder
+                    match app.annotation_manager.get_image_directory().cloned() {
+                        Some(image_dir) => Task::done(CocoMessage::LoadCocoFileFor(output, image_dir).into()),
+                        None => Task::none(),
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to save remapped annotations: {}", e);
+                    state.status = Some(format!("Error: {}", e));
+                    Task::none()
+                }
+            }
+        }
+    }
+}
+
+/// Builds the category dialog
+pub fn view_category_remap(state: &CategoryRemapState) -> Element<'_, Message, WinitTheme, Renderer> {
+    let planned = plan(&state.original, &state.edits);
+    let name_of = |id: u64| state.edits.iter().find(|edit| edit.id == id).map(|edit| edit.name.trim()).unwrap_or_default();
+
+    let rows = state.original.iter().zip(&state.edits).enumerate().fold(
+        Column::new().spacing(4),
+        |rows, (row_index, ((id, original), edit))| {
+            let count = state.counts.get(id).copied().unwrap_or(0);
+            let effect = match planned.as_ref().ok().and_then(|outcomes| outcomes.get(id)) {
+                Some(Outcome::Rename(_)) => "renamed".to_string(),
+                Some(Outcome::MergeInto(target)) => format!("merged into {}", name_of(*target)),
+                Some(Outcome::Delete) => "deleted".to_string(),
+                None => String::new(),
+            };
+            rows.push(
+                row![
+                    text(original.clone()).size(13).width(Length::Fixed(140.0)),
+                    text_input("", &edit.name)
+                        .size(13)
+                        .width(Length::Fixed(150.0))
+                        .on_input(move |name| CategoryRemapMessage::SetName(row_index, name).into()),
+                    text(count.to_string()).size(13).width(Length::Fixed(50.0)),
+                    button(text(if edit.delete { "Keep" } else { "Delete" }).size(11))
+                        .padding([1, 6])
+                        .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+                        .on_press(CategoryRemapMessage::ToggleDelete(row_index).into()),
+                    text(effect).size(12),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center)
+            )
+        },
+    );
+
+    let status_text = match (state.saving, state.status.as_deref(), &planned) {
+        (true, _, _) => "Saving...".to_string(),
+        (false, Some(status), _) => status.to_string(),
+        (false, None, Err(e)) => format!("Error: {}", e),
+        (false, None, Ok(outcomes)) => describe(&preview(outcomes, &state.counts)),
+    };
+    let is_error = status_text.starts_with("Error");
+    let label_style = |_theme: &WinitTheme| container::Style {
+        text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+        ..container::Style::default()
+    };
+
+    let content = column![
+        text("Edit Categories").size(18)
+            .font(Font {
+                family: iced_winit::core::font::Family::Name("Roboto"),
+                weight: iced_winit::core::font::Weight::Bold,
+                stretch: iced_winit::core::font::Stretch::Normal,
+                style: iced_winit::core::font::Style::Normal,
+            }),
+        text("Rename a category to another's name to merge them").size(12),
+        container(
+            row![
+                text("Category").size(12).width(Length::Fixed(140.0)),
+                text("New name").size(12).width(Length::Fixed(150.0)),
+                text("Count").size(12).width(Length::Fixed(50.0)),
+            ]
+            .spacing(10)
+        ).style(label_style),
+        container(scrollable(rows).height(Length::Shrink)).max_height(360.0).style(label_style),
+        container(text(status_text).size(14))
+            .style(move |theme: &WinitTheme| container::Style {
+                text_color: Some(if is_error {
+                    theme.extended_palette().danger.strong.color
+                } else {
+                    theme.extended_palette().success.strong.color
+                }),
+                ..container::Style::default()
+            })
+            .height(Length::Fixed(18.0)),
+        row![
+            button(text("Save As..."))
+                .padding([3, 10])
+                .on_press_maybe((!state.saving && planned.is_ok())
+                    .then_some(CategoryRemapMessage::Save.into())),
+            button(text("Reset"))
+                .padding([3, 10])
+                .on_press(CategoryRemapMessage::Reset.into()),
+            Space::with_width(Length::Fill),
+            button(text("Close"))
+                .padding([3, 10])
+                .on_press(CategoryRemapMessage::Hide.into()),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    ]
+    .spacing(10)
+    .width(Length::Fixed(560.0));
+
+    container(content)
+        .padding(15)
+        .style(|theme: &WinitTheme| {
+            iced_widget::container::Style {
+                background: Some(theme.extended_palette().background.base.color.into()),
+                text_color: Some(theme.extended_palette().primary.weak.text),
+                border: iced_winit::core::Border {
+                    color: theme.extended_palette().background.strong.color,
+                    width: 1.0,
+                    radius: iced_winit::core::border::Radius::from(8.0),
+                },
+                ..Default::default()
+            }
+        })
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn edits(rows: &[(u64, &str, bool)]) -> Vec<CategoryEdit> {
+        rows.iter().map(|(id, name, delete)| CategoryEdit { id: *id, name: name.to_string(), delete: *delete }).collect()
+    }
+
+    fn original() -> Vec<(u64, String)> {
+        vec![(1, "person".to_string()), (2, "car".to_string()), (3, "truck".to_string()), (4, "dog".to_string())]
+    }
+
+    #[test]
+    fn test_plan() {
+        // truck takes car's name and merges into it; person is renamed; dog is deleted
+        let outcomes = plan(&original(), &edits(&[
+            (1, "pedestrian", false),
+            (2, "car", false),
+            (3, " car ", false),
+            (4, "dog", true),
+        ])).unwrap();
+        assert_eq!(outcomes.get(&1), Some(&Outcome::Rename("pedestrian".to_string())));
+        assert_eq!(outcomes.get(&2), None);
+        assert_eq!(outcomes.get(&3), Some(&Outcome::MergeInto(2)));
+        assert_eq!(outcomes.get(&4), Some(&Outcome::Delete));
+
+        // Both renamed to a new name: the lower id survives
+        let outcomes = plan(&original(), &edits(&[
+            (1, "person", false),
+            (2, "vehicle", false),
+            (3, "vehicle", false),
+            (4, "dog", false),
+        ])).unwrap();
+        assert_eq!(outcomes.get(&2), Some(&Outcome::Rename("vehicle".to_string())));
+        assert_eq!(outcomes.get(&3), Some(&Outcome::MergeInto(2)));
+
+        assert!(plan(&original(), &edits(&[(1, " ", false)])).is_err());
+        assert!(plan(&original(), &edits(&[(1, "", true)])).is_ok());
+    }
+
+    #[test]
+    fn test_apply_matches_preview() {
+        let mut coco = json!({
+            "info": {"year": 2017},
+            "images": [{"id": 1, "file_name": "a.jpg", "width": 10, "height": 10}],
+            "annotations": [
+                {"id": 1, "image_id": 1, "category_id": 1, "bbox": [0, 0, 1, 1], "extra": true},
+                {"id": 2, "image_id": 1, "category_id": 3, "bbox": [0, 0, 1, 1]},
+                {"id": 3, "image_id": 1, "category_id": 4, "bbox": [0, 0, 1, 1]},
+                {"id": 4, "image_id": 1, "category_id": 3, "bbox": [0, 0, 1, 1]},
+            ],
+            "categories": [
+                {"id": 1, "name": "person"},
+                {"id": 2, "name": "car", "supercategory": "vehicle"},
+                {"id": 3, "name": "truck"},
+                {"id": 4, "name": "dog"},
+            ],
+        });
+        let outcomes = plan(&original(), &edits(&[
+            (1, "pedestrian", false),
+            (2, "car", false),
+            (3, "car", false),
+            (4, "dog", true),
+        ])).unwrap();
+        let counts = HashMap::from([(1, 1), (3, 2), (4, 1)]);
+
+        let summary = apply(&mut coco, &outcomes).unwrap();
+        assert_eq!(summary, preview(&outcomes, &counts));
+        assert_eq!(summary, Summary { renamed: 1, removed_categories: 2, merged: 2, deleted: 1 });
+
+        assert_eq!(coco["info"]["year"], 2017);
+        assert_eq!(coco["categories"], json!([
+            {"id": 1, "name": "pedestrian"},
+            {"id": 2, "name": "car", "supercategory": "vehicle"},
+        ]));
+        let category_ids: Vec<u64> = coco["annotations"].as_array().unwrap().iter()
+            .map(|a| a["category_id"].as_u64().unwrap())
+            .collect();
+        assert_eq!(category_ids, vec![1, 2, 2]);
+        assert_eq!(coco["annotations"][0]["extra"], true);
+    }
+}
@@ -0,0 +1,168 @@
+/// Export of per-annotation image crops
+///
+/// Cuts each annotation's bbox out of its source image and writes it to
+/// `<output>/<category>/<image stem>_<annotation id>.png`.
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use log::{info, warn};
+use rayon::prelude::*;
+
+use super::parser::{BoundingBox, ImageAnnotation};
+use crate::exif_utils::decode_with_exif_orientation;
+
+/// Crop export options (padding and optional resize)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CropExportOptions {
+    /// Padding added on each side, in percent of the bbox width/height
+    pub padding_percent: f32,
+    /// Longest side of the saved crop in pixels (None keeps the original size)
+    pub max_size: Option<u32>,
+}
+
+/// Annotations to export from a single image
+#[derive(Debug, Clone)]
+pub struct CropJob {
+    pub image_path: PathBuf,
+    pub annotations: Vec<ImageAnnotation>,
+}
+
+/// Result of a finished export
+#[derive(Debug, Clone, Default)]
+pub struct CropExportSummary {
+    pub output_dir: PathBuf,
+    pub exported: usize,
+    pub failed: usize,
+    pub categories: usize,
+}
+
+/// Compute the padded crop rectangle (x, y, width, height) clamped to the image
+///
+/// Returns None when the bbox lies entirely outside the image or has no area.
+pub fn padded_crop_rect(bbox: &BoundingBox, padding_percent: f32, image_width: u32, image_height: u32) -> Option<(u32, u32, u32, u32)> {
+    let pad_x = bbox.width * padding_percent / 100.0;
+    let pad_y = bbox.height * padding_percent / 100.0;
+
+    let x0 = (bbox.x - pad_x).floor().max(0.0);
+    let y0 = (bbox.y - pad_y).floor().max(0.0);
+    let x1 = (bbox.x + bbox.width + pad_x).ceil().min(image_width as f32);
+    let y1 = (bbox.y + bbox.height + pad_y).ceil().min(image_height as f32);
+
+    if x1 - x0 < 1.0 || y1 - y0 < 1.0 {
+        return None;
+    }
+    Some((x0 as u32, y0 as u32, (x1 - x0) as u32, (y1 - y0) as u32))
+}
+
+/// Make a category name safe to use as a directory name
+pub fn sanitize_dir_name(name: &str) -> String {
+    let sanitized: String = name.trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ' ') { c } else { '_' })
+        .collect();
+    if sanitized.is_empty() || sanitized.chars().all(|c| c == '.') {
+        "unnamed".to_string()
+    } else {
+        sanitized
+    }
+}
+
+/// Export crops for all jobs into `output_dir`, decoding images in parallel
+pub fn export_crops(jobs: Vec<CropJob>, output_dir: &Path, options: CropExportOptions) -> Result<CropExportSummary, String> {
+    std::fs::create_dir_all(output_dir)
+        .map_err(|e| format!("Failed to create {}: {}", output_dir.display(), e))?;
+
+    // Create category folders up front so workers don't race on them
+    let categories: HashSet<String> = jobs.iter()
+        .flat_map(|job| job.annotations.iter().map(|a| sanitize_dir_name(&a.category_name)))
+        .collect();
+    for category in &categories {
+        let dir = output_dir.join(category);
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+
+    let (exported, failed) = jobs.par_iter()
+        .map(|job| export_image_crops(job, output_dir, options))
+        .reduce(|| (0, 0), |a, b| (a.0 + b.0, a.1 + b.1));
+
+    info!("Exported {} annotation crop(s) to {} ({} failed)", exported, output_dir.display(), failed);
+
+    Ok(CropExportSummary {
+        output_dir: output_dir.to_path_buf(),
+        exported,
+        failed,
+        categories: categories.len(),
+    })
+}
+
+/// Export the crops of a single image, returning (exported, failed)
+fn export_image_crops(job: &CropJob, output_dir: &Path, options: CropExportOptions) -> (usize, usize) {
+    let image = match std::fs::read(&job.image_path)
+        .map_err(|e| e.kind())
+        .and_then(|bytes| decode_with_exif_orientation(&bytes))
+    {
+        Ok(image) => image,
+        Err(e) => {
+            warn!("Failed to decode {}: {:?}", job.image_path.display(), e);
+            return (0, job.annotations.len());
+        }
+    };
+
+    let stem = job.image_path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "image".to_string());
+
+    let mut exported = 0;
+    let mut failed = 0;
+    for annotation in &job.annotations {
+        let Some((x, y, width, height)) = padded_crop_rect(&annotation.bbox, options.padding_percent, image.width(), image.height()) else {
+            warn!("Annotation {} in {} is outside the image, skipping", annotation.id, job.image_path.display());
+            failed += 1;
+            continue;
+        };
+
+        let mut crop = image.crop_imm(x, y, width, height);
+        if let Some(max_size) = options.max_size.filter(|&max| crop.width().max(crop.height()) > max) {
+            crop = crop.resize(max_size, max_size, image::imageops::FilterType::Lanczos3);
+        }
+
+        let path = output_dir
+            .join(sanitize_dir_name(&annotation.category_name))
+            .join(format!("{}_{}.png", stem, annotation.id));
+        match crop.save_with_format(&path, image::ImageFormat::Png) {
+            Ok(()) => exported += 1,
+            Err(e) => {
+                warn!("Failed to save crop {}: {}", path.display(), e);
+                failed += 1;
+            }
+        }
+    }
+    (exported, failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_padded_crop_rect() {
+        let bbox = BoundingBox { x: 10.0, y: 20.0, width: 40.0, height: 20.0 };
+        assert_eq!(padded_crop_rect(&bbox, 0.0, 100, 100), Some((10, 20, 40, 20)));
+        // 25% padding grows each side by a quarter of the bbox size
+        assert_eq!(padded_crop_rect(&bbox, 25.0, 100, 100), Some((0, 15, 60, 30)));
+        // Clamped to the image bounds
+        assert_eq!(padded_crop_rect(&bbox, 50.0, 55, 100), Some((0, 10, 55, 40)));
+        // Entirely outside the image
+        let outside = BoundingBox { x: 200.0, y: 200.0, width: 10.0, height: 10.0 };
+        assert_eq!(padded_crop_rect(&outside, 0.0, 100, 100), None);
+    }
+
+    #[test]
+    fn test_sanitize_dir_name() {
+        assert_eq!(sanitize_dir_name("person"), "person");
+        assert_eq!(sanitize_dir_name("traffic light"), "traffic light");
+        assert_eq!(sanitize_dir_name("a/b:c"), "a_b_c");
+        assert_eq!(sanitize_dir_name(".."), "unnamed");
+        assert_eq!(sanitize_dir_name("  "), "unnamed");
+    }
+}
@@ -0,0 +1,282 @@
+/// Annotation file discovery for opened image folders
+///
+/// When a folder is shown, COCO JSON files near it are looked for: in the folder, in its
+/// parent, and in `annotations/` or `labels/` below either. Files named `annotations.json`,
+/// `instances_*.json` or `*_coco.json` (Roboflow exports use `_annotations.coco.json`), and
+/// any JSON inside an `annotations/` or `labels/` folder, are offered in a bar under the
+/// image, those mentioning the folder's name first.
+///
+/// The answer is remembered per folder in `<config dir>/viewskater/coco_associations.yaml`:
+/// the file picked is loaded without asking the next time the folder is opened, and "Never
+/// for This Folder" stops the bar from showing for it.
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, container, text, button, Row};
+use serde::{Deserialize, Serialize};
+use log::{info, warn};
+
+use crate::app::{DataViewer, Message};
+use crate::widgets::overview_strip;
+use super::widget::CocoMessage;
+
+const ASSOCIATIONS_FILE_NAME: &str = "coco_associations.yaml";
+/// Subfolders whose JSON files are all taken for annotations
+const ANNOTATION_FOLDERS: [&str; 2] = ["annotations", "labels"];
+/// Files offered in the bar at most
+const MAX_OFFERED: usize = 4;
+
+/// What to do when a folder is opened
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Association {
+    /// Load this annotation file
+    Annotations(PathBuf),
+    /// Don't offer annotation files
+    Ignore,
+}
+
+#[derive(Debug, Clone)]
+pub enum DiscoveryMessage {
+    /// Annotation files found for a folder
+    Found(PathBuf, Vec<PathBuf>),
+    /// Load one of the offered files and remember it for the folder
+    Load(PathBuf),
+    Ignore,
+    Dismiss,
+}
+
+impl From<DiscoveryMessage> for Message {
+    fn from(msg: DiscoveryMessage) -> Self {
+        Message::CocoDiscoveryAction(msg)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AnnotationDiscovery {
+    associations: BTreeMap<PathBuf, Association>,
+    /// Folder last looked at, so each opened folder is checked once
+    checked: Option<PathBuf>,
+    /// Folder and files shown in the bar
+    prompt: Option<(PathBuf, Vec<PathBuf>)>,
+}
+
+fn associations_path() -> PathBuf {
+    crate::app_dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("viewskater")
+        .join(ASSOCIATIONS_FILE_NAME)
+}
+
+/// Whether a file is taken for a COCO annotation file, by its name
+pub fn is_annotation_name(file_name: &str, in_annotation_folder: bool) -> bool {
+    let name = file_name.to_ascii_lowercase();
+    if !name.ends_with(".json") {
+        return false;
+    }
+    in_annotation_folder
+        || name == "annotations.json"
+        || name.starts_with("instances_")
+        || name.ends_with("_coco.json")
+        || name.ends_with(".coco.json")
+}
+
+/// Files mentioning `folder_name` in their name first, then by path
+pub fn sort_candidates(files: &mut [PathBuf], folder_name: &str) {
+    let folder_name = folder_name.to_ascii_lowercase();
+    files.sort_by_key(|path| {
+        let mentions = path.file_name()
+            .is_some_and(|name| name.to_string_lossy().to_ascii_lowercase().contains(&folder_name));
+        (!mentions, path.clone())
+    });
+}
+
+/// Annotation files near `image_dir`, best match first
+fn find_annotation_files(image_dir: &Path) -> Vec<PathBuf> {
+    let mut bases = vec![image_dir];
+    bases.extend(image_dir.parent());
+
+    let mut folders: Vec<(PathBuf, bool)> = Vec::new();
+    for base in bases {
+        folders.push((base.to_path_buf(), false));
+        folders.extend(ANNOTATION_FOLDERS.iter().map(|sub| (base.join(sub), true)));
+    }
+
+    let mut found: Vec<PathBuf> = folders.into_iter()
+        .filter_map(|(folder, in_annotation_folder)| {
+            let entries = std::fs::read_dir(crate::file_io::long_path(&folder)).ok()?;
+            Some(entries.filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+                .filter(move |entry| is_annotation_name(&entry.file_name().to_string_lossy(), in_annotation_folder))
+                .map(|entry| folder.join(entry.file_name()))
+                .collect::<Vec<_>>())
+        })
+        .flatten()
+        .collect();
+
+    let folder_name = image_dir.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    sort_candidates(&mut found, &folder_name);
+    found.dedup();
+    found.truncate(MAX_OFFERED);
+    found
+}
+
+impl AnnotationDiscovery {
+    pub fn load() -> Self {
+        let associations = std::fs::read_to_string(associations_path())
+            .ok()
+            .and_then(|yaml| serde_yaml::from_str(&yaml).ok())
+            .unwrap_or_default();
+        Self { associations, ..Self::default() }
+    }
+
+    fn save(&self) {
+        let path = associations_path();
+        let result = path.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, serde_yaml::to_string(&self.associations).unwrap_or_default()));
+        if let Err(e) = result {
+            warn!("Failed to save annotation associations to {}: {}", path.display(), e);
+        }
+    }
+
+    fn remember(&mut self, association: Association) -> Option<PathBuf> {
+        let (folder, _) = self.prompt.take()?;
+        self.associations.insert(folder.clone(), association);
+        self.save();
+        Some(folder)
+    }
+
+    /// Load the remembered annotations of a newly shown folder, or look for some
+    pub fn request(app: &mut DataViewer) -> Option<Task<Message>> {
+        let pane_index = overview_strip::active_pane(&app.pane_layout, app.is_slider_dual)?;
+        let pane = app.panes.get(pane_index).filter(|pane| pane.dir_loaded && !pane.has_compressed_file)?;
+        let folder = PathBuf::from(pane.directory_path.as_ref()?);
+        let discovery = &mut app.coco_discovery;
+        if discovery.checked.as_ref() == Some(&folder) {
+            return None;
+        }
+        discovery.checked = Some(folder.clone());
+        discovery.prompt = None;
+
+        // Opened through File > Open COCO File, or loaded on a previous visit this session
+        if app.annotation_manager.get_image_directory() == Some(&folder) {
+            return None;
+        }
+        match discovery.associations.get(&folder) {
+            Some(Association::Ignore) => None,
+            Some(Association::Annotations(json)) if json.is_file() => {
+                info!("Loading remembered annotations {} for {}", json.display(), folder.display());
+                Some(Task::done(CocoMessage::LoadCocoFileFor(json.clone(), folder).into()))
+            }
+            _ => Some(Task::perform(
+                async move {
+                    let search = folder.clone();
+                    let found = tokio::task::spawn_blocking(move || find_annotation_files(&search))
+                        .await
+                        .unwrap_or_default();
+                    (folder, found)
+                },
+                |(folder, found)| DiscoveryMessage::Found(folder, found).into()
+            )),
+        }
+    }
+}
+
+pub fn handle_discovery_message(app: &mut DataViewer, msg: DiscoveryMessage) -> Task<Message> {
+    let discovery = &mut app.coco_discovery;
+    match msg {
+        DiscoveryMessage::Found(folder, found) => {
+            // Another folder was opened meanwhile
+            if discovery.checked.as_ref() == Some(&folder) && !found.is_empty() {
+                discovery.prompt = Some((folder, found));
+            }
+            Task::none()
+        }
+        DiscoveryMessage::Load(json) => {
+            match discovery.remember(Association::Annotations(json.clone())) {
+                Some(folder) => Task::done(CocoMessage::LoadCocoFileFor(json, folder).into()),
+                None => Task::none(),
+            }
+        }
+        DiscoveryMessage::Ignore => {
+            discovery.remember(Association::Ignore);
+            Task::none()
+        }
+        DiscoveryMessage::Dismiss => {
+            discovery.prompt = None;
+            Task::none()
+        }
+    }
+}
+
+/// Offered annotation files while a folder has some, or nothing
+pub fn view(discovery: &AnnotationDiscovery) -> Element<'_, Message, WinitTheme, Renderer> {
+    let Some((folder, files)) = &discovery.prompt else {
+        return container(text("")).height(0).into();
+    };
+
+    let action = |label: String, msg: DiscoveryMessage| {
+        button(text(label).size(11))
+            .padding([1, 6])
+            .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+            .on_press(msg.into())
+    };
+
+    // Shown relative to the folder's parent, which tells `labels/x.json` from `x.json`
+    let base = folder.parent().unwrap_or(folder);
+    let loads = files.iter().fold(Row::new().spacing(6), |loads, file| {
+        let name = file.strip_prefix(base).unwrap_or(file).to_string_lossy().to_string();
+        loads.push(action(format!("Load {}", name), DiscoveryMessage::Load(file.clone())))
+    });
+    let folder_name = folder.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+
+    container(
+        row![
+            text(format!("Annotations found for {}", folder_name)).size(13),
+            container(loads).width(Length::Fill),
+            action("Never for This Folder".to_string(), DiscoveryMessage::Ignore),
+            action("Not Now".to_string(), DiscoveryMessage::Dismiss),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center)
+    )
+    .padding([4, 8])
+    .width(Length::Fill)
+    .style(|_theme: &WinitTheme| container::Style {
+        text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+        ..container::Style::default()
+    })
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_annotation_name() {
+        assert!(is_annotation_name("instances_val2017.json", false));
+        assert!(is_annotation_name("Annotations.JSON", false));
+        assert!(is_annotation_name("_annotations.coco.json", false));
+        assert!(is_annotation_name("dataset_coco.json", false));
+        assert!(!is_annotation_name("package.json", false));
+        assert!(is_annotation_name("package.json", true));
+        assert!(!is_annotation_name("instances_val2017.txt", true));
+    }
+
+    #[test]
+    fn test_sort_candidates() {
+        let mut files = vec![
+            PathBuf::from("data/annotations/instances_train2017.json"),
+            PathBuf::from("data/annotations/instances_val2017.json"),
+            PathBuf::from("data/annotations.json"),
+        ];
+        sort_candidates(&mut files, "val2017");
+        assert_eq!(files[0], PathBuf::from("data/annotations/instances_val2017.json"));
+        assert_eq!(files.len(), 3);
+    }
+}
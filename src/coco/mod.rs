@@ -2,8 +2,11 @@
 ///
 /// This module handles COCO format dataset loading, annotation management,
 /// and rendering of bounding boxes and segmentation masks.
-pub mod parser;
+pub use viewskater_core::coco::{parser, rle_decoder};
 pub mod annotation_manager;
 pub mod widget;
 pub mod overlay;
-pub mod rle_decoder;
+pub mod crop_export;
+pub mod discovery;
+pub mod category_remap;
+pub mod overlay_export;
@@ -8,14 +8,16 @@ use iced_widget::{Stack, container, text, column};
 use iced_core::Border;
 
 use crate::app::Message;
-use crate::coco::parser::{ImageAnnotation, CocoSegmentation};
+use crate::coco::parser::{ImageAnnotation, CocoSegmentation, BoundingBox};
+use crate::coco::annotation_manager::OverlayFilter;
+use crate::coco::widget::AnnotationHitLayer;
 use crate::settings::CocoMaskRenderMode;
 use super::bbox_shader::BBoxShader;
 use super::polygon_shader::PolygonShader;
 use super::mask_shader::MaskShader;
 
 /// Get YOLO color for category ID (same as bbox_shader)
-fn get_category_color(category_id: u64) -> Color {
+pub(crate) fn get_category_color(category_id: u64) -> Color {
     let colors = [
         [0.000, 0.447, 0.741], [0.850, 0.325, 0.098], [0.929, 0.694, 0.125],
         [0.494, 0.184, 0.556], [0.466, 0.674, 0.188], [0.301, 0.745, 0.933],
@@ -50,11 +52,68 @@ fn get_category_color(category_id: u64) -> Color {
     Color::from_rgb(rgb[0], rgb[1], rgb[2])
 }
 
+/// Maps image-space coordinates to screen space for the current zoom/pan
+///
+/// Mirrors the math used by BBoxShader: ContentFit::Contain scaling, then zoom,
+/// centering, and the pan offset subtracted like ImageShader does.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ImageTransform {
+    pub scale: f32,
+    pub origin: Point,
+}
+
+impl ImageTransform {
+    pub fn new(bounds: Rectangle, image_size: (u32, u32), zoom_scale: f32, zoom_offset: Vector) -> Self {
+        let image_width = image_size.0 as f32;
+        let image_height = image_size.1 as f32;
+        let base_scale = (bounds.width / image_width).min(bounds.height / image_height);
+        let scale = base_scale * zoom_scale;
+
+        let center_offset_x = (bounds.width - image_width * scale) / 2.0;
+        let center_offset_y = (bounds.height - image_height * scale) / 2.0;
+
+        Self {
+            scale,
+            origin: Point::new(
+                center_offset_x - zoom_offset.x + bounds.x,
+                center_offset_y - zoom_offset.y + bounds.y,
+            ),
+        }
+    }
+
+    /// Screen rectangle of a bbox given in image coordinates
+    pub fn to_screen(&self, bbox: &BoundingBox) -> Rectangle {
+        Rectangle {
+            x: bbox.x * self.scale + self.origin.x,
+            y: bbox.y * self.scale + self.origin.y,
+            width: bbox.width * self.scale,
+            height: bbox.height * self.scale,
+        }
+    }
+
+    /// Image coordinates of a screen point
+    pub fn to_image(&self, point: Point) -> Point {
+        Point::new(
+            (point.x - self.origin.x) / self.scale,
+            (point.y - self.origin.y) / self.scale,
+        )
+    }
+}
+
+/// Display options for per-bbox text labels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LabelOptions {
+    pub visible: bool,
+    pub size: f32,  // Font size in screen pixels (independent of zoom)
+}
+
 /// Render bounding box and segmentation mask overlays for a list of annotations
 ///
 /// Uses custom WGPU shader for rendering actual bbox rectangles with text labels.
 /// Renders segmentation masks as semi-transparent filled polygons or pixel-perfect textures.
 /// Applies zoom transformation based on scale and offset parameters.
+/// While `overlay_filter` is active only the annotations it flags are drawn.
+#[allow(clippy::too_many_arguments)]
 pub fn render_bbox_overlay<'a>(
     annotations: &'a [ImageAnnotation],
     image_size: (u32, u32),
@@ -65,6 +124,10 @@ pub fn render_bbox_overlay<'a>(
     has_invalid_annotations: bool,
     render_mode: CocoMaskRenderMode,
     disable_simplification: bool,
+    label_options: LabelOptions,
+    pane_index: usize,
+    selected_annotation: Option<u64>,
+    overlay_filter: OverlayFilter,
 ) -> Element<'a, Message, WinitTheme, Renderer> {
     if annotations.is_empty() {
         return container(iced_widget::Space::new(Length::Fill, Length::Fill))
@@ -73,6 +136,10 @@ pub fn render_bbox_overlay<'a>(
             .into();
     }
 
+    let total_count = annotations.len();
+    let flagged = overlay_filter.apply(annotations, image_size);
+    let annotations = flagged.as_slice();
+
     // Stack for layering visualizations
     let mut stack = Stack::new();
 
@@ -105,10 +172,22 @@ pub fn render_bbox_overlay<'a>(
         stack = stack.push(bbox_shader);
 
         // Create per-bbox label overlay
-        let labels_overlay = BBoxLabels::into_element(annotations.to_vec(), image_size, zoom_scale, zoom_offset);
-        stack = stack.push(labels_overlay);
+        if label_options.visible {
+            let labels_overlay = BBoxLabels::into_element(annotations.to_vec(), image_size, zoom_scale, zoom_offset, label_options.size);
+            stack = stack.push(labels_overlay);
+        }
     }
 
+    // Hover tooltips, click-to-select, and selection highlight
+    stack = stack.push(AnnotationHitLayer::new(
+        annotations.to_vec(),
+        image_size,
+        zoom_scale,
+        zoom_offset,
+        pane_index,
+        selected_annotation,
+    ));
+
     // Category summary: count occurrences of each category
     let mut category_counts = std::collections::HashMap::new();
     for annotation in annotations {
@@ -124,6 +203,16 @@ pub fn render_bbox_overlay<'a>(
     // Build category summary text
     let mut summary = column![];
 
+    if overlay_filter.is_active() {
+        summary = summary.push(
+            text(format!("Filter: {} of {} shown", annotations.len(), total_count))
+                .size(14)
+                .style(|_theme| iced_widget::text::Style {
+                    color: Some(Color::from([0.3, 0.9, 1.0, 1.0]))
+                })
+        );
+    }
+
     // Add invalid annotation warning at the top if needed
     if has_invalid_annotations {
         summary = summary.push(
@@ -161,24 +250,113 @@ pub fn render_bbox_overlay<'a>(
     // Add category summary on top
     stack = stack.push(summary_container);
 
+    // Details panel for the selected annotation, anchored to the bottom-left corner
+    if let Some(selected) = selected_annotation.and_then(|id| annotations.iter().find(|a| a.id == id)) {
+        stack = stack.push(
+            container(annotation_details_panel(selected))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .padding(8)
+                .align_y(iced_winit::core::alignment::Vertical::Bottom)
+        );
+    }
+
     stack.into()
 }
 
+/// Build the details panel for a selected annotation
+fn annotation_details_panel<'a>(annotation: &ImageAnnotation) -> Element<'a, Message, WinitTheme, Renderer> {
+    let color = get_category_color(annotation.category_id);
+    let bbox = annotation.bbox;
+
+    let mut details = column![
+        text(format!("{} (category {})", annotation.category_name, annotation.category_id))
+            .size(14)
+            .style(move |_theme| iced_widget::text::Style { color: Some(color) }),
+        text(format!("Annotation ID: {}", annotation.id)).size(13),
+        text(format!("BBox: x={:.1} y={:.1} w={:.1} h={:.1}", bbox.x, bbox.y, bbox.width, bbox.height)).size(13),
+        text(format!("Area: {:.1}", annotation.area)).size(13),
+    ]
+    .spacing(2);
+
+    if let Some(score) = annotation.score {
+        details = details.push(text(format!("Score: {:.3}", score)).size(13));
+    }
+
+    let mask_type = match annotation.segmentation {
+        Some(CocoSegmentation::Polygon(ref polygons)) => format!("Polygon ({} parts)", polygons.len()),
+        Some(CocoSegmentation::Rle(_)) => "RLE".to_string(),
+        None => "None".to_string(),
+    };
+    details = details.push(text(format!("Mask: {}", mask_type)).size(13));
+
+    container(details)
+        .padding(8)
+        .style(move |_theme: &WinitTheme| iced_widget::container::Style {
+            background: Some(Color::from([0.0, 0.0, 0.0, 0.75]).into()),
+            text_color: Some(Color::WHITE),
+            border: Border {
+                radius: 4.0.into(),
+                width: 1.0,
+                color,
+            },
+            ..iced_widget::container::Style::default()
+        })
+        .into()
+}
+
+/// Build the label text for an annotation: category name plus score when present
+fn label_text(annotation: &ImageAnnotation) -> String {
+    match annotation.score {
+        Some(score) => format!("{} {:.2}", annotation.category_name, score),
+        None => annotation.category_name.clone(),
+    }
+}
+
+/// Choose a screen rectangle for a label so it doesn't overlap already placed labels
+///
+/// Candidates are tried in order: above the box, inside its top edge, below the box.
+/// Each candidate is clamped horizontally into `clip`. Returns None if every candidate
+/// collides with a placed label or falls outside `clip`, in which case the label is skipped.
+fn place_label(
+    bbox: Rectangle,
+    label_width: f32,
+    label_height: f32,
+    placed: &[Rectangle],
+    clip: Rectangle,
+) -> Option<Rectangle> {
+    let max_x = (clip.x + clip.width - label_width).max(clip.x);
+    let x = bbox.x.clamp(clip.x, max_x);
+
+    let candidates = [
+        bbox.y - label_height,          // Above the box
+        bbox.y,                         // Inside, along the top edge
+        bbox.y + bbox.height,           // Below the box
+    ];
+
+    candidates.iter()
+        .map(|&y| Rectangle { x, y, width: label_width, height: label_height })
+        .filter(|rect| rect.y >= clip.y && rect.y + rect.height <= clip.y + clip.height)
+        .find(|rect| !placed.iter().any(|other| other.intersects(rect)))
+}
+
 /// Widget for rendering per-bbox labels
 struct BBoxLabels {
     annotations: Vec<ImageAnnotation>,
     image_size: (u32, u32),
     zoom_scale: f32,
     zoom_offset: Vector,
+    text_size: f32,
 }
 
 impl BBoxLabels {
-    fn into_element(annotations: Vec<ImageAnnotation>, image_size: (u32, u32), zoom_scale: f32, zoom_offset: Vector) -> Element<'static, Message, WinitTheme, Renderer> {
+    fn into_element(annotations: Vec<ImageAnnotation>, image_size: (u32, u32), zoom_scale: f32, zoom_offset: Vector, text_size: f32) -> Element<'static, Message, WinitTheme, Renderer> {
         let widget = Self {
             annotations,
             image_size,
             zoom_scale,
             zoom_offset,
+            text_size,
         };
         Element::new(widget)
     }
@@ -217,58 +395,49 @@ where
         use iced_core::text::Text;
 
         let bounds = layout.bounds();
+        let transform = ImageTransform::new(bounds, self.image_size, self.zoom_scale, self.zoom_offset);
+
+        // Label metrics stay constant in screen space so text is readable at any zoom
+        let padding = 4.0;
+        let label_height = (self.text_size * 1.4).ceil();
+
+        // Place larger boxes first so small objects don't push labels off big ones
+        let mut order: Vec<&ImageAnnotation> = self.annotations.iter().collect();
+        order.sort_by(|a, b| {
+            let area_a = a.bbox.width * a.bbox.height;
+            let area_b = b.bbox.width * b.bbox.height;
+            area_b.partial_cmp(&area_a).unwrap_or(std::cmp::Ordering::Equal)
+        });
 
-        // Calculate scaling (same as BBoxShader)
-        let image_width = self.image_size.0 as f32;
-        let image_height = self.image_size.1 as f32;
-        let display_width = bounds.width;
-        let display_height = bounds.height;
+        let mut placed: Vec<Rectangle> = Vec::with_capacity(order.len());
 
-        // Base scale from ContentFit::Contain
-        let width_ratio = display_width / image_width;
-        let height_ratio = display_height / image_height;
-        let base_scale = width_ratio.min(height_ratio);
+        // Draw label for each bbox
+        for annotation in order {
+            let bbox_rect = transform.to_screen(&annotation.bbox);
 
-        // Calculate zoomed image dimensions (changes with zoom)
-        let zoomed_image_width = image_width * base_scale * self.zoom_scale;
-        let zoomed_image_height = image_height * base_scale * self.zoom_scale;
+            // Skip boxes that are panned completely out of view
+            if !bounds.intersects(&bbox_rect) {
+                continue;
+            }
 
-        // Centering offset after zoom (changes as image grows/shrinks)
-        let center_offset_x = (display_width - zoomed_image_width) / 2.0;
-        let center_offset_y = (display_height - zoomed_image_height) / 2.0;
+            let content = label_text(annotation);
 
-        // Draw label for each bbox
-        for annotation in &self.annotations {
-            // Scale bbox coordinates by base_scale and zoom_scale
-            let scaled_bbox_x = annotation.bbox.x * base_scale * self.zoom_scale;
-            let scaled_bbox_y = annotation.bbox.y * base_scale * self.zoom_scale;
+            // Estimate text width (rough approximation for the default sans font)
+            let text_width = content.chars().count() as f32 * self.text_size * 0.58;
+            let label_width = text_width + padding * 2.0;
 
-            // Apply centering offset and pan offset (subtract offset like ImageShader does)
-            let x = scaled_bbox_x + center_offset_x - self.zoom_offset.x + bounds.x;
-            let y = scaled_bbox_y + center_offset_y - self.zoom_offset.y + bounds.y;
+            let Some(label_rect) = place_label(bbox_rect, label_width, label_height, &placed, bounds) else {
+                continue;
+            };
+            placed.push(label_rect);
 
             // Get color for this category
             let bg_color = get_category_color(annotation.category_id);
 
-            // Estimate text width (rough approximation) and scale with zoom
-            let base_text_width = annotation.category_name.len() as f32 * 7.5;
-            let text_width = base_text_width * self.zoom_scale;
-            let base_label_height = 18.0;
-            let label_height = base_label_height * self.zoom_scale;
-            let padding = 4.0 * self.zoom_scale;
-
-            // Position label just above the bbox
-            let label_y = y - label_height - 2.0 * self.zoom_scale;
-
             // Draw colored background rectangle
             renderer.fill_quad(
                 iced_core::renderer::Quad {
-                    bounds: Rectangle {
-                        x,
-                        y: label_y,
-                        width: text_width + padding * 2.0,
-                        height: label_height,
-                    },
+                    bounds: label_rect,
                     border: Border {
                         radius: 2.0.into(),
                         width: 0.0,
@@ -282,17 +451,17 @@ where
             // Draw white text on colored background
             renderer.fill_text(
                 Text {
-                    content: annotation.category_name.clone(),
+                    content,
                     bounds: iced_core::Size::new(f32::INFINITY, label_height),
-                    size: (13.0 * self.zoom_scale).into(),
+                    size: self.text_size.into(),
                     line_height: iced_core::text::LineHeight::default(),
                     font: renderer.default_font(),
                     horizontal_alignment: iced_core::alignment::Horizontal::Left,
-                    vertical_alignment: iced_core::alignment::Vertical::Top,
+                    vertical_alignment: iced_core::alignment::Vertical::Center,
                     shaping: iced_core::text::Shaping::Basic,
                     wrapping: iced_core::text::Wrapping::default(),
                 },
-                Point::new(x + padding, label_y + 2.0 * self.zoom_scale),
+                Point::new(label_rect.x + padding, label_rect.y + label_height / 2.0),
                 Color::WHITE,
                 bounds,
             );
@@ -514,4 +683,34 @@ mod tests {
         let color0_again = get_category_color(0);
         assert_eq!(color10, color0_again);
     }
+
+    #[test]
+    fn test_place_label_avoids_collisions() {
+        let clip = Rectangle { x: 0.0, y: 0.0, width: 200.0, height: 200.0 };
+        let bbox = Rectangle { x: 50.0, y: 50.0, width: 40.0, height: 40.0 };
+
+        // First label goes above the box
+        let first = place_label(bbox, 30.0, 10.0, &[], clip).unwrap();
+        assert_eq!(first.y, 40.0);
+
+        // Second label for the same box falls back to inside the top edge
+        let second = place_label(bbox, 30.0, 10.0, &[first], clip).unwrap();
+        assert_eq!(second.y, 50.0);
+
+        // Then below the box, then gives up
+        let third = place_label(bbox, 30.0, 10.0, &[first, second], clip).unwrap();
+        assert_eq!(third.y, 90.0);
+        assert!(place_label(bbox, 30.0, 10.0, &[first, second, third], clip).is_none());
+    }
+
+    #[test]
+    fn test_place_label_clamps_to_clip() {
+        let clip = Rectangle { x: 0.0, y: 0.0, width: 100.0, height: 100.0 };
+
+        // Box at the top-right corner: label can't go above, and is pulled left
+        let bbox = Rectangle { x: 90.0, y: 0.0, width: 10.0, height: 10.0 };
+        let rect = place_label(bbox, 30.0, 10.0, &[], clip).unwrap();
+        assert_eq!(rect.x, 70.0);
+        assert_eq!(rect.y, 0.0);
+    }
 }
@@ -0,0 +1,281 @@
+/// Offscreen rendering of annotation overlays onto an image
+///
+/// The image is uploaded into a render target and masks and bbox outlines are drawn over it
+/// with the colored-vertex shader the on-screen bbox overlay uses, then read back through
+/// `utils::save::read_texture`, the same readback that saves GPU-cached images. Masks are
+/// filled polygons (RLE masks are traced to polygons first) at the overlay's 40% opacity, and
+/// outlines are drawn as quads so they stay visible at any output size. Labels aren't drawn.
+use iced_wgpu::wgpu::{self, util::DeviceExt};
+use once_cell::sync::OnceCell;
+
+use crate::coco::parser::{ImageAnnotation, CocoSegmentation};
+use crate::coco::rle_decoder;
+use super::bbox_overlay::get_category_color;
+
+/// Mask fill opacity, as on screen
+const MASK_ALPHA: f32 = 0.4;
+/// Outline width as a fraction of the output's longest side
+const LINE_WIDTH_FRACTION: f32 = 1.0 / 400.0;
+const MIN_LINE_WIDTH: f32 = 2.0;
+
+const TARGET_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+/// What to draw, following the pane's overlay toggles
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BurnInOptions {
+    pub show_bboxes: bool,
+    pub show_masks: bool,
+    pub disable_simplification: bool,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: [f32; 2],
+    color: [f32; 4],
+}
+
+impl Vertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 2] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x4];
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+static PIPELINE: OnceCell<wgpu::RenderPipeline> = OnceCell::new();
+
+fn create_pipeline(device: &wgpu::Device) -> wgpu::RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Burn-in Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("bbox_shader.wgsl").into()),
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Burn-in Pipeline Layout"),
+        bind_group_layouts: &[],
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Burn-in Render Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[Vertex::desc()],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format: TARGET_FORMAT,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    })
+}
+
+/// Size of an image of `image_size` with its longest side limited to `max_size` and to the
+/// GPU's texture limit; images are never enlarged
+pub fn output_size(image_size: (u32, u32), max_size: Option<u32>, texture_limit: u32) -> (u32, u32) {
+    let longest = image_size.0.max(image_size.1).max(1);
+    let limit = max_size.unwrap_or(u32::MAX).min(texture_limit);
+    if longest <= limit {
+        return image_size;
+    }
+    let scale = limit as f32 / longest as f32;
+    let fit = |length: u32| ((length as f32 * scale).round() as u32).max(1);
+    (fit(image_size.0), fit(image_size.1))
+}
+
+/// The four bands of a rectangle outline `width` thick, centered on its edges, as
+/// (left, top, right, bottom)
+pub fn outline_rects(rect: (f32, f32, f32, f32), width: f32) -> [(f32, f32, f32, f32); 4] {
+    let (x0, y0, x1, y1) = rect;
+    let half = width / 2.0;
+    [
+        (x0 - half, y0 - half, x1 + half, y0 + half),
+        (x0 - half, y1 - half, x1 + half, y1 + half),
+        (x0 - half, y0 + half, x0 + half, y1 - half),
+        (x1 - half, y0 + half, x1 + half, y1 - half),
+    ]
+}
+
+/// Mask polygons of an annotation in image coordinates
+fn mask_polygons(annotation: &ImageAnnotation, image_size: (u32, u32), disable_simplification: bool) -> Vec<Vec<(f32, f32)>> {
+    match &annotation.segmentation {
+        Some(CocoSegmentation::Polygon(polygons)) => polygons.iter()
+            .map(|polygon| polygon.chunks_exact(2).map(|point| (point[0], point[1])).collect())
+            .collect(),
+        Some(CocoSegmentation::Rle(rle)) if rle.size.len() == 2 => {
+            let mask = rle_decoder::decode_rle(rle);
+            if mask.is_empty() {
+                return Vec::new();
+            }
+            let (mask_height, mask_width) = (rle.size[0] as usize, rle.size[1] as usize);
+            let simplify_epsilon = if disable_simplification { 0.0 } else { 1.0 };
+            // Masks stored at another resolution than the image are stretched to it, as on screen
+            let x_scale = image_size.0 as f32 / mask_width.max(1) as f32;
+            let y_scale = image_size.1 as f32 / mask_height.max(1) as f32;
+            rle_decoder::mask_to_polygons(&mask, mask_width, mask_height, simplify_epsilon)
+                .into_iter()
+                .map(|polygon| polygon.into_iter().map(|(x, y)| (x * x_scale, y * y_scale)).collect())
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Triangles of all overlays, in output pixels
+fn overlay_vertices(
+    annotations: &[ImageAnnotation],
+    image_size: (u32, u32),
+    output_size: (u32, u32),
+    options: BurnInOptions,
+) -> Vec<Vertex> {
+    let scale = output_size.0 as f32 / image_size.0.max(1) as f32;
+    let ndc = |(x, y): (f32, f32)| [
+        x / output_size.0 as f32 * 2.0 - 1.0,
+        1.0 - y / output_size.1 as f32 * 2.0,
+    ];
+    let mut vertices = Vec::new();
+
+    // Masks first, so outlines stay on top
+    if options.show_masks {
+        for annotation in annotations {
+            let color = get_category_color(annotation.category_id);
+            let color = [color.r, color.g, color.b, MASK_ALPHA];
+            for polygon in mask_polygons(annotation, image_size, options.disable_simplification) {
+                if polygon.len() < 3 {
+                    continue;
+                }
+                let points: Vec<(f32, f32)> = polygon.iter().map(|(x, y)| (x * scale, y * scale)).collect();
+                let coords: Vec<f64> = points.iter().flat_map(|(x, y)| [*x as f64, *y as f64]).collect();
+                let Ok(indices) = earcutr::earcut(&coords, &[], 2) else {
+                    continue;
+                };
+                vertices.extend(indices.iter().map(|&i| Vertex { position: ndc(points[i]), color }));
+            }
+        }
+    }
+
+    if options.show_bboxes {
+        let line_width = (output_size.0.max(output_size.1) as f32 * LINE_WIDTH_FRACTION).max(MIN_LINE_WIDTH);
+        for annotation in annotations {
+            let color = get_category_color(annotation.category_id);
+            let color = [color.r, color.g, color.b, 1.0];
+            let (x0, y0, x1, y1) = annotation.bbox.to_corners();
+            for (left, top, right, bottom) in outline_rects((x0 * scale, y0 * scale, x1 * scale, y1 * scale), line_width) {
+                let corners = [(left, top), (right, top), (right, bottom), (left, top), (right, bottom), (left, bottom)];
+                vertices.extend(corners.map(|corner| Vertex { position: ndc(corner), color }));
+            }
+        }
+    }
+    vertices
+}
+
+/// Draws the annotations over `image`, which is the source image of `image_size` already
+/// resized to the output size, and returns the result as RGBA rows
+pub fn render(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    image: &image::RgbaImage,
+    annotations: &[ImageAnnotation],
+    image_size: (u32, u32),
+    options: BurnInOptions,
+) -> Vec<u8> {
+    let (width, height) = image.dimensions();
+    let size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+    let target = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Burn-in Target"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: TARGET_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    queue.write_texture(
+        target.as_image_copy(),
+        image.as_raw(),
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(width * 4),
+            rows_per_image: Some(height),
+        },
+        size,
+    );
+
+    let vertices = overlay_vertices(annotations, image_size, (width, height), options);
+    if !vertices.is_empty() {
+        let pipeline = PIPELINE.get_or_init(|| create_pipeline(device));
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Burn-in Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let target_view = target.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Burn-in Encoder"),
+        });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Burn-in Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(pipeline);
+            pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            pass.draw(0..vertices.len() as u32, 0..1);
+        }
+        queue.submit([encoder.finish()]);
+    }
+
+    crate::utils::save::read_texture(device, queue, &target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_size() {
+        assert_eq!(output_size((4000, 3000), None, 8192), (4000, 3000));
+        assert_eq!(output_size((4000, 3000), Some(2048), 8192), (2048, 1536));
+        // Never enlarged
+        assert_eq!(output_size((800, 600), Some(2048), 8192), (800, 600));
+        // Capped by the GPU limit even at the original size
+        assert_eq!(output_size((3000, 12000), None, 8192), (2048, 8192));
+    }
+
+    #[test]
+    fn test_outline_rects() {
+        let [top, bottom, left, right] = outline_rects((10.0, 20.0, 50.0, 40.0), 4.0);
+        assert_eq!(top, (8.0, 18.0, 52.0, 22.0));
+        assert_eq!(bottom, (8.0, 38.0, 52.0, 42.0));
+        // Sides fill the gap between the top and bottom bands without overlapping them
+        assert_eq!(left, (8.0, 22.0, 12.0, 38.0));
+        assert_eq!(right, (48.0, 22.0, 52.0, 38.0));
+    }
+}
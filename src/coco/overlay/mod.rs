@@ -6,6 +6,7 @@ pub mod bbox_overlay;
 pub mod bbox_shader;
 pub mod polygon_shader;
 pub mod mask_shader;
+pub mod burn_in;
 
 // Re-export the main overlay rendering function
-pub use bbox_overlay::render_bbox_overlay;
+pub use bbox_overlay::{render_bbox_overlay, LabelOptions};
@@ -0,0 +1,160 @@
+/// Export of annotated images with their overlays burned in
+///
+/// Every image with annotations is decoded, scaled to the chosen size and written to
+/// `<output>/<image stem>.png` with its boxes and masks drawn by `overlay::burn_in`. What is
+/// drawn follows the active pane's box and mask toggles and the overlay filter.
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use iced_wgpu::wgpu;
+use iced_winit::runtime::Task;
+use log::{info, warn, error};
+
+use crate::app::{DataViewer, Message};
+use crate::exif_utils::decode_with_exif_orientation;
+use super::annotation_manager::OverlayFilter;
+use super::crop_export::CropJob;
+use super::overlay::burn_in::{self, BurnInOptions};
+
+#[derive(Debug, Clone)]
+pub enum OverlayExportMessage {
+    /// Pick an output folder and export, with the longest side limited to this many pixels
+    Export(Option<u32>),
+    Exported(Result<OverlayExportSummary, String>),
+}
+
+impl From<OverlayExportMessage> for Message {
+    fn from(msg: OverlayExportMessage) -> Self {
+        Message::OverlayExportAction(msg)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OverlayExportSummary {
+    pub output_dir: PathBuf,
+    pub exported: usize,
+    pub failed: usize,
+}
+
+fn export_image(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    job: &CropJob,
+    output_dir: &Path,
+    max_size: Option<u32>,
+    filter: OverlayFilter,
+    options: BurnInOptions,
+) -> Result<PathBuf, String> {
+    let bytes = std::fs::read(&job.image_path).map_err(|e| e.to_string())?;
+    let image = decode_with_exif_orientation(&bytes).map_err(|e| format!("{:?}", e))?;
+    let image_size = (image.width(), image.height());
+    let (width, height) = burn_in::output_size(image_size, max_size, device.limits().max_texture_dimension_2d);
+    let scaled = if (width, height) == image_size {
+        image.to_rgba8()
+    } else {
+        image::imageops::resize(&image.to_rgba8(), width, height, image::imageops::FilterType::Lanczos3)
+    };
+
+    let annotations = filter.apply(&job.annotations, image_size);
+    let pixels = burn_in::render(device, queue, &scaled, &annotations, image_size, options);
+
+    let stem = job.image_path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "image".to_string());
+    let path = output_dir.join(format!("{}.png", stem));
+    image::save_buffer_with_format(&path, &pixels, width, height, image::ColorType::Rgba8, image::ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// Export every job into `output_dir`, one image at a time since each waits on the GPU
+fn export_overlays(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    jobs: &[CropJob],
+    output_dir: &Path,
+    max_size: Option<u32>,
+    filter: OverlayFilter,
+    options: BurnInOptions,
+) -> Result<OverlayExportSummary, String> {
+    std::fs::create_dir_all(output_dir)
+        .map_err(|e| format!("Failed to create {}: {}", output_dir.display(), e))?;
+
+    let mut summary = OverlayExportSummary { output_dir: output_dir.to_path_buf(), ..Default::default() };
+    for job in jobs {
+        match export_image(device, queue, job, output_dir, max_size, filter, options) {
+            Ok(_) => summary.exported += 1,
+            Err(e) => {
+                warn!("Failed to export {}: {}", job.image_path.display(), e);
+                summary.failed += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+pub fn handle_overlay_export_message(app: &mut DataViewer, msg: OverlayExportMessage) -> Task<Message> {
+    match msg {
+        OverlayExportMessage::Export(max_size) => {
+            let jobs = app.annotation_manager.crop_jobs(None);
+            if jobs.is_empty() {
+                warn!("No annotated images to export");
+                return Task::none();
+            }
+            let pane_index = app.active_pane_index();
+            let Some(pane) = app.panes.get(pane_index) else {
+                return Task::none();
+            };
+            let options = BurnInOptions {
+                show_bboxes: pane.show_bboxes,
+                show_masks: pane.show_masks,
+                disable_simplification: app.coco_disable_simplification,
+            };
+            let filter = app.annotation_manager.overlay_filter();
+            let device = Arc::clone(&app.device);
+            let queue = Arc::clone(&app.queue);
+
+            let initial_dir = app.annotation_manager.get_image_directory()
+                .and_then(|dir| dir.parent())
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| "~".to_string());
+
+            Task::perform(
+                async move {
+                    let picked = tokio::task::spawn_blocking(move || {
+                        native_dialog::FileDialog::new()
+                            .set_title("Select output directory for annotated images")
+                            .set_location(&initial_dir)
+                            .show_open_single_dir()
+                    }).await;
+
+                    let output_dir = match picked {
+                        Ok(Ok(Some(path))) => path,
+                        _ => return None,
+                    };
+
+                    info!("Exporting {} annotated image(s) to {}", jobs.len(), output_dir.display());
+                    let result = tokio::task::spawn_blocking(move || {
+                        export_overlays(&device, &queue, &jobs, &output_dir, max_size, filter, options)
+                    }).await;
+                    Some(result.unwrap_or_else(|e| Err(format!("Overlay export task failed: {}", e))))
+                },
+                |result| match result {
+                    Some(result) => OverlayExportMessage::Exported(result).into(),
+                    None => Message::Nothing,
+                }
+            )
+        }
+        OverlayExportMessage::Exported(result) => {
+            match result {
+                Ok(summary) => {
+                    info!("Exported {} annotated image(s) to {}", summary.exported, summary.output_dir.display());
+                    if summary.failed > 0 {
+                        warn!("{} image(s) could not be exported", summary.failed);
+                    }
+                }
+                Err(e) => error!("Failed to export annotated images: {}", e),
+            }
+            Task::none()
+        }
+    }
+}
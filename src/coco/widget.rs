@@ -3,7 +3,7 @@
 /// This module is only compiled when the "coco" feature is enabled.
 /// It encapsulates all COCO-related messages and UI components.
 use std::path::PathBuf;
-use iced_winit::core::{Element, Color};
+use iced_winit::core::{Element, Color, Length, Point, Rectangle};
 use iced_winit::core::Theme as WinitTheme;
 use iced_winit::runtime::Task;
 use iced_wgpu::Renderer;
@@ -13,11 +13,14 @@ use iced_core::keyboard::{self, Key};
 use iced_core::Vector;
 use log::{info, error, warn};
 
-use crate::app::Message;
-use super::annotation_manager::AnnotationManager;
+use crate::app::{Message, IoMessage, NavigationMessage};
+use super::annotation_manager::{AnnotationManager, AnnotationFilter, OverlayFilter};
+use crate::loading_handler::{filtered_neighbors, FILTERED_PREFETCH_DEPTH};
 use crate::pane::Pane;
 use crate::menu::PaneLayout;
-use super::parser::CocoDataset;
+use super::parser::{CocoDataset, ImageAnnotation};
+use super::overlay::bbox_overlay::{ImageTransform, get_category_color};
+use super::crop_export::{CropExportOptions, CropExportSummary};
 
 /// Result type for COCO file loading: (dataset, path, skipped_count, warnings, invalid_image_ids)
 type CocoLoadResult = Result<(CocoDataset, PathBuf, usize, Vec<String>, std::collections::HashSet<u64>), String>;
@@ -31,6 +34,12 @@ pub enum CocoMessage {
     /// COCO file loaded (with result: dataset, path, skipped_count, warnings, images_with_invalid)
     CocoFileLoaded(CocoLoadResult),
 
+    /// Load a COCO JSON file for an image directory that is already open (json path, image directory)
+    LoadCocoFileFor(PathBuf, PathBuf),
+
+    /// COCO file loaded for an open image directory (result, image directory)
+    CocoFileLoadedFor(CocoLoadResult, PathBuf),
+
     /// User selected image directory (with pending dataset, json path, and invalid images)
     ImageDirectorySelected(Option<PathBuf>, CocoDataset, PathBuf, std::collections::HashSet<u64>),
 
@@ -51,6 +60,33 @@ pub enum CocoMessage {
 
     /// Image zoom/pan changed (pane_index, scale, offset)
     ZoomChanged(usize, f32, Vector),
+
+    /// Annotation area clicked (pane_index, ids under the cursor, smallest first)
+    AnnotationClicked(usize, Vec<u64>),
+
+    /// Set the jump filter and jump forward to the first matching image (pane_index, filter)
+    SetJumpFilter(usize, Option<AnnotationFilter>),
+
+    /// Jump to the next/previous image matching the jump filter (pane_index, forward)
+    JumpToMatch(usize, bool),
+
+    /// Only draw the annotations this filter flags
+    SetOverlayFilter(OverlayFilter),
+
+    /// Pick an output folder and export annotation crops
+    ExportCrops(CropExportScope, CropExportOptions),
+
+    /// Crop export finished
+    CropsExported(Result<CropExportSummary, String>),
+}
+
+/// Which annotations a crop export covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CropExportScope {
+    /// Every annotation in the dataset
+    All,
+    /// The selected annotation of the current image in a pane
+    Selected(usize),  // pane_index
 }
 
 /// Convert CocoMessage to the main Message type
@@ -236,10 +272,10 @@ pub fn handle_coco_message(
 
                             // Now open the image directory to actually load and display images
                             // We use FolderOpened message to trigger the standard directory loading
-                            Task::done(Message::FolderOpened(
+                            Task::done(Message::Io(IoMessage::FolderOpened(
                                 Ok(dir.to_string_lossy().to_string()),
                                 0  // pane_index
-                            ))
+                            )))
                         }
                     } else {
                         // Need to prompt user for directory
@@ -279,6 +315,41 @@ pub fn handle_coco_message(
             }
         }
 
+        CocoMessage::LoadCocoFileFor(path, image_dir) => {
+            info!("Loading COCO file {} for {}", path.display(), image_dir.display());
+
+            Task::perform(
+                async move {
+                    let result = CocoDataset::from_file(&path).map(|mut dataset| {
+                        let (skipped_count, warnings, images_with_invalid) = dataset.validate_and_clean();
+                        (dataset, path, skipped_count, warnings, images_with_invalid)
+                    });
+                    (result, image_dir)
+                },
+                |(result, image_dir)| Message::CocoAction(CocoMessage::CocoFileLoadedFor(result, image_dir))
+            )
+        }
+
+        CocoMessage::CocoFileLoadedFor(result, image_dir) => {
+            // The directory is already shown, so unlike CocoFileLoaded nothing is opened
+            match result.and_then(|(dataset, json_path, skipped_count, _, images_with_invalid)| {
+                if skipped_count > 0 {
+                    warn!("Skipped {} invalid annotation(s)", skipped_count);
+                }
+                annotation_manager.set_image_directory(dataset, json_path, image_dir.clone(), images_with_invalid)
+            }) {
+                Ok(()) => {
+                    info!("COCO annotations loaded for directory: {}", image_dir.display());
+                    for pane in panes.iter_mut() {
+                        pane.show_bboxes = true;
+                        pane.show_masks = true;
+                    }
+                }
+                Err(e) => error!("Failed to load COCO annotations for {}: {}", image_dir.display(), e),
+            }
+            Task::none()
+        }
+
         CocoMessage::ImageDirectorySelected(maybe_path, dataset, json_path, images_with_invalid) => {
             if let Some(dir_path) = maybe_path {
                 info!("User selected image directory: {}", dir_path.display());
@@ -302,10 +373,10 @@ pub fn handle_coco_message(
                     }
 
                     // Now open the image directory to actually load and display images
-                    Task::done(Message::FolderOpened(
+                    Task::done(Message::Io(IoMessage::FolderOpened(
                         Ok(dir_path.to_string_lossy().to_string()),
                         0  // pane_index
-                    ))
+                    )))
                 }
             } else {
                 warn!("User cancelled directory selection");
@@ -378,6 +449,399 @@ pub fn handle_coco_message(
             }
             Task::none()
         }
+
+        CocoMessage::AnnotationClicked(pane_index, hits) => {
+            if let Some(pane) = panes.get_mut(pane_index) {
+                pane.selected_annotation = next_selection(&hits, pane.selected_annotation);
+                log::debug!("AnnotationClicked: pane={}, hits={:?}, selected={:?}",
+                    pane_index, hits, pane.selected_annotation);
+            }
+            Task::none()
+        }
+
+        CocoMessage::SetJumpFilter(pane_index, filter) => {
+            annotation_manager.set_jump_filter(filter);
+            info!("Jump filter set to {:?}", filter);
+            if filter.is_some() {
+                Task::done(Message::CocoAction(CocoMessage::JumpToMatch(pane_index, true)))
+            } else {
+                // Leaving the filtered view, so its prefetched images are no longer needed
+                Task::done(Message::Io(IoMessage::PrefetchFiltered(pane_index, Vec::new())))
+            }
+        }
+
+        CocoMessage::SetOverlayFilter(filter) => {
+            annotation_manager.set_overlay_filter(filter);
+            info!("Overlay filter set to {:?}", filter);
+            // Nothing is flagged once the filter is off
+            if !filter.is_active() && annotation_manager.jump_filter() == Some(AnnotationFilter::Flagged) {
+                annotation_manager.set_jump_filter(None);
+            }
+            Task::none()
+        }
+
+        CocoMessage::ExportCrops(scope, options) => {
+            let jobs = match scope {
+                CropExportScope::All => annotation_manager.crop_jobs(None),
+                CropExportScope::Selected(pane_index) => {
+                    let selected = panes.get(pane_index).and_then(|pane| {
+                        let filename = pane.img_cache.image_paths.get(pane.img_cache.current_index)?.file_name().to_string();
+                        Some((filename, pane.selected_annotation?))
+                    });
+                    match selected {
+                        Some((filename, id)) => annotation_manager.crop_jobs(Some((filename.as_str(), std::slice::from_ref(&id)))),
+                        None => Vec::new(),
+                    }
+                }
+            };
+            if jobs.is_empty() {
+                warn!("No annotations to export for {:?}", scope);
+                return Task::none();
+            }
+
+            let initial_dir = annotation_manager.get_image_directory()
+                .and_then(|dir| dir.parent())
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| "~".to_string());
+
+            Task::perform(
+                async move {
+                    let picked = tokio::task::spawn_blocking(move || {
+                        native_dialog::FileDialog::new()
+                            .set_title("Select output directory for annotation crops")
+                            .set_location(&initial_dir)
+                            .show_open_single_dir()
+                    }).await;
+
+                    let output_dir = match picked {
+                        Ok(Ok(Some(path))) => path,
+                        _ => return None,
+                    };
+
+                    let result = tokio::task::spawn_blocking(move || {
+                        super::crop_export::export_crops(jobs, &output_dir, options)
+                    }).await;
+                    Some(result.unwrap_or_else(|e| Err(format!("Crop export task failed: {}", e))))
+                },
+                |result| match result {
+                    Some(result) => Message::CocoAction(CocoMessage::CropsExported(result)),
+                    None => Message::Nothing,
+                }
+            )
+        }
+
+        CocoMessage::CropsExported(result) => {
+            match result {
+                Ok(summary) => {
+                    info!("Exported {} crop(s) in {} categories to {}",
+                          summary.exported, summary.categories, summary.output_dir.display());
+                    if summary.failed > 0 {
+                        warn!("{} crop(s) could not be exported", summary.failed);
+                    }
+                }
+                Err(e) => error!("Failed to export annotation crops: {}", e),
+            }
+            Task::none()
+        }
+
+        CocoMessage::JumpToMatch(pane_index, forward) => {
+            let Some(pane) = panes.get(pane_index) else {
+                return Task::none();
+            };
+            if !pane.dir_loaded || !annotation_manager.has_annotations() {
+                return Task::none();
+            }
+
+            let paths = &pane.img_cache.image_paths;
+            let current = pane.img_cache.current_index;
+
+            // Without an explicit filter, follow the category of the selected annotation
+            let filter = annotation_manager.jump_filter().or_else(|| {
+                let selected = pane.selected_annotation?;
+                let filename = paths.get(current)?.file_name();
+                annotation_manager.get_annotations(&filename)?
+                    .iter()
+                    .find(|a| a.id == selected)
+                    .map(|a| AnnotationFilter::Category(a.category_id))
+            });
+            let Some(filter) = filter else {
+                info!("No jump filter set: pick a category from the Annotations menu or select an annotation");
+                return Task::none();
+            };
+
+            // Walked a page at a time, so a paged list isn't pulled into memory by the search
+            let annotation_manager = &*annotation_manager;
+            let matches = |from: usize, forward: bool| {
+                paths.walk_from(from, forward)
+                    .filter(move |(_, source)| annotation_manager.image_matches(&source.file_name(), filter))
+                    .map(|(idx, _)| idx)
+            };
+            match matches(current, forward).next() {
+                Some(idx) => {
+                    log::debug!("JumpToMatch: pane={}, filter={:?}, {} -> {}", pane_index, filter, current, idx);
+                    // Load the matches around the destination so the next jump hits the cache
+                    let neighbors = filtered_neighbors(FILTERED_PREFETCH_DEPTH, |forward| matches(idx, forward));
+                    Task::batch([
+                        Task::done(Message::Navigation(NavigationMessage::JumpToImage(pane_index, idx))),
+                        Task::done(Message::Io(IoMessage::PrefetchFiltered(pane_index, neighbors))),
+                    ])
+                }
+                None => {
+                    info!("No other image matches {:?}", filter);
+                    Task::none()
+                }
+            }
+        }
+    }
+}
+
+/// Collect ids of annotations whose bbox contains the given image-space point
+///
+/// Sorted by area ascending so the smallest (usually the intended) box comes first.
+pub fn annotations_at(annotations: &[ImageAnnotation], point: Point) -> Vec<u64> {
+    let mut hits: Vec<&ImageAnnotation> = annotations.iter()
+        .filter(|a| a.bbox.contains(point.x, point.y))
+        .collect();
+    hits.sort_by(|a, b| {
+        let area_a = a.bbox.width * a.bbox.height;
+        let area_b = b.bbox.width * b.bbox.height;
+        area_a.partial_cmp(&area_b).unwrap_or(std::cmp::Ordering::Equal)
+    });
+    hits.into_iter().map(|a| a.id).collect()
+}
+
+/// Pick the annotation to select after a click
+///
+/// Repeated clicks on the same spot cycle through overlapping annotations;
+/// clicking empty space clears the selection.
+pub fn next_selection(hits: &[u64], current: Option<u64>) -> Option<u64> {
+    if hits.is_empty() {
+        return None;
+    }
+    match current.and_then(|id| hits.iter().position(|&h| h == id)) {
+        Some(pos) => Some(hits[(pos + 1) % hits.len()]),
+        None => Some(hits[0]),
+    }
+}
+
+/// How far the cursor may move between press and release for it to still count as a click
+const CLICK_DRAG_THRESHOLD: f32 = 4.0;
+
+#[derive(Default)]
+struct HitLayerState {
+    /// Where the left button went down, until the cursor moves past the drag threshold
+    pressed_at: Option<Point>,
+}
+
+/// Transparent layer over the image handling annotation hover and selection
+///
+/// Draws a tooltip for the hovered annotation and an outline around the selected one.
+/// Clicks are published on release, and only when the cursor stayed put, so starting a pan
+/// drag on a box leaves the selection alone. Events are never captured so pan/zoom on the
+/// image keeps working.
+pub struct AnnotationHitLayer {
+    annotations: Vec<ImageAnnotation>,
+    image_size: (u32, u32),
+    zoom_scale: f32,
+    zoom_offset: Vector,
+    pane_index: usize,
+    selected: Option<u64>,
+}
+
+impl AnnotationHitLayer {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        annotations: Vec<ImageAnnotation>,
+        image_size: (u32, u32),
+        zoom_scale: f32,
+        zoom_offset: Vector,
+        pane_index: usize,
+        selected: Option<u64>,
+    ) -> Element<'static, Message, WinitTheme, Renderer> {
+        Element::new(Self {
+            annotations,
+            image_size,
+            zoom_scale,
+            zoom_offset,
+            pane_index,
+            selected,
+        })
+    }
+
+    fn transform(&self, bounds: Rectangle) -> ImageTransform {
+        ImageTransform::new(bounds, self.image_size, self.zoom_scale, self.zoom_offset)
+    }
+
+    fn hits_at(&self, bounds: Rectangle, cursor_position: Point) -> Vec<u64> {
+        let image_point = self.transform(bounds).to_image(cursor_position);
+        annotations_at(&self.annotations, image_point)
+    }
+}
+
+impl<Theme, R> iced_core::Widget<Message, Theme, R> for AnnotationHitLayer
+where
+    R: iced_core::Renderer + iced_core::text::Renderer,
+{
+    fn tag(&self) -> iced_core::widget::tree::Tag {
+        iced_core::widget::tree::Tag::of::<HitLayerState>()
+    }
+
+    fn state(&self) -> iced_core::widget::tree::State {
+        iced_core::widget::tree::State::new(HitLayerState::default())
+    }
+
+    fn size(&self) -> iced_core::Size<Length> {
+        iced_core::Size {
+            width: Length::Fill,
+            height: Length::Fill,
+        }
+    }
+
+    fn layout(
+        &self,
+        _tree: &mut iced_core::widget::Tree,
+        _renderer: &R,
+        limits: &iced_core::layout::Limits,
+    ) -> iced_core::layout::Node {
+        iced_core::layout::atomic(limits, Length::Fill, Length::Fill)
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut iced_core::widget::Tree,
+        event: iced_core::Event,
+        layout: iced_core::layout::Layout<'_>,
+        cursor: iced_core::mouse::Cursor,
+        _renderer: &R,
+        _clipboard: &mut dyn iced_core::Clipboard,
+        shell: &mut iced_core::Shell<'_, Message>,
+        _viewport: &Rectangle,
+    ) -> iced_core::event::Status {
+        use iced_core::mouse;
+
+        let bounds = layout.bounds();
+        let state = tree.state.downcast_mut::<HitLayerState>();
+        match event {
+            iced_core::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                state.pressed_at = cursor.position_over(bounds);
+            }
+            iced_core::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                if let (Some(pressed_at), Some(position)) = (state.pressed_at.take(), cursor.position_over(bounds)) {
+                    let hits = self.hits_at(bounds, pressed_at);
+                    // Skip the message when nothing is selected and nothing was hit
+                    if pressed_at.distance(position) <= CLICK_DRAG_THRESHOLD && (!hits.is_empty() || self.selected.is_some()) {
+                        shell.publish(Message::CocoAction(
+                            CocoMessage::AnnotationClicked(self.pane_index, hits)
+                        ));
+                    }
+                }
+            }
+            iced_core::Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                // A press that turned into a drag pans the image instead of selecting
+                if state.pressed_at.is_some_and(|pressed_at| pressed_at.distance(position) > CLICK_DRAG_THRESHOLD) {
+                    state.pressed_at = None;
+                }
+                // Tooltip follows the cursor
+                if cursor.is_over(bounds) {
+                    shell.request_redraw(iced_core::window::RedrawRequest::NextFrame);
+                }
+            }
+            _ => {}
+        }
+
+        // Never capture: the image below still needs the events for pan/zoom
+        iced_core::event::Status::Ignored
+    }
+
+    fn draw(
+        &self,
+        _tree: &iced_core::widget::Tree,
+        renderer: &mut R,
+        _theme: &Theme,
+        _style: &iced_core::renderer::Style,
+        layout: iced_core::layout::Layout<'_>,
+        cursor: iced_core::mouse::Cursor,
+        _viewport: &Rectangle,
+    ) {
+        use iced_core::text::Text;
+
+        let bounds = layout.bounds();
+        let transform = self.transform(bounds);
+
+        // Outline the selected annotation
+        if let Some(selected) = self.selected.and_then(|id| self.annotations.iter().find(|a| a.id == id)) {
+            let rect = transform.to_screen(&selected.bbox);
+            renderer.fill_quad(
+                iced_core::renderer::Quad {
+                    bounds: Rectangle {
+                        x: rect.x - 2.0,
+                        y: rect.y - 2.0,
+                        width: rect.width + 4.0,
+                        height: rect.height + 4.0,
+                    },
+                    border: iced_core::Border {
+                        radius: 2.0.into(),
+                        width: 3.0,
+                        color: Color::WHITE,
+                    },
+                    shadow: iced_core::Shadow::default(),
+                },
+                Color::TRANSPARENT,
+            );
+        }
+
+        // Tooltip for the hovered annotation (smallest box under the cursor)
+        let Some(position) = cursor.position_over(bounds) else {
+            return;
+        };
+        let hits = self.hits_at(bounds, position);
+        let Some(hovered) = hits.first().and_then(|id| self.annotations.iter().find(|a| a.id == *id)) else {
+            return;
+        };
+
+        let mut content = format!("#{} {}  area {:.0}", hovered.id, hovered.category_name, hovered.area);
+        if hits.len() > 1 {
+            content.push_str(&format!("  (+{} overlapping)", hits.len() - 1));
+        }
+
+        let text_size = 13.0;
+        let padding = 5.0;
+        let width = content.chars().count() as f32 * text_size * 0.58 + padding * 2.0;
+        let height = text_size * 1.4 + padding * 2.0;
+
+        // Keep the tooltip inside the overlay bounds
+        let x = (position.x + 14.0).min(bounds.x + bounds.width - width).max(bounds.x);
+        let y = (position.y + 18.0).min(bounds.y + bounds.height - height).max(bounds.y);
+
+        renderer.fill_quad(
+            iced_core::renderer::Quad {
+                bounds: Rectangle { x, y, width, height },
+                border: iced_core::Border {
+                    radius: 3.0.into(),
+                    width: 1.0,
+                    color: get_category_color(hovered.category_id),
+                },
+                shadow: iced_core::Shadow::default(),
+            },
+            Color::from([0.0, 0.0, 0.0, 0.8]),
+        );
+
+        renderer.fill_text(
+            Text {
+                content,
+                bounds: iced_core::Size::new(f32::INFINITY, height),
+                size: text_size.into(),
+                line_height: iced_core::text::LineHeight::default(),
+                font: renderer.default_font(),
+                horizontal_alignment: iced_core::alignment::Horizontal::Left,
+                vertical_alignment: iced_core::alignment::Vertical::Center,
+                shaping: iced_core::text::Shaping::Basic,
+                wrapping: iced_core::text::Wrapping::default(),
+            },
+            Point::new(x + padding, y + height / 2.0),
+            Color::WHITE,
+            bounds,
+        );
     }
 }
 
@@ -414,6 +878,56 @@ pub fn handle_keyboard_event(
                 CocoMessage::ToggleSegmentationMasks(pane_index)
             )))
         }
+        Key::Character("]") | Key::Character("[") => {
+            // Jump to the next/previous image matching the jump filter
+            let pane_index = get_pane_index();
+            let forward = key.as_ref() == Key::Character("]");
+            Some(Task::done(Message::CocoAction(
+                CocoMessage::JumpToMatch(pane_index, forward)
+            )))
+        }
         _ => None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coco::parser::BoundingBox;
+
+    fn annotation(id: u64, x: f32, y: f32, w: f32, h: f32) -> ImageAnnotation {
+        ImageAnnotation {
+            id,
+            bbox: BoundingBox { x, y, width: w, height: h },
+            category_id: 1,
+            category_name: "test".to_string(),
+            segmentation: None,
+            score: None,
+            area: w * h,
+        }
+    }
+
+    #[test]
+    fn test_annotations_at_sorts_smallest_first() {
+        let annotations = vec![
+            annotation(1, 0.0, 0.0, 100.0, 100.0),
+            annotation(2, 10.0, 10.0, 20.0, 20.0),
+            annotation(3, 200.0, 200.0, 10.0, 10.0),
+        ];
+        assert_eq!(annotations_at(&annotations, Point::new(15.0, 15.0)), vec![2, 1]);
+        assert_eq!(annotations_at(&annotations, Point::new(50.0, 50.0)), vec![1]);
+        assert!(annotations_at(&annotations, Point::new(150.0, 150.0)).is_empty());
+    }
+
+    #[test]
+    fn test_next_selection_cycles() {
+        let hits = vec![2, 1];
+        assert_eq!(next_selection(&hits, None), Some(2));
+        assert_eq!(next_selection(&hits, Some(2)), Some(1));
+        assert_eq!(next_selection(&hits, Some(1)), Some(2));
+        // Selection from elsewhere restarts at the smallest hit
+        assert_eq!(next_selection(&hits, Some(7)), Some(2));
+        // Clicking empty space clears
+        assert_eq!(next_selection(&[], Some(2)), None);
+    }
+}
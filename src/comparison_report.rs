@@ -0,0 +1,163 @@
+/// "Export Comparison Report" action
+///
+/// Pairs the images of the two panes by index, as the shared slider does, and writes an
+/// HTML report with `utils::comparison_report` on a worker thread. Progress and the result
+/// are shown in a bar above the footer.
+use std::path::PathBuf;
+use std::sync::Arc;
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, container, text, button, progress_bar};
+use log::{info, error};
+
+use crate::app::{Message, DataViewer};
+use crate::menu::PaneLayout;
+use crate::utils::comparison_report::export_report;
+
+#[derive(Debug, Default)]
+pub struct ComparisonReport {
+    progress: Option<(usize, usize)>,      // (pairs done, total) while exporting
+    status: Option<String>,
+}
+
+impl ComparisonReport {
+    pub fn is_running(&self) -> bool {
+        self.progress.is_some()
+    }
+}
+
+/// Updates sent from the export worker
+#[derive(Debug, Clone)]
+pub enum ReportEvent {
+    Progress(usize, usize),
+    Finished(Result<PathBuf, String>),
+}
+
+#[derive(Debug, Clone)]
+pub enum ReportMessage {
+    /// Ask for the output file, then start exporting
+    Export,
+    ExportTo(PathBuf),
+    DismissStatus,
+    Event(ReportEvent),
+}
+
+impl From<ReportMessage> for Message {
+    fn from(msg: ReportMessage) -> Self {
+        Message::ReportAction(msg)
+    }
+}
+
+/// Whether both panes have a folder open to compare
+pub fn is_available(app: &DataViewer) -> bool {
+    app.pane_layout == PaneLayout::DualPane && app.panes.len() >= 2 && app.panes.iter().take(2).all(|pane| pane.dir_loaded)
+}
+
+pub fn handle_report_message(app: &mut DataViewer, msg: ReportMessage) -> Task<Message> {
+    match msg {
+        ReportMessage::Export => {
+            if app.comparison_report.is_running() || !is_available(app) {
+                return Task::none();
+            }
+            Task::perform(
+                async move {
+                    rfd::AsyncFileDialog::new()
+                        .set_title("Export comparison report")
+                        .set_file_name("comparison.html")
+                        .add_filter("HTML", &["html"])
+                        .save_file()
+                        .await
+                },
+                |file_handle| match file_handle {
+                    Some(file) => ReportMessage::ExportTo(file.path().to_path_buf()).into(),
+                    None => Message::Nothing,
+                }
+            )
+        }
+        ReportMessage::ExportTo(output) => {
+            if !is_available(app) {
+                return Task::none();
+            }
+            let (pane_a, pane_b) = (&app.panes[0], &app.panes[1]);
+            let count = pane_a.img_cache.image_paths.len().min(pane_b.img_cache.image_paths.len());
+            let pairs: Vec<_> = (0..count)
+                .map(|i| (i, pane_a.img_cache.image_paths[i].clone(), pane_b.img_cache.image_paths[i].clone()))
+                .collect();
+            let titles = [pane_a, pane_b].map(|pane| pane.directory_path.clone().unwrap_or_default());
+            let archive_caches = [Arc::clone(&pane_a.archive_cache), Arc::clone(&pane_b.archive_cache)];
+
+            info!("Exporting comparison report of {} pair(s) to {}", pairs.len(), output.display());
+            let state = &mut app.comparison_report;
+            state.progress = Some((0, pairs.len()));
+            state.status = None;
+
+            let (sender, receiver) = futures::channel::mpsc::unbounded();
+            std::thread::spawn(move || {
+                let progress_sender = sender.clone();
+                let result = export_report(&pairs, archive_caches, titles, &output, |done, total| {
+                    let _ = progress_sender.unbounded_send(ReportEvent::Progress(done, total));
+                });
+                let _ = sender.unbounded_send(ReportEvent::Finished(result));
+            });
+
+            Task::run(receiver, |event| ReportMessage::Event(event).into())
+        }
+        ReportMessage::DismissStatus => {
+            app.comparison_report.status = None;
+            Task::none()
+        }
+        ReportMessage::Event(ReportEvent::Progress(done, total)) => {
+            app.comparison_report.progress = Some((done, total));
+            Task::none()
+        }
+        ReportMessage::Event(ReportEvent::Finished(result)) => {
+            let state = &mut app.comparison_report;
+            state.progress = None;
+            state.status = Some(match result {
+                Ok(path) => format!("Saved comparison report to {}", path.display()),
+                Err(e) => {
+                    error!("Comparison report failed: {}", e);
+                    format!("Comparison report failed: {}", e)
+                }
+            });
+            Task::none()
+        }
+    }
+}
+
+/// Progress bar while exporting, the result afterwards, or nothing
+pub fn view(state: &ComparisonReport) -> Element<'_, Message, WinitTheme, Renderer> {
+    let label_style = |_theme: &WinitTheme| iced_widget::text::Style {
+        color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+    };
+
+    let content: Element<'_, Message, WinitTheme, Renderer> = match (state.progress, &state.status) {
+        (Some((done, total)), _) => row![
+            text(format!("Comparing {} / {}", done, total)).size(11).style(label_style),
+            progress_bar(0.0..=total.max(1) as f32, done as f32)
+                .height(Length::Fixed(8.0))
+                .width(Length::Fill),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .into(),
+        (None, Some(status)) => row![
+            text(status).size(11).style(label_style).width(Length::Fill),
+            button(text("OK").size(11))
+                .padding([1, 6])
+                .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+                .on_press(ReportMessage::DismissStatus.into()),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .into(),
+        (None, None) => return container(text("")).height(0).into(),
+    };
+
+    container(content)
+        .padding([2, 8])
+        .width(Length::Fill)
+        .into()
+}
@@ -12,6 +12,9 @@ pub const DEFAULT_ATLAS_SIZE: u32 = 2048;
 pub const DEFAULT_DOUBLE_CLICK_THRESHOLD_MS: u16 = 250;
 pub const DEFAULT_ARCHIVE_CACHE_SIZE: u64 = 200;            // 200MB
 pub const DEFAULT_ARCHIVE_WARNING_THRESHOLD_MB: u64 = 500;  // 500MB threshold for warning dialog
+pub const DEFAULT_DIRECTORY_CACHE_MB: u64 = 512;          // 512MB of decoded images kept for recent directories
+pub use viewskater_core::load_limits::{DEFAULT_DECODE_THREADS, DEFAULT_MAX_CONCURRENT_READS};
+pub const DEFAULT_LOAD_TIMEOUT_SECS: u64 = 30;
 
 pub struct Config {
     #[allow(dead_code)]
@@ -22,6 +25,11 @@ pub struct Config {
     pub window_height: u32,                 // Default window height
     pub atlas_size: u32,                    // Size of the square texture atlas used in iced_wgpu (affects slider performance)
     pub double_click_threshold_ms: u16,     // Double-click detection threshold in milliseconds
+    pub directory_cache_mb: u64,            // Budget for decoded images of recently closed directories
+    pub decode_threads: usize,              // Images decoded in parallel by the loader
+    pub max_concurrent_reads: usize,        // Files read from disk at the same time
+    pub load_timeout_secs: u64,             // Seconds before a stuck image load is retried (0 = never)
+    pub convert_icc_profiles: bool,         // Convert images with an embedded ICC profile to sRGB when decoding
     pub window_position_x: i32,
     pub window_position_y: i32,
     pub window_state: WindowState,
@@ -29,7 +37,16 @@ pub struct Config {
 
 pub static CONFIG: Lazy<Config> = Lazy::new(|| {
     // Load settings from YAML file
-    let settings = UserSettings::load(None);
+    let mut settings = UserSettings::load(None);
+    if crate::safe_mode::is_enabled() {
+        let defaults = UserSettings::default();
+        settings.window_width = defaults.window_width;
+        settings.window_height = defaults.window_height;
+        settings.window_position_x = defaults.window_position_x;
+        settings.window_position_y = defaults.window_position_y;
+        settings.window_state = defaults.window_state;
+        settings.atlas_size = defaults.atlas_size;
+    }
 
     Config {
         cache_size: settings.cache_size,
@@ -39,6 +56,11 @@ pub static CONFIG: Lazy<Config> = Lazy::new(|| {
         window_height: settings.window_height,
         atlas_size: settings.atlas_size,
         double_click_threshold_ms: settings.double_click_threshold_ms,
+        directory_cache_mb: settings.directory_cache_mb,
+        decode_threads: settings.decode_threads,
+        max_concurrent_reads: settings.max_concurrent_reads,
+        load_timeout_secs: settings.load_timeout_secs,
+        convert_icc_profiles: settings.convert_icc_profiles,
         window_position_x: settings.window_position_x,
         window_position_y: settings.window_position_y,
         window_state: settings.window_state,
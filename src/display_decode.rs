@@ -0,0 +1,338 @@
+/// Decoding images at the size they are shown (`load_for_display` setting, and SVGs)
+///
+/// A 45-megapixel photo on a 1920x1080 window is decoded at many times the pixels it is
+/// shown with. With the setting on, JPEGs far larger than the window are decoded at 1/2, 1/4
+/// or 1/8 scale straight from their DCT coefficients, whichever still covers the window,
+/// which cuts decode time and cache memory for quick browsing.
+///
+/// SVG files have no pixel size of their own and are always rasterized to fit the window.
+///
+/// Once such an image is zoomed past 1:1, so a texel of the reduced texture covers more than
+/// one screen pixel, it is decoded again on a worker thread: a JPEG in full, an SVG at the
+/// size it is drawn at, so its edges stay crisp however far it is zoomed. The image shader
+/// draws the larger texture in place of the reduced one for as long as the image is shown.
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use iced_winit::runtime::Task;
+use iced_wgpu::engine::CompressionStrategy;
+use iced_wgpu::wgpu;
+use image::metadata::Orientation;
+use image::{DynamicImage, GenericImageView, ImageDecoder};
+use once_cell::sync::Lazy;
+use log::{debug, info, warn};
+
+use crate::app::{DataViewer, Message};
+use crate::pane::Pane;
+
+type TextureKey = wgpu::Id<wgpu::Texture>;
+
+/// An SVG is rendered a bit larger than it is drawn, so zooming in step by step doesn't
+/// render it again on every step
+const SVG_HEADROOM: f32 = 1.5;
+
+/// How the image of a reduced texture was decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reduced {
+    /// A JPEG at a reduced scale, and the size of the full image
+    Jpeg((u32, u32)),
+    /// An SVG rasterized to fit the window
+    Svg,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Physical size of the window, and its scale factor
+static VIEWPORT: Mutex<(u32, u32, f32)> = Mutex::new((1920, 1080, 1.0));
+
+#[derive(Debug, Default)]
+struct State {
+    /// Textures decoded at a reduced scale
+    reduced: HashMap<TextureKey, (Weak<wgpu::Texture>, Reduced)>,
+    /// Reduced textures zoomed past 1:1, and the physical width they are drawn at
+    wanted: HashMap<TextureKey, u32>,
+    /// Larger decodes of shown reduced textures and the width last asked for; the texture
+    /// is `None` until the first one is done
+    full: HashMap<TextureKey, (u32, Option<Arc<wgpu::Texture>>)>,
+}
+
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
+
+#[derive(Debug, Clone)]
+pub enum DisplayDecodeMessage {
+    /// Texture, the width asked for and the larger decode
+    FullDecoded(TextureKey, u32, Result<Arc<wgpu::Texture>, String>),
+}
+
+impl From<DisplayDecodeMessage> for Message {
+    fn from(msg: DisplayDecodeMessage) -> Self {
+        Message::DisplayDecodeAction(msg)
+    }
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Physical size of the window
+pub fn viewport() -> (u32, u32) {
+    VIEWPORT.lock().map_or((1920, 1080), |viewport| (viewport.0, viewport.1))
+}
+
+/// Scale factor of the window, physical pixels per logical one
+pub fn scale_factor() -> f32 {
+    VIEWPORT.lock().map_or(1.0, |viewport| viewport.2)
+}
+
+/// Keep track of the window size that reduced decodes have to cover
+pub fn set_viewport(width: u32, height: u32, scale_factor: f32) {
+    if let Ok(mut viewport) = VIEWPORT.lock() {
+        *viewport = (width, height, scale_factor);
+    }
+}
+
+fn swaps_axes(orientation: Orientation) -> bool {
+    matches!(orientation,
+        Orientation::Rotate90 | Orientation::Rotate270 | Orientation::Rotate90FlipH | Orientation::Rotate270FlipH)
+}
+
+/// Decodes an image to show at the window's size: an SVG always, a JPEG when the setting is on
+///
+/// Returns None when the image is decoded in full instead.
+pub fn decode_reduced(bytes: &[u8]) -> Option<(DynamicImage, Reduced)> {
+    if crate::utils::svg::is_svg(bytes) {
+        let (width, height) = viewport();
+        return match crate::utils::svg::rasterize(bytes, width, height) {
+            Ok(image) => Some((DynamicImage::ImageRgba8(image), Reduced::Svg)),
+            Err(e) => {
+                warn!("Failed to render SVG: {}", e);
+                None
+            }
+        };
+    }
+    if !is_enabled() {
+        return None;
+    }
+    decode_reduced_jpeg(bytes).map(|(image, full_size)| (image, Reduced::Jpeg(full_size)))
+}
+
+/// Decodes a JPEG at the smallest DCT scale that still covers the window, with its EXIF
+/// orientation applied
+///
+/// Returns the reduced image and the size of the full one, or None when the image isn't a
+/// JPEG or it isn't large enough to be reduced.
+fn decode_reduced_jpeg(bytes: &[u8]) -> Option<(DynamicImage, (u32, u32))> {
+    if !bytes.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let (viewport_width, viewport_height) = viewport();
+
+    let mut header = image::codecs::jpeg::JpegDecoder::new(Cursor::new(bytes)).ok()?;
+    let orientation = header.orientation().unwrap_or(Orientation::NoTransforms);
+    let (width, height) = header.dimensions();
+    // The window's size in the orientation the pixels are stored in
+    let (wanted_width, wanted_height) = if swaps_axes(orientation) {
+        (viewport_height, viewport_width)
+    } else {
+        (viewport_width, viewport_height)
+    };
+
+    let mut decoder = jpeg_decoder::Decoder::new(Cursor::new(bytes));
+    let (scaled_width, scaled_height) = decoder
+        .scale(wanted_width.min(u16::MAX as u32) as u16, wanted_height.min(u16::MAX as u32) as u16)
+        .ok()?;
+    let (scaled_width, scaled_height) = (scaled_width as u32, scaled_height as u32);
+    if scaled_width >= width {
+        return None;
+    }
+    let pixels = decoder.decode().ok()?;
+    let mut image = match decoder.info()?.pixel_format {
+        jpeg_decoder::PixelFormat::RGB24 => DynamicImage::ImageRgb8(image::RgbImage::from_raw(scaled_width, scaled_height, pixels)?),
+        jpeg_decoder::PixelFormat::L8 => DynamicImage::ImageLuma8(image::GrayImage::from_raw(scaled_width, scaled_height, pixels)?),
+        // CMYK and 16-bit images are left to the regular decoder
+        _ => return None,
+    };
+    image.apply_orientation(orientation);
+    crate::utils::icc_embedded::convert_to_srgb(bytes, &mut image);
+    debug!("Decoded {}x{} JPEG at {}x{} for display", width, height, scaled_width, scaled_height);
+
+    let full_size = if swaps_axes(orientation) { (height, width) } else { (width, height) };
+    Some((image, full_size))
+}
+
+/// Remember that `texture` holds a reduced decode
+pub fn mark_reduced(texture: &Arc<wgpu::Texture>, reduced: Reduced) {
+    if let Ok(mut state) = STATE.lock() {
+        state.reduced.insert(texture.global_id(), (Arc::downgrade(texture), reduced));
+    }
+}
+
+/// Pixel size of the image a texture shows, which is larger than the texture for a JPEG
+/// decoded at a reduced scale
+pub fn full_size(texture: &wgpu::Texture) -> (u32, u32) {
+    let reduced = STATE.lock().ok().and_then(|state| state.reduced.get(&texture.global_id()).map(|(_, reduced)| *reduced));
+    match reduced {
+        Some(Reduced::Jpeg(size)) => size,
+        _ => crate::cache::texture_tiles::image_size(texture),
+    }
+}
+
+/// Called by the image shader with the logical width `texture` is drawn at; asks for a
+/// larger decode once a reduced texture, or the larger one drawn in its place, is magnified
+pub fn note_shown_width(texture: &wgpu::Texture, shown_width: f32) {
+    let shown_width = shown_width * scale_factor();
+    let Ok(mut state) = STATE.lock() else {
+        return;
+    };
+    let key = texture.global_id();
+    let drawn = match state.full.get(&key) {
+        Some((_, Some(full))) => crate::cache::texture_tiles::image_size(full).0,
+        _ => texture.width(),
+    };
+    if shown_width > drawn as f32 && state.reduced.contains_key(&key) {
+        state.wanted.insert(key, shown_width.ceil() as u32);
+    }
+}
+
+/// Larger decode to draw instead of `texture`, once there is one
+pub fn full_texture(texture: &wgpu::Texture) -> Option<Arc<wgpu::Texture>> {
+    STATE.lock().ok()?.full.get(&texture.global_id())?.1.clone()
+}
+
+/// Decodes an image again at `width`: a JPEG in full, an SVG at that width
+fn decode_larger(
+    source: &crate::cache::img_cache::PathSource,
+    archive_cache: Option<&Mutex<crate::archive_cache::ArchiveCache>>,
+    reduced: Reduced,
+    width: u32,
+) -> Result<DynamicImage, String> {
+    match reduced {
+        Reduced::Jpeg(_) => crate::file_io::decode_path_source(source, archive_cache),
+        Reduced::Svg => {
+            let bytes = match archive_cache {
+                Some(archive_cache) => {
+                    let mut guard = archive_cache.lock().map_err(|e| e.to_string())?;
+                    crate::file_io::read_image_bytes(source, Some(&mut *guard))
+                }
+                None => crate::file_io::read_image_bytes(source, None),
+            }
+            .map_err(|e| e.to_string())?;
+            crate::utils::svg::rasterize(&bytes, width, u32::MAX).map(DynamicImage::ImageRgba8)
+        }
+    }
+}
+
+fn upload(device: &wgpu::Device, queue: &wgpu::Queue, image: DynamicImage) -> wgpu::Texture {
+    let image = crate::cache::texture_tiles::fit_to_device(image, device);
+    let (width, height) = image.dimensions();
+    let rgba = image.to_rgba8();
+    match crate::cache::texture_tiles::grid_for_device(device, width, height) {
+        Some(grid) => crate::cache::texture_tiles::create_tiled_texture(device, queue, rgba.as_raw(), grid),
+        None => crate::cache::cache_utils::create_and_upload_texture(
+            device, queue, rgba.as_raw(), width, height, CompressionStrategy::None),
+    }
+}
+
+/// Starts larger decodes of the reduced images zoomed past 1:1, and drops the larger
+/// textures of images no longer shown
+pub fn request(app: &DataViewer) -> Option<Task<Message>> {
+    let mut state = STATE.lock().ok()?;
+    state.reduced.retain(|_, (texture, _)| texture.strong_count() > 0);
+    let shown: Vec<(TextureKey, &Pane)> = app.panes.iter()
+        .filter(|pane| pane.dir_loaded)
+        .filter_map(|pane| Some((pane.scene.as_ref()?.get_texture()?.global_id(), pane)))
+        .collect();
+    state.full.retain(|key, _| shown.iter().any(|(shown_key, _)| shown_key == key));
+    let State { reduced, wanted, .. } = &mut *state;
+    wanted.retain(|key, _| reduced.contains_key(key));
+
+    let mut tasks = Vec::new();
+    for (key, pane) in shown {
+        let (Some(&shown_width), Some(&(_, reduced))) = (state.wanted.get(&key), state.reduced.get(&key)) else {
+            continue;
+        };
+        let width = match reduced {
+            // A JPEG is only decoded in full once
+            Reduced::Jpeg(_) if state.full.contains_key(&key) => continue,
+            Reduced::Jpeg((full_width, _)) => full_width,
+            Reduced::Svg => match state.full.get(&key) {
+                Some(&(asked, _)) if asked >= shown_width => continue,
+                _ => (shown_width as f32 * SVG_HEADROOM) as u32,
+            },
+        };
+        let Some(source) = pane.img_cache.image_paths.get(pane.img_cache.current_index).cloned() else {
+            continue;
+        };
+        // Keep drawing the last larger texture until the new one is done
+        let previous = state.full.remove(&key).and_then(|(_, texture)| texture);
+        state.full.insert(key, (width, previous));
+        info!("Decoding {} at {} px wide for zoom", source.file_name(), width);
+        let archive_cache = pane.has_compressed_file.then(|| Arc::clone(&pane.archive_cache));
+        let (device, queue) = (Arc::clone(&app.device), Arc::clone(&app.queue));
+        tasks.push(Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    let image = decode_larger(&source, archive_cache.as_deref(), reduced, width)?;
+                    Ok(Arc::new(upload(&device, &queue, image)))
+                })
+                .await
+                .unwrap_or_else(|e| Err(e.to_string()))
+            },
+            move |result| DisplayDecodeMessage::FullDecoded(key, width, result).into()
+        ));
+    }
+    (!tasks.is_empty()).then(|| Task::batch(tasks))
+}
+
+pub fn handle_display_decode_message(_app: &mut DataViewer, msg: DisplayDecodeMessage) -> Task<Message> {
+    match msg {
+        DisplayDecodeMessage::FullDecoded(key, width, result) => {
+            let Ok(mut state) = STATE.lock() else {
+                return Task::none();
+            };
+            match result {
+                // Keep it only while the reduced image is still shown and no larger one
+                // was asked for since
+                Ok(texture) => if let Some((asked, entry)) = state.full.get_mut(&key) {
+                    if *asked == width {
+                        *entry = Some(texture);
+                    }
+                },
+                Err(e) => warn!("Failed to decode larger image: {}", e),
+            }
+        }
+    }
+    Task::none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_reduced_covers_viewport() {
+        let image = image::RgbImage::from_fn(1600, 1200, |x, y| image::Rgb([(x % 256) as u8, (y % 256) as u8, 128]));
+        let mut jpeg = Vec::new();
+        image::codecs::jpeg::JpegEncoder::new(&mut jpeg).encode_image(&image).unwrap();
+
+        set_enabled(false);
+        assert!(decode_reduced(&jpeg).is_none());
+
+        set_enabled(true);
+        set_viewport(300, 300, 1.0);
+        let (reduced, kind) = decode_reduced(&jpeg).unwrap();
+        // 1/4 scale is the smallest that is still 300 wide or tall
+        assert_eq!(reduced.dimensions(), (400, 300));
+        assert_eq!(kind, Reduced::Jpeg((1600, 1200)));
+
+        // Too close to the window's size to gain anything
+        set_viewport(1500, 1100, 1.0);
+        assert!(decode_reduced(&jpeg).is_none());
+        set_enabled(false);
+    }
+}
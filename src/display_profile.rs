@@ -0,0 +1,195 @@
+/// ICC display compensation for the image view
+///
+/// Profiles are kept in `<config dir>/viewskater/icc/`: `<monitor name>.icc` applies to that
+/// monitor and `default.icc` to any other. The profile is picked again whenever the window
+/// moves to another monitor. The image pipelines read the active profile from a static, like
+/// the LUT, and convert linear sRGB to the display's primaries and tone curve in the final
+/// step of the image shader; UI elements are not compensated.
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use iced_winit::runtime::Task;
+use log::{error, info};
+
+use crate::app::Message;
+use crate::utils::icc::{parse_display_profile, DisplayProfile};
+
+const DEFAULT_PROFILE_NAME: &str = "default";
+
+#[derive(Debug, Default)]
+struct State {
+    monitor: Option<String>,
+    path: Option<PathBuf>,
+    profile: Option<DisplayProfile>,
+}
+
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
+
+#[derive(Debug, Clone)]
+pub enum DisplayProfileMessage {
+    /// Pick an ICC file for the monitor the window is on
+    Assign,
+    AssignFromPath(PathBuf),
+    /// Remove the profile of the current monitor, falling back to `default.icc`
+    Remove,
+}
+
+impl From<DisplayProfileMessage> for Message {
+    fn from(msg: DisplayProfileMessage) -> Self {
+        Message::DisplayProfileAction(msg)
+    }
+}
+
+pub fn icc_dir() -> PathBuf {
+    crate::app_dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("viewskater")
+        .join("icc")
+}
+
+/// Monitor name usable as a file name
+fn file_stem_for_monitor(monitor: &str) -> String {
+    let stem: String = monitor.trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if stem.is_empty() { DEFAULT_PROFILE_NAME.to_string() } else { stem }
+}
+
+/// Profile file for a monitor: its own one if present, else the default one
+fn profile_path_for(dir: &Path, monitor: Option<&str>) -> Option<PathBuf> {
+    let stems = monitor.map(file_stem_for_monitor).into_iter().chain([DEFAULT_PROFILE_NAME.to_string()]);
+    for stem in stems {
+        for extension in ["icc", "icm"] {
+            let path = dir.join(format!("{}.{}", stem, extension));
+            if path.is_file() {
+                return Some(path);
+            }
+        }
+    }
+    None
+}
+
+/// Active profile for the image shader
+pub fn active() -> Option<DisplayProfile> {
+    STATE.lock().ok()?.profile
+}
+
+pub fn active_path() -> Option<PathBuf> {
+    STATE.lock().ok()?.path.clone()
+}
+
+/// Load the profile for the monitor the window is now on
+pub fn select_for_monitor(monitor: Option<String>) {
+    let path = profile_path_for(&icc_dir(), monitor.as_deref());
+    let Ok(mut state) = STATE.lock() else {
+        return;
+    };
+    state.monitor = monitor;
+    if state.path == path {
+        return;
+    }
+    state.profile = path.as_ref().and_then(|path| {
+        match std::fs::read(path).map_err(|e| e.to_string()).and_then(|data| parse_display_profile(&data)) {
+            Ok(profile) => {
+                info!("Using display profile {}", path.display());
+                Some(profile)
+            }
+            Err(e) => {
+                error!("Ignoring display profile {}: {}", path.display(), e);
+                None
+            }
+        }
+    });
+    if path.is_none() && state.path.is_some() {
+        info!("No display profile for this monitor");
+    }
+    state.path = path;
+}
+
+fn current_monitor() -> Option<String> {
+    STATE.lock().ok()?.monitor.clone()
+}
+
+/// Profile file for the current monitor, which `Assign` writes and `Remove` deletes
+fn monitor_profile_path() -> PathBuf {
+    let stem = current_monitor().as_deref().map(file_stem_for_monitor).unwrap_or_else(|| DEFAULT_PROFILE_NAME.to_string());
+    icc_dir().join(format!("{}.icc", stem))
+}
+
+/// Re-read the profile after the files changed
+fn reselect() {
+    let monitor = current_monitor();
+    if let Ok(mut state) = STATE.lock() {
+        state.path = None;
+        state.profile = None;
+    }
+    select_for_monitor(monitor);
+}
+
+pub fn handle_display_profile_message(msg: DisplayProfileMessage) -> Task<Message> {
+    match msg {
+        DisplayProfileMessage::Assign => Task::perform(
+            async {
+                rfd::AsyncFileDialog::new()
+                    .set_title("Select display ICC profile")
+                    .add_filter("ICC profile", &["icc", "icm"])
+                    .pick_file()
+                    .await
+            },
+            |file_handle| match file_handle {
+                Some(file) => DisplayProfileMessage::AssignFromPath(file.path().to_path_buf()).into(),
+                None => Message::Nothing,
+            }
+        ),
+        DisplayProfileMessage::AssignFromPath(source) => {
+            // Check before copying so a bad file does not replace a working profile
+            let result = std::fs::read(&source)
+                .map_err(|e| e.to_string())
+                .and_then(|data| parse_display_profile(&data).map(|_| data))
+                .and_then(|data| {
+                    let target = monitor_profile_path();
+                    std::fs::create_dir_all(icc_dir())
+                        .and_then(|_| std::fs::write(&target, data))
+                        .map_err(|e| format!("{}: {}", target.display(), e))
+                });
+            match result {
+                Ok(()) => reselect(),
+                Err(e) => error!("Failed to use display profile {}: {}", source.display(), e),
+            }
+            Task::none()
+        }
+        DisplayProfileMessage::Remove => {
+            let path = monitor_profile_path();
+            if path.exists() {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    error!("Failed to remove {}: {}", path.display(), e);
+                }
+            }
+            reselect();
+            Task::none()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monitor_profile_falls_back_to_default() {
+        let dir = std::env::temp_dir().join(format!("viewskater_icc_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(profile_path_for(&dir, Some("DELL U2720Q")), None);
+
+        std::fs::write(dir.join("default.icm"), b"").unwrap();
+        assert_eq!(profile_path_for(&dir, Some("DELL U2720Q")), Some(dir.join("default.icm")));
+
+        assert_eq!(file_stem_for_monitor("DELL U2720Q"), "DELL_U2720Q");
+        std::fs::write(dir.join("DELL_U2720Q.icc"), b"").unwrap();
+        assert_eq!(profile_path_for(&dir, Some("DELL U2720Q")), Some(dir.join("DELL_U2720Q.icc")));
+        assert_eq!(profile_path_for(&dir, None), Some(dir.join("default.icm")));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,424 @@
+/// Per-image embeddings for similar-image navigation
+///
+/// Loads a `.npy` matrix or a parquet file of embedding vectors and answers
+/// "which images are closest to this one" by cosine similarity.
+///
+/// Rows are matched to images by filename when names are available (a `filename`
+/// column in parquet, or a `<stem>.txt` sidecar next to a `.npy` file with one
+/// filename per line); otherwise row `i` is the `i`-th image of the current list.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use log::{info, warn};
+
+/// Number of neighbors shown in the results strip
+pub const DEFAULT_NEIGHBOR_COUNT: usize = 12;
+
+/// Normalized embedding matrix loaded from disk
+pub struct EmbeddingIndex {
+    pub source: PathBuf,
+    dim: usize,
+    /// Row-major, L2-normalized vectors
+    vectors: Vec<f32>,
+    /// Filename for each row, if the file provided them
+    names: Option<Vec<String>>,
+}
+
+impl std::fmt::Debug for EmbeddingIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EmbeddingIndex")
+            .field("source", &self.source)
+            .field("rows", &self.len())
+            .field("dim", &self.dim)
+            .finish()
+    }
+}
+
+impl EmbeddingIndex {
+    /// Load embeddings from a `.npy` or `.parquet` file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let extension = path.extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        let (vectors, dim, names) = match extension.as_str() {
+            "npy" => {
+                let bytes = std::fs::read(path)
+                    .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                let (vectors, dim) = parse_npy(&bytes)?;
+                (vectors, dim, read_sidecar_names(path))
+            }
+            "parquet" => read_parquet_embeddings(path)?,
+            _ => return Err(format!("Unsupported embedding file: {}", path.display())),
+        };
+
+        Self::from_rows(path.to_path_buf(), vectors, dim, names)
+    }
+
+    fn from_rows(source: PathBuf, mut vectors: Vec<f32>, dim: usize, names: Option<Vec<String>>) -> Result<Self, String> {
+        if dim == 0 || vectors.is_empty() || vectors.len() % dim != 0 {
+            return Err("Embedding matrix is empty or malformed".to_string());
+        }
+        let rows = vectors.len() / dim;
+        if let Some(names) = &names {
+            if names.len() != rows {
+                return Err(format!("Found {} filenames for {} embedding rows", names.len(), rows));
+            }
+        }
+
+        for row in vectors.chunks_exact_mut(dim) {
+            let norm = row.iter().map(|v| v * v).sum::<f32>().sqrt();
+            if norm > 0.0 {
+                row.iter_mut().for_each(|v| *v /= norm);
+            }
+        }
+
+        info!("Loaded {} embeddings of dimension {} from {}", rows, dim, source.display());
+        Ok(Self { source, dim, vectors, names })
+    }
+
+    pub fn len(&self) -> usize {
+        self.vectors.len() / self.dim
+    }
+
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    fn row(&self, index: usize) -> &[f32] {
+        &self.vectors[index * self.dim..(index + 1) * self.dim]
+    }
+
+    /// The `k` rows most similar to `query`, best first, excluding `query` itself
+    pub fn nearest(&self, query: usize, k: usize) -> Vec<(usize, f32)> {
+        let target = self.row(query);
+        let mut scored: Vec<(usize, f32)> = (0..self.len())
+            .filter(|&i| i != query)
+            .map(|i| (i, self.row(i).iter().zip(target).map(|(a, b)| a * b).sum()))
+            .collect();
+
+        let by_score = |a: &(usize, f32), b: &(usize, f32)| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal);
+        if scored.len() > k {
+            scored.select_nth_unstable_by(k, by_score);
+            scored.truncate(k);
+        }
+        scored.sort_by(by_score);
+        scored
+    }
+}
+
+/// A neighbor shown in the results strip
+#[derive(Debug, Clone)]
+pub struct SimilarImage {
+    pub filename: String,
+    pub image_index: usize,
+    pub score: f32,
+}
+
+/// Results of the last "find similar" query
+#[derive(Debug, Clone)]
+pub struct SimilarResults {
+    pub pane_index: usize,
+    pub query: String,
+    pub neighbors: Vec<SimilarImage>,
+}
+
+/// Holds the loaded embeddings and the current similarity results
+#[derive(Default)]
+pub struct EmbeddingManager {
+    index: Option<Arc<EmbeddingIndex>>,
+    /// Filename -> embedding row
+    row_by_name: HashMap<String, usize>,
+    /// Embedding row -> filename
+    name_by_row: HashMap<usize, String>,
+    results: Option<SimilarResults>,
+}
+
+impl EmbeddingManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn has_embeddings(&self) -> bool {
+        self.index.is_some()
+    }
+
+    pub fn results(&self) -> Option<&SimilarResults> {
+        self.results.as_ref()
+    }
+
+    pub fn clear_results(&mut self) {
+        self.results = None;
+    }
+
+    /// Attach a loaded index to the image list of a pane
+    ///
+    /// Returns the number of images that have an embedding.
+    pub fn attach(&mut self, index: Arc<EmbeddingIndex>, image_names: &[String]) -> Result<usize, String> {
+        let mut row_by_name = HashMap::new();
+        match &index.names {
+            Some(names) => {
+                let known: std::collections::HashSet<&str> = image_names.iter().map(|s| s.as_str()).collect();
+                for (row, name) in names.iter().enumerate() {
+                    // Accept full or relative paths in the embedding file
+                    let base = Path::new(name).file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_else(|| name.clone());
+                    if known.contains(base.as_str()) {
+                        row_by_name.insert(base, row);
+                    }
+                }
+                if row_by_name.is_empty() {
+                    return Err("None of the embedding filenames match the current images".to_string());
+                }
+                if row_by_name.len() < image_names.len() {
+                    warn!("{} of {} images have no embedding", image_names.len() - row_by_name.len(), image_names.len());
+                }
+            }
+            None => {
+                if index.len() != image_names.len() {
+                    return Err(format!(
+                        "Embedding file has {} rows but {} images are loaded; add a filename list to match them",
+                        index.len(), image_names.len()
+                    ));
+                }
+                row_by_name.extend(image_names.iter().cloned().enumerate().map(|(row, name)| (name, row)));
+            }
+        }
+
+        self.name_by_row = row_by_name.iter().map(|(name, row)| (*row, name.clone())).collect();
+        self.row_by_name = row_by_name;
+        self.index = Some(index);
+        self.results = None;
+        Ok(self.row_by_name.len())
+    }
+
+    /// Find the images most similar to `filename` and store them as the current results
+    pub fn find_similar(&mut self, pane_index: usize, filename: &str, image_names: &[String], k: usize) -> Result<&SimilarResults, String> {
+        let index = self.index.as_ref().ok_or("No embeddings loaded")?;
+        let row = *self.row_by_name.get(filename)
+            .ok_or_else(|| format!("No embedding for {}", filename))?;
+
+        let image_index_by_name: HashMap<&str, usize> = image_names.iter()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), i))
+            .collect();
+
+        // Over-fetch so rows without a loaded image don't shrink the strip
+        let neighbors = index.nearest(row, k * 2)
+            .into_iter()
+            .filter_map(|(neighbor_row, score)| {
+                let name = self.name_by_row.get(&neighbor_row)?;
+                let image_index = *image_index_by_name.get(name.as_str())?;
+                Some(SimilarImage { filename: name.clone(), image_index, score })
+            })
+            .take(k)
+            .collect();
+
+        Ok(self.results.insert(SimilarResults {
+            pane_index,
+            query: filename.to_string(),
+            neighbors,
+        }))
+    }
+}
+
+/// Parse a little-endian float32/float64 2-D `.npy` array into (row-major values, columns)
+fn parse_npy(bytes: &[u8]) -> Result<(Vec<f32>, usize), String> {
+    const MAGIC: &[u8] = b"\x93NUMPY";
+    if bytes.len() < 10 || &bytes[..6] != MAGIC {
+        return Err("Not a .npy file".to_string());
+    }
+
+    let (header_len, header_start) = match bytes[6] {
+        1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+        2 | 3 if bytes.len() >= 12 => (u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize, 12),
+        version => return Err(format!("Unsupported .npy version {}", version)),
+    };
+    let data_start = header_start + header_len;
+    let header = bytes.get(header_start..data_start)
+        .map(String::from_utf8_lossy)
+        .ok_or("Truncated .npy header")?;
+
+    let field = |key: &str| -> Option<String> {
+        let re = regex::Regex::new(&format!(r#"'{}'\s*:\s*('[^']*'|\([^)]*\)|True|False)"#, key)).ok()?;
+        re.captures(&header).map(|c| c[1].to_string())
+    };
+
+    if field("fortran_order").as_deref() == Some("True") {
+        return Err("Fortran-ordered .npy arrays are not supported".to_string());
+    }
+    let descr = field("descr").ok_or("Missing dtype in .npy header")?;
+    let shape: Vec<usize> = field("shape")
+        .ok_or("Missing shape in .npy header")?
+        .trim_matches(|c| c == '(' || c == ')')
+        .split(',')
+        .filter_map(|s| s.trim().parse().ok())
+        .collect();
+    let (rows, cols) = match shape.as_slice() {
+        [rows, cols] => (*rows, *cols),
+        _ => return Err(format!("Expected a 2-D embedding matrix, got shape {:?}", shape)),
+    };
+
+    let dtype = descr.trim_matches('\'');
+    let item_size = match dtype {
+        "<f4" => 4,
+        "<f8" => 8,
+        other => return Err(format!("Unsupported .npy dtype {} (expected float32 or float64)", other)),
+    };
+    let data_len = rows.checked_mul(cols)
+        .and_then(|count| count.checked_mul(item_size))
+        .ok_or_else(|| format!("Shape {:?} is too large", shape))?;
+    let data = &bytes[data_start..];
+    if data.len() != data_len {
+        return Err(format!("Shape {:?} needs {} bytes of data, found {}", shape, data_len, data.len()));
+    }
+
+    let values = match item_size {
+        4 => data.chunks_exact(4)
+            .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect(),
+        _ => data.chunks_exact(8)
+            .map(|c| f64::from_le_bytes([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]]) as f32)
+            .collect(),
+    };
+    Ok((values, cols))
+}
+
+/// Read `<stem>.txt` next to a `.npy` file: one image filename per row
+fn read_sidecar_names(npy_path: &Path) -> Option<Vec<String>> {
+    let sidecar = npy_path.with_extension("txt");
+    let content = std::fs::read_to_string(&sidecar).ok()?;
+    info!("Using filename list {}", sidecar.display());
+    Some(content.lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect())
+}
+
+/// Column names recognized as the image filename in parquet files
+const NAME_COLUMNS: &[&str] = &["filename", "file_name", "path", "image_path", "name"];
+
+/// Read embeddings (first float list column, or one named `embedding`) and optional filenames from parquet
+fn read_parquet_embeddings(path: &Path) -> Result<(Vec<f32>, usize, Option<Vec<String>>), String> {
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::Field;
+
+    let file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let reader = SerializedFileReader::new(file)
+        .map_err(|e| format!("Failed to read parquet {}: {}", path.display(), e))?;
+
+    let mut vectors = Vec::new();
+    let mut names = Vec::new();
+    let mut dim = 0;
+
+    let rows = reader.get_row_iter(None).map_err(|e| e.to_string())?;
+    for row in rows {
+        let row = row.map_err(|e| e.to_string())?;
+        let mut embedding: Option<Vec<f32>> = None;
+        let mut name: Option<String> = None;
+
+        for (column, field) in row.get_column_iter() {
+            match field {
+                Field::ListInternal(list) if embedding.is_none() || column == "embedding" => {
+                    let values: Option<Vec<f32>> = list.elements().iter()
+                        .map(|f| match f {
+                            Field::Float(v) => Some(*v),
+                            Field::Double(v) => Some(*v as f32),
+                            _ => None,
+                        })
+                        .collect();
+                    if values.is_some() {
+                        embedding = values;
+                    }
+                }
+                Field::Str(s) if NAME_COLUMNS.contains(&column.as_str()) => name = Some(s.clone()),
+                _ => {}
+            }
+        }
+
+        let embedding = embedding.ok_or("Parquet row has no float list column")?;
+        if dim == 0 {
+            dim = embedding.len();
+        } else if embedding.len() != dim {
+            return Err(format!("Inconsistent embedding length: {} vs {}", embedding.len(), dim));
+        }
+        vectors.extend(embedding);
+        if let Some(name) = name {
+            names.push(name);
+        }
+    }
+
+    // Only use names when every row has one
+    let rows = if dim > 0 { vectors.len() / dim } else { 0 };
+    let names = (!names.is_empty() && names.len() == rows).then_some(names);
+    Ok((vectors, dim, names))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn npy_bytes(descr: &str, shape: (usize, usize), data: &[u8]) -> Vec<u8> {
+        let mut header = format!("{{'descr': '{}', 'fortran_order': False, 'shape': ({}, {}), }}", descr, shape.0, shape.1);
+        while (10 + header.len() + 1) % 64 != 0 {
+            header.push(' ');
+        }
+        header.push('\n');
+        let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+        bytes.extend((header.len() as u16).to_le_bytes());
+        bytes.extend(header.as_bytes());
+        bytes.extend(data);
+        bytes
+    }
+
+    #[test]
+    fn test_parse_npy_f32() {
+        let data: Vec<u8> = [1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0].iter().flat_map(|v| v.to_le_bytes()).collect();
+        let (values, cols) = parse_npy(&npy_bytes("<f4", (2, 3), &data)).unwrap();
+        assert_eq!(cols, 3);
+        assert_eq!(values, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+        assert!(parse_npy(&npy_bytes("<i8", (1, 1), &[0; 8])).is_err());
+        assert!(parse_npy(b"not numpy").is_err());
+
+        // The shape has to match the data exactly, without overflowing
+        assert!(parse_npy(&npy_bytes("<f4", (2, 3), &data[..20])).is_err());
+        assert!(parse_npy(&npy_bytes("<f4", (1, 3), &data)).is_err());
+        assert!(parse_npy(&npy_bytes("<f4", (usize::MAX, 2), &data)).is_err());
+        assert!(parse_npy(&npy_bytes("<f8", (usize::MAX / 8 + 1, 1), &data)).is_err());
+    }
+
+    #[test]
+    fn test_nearest_by_cosine() {
+        let vectors = vec![
+            1.0, 0.0,
+            0.9, 0.1,
+            0.0, 1.0,
+            -1.0, 0.0,
+        ];
+        let index = EmbeddingIndex::from_rows(PathBuf::from("test.npy"), vectors, 2, None).unwrap();
+        let nearest: Vec<usize> = index.nearest(0, 2).into_iter().map(|(i, _)| i).collect();
+        assert_eq!(nearest, vec![1, 2]);
+        assert_eq!(index.nearest(0, 10).len(), 3);
+    }
+
+    #[test]
+    fn test_attach_by_order_and_name() {
+        let images: Vec<String> = ["a.jpg", "b.jpg", "c.jpg"].iter().map(|s| s.to_string()).collect();
+        let vectors = vec![1.0, 0.0, 0.0, 1.0, 0.9, 0.1];
+
+        let mut manager = EmbeddingManager::new();
+        let index = EmbeddingIndex::from_rows(PathBuf::from("e.npy"), vectors.clone(), 2, None).unwrap();
+        assert_eq!(manager.attach(Arc::new(index), &images).unwrap(), 3);
+        let results = manager.find_similar(0, "a.jpg", &images, 1).unwrap();
+        assert_eq!(results.neighbors[0].filename, "c.jpg");
+        assert_eq!(results.neighbors[0].image_index, 2);
+
+        // Named rows in a different order than the image list
+        let names = Some(vec!["dir/c.jpg".to_string(), "a.jpg".to_string(), "missing.jpg".to_string()]);
+        let index = EmbeddingIndex::from_rows(PathBuf::from("e.npy"), vectors, 2, names).unwrap();
+        assert_eq!(manager.attach(Arc::new(index), &images).unwrap(), 2);
+        let results = manager.find_similar(0, "c.jpg", &images, 5).unwrap();
+        assert_eq!(results.neighbors.len(), 1);
+        assert_eq!(results.neighbors[0].image_index, 0);
+    }
+}
@@ -0,0 +1,248 @@
+/// Favorite images from any folder
+///
+/// Favorites are kept in `<config dir>/viewskater/favorites.yaml` as absolute paths with an
+/// optional note, which can be edited in the file and is shown in the sidebar. "Open
+/// Favorites" shows them together like a folder. Each entry also stores the file size and a
+/// fingerprint of its content, so a favorite renamed within its folder is found again when
+/// the favorites are opened, and its path is updated.
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use iced_winit::runtime::Task;
+use log::{info, warn};
+
+use crate::app::{DataViewer, Message};
+
+const FAVORITES_FILE_NAME: &str = "favorites.yaml";
+/// Bytes read from each end of a file for its fingerprint
+const FINGERPRINT_CHUNK: u64 = 64 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Favorite {
+    pub path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fingerprint: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum FavoritesMessage {
+    /// Add or remove the image shown in the first pane
+    ToggleCurrent,
+    Open,
+    Resolved(Vec<Favorite>),
+}
+
+impl From<FavoritesMessage> for Message {
+    fn from(msg: FavoritesMessage) -> Self {
+        Message::FavoritesAction(msg)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Favorites {
+    entries: Vec<Favorite>,
+}
+
+fn favorites_path() -> PathBuf {
+    crate::app_dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("viewskater")
+        .join(FAVORITES_FILE_NAME)
+}
+
+/// FNV-1a over the size and both ends of the file; stable across builds, unlike `DefaultHasher`
+fn fingerprint(path: &Path) -> Option<(u64, String)> {
+    let mut file = File::open(path).ok()?;
+    let size = file.metadata().ok()?.len();
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut feed = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    };
+    feed(&size.to_le_bytes());
+
+    let mut buffer = Vec::with_capacity(FINGERPRINT_CHUNK as usize);
+    file.by_ref().take(FINGERPRINT_CHUNK).read_to_end(&mut buffer).ok()?;
+    feed(&buffer);
+    if size > FINGERPRINT_CHUNK {
+        buffer.clear();
+        file.seek(SeekFrom::Start(size.saturating_sub(FINGERPRINT_CHUNK).max(FINGERPRINT_CHUNK))).ok()?;
+        file.read_to_end(&mut buffer).ok()?;
+        feed(&buffer);
+    }
+    Some((size, format!("{:016x}", hash)))
+}
+
+impl Favorite {
+    fn new(path: PathBuf) -> Self {
+        let (size, fingerprint) = fingerprint(&path).unzip();
+        Self { path, note: None, size, fingerprint }
+    }
+
+    /// A file in the favorite's old folder with the same content, after a rename
+    fn find_renamed(&self) -> Option<PathBuf> {
+        let size = self.size?;
+        let fingerprint = self.fingerprint.as_ref()?;
+        std::fs::read_dir(self.path.parent()?).ok()?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.metadata().is_ok_and(|metadata| metadata.is_file() && metadata.len() == size))
+            .map(|entry| entry.path())
+            .find(|path| fingerprint_matches(path, fingerprint))
+    }
+}
+
+fn fingerprint_matches(path: &Path, expected: &str) -> bool {
+    fingerprint(path).is_some_and(|(_, fingerprint)| fingerprint == expected)
+}
+
+/// Follow renamed favorites; entries that cannot be found are kept as they are
+fn resolve(mut entries: Vec<Favorite>) -> Vec<Favorite> {
+    for favorite in entries.iter_mut().filter(|favorite| !favorite.path.is_file()) {
+        if let Some(path) = favorite.find_renamed() {
+            info!("Favorite {} was renamed to {}", favorite.path.display(), path.display());
+            favorite.path = path;
+        }
+    }
+    entries
+}
+
+impl Favorites {
+    pub fn load() -> Self {
+        let entries = std::fs::read_to_string(favorites_path())
+            .ok()
+            .and_then(|yaml| serde_yaml::from_str(&yaml).ok())
+            .unwrap_or_default();
+        Self { entries }
+    }
+
+    fn save(&self) {
+        let path = favorites_path();
+        let result = path.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, serde_yaml::to_string(&self.entries).unwrap_or_default()));
+        if let Err(e) = result {
+            warn!("Failed to save favorites to {}: {}", path.display(), e);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&Favorite> {
+        self.entries.iter().find(|favorite| favorite.path == path)
+    }
+
+    /// Put the favorites in the order of `paths`; ones not listed go last
+    pub fn reorder(&mut self, paths: &[PathBuf]) {
+        self.entries.sort_by_key(|favorite| paths.iter().position(|path| *path == favorite.path).unwrap_or(paths.len()));
+        self.save();
+    }
+
+    /// Favorites for which `is_shown` holds, to pass on in a session
+    pub fn among(&self, is_shown: impl Fn(&Path) -> bool) -> Vec<Favorite> {
+        self.entries.iter().filter(|favorite| is_shown(&favorite.path)).cloned().collect()
+    }
+
+    /// Add the favorites of an opened session that aren't favorites here yet
+    pub fn merge(&mut self, favorites: Vec<Favorite>) {
+        let before = self.entries.len();
+        for favorite in favorites {
+            if self.get(&favorite.path).is_none() {
+                self.entries.push(favorite);
+            }
+        }
+        if self.entries.len() > before {
+            info!("Added {} favorite(s) from the session", self.entries.len() - before);
+            self.save();
+        }
+    }
+
+    fn toggle(&mut self, path: &Path) {
+        let path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+        };
+        if self.get(&path).is_some() {
+            self.entries.retain(|favorite| favorite.path != path);
+        } else {
+            self.entries.push(Favorite::new(path));
+        }
+    }
+}
+
+pub fn handle_favorites_message(app: &mut DataViewer, msg: FavoritesMessage) -> Task<Message> {
+    match msg {
+        FavoritesMessage::ToggleCurrent => {
+            if let Some(path) = app.plugin_image_path() {
+                app.favorites.toggle(&path);
+                app.favorites.save();
+            }
+            Task::none()
+        }
+        FavoritesMessage::Open => {
+            let entries = app.favorites.entries.clone();
+            let unresolved = entries.clone();
+            Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || resolve(entries))
+                        .await
+                        .unwrap_or(unresolved)
+                },
+                |entries| FavoritesMessage::Resolved(entries).into()
+            )
+        }
+        FavoritesMessage::Resolved(entries) => {
+            if entries != app.favorites.entries {
+                app.favorites.entries = entries;
+                app.favorites.save();
+            }
+            let paths: Vec<PathBuf> = app.favorites.entries.iter()
+                .map(|favorite| favorite.path.clone())
+                .filter(|path| path.is_file())
+                .collect();
+            if paths.len() < app.favorites.len() {
+                warn!("{} favorite(s) could not be found", app.favorites.len() - paths.len());
+            }
+            if paths.is_empty() {
+                return Task::none();
+            }
+            app.open_path_list(paths, 0, 0, crate::playlist::PlaylistSource::Favorites)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renamed_favorite_is_found_by_content() {
+        let dir = std::env::temp_dir().join(format!("viewskater_favorites_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let original = dir.join("a.png");
+        std::fs::write(&original, vec![7u8; 200_000]).unwrap();
+        std::fs::write(dir.join("other.png"), vec![8u8; 200_000]).unwrap();
+
+        let favorite = Favorite::new(original.clone());
+        let renamed = dir.join("b.png");
+        std::fs::rename(&original, &renamed).unwrap();
+
+        let resolved = resolve(vec![favorite]);
+        assert_eq!(resolved[0].path, renamed);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
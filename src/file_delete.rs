@@ -0,0 +1,423 @@
+/// Deleting files through the OS trash
+///
+/// "File -> Move to Trash" (or **Delete**) moves the active pane's file to the platform's trash
+/// or recycle bin with the `trash` crate, after a confirmation unless `confirm_delete` is off.
+/// With the `selection` feature, the images marked excluded in the folder can be trashed in
+/// one go; large batches show their progress in a bar above the footer and can be cancelled.
+/// Files the trash refuses, as on network shares without a trash folder, are listed in a
+/// second dialog that offers permanent deletion: nothing is ever deleted permanently without
+/// asking.
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, column, container, text, button, progress_bar, Space};
+use log::{info, warn};
+
+use crate::app::{DataViewer, Message};
+#[cfg(feature = "selection")]
+use crate::cache::img_cache::{PathIndex, PathSource};
+
+/// Number of file names listed in the dialogs before "and N more"
+const LISTED_FILES: usize = 5;
+
+#[derive(Debug, Clone)]
+pub enum DeleteEvent {
+    Progress(usize, usize),
+    Finished(DeleteSummary),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DeleteSummary {
+    pub removed: Vec<PathBuf>,
+    pub failed: Vec<(PathBuf, String)>,
+    pub permanent: bool,
+    pub cancelled: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum DeleteMessage {
+    /// Move the active pane's file to the trash
+    DeleteCurrent,
+    /// Move the images marked excluded in the active pane's folder to the trash
+    #[cfg(feature = "selection")]
+    DeleteExcluded,
+    Confirm,
+    /// Delete the files the trash refused, after asking
+    DeletePermanently,
+    Cancel,
+    DismissStatus,
+    Event(DeleteEvent),
+}
+
+impl From<DeleteMessage> for Message {
+    fn from(msg: DeleteMessage) -> Self {
+        Message::DeleteAction(msg)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct FileDelete {
+    pane_index: usize,
+    /// Files waiting for the trash confirmation
+    pending: Option<Vec<PathBuf>>,
+    /// Files the trash refused, waiting for the permanent deletion confirmation
+    refused: Option<(Vec<PathBuf>, String)>,
+    progress: Option<(usize, usize)>,      // (files done, total) while deleting
+    cancel: Option<Arc<AtomicBool>>,
+    status: Option<String>,
+}
+
+impl FileDelete {
+    pub fn is_running(&self) -> bool {
+        self.progress.is_some()
+    }
+
+    pub fn dialog_visible(&self) -> bool {
+        self.pending.is_some() || self.refused.is_some()
+    }
+}
+
+#[cfg(feature = "selection")]
+fn excluded_files(app: &DataViewer) -> Vec<PathBuf> {
+    let Some(pane) = app.panes.get(app.active_pane_index()).filter(|pane| pane.dir_loaded) else {
+        return Vec::new();
+    };
+    let paths = &pane.img_cache.image_paths;
+    paths.range(0..paths.len())
+        .filter_map(|source| match source {
+            PathSource::Filesystem(path) => Some(path),
+            _ => None,
+        })
+        .filter(|path| {
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            app.selection_manager.get_mark(&name) == crate::selection_manager::ImageMark::Excluded
+        })
+        .collect()
+}
+
+#[cfg(feature = "selection")]
+pub fn has_excluded(app: &DataViewer) -> bool {
+    !excluded_files(app).is_empty()
+}
+
+/// File to show after `removed` are gone: the first one left from `current` on, else the
+/// last one before it
+pub fn next_remaining(paths: &PathIndex, current: usize, removed: &HashSet<PathBuf>) -> Option<PathBuf> {
+    let current = current.min(paths.len());
+    paths.range(current..paths.len())
+        .chain(paths.range(0..current).rev())
+        .find(|source| !removed.contains(source.path()))
+        .map(|source| source.path().clone())
+}
+
+/// Trashes or deletes `paths` one by one, stopping early when `cancel` is set
+pub fn remove_files(
+    paths: &[PathBuf],
+    permanent: bool,
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(usize, usize),
+) -> DeleteSummary {
+    let mut summary = DeleteSummary { permanent, ..Default::default() };
+    for (i, path) in paths.iter().enumerate() {
+        if cancel.load(Ordering::Relaxed) {
+            summary.cancelled = true;
+            break;
+        }
+        let result = if permanent {
+            std::fs::remove_file(path).map_err(|e| e.to_string())
+        } else {
+            trash::delete(path).map_err(|e| e.to_string())
+        };
+        match result {
+            Ok(()) => summary.removed.push(path.clone()),
+            Err(e) => summary.failed.push((path.clone(), e)),
+        }
+        progress(i + 1, paths.len());
+    }
+    summary
+}
+
+fn start(app: &mut DataViewer, paths: Vec<PathBuf>, permanent: bool) -> Task<Message> {
+    let state = &mut app.file_delete;
+    info!("{} {} file(s)", if permanent { "Deleting" } else { "Trashing" }, paths.len());
+    let cancel = Arc::new(AtomicBool::new(false));
+    state.cancel = Some(Arc::clone(&cancel));
+    state.progress = Some((0, paths.len()));
+    state.status = None;
+
+    let (sender, receiver) = futures::channel::mpsc::unbounded();
+    std::thread::spawn(move || {
+        let progress_sender = sender.clone();
+        let summary = remove_files(&paths, permanent, &cancel, |done, total| {
+            let _ = progress_sender.unbounded_send(DeleteEvent::Progress(done, total));
+        });
+        let _ = sender.unbounded_send(DeleteEvent::Finished(summary));
+    });
+
+    Task::run(receiver, |event| DeleteMessage::Event(event).into())
+}
+
+/// Asks first unless `confirm_delete` is off
+fn request(app: &mut DataViewer, paths: Vec<PathBuf>) -> Task<Message> {
+    if paths.is_empty() || app.file_delete.is_running() || app.file_delete.dialog_visible() {
+        return Task::none();
+    }
+    app.file_delete.pane_index = app.active_pane_index();
+    if app.confirm_delete {
+        app.file_delete.pending = Some(paths);
+        Task::none()
+    } else {
+        start(app, paths, false)
+    }
+}
+
+/// Shows the pane's next file once `removed` are gone, or empties the pane
+fn reload_pane(app: &mut DataViewer, removed: &[PathBuf]) -> Task<Message> {
+    let pane_index = app.file_delete.pane_index;
+    let Some(pane) = app.panes.get(pane_index).filter(|pane| pane.dir_loaded && !pane.has_compressed_file) else {
+        return Task::none();
+    };
+    let paths = &pane.img_cache.image_paths;
+    let removed: HashSet<PathBuf> = removed.iter().cloned().collect();
+    if !paths.range(0..paths.len()).any(|source| removed.contains(source.path())) {
+        return Task::none();
+    }
+    let next = next_remaining(paths, pane.img_cache.current_index, &removed);
+
+    #[cfg(feature = "selection")]
+    {
+        for path in &removed {
+            if let Some(name) = path.file_name() {
+                app.selection_manager.clear_mark(&name.to_string_lossy());
+            }
+        }
+        if let Err(e) = app.selection_manager.save() {
+            warn!("Failed to save selections: {}", e);
+        }
+    }
+
+    match next {
+        Some(next) => app.initialize_dir_path(&next, pane_index),
+        None => {
+            app.reset_state(pane_index as isize);
+            Task::none()
+        }
+    }
+}
+
+fn finish(app: &mut DataViewer, summary: DeleteSummary) -> Task<Message> {
+    let state = &mut app.file_delete;
+    state.progress = None;
+    state.cancel = None;
+
+    let verb = if summary.permanent { "Deleted" } else { "Moved to trash:" };
+    let mut status = format!("{} {} file(s)", verb, summary.removed.len());
+    if summary.cancelled {
+        status.push_str(", cancelled");
+    }
+    if !summary.failed.is_empty() {
+        for (path, e) in &summary.failed {
+            warn!("Failed to remove {}: {}", path.display(), e);
+        }
+        status.push_str(&format!(", {} failed", summary.failed.len()));
+        // The trash is unavailable on some filesystems; permanent deletion is offered instead
+        if !summary.permanent {
+            let reason = summary.failed[0].1.clone();
+            state.refused = Some((summary.failed.into_iter().map(|(path, _)| path).collect(), reason));
+        }
+    }
+    state.status = Some(status);
+    reload_pane(app, &summary.removed)
+}
+
+pub fn handle_delete_message(app: &mut DataViewer, msg: DeleteMessage) -> Task<Message> {
+    match msg {
+        DeleteMessage::DeleteCurrent => match app.current_file() {
+            Some(path) => request(app, vec![path]),
+            None => Task::none(),
+        },
+        #[cfg(feature = "selection")]
+        DeleteMessage::DeleteExcluded => {
+            let paths = excluded_files(app);
+            request(app, paths)
+        }
+        DeleteMessage::Confirm => match app.file_delete.pending.take() {
+            Some(paths) => start(app, paths, false),
+            None => Task::none(),
+        },
+        DeleteMessage::DeletePermanently => match app.file_delete.refused.take() {
+            Some((paths, _)) => start(app, paths, true),
+            None => Task::none(),
+        },
+        DeleteMessage::Cancel => {
+            let state = &mut app.file_delete;
+            state.pending = None;
+            state.refused = None;
+            if let Some(cancel) = &state.cancel {
+                cancel.store(true, Ordering::Relaxed);
+            }
+            Task::none()
+        }
+        DeleteMessage::DismissStatus => {
+            app.file_delete.status = None;
+            Task::none()
+        }
+        DeleteMessage::Event(DeleteEvent::Progress(done, total)) => {
+            app.file_delete.progress = Some((done, total));
+            Task::none()
+        }
+        DeleteMessage::Event(DeleteEvent::Finished(summary)) => finish(app, summary),
+    }
+}
+
+fn file_list(paths: &[PathBuf]) -> String {
+    let name = |path: &PathBuf| path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let mut lines: Vec<String> = paths.iter().take(LISTED_FILES).map(name).collect();
+    if paths.len() > LISTED_FILES {
+        lines.push(format!("and {} more", paths.len() - LISTED_FILES));
+    }
+    lines.join("\n")
+}
+
+/// The trash or permanent deletion confirmation, while one is waiting
+pub fn dialog(state: &FileDelete) -> Option<Element<'_, Message, WinitTheme, Renderer>> {
+    let (title, body, confirm_label, confirm) = if let Some(paths) = &state.pending {
+        (
+            format!("Move {} file(s) to the trash?", paths.len()),
+            file_list(paths),
+            "Move to Trash",
+            DeleteMessage::Confirm,
+        )
+    } else if let Some((paths, reason)) = &state.refused {
+        (
+            format!("{} file(s) could not be moved to the trash", paths.len()),
+            format!("{}\n\n{}\n\nDelete them permanently? This cannot be undone.", file_list(paths), reason),
+            "Delete Permanently",
+            DeleteMessage::DeletePermanently,
+        )
+    } else {
+        return None;
+    };
+
+    let content = column![
+        text(title)
+            .size(18)
+            .font(iced_winit::core::Font {
+                family: iced_winit::core::font::Family::Name("Roboto"),
+                weight: iced_winit::core::font::Weight::Bold,
+                stretch: iced_winit::core::font::Stretch::Normal,
+                style: iced_winit::core::font::Style::Normal,
+            }),
+        text(body).size(13),
+        row![
+            Space::with_width(Length::Fill),
+            button(text("Cancel"))
+                .padding([3, 10])
+                .on_press(DeleteMessage::Cancel.into()),
+            button(text(confirm_label))
+                .padding([3, 10])
+                .on_press(confirm.into()),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    ]
+    .spacing(10)
+    .width(Length::Fixed(420.0));
+
+    Some(container(content)
+        .padding(15)
+        .style(|theme: &WinitTheme| {
+            iced_widget::container::Style {
+                background: Some(theme.extended_palette().background.base.color.into()),
+                text_color: Some(theme.extended_palette().primary.weak.text),
+                border: iced_winit::core::Border {
+                    color: theme.extended_palette().background.strong.color,
+                    width: 1.0,
+                    radius: iced_winit::core::border::Radius::from(8.0),
+                },
+                ..Default::default()
+            }
+        })
+        .into())
+}
+
+/// Progress bar while deleting, the result afterwards, or nothing
+pub fn view(state: &FileDelete) -> Element<'_, Message, WinitTheme, Renderer> {
+    let label_style = |_theme: &WinitTheme| iced_widget::text::Style {
+        color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+    };
+    let small_button = |label: &'static str, msg: DeleteMessage| {
+        button(text(label).size(11))
+            .padding([1, 6])
+            .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+            .on_press(msg.into())
+    };
+
+    let content: Element<'_, Message, WinitTheme, Renderer> = match (state.progress, &state.status) {
+        (Some((done, total)), _) => row![
+            text(format!("Deleting {} / {}", done, total)).size(11).style(label_style),
+            progress_bar(0.0..=total.max(1) as f32, done as f32)
+                .height(Length::Fixed(8.0))
+                .width(Length::Fill),
+            small_button("Cancel", DeleteMessage::Cancel),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .into(),
+        (None, Some(status)) => row![
+            text(status).size(11).style(label_style).width(Length::Fill),
+            small_button("OK", DeleteMessage::DismissStatus),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .into(),
+        (None, None) => return container(text("")).height(0).into(),
+    };
+
+    container(content)
+        .padding([2, 8])
+        .width(Length::Fill)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_remaining() {
+        let paths = PathIndex::from_filesystem_paths(["a.jpg", "b.jpg", "c.jpg", "d.jpg"].iter().map(PathBuf::from).collect());
+        let removed = |names: &[&str]| names.iter().map(PathBuf::from).collect::<HashSet<_>>();
+        assert_eq!(next_remaining(&paths, 1, &removed(&["b.jpg"])), Some(PathBuf::from("c.jpg")));
+        // Past the end, the previous file is shown
+        assert_eq!(next_remaining(&paths, 3, &removed(&["c.jpg", "d.jpg"])), Some(PathBuf::from("b.jpg")));
+        assert_eq!(next_remaining(&paths, 0, &removed(&["a.jpg", "b.jpg", "c.jpg", "d.jpg"])), None);
+    }
+
+    #[test]
+    fn test_remove_files_permanently() {
+        let dir = std::env::temp_dir().join("viewskater_delete_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("a.txt");
+        std::fs::write(&file, b"a").unwrap();
+        let missing = dir.join("missing.txt");
+
+        let mut reported = Vec::new();
+        let summary = remove_files(&[file.clone(), missing.clone()], true, &AtomicBool::new(false), |done, total| {
+            reported.push((done, total));
+        });
+        assert_eq!(summary.removed, vec![file.clone()]);
+        assert_eq!(summary.failed.len(), 1);
+        assert_eq!(reported, vec![(1, 2), (2, 2)]);
+        assert!(!file.exists());
+
+        let summary = remove_files(&[missing], true, &AtomicBool::new(true), |_, _| {});
+        assert!(summary.cancelled && summary.removed.is_empty() && summary.failed.is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
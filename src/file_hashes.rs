@@ -0,0 +1,210 @@
+/// MD5, SHA-256 and perceptual hashes of the current file
+///
+/// "File -> Copy Hash" (or **#** for SHA-256) hashes the file shown in the active pane on a
+/// worker thread and copies the result to the clipboard. Hashes computed for the shown image
+/// are also listed in the sidebar's Image section. The perceptual hash is the usual 64-bit
+/// DCT pHash of the decoded image, so near-identical images differ in only a few bits;
+/// `compute` is what a duplicate scan would call for it.
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use iced_runtime::clipboard;
+use iced_winit::runtime::Task;
+use image::DynamicImage;
+use log::{debug, warn};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+use crate::app::{DataViewer, Message};
+
+/// Side of the grayscale image the DCT runs on
+const PHASH_SIZE: usize = 32;
+/// Side of the low-frequency block whose signs make up the hash
+const PHASH_BLOCK: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashKind {
+    Md5,
+    Sha256,
+    PHash,
+}
+
+impl HashKind {
+    pub const ALL: [HashKind; 3] = [HashKind::Md5, HashKind::Sha256, HashKind::PHash];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HashKind::Md5 => "MD5",
+            HashKind::Sha256 => "SHA-256",
+            HashKind::PHash => "pHash",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum HashMessage {
+    /// Hash the active pane's file and copy the hash
+    Copy(HashKind),
+    Computed(PathBuf, HashKind, Result<String, String>),
+}
+
+impl From<HashMessage> for Message {
+    fn from(msg: HashMessage) -> Self {
+        Message::HashAction(msg)
+    }
+}
+
+/// Hashes of the last file hashed
+#[derive(Debug, Default)]
+pub struct FileHashes {
+    path: Option<PathBuf>,
+    values: HashMap<HashKind, String>,
+    pending: HashSet<HashKind>,
+}
+
+impl FileHashes {
+    /// Hashes known for `path`, in `HashKind::ALL` order
+    pub fn fields(&self, path: &Path) -> Vec<(String, String)> {
+        if self.path.as_deref() != Some(path) {
+            return Vec::new();
+        }
+        HashKind::ALL.iter()
+            .filter_map(|kind| self.values.get(kind).map(|value| (kind.label().to_string(), value.clone())))
+            .collect()
+    }
+}
+
+fn digest_file<D: Digest + std::io::Write>(path: &Path, mut hasher: D) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    std::io::copy(&mut file, &mut hasher).map_err(|e| e.to_string())?;
+    Ok(hex(&hasher.finalize()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// 1D DCT-II of `PHASH_SIZE` values, unscaled
+fn dct(values: &[f32]) -> Vec<f32> {
+    let n = values.len();
+    (0..n).map(|k| {
+        values.iter().enumerate()
+            .map(|(i, v)| v * (std::f32::consts::PI / n as f32 * (i as f32 + 0.5) * k as f32).cos())
+            .sum()
+    }).collect()
+}
+
+/// 64-bit DCT hash: the low-frequency coefficients of a 32x32 grayscale version, one bit per
+/// coefficient above their median
+pub fn phash(image: &DynamicImage) -> u64 {
+    let size = PHASH_SIZE as u32;
+    let gray = image.resize_exact(size, size, image::imageops::FilterType::Triangle).to_luma8();
+    let pixels: Vec<f32> = gray.pixels().map(|p| p.0[0] as f32).collect();
+
+    let rows: Vec<Vec<f32>> = pixels.chunks_exact(PHASH_SIZE).map(dct).collect();
+    let columns: Vec<Vec<f32>> = (0..PHASH_BLOCK)
+        .map(|x| dct(&rows.iter().map(|row| row[x]).collect::<Vec<_>>()))
+        .collect();
+    let block: Vec<f32> = (0..PHASH_BLOCK)
+        .flat_map(|y| columns.iter().map(move |column| column[y]))
+        .collect();
+
+    let mut sorted = block.clone();
+    sorted.sort_by(f32::total_cmp);
+    let median = (sorted[31] + sorted[32]) / 2.0;
+    block.iter().fold(0u64, |hash, &v| (hash << 1) | (v > median) as u64)
+}
+
+/// A hash of a file as shown to the user: lowercase hex
+pub fn compute(kind: HashKind, path: &Path) -> Result<String, String> {
+    match kind {
+        HashKind::Md5 => digest_file(path, Md5::new()),
+        HashKind::Sha256 => digest_file(path, Sha256::new()),
+        HashKind::PHash => {
+            let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+            let image = crate::exif_utils::decode_with_exif_orientation(&bytes)
+                .map_err(|e| format!("Failed to decode: {:?}", e))?;
+            Ok(format!("{:016x}", phash(&image)))
+        }
+    }
+}
+
+pub fn handle_hash_message(app: &mut DataViewer, msg: HashMessage) -> Task<Message> {
+    match msg {
+        HashMessage::Copy(kind) => {
+            let Some(path) = app.current_file() else {
+                return Task::none();
+            };
+            let hashes = &mut app.file_hashes;
+            if hashes.path.as_ref() != Some(&path) {
+                *hashes = FileHashes { path: Some(path.clone()), ..Default::default() };
+            }
+            if let Some(value) = hashes.values.get(&kind) {
+                return clipboard::write(value.clone());
+            }
+            if !hashes.pending.insert(kind) {
+                return Task::none();
+            }
+            Task::perform(
+                async move {
+                    let hash_path = path.clone();
+                    let result = tokio::task::spawn_blocking(move || compute(kind, &hash_path))
+                        .await
+                        .unwrap_or_else(|e| Err(e.to_string()));
+                    (path, kind, result)
+                },
+                |(path, kind, result)| HashMessage::Computed(path, kind, result).into()
+            )
+        }
+        HashMessage::Computed(path, kind, result) => {
+            let hashes = &mut app.file_hashes;
+            // Dropped if another file was hashed in the meantime
+            if hashes.path.as_ref() != Some(&path) {
+                return Task::none();
+            }
+            hashes.pending.remove(&kind);
+            match result {
+                Ok(value) => {
+                    debug!("{} of {}: {}", kind.label(), path.display(), value);
+                    hashes.values.insert(kind, value.clone());
+                    clipboard::write(value)
+                }
+                Err(e) => {
+                    warn!("Failed to hash {}: {}", path.display(), e);
+                    Task::none()
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+
+    #[test]
+    fn test_digests() {
+        let path = std::env::temp_dir().join("viewskater_hash_test.txt");
+        std::fs::write(&path, b"abc").unwrap();
+        assert_eq!(compute(HashKind::Md5, &path).unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(
+            compute(HashKind::Sha256, &path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_phash_is_stable_under_scaling() {
+        // A bright block in the top-left corner on a horizontal gradient
+        let scene = |size: u32, mirrored: bool| DynamicImage::ImageLuma8(GrayImage::from_fn(size, size, |x, y| {
+            let x = if mirrored { size - 1 - x } else { x };
+            let block = x < size * 2 / 5 && y < size / 3;
+            Luma([if block { 250 } else { (x * 160 / size) as u8 }])
+        }));
+        let large = phash(&scene(256, false));
+        assert!((phash(&scene(64, false)) ^ large).count_ones() <= 4);
+        assert!((phash(&scene(256, true)) ^ large).count_ones() > 16);
+    }
+}
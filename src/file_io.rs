@@ -1,9 +1,10 @@
+pub use viewskater_core::paths::long_path;
 use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
 use tokio::io::AsyncReadExt;
-use futures::future::join_all;
-use crate::cache::img_cache::LoadOperation;
+use futures::future::{join_all, BoxFuture};
+use crate::cache::img_cache::{LoadOperation, LoadPriority};
 use tokio::time::Instant;
 
 #[allow(unused_imports)]
@@ -13,18 +14,20 @@ use std::error::Error as StdError;
 use std::io;
 use std::io::Cursor;
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
 use once_cell::sync::Lazy;
 use image::{GenericImageView, ImageReader};
 use iced_wgpu::wgpu;
 
 use crate::cache::img_cache::CachedData;
 use crate::utils::timing::TimingStats;
+use crate::utils::frame_stats::{self, Stage};
 use crate::cache::img_cache::CacheStrategy;
 use iced_wgpu::engine::CompressionStrategy;
 use image::DynamicImage;
 
-const ALLOWED_EXTENSIONS: [&str; 15] = ["jpg", "jpeg", "png", "gif", "bmp", "ico", "tiff", "tif",
-        "webp", "pnm", "pbm", "pgm", "ppm", "qoi", "tga"];
+const ALLOWED_EXTENSIONS: [&str; 17] = ["jpg", "jpeg", "png", "gif", "bmp", "ico", "tiff", "tif",
+        "webp", "pnm", "pbm", "pgm", "ppm", "qoi", "tga", "exr", "hdr"];
 
 /// Check if the given bytes represent a JPEG 2000 file by checking magic bytes
 #[cfg(feature = "jp2")]
@@ -68,8 +71,68 @@ fn decode_jp2(bytes: &[u8]) -> Result<DynamicImage, std::io::ErrorKind> {
         })
 }
 
-/// Decode image from bytes, handling both standard formats and JPEG 2000.
-/// Applies EXIF orientation correction for supported formats (primarily JPEG).
+/// Check if the given bytes are an AVIF or HEIC/HEIF file by the brand of their `ftyp` box
+#[cfg(feature = "heif")]
+fn is_heif_format(bytes: &[u8]) -> bool {
+    const BRANDS: [&[u8]; 10] = [b"heic", b"heix", b"hevc", b"hevx", b"heim", b"heis",
+        b"mif1", b"msf1", b"avif", b"avis"];
+    bytes.len() >= 12 && &bytes[4..8] == b"ftyp" && BRANDS.contains(&&bytes[8..12])
+}
+
+/// Decode the primary image of an AVIF or HEIC/HEIF file from bytes
+#[cfg(feature = "heif")]
+fn decode_heif(bytes: &[u8]) -> Result<DynamicImage, std::io::ErrorKind> {
+    use libheif_rs::{ColorSpace, HeifContext, HeifError, LibHeif, RgbChroma};
+
+    let invalid = |e: HeifError| {
+        error!("Failed to decode HEIF image: {}", e);
+        std::io::ErrorKind::InvalidData
+    };
+    let context = HeifContext::read_from_bytes(bytes).map_err(invalid)?;
+    let handle = context.primary_image_handle().map_err(invalid)?;
+    // libheif applies the rotation and mirroring stored in the container
+    let image = LibHeif::new()
+        .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgba), None)
+        .map_err(invalid)?;
+
+    let plane = image.planes().interleaved.ok_or(std::io::ErrorKind::InvalidData)?;
+    // Rows may be padded past the pixels they hold
+    let row_len = plane.width as usize * 4;
+    let pixels: Vec<u8> = plane.data.chunks(plane.stride)
+        .take(plane.height as usize)
+        .flat_map(|row| &row[..row_len])
+        .copied()
+        .collect();
+    image::RgbaImage::from_raw(plane.width, plane.height, pixels)
+        .map(DynamicImage::ImageRgba8)
+        .ok_or(std::io::ErrorKind::InvalidData)
+}
+
+/// Check if the given bytes are a JPEG XL file, either a bare codestream or the ISOBMFF container
+#[cfg(feature = "jxl")]
+fn is_jxl_format(bytes: &[u8]) -> bool {
+    const CONTAINER_MAGIC: [u8; 12] = [0x00, 0x00, 0x00, 0x0C, 0x4A, 0x58, 0x4C, 0x20, 0x0D, 0x0A, 0x87, 0x0A];
+    bytes.starts_with(&[0xFF, 0x0A]) || bytes.starts_with(&CONTAINER_MAGIC)
+}
+
+/// Decode a JPEG XL image from bytes
+#[cfg(feature = "jxl")]
+fn decode_jxl(bytes: &[u8]) -> Result<DynamicImage, std::io::ErrorKind> {
+    // jxl-oxide renders with the orientation from the image header applied
+    let decoder = jxl_oxide::integration::JxlDecoder::new(std::io::Cursor::new(bytes)).map_err(|e| {
+        error!("Failed to read JPEG XL image: {}", e);
+        std::io::ErrorKind::InvalidData
+    })?;
+    DynamicImage::from_decoder(decoder).map_err(|e| {
+        error!("Failed to decode JPEG XL image: {}", e);
+        std::io::ErrorKind::InvalidData
+    })
+}
+
+/// Decode image from bytes, handling standard formats, JPEG 2000, AVIF/HEIC, JPEG XL, SVG,
+/// and PSD/XCF through their flattened image.
+/// Applies EXIF orientation correction for supported formats (primarily JPEG), and converts
+/// images with an embedded ICC profile to sRGB.
 pub fn decode_image_from_bytes(bytes: &[u8]) -> Result<DynamicImage, std::io::ErrorKind> {
     // Check for JPEG 2000 format first when feature is enabled
     // Note: JP2 doesn't use EXIF orientation, so decode directly
@@ -78,8 +141,133 @@ pub fn decode_image_from_bytes(bytes: &[u8]) -> Result<DynamicImage, std::io::Er
         return decode_jp2(bytes);
     }
 
+    // libheif reads orientation from the container rather than EXIF
+    #[cfg(feature = "heif")]
+    if is_heif_format(bytes) {
+        return decode_heif(bytes);
+    }
+
+    #[cfg(feature = "jxl")]
+    if is_jxl_format(bytes) {
+        return decode_jxl(bytes);
+    }
+
+    if crate::utils::layered::is_layered(bytes) {
+        return crate::utils::layered::decode(bytes)
+            .map(DynamicImage::ImageRgba8)
+            .map_err(|e| {
+                error!("Failed to flatten layered image: {}", e);
+                std::io::ErrorKind::InvalidData
+            });
+    }
+
+    // Vector images have no pixel size; render them to fit the window
+    if crate::utils::svg::is_svg(bytes) {
+        let (width, height) = crate::display_decode::viewport();
+        return crate::utils::svg::rasterize(bytes, width, height)
+            .map(DynamicImage::ImageRgba8)
+            .map_err(|e| {
+                error!("Failed to render SVG: {}", e);
+                std::io::ErrorKind::InvalidData
+            });
+    }
+
     // Use EXIF-aware decoding for standard formats
-    crate::exif_utils::decode_with_exif_orientation(bytes)
+    let mut image = crate::exif_utils::decode_with_exif_orientation(bytes)?;
+    crate::utils::icc_embedded::convert_to_srgb(bytes, &mut image);
+    Ok(image)
+}
+
+/// Image decoded to be shown, with what it takes to upload it
+struct DisplayImage {
+    image: Result<DynamicImage, std::io::ErrorKind>,
+    reduced: Option<crate::display_decode::Reduced>,
+    /// Colors of an indexed PNG, whose `image` then holds the palette indices
+    palette: Option<crate::cache::compact_textures::Palette>,
+}
+
+/// Decode image from bytes to show, at the window's size for SVGs and, when the
+/// `load_for_display` setting allows it, large JPEGs. Indexed PNGs are left as indices.
+fn decode_for_display(bytes: &[u8]) -> DisplayImage {
+    if let Some((indices, palette)) = crate::cache::compact_textures::decode_indexed_png(bytes) {
+        return DisplayImage { image: Ok(DynamicImage::ImageLuma8(indices)), reduced: None, palette: Some(palette) };
+    }
+    match crate::display_decode::decode_reduced(bytes) {
+        Some((image, reduced)) => DisplayImage { image: Ok(image), reduced: Some(reduced), palette: None },
+        None => DisplayImage { image: decode_image_from_bytes(bytes), reduced: None, palette: None },
+    }
+}
+
+/// Reads and decodes a pane's image off the UI thread, holding the pane's archive cache lock
+/// while reading when the image is inside an archive
+pub fn decode_path_source(
+    path_source: &crate::cache::img_cache::PathSource,
+    archive_cache: Option<&Mutex<crate::archive_cache::ArchiveCache>>,
+) -> Result<DynamicImage, String> {
+    let bytes = match archive_cache {
+        Some(archive_cache) => {
+            let mut guard = archive_cache.lock().map_err(|e| e.to_string())?;
+            read_image_bytes(path_source, Some(&mut *guard))
+        }
+        None => read_image_bytes(path_source, None),
+    }
+    .map_err(|e| e.to_string())?;
+    decode_image_from_bytes(&bytes).map_err(|e| format!("{:?}", e))
+}
+
+/// One frame of an animated image, composited onto the full canvas
+#[derive(Clone)]
+pub struct AnimationFrame {
+    pub image: image::RgbaImage,
+    pub delay: std::time::Duration,
+}
+
+impl std::fmt::Debug for AnimationFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AnimationFrame({}x{}, {:?})", self.image.width(), self.image.height(), self.delay)
+    }
+}
+
+/// Frames of an animated GIF or APNG, stopping once they would take more than `max_bytes`
+///
+/// Returns None for still images, including plain PNGs and single-frame GIFs.
+pub fn decode_animation(bytes: &[u8], max_bytes: usize) -> Option<Vec<AnimationFrame>> {
+    use image::AnimationDecoder;
+    use image::codecs::gif::GifDecoder;
+    use image::codecs::png::PngDecoder;
+
+    let frames = match image::guess_format(bytes).ok()? {
+        image::ImageFormat::Gif => GifDecoder::new(Cursor::new(bytes)).ok()?.into_frames(),
+        image::ImageFormat::Png => {
+            let decoder = PngDecoder::new(Cursor::new(bytes)).ok()?;
+            if !decoder.is_apng().ok()? {
+                return None;
+            }
+            decoder.apng().ok()?.into_frames()
+        }
+        _ => return None,
+    };
+
+    let mut decoded = Vec::new();
+    let mut total_bytes = 0;
+    for frame in frames {
+        let frame = match frame {
+            Ok(frame) => frame,
+            Err(e) => {
+                warn!("Stopped decoding animation at frame {}: {}", decoded.len(), e);
+                break;
+            }
+        };
+        let delay = std::time::Duration::from(frame.delay());
+        let image = frame.into_buffer();
+        total_bytes += image.as_raw().len();
+        if total_bytes > max_bytes {
+            warn!("Animation truncated to {} frames to stay within {} bytes", decoded.len(), max_bytes);
+            break;
+        }
+        decoded.push(AnimationFrame { image, delay });
+    }
+    (decoded.len() > 1).then_some(decoded)
 }
 
 /// Check if a file extension is a supported image format
@@ -90,16 +278,70 @@ fn is_supported_extension(ext: &str) -> bool {
         return true;
     }
 
+    // Shown through their embedded JPEG previews
+    if crate::utils::raw_preview::RAW_EXTENSIONS.contains(&ext_lower.as_str()) {
+        return true;
+    }
+
+    if crate::utils::svg::SVG_EXTENSIONS.contains(&ext_lower.as_str()) {
+        return true;
+    }
+
+    if crate::utils::layered::LAYERED_EXTENSIONS.contains(&ext_lower.as_str()) {
+        return true;
+    }
+
     #[cfg(feature = "jp2")]
     if ALLOWED_EXTENSIONS_JP2.contains(&ext_lower.as_str()) {
         return true;
     }
 
-    false
+    #[cfg(feature = "heif")]
+    if ALLOWED_EXTENSIONS_HEIF.contains(&ext_lower.as_str()) {
+        return true;
+    }
+
+    #[cfg(feature = "jxl")]
+    if ALLOWED_EXTENSIONS_JXL.contains(&ext_lower.as_str()) {
+        return true;
+    }
+
+    crate::plugins::source::handles_extension(&ext_lower)
+}
+
+/// Whether symlinked images are listed when reading a directory (`follow_symlinks` setting)
+static FOLLOW_SYMLINKS: AtomicBool = AtomicBool::new(true);
+
+pub fn set_follow_symlinks(follow: bool) {
+    FOLLOW_SYMLINKS.store(follow, Ordering::Relaxed);
+}
+
+pub fn follows_symlinks() -> bool {
+    FOLLOW_SYMLINKS.load(Ordering::Relaxed)
+}
+
+/// Check a symlinked image found while listing a directory. Links are listed only when
+/// `follow_symlinks` is on and they resolve to a file; otherwise the reason is returned.
+fn check_symlinked_image(path: &Path) -> Result<(), &'static str> {
+    if !follows_symlinks() {
+        return Err("symlink");
+    }
+    match fs::metadata(long_path(path)) {
+        Ok(metadata) if metadata.is_file() => Ok(()),
+        _ => Err("broken link"),
+    }
 }
 #[cfg(feature = "jp2")]
 const ALLOWED_EXTENSIONS_JP2: [&str; 3] = ["jp2", "j2k", "j2c"];
+#[cfg(feature = "heif")]
+const ALLOWED_EXTENSIONS_HEIF: [&str; 4] = ["avif", "heic", "heif", "hif"];
+#[cfg(feature = "jxl")]
+const ALLOWED_EXTENSIONS_JXL: [&str; 1] = ["jxl"];
+#[cfg(not(feature = "parquet"))]
 pub const ALLOWED_COMPRESSED_FILES: [&str; 3] = ["zip", "rar", "7z"];
+// Parquet datasets are opened through the same archive path (rows are preloaded)
+#[cfg(feature = "parquet")]
+pub const ALLOWED_COMPRESSED_FILES: [&str; 4] = ["zip", "rar", "7z", "parquet"];
 
 pub fn supported_image(name: &str) -> bool {
     // Filter out macOS metadata files
@@ -109,7 +351,9 @@ pub fn supported_image(name: &str) -> bool {
 
     let ext = name.split('.').next_back().unwrap_or("").to_lowercase();
 
-    if ALLOWED_EXTENSIONS.contains(&ext.as_str()) {
+    if ALLOWED_EXTENSIONS.contains(&ext.as_str())
+        || crate::utils::svg::SVG_EXTENSIONS.contains(&ext.as_str())
+        || crate::utils::layered::LAYERED_EXTENSIONS.contains(&ext.as_str()) {
         return true;
     }
 
@@ -118,7 +362,17 @@ pub fn supported_image(name: &str) -> bool {
         return true;
     }
 
-    false
+    #[cfg(feature = "heif")]
+    if ALLOWED_EXTENSIONS_HEIF.contains(&ext.as_str()) {
+        return true;
+    }
+
+    #[cfg(feature = "jxl")]
+    if ALLOWED_EXTENSIONS_JXL.contains(&ext.as_str()) {
+        return true;
+    }
+
+    crate::plugins::source::handles_extension(&ext)
 }
 
 static IMAGE_LOAD_STATS: Lazy<Mutex<TimingStats>> = Lazy::new(|| {
@@ -133,6 +387,8 @@ static GPU_UPLOAD_STATS: Lazy<Mutex<TimingStats>> = Lazy::new(|| {
 #[derive(Debug, Clone)]
 pub enum Error {
     DialogClosed,
+    /// Another file dialog is already showing
+    DialogBusy,
     InvalidSelection,
     InvalidExtension,
 }
@@ -160,148 +416,53 @@ pub fn get_filename(path: &str) -> Option<String> {
 /// * `Ok(Vec<u8>)` - The raw bytes of the image file
 /// * `Err(io::Error)` - An error if reading fails
 pub fn read_image_bytes(path_source: &crate::cache::img_cache::PathSource, archive_cache: Option<&mut crate::archive_cache::ArchiveCache>) -> Result<Vec<u8>, std::io::Error> {
-    use std::fs::File;
-    use std::io::{self, Read};
-    use memmap2::Mmap;
+    let bytes = read_file_bytes(path_source, archive_cache)?;
+    crate::plugins::source::convert(&path_source.file_name(), bytes)
+}
+
+/// Raw bytes of a file, before source plugins convert it
+fn read_file_bytes(path_source: &crate::cache::img_cache::PathSource, archive_cache: Option<&mut crate::archive_cache::ArchiveCache>) -> Result<Vec<u8>, std::io::Error> {
     use crate::cache::img_cache::PathSource;
 
-    // Dispatch based on PathSource type
     match path_source {
-        PathSource::Filesystem(path) => {
-            // Direct filesystem reading with mmap optimization
-            if !path.exists() {
-                return Err(io::Error::new(
-                    io::ErrorKind::NotFound,
-                    format!("Filesystem file not found: {}", path.display())
-                ));
-            }
-
-            let file = File::open(path)?;
-            let metadata = file.metadata()?;
-            let file_size = metadata.len() as usize;
-
-            // Use mmap for files over 1MB, regular reading for smaller files
-            if file_size > 1_048_576 {
-                let mmap = unsafe { Mmap::map(&file)? };
-                let bytes = mmap.to_vec();
-                debug!("Read {} bytes from filesystem using mmap: {}", bytes.len(), path.display());
-                Ok(bytes)
-            } else {
-                // For smaller files, regular reading is often faster
-                let mut buffer = Vec::with_capacity(file_size);
-                let mut file = File::open(path)?;
-                file.read_to_end(&mut buffer)?;
-                debug!("Read {} bytes from filesystem: {}", buffer.len(), path.display());
-                Ok(buffer)
-            }
+        PathSource::Filesystem(path) if crate::auto_rotate::rotation(path) != 0 => {
+            crate::auto_rotate::read_rotated(path)
         },
-
-        PathSource::Preloaded(path) => {
-            // Direct HashMap lookup - fastest path for preloaded content
-            let cache = archive_cache.ok_or_else(|| io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "Archive cache required for preloaded content"
-            ))?;
-
-            let path_str = path.to_string_lossy();
-            if let Some(data) = cache.get_preloaded_data(&path_str) {
-                debug!("Using preloaded data for: {}", path_str);
-                Ok(data.to_vec())
-            } else {
-                Err(io::Error::new(
-                    io::ErrorKind::NotFound,
-                    format!("Preloaded data not found: {}", path_str)
-                ))
-            }
+        PathSource::Filesystem(path) if crate::utils::raw_preview::is_raw(path) => {
+            crate::utils::raw_preview::read(path)
         },
-
-        PathSource::Archive(path) => {
-            // Direct archive reading - no filesystem checks
-            let cache = archive_cache.ok_or_else(|| io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "Archive cache required for archive content"
-            ))?;
-
-            let path_str = path.to_string_lossy();
-            debug!("Reading from archive: {}", path_str);
-            cache.read_from_archive(&path_str)
-                .map_err(|e| io::Error::other(format!("Failed to read from archive: {}", e)))
-        }
+        _ => viewskater_core::reader::read_bytes(path_source, archive_cache),
     }
 }
 
+/// Bytes of a filesystem image read directly, after source plugins convert it
+fn convert_source_bytes(path: &Path, bytes: Vec<u8>) -> Result<Vec<u8>, std::io::ErrorKind> {
+    crate::plugins::source::convert(&path.to_string_lossy(), bytes).map_err(|e| {
+        error!("Failed to convert {}: {}", path.display(), e);
+        e.kind()
+    })
+}
+
 /// Reads image bytes and returns (bytes, file_size_in_bytes)
 pub fn read_image_bytes_with_size(path_source: &crate::cache::img_cache::PathSource, archive_cache: Option<&mut crate::archive_cache::ArchiveCache>) -> Result<(Vec<u8>, u64), std::io::Error> {
-    use std::fs::File;
-    use std::io::{self, Read};
-    use memmap2::Mmap;
+    let (bytes, file_size) = read_file_bytes_with_size(path_source, archive_cache)?;
+    Ok((crate::plugins::source::convert(&path_source.file_name(), bytes)?, file_size))
+}
+
+fn read_file_bytes_with_size(path_source: &crate::cache::img_cache::PathSource, archive_cache: Option<&mut crate::archive_cache::ArchiveCache>) -> Result<(Vec<u8>, u64), std::io::Error> {
     use crate::cache::img_cache::PathSource;
 
-    // Dispatch based on PathSource type
     match path_source {
-        PathSource::Filesystem(path) => {
-            // Direct filesystem reading with mmap optimization
-            if !path.exists() {
-                return Err(io::Error::new(
-                    io::ErrorKind::NotFound,
-                    format!("Filesystem file not found: {}", path.display())
-                ));
-            }
-
-            let file = File::open(path)?;
-            let metadata = file.metadata()?;
-            let file_size = metadata.len();
-
-            // Use mmap for files over 1MB, regular reading for smaller files
-            if file_size > 1_048_576 {
-                let mmap = unsafe { Mmap::map(&file)? };
-                let bytes = mmap.to_vec();
-                debug!("Read {} bytes from filesystem using mmap: {}", bytes.len(), path.display());
-                Ok((bytes, file_size))
-            } else {
-                // For smaller files, regular reading is often faster
-                let mut buffer = Vec::with_capacity(file_size as usize);
-                let mut file = File::open(path)?;
-                file.read_to_end(&mut buffer)?;
-                debug!("Read {} bytes from filesystem: {}", buffer.len(), path.display());
-                Ok((buffer, file_size))
-            }
+        PathSource::Filesystem(path) if crate::auto_rotate::rotation(path) != 0 => {
+            let file_size = std::fs::metadata(long_path(path))?.len();
+            Ok((crate::auto_rotate::read_rotated(path)?, file_size))
         },
-
-        PathSource::Preloaded(path) => {
-            // Direct HashMap lookup - fastest path for preloaded content
-            let cache = archive_cache.ok_or_else(|| io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "Archive cache required for preloaded content"
-            ))?;
-
-            let path_str = path.to_string_lossy();
-            if let Some(data) = cache.get_preloaded_data(&path_str) {
-                debug!("Using preloaded data for: {}", path_str);
-                let file_size = data.len() as u64;
-                Ok((data.to_vec(), file_size))
-            } else {
-                Err(io::Error::new(
-                    io::ErrorKind::NotFound,
-                    format!("Preloaded data not found: {}", path_str)
-                ))
-            }
+        PathSource::Filesystem(path) if crate::utils::raw_preview::is_raw(path) => {
+            // The size on disk is the RAW file's, not the preview's
+            let file_size = std::fs::metadata(long_path(path))?.len();
+            Ok((crate::utils::raw_preview::read(path)?, file_size))
         },
-
-        PathSource::Archive(path) => {
-            // Direct archive reading - no filesystem checks
-            let cache = archive_cache.ok_or_else(|| io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "Archive cache required for archive content"
-            ))?;
-
-            let path_str = path.to_string_lossy();
-            debug!("Reading from archive: {}", path_str);
-            let bytes = cache.read_from_archive(&path_str)
-                .map_err(|e| io::Error::other(format!("Failed to read from archive: {}", e)))?;
-            let file_size = bytes.len() as u64;
-            Ok((bytes, file_size))
-        }
+        _ => viewskater_core::reader::read_bytes_with_size(path_source, archive_cache),
     }
 }
 
@@ -309,36 +470,7 @@ pub fn read_image_bytes_with_size(path_source: &crate::cache::img_cache::PathSou
 /// For filesystem files, uses std::fs::metadata() which only reads the inode.
 /// For archive/preloaded content, reads from archive cache.
 pub fn get_file_size(path_source: &crate::cache::img_cache::PathSource, archive_cache: Option<&mut crate::archive_cache::ArchiveCache>) -> u64 {
-    use crate::cache::img_cache::PathSource;
-
-    match path_source {
-        PathSource::Filesystem(path) => {
-            // Use fs::metadata() - only reads inode, not file content (O(1) operation)
-            std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
-        },
-        PathSource::Preloaded(path) => {
-            // Need to check preloaded data length
-            if let Some(cache) = archive_cache {
-                let path_str = path.to_string_lossy();
-                cache.get_preloaded_data(&path_str)
-                    .map(|data| data.len() as u64)
-                    .unwrap_or(0)
-            } else {
-                0
-            }
-        },
-        PathSource::Archive(path) => {
-            // For archives, we need to read from cache to get size
-            if let Some(cache) = archive_cache {
-                let path_str = path.to_string_lossy();
-                cache.read_from_archive(&path_str)
-                    .map(|bytes| bytes.len() as u64)
-                    .unwrap_or(0)
-            } else {
-                0
-            }
-        }
-    }
+    viewskater_core::reader::file_size(path_source, archive_cache)
 }
 
 #[allow(dead_code)]
@@ -371,16 +503,23 @@ async fn load_image_cpu_async(path_source: Option<crate::cache::img_cache::PathS
         // Dispatch based on PathSource type - get bytes and file size
         let (bytes, file_size) = match &path_source {
             crate::cache::img_cache::PathSource::Filesystem(path) => {
-                // Direct filesystem reading - get file size from metadata
-                let metadata = match tokio::fs::metadata(path).await {
-                    Ok(m) => m,
-                    Err(e) => return Err(e.kind()),
-                };
-                let file_size = metadata.len();
+                if let Some(bytes) = crate::cache::warm_cache::get(path) {
+                    let file_size = bytes.len() as u64;
+                    (convert_source_bytes(path, bytes)?, file_size)
+                } else {
+                    // Direct filesystem reading - get file size from metadata
+                    let _read_slot = crate::cache::load_limits::READ_SLOTS.acquire().await;
+                    let long = long_path(path);
+                    let metadata = match tokio::fs::metadata(&long).await {
+                        Ok(m) => m,
+                        Err(e) => return Err(e.kind()),
+                    };
+                    let file_size = metadata.len();
 
-                match tokio::fs::read(path).await {
-                    Ok(bytes) => (bytes, file_size),
-                    Err(e) => return Err(e.kind()),
+                    match tokio::fs::read(&long).await {
+                        Ok(bytes) => (convert_source_bytes(path, bytes)?, file_size),
+                        Err(e) => return Err(e.kind()),
+                    }
                 }
             },
             crate::cache::img_cache::PathSource::Archive(_) | crate::cache::img_cache::PathSource::Preloaded(_) => {
@@ -438,23 +577,32 @@ async fn load_image_gpu_async(
         let start = Instant::now();
 
         // Dispatch based on PathSource type - get decoded image and file size
-        let (img_result, file_size) = match &path_source {
+        let (decoded, file_size) = match &path_source {
             crate::cache::img_cache::PathSource::Filesystem(path) => {
-                // Read bytes and use unified decode function for format detection
-                // Get file size first
-                let file_size = match std::fs::metadata(path) {
-                    Ok(m) => m.len(),
-                    Err(e) => {
-                        error!("Failed to read filesystem metadata: {}", e);
-                        return Err(e.kind());
-                    }
-                };
-                match std::fs::read(path) {
-                    Ok(bytes) => (decode_image_from_bytes(&bytes), file_size),
-                    Err(e) => {
-                        error!("Failed to read filesystem image: {}", e);
-                        return Err(e.kind());
-                    }
+                if let Some(bytes) = crate::cache::warm_cache::get(path) {
+                    let file_size = bytes.len() as u64;
+                    (convert_source_bytes(path, bytes).map_or_else(|e| (Err(e), None), |bytes| decode_for_display(&bytes)), file_size)
+                } else {
+                    // Read bytes and use unified decode function for format detection
+                    // Get file size first; the read slot is released before decoding
+                    let read_slot = crate::cache::load_limits::READ_SLOTS.acquire().await;
+                    let long = long_path(path);
+                    let file_size = match std::fs::metadata(&long) {
+                        Ok(m) => m.len(),
+                        Err(e) => {
+                            error!("Failed to read filesystem metadata: {}", e);
+                            return Err(e.kind());
+                        }
+                    };
+                    let bytes = match std::fs::read(&long) {
+                        Ok(bytes) => bytes,
+                        Err(e) => {
+                            error!("Failed to read filesystem image: {}", e);
+                            return Err(e.kind());
+                        }
+                    };
+                    drop(read_slot);
+                    (convert_source_bytes(path, bytes).map_or_else(|e| (Err(e), None), |bytes| decode_for_display(&bytes)), file_size)
                 }
             },
             crate::cache::img_cache::PathSource::Archive(_) | crate::cache::img_cache::PathSource::Preloaded(_) => {
@@ -471,7 +619,7 @@ async fn load_image_gpu_async(
                     };
 
                     match cache_bytes_result {
-                        Ok((bytes, file_size)) => (decode_image_from_bytes(&bytes), file_size),
+                        Ok((bytes, file_size)) => (decode_for_display(&bytes), file_size),
                         Err(e) => {
                             error!("Failed to read archive content: {}", e);
                             return Err(std::io::ErrorKind::Other);
@@ -484,20 +632,85 @@ async fn load_image_gpu_async(
             }
         };
 
-        match img_result {
+        let DisplayImage { image, reduced, palette } = decoded;
+        match image {
             Ok(img) => {
-                // Apply size check and resize if image exceeds 8192px limit
-                let img = crate::cache::cache_utils::check_and_resize_if_oversized(img);
+                let track = |texture: Arc<wgpu::Texture>| {
+                    if let Some(reduced) = reduced {
+                        crate::display_decode::mark_reduced(&texture, reduced);
+                    }
+                    texture
+                };
+
+                // Create metadata with original file size and the full image's dimensions
+                let metadata = |width: u32, height: u32| {
+                    let (full_width, full_height) = match reduced {
+                        Some(crate::display_decode::Reduced::Jpeg(full_size)) => full_size,
+                        _ => (width, height),
+                    };
+                    ImageMetadata::new(full_width, full_height, file_size)
+                };
+
+                // 8-bit grayscale and indexed images stay single-channel, unless BC1 makes
+                // them smaller still
+                let (width, height) = img.dimensions();
+                if !crate::cache::cache_utils::should_use_compression(width, height, compression_strategy) {
+                    let decode_duration = start.elapsed();
+                    let upload_start = Instant::now();
+                    if let Some(texture) = crate::cache::compact_textures::create_texture(device, queue, &img, palette.as_ref()) {
+                        IMAGE_LOAD_STATS.lock().unwrap().add_measurement(decode_duration);
+                        frame_stats::record(Stage::Decode, decode_duration);
+                        crate::usage_stats::record_decode(decode_duration);
+                        let upload_duration = upload_start.elapsed();
+                        GPU_UPLOAD_STATS.lock().unwrap().add_measurement(upload_duration);
+                        frame_stats::record(Stage::Upload, upload_duration);
+                        return Ok(Some((CachedData::Gpu(track(texture)), metadata(width, height))));
+                    }
+                }
+
+                // HDR images keep their range in a float texture, 16-bit grayscale ones their
+                // precision for windowing
+                let decode_duration = start.elapsed();
+                let upload_start = Instant::now();
+                let texture = crate::cache::hdr_textures::create_texture(device, queue, &img)
+                    .or_else(|| crate::cache::gray16_textures::create_texture(device, queue, &img));
+                if let Some(texture) = texture {
+                    IMAGE_LOAD_STATS.lock().unwrap().add_measurement(decode_duration);
+                    frame_stats::record(Stage::Decode, decode_duration);
+                    crate::usage_stats::record_decode(decode_duration);
+                    let upload_duration = upload_start.elapsed();
+                    GPU_UPLOAD_STATS.lock().unwrap().add_measurement(upload_duration);
+                    frame_stats::record(Stage::Upload, upload_duration);
+                    return Ok(Some((CachedData::Gpu(track(texture)), metadata(width, height))));
+                }
+
+                let img = match &palette {
+                    Some(palette) => palette.expand(&img.into_luma8()),
+                    None if crate::cache::hdr_textures::is_float_image(&img) => crate::cache::hdr_textures::tone_map(&img),
+                    None => img,
+                };
+
+                // Tile images larger than the device allows, downscale them if even that fails
+                let img = crate::cache::texture_tiles::fit_to_device(img, device);
 
                 let (width, height) = img.dimensions();
                 let rgba = img.to_rgba8();
                 let rgba_data = rgba.as_raw();
+                let metadata = metadata(width, height);
 
-                // Create metadata with original file size and current dimensions
-                let metadata = ImageMetadata::new(width, height, file_size);
+                if let Some(grid) = crate::cache::texture_tiles::grid_for_device(device, width, height) {
+                    let upload_start = Instant::now();
+                    let texture = crate::cache::texture_tiles::create_tiled_texture(device, queue, rgba_data, grid);
+                    let upload_duration = upload_start.elapsed();
+                    GPU_UPLOAD_STATS.lock().unwrap().add_measurement(upload_duration);
+                    frame_stats::record(Stage::Upload, upload_duration);
+                    return Ok(Some((CachedData::Gpu(track(Arc::new(texture))), metadata)));
+                }
 
                 let duration = start.elapsed();
                 IMAGE_LOAD_STATS.lock().unwrap().add_measurement(duration);
+                frame_stats::record(Stage::Decode, duration);
+                crate::usage_stats::record_decode(duration);
 
                 let upload_start = Instant::now();
 
@@ -524,8 +737,9 @@ async fn load_image_gpu_async(
 
                     let upload_duration = upload_start.elapsed();
                     GPU_UPLOAD_STATS.lock().unwrap().add_measurement(upload_duration);
+                    frame_stats::record(Stage::Upload, upload_duration);
 
-                    return Ok(Some((CachedData::BC1(Arc::new(texture)), metadata)));
+                    return Ok(Some((CachedData::BC1(track(Arc::new(texture))), metadata)));
                 } else {
                     // Upload uncompressed
                     crate::cache::cache_utils::upload_uncompressed_texture(
@@ -534,8 +748,9 @@ async fn load_image_gpu_async(
 
                     let upload_duration = upload_start.elapsed();
                     GPU_UPLOAD_STATS.lock().unwrap().add_measurement(upload_duration);
+                    frame_stats::record(Stage::Upload, upload_duration);
 
-                    return Ok(Some((CachedData::Gpu(Arc::new(texture)), metadata)));
+                    return Ok(Some((CachedData::Gpu(track(Arc::new(texture))), metadata)));
                 }
             }
             Err(e) => {
@@ -549,6 +764,54 @@ async fn load_image_gpu_async(
 }
 
 
+/// Start loading one image with the cache strategy's loader
+///
+/// Resolves to the load itself once it holds its decode slot, so a timeout on the returned
+/// future only counts time spent loading and not time spent queued behind other images.
+async fn start_image_load(
+    path: Option<crate::cache::img_cache::PathSource>,
+    cache_strategy: CacheStrategy,
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    compression_strategy: CompressionStrategy,
+    archive_cache: Option<Arc<Mutex<crate::archive_cache::ArchiveCache>>>,
+) -> BoxFuture<'static, Result<Option<(CachedData, crate::cache::img_cache::ImageMetadata)>, std::io::ErrorKind>> {
+    match cache_strategy {
+        CacheStrategy::Cpu => {
+            debug!("load_images_async - loading image with CPU strategy");
+            spawn_decode(move || load_image_cpu_async(path, archive_cache)).await
+        },
+        CacheStrategy::Gpu => {
+            debug!("load_images_async - loading image with GPU strategy and compression: {:?}", compression_strategy);
+            spawn_decode(move || async move {
+                load_image_gpu_async(path, &device, &queue, compression_strategy, archive_cache).await
+            }).await
+        },
+    }
+}
+
+/// Waits for a decode slot, then runs `load` on the blocking pool so up to `decode_threads`
+/// images decode in parallel without stalling the executor's worker threads
+async fn spawn_decode<T, F>(load: impl FnOnce() -> F + Send + 'static) -> BoxFuture<'static, Result<T, std::io::ErrorKind>>
+where
+    T: Send + 'static,
+    F: std::future::Future<Output = Result<T, std::io::ErrorKind>>,
+{
+    let decode_slot = crate::cache::load_limits::DECODE_SLOTS.acquire().await;
+    let handle = tokio::runtime::Handle::current();
+    let task = tokio::task::spawn_blocking(move || {
+        // Held until the decode returns, even after the watchdog has given up on it
+        let _decode_slot = decode_slot;
+        handle.block_on(load())
+    });
+    Box::pin(async move {
+        task.await.unwrap_or_else(|e| {
+            error!("Image decode task failed: {}", e);
+            Err(std::io::ErrorKind::Other)
+        })
+    })
+}
+
 pub async fn load_images_async(
     paths: Vec<Option<crate::cache::img_cache::PathSource>>,
     cache_strategy: CacheStrategy,
@@ -561,26 +824,56 @@ pub async fn load_images_async(
     let start = Instant::now();
     debug!("load_images_async - cache_strategy: {:?}, compression: {:?}", cache_strategy, compression_strategy);
 
+    let is_background = load_operation.priority() == LoadPriority::Background;
+    let watchdog_timeout = crate::cache::load_watchdog::load_timeout();
+    // Slider jumps report each image to the progress bar and can be cancelled between images
+    let bulk_load = matches!(load_operation, LoadOperation::LoadPos(_))
+        .then(crate::bulk_load::current)
+        .flatten();
     let futures = paths.into_iter().enumerate().map(|(i, path)| {
         let device = Arc::clone(device);
         let queue = Arc::clone(queue);
         let pane_archive_cache = archive_caches.get(i).cloned().flatten();
+        let bulk_load = bulk_load.clone();
 
         async move {
-            match cache_strategy {
-                CacheStrategy::Cpu => {
-                    debug!("load_images_async - loading image with CPU strategy");
-                    load_image_cpu_async(path, pane_archive_cache).await
-                },
-                CacheStrategy::Gpu => {
-                    debug!("load_images_async - loading image with GPU strategy and compression: {:?}", compression_strategy);
-                    load_image_gpu_async(path, &device, &queue, compression_strategy, pane_archive_cache).await
-                },
+            if is_background {
+                crate::loading_status::yield_to_interactive_loads().await;
+            }
+            if let Some(bulk_load) = bulk_load.as_ref().filter(|bulk_load| bulk_load.is_cancelled()) {
+                bulk_load.finish_image();
+                return Ok(None);
+            }
+            let result = match watchdog_timeout {
+                None => start_image_load(path, cache_strategy, device, queue, compression_strategy, pane_archive_cache).await.await,
+                Some(timeout) => {
+                    let label = path.as_ref().map(|source| source.file_name().to_string()).unwrap_or_default();
+                    let start = || start_image_load(
+                        path.clone(), cache_strategy, Arc::clone(&device), Arc::clone(&queue), compression_strategy, pane_archive_cache.clone()
+                    );
+                    match crate::cache::load_watchdog::with_retry(&label, timeout, crate::cache::load_watchdog::LOAD_RETRIES, start).await {
+                        Some(result) => result,
+                        None => Ok(crate::cache::load_watchdog::placeholder(cache_strategy, &device, &queue)),
+                    }
+                }
+            };
+            if let Some(bulk_load) = &bulk_load {
+                bulk_load.finish_image();
             }
+            result
         }
     });
 
-    let results = join_all(futures).await;
+    // Background images load one at a time so each re-checks for pending interactive loads
+    let results = if is_background {
+        let mut results = Vec::new();
+        for future in futures {
+            results.push(future.await);
+        }
+        results
+    } else {
+        join_all(futures).await
+    };
     let duration = start.elapsed();
     debug!("Finished loading images in {:?}", duration);
 
@@ -604,8 +897,30 @@ pub async fn load_images_async(
     Ok((images, metadata_vec, Some(load_operation)))
 }
 
+/// Set while one of the pickers below is showing. The dialogs are async so the window keeps
+/// rendering behind them, which also means a shortcut could otherwise open a second one.
+static DIALOG_OPEN: AtomicBool = AtomicBool::new(false);
+
+struct DialogGuard;
+
+impl DialogGuard {
+    fn acquire() -> Result<Self, Error> {
+        if DIALOG_OPEN.swap(true, Ordering::AcqRel) {
+            debug!("A file dialog is already open");
+            return Err(Error::DialogBusy);
+        }
+        Ok(Self)
+    }
+}
+
+impl Drop for DialogGuard {
+    fn drop(&mut self) {
+        DIALOG_OPEN.store(false, Ordering::Release);
+    }
+}
 
 pub async fn pick_folder() -> Result<String, Error> {
+    let _guard = DialogGuard::acquire()?;
     let handle= rfd::AsyncFileDialog::new()
         .set_title("Open Folder with images")
         .pick_folder()
@@ -631,13 +946,16 @@ pub async fn pick_save_file() -> Result<PathBuf, Error> {
     #[cfg(not(feature = "jp2"))]
     let extensions = [&ALLOWED_EXTENSIONS[..]].concat();
     
-    let handle = rfd::FileDialog::new()
+    let _guard = DialogGuard::acquire()?;
+    let handle = rfd::AsyncFileDialog::new()
         .set_title("Save File")
         .add_filter("Supported files", extensions.as_slice())
-        .save_file();
+        .save_file()
+        .await;
 
     match handle {
-        Some(file_info) => {
+        Some(file_handle) => {
+            let file_info = file_handle.path().to_path_buf();
             if let Some(extension) = file_info.extension().and_then(|ext| ext.to_str()) {
                 if extensions.contains(&extension.to_lowercase().as_str()) {
                     Ok(file_info)
@@ -655,18 +973,29 @@ pub async fn pick_save_file() -> Result<PathBuf, Error> {
 
 pub async fn pick_file() -> Result<String, Error> {
     // https://stackoverflow.com/a/71194526
+    #[allow(unused_mut)]
+    let mut extensions = [
+        &ALLOWED_COMPRESSED_FILES[..],
+        &ALLOWED_EXTENSIONS[..],
+        &crate::utils::svg::SVG_EXTENSIONS[..],
+        &crate::utils::layered::LAYERED_EXTENSIONS[..],
+    ].concat();
     #[cfg(feature = "jp2")]
-    let extensions = [&ALLOWED_COMPRESSED_FILES[..], &ALLOWED_EXTENSIONS[..], &ALLOWED_EXTENSIONS_JP2[..]].concat();
-    #[cfg(not(feature = "jp2"))]
-    let extensions = [&ALLOWED_COMPRESSED_FILES[..], &ALLOWED_EXTENSIONS[..]].concat();
-    let handle = rfd::FileDialog::new()
+    extensions.extend_from_slice(&ALLOWED_EXTENSIONS_JP2);
+    #[cfg(feature = "heif")]
+    extensions.extend_from_slice(&ALLOWED_EXTENSIONS_HEIF);
+    #[cfg(feature = "jxl")]
+    extensions.extend_from_slice(&ALLOWED_EXTENSIONS_JXL);
+    let _guard = DialogGuard::acquire()?;
+    let handle = rfd::AsyncFileDialog::new()
         .set_title("Open File")
         .add_filter("Supported Files", extensions.as_slice())
-        .pick_file();
+        .pick_file()
+        .await;
 
     match handle {
-        Some(file_info) => {
-            let path = file_info.as_path();
+        Some(file_handle) => {
+            let path = file_handle.path();
             // Convert the extension to lowercase for case-insensitive comparison
             if let Some(extension) = path.extension().and_then(|ext| ext.to_str()) {
                 if extensions.contains(&extension.to_lowercase().as_str()) {
@@ -747,11 +1076,11 @@ pub async fn _literal_empty_async_block() -> Result<(), std::io::ErrorKind> {
 
 
 pub fn is_file(path: &Path) -> bool {
-    fs::metadata(path).map(|metadata| metadata.is_file()).unwrap_or(false)
+    fs::metadata(long_path(path)).map(|metadata| metadata.is_file()).unwrap_or(false)
 }
 
 pub fn is_directory(path: &Path) -> bool {
-    fs::metadata(path).map(|metadata| metadata.is_dir()).unwrap_or(false)
+    fs::metadata(long_path(path)).map(|metadata| metadata.is_dir()).unwrap_or(false)
 }
 
 pub fn get_file_index(files: &[PathBuf], file: &Path) -> Option<usize> {
@@ -968,9 +1297,14 @@ fn process_directory_entries(
     let mut image_paths: Vec<PathBuf> = Vec::new();
 
     for entry in entries.flatten() {
-        if let Some(extension) = entry.path().extension().and_then(std::ffi::OsStr::to_str) {
+        // Rebuilt from `directory_path` so listed paths keep the form the folder was opened with
+        let entry_path = directory_path.join(entry.file_name());
+        if let Some(extension) = entry_path.extension().and_then(std::ffi::OsStr::to_str) {
             if is_supported_extension(extension) {
-                image_paths.push(entry.path());
+                let is_symlink = entry.file_type().map(|t| t.is_symlink()).unwrap_or(false);
+                if !is_symlink || check_symlinked_image(&entry_path).is_ok() {
+                    image_paths.push(entry_path);
+                }
             }
         }
     }
@@ -1006,7 +1340,7 @@ pub fn get_image_paths(directory_path: &Path) -> Result<Vec<PathBuf>, ImageError
 fn get_image_paths_standard(directory_path: &Path) -> Result<Vec<PathBuf>, ImageError> {
     debug!("Standard directory reading for path: {}", directory_path.display());
     
-    let dir_entries = fs::read_dir(directory_path)
+    let dir_entries = fs::read_dir(long_path(directory_path))
         .map_err(ImageError::DirectoryError)?;
     
     process_directory_entries(dir_entries, directory_path)
@@ -1117,60 +1451,321 @@ fn convert_file_paths_to_image_paths(
 // Async Directory Enumeration (Issue #73 - NFS Performance Fix)
 // ============================================================================
 
-use crate::app::{DirectoryEnumResult, DirectoryEnumError};
-
-/// Async directory enumeration for non-blocking UI
-/// Uses tokio::fs for async I/O to prevent UI freezes on slow filesystems (NFS)
-pub async fn enumerate_directory_async(path: PathBuf) -> Result<DirectoryEnumResult, DirectoryEnumError> {
-    use tokio::fs as async_fs;
-
-    // Determine if path is a file or directory (sync metadata check is fast)
-    let (dir_path, is_file_drop) = if is_file(&path) {
-        let parent = path.parent()
-            .ok_or(DirectoryEnumError::NotFound)?
-            .to_path_buf();
-        (parent, true)
-    } else if is_directory(&path) {
-        (path.clone(), false)
+use crate::app::{DirectoryEnumResult, DirectoryEnumError, ListingEvent};
+use crate::cache::img_cache::PathIndex;
+use viewskater_core::path_index::{PathIndexBuilder, PAGED_THRESHOLD};
+
+/// Images a large folder opens on while the rest of it is still being listed
+const LISTING_FIRST_BATCH: usize = 2_000;
+/// Images sorted and merged at a time after the first batch
+const LISTING_CHUNK: usize = 20_000;
+
+/// Merges `chunk` into `sorted`, both already in natural order
+fn merge_sorted(sorted: Vec<PathBuf>, chunk: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut merged = Vec::with_capacity(sorted.len() + chunk.len());
+    let mut sorted = sorted.into_iter().peekable();
+    let mut chunk = chunk.into_iter().peekable();
+    while let (Some(a), Some(b)) = (sorted.peek(), chunk.peek()) {
+        let next = if alphanumeric_sort::compare_path(a, b).is_le() { &mut sorted } else { &mut chunk };
+        merged.extend(next.next());
+    }
+    merged.extend(sorted);
+    merged.extend(chunk);
+    merged
+}
+
+/// Paths of the sorted `runs` in one natural order, read from each a page at a time
+fn merge_runs(runs: &[PathIndex]) -> impl Iterator<Item = PathBuf> + '_ {
+    struct Head(PathBuf, usize);
+    impl PartialEq for Head {
+        fn eq(&self, other: &Self) -> bool {
+            self.cmp(other).is_eq()
+        }
+    }
+    impl Eq for Head {}
+    impl PartialOrd for Head {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+    // Reversed, so the max-heap pops the first path; ties keep the order of the runs
+    impl Ord for Head {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            alphanumeric_sort::compare_path(&other.0, &self.0).then(other.1.cmp(&self.1))
+        }
+    }
+
+    let mut readers: Vec<_> = runs.iter().map(|run| run.range(0..run.len())).collect();
+    let mut heads: std::collections::BinaryHeap<Head> = readers.iter_mut()
+        .enumerate()
+        .filter_map(|(run, reader)| Some(Head(reader.next()?.path().clone(), run)))
+        .collect();
+    std::iter::from_fn(move || {
+        let Head(path, run) = heads.pop()?;
+        if let Some(next) = readers[run].next() {
+            heads.push(Head(next.path().clone(), run));
+        }
+        Some(path)
+    })
+}
+
+fn listing_result(
+    image_paths: Vec<PathBuf>,
+    dir_path: &Path,
+    dropped: Option<&Path>,
+    skipped: crate::folder_stats::SkippedFiles,
+) -> DirectoryEnumResult {
+    let (image_paths, raw_pairs) = crate::raw_pairs::apply(image_paths, dropped);
+    let initial_index = dropped.and_then(|path| get_file_index(&image_paths, path)).unwrap_or(0);
+    DirectoryEnumResult {
+        file_paths: PathIndex::from_filesystem_paths(image_paths),
+        directory_path: dir_path.to_string_lossy().to_string(),
+        initial_index,
+        skipped,
+        raw_pairs,
+    }
+}
+
+/// `listing_result` for a folder listed as sorted runs, merged straight into a paged index
+fn paged_listing_result(
+    runs: Vec<PathIndex>,
+    dir_path: &Path,
+    dropped: Option<&Path>,
+    skipped: crate::folder_stats::SkippedFiles,
+) -> DirectoryEnumResult {
+    let dropped_name = dropped.and_then(Path::file_name);
+    let mut initial_index = None;
+    let mut file_paths = PathIndexBuilder::new();
+    let raw_pairs = crate::raw_pairs::apply_sorted(merge_runs(&runs), dropped, |path| {
+        if initial_index.is_none() && dropped_name.is_some() && path.file_name() == dropped_name {
+            initial_index = Some(file_paths.len());
+        }
+        file_paths.push(path);
+    });
+    DirectoryEnumResult {
+        file_paths: file_paths.finish(),
+        directory_path: dir_path.to_string_lossy().to_string(),
+        initial_index: initial_index.unwrap_or(0),
+        skipped,
+        raw_pairs,
+    }
+}
+
+/// Lists the images of the folder `path` is in or names, sending `Complete` at the end
+///
+/// A folder with more than `LISTING_FIRST_BATCH` images also sends a `Partial` listing of the
+/// first ones found, so it can be opened and browsed right away, and a `Progress` count after
+/// every further chunk. Chunks are sorted as they are read and merged into the images sorted
+/// so far, which spreads the sort of a huge folder over the listing instead of leaving it
+/// all to the end. Past `PAGED_THRESHOLD` images, the sorted chunks are paged out to disk
+/// instead and merged into the final paged list at the end, so the whole listing is never
+/// held in memory.
+pub fn list_directory(path: &Path, mut send: impl FnMut(ListingEvent)) {
+    let result = list_directory_chunked(path, &mut send);
+    send(ListingEvent::Complete(result));
+}
+
+fn list_directory_chunked(
+    path: &Path,
+    send: &mut impl FnMut(ListingEvent),
+) -> Result<DirectoryEnumResult, DirectoryEnumError> {
+    let (dir_path, dropped) = if is_file(path) {
+        let parent = path.parent().ok_or(DirectoryEnumError::NotFound)?;
+        (parent.to_path_buf(), Some(path))
+    } else if is_directory(path) {
+        (path.to_path_buf(), None)
     } else {
         return Err(DirectoryEnumError::NotFound);
     };
 
-    // Async directory enumeration
-    let mut entries = async_fs::read_dir(&dir_path)
-        .await
-        .map_err(|e| DirectoryEnumError::DirectoryError(e.to_string()))?;
-
-    let mut image_paths: Vec<PathBuf> = Vec::new();
+    let entries = fs::read_dir(long_path(&dir_path)).map_err(|e| match e.kind() {
+        io::ErrorKind::PermissionDenied => DirectoryEnumError::AccessDenied(path.to_path_buf()),
+        _ => DirectoryEnumError::DirectoryError(e.to_string()),
+    })?;
+
+    let mut sorted: Vec<PathBuf> = Vec::new();
+    // Sorted chunks of a folder too large to hold, each paged out
+    let mut runs: Vec<PathIndex> = Vec::new();
+    let mut chunk: Vec<PathBuf> = Vec::new();
+    let mut listed = 0;
+    let mut skipped = crate::folder_stats::SkippedFiles::default();
+    let mut opened = false;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| DirectoryEnumError::DirectoryError(e.to_string()))?;
+        let entry_path = dir_path.join(entry.file_name());
+        let is_image = entry_path.extension()
+            .and_then(std::ffi::OsStr::to_str)
+            .is_some_and(is_supported_extension);
+        let file_type = entry.file_type().ok();
+        let is_symlink = file_type.is_some_and(|t| t.is_symlink());
+        if is_image {
+            match if is_symlink { check_symlinked_image(&entry_path) } else { Ok(()) } {
+                Ok(()) => chunk.push(entry_path),
+                Err(reason) => skipped.add_kind(reason),
+            }
+        } else if !file_type.is_some_and(|t| t.is_dir()) && !(is_symlink && entry_path.is_dir()) {
+            skipped.add(&entry_path);
+        }
 
-    while let Some(entry) = entries.next_entry().await
-        .map_err(|e| DirectoryEnumError::DirectoryError(e.to_string()))?
-    {
-        let entry_path = entry.path();
-        if let Some(extension) = entry_path.extension().and_then(std::ffi::OsStr::to_str) {
-            if is_supported_extension(extension) {
-                image_paths.push(entry_path);
+        if chunk.len() < if opened { LISTING_CHUNK } else { LISTING_FIRST_BATCH } {
+            continue;
+        }
+        alphanumeric_sort::sort_path_slice(&mut chunk);
+        listed += chunk.len();
+        if listed > PAGED_THRESHOLD {
+            if !sorted.is_empty() {
+                runs.push(paged_run(std::mem::take(&mut sorted)));
+            }
+            runs.push(paged_run(std::mem::take(&mut chunk)));
+        } else {
+            sorted = merge_sorted(sorted, std::mem::take(&mut chunk));
+        }
+        if opened {
+            send(ListingEvent::Progress(listed));
+            continue;
+        }
+        opened = true;
+        // A dropped file not reached yet is shown in its place until the full listing arrives
+        let mut first = sorted.clone();
+        let dropped_image = dropped
+            .filter(|dropped| dropped.extension().and_then(std::ffi::OsStr::to_str).is_some_and(is_supported_extension))
+            .and_then(Path::file_name)
+            .map(|name| dir_path.join(name));
+        if let Some(dropped) = dropped_image {
+            if let Err(at) = first.binary_search_by(|p| alphanumeric_sort::compare_path(p, &dropped)) {
+                first.insert(at, dropped);
             }
         }
+        debug!("Opening {} on the first {} images while listing continues", dir_path.display(), first.len());
+        send(ListingEvent::Partial(listing_result(first, &dir_path, dropped, Default::default())));
     }
 
+    alphanumeric_sort::sort_path_slice(&mut chunk);
+    if !runs.is_empty() {
+        runs.push(PathIndex::from_filesystem_paths(chunk));
+        return Ok(paged_listing_result(runs, &dir_path, dropped, skipped));
+    }
+    let image_paths = merge_sorted(sorted, chunk);
     if image_paths.is_empty() {
         return Err(DirectoryEnumError::NoImagesFound);
     }
+    Ok(listing_result(image_paths, &dir_path, dropped, skipped))
+}
 
-    // Sort paths for consistent ordering
-    alphanumeric_sort::sort_path_slice(&mut image_paths);
+/// Sorted chunk of a listing, paged out until the runs are merged
+fn paged_run(paths: Vec<PathBuf>) -> PathIndex {
+    let mut run = PathIndexBuilder::with_threshold(0);
+    paths.into_iter().for_each(|path| run.push(path));
+    run.finish()
+}
 
-    // Calculate initial index for file drops
-    let initial_index = if is_file_drop {
-        get_file_index(&image_paths, &path).unwrap_or(0)
-    } else {
-        0
-    };
+/// Lists `path` on a worker thread, so neither a huge folder nor a slow mount (NFS) blocks the UI
+pub fn list_directory_in_background(path: PathBuf) -> futures::channel::mpsc::UnboundedReceiver<ListingEvent> {
+    let (sender, receiver) = futures::channel::mpsc::unbounded();
+    std::thread::spawn(move || list_directory(&path, |event| {
+        let _ = sender.unbounded_send(event);
+    }));
+    receiver
+}
 
-    Ok(DirectoryEnumResult {
-        file_paths: image_paths,
-        directory_path: dir_path.to_string_lossy().to_string(),
-        initial_index,
-    })
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "heif")]
+    #[test]
+    fn test_heif_brands() {
+        let ftyp = |brand: &[u8]| [&[0, 0, 0, 0x18][..], b"ftyp", brand, &[0, 0, 0, 0]].concat();
+        assert!(is_heif_format(&ftyp(b"heic")));
+        assert!(is_heif_format(&ftyp(b"avif")));
+        assert!(is_heif_format(&ftyp(b"mif1")));
+        // MP4 video shares the box layout
+        assert!(!is_heif_format(&ftyp(b"isom")));
+        assert!(!is_heif_format(b"ftyp"));
+    }
+
+    #[cfg(feature = "jxl")]
+    #[test]
+    fn test_jxl_signatures() {
+        assert!(is_jxl_format(&[0xFF, 0x0A, 0xFA, 0x1F]));
+        assert!(is_jxl_format(&[0x00, 0x00, 0x00, 0x0C, b'J', b'X', b'L', b' ', 0x0D, 0x0A, 0x87, 0x0A]));
+        // JPEG starts with FF D8
+        assert!(!is_jxl_format(&[0xFF, 0xD8, 0xFF, 0xE0]));
+    }
+
+    #[test]
+    fn test_merge_sorted() {
+        let paths = |names: &[&str]| names.iter().map(PathBuf::from).collect::<Vec<_>>();
+        let merged = merge_sorted(paths(&["a/img2.png", "a/img10.png"]), paths(&["a/img1.png", "a/img3.png", "a/img20.png"]));
+        assert_eq!(merged, paths(&["a/img1.png", "a/img2.png", "a/img3.png", "a/img10.png", "a/img20.png"]));
+        assert_eq!(merge_sorted(Vec::new(), paths(&["b.png"])), paths(&["b.png"]));
+    }
+
+    #[test]
+    fn test_merge_runs() {
+        let paths = |names: &[&str]| names.iter().map(PathBuf::from).collect::<Vec<_>>();
+        let runs = [
+            paged_run(paths(&["a/img2.png", "a/img10.png"])),
+            paged_run(paths(&["a/img1.png", "a/img3.png", "a/img20.png"])),
+            PathIndex::from_filesystem_paths(paths(&["a/img4.png"])),
+        ];
+        assert!(runs[0].is_paged());
+        let merged: Vec<PathBuf> = merge_runs(&runs).collect();
+        assert_eq!(merged, paths(&["a/img1.png", "a/img2.png", "a/img3.png", "a/img4.png", "a/img10.png", "a/img20.png"]));
+        assert_eq!(merge_runs(&[]).count(), 0);
+    }
+
+    #[test]
+    fn test_decode_animation() {
+        use image::{Delay, Frame, Rgba, RgbaImage};
+        use image::codecs::gif::GifEncoder;
+
+        let mut gif = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut gif);
+            let frames = [Rgba([255, 0, 0, 255]), Rgba([0, 0, 255, 255])].map(|color| {
+                Frame::from_parts(RgbaImage::from_pixel(4, 3, color), 0, 0, Delay::from_numer_denom_ms(50, 1))
+            });
+            encoder.encode_frames(frames).unwrap();
+        }
+        let frames = decode_animation(&gif, usize::MAX).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].image.dimensions(), (4, 3));
+        assert_eq!(frames[1].image.get_pixel(0, 0), &Rgba([0, 0, 255, 255]));
+        assert_eq!(frames[0].delay, std::time::Duration::from_millis(50));
+
+        // Frames past the byte budget are dropped, leaving a still image
+        assert!(decode_animation(&gif, 4 * 3 * 4).is_none());
+
+        let mut png = Vec::new();
+        RgbaImage::new(4, 3).write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png).unwrap();
+        assert!(decode_animation(&png, usize::MAX).is_none());
+    }
+
+    #[test]
+    fn test_large_folder_opens_before_listing_completes() {
+        let dir = std::env::temp_dir().join(format!("viewskater_listing_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let count = LISTING_FIRST_BATCH + 500;
+        for i in 0..count {
+            fs::write(dir.join(format!("img{}.png", i)), b"").unwrap();
+        }
+        let dropped = dir.join(format!("img{}.png", count - 1));
+
+        let mut events = Vec::new();
+        list_directory(&dropped, |event| events.push(event));
+        let _ = fs::remove_dir_all(&dir);
+
+        let [ListingEvent::Partial(first), ListingEvent::Complete(Ok(all))] = events.as_slice() else {
+            panic!("Unexpected events: {:?}", events);
+        };
+        // The dropped file is open in either listing
+        assert_eq!(first.file_paths[first.initial_index].path(), &dropped);
+        assert_eq!(all.file_paths[all.initial_index].path(), &dropped);
+        assert!(first.file_paths.len() <= LISTING_FIRST_BATCH + 1);
+        let expected: Vec<PathBuf> = (0..count).map(|i| dir.join(format!("img{}.png", i))).collect();
+        assert!(all.file_paths.iter().map(|source| source.path()).eq(expected.iter()));
+    }
 }
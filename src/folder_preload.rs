@@ -0,0 +1,152 @@
+/// "Preload folder into memory" action
+///
+/// Runs `cache::warm_cache::warm_up` over the active pane's file list on a worker thread
+/// and shows its progress in a bar above the footer, with a button to cancel.
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, container, text, button, progress_bar};
+use log::{info, error};
+
+use crate::app::{Message, DataViewer};
+use crate::cache::warm_cache::{self, WarmUpEvent};
+
+#[derive(Debug, Default)]
+pub struct FolderPreload {
+    progress: Option<(usize, usize)>,      // (files done, total) while reading
+    cancel: Option<Arc<AtomicBool>>,
+    status: Option<String>,
+}
+
+impl FolderPreload {
+    pub fn is_running(&self) -> bool {
+        self.progress.is_some()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PreloadMessage {
+    Start,
+    Cancel,
+    Release,
+    DismissStatus,
+    Event(WarmUpEvent),
+}
+
+impl From<PreloadMessage> for Message {
+    fn from(msg: PreloadMessage) -> Self {
+        Message::PreloadAction(msg)
+    }
+}
+
+pub fn handle_preload_message(app: &mut DataViewer, msg: PreloadMessage) -> Task<Message> {
+    let state = &mut app.folder_preload;
+    match msg {
+        PreloadMessage::Start => {
+            if state.is_running() {
+                return Task::none();
+            }
+            let pane_index = app.active_pane_index();
+            let Some(pane) = app.panes.get(pane_index).filter(|p| p.dir_loaded && !p.has_compressed_file) else {
+                return Task::none();
+            };
+            let Some(directory) = pane.directory_path.clone() else {
+                return Task::none();
+            };
+
+            let paths = pane.img_cache.image_paths.clone();
+            info!("Preloading {} file(s) of {}", paths.len(), directory);
+            let cancel = Arc::new(AtomicBool::new(false));
+            state.cancel = Some(Arc::clone(&cancel));
+            state.progress = Some((0, paths.len()));
+            state.status = None;
+
+            let (sender, receiver) = futures::channel::mpsc::unbounded();
+            std::thread::spawn(move || {
+                let progress_sender = sender.clone();
+                let result = warm_cache::warm_up(&directory, &paths, warm_cache::default_memory_limit(), &cancel, |done, total| {
+                    let _ = progress_sender.unbounded_send(WarmUpEvent::Progress(done, total));
+                });
+                let _ = sender.unbounded_send(WarmUpEvent::Finished(result));
+            });
+
+            Task::run(receiver, |event| Message::PreloadAction(PreloadMessage::Event(event)))
+        }
+        PreloadMessage::Cancel => {
+            if let Some(cancel) = &state.cancel {
+                cancel.store(true, Ordering::Relaxed);
+            }
+            Task::none()
+        }
+        PreloadMessage::Release => {
+            warm_cache::clear();
+            state.status = None;
+            Task::none()
+        }
+        PreloadMessage::DismissStatus => {
+            state.status = None;
+            Task::none()
+        }
+        PreloadMessage::Event(WarmUpEvent::Progress(done, total)) => {
+            state.progress = Some((done, total));
+            Task::none()
+        }
+        PreloadMessage::Event(WarmUpEvent::Finished(result)) => {
+            state.progress = None;
+            state.cancel = None;
+            state.status = Some(match result {
+                Ok(summary) if summary.cancelled => format!("Preload cancelled after {} files", summary.files),
+                Ok(summary) if summary.truncated => format!("Preloaded {} files ({} MB), memory limit reached",
+                    summary.files, summary.bytes / 1_048_576),
+                Ok(summary) => format!("Preloaded {} files ({} MB)", summary.files, summary.bytes / 1_048_576),
+                Err(e) => {
+                    error!("Folder preload failed: {}", e);
+                    format!("Preload failed: {}", e)
+                }
+            });
+            Task::none()
+        }
+    }
+}
+
+/// Progress bar while preloading, the result afterwards, or nothing
+pub fn view(state: &FolderPreload) -> Element<'_, Message, WinitTheme, Renderer> {
+    let label_style = |_theme: &WinitTheme| iced_widget::text::Style {
+        color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+    };
+    let small_button = |label: &'static str, msg: PreloadMessage| {
+        button(text(label).size(11))
+            .padding([1, 6])
+            .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+            .on_press(msg.into())
+    };
+
+    let content: Element<'_, Message, WinitTheme, Renderer> = match (state.progress, &state.status) {
+        (Some((done, total)), _) => row![
+            text(format!("Preloading {} / {}", done, total)).size(11).style(label_style),
+            progress_bar(0.0..=total.max(1) as f32, done as f32)
+                .height(Length::Fixed(8.0))
+                .width(Length::Fill),
+            small_button("Cancel", PreloadMessage::Cancel),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .into(),
+        (None, Some(status)) => row![
+            text(status).size(11).style(label_style).width(Length::Fill),
+            small_button("OK", PreloadMessage::DismissStatus),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .into(),
+        (None, None) => return container(text("")).height(0).into(),
+    };
+
+    container(content)
+        .padding([2, 8])
+        .width(Length::Fill)
+        .into()
+}
@@ -0,0 +1,159 @@
+/// Summary of the files skipped when a folder is opened
+///
+/// Folder enumeration keeps only supported images; everything else is counted by extension
+/// here and reported in a short-lived notice such as "312 images, 14 skipped (mp4, txt)",
+/// so files that don't show up in the viewer don't go missing unnoticed.
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, container, text, button};
+use log::info;
+
+use crate::app::Message;
+
+/// How long the notice stays up unless dismissed
+const NOTICE_DURATION: Duration = Duration::from_secs(8);
+/// Extensions named in the notice before the rest are elided
+const MAX_LISTED_EXTENSIONS: usize = 4;
+
+/// Count of skipped files per lowercase extension, or per reason for skipped links
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SkippedFiles {
+    by_extension: BTreeMap<String, usize>,
+}
+
+impl SkippedFiles {
+    /// Count a file that is not a supported image. Hidden files are ignored.
+    pub fn add(&mut self, path: &Path) {
+        let hidden = path.file_name()
+            .map(|name| name.to_string_lossy().starts_with('.'))
+            .unwrap_or(true);
+        if hidden {
+            return;
+        }
+        let extension = path.extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_else(|| "no extension".to_string());
+        *self.by_extension.entry(extension).or_insert(0) += 1;
+    }
+
+    /// Count a file skipped for a reason other than its type, such as a broken link
+    pub fn add_kind(&mut self, kind: &str) {
+        *self.by_extension.entry(kind.to_string()).or_insert(0) += 1;
+    }
+
+    pub fn total(&self) -> usize {
+        self.by_extension.values().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_extension.is_empty()
+    }
+
+    /// "312 images, 14 skipped (mp4, txt)", most common extensions first
+    pub fn summary(&self, images: usize) -> String {
+        let mut extensions: Vec<(&String, &usize)> = self.by_extension.iter().collect();
+        extensions.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        let mut listed: Vec<&str> = extensions.iter()
+            .take(MAX_LISTED_EXTENSIONS)
+            .map(|(ext, _)| ext.as_str())
+            .collect();
+        if extensions.len() > MAX_LISTED_EXTENSIONS {
+            listed.push("...");
+        }
+        format!("{} images, {} skipped ({})", images, self.total(), listed.join(", "))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct FolderStats {
+    notice: Option<String>,
+    /// Bumped for each notice so an older timer doesn't dismiss a newer one
+    generation: u64,
+}
+
+#[derive(Debug, Clone)]
+pub enum FolderStatsMessage {
+    Dismiss(u64),
+}
+
+impl From<FolderStatsMessage> for Message {
+    fn from(msg: FolderStatsMessage) -> Self {
+        Message::FolderStatsAction(msg)
+    }
+}
+
+impl FolderStats {
+    /// Show the notice for a folder just opened in `pane_label`, if anything was skipped
+    pub fn report(&mut self, pane_label: Option<&str>, images: usize, skipped: &SkippedFiles) -> Task<Message> {
+        if skipped.is_empty() {
+            return Task::none();
+        }
+        let summary = skipped.summary(images);
+        info!("Opened folder: {}", summary);
+        self.notice = Some(match pane_label {
+            Some(label) => format!("{}: {}", label, summary),
+            None => summary,
+        });
+        self.generation += 1;
+        let generation = self.generation;
+        Task::perform(
+            async move { tokio::time::sleep(NOTICE_DURATION).await },
+            move |_| FolderStatsMessage::Dismiss(generation).into(),
+        )
+    }
+}
+
+pub fn handle_folder_stats_message(state: &mut FolderStats, msg: FolderStatsMessage) -> Task<Message> {
+    match msg {
+        FolderStatsMessage::Dismiss(generation) => {
+            if generation == state.generation {
+                state.notice = None;
+            }
+        }
+    }
+    Task::none()
+}
+
+/// The skipped-files notice, or nothing
+pub fn view(state: &FolderStats) -> Element<'_, Message, WinitTheme, Renderer> {
+    let Some(notice) = &state.notice else {
+        return container(text("")).height(0).into();
+    };
+
+    container(
+        row![
+            text(notice).size(11).width(Length::Fill).style(|_theme: &WinitTheme| iced_widget::text::Style {
+                color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+            }),
+            button(text("OK").size(11))
+                .padding([1, 6])
+                .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+                .on_press(FolderStatsMessage::Dismiss(state.generation).into()),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+    )
+    .padding([2, 8])
+    .width(Length::Fill)
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skipped_summary() {
+        let mut skipped = SkippedFiles::default();
+        for name in ["a.mp4", "b.MP4", "c.txt", ".DS_Store", "README"] {
+            skipped.add(Path::new(name));
+        }
+        assert_eq!(skipped.total(), 4);
+        assert_eq!(skipped.summary(312), "312 images, 4 skipped (mp4, no extension, txt)");
+    }
+}
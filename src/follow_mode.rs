@@ -0,0 +1,265 @@
+/// `--follow DIR`: jump to each new image as it appears in a directory
+///
+/// Like `tail -f` for images, for training sample dumps or camera capture folders. A worker
+/// thread polls the directory and reports files that were not there at start, once their
+/// size stops changing so half-written files are skipped. The pane is reopened on the
+/// newest arrival; while paused (P), arrivals are counted and the jump happens on resume.
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use once_cell::sync::Lazy;
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, container, text, button};
+use log::{debug, info};
+
+use crate::app::{Message, DataViewer};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Directory given on the command line, waiting for the app to start following it
+static REQUESTED: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Default)]
+pub struct FollowMode {
+    directory: Option<PathBuf>,
+    paused: bool,
+    pending: Option<PathBuf>,       // newest arrival held back while paused
+    missed: usize,
+    stop: Option<Arc<AtomicBool>>,
+}
+
+impl FollowMode {
+    pub fn is_active(&self) -> bool {
+        self.directory.is_some()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum FollowMessage {
+    TogglePause,
+    Stop,
+    /// Newly completed files, oldest first
+    NewImages(Vec<PathBuf>),
+}
+
+impl From<FollowMessage> for Message {
+    fn from(msg: FollowMessage) -> Self {
+        Message::FollowAction(msg)
+    }
+}
+
+/// Size and modification time of each supported image in a directory
+type Listing = HashMap<PathBuf, (u64, SystemTime)>;
+
+fn scan(directory: &Path) -> Listing {
+    let Ok(entries) = std::fs::read_dir(crate::file_io::long_path(directory)) else {
+        return Listing::new();
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| crate::file_io::supported_image(&entry.file_name().to_string_lossy()))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            Some((directory.join(entry.file_name()), (metadata.len(), metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH))))
+        })
+        .collect()
+}
+
+/// Tells new files apart from the ones present at start, and waits for them to settle
+#[derive(Debug, Default)]
+struct ArrivalTracker {
+    known: HashSet<PathBuf>,
+    settling: HashMap<PathBuf, u64>,
+}
+
+impl ArrivalTracker {
+    fn new(initial: &Listing) -> Self {
+        Self { known: initial.keys().cloned().collect(), settling: HashMap::new() }
+    }
+
+    /// Files whose size was the same in this listing and the previous one, oldest first
+    fn update(&mut self, listing: &Listing) -> Vec<PathBuf> {
+        let mut ready: Vec<(SystemTime, PathBuf)> = Vec::new();
+        for (path, &(size, modified)) in listing {
+            if self.known.contains(path) {
+                continue;
+            }
+            if self.settling.get(path) == Some(&size) && size > 0 {
+                self.settling.remove(path);
+                self.known.insert(path.clone());
+                ready.push((modified, path.clone()));
+            } else {
+                self.settling.insert(path.clone(), size);
+            }
+        }
+        // Forget files that disappeared so a re-created one is reported again
+        self.settling.retain(|path, _| listing.contains_key(path));
+        self.known.retain(|path| listing.contains_key(path));
+
+        ready.sort();
+        ready.into_iter().map(|(_, path)| path).collect()
+    }
+}
+
+/// Remember `--follow DIR` until the app is ready to start following
+pub fn request(directory: PathBuf) {
+    if let Ok(mut requested) = REQUESTED.lock() {
+        *requested = Some(directory);
+    }
+}
+
+pub fn take_request() -> Option<PathBuf> {
+    REQUESTED.lock().ok()?.take()
+}
+
+/// Open the newest image of `directory` in the first pane and start watching it
+pub fn start(app: &mut DataViewer, directory: PathBuf) -> Task<Message> {
+    if let Some(stop) = app.follow_mode.stop.take() {
+        stop.store(true, Ordering::Relaxed);
+    }
+    info!("Following new images in {}", directory.display());
+
+    let initial = scan(&directory);
+    let newest = initial.iter()
+        .max_by(|a, b| (a.1 .1, a.0).cmp(&(b.1 .1, b.0)))
+        .map(|(path, _)| path.clone());
+
+    let stop = Arc::new(AtomicBool::new(false));
+    app.follow_mode = FollowMode {
+        directory: Some(directory.clone()),
+        stop: Some(Arc::clone(&stop)),
+        ..FollowMode::default()
+    };
+
+    let (sender, receiver) = futures::channel::mpsc::unbounded();
+    let watched = directory.clone();
+    std::thread::spawn(move || {
+        let mut tracker = ArrivalTracker::new(&initial);
+        while !stop.load(Ordering::Relaxed) {
+            std::thread::sleep(POLL_INTERVAL);
+            let arrived = tracker.update(&scan(&watched));
+            if !arrived.is_empty() && sender.unbounded_send(arrived).is_err() {
+                break;
+            }
+        }
+        debug!("Stopped following {}", watched.display());
+    });
+
+    let watch_task = Task::run(receiver, |paths| Message::FollowAction(FollowMessage::NewImages(paths)));
+    match newest {
+        Some(path) => Task::batch([app.initialize_dir_path(&path, 0), watch_task]),
+        None => watch_task,
+    }
+}
+
+pub fn handle_follow_message(app: &mut DataViewer, msg: FollowMessage) -> Task<Message> {
+    let state = &mut app.follow_mode;
+    match msg {
+        FollowMessage::TogglePause => {
+            if !state.is_active() {
+                return Task::none();
+            }
+            state.paused = !state.paused;
+            state.missed = 0;
+            match state.pending.take() {
+                Some(path) if !state.paused => app.initialize_dir_path(&path, 0),
+                _ => Task::none(),
+            }
+        }
+        FollowMessage::Stop => {
+            if let Some(stop) = &state.stop {
+                stop.store(true, Ordering::Relaxed);
+            }
+            *state = FollowMode::default();
+            Task::none()
+        }
+        FollowMessage::NewImages(paths) => {
+            let Some(directory) = state.directory.clone() else {
+                return Task::none();
+            };
+            // Opening another folder in the pane ends following
+            let opened_elsewhere = app.panes.first().is_some_and(|pane| {
+                pane.dir_loaded && pane.directory_path.as_deref().map(Path::new) != Some(directory.as_path())
+            });
+            if opened_elsewhere {
+                info!("Stopped following {}: another folder was opened", directory.display());
+                return handle_follow_message(app, FollowMessage::Stop);
+            }
+            let Some(newest) = paths.last().cloned() else {
+                return Task::none();
+            };
+            let state = &mut app.follow_mode;
+            if state.paused {
+                state.missed += paths.len();
+                state.pending = Some(newest);
+                return Task::none();
+            }
+            debug!("Following: jumping to {}", newest.display());
+            app.initialize_dir_path(&newest, 0)
+        }
+    }
+}
+
+/// Status bar while following, or nothing
+pub fn view(state: &FollowMode) -> Element<'_, Message, WinitTheme, Renderer> {
+    let Some(directory) = &state.directory else {
+        return container(text("")).height(0).into();
+    };
+    let label_style = |_theme: &WinitTheme| iced_widget::text::Style {
+        color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+    };
+    let small_button = |label: &'static str, msg: FollowMessage| {
+        button(text(label).size(11))
+            .padding([1, 6])
+            .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+            .on_press(msg.into())
+    };
+
+    let status = if state.paused {
+        format!("Paused following {} ({} new)", directory.display(), state.missed)
+    } else {
+        format!("Following {}", directory.display())
+    };
+    container(
+        row![
+            text(status).size(11).style(label_style).width(Length::Fill),
+            small_button(if state.paused { "Resume (P)" } else { "Pause (P)" }, FollowMessage::TogglePause),
+            small_button("Stop", FollowMessage::Stop),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+    )
+    .padding([2, 8])
+    .width(Length::Fill)
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listing(files: &[(&str, u64, u64)]) -> Listing {
+        files.iter()
+            .map(|&(name, size, secs)| (PathBuf::from(name), (size, SystemTime::UNIX_EPOCH + Duration::from_secs(secs))))
+            .collect()
+    }
+
+    #[test]
+    fn test_arrivals_wait_for_stable_size() {
+        let mut tracker = ArrivalTracker::new(&listing(&[("old.png", 10, 1)]));
+
+        // A new file is reported only once two polls agree on its size
+        assert!(tracker.update(&listing(&[("old.png", 10, 1), ("b.png", 5, 3)])).is_empty());
+        assert!(tracker.update(&listing(&[("old.png", 10, 1), ("b.png", 9, 3), ("a.png", 4, 2)])).is_empty());
+        let ready = tracker.update(&listing(&[("old.png", 10, 1), ("b.png", 9, 3), ("a.png", 4, 2)]));
+        assert_eq!(ready, vec![PathBuf::from("a.png"), PathBuf::from("b.png")]);
+
+        // Already reported files and files present at start are not repeated
+        assert!(tracker.update(&listing(&[("old.png", 10, 1), ("b.png", 9, 3), ("a.png", 4, 2)])).is_empty());
+    }
+}
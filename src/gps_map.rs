@@ -0,0 +1,400 @@
+/// Map of geotagged images in the sidebar (`gps-map` feature)
+///
+/// When the sidebar is open, the active pane's folder is scanned once for EXIF GPS positions
+/// and a "Map" section shows a pin per geotagged image, the current one highlighted; clicking
+/// a pin jumps to that image. The view is fitted to the pins in Web Mercator and drawn over
+/// OpenStreetMap tiles, which are kept in `<cache dir>/viewskater/tiles/` and only downloaded
+/// when missing. Offline and without cached tiles, the pins are drawn on a plain background.
+use std::io::Read;
+use std::path::PathBuf;
+use std::time::Duration;
+use iced_winit::core::{mouse, Color, Element, Length, Point, Rectangle};
+use iced_winit::core::image::Handle;
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::canvas::{self, Path as CanvasPath};
+use iced_widget::{column, container, stack, text};
+use log::{debug, info, warn};
+use rayon::prelude::*;
+
+use crate::app::{DataViewer, Message, NavigationMessage};
+use crate::cache::img_cache::PathSource;
+use crate::pane::Pane;
+
+const MAP_WIDTH: u32 = 256;
+const MAP_HEIGHT: u32 = 192;
+const TILE_SIZE: f64 = 256.0;
+/// Closest zoom used when fitting the pins (street level)
+const MAX_ZOOM: u32 = 16;
+/// Room kept between the outermost pins and the map edge
+const PIN_MARGIN: f64 = 12.0;
+/// Clicks further than this from every pin are ignored
+const PIN_HIT_RADIUS: f32 = 10.0;
+const TILE_URL: &str = "https://tile.openstreetmap.org";
+const TILE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Tiles are small PNGs; anything larger is not a tile
+const MAX_TILE_BYTES: u64 = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pin {
+    pub index: usize,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// Zoom level and the world-pixel position of the map's top-left corner
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapView {
+    pub zoom: u32,
+    pub origin_x: f64,
+    pub origin_y: f64,
+}
+
+impl MapView {
+    fn project(&self, pin: &Pin) -> Point {
+        let (x, y) = world_pixel(pin.latitude, pin.longitude, self.zoom);
+        Point::new((x - self.origin_x) as f32, (y - self.origin_y) as f32)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct GpsMap {
+    /// Folder the pins were scanned from, and the pane showing it
+    folder: Option<String>,
+    pane_index: usize,
+    scanning: bool,
+    /// Images in the folder, for the "n of m" line
+    total: usize,
+    pins: Vec<Pin>,
+    view: Option<MapView>,
+    basemap: Option<Handle>,
+}
+
+#[derive(Debug, Clone)]
+pub enum MapMessage {
+    Scanned(String, Vec<Pin>),
+    BasemapReady(String, Option<Handle>),
+}
+
+impl From<MapMessage> for Message {
+    fn from(msg: MapMessage) -> Self {
+        Message::MapAction(msg)
+    }
+}
+
+/// Web Mercator position in pixels of the whole world at `zoom`
+pub fn world_pixel(latitude: f64, longitude: f64, zoom: u32) -> (f64, f64) {
+    let size = TILE_SIZE * (1u64 << zoom) as f64;
+    let latitude = latitude.clamp(-85.0511, 85.0511).to_radians();
+    let x = (longitude + 180.0) / 360.0 * size;
+    let y = (1.0 - (latitude.tan() + 1.0 / latitude.cos()).ln() / std::f64::consts::PI) / 2.0 * size;
+    (x, y)
+}
+
+/// The closest view that shows every pin, centered on them
+pub fn fit_view(pins: &[Pin], width: f64, height: f64) -> Option<MapView> {
+    if pins.is_empty() {
+        return None;
+    }
+    (0..=MAX_ZOOM).rev().find_map(|zoom| {
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for pin in pins {
+            let (x, y) = world_pixel(pin.latitude, pin.longitude, zoom);
+            (min_x, min_y, max_x, max_y) = (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y));
+        }
+        let fits = max_x - min_x <= width - 2.0 * PIN_MARGIN && max_y - min_y <= height - 2.0 * PIN_MARGIN;
+        (fits || zoom == 0).then(|| MapView {
+            zoom,
+            origin_x: (min_x + max_x - width) / 2.0,
+            origin_y: (min_y + max_y - height) / 2.0,
+        })
+    })
+}
+
+fn tile_dir() -> PathBuf {
+    crate::app_dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("viewskater")
+        .join("tiles")
+}
+
+/// A map tile from the cache, downloading it first if it isn't there
+fn load_tile(zoom: u32, x: u64, y: u64) -> Option<image::RgbaImage> {
+    let path = tile_dir().join(zoom.to_string()).join(x.to_string()).join(format!("{}.png", y));
+    let bytes = match std::fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            let url = format!("{}/{}/{}/{}.png", TILE_URL, zoom, x, y);
+            let response = ureq::get(&url)
+                .set("User-Agent", &format!("viewskater/{}", env!("CARGO_PKG_VERSION")))
+                .timeout(TILE_TIMEOUT)
+                .call()
+                .map_err(|e| debug!("Map tile {} unavailable: {}", url, e))
+                .ok()?;
+            let mut bytes = Vec::new();
+            response.into_reader().take(MAX_TILE_BYTES).read_to_end(&mut bytes).ok()?;
+            let saved = path.parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(&path, &bytes));
+            if let Err(e) = saved {
+                warn!("Failed to cache map tile {}: {}", path.display(), e);
+            }
+            bytes
+        }
+    };
+    image::load_from_memory(&bytes).ok().map(|tile| tile.to_rgba8())
+}
+
+/// Tiles covering `view`, stitched into one image, or `None` if none could be loaded
+fn build_basemap(view: MapView) -> Option<Handle> {
+    let mut basemap = image::RgbaImage::from_pixel(MAP_WIDTH, MAP_HEIGHT, image::Rgba([32, 32, 32, 255]));
+    let tiles = 1i64 << view.zoom;
+    let first_x = (view.origin_x / TILE_SIZE).floor() as i64;
+    let last_x = ((view.origin_x + MAP_WIDTH as f64) / TILE_SIZE).floor() as i64;
+    let first_y = ((view.origin_y / TILE_SIZE).floor() as i64).max(0);
+    let last_y = (((view.origin_y + MAP_HEIGHT as f64) / TILE_SIZE).floor() as i64).min(tiles - 1);
+
+    let mut loaded = 0;
+    for tile_y in first_y..=last_y {
+        for tile_x in first_x..=last_x {
+            // The world repeats horizontally
+            let Some(tile) = load_tile(view.zoom, tile_x.rem_euclid(tiles) as u64, tile_y as u64) else {
+                continue;
+            };
+            let left = (tile_x as f64 * TILE_SIZE - view.origin_x).round() as i64;
+            let top = (tile_y as f64 * TILE_SIZE - view.origin_y).round() as i64;
+            image::imageops::overlay(&mut basemap, &tile, left, top);
+            loaded += 1;
+        }
+    }
+    (loaded > 0).then(|| Handle::from_rgba(MAP_WIDTH, MAP_HEIGHT, basemap.into_raw()))
+}
+
+/// Starts a scan when the sidebar shows a folder that hasn't been scanned yet
+pub fn request(app: &mut DataViewer) -> Option<Task<Message>> {
+    if !app.sidebar.is_visible() {
+        return None;
+    }
+    let pane_index = app.active_pane_index();
+    let pane = app.panes.get(pane_index).filter(|pane| pane.dir_loaded && !pane.has_compressed_file)?;
+    let folder = pane.directory_path.clone()?;
+    let map = &mut app.gps_map;
+    if map.folder.as_ref() == Some(&folder) {
+        return None;
+    }
+
+    let paths: Vec<(usize, PathBuf)> = pane.img_cache.image_paths.iter()
+        .enumerate()
+        .filter_map(|(index, source)| match source {
+            PathSource::Filesystem(path) => Some((index, path.clone())),
+            _ => None,
+        })
+        .collect();
+    *map = GpsMap {
+        folder: Some(folder.clone()),
+        pane_index,
+        scanning: true,
+        total: paths.len(),
+        ..GpsMap::default()
+    };
+
+    Some(Task::perform(
+        async move {
+            let pins = tokio::task::spawn_blocking(move || {
+                paths.par_iter()
+                    .filter_map(|(index, path)| {
+                        let (latitude, longitude) = crate::utils::exif_tags::read_gps(path)?;
+                        Some(Pin { index: *index, latitude, longitude })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await
+            .unwrap_or_default();
+            (folder, pins)
+        },
+        |(folder, pins)| MapMessage::Scanned(folder, pins).into()
+    ))
+}
+
+pub fn handle_map_message(app: &mut DataViewer, msg: MapMessage) -> Task<Message> {
+    let map = &mut app.gps_map;
+    match msg {
+        MapMessage::Scanned(folder, pins) => {
+            if map.folder.as_ref() != Some(&folder) {
+                return Task::none();
+            }
+            info!("Found {} geotagged image(s) in {}", pins.len(), folder);
+            map.scanning = false;
+            map.view = fit_view(&pins, MAP_WIDTH as f64, MAP_HEIGHT as f64);
+            map.pins = pins;
+            let Some(view) = map.view else {
+                return Task::none();
+            };
+            Task::perform(
+                async move {
+                    let basemap = tokio::task::spawn_blocking(move || build_basemap(view))
+                        .await
+                        .ok()
+                        .flatten();
+                    (folder, basemap)
+                },
+                |(folder, basemap)| MapMessage::BasemapReady(folder, basemap).into()
+            )
+        }
+        MapMessage::BasemapReady(folder, basemap) => {
+            if map.folder.as_ref() == Some(&folder) {
+                map.basemap = basemap;
+            }
+            Task::none()
+        }
+    }
+}
+
+/// Pins over the basemap; clicking one jumps to its image
+struct MapPins<'a> {
+    pins: &'a [Pin],
+    view: MapView,
+    current: usize,
+    pane_index: usize,
+    basemap: bool,
+}
+
+impl canvas::Program<Message, WinitTheme, Renderer> for MapPins<'_> {
+    type State = ();
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<Message>) {
+        let canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) = event else {
+            return (canvas::event::Status::Ignored, None);
+        };
+        let Some(position) = cursor.position_in(bounds) else {
+            return (canvas::event::Status::Ignored, None);
+        };
+        let nearest = self.pins.iter()
+            .map(|pin| (pin, self.view.project(pin).distance(position)))
+            .filter(|(_, distance)| *distance <= PIN_HIT_RADIUS)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        match nearest {
+            Some((pin, _)) => (canvas::event::Status::Captured, Some(Message::Navigation(NavigationMessage::JumpToImage(self.pane_index, pin.index)))),
+            None => (canvas::event::Status::Ignored, None),
+        }
+    }
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &WinitTheme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry<Renderer>> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        if !self.basemap {
+            frame.fill_rectangle(Point::ORIGIN, bounds.size(), Color::from_rgb(0.12, 0.12, 0.12));
+        }
+        let outline = Color::from_rgb(0.1, 0.1, 0.1);
+        let mut current = None;
+        for pin in self.pins {
+            let point = self.view.project(pin);
+            if pin.index == self.current {
+                current = Some(point);
+                continue;
+            }
+            frame.fill(&CanvasPath::circle(point, 5.0), outline);
+            frame.fill(&CanvasPath::circle(point, 4.0), Color::from_rgb(0.35, 0.65, 1.0));
+        }
+        // Drawn last so other pins don't cover it
+        if let Some(point) = current {
+            frame.fill(&CanvasPath::circle(point, 7.0), outline);
+            frame.fill(&CanvasPath::circle(point, 6.0), Color::from_rgb(1.0, 0.8, 0.2));
+        }
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(&self, _state: &Self::State, bounds: Rectangle, cursor: mouse::Cursor) -> mouse::Interaction {
+        let over_pin = cursor.position_in(bounds).is_some_and(|position| {
+            self.pins.iter().any(|pin| self.view.project(pin).distance(position) <= PIN_HIT_RADIUS)
+        });
+        if over_pin { mouse::Interaction::Pointer } else { mouse::Interaction::default() }
+    }
+}
+
+/// Sidebar section for the pane's folder, once a scan has been started for it
+pub fn section<'a>(map: &'a GpsMap, pane: &Pane) -> Option<Element<'a, Message, WinitTheme, Renderer>> {
+    if map.folder.is_none() || map.folder != pane.directory_path {
+        return None;
+    }
+    if map.scanning {
+        return Some(text("Reading GPS tags...").size(12).into());
+    }
+    let Some(view) = map.view else {
+        return Some(text("No geotagged images in this folder").size(12).into());
+    };
+
+    let pins = canvas::Canvas::new(MapPins {
+        pins: &map.pins,
+        view,
+        current: pane.img_cache.current_index,
+        pane_index: map.pane_index,
+        basemap: map.basemap.is_some(),
+    })
+    .width(Length::Fixed(MAP_WIDTH as f32))
+    .height(Length::Fixed(MAP_HEIGHT as f32));
+
+    let map_view: Element<'a, Message, WinitTheme, Renderer> = match &map.basemap {
+        Some(basemap) => stack![
+            iced_widget::image(basemap.clone())
+                .width(Length::Fixed(MAP_WIDTH as f32))
+                .height(Length::Fixed(MAP_HEIGHT as f32)),
+            pins,
+        ]
+        .into(),
+        None => pins.into(),
+    };
+    let caption = format!("{} of {} images geotagged", map.pins.len(), map.total);
+    let mut content = column![container(map_view), text(caption).size(11)].spacing(4);
+    if map.basemap.is_some() {
+        content = content.push(text("© OpenStreetMap contributors").size(10));
+    }
+    Some(content.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pin(index: usize, latitude: f64, longitude: f64) -> Pin {
+        Pin { index, latitude, longitude }
+    }
+
+    #[test]
+    fn test_world_pixel() {
+        assert_eq!(world_pixel(0.0, 0.0, 0), (128.0, 128.0));
+        let (x, y) = world_pixel(85.0511, -180.0, 1);
+        assert!(x.abs() < 1e-9 && y.abs() < 0.01);
+    }
+
+    #[test]
+    fn test_fit_view_contains_pins() {
+        assert_eq!(fit_view(&[], 256.0, 192.0), None);
+
+        let single = fit_view(&[pin(0, 35.0, 139.0)], 256.0, 192.0).unwrap();
+        assert_eq!(single.zoom, MAX_ZOOM);
+        let center = single.project(&pin(0, 35.0, 139.0));
+        assert!((center.x - 128.0).abs() < 1e-3 && (center.y - 96.0).abs() < 1e-3);
+
+        let pins = [pin(0, 35.68, 139.76), pin(1, 34.69, 135.50), pin(2, 43.06, 141.35)];
+        let view = fit_view(&pins, 256.0, 192.0).unwrap();
+        assert!(view.zoom < MAX_ZOOM);
+        for p in &pins {
+            let point = view.project(p);
+            assert!(point.x >= 0.0 && point.x <= 256.0 && point.y >= 0.0 && point.y <= 192.0);
+        }
+    }
+}
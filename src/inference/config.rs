@@ -0,0 +1,138 @@
+/// Model configuration files
+///
+/// A model is described by a YAML file next to it:
+///
+/// ```yaml
+/// model: yolov8n.onnx          # relative to this file
+/// task: detection              # detection | segmentation | super_resolution | background_removal
+/// input_size: [640, 640]       # width, height
+/// resize: letterbox            # letterbox | stretch
+/// mean: [0.0, 0.0, 0.0]        # per channel, on 0-1 values
+/// std: [1.0, 1.0, 1.0]
+/// output: yolov8               # detection: yolov5 | yolov8 | boxes
+/// score_threshold: 0.25
+/// iou_threshold: 0.45
+/// background_class: 0          # segmentation: class left undrawn
+/// labels: [person, bicycle, car]
+/// ```
+///
+/// `labels_file` may point to a text file with one label per line instead of `labels`.
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Task {
+    Detection,
+    /// Per-pixel class scores, `[1, classes, height, width]`
+    Segmentation,
+    /// `[1, 3, height * scale, width * scale]` RGB on 0-1; `input_size` is the tile size
+    SuperResolution,
+    /// `[1, 1, height, width]` subject saliency, u2net-style
+    BackgroundRemoval,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Resize {
+    /// Keep the aspect ratio and pad with gray
+    #[default]
+    Letterbox,
+    Stretch,
+}
+
+/// Layout of a detection model's first output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// `[1, boxes, 5 + classes]`: cx, cy, w, h, objectness, class scores
+    Yolov5,
+    /// `[1, 4 + classes, boxes]`: cx, cy, w, h, class scores
+    #[default]
+    Yolov8,
+    /// `[1, boxes, 6]`: x1, y1, x2, y2, score, class, already suppressed
+    Boxes,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModelConfig {
+    pub model: PathBuf,
+    pub task: Task,
+    pub input_size: (u32, u32),
+    #[serde(default)]
+    pub resize: Resize,
+    #[serde(default = "default_mean")]
+    pub mean: [f32; 3],
+    #[serde(default = "default_std")]
+    pub std: [f32; 3],
+    #[serde(default)]
+    pub output: OutputFormat,
+    #[serde(default = "default_score_threshold")]
+    pub score_threshold: f32,
+    #[serde(default = "default_iou_threshold")]
+    pub iou_threshold: f32,
+    #[serde(default)]
+    pub background_class: Option<usize>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub labels_file: Option<PathBuf>,
+}
+
+fn default_mean() -> [f32; 3] {
+    [0.0; 3]
+}
+
+fn default_std() -> [f32; 3] {
+    [1.0; 3]
+}
+
+fn default_score_threshold() -> f32 {
+    0.25
+}
+
+fn default_iou_threshold() -> f32 {
+    0.45
+}
+
+impl ModelConfig {
+    /// Reads a config file, resolving the model and labels paths against its folder
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let yaml = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let mut config: Self = serde_yaml::from_str(&yaml)
+            .map_err(|e| format!("Invalid model config {}: {}", path.display(), e))?;
+        let base = path.parent().unwrap_or(Path::new("."));
+        config.model = base.join(&config.model);
+        if let Some(labels_file) = config.labels_file.take() {
+            let labels_file = base.join(labels_file);
+            let labels = std::fs::read_to_string(&labels_file)
+                .map_err(|e| format!("Failed to read {}: {}", labels_file.display(), e))?;
+            config.labels = labels.lines().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string).collect();
+        }
+        if config.input_size.0 == 0 || config.input_size.1 == 0 {
+            return Err("input_size must not be zero".to_string());
+        }
+        Ok(config)
+    }
+
+    /// Label of a class index, or the index itself
+    pub fn label(&self, class: usize) -> String {
+        self.labels.get(class).cloned().unwrap_or_else(|| format!("class {}", class))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_defaults() {
+        let config: ModelConfig = serde_yaml::from_str("model: m.onnx\ntask: detection\ninput_size: [640, 480]\n").unwrap();
+        assert_eq!(config.input_size, (640, 480));
+        assert_eq!(config.resize, Resize::Letterbox);
+        assert_eq!(config.output, OutputFormat::Yolov8);
+        assert_eq!(config.std, [1.0; 3]);
+        assert_eq!(config.label(3), "class 3");
+    }
+}
@@ -0,0 +1,268 @@
+/// Background removal preview
+///
+/// With a salient-object model loaded (`task: background_removal`, u2net-style), the active
+/// pane's image is shown next to its subject cutout over a checkerboard, so images with a
+/// clean, separable subject can be told apart from busy ones while stepping through a
+/// folder. The dialog follows the image shown; nothing is written to disk.
+use std::path::PathBuf;
+use std::sync::Arc;
+use iced_winit::core::{Element, Length, Alignment};
+use iced_winit::core::image::Handle;
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, column, container, text, button, Space};
+use image::{DynamicImage, GrayImage, Luma, Rgb, RgbImage};
+
+use crate::app::{DataViewer, Message};
+use super::config::ModelConfig;
+use super::model::{planar_input, OnnxModel, Output};
+use super::{InferenceMessage, InferenceState};
+
+/// Images are scaled down to this longest side before compositing
+const MAX_PREVIEW_SIDE: u32 = 1024;
+/// Width and height of each image in the dialog
+const PREVIEW_SIZE: f32 = 420.0;
+/// Side of a checkerboard square in preview pixels
+const CHECKER_SIZE: u32 = 16;
+
+/// A loaded background removal model with its config
+#[derive(Debug)]
+pub struct BackgroundRemover {
+    pub config: ModelConfig,
+    model: OnnxModel,
+}
+
+impl BackgroundRemover {
+    pub fn load(config: ModelConfig) -> Result<Self, String> {
+        let model = OnnxModel::load(&config.model, config.input_size)?;
+        Ok(Self { config, model })
+    }
+
+    /// Subject mask of `image` at its size, 255 for subject
+    pub fn mask(&self, image: &RgbImage) -> Result<GrayImage, String> {
+        let (input_width, input_height) = self.model.input_size();
+        let input = image::imageops::resize(image, input_width, input_height, image::imageops::FilterType::Triangle);
+        let outputs = self.model.run(planar_input(input.as_raw(), self.config.mean, self.config.std))?;
+        let first = outputs.first().ok_or("The model has no outputs")?;
+        let mask = saliency_mask(first)?;
+        Ok(image::imageops::resize(&mask, image.width(), image.height(), image::imageops::FilterType::Triangle))
+    }
+}
+
+/// Mask from the model's `[.., height, width]` saliency map, stretched to the full 0-255 range
+/// since u2net-style outputs are not calibrated
+pub fn saliency_mask(output: &Output) -> Result<GrayImage, String> {
+    let (shape, values) = output;
+    let &[.., height, width] = shape.as_slice() else {
+        return Err(format!("Unexpected output shape {:?}", shape));
+    };
+    let plane = values.get(..width * height)
+        .filter(|plane| !plane.is_empty())
+        .ok_or_else(|| format!("Output shape {:?} does not match its values", shape))?;
+    let (min, max) = plane.iter().fold((f32::MAX, f32::MIN), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+    let range = (max - min).max(f32::EPSILON);
+    let pixels = plane.iter().map(|&v| ((v - min) / range * 255.0).round() as u8).collect();
+    GrayImage::from_raw(width as u32, height as u32, pixels).ok_or_else(|| "Invalid mask size".to_string())
+}
+
+/// `image` blended over a light checkerboard by `mask`
+pub fn composite_checkerboard(image: &RgbImage, mask: &GrayImage) -> RgbImage {
+    RgbImage::from_fn(image.width(), image.height(), |x, y| {
+        let backdrop = if (x / CHECKER_SIZE + y / CHECKER_SIZE).is_multiple_of(2) { 255.0 } else { 204.0 };
+        let Luma([alpha]) = *mask.get_pixel(x, y);
+        let alpha = alpha as f32 / 255.0;
+        let Rgb(pixel) = *image.get_pixel(x, y);
+        Rgb(pixel.map(|c| (c as f32 * alpha + backdrop * (1.0 - alpha)).round() as u8))
+    })
+}
+
+/// Share of the mask that is mostly subject
+pub fn subject_fraction(mask: &GrayImage) -> f32 {
+    let subject = mask.pixels().filter(|Luma([alpha])| *alpha >= 128).count();
+    subject as f32 / (mask.width() * mask.height()).max(1) as f32
+}
+
+#[derive(Debug, Clone)]
+pub struct Cutout {
+    pub original: Handle,
+    pub cutout: Handle,
+    pub subject_fraction: f32,
+}
+
+#[derive(Debug, Clone)]
+pub enum CutoutPreview {
+    Running(PathBuf),
+    Done(PathBuf, Cutout),
+    Failed(PathBuf, String),
+}
+
+impl CutoutPreview {
+    /// Image the preview is for
+    fn path(&self) -> &PathBuf {
+        match self {
+            CutoutPreview::Running(path) | CutoutPreview::Done(path, _) | CutoutPreview::Failed(path, _) => path,
+        }
+    }
+}
+
+fn cut_out(remover: &BackgroundRemover, image: &DynamicImage) -> Result<Cutout, String> {
+    let image = if image.width().max(image.height()) > MAX_PREVIEW_SIDE {
+        image.resize(MAX_PREVIEW_SIDE, MAX_PREVIEW_SIDE, image::imageops::FilterType::Triangle)
+    } else {
+        image.clone()
+    };
+    let rgb = image.to_rgb8();
+    let mask = remover.mask(&rgb)?;
+    let handle = |rgb: RgbImage| {
+        let rgba = DynamicImage::ImageRgb8(rgb).to_rgba8();
+        Handle::from_rgba(rgba.width(), rgba.height(), rgba.into_raw())
+    };
+    Ok(Cutout {
+        subject_fraction: subject_fraction(&mask),
+        cutout: handle(composite_checkerboard(&rgb, &mask)),
+        original: handle(rgb),
+    })
+}
+
+/// Cuts out the active pane's image
+pub fn run(app: &mut DataViewer) -> Task<Message> {
+    let Some(remover) = app.inference.remover.clone() else {
+        return Task::none();
+    };
+    let Some(pane) = app.panes.get(app.active_pane_index()).filter(|pane| pane.dir_loaded) else {
+        return Task::none();
+    };
+    let Some(source) = pane.img_cache.image_paths.get(pane.img_cache.current_index).cloned() else {
+        return Task::none();
+    };
+    let archive_cache = pane.has_compressed_file.then(|| Arc::clone(&pane.archive_cache));
+
+    let path = source.path().clone();
+    app.inference.cutout_preview = Some(CutoutPreview::Running(path.clone()));
+    Task::perform(
+        async move {
+            let result = tokio::task::spawn_blocking(move || {
+                let image = crate::file_io::decode_path_source(&source, archive_cache.as_deref())?;
+                cut_out(&remover, &image)
+            })
+            .await
+            .unwrap_or_else(|e| Err(e.to_string()));
+            (path, result)
+        },
+        |(path, result)| InferenceMessage::CutOut(path, result).into()
+    )
+}
+
+/// Refreshes the preview when another image is shown while it is open
+pub fn request(app: &mut DataViewer) -> Option<Task<Message>> {
+    let preview = app.inference.cutout_preview.as_ref()?;
+    if matches!(preview, CutoutPreview::Running(_)) {
+        return None;
+    }
+    let pane = app.panes.get(app.active_pane_index())?;
+    let current = pane.img_cache.image_paths.get(pane.img_cache.current_index)?;
+    (current.path() != preview.path()).then(|| run(app))
+}
+
+/// The cutout dialog while the preview is on
+pub fn view(state: &InferenceState) -> Option<Element<'_, Message, WinitTheme, Renderer>> {
+    let preview = state.cutout_preview.as_ref()?;
+    let name = preview.path().file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let body: Element<'_, Message, WinitTheme, Renderer> = match preview {
+        CutoutPreview::Running(_) => container(text("Removing the background...").size(14))
+            .padding(20)
+            .into(),
+        CutoutPreview::Failed(_, e) => container(text(e.clone()).size(14))
+            .padding(20)
+            .into(),
+        CutoutPreview::Done(_, cutout) => {
+            let panel = |label: &'static str, handle: &Handle| {
+                column![
+                    text(label).size(13),
+                    iced_widget::image(handle.clone())
+                        .width(Length::Fixed(PREVIEW_SIZE))
+                        .height(Length::Fixed(PREVIEW_SIZE)),
+                ]
+                .spacing(6)
+                .align_x(Alignment::Center)
+            };
+            column![
+                text(format!("Subject covers {:.0}% of the image", cutout.subject_fraction * 100.0)).size(12),
+                row![
+                    panel("Original", &cutout.original),
+                    panel("Cutout", &cutout.cutout),
+                ]
+                .spacing(10),
+            ]
+            .spacing(8)
+            .into()
+        }
+    };
+
+    let content = column![
+        text(format!("Background Removal: {}", name))
+            .size(18)
+            .font(iced_winit::core::Font {
+                family: iced_winit::core::font::Family::Name("Roboto"),
+                weight: iced_winit::core::font::Weight::Bold,
+                stretch: iced_winit::core::font::Stretch::Normal,
+                style: iced_winit::core::font::Style::Normal,
+            }),
+        body,
+        row![
+            Space::with_width(Length::Fill),
+            button(text("Close"))
+                .padding([3, 10])
+                .on_press(InferenceMessage::SetCutoutPreview(false).into()),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    ]
+    .spacing(10);
+
+    Some(container(content)
+        .padding(15)
+        .style(|theme: &WinitTheme| {
+            iced_widget::container::Style {
+                background: Some(theme.extended_palette().background.base.color.into()),
+                text_color: Some(theme.extended_palette().primary.weak.text),
+                border: iced_winit::core::Border {
+                    color: theme.extended_palette().background.strong.color,
+                    width: 1.0,
+                    radius: iced_winit::core::border::Radius::from(8.0),
+                },
+                ..Default::default()
+            }
+        })
+        .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saliency_mask() {
+        let mask = saliency_mask(&(vec![1, 1, 2, 2], vec![0.2, 0.6, 1.0, 0.2])).unwrap();
+        assert_eq!(mask.dimensions(), (2, 2));
+        assert_eq!(mask.as_raw(), &vec![0, 128, 255, 0]);
+        assert!(saliency_mask(&(vec![1, 1, 2, 2], vec![0.0; 3])).is_err());
+    }
+
+    #[test]
+    fn test_composite_checkerboard() {
+        let image = RgbImage::from_pixel(32, 32, Rgb([10, 20, 30]));
+        let mut mask = GrayImage::new(32, 32);
+        mask.put_pixel(0, 0, Luma([255]));
+        let composite = composite_checkerboard(&image, &mask);
+        assert_eq!(*composite.get_pixel(0, 0), Rgb([10, 20, 30]));
+        // Background shows the checkerboard: white, then gray one square over
+        assert_eq!(*composite.get_pixel(1, 0), Rgb([255, 255, 255]));
+        assert_eq!(*composite.get_pixel(CHECKER_SIZE, 0), Rgb([204, 204, 204]));
+        assert_eq!(subject_fraction(&mask), 1.0 / 1024.0);
+    }
+}
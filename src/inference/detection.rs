@@ -0,0 +1,326 @@
+/// Detection and segmentation models shown through the COCO overlay
+///
+/// The image is fitted to the model input (letterboxed or stretched), normalized as the
+/// config says and run; boxes are decoded from the first output, filtered by score and
+/// non-maximum suppression per class, and mapped back to image pixels. Segmentation outputs
+/// are taken per pixel by their highest class score and traced to polygons. Either way the
+/// result is a list of `ImageAnnotation`s, so the overlay draws them like loaded annotations.
+use image::DynamicImage;
+
+use crate::coco::parser::{BoundingBox, CocoSegmentation, ImageAnnotation};
+use crate::coco::rle_decoder;
+use super::config::{ModelConfig, OutputFormat, Resize, Task};
+use super::model::{planar_input, OnnxModel, Output};
+
+/// Gray the letterbox padding is filled with, as in YOLO preprocessing
+const PAD_VALUE: u8 = 114;
+
+/// A box in model input pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Detection {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+    pub score: f32,
+    pub class: usize,
+}
+
+/// How the image was placed in the model input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    pub scale_x: f32,
+    pub scale_y: f32,
+    pub pad_x: f32,
+    pub pad_y: f32,
+}
+
+impl Placement {
+    pub fn new(image_size: (u32, u32), input_size: (u32, u32), resize: Resize) -> Self {
+        let scale_x = input_size.0 as f32 / image_size.0.max(1) as f32;
+        let scale_y = input_size.1 as f32 / image_size.1.max(1) as f32;
+        match resize {
+            Resize::Stretch => Self { scale_x, scale_y, pad_x: 0.0, pad_y: 0.0 },
+            Resize::Letterbox => {
+                let scale = scale_x.min(scale_y);
+                let fitted = |length: u32| (length as f32 * scale).round();
+                Self {
+                    scale_x: scale,
+                    scale_y: scale,
+                    pad_x: ((input_size.0 as f32 - fitted(image_size.0)) / 2.0).floor(),
+                    pad_y: ((input_size.1 as f32 - fitted(image_size.1)) / 2.0).floor(),
+                }
+            }
+        }
+    }
+
+    /// Image pixel position of a model input position
+    pub fn to_image(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        ((x - self.pad_x) / self.scale_x, (y - self.pad_y) / self.scale_y)
+    }
+}
+
+/// The image fitted into the model input as interleaved RGB
+fn fit_image(image: &DynamicImage, input_size: (u32, u32), placement: &Placement) -> Vec<u8> {
+    let width = ((image.width() as f32 * placement.scale_x).round() as u32).clamp(1, input_size.0);
+    let height = ((image.height() as f32 * placement.scale_y).round() as u32).clamp(1, input_size.1);
+    let resized = image.resize_exact(width, height, image::imageops::FilterType::Triangle).to_rgb8();
+    let mut canvas = image::RgbImage::from_pixel(input_size.0, input_size.1, image::Rgb([PAD_VALUE; 3]));
+    image::imageops::replace(&mut canvas, &resized, placement.pad_x as i64, placement.pad_y as i64);
+    canvas.into_raw()
+}
+
+/// Boxes scoring at least `score_threshold` from a detection output
+pub fn decode_detections(output: &Output, format: OutputFormat, score_threshold: f32) -> Vec<Detection> {
+    let (shape, values) = output;
+    let [first, second] = shape[shape.len().saturating_sub(2)..] else {
+        return Vec::new();
+    };
+    if values.len() < first * second {
+        return Vec::new();
+    }
+    let from_center = |cx: f32, cy: f32, w: f32, h: f32, score: f32, class: usize| Detection {
+        x1: cx - w / 2.0,
+        y1: cy - h / 2.0,
+        x2: cx + w / 2.0,
+        y2: cy + h / 2.0,
+        score,
+        class,
+    };
+    let best_class = |scores: &mut dyn Iterator<Item = f32>| {
+        scores.enumerate().fold((0, f32::MIN), |best, (class, score)| if score > best.1 { (class, score) } else { best })
+    };
+
+    let detections: Vec<Detection> = match format {
+        OutputFormat::Yolov5 if second > 5 => values.chunks_exact(second)
+            .map(|row| {
+                let (class, class_score) = best_class(&mut row[5..].iter().copied());
+                from_center(row[0], row[1], row[2], row[3], row[4] * class_score, class)
+            })
+            .collect(),
+        // Attributes first: value `a` of box `i` is at `a * boxes + i`
+        OutputFormat::Yolov8 if first > 4 => (0..second)
+            .map(|i| {
+                let at = |a: usize| values[a * second + i];
+                let (class, score) = best_class(&mut (4..first).map(&at));
+                from_center(at(0), at(1), at(2), at(3), score, class)
+            })
+            .collect(),
+        OutputFormat::Boxes if second >= 6 => values.chunks_exact(second)
+            .map(|row| Detection {
+                x1: row[0],
+                y1: row[1],
+                x2: row[2],
+                y2: row[3],
+                score: row[4],
+                class: row[5].max(0.0) as usize,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    detections.into_iter().filter(|d| d.score >= score_threshold).collect()
+}
+
+pub fn iou(a: &Detection, b: &Detection) -> f32 {
+    let width = (a.x2.min(b.x2) - a.x1.max(b.x1)).max(0.0);
+    let height = (a.y2.min(b.y2) - a.y1.max(b.y1)).max(0.0);
+    let intersection = width * height;
+    let area = |d: &Detection| (d.x2 - d.x1).max(0.0) * (d.y2 - d.y1).max(0.0);
+    let union = area(a) + area(b) - intersection;
+    if union <= 0.0 { 0.0 } else { intersection / union }
+}
+
+/// Non-maximum suppression within each class, best score first
+pub fn suppress(mut detections: Vec<Detection>, iou_threshold: f32) -> Vec<Detection> {
+    detections.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let mut kept: Vec<Detection> = Vec::new();
+    for detection in detections {
+        if kept.iter().all(|k| k.class != detection.class || iou(k, &detection) <= iou_threshold) {
+            kept.push(detection);
+        }
+    }
+    kept
+}
+
+/// Row-major masks of each class that wins at least one pixel of a `[1, classes, height,
+/// width]` output, except `background`, with the mask size
+pub fn class_masks(output: &Output, background: Option<usize>) -> (Vec<(usize, Vec<u8>)>, (usize, usize)) {
+    let (shape, values) = output;
+    let [classes, height, width] = shape[shape.len().saturating_sub(3)..] else {
+        return (Vec::new(), (0, 0));
+    };
+    let plane = width * height;
+    if values.len() < classes * plane {
+        return (Vec::new(), (0, 0));
+    }
+    let mut masks: Vec<Option<Vec<u8>>> = vec![None; classes];
+    for pixel in 0..plane {
+        let class = (0..classes)
+            .max_by(|&a, &b| values[a * plane + pixel].total_cmp(&values[b * plane + pixel]))
+            .unwrap_or(0);
+        if Some(class) == background {
+            continue;
+        }
+        masks[class].get_or_insert_with(|| vec![0; plane])[pixel] = 1;
+    }
+    let masks = masks.into_iter()
+        .enumerate()
+        .filter_map(|(class, mask)| Some((class, mask?)))
+        .collect();
+    (masks, (width, height))
+}
+
+fn annotation(id: u64, class: usize, score: Option<f32>, corners: (f32, f32, f32, f32), config: &ModelConfig) -> ImageAnnotation {
+    let (x1, y1, x2, y2) = corners;
+    ImageAnnotation {
+        id,
+        bbox: BoundingBox { x: x1, y: y1, width: x2 - x1, height: y2 - y1 },
+        // Category ids start at 1 for the overlay's color table
+        category_id: class as u64 + 1,
+        category_name: config.label(class),
+        segmentation: None,
+        score,
+        area: (x2 - x1) * (y2 - y1),
+    }
+}
+
+/// A loaded model with its config
+#[derive(Debug)]
+pub struct Detector {
+    pub config: ModelConfig,
+    model: OnnxModel,
+}
+
+impl Detector {
+    pub fn load(config: ModelConfig) -> Result<Self, String> {
+        let model = OnnxModel::load(&config.model, config.input_size)?;
+        Ok(Self { config, model })
+    }
+
+    /// Predictions for an image, in its pixel coordinates
+    pub fn predict(&self, image: &DynamicImage) -> Result<Vec<ImageAnnotation>, String> {
+        let config = &self.config;
+        let image_size = (image.width(), image.height());
+        let placement = Placement::new(image_size, self.model.input_size(), config.resize);
+        let input = planar_input(&fit_image(image, self.model.input_size(), &placement), config.mean, config.std);
+        let outputs = self.model.run(input)?;
+        let first = outputs.first().ok_or("The model has no outputs")?;
+
+        let clamp = |(x, y): (f32, f32)| (x.clamp(0.0, image_size.0 as f32), y.clamp(0.0, image_size.1 as f32));
+        match config.task {
+            Task::Detection => {
+                let detections = decode_detections(first, config.output, config.score_threshold);
+                let detections = match config.output {
+                    OutputFormat::Boxes => detections,
+                    _ => suppress(detections, config.iou_threshold),
+                };
+                Ok(detections.iter().enumerate().map(|(i, d)| {
+                    let (x1, y1) = clamp(placement.to_image((d.x1, d.y1)));
+                    let (x2, y2) = clamp(placement.to_image((d.x2, d.y2)));
+                    annotation(i as u64 + 1, d.class, Some(d.score), (x1, y1, x2, y2), config)
+                }).collect())
+            }
+            Task::Segmentation => {
+                let (masks, (mask_width, mask_height)) = class_masks(first, config.background_class);
+                // The output may be smaller than the input; its pixels cover the input evenly
+                let (input_width, input_height) = self.model.input_size();
+                let to_input = |(x, y): (f32, f32)| {
+                    (x * input_width as f32 / mask_width.max(1) as f32, y * input_height as f32 / mask_height.max(1) as f32)
+                };
+                Ok(masks.into_iter().enumerate().filter_map(|(i, (class, mask))| {
+                    let polygons: Vec<Vec<f32>> = rle_decoder::mask_to_polygons(&mask, mask_width, mask_height, 1.0)
+                        .into_iter()
+                        .filter(|polygon| polygon.len() >= 3)
+                        .map(|polygon| polygon.into_iter()
+                            .flat_map(|point| {
+                                let (x, y) = clamp(placement.to_image(to_input(point)));
+                                [x, y]
+                            })
+                            .collect())
+                        .collect();
+                    let xs = polygons.iter().flat_map(|p| p.iter().step_by(2).copied());
+                    let ys = polygons.iter().flat_map(|p| p.iter().skip(1).step_by(2).copied());
+                    let (x1, x2) = xs.fold((f32::MAX, f32::MIN), |(lo, hi), x| (lo.min(x), hi.max(x)));
+                    let (y1, y2) = ys.fold((f32::MAX, f32::MIN), |(lo, hi), y| (lo.min(y), hi.max(y)));
+                    if polygons.is_empty() || x2 <= x1 || y2 <= y1 {
+                        return None;
+                    }
+                    let mut annotation = annotation(i as u64 + 1, class, None, (x1, y1, x2, y2), config);
+                    annotation.segmentation = Some(CocoSegmentation::Polygon(polygons));
+                    Some(annotation)
+                }).collect())
+            }
+            Task::SuperResolution | Task::BackgroundRemoval => {
+                Err("Upscaling and background removal models are not detectors".to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detection(x1: f32, y1: f32, x2: f32, y2: f32, score: f32, class: usize) -> Detection {
+        Detection { x1, y1, x2, y2, score, class }
+    }
+
+    #[test]
+    fn test_letterbox_placement() {
+        let placement = Placement::new((1280, 720), (640, 640), Resize::Letterbox);
+        assert_eq!(placement.scale_x, 0.5);
+        assert_eq!((placement.pad_x, placement.pad_y), (0.0, 140.0));
+        assert_eq!(placement.to_image((320.0, 140.0)), (640.0, 0.0));
+
+        let stretched = Placement::new((1280, 720), (640, 640), Resize::Stretch);
+        assert_eq!(stretched.to_image((640.0, 640.0)), (1280.0, 720.0));
+    }
+
+    #[test]
+    fn test_decode_yolov8() {
+        // Two boxes, two classes: attributes are rows
+        let values = vec![
+            10.0, 50.0,  // cx
+            10.0, 50.0,  // cy
+            4.0, 20.0,   // w
+            4.0, 20.0,   // h
+            0.9, 0.1,    // class 0
+            0.2, 0.3,    // class 1
+        ];
+        let detections = decode_detections(&(vec![1, 6, 2], values), OutputFormat::Yolov8, 0.25);
+        assert_eq!(detections, vec![
+            detection(8.0, 8.0, 12.0, 12.0, 0.9, 0),
+            detection(40.0, 40.0, 60.0, 60.0, 0.3, 1),
+        ]);
+    }
+
+    #[test]
+    fn test_decode_yolov5() {
+        let values = vec![10.0, 10.0, 4.0, 4.0, 0.5, 0.2, 0.8];
+        let detections = decode_detections(&(vec![1, 1, 7], values), OutputFormat::Yolov5, 0.25);
+        assert_eq!(detections, vec![detection(8.0, 8.0, 12.0, 12.0, 0.4, 1)]);
+        assert!(decode_detections(&(vec![1, 1, 7], vec![0.0; 7]), OutputFormat::Yolov5, 0.25).is_empty());
+    }
+
+    #[test]
+    fn test_suppress() {
+        let detections = vec![
+            detection(0.0, 0.0, 10.0, 10.0, 0.8, 0),
+            detection(1.0, 1.0, 10.0, 10.0, 0.9, 0),
+            // Same place, other class
+            detection(0.0, 0.0, 10.0, 10.0, 0.7, 1),
+            detection(20.0, 20.0, 30.0, 30.0, 0.5, 0),
+        ];
+        let kept = suppress(detections, 0.45);
+        assert_eq!(kept.iter().map(|d| d.score).collect::<Vec<_>>(), vec![0.9, 0.7, 0.5]);
+    }
+
+    #[test]
+    fn test_class_masks() {
+        // 3 classes over a 2x1 image: pixel 0 is class 2, pixel 1 background
+        let values = vec![0.1, 0.9, 0.2, 0.0, 0.7, 0.05];
+        let (masks, size) = class_masks(&(vec![1, 3, 1, 2], values), Some(0));
+        assert_eq!(size, (2, 1));
+        assert_eq!(masks, vec![(2, vec![1, 0])]);
+    }
+}
@@ -0,0 +1,358 @@
+/// Model inference on the current image
+///
+/// Loads an ONNX detection or segmentation model described by a YAML config (see
+/// `config`) and runs it with tract on the active pane's image, either on demand or on every
+/// image shown. Predictions are drawn by the COCO overlay in the single-pane view, with the
+/// class labels and scores of the config, which makes a quick smoke test of an exported
+/// model without writing any glue code. An upscaling model instead enables the AI upscale
+/// preview of the zoomed region (see `upscale`), and a salient-object model the background
+/// removal preview (see `cutout`).
+pub mod config;
+pub mod cutout;
+pub mod detection;
+pub mod model;
+pub mod upscale;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, container, text, button};
+use log::{info, error};
+
+use crate::app::{DataViewer, Message};
+use crate::coco::parser::ImageAnnotation;
+use crate::menu::PaneLayout;
+use self::config::{ModelConfig, Task as ModelTask};
+use self::cutout::{BackgroundRemover, Cutout, CutoutPreview};
+use self::detection::Detector;
+use self::upscale::{Comparison, Upscaler, UpscalePreview};
+
+/// A model ready to run, by what its config says it does
+#[derive(Debug, Clone)]
+pub enum LoadedModel {
+    Detector(Arc<Detector>),
+    Upscaler(Arc<Upscaler>),
+    BackgroundRemover(Arc<BackgroundRemover>),
+}
+
+#[derive(Debug, Clone)]
+pub enum InferenceMessage {
+    /// Pick a model config file
+    LoadConfig,
+    ConfigPicked(PathBuf),
+    ModelLoaded(Result<LoadedModel, String>),
+    /// Run on the active pane's image
+    Run,
+    Finished(PathBuf, Result<Vec<ImageAnnotation>, String>),
+    SetAutoRun(bool),
+    Clear,
+    /// Show or hide the upscale comparison of the zoomed region
+    SetUpscalePreview(bool),
+    RefreshUpscale,
+    Upscaled(Result<Comparison, String>),
+    /// Show or hide the background removal preview of the shown image
+    SetCutoutPreview(bool),
+    CutOut(PathBuf, Result<Cutout, String>),
+}
+
+impl From<InferenceMessage> for Message {
+    fn from(msg: InferenceMessage) -> Self {
+        Message::InferenceAction(msg)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct InferenceState {
+    detector: Option<Arc<Detector>>,
+    /// Run on each image shown
+    pub auto_run: bool,
+    loading: bool,
+    /// Image being processed
+    running: Option<PathBuf>,
+    /// Predictions and the image they are for
+    results: Option<(PathBuf, Vec<ImageAnnotation>)>,
+    error: Option<String>,
+    upscaler: Option<Arc<Upscaler>>,
+    /// Comparison dialog, while shown
+    upscale_preview: Option<UpscalePreview>,
+    remover: Option<Arc<BackgroundRemover>>,
+    /// Cutout dialog, while shown
+    cutout_preview: Option<CutoutPreview>,
+}
+
+impl InferenceState {
+    pub fn has_model(&self) -> bool {
+        self.detector.is_some()
+    }
+
+    pub fn has_upscaler(&self) -> bool {
+        self.upscaler.is_some()
+    }
+
+    pub fn has_remover(&self) -> bool {
+        self.remover.is_some()
+    }
+
+    pub fn upscale_preview_visible(&self) -> bool {
+        self.upscale_preview.is_some()
+    }
+
+    pub fn has_results(&self) -> bool {
+        self.results.is_some()
+    }
+}
+
+fn run(app: &mut DataViewer, image: PathBuf) -> Task<Message> {
+    let state = &mut app.inference;
+    let Some(detector) = state.detector.clone() else {
+        return Task::none();
+    };
+    state.running = Some(image.clone());
+    state.error = None;
+    Task::perform(
+        async move {
+            let path = image.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                let bytes = std::fs::read(&path).map_err(|e| e.to_string())?;
+                let image = crate::exif_utils::decode_with_exif_orientation(&bytes)
+                    .map_err(|e| format!("Failed to decode {}: {:?}", path.display(), e))?;
+                detector.predict(&image)
+            })
+            .await
+            .unwrap_or_else(|e| Err(e.to_string()));
+            (image, result)
+        },
+        |(image, result)| InferenceMessage::Finished(image, result).into()
+    )
+}
+
+/// Runs the model on a newly shown image while auto-run is on, and refreshes an open
+/// background removal preview
+pub fn request(app: &mut DataViewer) -> Option<Task<Message>> {
+    let cutout_task = cutout::request(app);
+    let detection_task = request_detection(app);
+    match (cutout_task, detection_task) {
+        (Some(cutout_task), Some(detection_task)) => Some(Task::batch([cutout_task, detection_task])),
+        (cutout_task, detection_task) => cutout_task.or(detection_task),
+    }
+}
+
+fn request_detection(app: &mut DataViewer) -> Option<Task<Message>> {
+    let state = &app.inference;
+    if !state.auto_run || state.detector.is_none() || state.running.is_some() {
+        return None;
+    }
+    let image = app.current_file()?;
+    if state.results.as_ref().is_some_and(|(path, _)| *path == image) {
+        return None;
+    }
+    Some(run(app, image))
+}
+
+pub fn handle_inference_message(app: &mut DataViewer, msg: InferenceMessage) -> Task<Message> {
+    match msg {
+        InferenceMessage::LoadConfig => Task::perform(
+            async {
+                rfd::AsyncFileDialog::new()
+                    .set_title("Open model config")
+                    .add_filter("Model config", &["yaml", "yml"])
+                    .pick_file()
+                    .await
+            },
+            |file_handle| match file_handle {
+                Some(file) => InferenceMessage::ConfigPicked(file.path().to_path_buf()).into(),
+                None => Message::Nothing,
+            }
+        ),
+        InferenceMessage::ConfigPicked(path) => {
+            app.inference.loading = true;
+            app.inference.error = None;
+            Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || {
+                        let config = ModelConfig::load(&path)?;
+                        info!("Loading model {}", config.model.display());
+                        match config.task {
+                            ModelTask::Detection | ModelTask::Segmentation => {
+                                Detector::load(config).map(|detector| LoadedModel::Detector(Arc::new(detector)))
+                            }
+                            ModelTask::SuperResolution => {
+                                Upscaler::load(config).map(|upscaler| LoadedModel::Upscaler(Arc::new(upscaler)))
+                            }
+                            ModelTask::BackgroundRemoval => {
+                                BackgroundRemover::load(config).map(|remover| LoadedModel::BackgroundRemover(Arc::new(remover)))
+                            }
+                        }
+                    })
+                    .await
+                    .unwrap_or_else(|e| Err(e.to_string()))
+                },
+                |result| InferenceMessage::ModelLoaded(result).into()
+            )
+        }
+        InferenceMessage::ModelLoaded(result) => {
+            let state = &mut app.inference;
+            state.loading = false;
+            match result {
+                Ok(LoadedModel::Detector(detector)) => {
+                    info!("Model ready: {}", detector.config.model.display());
+                    state.detector = Some(detector);
+                    state.results = None;
+                    match app.current_file() {
+                        Some(image) => run(app, image),
+                        None => Task::none(),
+                    }
+                }
+                Ok(LoadedModel::Upscaler(upscaler)) => {
+                    info!("Upscaling model ready: {}", upscaler.config.model.display());
+                    state.upscaler = Some(upscaler);
+                    Task::none()
+                }
+                Ok(LoadedModel::BackgroundRemover(remover)) => {
+                    info!("Background removal model ready: {}", remover.config.model.display());
+                    state.remover = Some(remover);
+                    Task::none()
+                }
+                Err(e) => {
+                    error!("Failed to load model: {}", e);
+                    state.error = Some(e);
+                    Task::none()
+                }
+            }
+        }
+        InferenceMessage::Run => match app.current_file() {
+            Some(image) if app.inference.running.is_none() => run(app, image),
+            Some(_) => Task::none(),
+            None => {
+                app.inference.error = Some("Only image files can be run through the model".to_string());
+                Task::none()
+            }
+        },
+        InferenceMessage::Finished(image, result) => {
+            let state = &mut app.inference;
+            state.running = None;
+            match result {
+                Ok(predictions) => {
+                    info!("{} prediction(s) for {}", predictions.len(), image.display());
+                    state.results = Some((image, predictions));
+                }
+                Err(e) => {
+                    error!("Model failed on {}: {}", image.display(), e);
+                    state.error = Some(e);
+                }
+            }
+            Task::none()
+        }
+        InferenceMessage::SetAutoRun(enabled) => {
+            app.inference.auto_run = enabled;
+            Task::none()
+        }
+        InferenceMessage::Clear => {
+            app.inference.results = None;
+            app.inference.error = None;
+            Task::none()
+        }
+        InferenceMessage::SetUpscalePreview(true) | InferenceMessage::RefreshUpscale => upscale::run(app),
+        InferenceMessage::SetUpscalePreview(false) => {
+            app.inference.upscale_preview = None;
+            Task::none()
+        }
+        InferenceMessage::Upscaled(result) => {
+            // Dropped if the dialog was closed while upscaling
+            if app.inference.upscale_preview.is_some() {
+                if let Err(e) = &result {
+                    error!("Upscale preview failed: {}", e);
+                }
+                app.inference.upscale_preview = Some(match result {
+                    Ok(comparison) => UpscalePreview::Done(comparison),
+                    Err(e) => UpscalePreview::Failed(e),
+                });
+            }
+            Task::none()
+        }
+        InferenceMessage::SetCutoutPreview(true) => cutout::run(app),
+        InferenceMessage::SetCutoutPreview(false) => {
+            app.inference.cutout_preview = None;
+            Task::none()
+        }
+        InferenceMessage::CutOut(path, result) => {
+            // Dropped if the dialog was closed while running
+            if app.inference.cutout_preview.is_some() {
+                if let Err(e) = &result {
+                    error!("Background removal failed on {}: {}", path.display(), e);
+                }
+                app.inference.cutout_preview = Some(match result {
+                    Ok(cutout) => CutoutPreview::Done(path, cutout),
+                    Err(e) => CutoutPreview::Failed(path, e),
+                });
+            }
+            Task::none()
+        }
+    }
+}
+
+/// Predictions overlay for the single-pane view, while they match the image shown
+pub fn overlay(app: &DataViewer) -> Option<Element<'_, Message, WinitTheme, Renderer>> {
+    let (image, predictions) = app.inference.results.as_ref()?;
+    let pane = &app.panes[0];
+    if app.pane_layout != PaneLayout::SinglePane || app.current_file().as_ref() != Some(image) {
+        return None;
+    }
+    let image_size = (pane.current_image.width(), pane.current_image.height());
+    Some(crate::coco::overlay::render_bbox_overlay(
+        predictions,
+        image_size,
+        pane.zoom_scale,
+        pane.zoom_offset,
+        true,
+        true,
+        false,
+        app.coco_mask_render_mode,
+        app.coco_disable_simplification,
+        crate::coco::overlay::LabelOptions {
+            visible: app.coco_show_labels,
+            size: app.coco_label_size,
+        },
+        0,
+        None,
+        crate::coco::annotation_manager::OverlayFilter::default(),
+    ))
+}
+
+/// Model status under the image while loading, running or failed, or nothing
+pub fn view(state: &InferenceState) -> Element<'_, Message, WinitTheme, Renderer> {
+    let status = if state.loading {
+        "Loading model...".to_string()
+    } else if let Some(image) = &state.running {
+        let name = image.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        format!("Running model on {}...", name)
+    } else if let Some(e) = &state.error {
+        format!("Model error: {}", e)
+    } else {
+        return container(text("")).height(0).into();
+    };
+
+    let dismiss = button(text("Dismiss").size(11))
+        .padding([1, 6])
+        .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+        .on_press_maybe(state.error.is_some().then(|| InferenceMessage::Clear.into()));
+
+    container(
+        row![
+            container(text(status).size(13)).width(Length::Fill),
+            dismiss,
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center)
+    )
+    .padding([4, 8])
+    .width(Length::Fill)
+    .style(|_theme: &WinitTheme| container::Style {
+        text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+        ..container::Style::default()
+    })
+    .into()
+}
@@ -0,0 +1,83 @@
+/// ONNX models run with tract
+///
+/// Models take one `[1, 3, height, width]` float input of a fixed size, set when loading, and
+/// every output is returned as a flat array with its shape.
+use std::path::Path;
+use tract_onnx::prelude::*;
+
+pub struct OnnxModel {
+    plan: TypedRunnableModel<TypedModel>,
+    input_size: (u32, u32),
+}
+
+impl std::fmt::Debug for OnnxModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OnnxModel")
+            .field("input_size", &self.input_size)
+            .finish()
+    }
+}
+
+/// A model output as (shape, row-major values)
+pub type Output = (Vec<usize>, Vec<f32>);
+
+impl OnnxModel {
+    /// Loads and optimizes a model for inputs of `input_size` (width, height)
+    pub fn load(path: &Path, input_size: (u32, u32)) -> Result<Self, String> {
+        let (width, height) = (input_size.0 as usize, input_size.1 as usize);
+        let plan = tract_onnx::onnx()
+            .model_for_path(path)
+            .and_then(|model| model.with_input_fact(0, f32::fact([1, 3, height, width]).into()))
+            .and_then(|model| model.into_optimized())
+            .and_then(|model| model.into_runnable())
+            .map_err(|e| format!("Failed to load {}: {}", path.display(), e))?;
+        Ok(Self { plan, input_size })
+    }
+
+    pub fn input_size(&self) -> (u32, u32) {
+        self.input_size
+    }
+
+    /// Runs the model on planar RGB values (`3 * height * width`, channel by channel)
+    pub fn run(&self, input: Vec<f32>) -> Result<Vec<Output>, String> {
+        let (width, height) = (self.input_size.0 as usize, self.input_size.1 as usize);
+        let tensor = tract_ndarray::Array4::from_shape_vec((1, 3, height, width), input)
+            .map_err(|e| e.to_string())?;
+        let outputs = self.plan.run(tvec!(Tensor::from(tensor).into()))
+            .map_err(|e| format!("Model failed: {}", e))?;
+        outputs.iter()
+            .map(|output| {
+                let view = output.to_array_view::<f32>().map_err(|e| e.to_string())?;
+                Ok((view.shape().to_vec(), view.iter().copied().collect()))
+            })
+            .collect()
+    }
+}
+
+/// Planar RGB input from interleaved RGB pixels: each value scaled to 0-1, then normalized
+/// with `mean` and `std`
+pub fn planar_input(rgb: &[u8], mean: [f32; 3], std: [f32; 3]) -> Vec<f32> {
+    let pixel_count = rgb.len() / 3;
+    let mut input = vec![0.0; pixel_count * 3];
+    for (i, pixel) in rgb.chunks_exact(3).enumerate() {
+        for c in 0..3 {
+            input[c * pixel_count + i] = (pixel[c] as f32 / 255.0 - mean[c]) / std[c];
+        }
+    }
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_planar_input() {
+        let input = planar_input(&[255, 0, 51, 0, 255, 102], [0.0, 0.5, 0.0], [1.0, 0.5, 0.2]);
+        assert_eq!(input.len(), 6);
+        // R plane, then G, then B
+        assert_eq!(&input[0..2], &[1.0, 0.0]);
+        assert_eq!(&input[2..4], &[-1.0, 1.0]);
+        assert!((input[4] - 1.0).abs() < 1e-5 && (input[5] - 2.0).abs() < 1e-5);
+    }
+}
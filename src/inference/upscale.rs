@@ -0,0 +1,290 @@
+/// Super-resolution preview of the zoomed region
+///
+/// With an upscaling model loaded (`task: super_resolution`), the part of the image the active
+/// pane shows is cropped from a full-resolution decode, run through the model tile by tile and
+/// shown next to a bilinear resize of the same crop to the same size. The preview is only for
+/// looking: nothing is saved and the pane keeps its own image.
+use std::sync::Arc;
+use iced_winit::core::{Element, Length, Alignment};
+use iced_winit::core::image::Handle;
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, column, container, text, button, Space};
+use image::{DynamicImage, Rgb, RgbImage};
+
+use crate::app::{DataViewer, Message};
+use super::config::ModelConfig;
+use super::model::{planar_input, OnnxModel, Output};
+use super::{InferenceMessage, InferenceState};
+
+/// Crops wider or taller than this are refused; zoom in further instead
+pub const MAX_CROP_SIZE: u32 = 512;
+/// Width and height of each image in the preview
+const PREVIEW_SIZE: f32 = 420.0;
+
+/// A loaded upscaling model with its config
+#[derive(Debug)]
+pub struct Upscaler {
+    pub config: ModelConfig,
+    model: OnnxModel,
+}
+
+impl Upscaler {
+    pub fn load(config: ModelConfig) -> Result<Self, String> {
+        let model = OnnxModel::load(&config.model, config.input_size)?;
+        Ok(Self { config, model })
+    }
+
+    pub fn upscale(&self, image: &RgbImage) -> Result<RgbImage, String> {
+        let (mean, std) = (self.config.mean, self.config.std);
+        upscale_tiled(image, self.model.input_size(), |tile| {
+            let outputs = self.model.run(planar_input(tile.as_raw(), mean, std))?;
+            outputs.into_iter().next().ok_or_else(|| "The model has no outputs".to_string())
+        })
+    }
+}
+
+/// Upscales `image` in tiles of `tile_size`, the model's input size. Edge tiles repeat the
+/// last row and column; `run` returns `[1, 3, height * scale, width * scale]` values on 0-1.
+pub fn upscale_tiled(
+    image: &RgbImage,
+    tile_size: (u32, u32),
+    mut run: impl FnMut(&RgbImage) -> Result<Output, String>,
+) -> Result<RgbImage, String> {
+    let (width, height) = image.dimensions();
+    let (tile_width, tile_height) = tile_size;
+    let mut upscaled: Option<(u32, RgbImage)> = None;
+
+    for y0 in (0..height).step_by(tile_height as usize) {
+        for x0 in (0..width).step_by(tile_width as usize) {
+            let tile = RgbImage::from_fn(tile_width, tile_height, |x, y| {
+                *image.get_pixel((x0 + x).min(width - 1), (y0 + y).min(height - 1))
+            });
+            let (shape, values) = run(&tile)?;
+            let &[.., channels, out_height, out_width] = shape.as_slice() else {
+                return Err(format!("Unexpected output shape {:?}", shape));
+            };
+            let scale = out_width / tile_width as usize;
+            if channels != 3 || scale == 0 || out_width != tile_width as usize * scale
+                || out_height != tile_height as usize * scale || values.len() < 3 * out_width * out_height {
+                return Err(format!("Output shape {:?} is not a 3-channel multiple of the input", shape));
+            }
+            let (scale_u32, plane) = (scale as u32, out_width * out_height);
+            let (_, output) = upscaled.get_or_insert_with(|| (scale_u32, RgbImage::new(width * scale_u32, height * scale_u32)));
+
+            for y in 0..tile_height.min(height - y0) * scale_u32 {
+                for x in 0..tile_width.min(width - x0) * scale_u32 {
+                    let i = y as usize * out_width + x as usize;
+                    let pixel = [0, 1, 2].map(|c| (values[c * plane + i] * 255.0).round().clamp(0.0, 255.0) as u8);
+                    output.put_pixel(x0 * scale_u32 + x, y0 * scale_u32 + y, Rgb(pixel));
+                }
+            }
+        }
+    }
+    upscaled.map(|(_, output)| output).ok_or_else(|| "Empty image".to_string())
+}
+
+/// Pixels of `image` in `region` (fractions of its size), if small enough to upscale
+pub fn crop_region(image: &DynamicImage, region: [f32; 4]) -> Result<RgbImage, String> {
+    let (width, height) = (image.width(), image.height());
+    let x0 = ((region[0] * width as f32).floor() as u32).min(width);
+    let y0 = ((region[1] * height as f32).floor() as u32).min(height);
+    let x1 = ((region[2] * width as f32).ceil() as u32).min(width);
+    let y1 = ((region[3] * height as f32).ceil() as u32).min(height);
+    let (crop_width, crop_height) = (x1.saturating_sub(x0), y1.saturating_sub(y0));
+    if crop_width == 0 || crop_height == 0 {
+        return Err("Nothing of the image is visible".to_string());
+    }
+    if crop_width > MAX_CROP_SIZE || crop_height > MAX_CROP_SIZE {
+        return Err(format!(
+            "Zoom in further: the visible region is {}x{} px, at most {}x{} can be upscaled",
+            crop_width, crop_height, MAX_CROP_SIZE, MAX_CROP_SIZE
+        ));
+    }
+    Ok(image.crop_imm(x0, y0, crop_width, crop_height).to_rgb8())
+}
+
+/// Model and bilinear upscales of the same crop
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub naive: Handle,
+    pub enhanced: Handle,
+    pub crop_size: (u32, u32),
+    pub scale: u32,
+}
+
+#[derive(Debug, Clone)]
+pub enum UpscalePreview {
+    Running,
+    Done(Comparison),
+    Failed(String),
+}
+
+fn compare(upscaler: &Upscaler, image: &DynamicImage, region: [f32; 4]) -> Result<Comparison, String> {
+    let crop = crop_region(image, region)?;
+    let enhanced = upscaler.upscale(&crop)?;
+    let naive = image::imageops::resize(&crop, enhanced.width(), enhanced.height(), image::imageops::FilterType::Triangle);
+    let handle = |rgb: RgbImage| {
+        let rgba = DynamicImage::ImageRgb8(rgb).to_rgba8();
+        Handle::from_rgba(rgba.width(), rgba.height(), rgba.into_raw())
+    };
+    Ok(Comparison {
+        scale: enhanced.width() / crop.width(),
+        crop_size: crop.dimensions(),
+        naive: handle(naive),
+        enhanced: handle(enhanced),
+    })
+}
+
+/// Upscales the active pane's visible region
+pub fn run(app: &mut DataViewer) -> Task<Message> {
+    let Some(upscaler) = app.inference.upscaler.clone() else {
+        return Task::none();
+    };
+    let pane_index = app.active_pane_index();
+    let Some(pane) = app.panes.get(pane_index).filter(|pane| pane.dir_loaded) else {
+        return Task::none();
+    };
+    let Some(source) = pane.img_cache.image_paths.get(pane.img_cache.current_index).cloned() else {
+        return Task::none();
+    };
+    let archive_cache = pane.has_compressed_file.then(|| Arc::clone(&pane.archive_cache));
+    let region = crate::noise_analysis::visible_region(pane_index).unwrap_or([0.0, 0.0, 1.0, 1.0]);
+
+    app.inference.upscale_preview = Some(UpscalePreview::Running);
+    Task::perform(
+        async move {
+            tokio::task::spawn_blocking(move || {
+                let image = crate::file_io::decode_path_source(&source, archive_cache.as_deref())?;
+                compare(&upscaler, &image, region)
+            })
+            .await
+            .unwrap_or_else(|e| Err(e.to_string()))
+        },
+        |result| InferenceMessage::Upscaled(result).into()
+    )
+}
+
+/// The comparison dialog while the preview is on
+pub fn view(state: &InferenceState) -> Option<Element<'_, Message, WinitTheme, Renderer>> {
+    let preview = state.upscale_preview.as_ref()?;
+
+    let body: Element<'_, Message, WinitTheme, Renderer> = match preview {
+        UpscalePreview::Running => container(text("Upscaling the visible region...").size(14))
+            .padding(20)
+            .into(),
+        UpscalePreview::Failed(e) => container(text(e.clone()).size(14))
+            .padding(20)
+            .into(),
+        UpscalePreview::Done(comparison) => {
+            let panel = |label: String, handle: &Handle| {
+                column![
+                    text(label).size(13),
+                    iced_widget::image(handle.clone())
+                        .width(Length::Fixed(PREVIEW_SIZE))
+                        .height(Length::Fixed(PREVIEW_SIZE)),
+                ]
+                .spacing(6)
+                .align_x(Alignment::Center)
+            };
+            column![
+                text(format!(
+                    "{}x{} px region, {}x",
+                    comparison.crop_size.0, comparison.crop_size.1, comparison.scale
+                )).size(12),
+                row![
+                    panel("Bilinear".to_string(), &comparison.naive),
+                    panel("Model".to_string(), &comparison.enhanced),
+                ]
+                .spacing(10),
+            ]
+            .spacing(8)
+            .into()
+        }
+    };
+
+    let running = matches!(preview, UpscalePreview::Running);
+    let content = column![
+        text("AI Upscale Preview")
+            .size(18)
+            .font(iced_winit::core::Font {
+                family: iced_winit::core::font::Family::Name("Roboto"),
+                weight: iced_winit::core::font::Weight::Bold,
+                stretch: iced_winit::core::font::Stretch::Normal,
+                style: iced_winit::core::font::Style::Normal,
+            }),
+        body,
+        row![
+            button(text("Refresh"))
+                .padding([3, 10])
+                .on_press_maybe((!running).then(|| InferenceMessage::RefreshUpscale.into())),
+            Space::with_width(Length::Fill),
+            button(text("Close"))
+                .padding([3, 10])
+                .on_press(InferenceMessage::SetUpscalePreview(false).into()),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    ]
+    .spacing(10);
+
+    Some(container(content)
+        .padding(15)
+        .style(|theme: &WinitTheme| {
+            iced_widget::container::Style {
+                background: Some(theme.extended_palette().background.base.color.into()),
+                text_color: Some(theme.extended_palette().primary.weak.text),
+                border: iced_winit::core::Border {
+                    color: theme.extended_palette().background.strong.color,
+                    width: 1.0,
+                    radius: iced_winit::core::border::Radius::from(8.0),
+                },
+                ..Default::default()
+            }
+        })
+        .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Nearest-neighbor 2x "model" reading the planar input back as 0-1 values
+    fn nearest_2x(tile: &RgbImage) -> Result<Output, String> {
+        let (width, height) = (tile.width() as usize, tile.height() as usize);
+        let input = planar_input(tile.as_raw(), [0.0; 3], [1.0; 3]);
+        let mut values = vec![0.0; 3 * width * height * 4];
+        for c in 0..3 {
+            for y in 0..height * 2 {
+                for x in 0..width * 2 {
+                    values[c * width * height * 4 + y * width * 2 + x] = input[c * width * height + (y / 2) * width + x / 2];
+                }
+            }
+        }
+        Ok((vec![1, 3, height * 2, width * 2], values))
+    }
+
+    #[test]
+    fn test_upscale_tiled() {
+        // 5x3 image in 2x2 tiles: the edge tiles are partly outside
+        let image = RgbImage::from_fn(5, 3, |x, y| Rgb([(x * 40) as u8, (y * 80) as u8, 7]));
+        let upscaled = upscale_tiled(&image, (2, 2), nearest_2x).unwrap();
+        assert_eq!(upscaled.dimensions(), (10, 6));
+        for (x, y, pixel) in upscaled.enumerate_pixels() {
+            assert_eq!(pixel, image.get_pixel(x / 2, y / 2));
+        }
+
+        let bad_shape = |_: &RgbImage| Ok((vec![1, 3, 3, 4], vec![0.0; 36]));
+        assert!(upscale_tiled(&image, (2, 2), bad_shape).is_err());
+    }
+
+    #[test]
+    fn test_crop_region() {
+        let image = DynamicImage::new_rgb8(2000, 1000);
+        let crop = crop_region(&image, [0.5, 0.5, 0.6, 0.7]).unwrap();
+        assert_eq!(crop.dimensions(), (200, 200));
+        assert!(crop_region(&image, [0.0, 0.0, 1.0, 1.0]).is_err());
+        assert!(crop_region(&image, [1.0, 0.0, 1.0, 1.0]).is_err());
+    }
+}
@@ -1,12 +1,185 @@
 #[allow(unused_imports)]
 use log::{debug, error, warn, info};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use iced_wgpu::wgpu;
+use iced_wgpu::engine::CompressionStrategy;
+use iced_winit::runtime::Task;
 use crate::Arc;
 use crate::pane;
+use crate::app::{Message, IoMessage, ImagesLoadedResult};
+use crate::file_io;
 use crate::loading_status::LoadingStatus;
-use crate::cache::img_cache::{LoadOperation, LoadOperationType, ImageMetadata};
+use crate::cache::img_cache::{LoadOperation, LoadOperationType, ImageMetadata, CacheStrategy};
 use crate::cache::img_cache::CachedData;
 use crate::widgets::shader::scene::Scene;
 
+/// Number of matches prefetched on each side of the current image in a filtered view
+pub const FILTERED_PREFETCH_DEPTH: usize = 2;
+
+/// Images loaded ahead along a filtered view
+///
+/// The sliding window in `ImageCache` only holds neighbors in directory order, so jumping
+/// to the next filter match always missed it. Matches around the current image are kept
+/// here instead, keyed by image index and checked against the path when read back.
+#[derive(Default)]
+pub struct FilteredPrefetch {
+    entries: HashMap<usize, (PathBuf, CachedData, Option<ImageMetadata>)>,
+    wanted: HashSet<usize>,
+    pending: HashSet<usize>,
+}
+
+impl FilteredPrefetch {
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.wanted.clear();
+        self.pending.clear();
+    }
+
+    /// Prefetched data for `index`, if it still refers to `path`
+    pub fn get(&self, index: usize, path: &PathBuf) -> Option<(CachedData, Option<ImageMetadata>)> {
+        self.entries.get(&index)
+            .filter(|(entry_path, _, _)| entry_path == path)
+            .map(|(_, data, metadata)| (data.clone(), metadata.clone()))
+    }
+}
+
+/// Indices of the next and previous `depth` matches around the current image, nearest first
+///
+/// `matches(forward)` yields the matching indices on one side, nearest first and wrapping
+/// around the list like filtered navigation (`PathIndex::walk_from`), so the prefetch
+/// follows the same order. The current image itself is never included.
+pub fn filtered_neighbors<I: Iterator<Item = usize>>(
+    depth: usize,
+    matches: impl Fn(bool) -> I,
+) -> Vec<usize> {
+    let walk = |forward: bool| -> Vec<usize> { matches(forward).take(depth).collect() };
+    let (next, prev) = (walk(true), walk(false));
+
+    let mut neighbors = Vec::with_capacity(depth * 2);
+    for i in 0..depth {
+        for idx in [next.get(i), prev.get(i)].into_iter().flatten() {
+            if !neighbors.contains(idx) {
+                neighbors.push(*idx);
+            }
+        }
+    }
+    neighbors
+}
+
+/// Load the filtered neighbors of a pane that are not prefetched yet
+///
+/// Entries outside `targets` are dropped first, so the side cache never holds more than
+/// `2 * FILTERED_PREFETCH_DEPTH` images. An empty `targets` clears it.
+#[allow(clippy::too_many_arguments)]
+pub fn prefetch_filtered(
+    device: &Arc<wgpu::Device>,
+    queue: &Arc<wgpu::Queue>,
+    cache_strategy: CacheStrategy,
+    compression_strategy: CompressionStrategy,
+    panes: &mut [pane::Pane],
+    pane_index: usize,
+    targets: &[usize],
+) -> Task<Message> {
+    let Some(pane) = panes.get_mut(pane_index) else {
+        return Task::none();
+    };
+    let prefetch = &mut pane.filtered_prefetch;
+    prefetch.wanted = targets.iter().copied().collect();
+    let wanted = &prefetch.wanted;
+    prefetch.entries.retain(|idx, _| wanted.contains(idx));
+
+    let missing: Vec<(usize, PathBuf)> = targets.iter()
+        .filter(|idx| !prefetch.entries.contains_key(idx) && !prefetch.pending.contains(idx))
+        .filter_map(|&idx| pane.img_cache.image_paths.get(idx).map(|p| (idx, p.path().clone())))
+        .collect();
+    if missing.is_empty() {
+        return Task::none();
+    }
+    debug!("prefetch_filtered: pane {} loading {:?}", pane_index, missing.iter().map(|(idx, _)| idx).collect::<Vec<_>>());
+    prefetch.pending.extend(missing.iter().map(|(idx, _)| *idx));
+
+    let paths = missing.iter()
+        .map(|(idx, _)| pane.img_cache.image_paths.get(*idx).cloned())
+        .collect();
+    let archive_caches = missing.iter()
+        .map(|_| pane.has_compressed_file.then(|| Arc::clone(&pane.archive_cache)))
+        .collect();
+    let device = Arc::clone(device);
+    let queue = Arc::clone(queue);
+    Task::perform(
+        async move {
+            file_io::load_images_async(
+                paths,
+                cache_strategy,
+                &device,
+                &queue,
+                compression_strategy,
+                LoadOperation::LoadPos((pane_index, Vec::new())),
+                archive_caches,
+            ).await
+        },
+        move |result| Message::Io(IoMessage::FilteredPrefetchLoaded(pane_index, missing.clone(), result))
+    )
+}
+
+/// Store images loaded by `prefetch_filtered` that are still wanted
+pub fn handle_filtered_prefetch_loaded(
+    panes: &mut [pane::Pane],
+    pane_index: usize,
+    targets: Vec<(usize, PathBuf)>,
+    result: ImagesLoadedResult,
+) {
+    let Some(pane) = panes.get_mut(pane_index) else {
+        return;
+    };
+    let prefetch = &mut pane.filtered_prefetch;
+    for (idx, _) in &targets {
+        prefetch.pending.remove(idx);
+    }
+
+    let (image_data, metadata, _) = match result {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            debug!("Filtered prefetch failed: {:?}", err);
+            return;
+        }
+    };
+    for (i, ((idx, path), data)) in targets.into_iter().zip(image_data).enumerate() {
+        let still_listed = pane.img_cache.image_paths.get(idx).is_some_and(|p| *p.path() == path);
+        if let (Some(data), true, true) = (data, still_listed, prefetch.wanted.contains(&idx)) {
+            let meta = metadata.get(i).cloned().flatten();
+            prefetch.entries.insert(idx, (path, data, meta));
+        }
+    }
+}
+
+/// Show prefetched data as the current image of a pane, stored at `cache_pos` in its window
+pub fn apply_prefetched(
+    pane: &mut pane::Pane,
+    pos: usize,
+    cache_pos: usize,
+    data: CachedData,
+    metadata: Option<ImageMetadata>,
+) {
+    let img_cache = &mut pane.img_cache;
+    img_cache.cached_data[cache_pos] = Some(data.clone());
+    img_cache.cached_metadata[cache_pos] = metadata.clone();
+    img_cache.cached_image_indices[cache_pos] = pos as isize;
+    img_cache.current_index = pos;
+
+    pane.scene = Some(Scene::new(Some(&data)));
+    if !matches!(data, CachedData::Gpu(_)) {
+        // CPU and BC1 data need their texture created before the next draw
+        if let (Some(scene), Some(device), Some(queue)) = (&mut pane.scene, &pane.device, &pane.queue) {
+            scene.ensure_texture(device, queue, pane.pane_id);
+        }
+    }
+    pane.current_image = data;
+    pane.current_image_index = Some(pos);
+    pane.current_image_metadata = metadata;
+}
+
 pub fn handle_load_operation_all(
     panes: &mut [pane::Pane],
     loading_status: &mut LoadingStatus,
@@ -244,3 +417,25 @@ pub fn handle_load_pos_operation(
         debug!("LoadPos: After processing, pane[{}].current_index = {}", pane_index, cache.current_index);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filtered_neighbors() {
+        let around = |count: usize, current: usize, matches: fn(&usize) -> bool| move |forward: bool| {
+            (1..count)
+                .map(move |step| if forward { (current + step) % count } else { (current + count - step) % count })
+                .filter(matches)
+        };
+        let every_third: fn(&usize) -> bool = |i| i % 3 == 0;
+        // Nearest matches first, alternating next and previous
+        assert_eq!(filtered_neighbors(2, around(20, 9, every_third)), vec![12, 6, 15, 3]);
+        // Wraps around like filtered navigation
+        assert_eq!(filtered_neighbors(1, around(10, 0, every_third)), vec![3, 9]);
+        // A match reached from both sides is listed once
+        assert_eq!(filtered_neighbors(2, around(7, 0, |i| *i == 3)), vec![3]);
+        assert!(filtered_neighbors(2, around(0, 0, every_third)).is_empty());
+    }
+}
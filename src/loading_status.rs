@@ -1,5 +1,7 @@
 use crate::cache::img_cache::{LoadOperation, LoadOperationType};
 use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 use crate::pane::Pane;
 #[allow(unused_imports)]
 use log::{Level, debug, info, warn, error};
@@ -143,4 +145,38 @@ impl LoadingStatus {
         self.loading_queue.iter().any(|op| op.operation_type() == operation) ||
         self.being_loaded_queue.iter().any(|op| op.operation_type() == operation)
     }
-}
\ No newline at end of file
+}
+
+/// Number of interactive loads created but not finished yet
+static INTERACTIVE_LOADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Longest a background image waits for interactive loads, so it cannot starve
+const MAX_BACKGROUND_YIELD: Duration = Duration::from_millis(500);
+
+/// Marks an interactive load as in flight until dropped
+///
+/// Create it when the load task is built, not when it starts running, so a background
+/// load already in progress sees it before decoding its next image.
+pub struct InteractiveLoadGuard;
+
+#[allow(clippy::new_without_default)]
+impl InteractiveLoadGuard {
+    pub fn new() -> Self {
+        INTERACTIVE_LOADS.fetch_add(1, Ordering::AcqRel);
+        Self
+    }
+}
+
+impl Drop for InteractiveLoadGuard {
+    fn drop(&mut self) {
+        INTERACTIVE_LOADS.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Wait before the next background image while interactive loads are pending
+pub async fn yield_to_interactive_loads() {
+    let start = Instant::now();
+    while INTERACTIVE_LOADS.load(Ordering::Acquire) > 0 && start.elapsed() < MAX_BACKGROUND_YIELD {
+        tokio::time::sleep(Duration::from_millis(2)).await;
+    }
+}
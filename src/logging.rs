@@ -285,7 +285,7 @@ pub fn setup_logger(_app_name: &str) -> Arc<Mutex<VecDeque<String>>> {
 }
 
 pub fn get_log_directory(app_name: &str) -> PathBuf {
-    dirs::data_dir().unwrap_or_else(|| PathBuf::from(".")).join(app_name).join("logs")
+    crate::app_dirs::data_dir().unwrap_or_else(|| PathBuf::from(".")).join(app_name).join("logs")
 }
 
 
@@ -845,7 +845,7 @@ pub fn write_immediate_crash_log(message: &str) {
     let mut paths = Vec::new();
 
     // Primary location: Use dirs crate like file_io does
-    let app_log_dir = dirs::data_dir()
+    let app_log_dir = crate::app_dirs::data_dir()
         .unwrap_or_else(|| std::path::PathBuf::from("."))
         .join("viewskater")
         .join("logs");
@@ -855,7 +855,7 @@ pub fn write_immediate_crash_log(message: &str) {
     }
 
     // Backup: Use cache directory
-    if let Some(cache_dir) = dirs::cache_dir() {
+    if let Some(cache_dir) = crate::app_dirs::cache_dir() {
         let cache_log_dir = cache_dir.join("viewskater");
         if std::fs::create_dir_all(&cache_log_dir).is_ok() {
             paths.push(cache_log_dir.join("crash.log"));
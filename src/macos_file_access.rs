@@ -163,6 +163,10 @@ pub mod macos_file_handler {
     // This prevents multiple URLByResolvingBookmarkData calls for the same directory within a session
     static SESSION_RESOLVED_URLS: Lazy<Mutex<HashMap<String, Retained<NSURL>>>> = 
         Lazy::new(|| Mutex::new(HashMap::new()));
+
+    // Why the stored bookmark of a directory could not be used, shown in the access dialog
+    static RESOLVE_FAILURES: Lazy<Mutex<HashMap<String, String>>> =
+        Lazy::new(|| Mutex::new(HashMap::new()));
     
     // Constants for security-scoped bookmarks
     const NSURL_BOOKMARK_CREATION_WITH_SECURITY_SCOPE: u64 = 1 << 11;  // 0x800
@@ -226,8 +230,37 @@ pub mod macos_file_handler {
         (modern_ns, legacy_ns)
     }
     
+    /// UserDefaults key for the Unix time a bookmark was created ("Created") or last
+    /// resolved ("Resolved")
+    fn make_bookmark_time_key(kind: &str, directory_path: &str) -> Retained<NSString> {
+        NSString::from_str(&format!("VSBookmark{}|{}", kind, directory_path))
+    }
+
+    fn store_bookmark_time(kind: &str, directory_path: &str) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as isize;
+        autoreleasepool(|_pool| unsafe {
+            let defaults = NSUserDefaults::standardUserDefaults();
+            let key = make_bookmark_time_key(kind, directory_path);
+            let _: () = msg_send![&*defaults, setInteger: now forKey: &*key];
+        });
+    }
+
     // ==================== END CRASH DEBUG LOGGING ====================
 
+    fn record_resolve_failure(directory_path: &str, reason: &str) {
+        if let Ok(mut failures) = RESOLVE_FAILURES.lock() {
+            failures.insert(directory_path.to_string(), reason.to_string());
+        }
+    }
+
+    /// Why the stored bookmark of `directory_path` failed to resolve this session, if it did
+    pub fn resolve_failure(directory_path: &str) -> Option<String> {
+        RESOLVE_FAILURES.lock().ok()?.get(directory_path).cloned()
+    }
+
     pub fn set_file_channel(sender: Sender<String>) {
         debug!("Setting file channel for macOS file handler");
         unsafe {
@@ -437,11 +470,16 @@ pub mod macos_file_handler {
             // Also store legacy key for back-compat migration
             let _: () = msg_send![&*defaults, setObject: bookmark_data forKey: &*legacy_key];
             
+            store_bookmark_time("Created", directory_path);
+            
             // Synchronize to ensure it's persisted
             let sync_ok: bool = msg_send![&*defaults, synchronize];
             if sync_ok {
                 write_security_bookmark_debug_log("BOOKMARK_CREATE_FIXED: SUCCESS - bookmark stored and synchronized");
                 debug!("Successfully stored security-scoped bookmark");
+                if let Ok(mut failures) = RESOLVE_FAILURES.lock() {
+                    failures.remove(directory_path);
+                }
                 // Immediate read-back verification and logging
                 let modern_obj: *mut AnyObject = msg_send![&*defaults, objectForKey: &*modern_key];
                 if modern_obj.is_null() {
@@ -491,7 +529,7 @@ pub mod macos_file_handler {
 
     /// Requests directory access via NSOpenPanel and creates persistent bookmark
     /// Optimized single-dialog approach that feels like a yes/no confirmation
-    fn request_directory_access_with_optimized_dialog(requested_path: &str) -> bool {
+    pub fn request_directory_access_with_optimized_dialog(requested_path: &str) -> bool {
         crate::logging::write_immediate_crash_log(&format!("OPTIMIZED_DIALOG: Starting for path: {}", requested_path));
         debug!("Requesting directory access via optimized dialog for: {}", requested_path);
         
@@ -1017,6 +1055,7 @@ pub mod macos_file_handler {
                 crate::logging::write_immediate_crash_log("SESSION_RESOLVE: ERROR - bookmark data is not NSData, removing");
                 let _: () = msg_send![&*defaults, removeObjectForKey: &*modern_key];
                 let _: () = msg_send![&*defaults, removeObjectForKey: &*legacy_key];
+                record_resolve_failure(directory_path, "The saved permission for this folder was unreadable and has been removed.");
                 return None;
             }
             
@@ -1045,14 +1084,17 @@ pub mod macos_file_handler {
                     let desc_nsstring = &*(error_desc as *const NSString);
                     let error_msg = desc_nsstring.as_str(pool);
                     crate::logging::write_immediate_crash_log(&format!("SESSION_RESOLVE: URLByResolvingBookmarkData ERROR: {}", error_msg));
+                    record_resolve_failure(directory_path, &format!("The saved permission for this folder could not be resolved: {}", error_msg));
                 } else {
                     crate::logging::write_immediate_crash_log("SESSION_RESOLVE: URLByResolvingBookmarkData failed with unknown error");
+                    record_resolve_failure(directory_path, "The saved permission for this folder could not be resolved.");
                 }
                 return None;
             }
             
             if resolved_url.is_null() {
                 crate::logging::write_immediate_crash_log("SESSION_RESOLVE: URLByResolvingBookmarkData returned null URL (no error)");
+                record_resolve_failure(directory_path, "The saved permission for this folder could not be resolved.");
                 return None;
             }
             
@@ -1180,6 +1222,7 @@ pub mod macos_file_handler {
                 }
             } else {
                 crate::logging::write_immediate_crash_log("SESSION_RESOLVE: FAILURE - startAccessingSecurityScopedResource returned false");
+                record_resolve_failure(directory_path, "macOS no longer accepts the saved permission for this folder; it may have been moved or renamed.");
                 
                 // Enhanced failure diagnostics
                 
@@ -1217,6 +1260,10 @@ pub mod macos_file_handler {
         
         // STEP 3: Cache the resolved URL for future use (success or failure)
         if let Some(ref url) = resolved_url {
+            if let Ok(mut failures) = RESOLVE_FAILURES.lock() {
+                failures.remove(directory_path);
+            }
+            store_bookmark_time("Resolved", directory_path);
             if let Ok(mut session_cache) = SESSION_RESOLVED_URLS.lock() {
                 session_cache.insert(directory_path.to_string(), url.clone());
                 crate::logging::write_immediate_crash_log(&format!("SESSION_RESOLVE: CACHED - Stored resolved URL in session cache for: {}", directory_path));
@@ -1228,6 +1275,111 @@ pub mod macos_file_handler {
         resolved_url
     }
 
+    /// Whether a stored bookmark works this session
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum BookmarkStatus {
+        /// Resolved and in use
+        Active,
+        /// Not needed since the app started
+        Unused,
+        /// Failed to resolve, with the reason
+        Broken(String),
+    }
+
+    /// A folder grant stored in UserDefaults
+    #[derive(Debug, Clone)]
+    pub struct StoredBookmark {
+        pub path: String,
+        /// Unix time the bookmark was created; unknown for bookmarks stored by older versions
+        pub created: Option<i64>,
+        /// Unix time the bookmark last resolved
+        pub last_resolved: Option<i64>,
+        pub status: BookmarkStatus,
+    }
+
+    /// Lists the stored bookmarks by path, for the permissions page of the settings
+    pub fn list_stored_bookmarks() -> Vec<StoredBookmark> {
+        let entries: Vec<(String, i64, i64)> = autoreleasepool(|pool| unsafe {
+            let defaults = NSUserDefaults::standardUserDefaults();
+            let dictionary: *mut AnyObject = msg_send![&*defaults, dictionaryRepresentation];
+            if dictionary.is_null() {
+                return Vec::new();
+            }
+            let keys: *mut AnyObject = msg_send![dictionary, allKeys];
+            if keys.is_null() {
+                return Vec::new();
+            }
+            let keys = &*(keys as *const NSArray<NSString>);
+            let mut entries = Vec::new();
+            for i in 0..keys.len() {
+                // Legacy keys are truncated and always stored next to a modern one
+                if let Some(path) = keys[i].as_str(pool).strip_prefix("VSBookmark|") {
+                    let created: isize = msg_send![&*defaults, integerForKey: &*make_bookmark_time_key("Created", path)];
+                    let resolved: isize = msg_send![&*defaults, integerForKey: &*make_bookmark_time_key("Resolved", path)];
+                    entries.push((path.to_string(), created as i64, resolved as i64));
+                }
+            }
+            entries
+        });
+
+        let mut bookmarks: Vec<StoredBookmark> = entries.into_iter()
+            .map(|(path, created, resolved)| {
+                let active = SESSION_RESOLVED_URLS.lock().is_ok_and(|cache| cache.contains_key(&path))
+                    || has_security_scoped_access(&path);
+                let status = match resolve_failure(&path) {
+                    Some(reason) => BookmarkStatus::Broken(reason),
+                    None if active => BookmarkStatus::Active,
+                    None => BookmarkStatus::Unused,
+                };
+                StoredBookmark {
+                    path,
+                    // integerForKey: gives 0 for keys that are not set
+                    created: (created > 0).then_some(created),
+                    last_resolved: (resolved > 0).then_some(resolved),
+                    status,
+                }
+            })
+            .collect();
+        bookmarks.sort_by(|a, b| a.path.cmp(&b.path));
+        bookmarks
+    }
+
+    /// Removes the stored bookmark of `directory_path` and ends the access it gave this session
+    pub fn remove_stored_bookmark(directory_path: &str) {
+        debug!("Removing stored bookmark for: {}", directory_path);
+        autoreleasepool(|_pool| unsafe {
+            let defaults = NSUserDefaults::standardUserDefaults();
+            let (modern_key, legacy_key) = make_bookmark_keys(directory_path);
+            let created_key = make_bookmark_time_key("Created", directory_path);
+            let resolved_key = make_bookmark_time_key("Resolved", directory_path);
+            for key in [modern_key, legacy_key, created_key, resolved_key] {
+                let _: () = msg_send![&*defaults, removeObjectForKey: &*key];
+            }
+            let sync_ok: bool = msg_send![&*defaults, synchronize];
+            crate::logging::write_immediate_crash_log(&format!("BOOKMARK_REMOVE: key='VSBookmark|{}' sync_ok={}", directory_path, sync_ok));
+        });
+
+        if let Ok(mut session_cache) = SESSION_RESOLVED_URLS.lock() {
+            if let Some(url) = session_cache.remove(directory_path) {
+                unsafe {
+                    let _: () = msg_send![&*url, stopAccessingSecurityScopedResource];
+                }
+            }
+        }
+        if let Ok(mut urls) = SECURITY_SCOPED_URLS.lock() {
+            if let Some(info) = urls.remove(directory_path) {
+                if info.has_active_scope {
+                    unsafe {
+                        let _: () = msg_send![&*info.url, stopAccessingSecurityScopedResource];
+                    }
+                }
+            }
+        }
+        if let Ok(mut failures) = RESOLVE_FAILURES.lock() {
+            failures.remove(directory_path);
+        }
+    }
+
     /// Read directory contents using the resolved security-scoped NSURL directly
     /// This follows Apple's pattern - use the URL instance directly, don't convert to path
     pub fn read_directory_with_security_scoped_url(directory_path: &str) -> Option<Vec<String>> {
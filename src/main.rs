@@ -3,14 +3,18 @@
 mod cache;
 mod navigation_keyboard;
 mod navigation_slider;
+mod navigation_history;
+mod zoom_lock;
 mod file_io;
 mod menu;
+mod menu_layout;
 mod widgets;
 mod pane;
 mod ui;
 mod loading_status;
 mod loading_handler;
 mod config;
+mod app_dirs;
 mod settings;
 mod app;
 mod utils;
@@ -20,14 +24,66 @@ mod logging;
 mod selection_manager;
 #[cfg(feature = "coco")]
 mod coco;
+#[cfg(feature = "embeddings")]
+mod embeddings;
+#[cfg(feature = "parquet")]
+mod parquet_dataset;
+#[cfg(feature = "scripting")]
+mod scripting;
 mod settings_modal;
+mod sequence_export_modal;
+mod plugin_manager_modal;
+mod metadata_editor;
+mod folder_preload;
+mod bulk_load;
+mod animation;
+mod display_decode;
+mod comparison_report;
+mod folder_stats;
+mod usage_stats;
+#[cfg(feature = "update-check")]
+mod update_check;
+#[cfg(feature = "gps-map")]
+mod gps_map;
+#[cfg(feature = "inference")]
+mod inference;
+mod follow_mode;
+mod triggers;
+mod settings_watch;
+mod profiles;
+mod safe_mode;
+mod favorites;
+mod playlist;
+mod session;
+mod file_hashes;
+mod file_delete;
+mod viewed_images;
+mod display_profile;
+mod soft_proof;
+mod white_balance;
+mod noise_analysis;
+mod raw_pairs;
+mod burst_stacks;
+mod auto_rotate;
+mod channel_diff;
+mod plugins;
+mod stdin_input;
+mod startup_image;
 mod replay;
-mod exif_utils;
 mod window_state;
+mod offscreen;
+#[cfg(test)]
+mod test_harness;
 
 #[cfg(target_os = "macos")]
 mod macos_file_access;
-mod archive_cache;
+#[cfg(target_os = "macos")]
+mod access_prompt;
+#[cfg(target_os = "macos")]
+mod bookmark_permissions;
+
+// Source and archive reading, shared with other tools through the viewskater-core crate
+use viewskater_core::{archive_cache, exif_utils};
 
 use iced_winit::winit::dpi::PhysicalPosition;
 #[allow(unused_imports)]
@@ -75,13 +131,15 @@ use iced_wgpu::engine::CompressionStrategy;
 
 use crate::settings::WindowState;
 use crate::utils::timing::TimingStats;
-use crate::app::{Message, DataViewer};
+use crate::app::{Message, PaneMessage, DataViewer};
 use crate::widgets::shader::scene::Scene;
 use crate::config::CONFIG;
 use std::sync::mpsc::{self as std_mpsc, Receiver as StdReceiver, Sender as StdSender};
 
 // Maximum texture size supported by most GPUs (prevents wgpu surface configuration panic)
 const MAX_TEXTURE_SIZE: u32 = 8192;
+// How often the event loop checks whether GPU setup has finished during startup
+const GPU_SETUP_POLL_INTERVAL: Duration = Duration::from_millis(5);
 use iced_wgpu::{get_image_rendering_diagnostics, log_image_rendering_stats};
 use iced_wgpu::engine::ImageConfig;
 use std::sync::mpsc::{self, Receiver};
@@ -171,13 +229,37 @@ use std::path::PathBuf;
 #[command(about = "A fast image viewer for browsing large collections of images")]
 #[command(version)]
 struct Args {
-    /// Path to image file or directory to open
+    /// Path to image file or directory to open, or `-` to read an image from stdin
     path: Option<PathBuf>,
 
+    /// Read newline-separated image paths from stdin and open them as a list
+    #[arg(long)]
+    stdin_list: bool,
+
+    /// Watch DIR and jump to each new image as it is written (P pauses)
+    #[arg(long, value_name = "DIR")]
+    follow: Option<PathBuf>,
+
+    /// Listen for OSC navigation messages on this UDP port (see triggers.yaml for MIDI)
+    #[arg(long, value_name = "PORT")]
+    osc_port: Option<u16>,
+
     /// Path to custom settings file
     #[arg(long = "settings")]
     settings_path: Option<String>,
 
+    /// Start with the named profile from the profiles folder
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Keep settings and logs in a `viewskater-data` folder next to the executable
+    #[arg(long)]
+    portable: bool,
+
+    /// Start without BC1 compression, saved window geometry, adapter override or plugins
+    #[arg(long)]
+    safe_mode: bool,
+
     /// Enable replay/benchmark mode
     #[arg(long)]
     replay: bool,
@@ -229,6 +311,19 @@ struct Args {
     /// Step size for slider navigation mode (how many images to skip per navigation)
     #[arg(long, default_value = "1")]
     slider_step: u16,
+
+    /// Write frame timing and navigation latency percentiles as JSON to FILE on exit
+    #[arg(long, value_name = "FILE")]
+    frame_stats: Option<PathBuf>,
+
+    /// Render FRAMES frames of navigation through the path or --test-dir folder offscreen on
+    /// the software adapter, print timing percentiles and exit
+    #[arg(long, value_name = "FRAMES")]
+    render_offscreen: Option<u32>,
+
+    /// With --render-offscreen, exit with an error when the p99 frame time exceeds MS
+    #[arg(long, value_name = "MS")]
+    max_frame_p99: Option<f64>,
 }
 
 fn register_font_manually(font_data: &'static [u8]) {
@@ -244,6 +339,34 @@ fn register_font_manually(font_data: &'static [u8]) {
     font_system_guard.load_font(Cow::Borrowed(font_data));
 }
 
+/// Fonts the UI uses, loaded into the global font system
+fn register_fonts() {
+    register_font_manually(include_bytes!("../assets/fonts/viewskater-fonts.ttf"));
+    register_font_manually(include_bytes!("../assets/fonts/Iosevka-Regular-ascii.ttf"));
+    register_font_manually(include_bytes!("../assets/fonts/Roboto-Regular.ttf"));
+}
+
+fn app_theme() -> Theme {
+    Theme::custom_with_fn(
+        "Custom Theme".to_string(),
+        iced_winit::core::theme::Palette {
+            primary: iced_winit::core::Color::from_rgba8(20, 148, 163, 1.0),
+            text: iced_winit::core::Color::from_rgba8(224, 224, 224, 1.0),
+            ..Theme::Dark.palette()
+        },
+        |palette| {
+            // Generate the extended palette from the base palette
+            let mut extended: iced_core::theme::palette::Extended = iced_core::theme::palette::Extended::generate(palette);
+
+            // Customize specific parts of the extended palette
+            extended.primary.weak.text = iced_winit::core::Color::from_rgba8(224, 224, 224, 1.0);
+
+            // Return the modified extended palette
+            extended
+        }
+    )
+}
+
 #[allow(dead_code)]
 enum Control {
     ChangeFlow(winit::event_loop::ControlFlow),
@@ -302,6 +425,135 @@ fn update_memory_usage() {
 
 
 
+/// GPU objects created on a worker thread during startup
+struct GpuSetup {
+    surface: wgpu::Surface<'static>,
+    adapter: wgpu::Adapter,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    format: wgpu::TextureFormat,
+    present_mode: wgpu::PresentMode,
+}
+
+/// Pick the adapter, present mode and surface format, and create the device
+fn setup_gpu(instance: wgpu::Instance, surface: wgpu::Surface<'static>) -> GpuSetup {
+    let (format, adapter, device, queue, present_mode) =
+        futures::futures::executor::block_on(async {
+            let adapter = if safe_mode::is_enabled() {
+                instance.request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::default(),
+                    force_fallback_adapter: false,
+                    compatible_surface: Some(&surface),
+                })
+                .await
+                .expect("Create adapter")
+            } else {
+                wgpu::util::initialize_adapter_from_env_or_default(
+                    &instance,
+                    Some(&surface),
+                )
+                .await
+                .expect("Create adapter")
+            };
+
+            let capabilities = surface.get_capabilities(&adapter);
+
+            info!("GPU: {:?}", adapter.get_info().name);
+            info!("Available present modes: {:?}", capabilities.present_modes);
+
+            // Select non-blocking present mode to prevent frame.present()
+            // from stalling the event loop on NVIDIA GPUs (strict FIFO queue).
+            // Mailbox: non-blocking, replaces pending frame with latest (ideal)
+            // Immediate: non-blocking, no VSync (fallback)
+            // AutoNoVsync: auto-selects Mailbox or Immediate
+            let present_mode = if capabilities.present_modes.contains(&wgpu::PresentMode::Mailbox) {
+                info!("Selected Mailbox present mode (non-blocking)");
+                wgpu::PresentMode::Mailbox
+            } else if capabilities.present_modes.contains(&wgpu::PresentMode::Immediate) {
+                info!("Mailbox not available, selected Immediate present mode");
+                wgpu::PresentMode::Immediate
+            } else {
+                info!("Selected AutoNoVsync present mode (fallback)");
+                wgpu::PresentMode::AutoNoVsync
+            };
+
+            // Safe mode does not insist on BC texture support
+            let required_features = if safe_mode::is_enabled() {
+                adapter.features() & wgpu::Features::TEXTURE_COMPRESSION_BC
+            } else {
+                wgpu::Features::empty() | wgpu::Features::TEXTURE_COMPRESSION_BC
+            };
+            let (device, queue) = adapter
+                .request_device(
+                    &wgpu::DeviceDescriptor {
+                        label: Some("Main Device"),
+                        required_features,
+                        required_limits: wgpu::Limits::default(),
+                    },
+                    None,
+                )
+                .await
+                .expect("Request device");
+
+            (
+                capabilities
+                    .formats
+                    .iter()
+                    .copied()
+                    .find(wgpu::TextureFormat::is_srgb)
+                    .or_else(|| {
+                        capabilities.formats.first().copied()
+                    })
+                    .expect("Get preferred format"),
+                adapter,
+                device,
+                queue,
+                present_mode,
+            )
+        });
+    GpuSetup { surface, adapter, device, queue, format, present_mode }
+}
+
+/// `--render-offscreen`: returns the process exit code
+fn run_offscreen(args: &Args, frames: u32) -> i32 {
+    let Some(directory) = args.test_directories.first().or(args.path.as_ref()).filter(|dir| dir.is_dir()) else {
+        eprintln!("Error: --render-offscreen needs a folder. Use --test-dir or provide a path argument.");
+        return 1;
+    };
+    if let Some(output) = args.frame_stats.clone() {
+        utils::frame_stats::enable(output);
+    }
+
+    let run = offscreen::OffscreenRun {
+        directory: directory.clone(),
+        frames,
+        settings_path: args.settings_path.clone(),
+    };
+    let report = match offscreen::run(&run) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Error: Offscreen rendering failed: {}", e);
+            return 1;
+        }
+    };
+    utils::frame_stats::write_report();
+
+    println!("Rendered {} frame(s) of {} offscreen on {}", report.frame.count, directory.display(), report.adapter);
+    println!("  Open folder: {:.2} ms", report.open_ms);
+    for (label, summary) in [("Frame", &report.frame), ("Present", &report.present)] {
+        println!("  {:<8} mean {:.2} ms, p50 {:.2} ms, p90 {:.2} ms, p99 {:.2} ms, max {:.2} ms",
+            label, summary.mean_ms, summary.p50_ms, summary.p90_ms, summary.p99_ms, summary.max_ms);
+    }
+
+    match args.max_frame_p99 {
+        Some(limit) if report.frame.p99_ms > limit => {
+            eprintln!("Error: p99 frame time {:.2} ms is over the {:.2} ms limit", report.frame.p99_ms, limit);
+            1
+        }
+        _ => 0,
+    }
+}
+
 pub fn main() -> Result<(), winit::error::EventLoopError> {
     // CRITICAL: Write to crash log IMMEDIATELY - before any other operations
     crate::logging::write_crash_debug_log("MAIN: App startup initiated");
@@ -326,6 +578,9 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
     crate::logging::write_crash_debug_log("MAIN: About to setup logger");
     let app_name = "viewskater";
     let shared_log_buffer = crate::logging::setup_logger(app_name);
+    if let Some(root) = app_dirs::portable_root_dir() {
+        info!("Portable mode: storing settings and logs in {}", root.display());
+    }
 
     // Store the log buffer reference for global access
     set_shared_log_buffer(Arc::clone(&shared_log_buffer));
@@ -335,6 +590,14 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
     crate::logging::setup_panic_hook(app_name, shared_log_buffer);
     crate::logging::write_crash_debug_log("MAIN: Panic hook setup completed");
 
+    // Parse command line arguments
+    let args = Args::parse();
+
+    // Offscreen rendering needs no window, so it runs before winit looks for a display
+    if let Some(frames) = args.render_offscreen {
+        std::process::exit(run_offscreen(&args, frames));
+    }
+
     // Initialize winit FIRST
     let event_loop = EventLoop::<Action<Message>>::with_user_event()
         .build()
@@ -343,11 +606,55 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
     // Set up the file channel AFTER winit initialization
     let (file_sender, file_receiver) = mpsc::channel();
 
-    // Parse command line arguments
-    let args = Args::parse();
     let settings_path = args.settings_path.clone();
+    if args.safe_mode {
+        safe_mode::enable();
+        info!("Safe mode: using conservative defaults for this session");
+    }
+    if args.portable && app_dirs::portable_root_dir().is_none() {
+        eprintln!("Warning: --portable ignored, the executable's folder could not be determined");
+    }
     #[cfg(not(target_os = "macos"))]
-    let file_arg = args.path.as_ref().map(|p| p.to_string_lossy().to_string());
+    let file_arg = args.path.as_ref()
+        .filter(|p| p.as_os_str() != "-")
+        .map(|p| p.to_string_lossy().to_string());
+
+    if let Some(output) = args.frame_stats.clone() {
+        utils::frame_stats::enable(output);
+    }
+
+    // `-` opens an image piped in on stdin, --stdin-list a list of paths
+    if args.path.as_ref().is_some_and(|p| p.as_os_str() == "-") {
+        match stdin_input::read_image(std::io::stdin().lock()) {
+            Ok(path) => {
+                if let Err(e) = file_sender.send(path.to_string_lossy().to_string()) {
+                    println!("Failed to send file path through channel: {}", e);
+                }
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else if args.stdin_list {
+        // Read once the window is up, so the list opens on the first path rather than at EOF
+        stdin_input::request_list(std::env::current_dir().unwrap_or_default());
+    } else if let Some(directory) = args.follow.clone() {
+        if !directory.is_dir() {
+            eprintln!("Error: --follow needs a directory: {}", directory.display());
+            std::process::exit(1);
+        }
+        follow_mode::request(directory);
+    }
+    triggers::request(args.osc_port);
+    settings_watch::request(args.settings_path.as_deref());
+    if let Some(name) = args.profile.clone() {
+        if !profiles::available().contains(&name) {
+            eprintln!("Error: No profile named {} in {}", name, profiles::profiles_dir().display());
+            std::process::exit(1);
+        }
+        profiles::request(name);
+    }
 
     // Create replay configuration if replay mode is enabled
     let replay_config = if args.replay {
@@ -481,6 +788,12 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
                 } else {
                     println!("Successfully queued file path for loading: {}", file_path);
                 }
+
+                // The GPU cache decodes the opened file itself; start that decode now
+                let cache_strategy = settings::UserSettings::load(settings_path.as_deref()).get_cache_strategy();
+                if cache_strategy == crate::cache::img_cache::CacheStrategy::Gpu {
+                    startup_image::prefetch(std::path::Path::new(file_path));
+                }
             } else {
                 println!("Warning: Specified file path does not exist: {}", file_path);
             }
@@ -504,6 +817,17 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
             settings_path: Option<String>,
             replay_config: Option<replay::ReplayConfig>,
         },
+        /// Window is shown while `setup_gpu` runs on a worker thread
+        Initializing {
+            window: Arc<winit::window::Window>,
+            gpu_receiver: StdReceiver<GpuSetup>,
+            proxy: EventLoopProxy<Action<Message>>,
+            event_sender: StdSender<Event<Action<Message>>>,
+            control_receiver: StdReceiver<Control>,
+            file_receiver: Receiver<String>,
+            settings_path: Option<String>,
+            replay_config: Option<replay::ReplayConfig>,
+        },
         Ready {
             window: Arc<winit::window::Window>,
             device: Arc<wgpu::Device>,
@@ -538,6 +862,185 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
     }
 
     impl Runner {
+        /// Build the renderer and app once the GPU is ready, and start drawing frames
+        fn finish_startup(
+            &mut self,
+            event_loop: &winit::event_loop::ActiveEventLoop,
+            gpu: GpuSetup,
+        ) {
+            let Self::Initializing { window, proxy, event_sender, control_receiver, file_receiver, settings_path, replay_config, .. } = self else {
+                return;
+            };
+            let window = window.clone();
+
+            let custom_theme = app_theme();
+
+            let GpuSetup { surface, adapter, device, queue, format, present_mode } = gpu;
+            let physical_size = window.inner_size();
+            // Cap to wgpu texture limits
+            let capped_width = physical_size.width.min(MAX_TEXTURE_SIZE);
+            let capped_height = physical_size.height.min(MAX_TEXTURE_SIZE);
+            let viewport = Viewport::with_physical_size(
+                Size::new(capped_width, capped_height),
+                window.scale_factor(),
+            );
+            let clipboard = Clipboard::connect(window.clone());
+
+            surface.configure(
+                &device,
+                &wgpu::SurfaceConfiguration {
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    format,
+                    width: capped_width,
+                    height: capped_height,
+                    present_mode,
+                    alpha_mode: wgpu::CompositeAlphaMode::Auto,
+                    view_formats: vec![],
+                    desired_maximum_frame_latency: 2,
+                },
+            );
+
+            // Create shared Arc instances of device and queue
+            let device = Arc::new(device);
+            let queue = Arc::new(queue);
+            let backend = adapter.get_info().backend;
+
+            // Initialize iced
+            let mut debug_tool = Debug::new();
+
+            let config = ImageConfig {
+                atlas_size: CONFIG.atlas_size,
+                compression_strategy: if safe_mode::is_enabled() { CompressionStrategy::None } else { CompressionStrategy::Bc1 },
+            };
+            let engine = Arc::new(Mutex::new(Engine::new(
+                &adapter, &device, &queue, format, None, Some(config))));
+            {
+                let engine_guard = engine.lock().unwrap();
+                engine_guard.create_image_cache(&device);
+            }
+
+            register_fonts();
+
+            // Create renderer with Rc<Mutex>
+            let renderer = std::rc::Rc::new(Mutex::new(Renderer::new(
+                &device,
+                &engine.lock().unwrap(),
+                Font::with_name("Roboto"),
+                Pixels::from(16),
+            )));
+
+            // Create the renderer request channel
+            let (renderer_request_sender, renderer_request_receiver) = mpsc::channel();
+
+            // Pass a cloned Arc reference to DataViewer
+            let mut shader_widget = DataViewer::new(
+                Arc::clone(&device),
+                Arc::clone(&queue),
+                backend,
+                renderer_request_sender,
+                std::mem::replace(file_receiver, mpsc::channel().1),
+                settings_path.as_deref(),
+                std::mem::take(replay_config),
+            );
+
+            shader_widget.last_monitor = window.current_monitor();
+            display_profile::select_for_monitor(shader_widget.last_monitor.as_ref().and_then(|m| m.name()));
+
+            // Update state creation to lock renderer
+            let mut renderer_guard = renderer.lock().unwrap();
+            let mut state = program::State::new(
+                shader_widget,
+                viewport.logical_size(),
+                &mut *renderer_guard,
+                &mut debug_tool,
+            );
+
+            match CONFIG.window_state {
+                WindowState::Maximized => {
+                    // On macOS, setup_macos_window() calls NSWindow.zoom() instead —
+                    // set_maximized() doesn't establish _savedFrame for unzoom
+                    #[cfg(not(target_os = "macos"))]
+                    window.set_maximized(true);
+                },
+                WindowState::FullScreen => {
+                    let fullscreen = Some(winit::window::Fullscreen::Borderless(None));
+                    state.queue_message(Message::Pane(PaneMessage::ToggleFullScreen(true)));
+                    #[cfg(target_os = "macos")] {
+                        use iced_winit::winit::platform::macos::WindowExtMacOS;
+                        window.set_simple_fullscreen(fullscreen.is_some());
+                    }
+                    #[cfg(not(target_os = "macos"))] {
+                        window.set_fullscreen(fullscreen);
+                    }
+                },
+                _ => {},
+            }
+
+            // Set control flow
+            event_loop.set_control_flow(ControlFlow::Poll);
+
+            let (p, worker) = iced_winit::Proxy::new(proxy.clone());
+            let Ok(executor) = iced_futures::backend::native::tokio::Executor::new() else {
+                panic!("could not create runtime")
+            };
+            executor.spawn(worker);
+            let runtime = iced_futures::Runtime::new(executor, p);
+
+            // Create a proper static waker
+            let waker = {
+                // Create a waker that does nothing
+                struct NoopWaker;
+
+                impl Wake for NoopWaker {
+                    fn wake(self: Arc<Self>) {}
+                    fn wake_by_ref(self: &Arc<Self>) {}
+                }
+
+                // Create a waker and leak it to make it 'static
+                let waker_arc = Arc::new(NoopWaker);
+                let waker = Waker::from(waker_arc);
+                Box::leak(Box::new(waker))
+            };
+
+            let context = task::Context::from_waker(waker);
+
+            // Create a new Ready state with the event_sender and control_receiver
+            // Note: We don't clone the receiver as it's not clonable
+            let event_sender = event_sender.clone();
+
+            // Move the control_receiver into the Ready state
+            // We need to take ownership of it from the Initializing state
+            let control_receiver = std::mem::replace(control_receiver, std_mpsc::channel().1);
+
+            *self = Self::Ready {
+                window,
+                device,
+                queue,
+                surface,
+                format,
+                present_mode,
+                engine,
+                renderer: renderer.clone(),
+                state,
+                cursor_position: None,
+                modifiers: ModifiersState::default(),
+                clipboard,
+                runtime,
+                viewport,
+                resized: false,
+                moved: false,
+                redraw: true,
+                last_title: String::new(),
+                debug: false,
+                debug_tool,
+                _event_sender: event_sender,
+                control_receiver,
+                _context: context,
+                custom_theme,
+                renderer_request_receiver,
+            };
+        }
+
         fn process_event(
             &mut self,
             event_loop: &winit::event_loop::ActiveEventLoop,
@@ -560,6 +1063,22 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
                         _ => {}
                     }
                 }
+                Runner::Initializing { gpu_receiver, .. } => {
+                    if let Event::EventLoopAwakened(winit::event::Event::WindowEvent { event: WindowEvent::CloseRequested, .. }) = event {
+                        event_loop.exit();
+                        return;
+                    }
+                    match gpu_receiver.try_recv() {
+                        Ok(gpu) => self.finish_startup(event_loop, gpu),
+                        Err(std_mpsc::TryRecvError::Empty) => {
+                            event_loop.set_control_flow(ControlFlow::WaitUntil(Instant::now() + GPU_SETUP_POLL_INTERVAL));
+                        }
+                        Err(std_mpsc::TryRecvError::Disconnected) => {
+                            error!("GPU initialization failed, see the log above");
+                            event_loop.exit();
+                        }
+                    }
+                }
                 Runner::Ready {
                     window,
                     device,
@@ -612,18 +1131,19 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
                                         // Update app's window width for responsive layout
                                         // Divide by scale factor to get logical pixels (important for macOS Retina)
                                         let logical_width = size.width as f32 / window.scale_factor() as f32;
-                                        state.queue_message(Message::WindowResized(logical_width, size, window.is_maximized()));
+                                        state.queue_message(Message::Pane(PaneMessage::WindowResized(logical_width, size, window.is_maximized())));
                                     } else {
                                         // Skip resizing and avoid configuring the surface
                                         *resized = false;
                                     }
                                 }
                                 WindowEvent::Moved(position) => {
-                                    state.queue_message(Message::PositionChanged(position, window.current_monitor()));
+                                    state.queue_message(Message::Pane(PaneMessage::PositionChanged(position, window.current_monitor())));
                                     *moved = true;
                                 }
                                 WindowEvent::CloseRequested => {
                                     window_state::save_window_state_to_disk(state.program(), &window);
+                                    state.program().viewed_images.save();
                                     #[cfg(target_os = "macos")]
                                     {
                                         // Clean up all active security-scoped access before shutdown
@@ -633,9 +1153,9 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
                                 }
                                 WindowEvent::CursorMoved { position, .. } => {
                                     if state.program().window_state == WindowState::FullScreen {
-                                        state.queue_message(Message::CursorOnTop(position.y < FULLSCREEN_TOP_ZONE_HEIGHT));
-                                        state.queue_message(Message::CursorOnFooter(
-                                            position.y > (window.inner_size().height as f64 - FULLSCREEN_BOTTOM_ZONE_HEIGHT)));
+                                        state.queue_message(Message::Pane(PaneMessage::CursorOnTop(position.y < FULLSCREEN_TOP_ZONE_HEIGHT)));
+                                        state.queue_message(Message::Pane(PaneMessage::CursorOnFooter(
+                                            position.y > (window.inner_size().height as f64 - FULLSCREEN_BOTTOM_ZONE_HEIGHT))));
                                     }
                                     *cursor_position = Some(position);
                                 }
@@ -662,10 +1182,10 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
                                         // On macOS, window.fullscreen().is_some() doesn't work with set_simple_fullscreen()
                                         // so we need to use the application's internal state
                                         let fullscreen = if state.program().window_state == WindowState::FullScreen {
-                                            state.queue_message(Message::ToggleFullScreen(false));
+                                            state.queue_message(Message::Pane(PaneMessage::ToggleFullScreen(false)));
                                             None
                                         } else {
-                                            state.queue_message(Message::ToggleFullScreen(true));
+                                            state.queue_message(Message::Pane(PaneMessage::ToggleFullScreen(true)));
                                             Some(winit::window::Fullscreen::Borderless(None))
                                         };
                                         use iced_winit::winit::platform::macos::WindowExtMacOS;
@@ -673,10 +1193,10 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
                                     }
                                     #[cfg(not(target_os = "macos"))] {
                                         let fullscreen = if window.fullscreen().is_some() {
-                                            state.queue_message(Message::ToggleFullScreen(false));
+                                            state.queue_message(Message::Pane(PaneMessage::ToggleFullScreen(false)));
                                             None
                                         } else {
-                                            state.queue_message(Message::ToggleFullScreen(true));
+                                            state.queue_message(Message::Pane(PaneMessage::ToggleFullScreen(true)));
                                             Some(winit::window::Fullscreen::Borderless(None))
                                         };
                                         window.set_fullscreen(fullscreen);
@@ -696,14 +1216,14 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
                                     // Handle Escape key to exit fullscreen on macOS
                                     #[cfg(target_os = "macos")] {
                                         if window.fullscreen().is_some() || state.program().window_state == WindowState::FullScreen {
-                                            state.queue_message(Message::ToggleFullScreen(false));
+                                            state.queue_message(Message::Pane(PaneMessage::ToggleFullScreen(false)));
                                             use iced_winit::winit::platform::macos::WindowExtMacOS;
                                             window.set_simple_fullscreen(false);
                                         }
                                     }
                                     #[cfg(not(target_os = "macos"))] {
                                         if window.fullscreen().is_some() {
-                                            state.queue_message(Message::ToggleFullScreen(false));
+                                            state.queue_message(Message::Pane(PaneMessage::ToggleFullScreen(false)));
                                             window.set_fullscreen(None);
                                         }
                                     }
@@ -835,8 +1355,8 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
                                 };
                             }
 
-                            // Render if needed
-                            if *redraw {
+                            // Render if needed, once the startup image can be shown
+                            if *redraw && !startup_image::holds_first_frame(state.program()) {
                                 *redraw = false;
 
                                 let frame_start = Instant::now();
@@ -876,6 +1396,7 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
                                             engine_guard.submit(queue, encoder);
                                         }
                                         let present_time = present_start.elapsed();
+                                        utils::frame_stats::record(utils::frame_stats::Stage::Present, present_time);
 
                                         // Submit the commands to the queue
                                         let submit_start = Instant::now();
@@ -922,15 +1443,25 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
                                             let new_val = !state.program().cursor_on_footer
                                                 && state.mouse_interaction() == mouse::Interaction::Pointer;
                                             if new_val != state.program().cursor_on_menu {
-                                                state.queue_message(Message::CursorOnMenu(new_val));
+                                                state.queue_message(Message::Pane(PaneMessage::CursorOnMenu(new_val)));
                                             }
                                         }
 
-                                        // Continue animation loop if spinner is active
-                                        if state.program().is_any_pane_loading() {
+                                        // Recompute the pane difference once a pan or zoom has been drawn
+                                        if channel_diff::needs_refresh(state.program()) {
+                                            state.queue_message(channel_diff::ChannelDiffMessage::Refresh.into());
+                                        }
+
+                                        // Continue animation loop if the spinner, the clipping stripes or an animated image are active
+                                        if state.program().is_any_pane_loading()
+                                            || widgets::shader::clipping_warning::is_enabled()
+                                            || animation::is_playing()
+                                        {
                                             window.request_redraw();
                                         }
 
+                                        utils::frame_stats::record(utils::frame_stats::Stage::Frame, frame_start.elapsed());
+
                                         if *debug {
                                             let total_frame_time = frame_start.elapsed();
                                             trace!("Total frame time: {:?}", total_frame_time);
@@ -1085,25 +1616,6 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
                 Self::Loading { proxy, event_sender, control_receiver, file_receiver, settings_path, replay_config } => {
                     info!("resumed()...");
 
-                    let custom_theme = Theme::custom_with_fn(
-                        "Custom Theme".to_string(),
-                        iced_winit::core::theme::Palette {
-                            primary: iced_winit::core::Color::from_rgba8(20, 148, 163, 1.0),
-                            text: iced_winit::core::Color::from_rgba8(224, 224, 224, 1.0),
-                            ..Theme::Dark.palette()
-                        },
-                        |palette| {
-                            // Generate the extended palette from the base palette
-                            let mut extended: iced_core::theme::palette::Extended = iced_core::theme::palette::Extended::generate(palette);
-
-                            // Customize specific parts of the extended palette
-                            extended.primary.weak.text = iced_winit::core::Color::from_rgba8(224, 224, 224, 1.0);
-
-                            // Return the modified extended palette
-                            extended
-                        }
-                    );
-
                     // On macOS, NSWindow.zoom() handles maximize instead of winit's set_maximized
                     #[cfg(target_os = "macos")]
                     let should_maximize = false;
@@ -1166,15 +1678,9 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
                     #[cfg(target_os = "macos")]
                     window_state::setup_macos_window(&window);
 
-                    let physical_size = window.inner_size();
-                    // Cap to wgpu texture limits
-                    let capped_width = physical_size.width.min(MAX_TEXTURE_SIZE);
-                    let capped_height = physical_size.height.min(MAX_TEXTURE_SIZE);
-                    let viewport = Viewport::with_physical_size(
-                        Size::new(capped_width, capped_height),
-                        window.scale_factor(),
-                    );
-                    let clipboard = Clipboard::connect(window.clone());
+                    // Shown until the first frame sets the real title
+                    window.set_title("ViewSkater - Loading...");
+
                     let backend = wgpu::util::backend_bits_from_env().unwrap_or_default();
 
                     let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
@@ -1185,224 +1691,27 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
                         .create_surface(window.clone())
                         .expect("Create window surface");
 
-                    let (format, adapter, device, queue, present_mode) =
-                        futures::futures::executor::block_on(async {
-                            let adapter =
-                                wgpu::util::initialize_adapter_from_env_or_default(
-                                    &instance,
-                                    Some(&surface),
-                                )
-                                .await
-                                .expect("Create adapter");
-
-                                let capabilities = surface.get_capabilities(&adapter);
-
-                                info!("GPU: {:?}", adapter.get_info().name);
-                                info!("Available present modes: {:?}", capabilities.present_modes);
-
-                                // Select non-blocking present mode to prevent frame.present()
-                                // from stalling the event loop on NVIDIA GPUs (strict FIFO queue).
-                                // Mailbox: non-blocking, replaces pending frame with latest (ideal)
-                                // Immediate: non-blocking, no VSync (fallback)
-                                // AutoNoVsync: auto-selects Mailbox or Immediate
-                                let present_mode = if capabilities.present_modes.contains(&wgpu::PresentMode::Mailbox) {
-                                    info!("Selected Mailbox present mode (non-blocking)");
-                                    wgpu::PresentMode::Mailbox
-                                } else if capabilities.present_modes.contains(&wgpu::PresentMode::Immediate) {
-                                    info!("Mailbox not available, selected Immediate present mode");
-                                    wgpu::PresentMode::Immediate
-                                } else {
-                                    info!("Selected AutoNoVsync present mode (fallback)");
-                                    wgpu::PresentMode::AutoNoVsync
-                                };
-
-                                let (device, queue) = adapter
-                                    .request_device(
-                                        &wgpu::DeviceDescriptor {
-                                            label: Some("Main Device"),
-                                            required_features: wgpu::Features::empty() | wgpu::Features::TEXTURE_COMPRESSION_BC,
-                                            required_limits: wgpu::Limits::default(),
-                                        },
-                                        None,
-                                    )
-                                    .await
-                                    .expect("Request device");
-
-                                (
-                                    capabilities
-                                        .formats
-                                        .iter()
-                                        .copied()
-                                        .find(wgpu::TextureFormat::is_srgb)
-                                        .or_else(|| {
-                                            capabilities.formats.first().copied()
-                                        })
-                                        .expect("Get preferred format"),
-                                    adapter,
-                                    device,
-                                    queue,
-                                    present_mode,
-                                )
-                        });
-
-                    surface.configure(
-                        &device,
-                        &wgpu::SurfaceConfiguration {
-                            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                            format,
-                            width: capped_width,
-                            height: capped_height,
-                            present_mode,
-                            alpha_mode: wgpu::CompositeAlphaMode::Auto,
-                            view_formats: vec![],
-                            desired_maximum_frame_latency: 2,
-                        },
-                    );
-
-                    // Create shared Arc instances of device and queue
-                    let device = Arc::new(device);
-                    let queue = Arc::new(queue);
-                    let backend = adapter.get_info().backend;
-
-                    // Initialize iced
-                    let mut debug_tool = Debug::new();
-
-                    let config = ImageConfig {
-                        atlas_size: CONFIG.atlas_size,
-                        compression_strategy: CompressionStrategy::Bc1,
-                    };
-                    let engine = Arc::new(Mutex::new(Engine::new(
-                        &adapter, &device, &queue, format, None, Some(config))));
-                    {
-                        let engine_guard = engine.lock().unwrap();
-                        engine_guard.create_image_cache(&device);
-                    }
-
-                    // Manually register fonts
-                    register_font_manually(include_bytes!("../assets/fonts/viewskater-fonts.ttf"));
-                    register_font_manually(include_bytes!("../assets/fonts/Iosevka-Regular-ascii.ttf"));
-                    register_font_manually(include_bytes!("../assets/fonts/Roboto-Regular.ttf"));
-
-                    // Create renderer with Rc<Mutex>
-                    let renderer = std::rc::Rc::new(Mutex::new(Renderer::new(
-                        &device,
-                        &engine.lock().unwrap(),
-                        Font::with_name("Roboto"),
-                        Pixels::from(16),
-                    )));
-
-                    // Create the renderer request channel
-                    let (renderer_request_sender, renderer_request_receiver) = mpsc::channel();
-
-                    // Pass a cloned Arc reference to DataViewer
-                    let mut shader_widget = DataViewer::new(
-                        Arc::clone(&device),
-                        Arc::clone(&queue),
-                        backend,
-                        renderer_request_sender,
-                        std::mem::replace(file_receiver, mpsc::channel().1),
-                        settings_path.as_deref(),
-                        std::mem::take(replay_config),
-                    );
-
-                    shader_widget.last_monitor = window.current_monitor();
-
-                    // Update state creation to lock renderer
-                    let mut renderer_guard = renderer.lock().unwrap();
-                    let mut state = program::State::new(
-                        shader_widget,
-                        viewport.logical_size(),
-                        &mut *renderer_guard,
-                        &mut debug_tool,
-                    );
-
-                    match CONFIG.window_state {
-                        WindowState::Maximized => {
-                            // On macOS, setup_macos_window() calls NSWindow.zoom() instead —
-                            // set_maximized() doesn't establish _savedFrame for unzoom
-                            #[cfg(not(target_os = "macos"))]
-                            window.set_maximized(true);
-                        },
-                        WindowState::FullScreen => {
-                            let fullscreen = Some(winit::window::Fullscreen::Borderless(None));
-                            state.queue_message(Message::ToggleFullScreen(true));
-                            #[cfg(target_os = "macos")] {
-                                use iced_winit::winit::platform::macos::WindowExtMacOS;
-                                window.set_simple_fullscreen(fullscreen.is_some());
-                            }
-                            #[cfg(not(target_os = "macos"))] {
-                                window.set_fullscreen(fullscreen);
-                            }
-                        },
-                        _ => {},
-                    }
-
-                    // Set control flow
-                    event_loop.set_control_flow(ControlFlow::Poll);
-
-                    let (p, worker) = iced_winit::Proxy::new(proxy.clone());
-                    let Ok(executor) = iced_futures::backend::native::tokio::Executor::new() else {
-                        panic!("could not create runtime")
-                    };
-                    executor.spawn(worker);
-                    let runtime = iced_futures::Runtime::new(executor, p);
-
-                    // Create a proper static waker
-                    let waker = {
-                        // Create a waker that does nothing
-                        struct NoopWaker;
-
-                        impl Wake for NoopWaker {
-                            fn wake(self: Arc<Self>) {}
-                            fn wake_by_ref(self: &Arc<Self>) {}
-                        }
-
-                        // Create a waker and leak it to make it 'static
-                        let waker_arc = Arc::new(NoopWaker);
-                        let waker = Waker::from(waker_arc);
-                        Box::leak(Box::new(waker))
-                    };
-
-                    let context = task::Context::from_waker(waker);
-
-                    // Create a new Ready state with the event_sender and control_receiver
-                    // Note: We don't clone the receiver as it's not clonable
-                    let event_sender = event_sender.clone();
-
-                    // Move the control_receiver into the Ready state
-                    // We need to take ownership of it from the Loading state
-                    let control_receiver = std::mem::replace(control_receiver, std_mpsc::channel().1);
+                    // Adapter and device setup can take hundreds of milliseconds on some
+                    // drivers, so it runs on a worker thread while the window is on screen
+                    let (gpu_sender, gpu_receiver) = std_mpsc::channel();
+                    std::thread::spawn(move || {
+                        let _ = gpu_sender.send(setup_gpu(instance, surface));
+                    });
+                    event_loop.set_control_flow(ControlFlow::WaitUntil(Instant::now() + GPU_SETUP_POLL_INTERVAL));
 
-                    *self = Self::Ready {
+                    *self = Self::Initializing {
                         window,
-                        device,
-                        queue,
-                        surface,
-                        format,
-                        present_mode,
-                        engine,
-                        renderer: renderer.clone(),
-                        state,
-                        cursor_position: None,
-                        modifiers: ModifiersState::default(),
-                        clipboard,
-                        runtime,
-                        viewport,
-                        resized: false,
-                        moved: false,
-                        redraw: true,
-                        last_title: String::new(),
-                        debug: false,
-                        debug_tool,
-                        _event_sender: event_sender,
-                        control_receiver,
-                        _context: context,
-                        custom_theme,
-                        renderer_request_receiver,
+                        gpu_receiver,
+                        proxy: proxy.clone(),
+                        event_sender: event_sender.clone(),
+                        control_receiver: std::mem::replace(control_receiver, std_mpsc::channel().1),
+                        file_receiver: std::mem::replace(file_receiver, mpsc::channel().1),
+                        settings_path: settings_path.take(),
+                        replay_config: replay_config.take(),
                     };
                 }
-                Self::Ready { .. } => {
-                    // Already initialized
+                Self::Initializing { .. } | Self::Ready { .. } => {
+                    // Already initialized or in progress
                 }
             }
         }
@@ -1464,7 +1773,10 @@ pub fn main() -> Result<(), winit::error::EventLoopError> {
         replay_config,
     };
 
-    event_loop.run_app(&mut runner)
+    let result = event_loop.run_app(&mut runner);
+    utils::frame_stats::write_report();
+    stdin_input::cleanup();
+    result
 }
 
 // Called in render method
@@ -28,13 +28,23 @@ use iced_wgpu::Renderer;
 use iced_wgpu::engine::CompressionStrategy;
 
 use iced_aw::menu::{self, Item, Menu};
-use iced_aw::{menu_bar, menu_items};
+use iced_aw::menu_items;
 use iced_aw::MenuBar;
 use iced_aw::style::{menu_bar::primary, Status};
 
-use crate::{app::Message, DataViewer};
+use crate::{app::{Message, IoMessage, OverlayMessage, PaneMessage}, DataViewer};
 use crate::widgets::toggler;
 use crate::cache::img_cache::CacheStrategy;
+use crate::widgets::shader::stereo_scene::StereoMode;
+use crate::widgets::shader::user_shader::UserShaderMessage;
+use crate::widgets::lut_panel::LutMessage;
+use crate::display_profile::DisplayProfileMessage;
+use crate::soft_proof::SoftProofMessage;
+use crate::white_balance::{WhiteBalanceMessage, WhiteBalanceMode};
+use crate::noise_analysis::NoiseMessage;
+use crate::profiles::ProfileMessage;
+use crate::menu_layout::{self, MenuLayoutMessage};
+use crate::settings::MenuBarMode;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PaneLayout {
@@ -164,6 +174,22 @@ fn labeled_button_maybe<'a>(
     .width(Length::Fill)
 }
 
+/// Like `labeled_button_maybe`, for labels built at runtime
+fn labeled_button_owned<'a>(
+    label: String,
+    text_size: u16,
+    msg: Option<Message>,
+) -> button::Button<'a, Message, WinitTheme, Renderer> {
+    button(
+        text(label)
+            .size(text_size)
+            .font(Font::with_name("Roboto"))
+    )
+    .style(labeled_style)
+    .on_press_maybe(msg)
+    .width(Length::Fill)
+}
+
 #[allow(dead_code)]
 fn nothing_button<'a>(label: &'a str, text_size: u16) -> button::Button<'a, Message, WinitTheme, Renderer> {
     button(
@@ -211,12 +237,12 @@ pub fn menu_3<'a>(app: &DataViewer) -> Menu<'a, Message, WinitTheme, Renderer> {
         (labeled_button(
             single_pane_text,
             MENU_ITEM_FONT_SIZE,
-            Message::TogglePaneLayout(PaneLayout::SinglePane)
+            Message::Pane(PaneMessage::TogglePaneLayout(PaneLayout::SinglePane))
         ))
         (labeled_button(
             dual_pane_text,
             MENU_ITEM_FONT_SIZE,
-            Message::TogglePaneLayout(PaneLayout::DualPane)
+            Message::Pane(PaneMessage::TogglePaneLayout(PaneLayout::DualPane))
         ))
     ))
     .max_width(180.0)
@@ -227,7 +253,7 @@ pub fn menu_3<'a>(app: &DataViewer) -> Menu<'a, Message, WinitTheme, Renderer> {
             toggler::Toggler::new(
                 Some("  Toggle Slider (Space)".into()),
                 app.is_slider_dual,
-                Message::ToggleSliderType,
+                |enabled| Message::Pane(PaneMessage::ToggleSliderType(enabled)),
             ).width(Length::Fill)
         ).style(|_theme: &WinitTheme| container::Style {
             text_color: Some(iced_core::Color::from_rgb(0.878, 0.878, 0.878)),
@@ -237,7 +263,37 @@ pub fn menu_3<'a>(app: &DataViewer) -> Menu<'a, Message, WinitTheme, Renderer> {
             toggler::Toggler::new(
                 Some("  Toggle Footer (Tab)".into()),
                 app.show_footer,
-                Message::ToggleFooter,
+                |enabled| Message::Pane(PaneMessage::ToggleFooter(enabled)),
+            ).width(Length::Fill)
+        ).style(|_theme: &WinitTheme| container::Style {
+            text_color: Some(iced_core::Color::from_rgb(0.878, 0.878, 0.878)),
+            ..container::Style::default()
+        }))
+        (container(
+            toggler::Toggler::new(
+                Some("  Toggle Sidebar (I)".into()),
+                app.sidebar.is_visible(),
+                |visible| crate::widgets::sidebar::SidebarMessage::SetVisible(visible).into(),
+            ).width(Length::Fill)
+        ).style(|_theme: &WinitTheme| container::Style {
+            text_color: Some(iced_core::Color::from_rgb(0.878, 0.878, 0.878)),
+            ..container::Style::default()
+        }))
+        (container(
+            toggler::Toggler::new(
+                Some("  Timeline by Date".into()),
+                app.timeline.visible,
+                |visible| crate::widgets::timeline_strip::TimelineMessage::SetVisible(visible).into(),
+            ).width(Length::Fill)
+        ).style(|_theme: &WinitTheme| container::Style {
+            text_color: Some(iced_core::Color::from_rgb(0.878, 0.878, 0.878)),
+            ..container::Style::default()
+        }))
+        (container(
+            toggler::Toggler::new(
+                Some("  Burst Stacks".into()),
+                app.bursts.enabled,
+                |enabled| crate::burst_stacks::BurstMessage::SetEnabled(enabled).into(),
             ).width(Length::Fill)
         ).style(|_theme: &WinitTheme| container::Style {
             text_color: Some(iced_core::Color::from_rgb(0.878, 0.878, 0.878)),
@@ -247,7 +303,7 @@ pub fn menu_3<'a>(app: &DataViewer) -> Menu<'a, Message, WinitTheme, Renderer> {
             toggler::Toggler::new(
                 Some("  Horizontal Split (H)".into()),
                 app.is_horizontal_split,
-                Message::ToggleSplitOrientation,
+                |enabled| Message::Pane(PaneMessage::ToggleSplitOrientation(enabled)),
             ).width(Length::Fill)
         ).style(|_theme: &WinitTheme| container::Style {
             text_color: Some(iced_core::Color::from_rgb(0.878, 0.878, 0.878)),
@@ -257,7 +313,7 @@ pub fn menu_3<'a>(app: &DataViewer) -> Menu<'a, Message, WinitTheme, Renderer> {
             toggler::Toggler::new(
                 Some("  Toggle FPS Display".into()),
                 app.show_fps,
-                Message::ToggleFpsDisplay,
+                |enabled| Message::Pane(PaneMessage::ToggleFpsDisplay(enabled)),
             ).width(Length::Fill)
         ).style(|_theme: &WinitTheme| container::Style {
             text_color: Some(iced_core::Color::from_rgb(0.878, 0.878, 0.878)),
@@ -267,7 +323,17 @@ pub fn menu_3<'a>(app: &DataViewer) -> Menu<'a, Message, WinitTheme, Renderer> {
             toggler::Toggler::new(
                 Some("  Sync Zoom/Pan".into()),
                 app.synced_zoom,
-                Message::ToggleSyncedZoom,
+                |enabled| Message::Pane(PaneMessage::ToggleSyncedZoom(enabled)),
+            ).width(Length::Fill)
+        ).style(|_theme: &WinitTheme| container::Style {
+            text_color: Some(iced_core::Color::from_rgb(0.878, 0.878, 0.878)),
+            ..container::Style::default()
+        }))
+        (container(
+            toggler::Toggler::new(
+                Some("  Blend Compare (Onion Skin)".into()),
+                app.blend_compare,
+                |enabled| Message::Pane(PaneMessage::ToggleBlendCompare(enabled)),
             ).width(Length::Fill)
         ).style(|_theme: &WinitTheme| container::Style {
             text_color: Some(iced_core::Color::from_rgb(0.878, 0.878, 0.878)),
@@ -277,7 +343,47 @@ pub fn menu_3<'a>(app: &DataViewer) -> Menu<'a, Message, WinitTheme, Renderer> {
             toggler::Toggler::new(
                 Some("  Toggle Mouse Wheel Zoom".into()),
                 app.mouse_wheel_zoom,
-                Message::ToggleMouseWheelZoom,
+                |enabled| Message::Pane(PaneMessage::ToggleMouseWheelZoom(enabled)),
+            ).width(Length::Fill)
+        ).style(|_theme: &WinitTheme| container::Style {
+            text_color: Some(iced_core::Color::from_rgb(0.878, 0.878, 0.878)),
+            ..container::Style::default()
+        }))
+        (container(
+            toggler::Toggler::new(
+                Some("  Lock Zoom Region (L)".into()),
+                crate::zoom_lock::is_active(),
+                |enabled| Message::Pane(PaneMessage::ToggleZoomLock(enabled)),
+            ).width(Length::Fill)
+        ).style(|_theme: &WinitTheme| container::Style {
+            text_color: Some(iced_core::Color::from_rgb(0.878, 0.878, 0.878)),
+            ..container::Style::default()
+        }))
+        (container(
+            toggler::Toggler::new(
+                Some("  Clipping Warning (Z)".into()),
+                crate::widgets::shader::clipping_warning::is_enabled(),
+                |enabled| Message::Pane(PaneMessage::ToggleClippingWarning(enabled)),
+            ).width(Length::Fill)
+        ).style(|_theme: &WinitTheme| container::Style {
+            text_color: Some(iced_core::Color::from_rgb(0.878, 0.878, 0.878)),
+            ..container::Style::default()
+        }))
+        (container(
+            toggler::Toggler::new(
+                Some("  Pair RAW+JPEG".into()),
+                crate::raw_pairs::is_enabled(),
+                |enabled| Message::Pane(PaneMessage::ToggleRawPairing(enabled)),
+            ).width(Length::Fill)
+        ).style(|_theme: &WinitTheme| container::Style {
+            text_color: Some(iced_core::Color::from_rgb(0.878, 0.878, 0.878)),
+            ..container::Style::default()
+        }))
+        (container(
+            toggler::Toggler::new(
+                Some("  Pane Difference in Footer".into()),
+                crate::channel_diff::is_enabled(),
+                |enabled| crate::channel_diff::ChannelDiffMessage::SetEnabled(enabled).into(),
             ).width(Length::Fill)
         ).style(|_theme: &WinitTheme| container::Style {
             text_color: Some(iced_core::Color::from_rgb(0.878, 0.878, 0.878)),
@@ -291,16 +397,20 @@ pub fn menu_3<'a>(app: &DataViewer) -> Menu<'a, Message, WinitTheme, Renderer> {
     let cpu_cache_text = if app.cache_strategy == CacheStrategy::Cpu { "[x] CPU cache" } else { "[  ] CPU cache" };
     let gpu_cache_text = if app.cache_strategy == CacheStrategy::Gpu { "[x] GPU cache" } else { "[  ] GPU cache" };
 
+    // The choice is kept for the kind of source shown, see cache::source_strategy
+    let source_text = format!("For {}", app.active_source.label());
+
     let cache_type_submenu = Menu::new(menu_items!(
+        (labeled_button_owned(source_text.clone(), MENU_ITEM_FONT_SIZE, None))
         (labeled_button(
             cpu_cache_text,
             MENU_ITEM_FONT_SIZE,
-            Message::SetCacheStrategy(CacheStrategy::Cpu)
+            Message::Pane(PaneMessage::SetCacheStrategy(CacheStrategy::Cpu))
         ))
         (labeled_button(
             gpu_cache_text,
             MENU_ITEM_FONT_SIZE,
-            Message::SetCacheStrategy(CacheStrategy::Gpu)
+            Message::Pane(PaneMessage::SetCacheStrategy(CacheStrategy::Gpu))
         ))
     ))
     .max_width(180.0)
@@ -311,26 +421,252 @@ pub fn menu_3<'a>(app: &DataViewer) -> Menu<'a, Message, WinitTheme, Renderer> {
     let bc1_compression_text = if app.compression_strategy == CompressionStrategy::Bc1 { "[x] BC1 compression" } else { "[  ] BC1 compression" };
 
     let compression_submenu = Menu::new(menu_items!(
+        (labeled_button_owned(source_text, MENU_ITEM_FONT_SIZE, None))
         (labeled_button(
             no_compression_text,
             MENU_ITEM_FONT_SIZE,
-            Message::SetCompressionStrategy(CompressionStrategy::None)
+            Message::Pane(PaneMessage::SetCompressionStrategy(CompressionStrategy::None))
         ))
         (labeled_button(
             bc1_compression_text,
             MENU_ITEM_FONT_SIZE,
-            Message::SetCompressionStrategy(CompressionStrategy::Bc1)
+            Message::Pane(PaneMessage::SetCompressionStrategy(CompressionStrategy::Bc1))
         ))
     ))
     .max_width(180.0)
     .spacing(0.0);
 
-    Menu::new(menu_items!(
+    let stereo_items: Vec<Item<'a, Message, WinitTheme, Renderer>> = StereoMode::ALL
+        .iter()
+        .map(|mode| {
+            let checked = if app.stereo_mode == *mode { "[x]" } else { "[  ]" };
+            Item::new(labeled_button_owned(
+                format!("{} {}", checked, mode.label()),
+                MENU_ITEM_FONT_SIZE,
+                Some(Message::Pane(PaneMessage::SetStereoMode(*mode))),
+            ))
+        })
+        .collect();
+    let stereo_submenu = Menu::new(stereo_items)
+        .max_width(180.0)
+        .spacing(0.0);
+
+    let user_shader = crate::widgets::shader::user_shader::current_path();
+    let user_shader_text = match user_shader.as_deref().and_then(|path| path.file_name()) {
+        Some(name) => format!("Active: {}", name.to_string_lossy()),
+        None => "No user shader".to_string(),
+    };
+    let shader_submenu = Menu::new(vec![
+        Item::new(labeled_button_owned(user_shader_text, MENU_ITEM_FONT_SIZE, None)),
+        Item::new(labeled_button("Load User Shader...", MENU_ITEM_FONT_SIZE, UserShaderMessage::Open.into())),
+        Item::new(labeled_button_maybe(
+            "Clear User Shader",
+            MENU_ITEM_FONT_SIZE,
+            user_shader.map(|_| UserShaderMessage::Clear.into())
+        )),
+    ])
+    .max_width(200.0)
+    .spacing(0.0);
+
+    let active_lut = crate::widgets::lut_panel::active_path();
+    let mut lut_items: Vec<Item<'a, Message, WinitTheme, Renderer>> = crate::widgets::lut_panel::available_luts()
+        .into_iter()
+        .map(|path| {
+            let checked = if active_lut.as_ref() == Some(&path) { "[x]" } else { "[  ]" };
+            let name = path.file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            Item::new(labeled_button_owned(
+                format!("{} {}", checked, name),
+                MENU_ITEM_FONT_SIZE,
+                Some(LutMessage::Load(path).into()),
+            ))
+        })
+        .collect();
+    lut_items.push(Item::new(labeled_button("Load LUT File...", MENU_ITEM_FONT_SIZE, LutMessage::Open.into())));
+    lut_items.push(Item::new(labeled_button_maybe(
+        "Clear LUT",
+        MENU_ITEM_FONT_SIZE,
+        active_lut.map(|_| LutMessage::Clear.into())
+    )));
+    let lut_submenu = Menu::new(lut_items)
+        .max_width(200.0)
+        .spacing(0.0);
+
+    let display_profile = crate::display_profile::active_path();
+    let display_profile_text = match display_profile.as_deref().and_then(|path| path.file_name()) {
+        Some(name) => format!("Profile: {}", name.to_string_lossy()),
+        None => "No display profile".to_string(),
+    };
+    let display_profile_submenu = Menu::new(vec![
+        Item::new(labeled_button_owned(display_profile_text, MENU_ITEM_FONT_SIZE, None)),
+        Item::new(labeled_button("Assign ICC Profile to This Monitor...", MENU_ITEM_FONT_SIZE, DisplayProfileMessage::Assign.into())),
+        Item::new(labeled_button_maybe(
+            "Remove Profile for This Monitor",
+            MENU_ITEM_FONT_SIZE,
+            display_profile.map(|_| DisplayProfileMessage::Remove.into())
+        )),
+    ])
+    .max_width(260.0)
+    .spacing(0.0);
+
+    let proof_profile = crate::soft_proof::active_path();
+    let (proof_colors, gamut_warning) = crate::soft_proof::modes();
+    let mut proof_items: Vec<Item<'a, Message, WinitTheme, Renderer>> = crate::soft_proof::available_profiles()
+        .into_iter()
+        .map(|path| {
+            let checked = if proof_profile.as_ref() == Some(&path) { "[x]" } else { "[  ]" };
+            let name = path.file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            Item::new(labeled_button_owned(
+                format!("{} {}", checked, name),
+                MENU_ITEM_FONT_SIZE,
+                Some(SoftProofMessage::Load(path).into()),
+            ))
+        })
+        .collect();
+    proof_items.push(Item::new(labeled_button("Open Proof Profile...", MENU_ITEM_FONT_SIZE, SoftProofMessage::Open.into())));
+    proof_items.push(Item::new(labeled_button_owned(
+        format!("{} Proof Colors (Ctrl+Y)", if proof_colors { "[x]" } else { "[  ]" }),
+        MENU_ITEM_FONT_SIZE,
+        proof_profile.as_ref().map(|_| SoftProofMessage::ToggleProofColors.into()),
+    )));
+    proof_items.push(Item::new(labeled_button_owned(
+        format!("{} Gamut Warning (Ctrl+Shift+Y)", if gamut_warning { "[x]" } else { "[  ]" }),
+        MENU_ITEM_FONT_SIZE,
+        proof_profile.as_ref().map(|_| SoftProofMessage::ToggleGamutWarning.into()),
+    )));
+    proof_items.push(Item::new(labeled_button_maybe(
+        "Clear Proof Profile",
+        MENU_ITEM_FONT_SIZE,
+        proof_profile.map(|_| SoftProofMessage::Clear.into())
+    )));
+    let proof_submenu = Menu::new(proof_items)
+        .max_width(260.0)
+        .spacing(0.0);
+
+    let white_balance_mode = crate::white_balance::mode();
+    let mut white_balance_items: Vec<Item<'a, Message, WinitTheme, Renderer>> = [WhiteBalanceMode::GrayWorld, WhiteBalanceMode::WhitePatch]
+        .into_iter()
+        .map(|mode| {
+            let checked = if white_balance_mode == Some(mode) { "[x]" } else { "[  ]" };
+            Item::new(labeled_button_owned(
+                format!("{} {}", checked, mode.label()),
+                MENU_ITEM_FONT_SIZE,
+                Some(WhiteBalanceMessage::SetMode(Some(mode)).into()),
+            ))
+        })
+        .collect();
+    white_balance_items.push(Item::new(labeled_button_maybe(
+        "Turn Off Preview",
+        MENU_ITEM_FONT_SIZE,
+        white_balance_mode.map(|_| WhiteBalanceMessage::SetMode(None).into())
+    )));
+    let white_balance_submenu = Menu::new(white_balance_items)
+        .max_width(200.0)
+        .spacing(0.0);
+
+    let active_profile = crate::profiles::active_name();
+    let mut profile_items: Vec<Item<'a, Message, WinitTheme, Renderer>> = vec![
+        Item::new(labeled_button_owned(
+            format!("{} Default", if active_profile.is_none() { "[x]" } else { "[  ]" }),
+            MENU_ITEM_FONT_SIZE,
+            Some(ProfileMessage::Clear.into()),
+        )),
+    ];
+    profile_items.extend(crate::profiles::available().into_iter().map(|name| {
+        let checked = if active_profile.as_ref() == Some(&name) { "[x]" } else { "[  ]" };
+        Item::new(labeled_button_owned(
+            format!("{} {}", checked, name),
+            MENU_ITEM_FONT_SIZE,
+            Some(ProfileMessage::Select(name).into()),
+        ))
+    }));
+    let profile_submenu = Menu::new(profile_items)
+        .max_width(200.0)
+        .spacing(0.0);
+
+    let viewed_pane = app.active_pane_index();
+    let viewed_progress = app.panes.get(viewed_pane)
+        .and_then(|pane| app.viewed_images.progress(viewed_pane, pane));
+    let viewed_submenu = Menu::new(vec![
+        Item::new(labeled_button_owned(
+            match viewed_progress {
+                Some((viewed, total)) => format!("{} of {} viewed", viewed, total),
+                None => "No folder open".to_string(),
+            },
+            MENU_ITEM_FONT_SIZE,
+            None,
+        )),
+        Item::new(labeled_button_maybe(
+            "Jump to First Unviewed (J)",
+            MENU_ITEM_FONT_SIZE,
+            viewed_progress.filter(|(viewed, total)| viewed < total)
+                .map(|_| crate::viewed_images::ViewedMessage::JumpToFirstUnviewed.into())
+        )),
+        Item::new(labeled_button_maybe(
+            "Reset Viewed in This Folder",
+            MENU_ITEM_FONT_SIZE,
+            viewed_progress.map(|_| crate::viewed_images::ViewedMessage::ClearFolder.into())
+        )),
+    ])
+    .max_width(220.0)
+    .spacing(0.0);
+
+    let layout = &app.menu_layout;
+    let mode_item = |label: &str, mode: MenuBarMode| Item::new(labeled_button_owned(
+        format!("{} {}", if layout.mode == mode { "[x]" } else { "[  ]" }, label),
+        MENU_ITEM_FONT_SIZE,
+        Some(MenuLayoutMessage::SetMode(mode).into()),
+    ));
+    let mut menu_bar_items = vec![
+        mode_item("Full", MenuBarMode::Full),
+        mode_item("Compact", MenuBarMode::Compact),
+        mode_item("Hidden (tap Alt)", MenuBarMode::Hidden),
+    ];
+    // Checked entries are shown
+    menu_bar_items.extend(menu_layout::hideable_menus().into_iter().chain(menu_layout::HIDEABLE_ENTRIES).map(|name| {
+        Item::new(labeled_button_owned(
+            format!("{} {}", if layout.is_hidden(name) { "[  ]" } else { "[x]" }, name),
+            MENU_ITEM_FONT_SIZE,
+            Some(MenuLayoutMessage::ToggleHidden(name.to_string()).into()),
+        ))
+    }));
+    let menu_bar_submenu = Menu::new(menu_bar_items)
+        .max_width(180.0)
+        .spacing(0.0);
+
+    // Entries the menu bar layout can hide
+    let shown = |entries: Vec<(&'static str, Menu<'a, Message, WinitTheme, Renderer>)>| entries.into_iter()
+        .filter(|(name, _)| !layout.is_hidden(name))
+        .map(|(name, submenu)| Item::with_menu(submenu_button(name, MENU_ITEM_FONT_SIZE), submenu))
+        .collect::<Vec<_>>();
+
+    let mut items = menu_items!(
         (submenu_button("Pane Layout", MENU_ITEM_FONT_SIZE), pane_layout_submenu)
         (submenu_button("Controls", MENU_ITEM_FONT_SIZE), controls_menu)
         (submenu_button("Cache Type", MENU_ITEM_FONT_SIZE), cache_type_submenu)
         (submenu_button("Compression", MENU_ITEM_FONT_SIZE), compression_submenu)
-    ))
+    );
+    items.extend(shown(vec![
+        ("Stereo", stereo_submenu),
+        ("Shader", shader_submenu),
+        ("LUT", lut_submenu),
+        ("Display Profile", display_profile_submenu),
+        ("Soft Proof", proof_submenu),
+        ("White Balance", white_balance_submenu),
+    ]));
+    items.extend(menu_items!(
+        (labeled_button("Measure Noise (N)", MENU_ITEM_FONT_SIZE, NoiseMessage::Measure.into()))
+        (labeled_button("Switch RAW/JPEG (R)", MENU_ITEM_FONT_SIZE, Message::Io(IoMessage::SwapRawPair)))
+        (labeled_button("Suggest Rotation (T)", MENU_ITEM_FONT_SIZE, crate::auto_rotate::RotateMessage::Suggest.into()))
+    ));
+    items.extend(shown(vec![
+        ("Profiles", profile_submenu),
+        ("Viewed Images", viewed_submenu),
+    ]));
+    items.extend(menu_items!(
+        (submenu_button("Menu Bar", MENU_ITEM_FONT_SIZE), menu_bar_submenu)
+    ));
+
+    Menu::new(items)
     .max_width(120.0)
     .spacing(0.0)
     .offset(5.0)
@@ -370,12 +706,12 @@ pub fn menu_1<'a>(app: &DataViewer) -> Menu<'a, Message, WinitTheme, Renderer> {
         (labeled_button(
             "Pane 1 (Alt+1)",
             MENU_ITEM_FONT_SIZE,
-            Message::OpenFolder(0)
+            Message::Io(IoMessage::OpenFolder(0))
         ))
         (labeled_button(
             "Pane 2 (Alt+2)",
             MENU_ITEM_FONT_SIZE,
-            Message::OpenFolder(1)
+            Message::Io(IoMessage::OpenFolder(1))
         ))
     ))
     .max_width(180.0)
@@ -386,18 +722,61 @@ pub fn menu_1<'a>(app: &DataViewer) -> Menu<'a, Message, WinitTheme, Renderer> {
         (labeled_button(
             "Pane 1 (Shift+Alt+1)",
             MENU_ITEM_FONT_SIZE,
-            Message::OpenFile(0)
+            Message::Io(IoMessage::OpenFile(0))
         ))
         (labeled_button(
             "Pane 2 (Shift+Alt+2)",
             MENU_ITEM_FONT_SIZE,
-            Message::OpenFile(1)
+            Message::Io(IoMessage::OpenFile(1))
         ))
     ))
     .max_width(180.0)
     .spacing(0.0);
 
-    menu_tpl_2(menu_items!((
+    // Reading ahead only applies to plain folders; archives have their own preloading
+    let preload_item = match crate::cache::warm_cache::current() {
+        Some((_, bytes)) if !app.folder_preload.is_running() => labeled_button_owned(
+            format!("Release Preloaded Folder ({} MB)", bytes / 1_048_576),
+            MENU_ITEM_FONT_SIZE,
+            Some(crate::folder_preload::PreloadMessage::Release.into()),
+        ),
+        _ => labeled_button_owned(
+            "Preload Folder into Memory".to_string(),
+            MENU_ITEM_FONT_SIZE,
+            (!app.folder_preload.is_running() && app.panes.iter().any(|pane| pane.dir_loaded && !pane.has_compressed_file))
+                .then(|| crate::folder_preload::PreloadMessage::Start.into()),
+        ),
+    };
+
+    // Hashes of the active pane's file, copied to the clipboard
+    let has_file = app.current_file().is_some();
+    let copy_hash_submenu = Menu::new(crate::file_hashes::HashKind::ALL.iter()
+        .map(|&kind| {
+            let label = match kind {
+                crate::file_hashes::HashKind::Sha256 => format!("{} (#)", kind.label()),
+                _ => kind.label().to_string(),
+            };
+            Item::new(labeled_button_owned(
+                label,
+                MENU_ITEM_FONT_SIZE,
+                has_file.then(|| crate::file_hashes::HashMessage::Copy(kind).into()),
+            ))
+        })
+        .collect())
+        .max_width(150.0)
+        .spacing(0.0)
+        .offset(5.0);
+
+    // Only plain files can be trashed, not archive entries
+    let can_delete = !app.file_delete.is_running() && app.current_file().is_some();
+
+    let favorite_text = match app.plugin_image_path() {
+        Some(path) if app.favorites.get(&path).is_some() => "Remove from Favorites (*)",
+        _ => "Add to Favorites (*)",
+    };
+
+    #[allow(unused_mut)]
+    let mut items = menu_items!((
         submenu_button(open_folder_text, MENU_ITEM_FONT_SIZE),
         open_folder_submenu
     )(
@@ -406,72 +785,411 @@ pub fn menu_1<'a>(app: &DataViewer) -> Menu<'a, Message, WinitTheme, Renderer> {
     )(labeled_button_maybe(
         save_text,
         MENU_ITEM_FONT_SIZE,
-        is_image_loaded.then(|| Message::RequestSaveImage)
+        is_image_loaded.then(|| Message::Io(IoMessage::RequestSaveImage))
     )
 
-)(labeled_button(
+)(labeled_button_maybe(
+        "Export Animation...",
+        MENU_ITEM_FONT_SIZE,
+        app.panes.iter().any(|pane| pane.dir_loaded)
+            .then(|| crate::sequence_export_modal::SequenceExportMessage::Show.into())
+    ))(labeled_button_maybe(
+        "Edit Metadata...",
+        MENU_ITEM_FONT_SIZE,
+        app.panes.iter().any(|pane| pane.dir_loaded)
+            .then(|| crate::metadata_editor::MetadataEditorMessage::Show.into())
+    ))(labeled_button_maybe(
+        "Export Comparison Report...",
+        MENU_ITEM_FONT_SIZE,
+        (crate::comparison_report::is_available(app) && !app.comparison_report.is_running())
+            .then(|| crate::comparison_report::ReportMessage::Export.into())
+    ))(labeled_button_maybe(
+        "Load Metrics CSV...",
+        MENU_ITEM_FONT_SIZE,
+        app.panes.iter().any(|pane| pane.dir_loaded)
+            .then(|| crate::widgets::metrics_panel::MetricsMessage::Load.into())
+    ))(labeled_button_maybe(
+        favorite_text,
+        MENU_ITEM_FONT_SIZE,
+        app.plugin_image_path().map(|_| crate::favorites::FavoritesMessage::ToggleCurrent.into())
+    ))(labeled_button_owned(
+        format!("Open Favorites ({})", app.favorites.len()),
+        MENU_ITEM_FONT_SIZE,
+        (!app.favorites.is_empty()).then(|| crate::favorites::FavoritesMessage::Open.into())
+    ))(labeled_button(
+        "Open Session...",
+        MENU_ITEM_FONT_SIZE,
+        crate::session::SessionMessage::Open.into()
+    ))(labeled_button_maybe(
+        "Save Session...",
+        MENU_ITEM_FONT_SIZE,
+        app.panes.iter().any(|pane| pane.dir_loaded)
+            .then(|| crate::session::SessionMessage::Save.into())
+    ))(
+        submenu_button("Copy Hash", MENU_ITEM_FONT_SIZE),
+        copy_hash_submenu
+    )(preload_item)(labeled_button_maybe(
+        "Move to Trash (Del)",
+        MENU_ITEM_FONT_SIZE,
+        can_delete.then(|| crate::file_delete::DeleteMessage::DeleteCurrent.into())
+    ))(labeled_button(
         close_text,
         MENU_ITEM_FONT_SIZE,
-        Message::Close
+        Message::Io(IoMessage::Close)
     ))(labeled_button(
         quit_text,
         MENU_ITEM_FONT_SIZE,
         Message::Quit
-    ))))
+    )));
+    // Trashing the excluded images sits right under "Move to Trash", above Close and Quit
+    #[cfg(feature = "selection")]
+    items.insert(items.len() - 2, Item::new(labeled_button_maybe(
+        "Move Excluded to Trash",
+        MENU_ITEM_FONT_SIZE,
+        (!app.file_delete.is_running() && crate::file_delete::has_excluded(app))
+            .then(|| crate::file_delete::DeleteMessage::DeleteExcluded.into())
+    )));
+    menu_tpl_2(items)
 }
 
 pub fn menu_help<'a>(_app: &DataViewer) -> Menu<'a, Message, WinitTheme, Renderer> {
     let menu_tpl_2 = |items| Menu::new(items).max_width(200.0).offset(5.0);
-    menu_tpl_2(
-        menu_items!(
-            (labeled_button("Settings...", MENU_ITEM_FONT_SIZE, Message::ShowOptions))
-            (labeled_button("About", MENU_ITEM_FONT_SIZE, Message::ShowAbout))
-            (labeled_button("Show logs", MENU_ITEM_FONT_SIZE, Message::ShowLogs))
-            (labeled_button("Export debug logs", MENU_ITEM_FONT_SIZE, Message::ExportDebugLogs))
-            (labeled_button("Export all logs", MENU_ITEM_FONT_SIZE, Message::ExportAllLogs))
-        )
-    )
+    #[allow(unused_mut)]
+    let mut items = menu_items!(
+        (labeled_button("Settings...", MENU_ITEM_FONT_SIZE, Message::Overlay(OverlayMessage::ShowOptions)))
+        (labeled_button("About", MENU_ITEM_FONT_SIZE, Message::Overlay(OverlayMessage::ShowAbout)))
+        (labeled_button("Usage Statistics", MENU_ITEM_FONT_SIZE, crate::usage_stats::UsageStatsMessage::Show.into()))
+        (labeled_button("Show logs", MENU_ITEM_FONT_SIZE, Message::Overlay(OverlayMessage::ShowLogs)))
+        (labeled_button("Export debug logs", MENU_ITEM_FONT_SIZE, Message::Overlay(OverlayMessage::ExportDebugLogs)))
+        (labeled_button("Export all logs", MENU_ITEM_FONT_SIZE, Message::Overlay(OverlayMessage::ExportAllLogs)))
+    );
+    #[cfg(feature = "update-check")]
+    items.push(Item::new(labeled_button(
+        "Check for Updates",
+        MENU_ITEM_FONT_SIZE,
+        crate::update_check::UpdateMessage::CheckNow.into(),
+    )));
+    menu_tpl_2(items)
 }
 
 
-pub fn build_menu(app: &DataViewer) -> MenuBar<'_, Message, WinitTheme, Renderer> {
-    menu_bar!(
-        (
-            container(
-                text("File").size(MENU_FONT_SIZE).font(Font::with_name("Roboto"))
-            )
-            .style(|_theme: &WinitTheme| container::Style {
-                text_color: Some(iced_core::Color::from_rgb(0.878, 0.878, 0.878)),
-                ..container::Style::default()
-            })
-            .padding([MENU_PADDING_VERTICAL, MENU_PADDING_HORIZONTAL]),
-            menu_1(app)
-        )
-
-        (
-            container(
-                text("Controls").size(MENU_FONT_SIZE).font(Font::with_name("Roboto")),//.align_y(alignment::Vertical::Center)
-            )
-            .style(|_theme: &WinitTheme| container::Style {
-                text_color: Some(iced_core::Color::from_rgb(0.878, 0.878, 0.878)),
-                ..container::Style::default()
-            })
-            .padding([MENU_PADDING_VERTICAL, MENU_PADDING_HORIZONTAL]), // // [top/bottom, left/right
-            menu_3(app)
-        )
-
-        (
-            container(
-                text("Help").size(MENU_FONT_SIZE).font(Font::with_name("Roboto"))
-            )
-            .style(|_theme: &WinitTheme| container::Style {
-                text_color: Some(iced_core::Color::from_rgb(0.878, 0.878, 0.878)),
-                ..container::Style::default()
-            })
-            .padding([MENU_PADDING_VERTICAL, MENU_PADDING_HORIZONTAL]),
-            menu_help(app)
-        )
+#[cfg(feature = "coco")]
+pub fn menu_annotations<'a>(app: &DataViewer) -> Menu<'a, Message, WinitTheme, Renderer> {
+    use crate::coco::annotation_manager::{AnnotationFilter, OverlayFilter};
+    use crate::coco::crop_export::CropExportOptions;
+    use crate::coco::widget::{CocoMessage, CropExportScope};
+
+    let pane_index = app.active_pane_index();
+    let has_annotations = app.annotation_manager.has_annotations();
+    let active_filter = app.annotation_manager.jump_filter();
+    let jump = |filter: Option<AnnotationFilter>| {
+        has_annotations.then_some(Message::CocoAction(CocoMessage::SetJumpFilter(pane_index, filter)))
+    };
+    let checked = |filter: AnnotationFilter| if active_filter == Some(filter) { "[x]" } else { "[  ]" };
+
+    let selected_annotation = app.panes.get(pane_index).and_then(|pane| pane.selected_annotation);
+    let crop_options = CropExportOptions {
+        padding_percent: app.coco_crop_padding,
+        max_size: (app.coco_crop_size > 0).then_some(app.coco_crop_size),
+    };
+    let export = |scope: CropExportScope| Message::CocoAction(CocoMessage::ExportCrops(scope, crop_options));
+
+    let category_items: Vec<Item<'a, Message, WinitTheme, Renderer>> = app.annotation_manager.categories()
+        .into_iter()
+        .map(|(id, name)| {
+            let filter = AnnotationFilter::Category(id);
+            Item::new(labeled_button_owned(
+                format!("{} {}", checked(filter), name),
+                MENU_ITEM_FONT_SIZE,
+                jump(Some(filter)),
+            ))
+        })
+        .collect();
+    let category_submenu = Menu::new(category_items)
+        .max_width(200.0)
+        .spacing(0.0);
+
+    // Box size thresholds in pixels per side; 32 is COCO's "small" cutoff
+    let overlay_filter = app.annotation_manager.overlay_filter();
+    let set_overlay_filter = |filter: OverlayFilter| Some(Message::CocoAction(CocoMessage::SetOverlayFilter(filter)));
+    let mark = |on: bool| if on { "[x]" } else { "[  ]" };
+    let mut filter_items: Vec<Item<'a, Message, WinitTheme, Renderer>> = vec![
+        Item::new(labeled_button_owned(
+            format!("{} All annotations", mark(!overlay_filter.is_active())),
+            MENU_ITEM_FONT_SIZE,
+            set_overlay_filter(OverlayFilter::default()),
+        )),
+    ];
+    filter_items.extend([8u32, 16, 32].map(|side| {
+        let max_area = (side * side) as f32;
+        let on = overlay_filter.max_area == Some(max_area);
+        Item::new(labeled_button_owned(
+            format!("{} Smaller than {}x{} px", mark(on), side, side),
+            MENU_ITEM_FONT_SIZE,
+            set_overlay_filter(OverlayFilter { max_area: (!on).then_some(max_area), ..overlay_filter }),
+        ))
+    }));
+    filter_items.push(Item::new(labeled_button_owned(
+        format!("{} Touching image border", mark(overlay_filter.touching_border)),
+        MENU_ITEM_FONT_SIZE,
+        set_overlay_filter(OverlayFilter { touching_border: !overlay_filter.touching_border, ..overlay_filter }),
+    )));
+    filter_items.push(Item::new(labeled_button_maybe(
+        "Jump to flagged images",
+        MENU_ITEM_FONT_SIZE,
+        overlay_filter.is_active().then(|| jump(Some(AnnotationFilter::Flagged))).flatten(),
+    )));
+    let filter_submenu = Menu::new(filter_items)
+        .max_width(220.0)
+        .spacing(0.0);
+
+    let burn_in_items: Vec<Item<'a, Message, WinitTheme, Renderer>> = [None, Some(4096u32), Some(2048), Some(1024)]
+        .into_iter()
+        .map(|max_size| {
+            let label = match max_size {
+                None => "Original size...".to_string(),
+                Some(size) => format!("Longest side {} px...", size),
+            };
+            Item::new(labeled_button_owned(
+                label,
+                MENU_ITEM_FONT_SIZE,
+                has_annotations.then(|| crate::coco::overlay_export::OverlayExportMessage::Export(max_size).into()),
+            ))
+        })
+        .collect();
+    let burn_in_submenu = Menu::new(burn_in_items)
+        .max_width(200.0)
+        .spacing(0.0);
+
+    Menu::new(menu_items!(
+        (labeled_button_maybe(
+            "Next match (])",
+            MENU_ITEM_FONT_SIZE,
+            has_annotations.then_some(Message::CocoAction(CocoMessage::JumpToMatch(pane_index, true)))
+        ))
+        (labeled_button_maybe(
+            "Previous match ([)",
+            MENU_ITEM_FONT_SIZE,
+            has_annotations.then_some(Message::CocoAction(CocoMessage::JumpToMatch(pane_index, false)))
+        ))
+        (submenu_button("Jump to category", MENU_ITEM_FONT_SIZE), category_submenu)
+        (submenu_button("Overlay filter", MENU_ITEM_FONT_SIZE), filter_submenu)
+        (labeled_button_owned(
+            format!("{} Images without annotations", checked(AnnotationFilter::Unannotated)),
+            MENU_ITEM_FONT_SIZE,
+            jump(Some(AnnotationFilter::Unannotated))
+        ))
+        (labeled_button_maybe(
+            "Clear jump filter",
+            MENU_ITEM_FONT_SIZE,
+            active_filter.and(jump(None))
+        ))
+        (labeled_button_maybe(
+            "Export selected crop...",
+            MENU_ITEM_FONT_SIZE,
+            selected_annotation.map(|_| export(CropExportScope::Selected(pane_index)))
+        ))
+        (labeled_button_maybe(
+            "Export all crops...",
+            MENU_ITEM_FONT_SIZE,
+            has_annotations.then(|| export(CropExportScope::All))
+        ))
+        (submenu_button("Export with overlays", MENU_ITEM_FONT_SIZE), burn_in_submenu)
+        (labeled_button_maybe(
+            "Edit categories...",
+            MENU_ITEM_FONT_SIZE,
+            has_annotations.then(|| crate::coco::category_remap::CategoryRemapMessage::Show.into())
+        ))
+    ))
+    .max_width(230.0)
+    .spacing(0.0)
+    .offset(5.0)
+}
+
+#[cfg(feature = "embeddings")]
+pub fn menu_similar<'a>(app: &DataViewer) -> Menu<'a, Message, WinitTheme, Renderer> {
+    use crate::widgets::similarity_widget::SimilarityMessage;
+
+    let pane_index = app.active_pane_index();
+    let dir_loaded = app.panes.get(pane_index).is_some_and(|pane| pane.dir_loaded);
+    let has_embeddings = app.embedding_manager.has_embeddings();
+
+    Menu::new(menu_items!(
+        (labeled_button_maybe(
+            "Load embeddings...",
+            MENU_ITEM_FONT_SIZE,
+            dir_loaded.then_some(Message::SimilarityAction(SimilarityMessage::LoadEmbeddings(pane_index)))
+        ))
+        (labeled_button_maybe(
+            "Find similar (F)",
+            MENU_ITEM_FONT_SIZE,
+            (dir_loaded && has_embeddings).then_some(Message::SimilarityAction(SimilarityMessage::FindSimilar(pane_index)))
+        ))
+        (labeled_button_maybe(
+            "Close results",
+            MENU_ITEM_FONT_SIZE,
+            app.embedding_manager.results().map(|_| Message::SimilarityAction(SimilarityMessage::CloseResults))
+        ))
+    ))
+    .max_width(180.0)
+    .spacing(0.0)
+    .offset(5.0)
+}
+
+#[cfg(feature = "inference")]
+pub fn menu_model<'a>(app: &DataViewer) -> Menu<'a, Message, WinitTheme, Renderer> {
+    use crate::inference::InferenceMessage;
+
+    let has_model = app.inference.has_model();
+    let auto_run = app.inference.auto_run;
+
+    Menu::new(menu_items!(
+        (labeled_button("Load model config...", MENU_ITEM_FONT_SIZE, InferenceMessage::LoadConfig.into()))
+        (labeled_button_maybe(
+            "Run on current image",
+            MENU_ITEM_FONT_SIZE,
+            has_model.then(|| InferenceMessage::Run.into())
+        ))
+        (labeled_button_maybe(
+            if auto_run { "[x] Run on every image" } else { "[  ] Run on every image" },
+            MENU_ITEM_FONT_SIZE,
+            has_model.then(|| InferenceMessage::SetAutoRun(!auto_run).into())
+        ))
+        (labeled_button_maybe(
+            "Clear results",
+            MENU_ITEM_FONT_SIZE,
+            app.inference.has_results().then(|| InferenceMessage::Clear.into())
+        ))
+        (labeled_button_maybe(
+            "AI upscale zoomed region",
+            MENU_ITEM_FONT_SIZE,
+            app.inference.has_upscaler().then(|| InferenceMessage::SetUpscalePreview(true).into())
+        ))
+        (labeled_button_maybe(
+            "Background removal preview",
+            MENU_ITEM_FONT_SIZE,
+            app.inference.has_remover().then(|| InferenceMessage::SetCutoutPreview(true).into())
+        ))
+    ))
+    .max_width(180.0)
+    .spacing(0.0)
+    .offset(5.0)
+}
+
+pub fn menu_plugins<'a>(app: &DataViewer) -> Menu<'a, Message, WinitTheme, Renderer> {
+    use crate::plugins::PluginMessage;
+
+    let mut items: Vec<Item<'a, Message, WinitTheme, Renderer>> = app.plugins.plugins().iter()
+        .map(|plugin| {
+            let checked = if app.plugins.is_enabled(&plugin.name) { "[x]" } else { "[  ]" };
+            Item::new(labeled_button_owned(
+                format!("{} {}", checked, plugin.name),
+                MENU_ITEM_FONT_SIZE,
+                Some(PluginMessage::Toggle(plugin.name.clone()).into()),
+            ))
+        })
+        .collect();
+    if items.is_empty() {
+        items.push(Item::new(labeled_button_maybe("No plugins installed", MENU_ITEM_FONT_SIZE, None)));
+    }
+    // Analyzers run on the image in the first pane, like metadata plugins
+    let image_path = app.plugin_image_path();
+    for analyzer in app.plugins.analyzers() {
+        items.push(Item::new(labeled_button_owned(
+            format!("Analyze with {}", analyzer.name),
+            MENU_ITEM_FONT_SIZE,
+            image_path.clone().map(|path| PluginMessage::Analyze(analyzer.name.clone(), path).into()),
+        )));
+    }
+    items.push(Item::new(labeled_button("Reload plugins", MENU_ITEM_FONT_SIZE, PluginMessage::Reload.into())));
+    items.push(Item::new(labeled_button("Manage Plugins...", MENU_ITEM_FONT_SIZE, PluginMessage::ShowManager.into())));
+
+    Menu::new(items)
+        .max_width(220.0)
+        .spacing(0.0)
+        .offset(5.0)
+}
+
+#[cfg(feature = "scripting")]
+pub fn menu_scripts<'a>(app: &DataViewer) -> Menu<'a, Message, WinitTheme, Renderer> {
+    use crate::scripting::ScriptMessage;
+
+    let mut items: Vec<Item<'a, Message, WinitTheme, Renderer>> = app.script_manager.scripts().iter()
+        .enumerate()
+        .map(|(index, script)| {
+            let label = match &script.binding {
+                Some(binding) => format!("{} ({})", script.name, binding),
+                None => script.name.clone(),
+            };
+            Item::new(labeled_button_owned(label, MENU_ITEM_FONT_SIZE, Some(ScriptMessage::Run(index).into())))
+        })
+        .collect();
+    if items.is_empty() {
+        items.push(Item::new(labeled_button_maybe("No scripts found", MENU_ITEM_FONT_SIZE, None)));
+    }
+    items.push(Item::new(labeled_button("Reload scripts", MENU_ITEM_FONT_SIZE, ScriptMessage::Reload.into())));
+
+    Menu::new(items)
+        .max_width(220.0)
+        .spacing(0.0)
+        .offset(5.0)
+}
+
+/// Top-level menu bar label; the compact bar shows a single glyph instead, at the same height
+fn menu_root<'a>(label: &'a str, compact: bool) -> Element<'a, Message, WinitTheme, Renderer> {
+    let (label, font, horizontal_padding) = match (compact, label) {
+        (false, _) => (label, Font::with_name("Roboto"), MENU_PADDING_HORIZONTAL),
+        (true, "File") => ("\u{F115}", Font::with_name("viewskater-fonts"), MENU_PADDING_VERTICAL),
+        (true, "Help") => ("?", Font::with_name("Roboto"), MENU_PADDING_VERTICAL),
+        (true, _) => (&label[..1], Font::with_name("Roboto"), MENU_PADDING_VERTICAL),
+    };
+    container(
+        text(label).size(MENU_FONT_SIZE).font(font)
     )
+    .style(|_theme: &WinitTheme| container::Style {
+        text_color: Some(iced_core::Color::from_rgb(0.878, 0.878, 0.878)),
+        ..container::Style::default()
+    })
+    .padding([MENU_PADDING_VERTICAL, horizontal_padding]) // [top/bottom, left/right]
+    .into()
+}
+
+pub fn build_menu(app: &DataViewer) -> MenuBar<'_, Message, WinitTheme, Renderer> {
+    let compact = app.menu_layout.mode == MenuBarMode::Compact;
+    let root = |label| menu_root(label, compact);
+    let mut roots = vec![
+        Item::with_menu(root("File"), menu_1(app)),
+        Item::with_menu(root("Controls"), menu_3(app)),
+    ];
+
+    // Only shown once a COCO dataset is loaded
+    #[cfg(feature = "coco")]
+    if app.annotation_manager.has_annotations() && !app.menu_layout.is_hidden("Annotations") {
+        roots.push(Item::with_menu(root("Annotations"), menu_annotations(app)));
+    }
+
+    #[cfg(feature = "embeddings")]
+    if !app.menu_layout.is_hidden("Similar") {
+        roots.push(Item::with_menu(root("Similar"), menu_similar(app)));
+    }
+
+    #[cfg(feature = "inference")]
+    if !app.menu_layout.is_hidden("Model") {
+        roots.push(Item::with_menu(root("Model"), menu_model(app)));
+    }
+
+    if !app.menu_layout.is_hidden("Plugins") {
+        roots.push(Item::with_menu(root("Plugins"), menu_plugins(app)));
+    }
+    #[cfg(feature = "scripting")]
+    if !app.menu_layout.is_hidden("Scripts") {
+        roots.push(Item::with_menu(root("Scripts"), menu_scripts(app)));
+    }
+    if !app.menu_layout.is_hidden("Help") {
+        roots.push(Item::with_menu(root("Help"), menu_help(app)));
+    }
+
+    MenuBar::new(roots)
     //.spacing(10)
     // ref: https://github.com/iced-rs/iced_aw/blob/main/src/style/menu_bar.rs
     .draw_path(menu::DrawPath::Backdrop)
@@ -0,0 +1,168 @@
+/// Layout of the menu bar
+///
+/// Menus and Controls entries that are rarely used can be hidden, and the bar drawn in full,
+/// compact (one glyph per menu) or not at all: in hidden mode a tap of Alt shows it until
+/// Alt is tapped again or Escape is pressed. The layout is set from "Controls -> Menu Bar"
+/// and written to the settings file right away, like the window state, so it is kept
+/// across restarts.
+use iced_core::keyboard::{Key, key::Named};
+use iced_winit::runtime::Task;
+use log::error;
+
+use crate::app::{DataViewer, Message};
+use crate::menu::MENU_BAR_HEIGHT;
+use crate::settings::{MenuBarMode, UserSettings};
+
+/// Top-level menus of this build that can be hidden; File and Controls always stay, since
+/// the layout is changed from Controls
+pub fn hideable_menus() -> Vec<&'static str> {
+    let mut menus = Vec::new();
+    #[cfg(feature = "coco")]
+    menus.push("Annotations");
+    #[cfg(feature = "embeddings")]
+    menus.push("Similar");
+    #[cfg(feature = "inference")]
+    menus.push("Model");
+    menus.push("Plugins");
+    #[cfg(feature = "scripting")]
+    menus.push("Scripts");
+    menus.push("Help");
+    menus
+}
+
+/// Submenus of Controls that can be hidden
+pub const HIDEABLE_ENTRIES: [&str; 8] = [
+    "Stereo", "Shader", "LUT", "Display Profile", "Soft Proof", "White Balance", "Profiles", "Viewed Images",
+];
+
+#[derive(Debug, Clone)]
+pub enum MenuLayoutMessage {
+    SetMode(MenuBarMode),
+    /// Show or hide a menu or Controls entry by name
+    ToggleHidden(String),
+}
+
+impl From<MenuLayoutMessage> for Message {
+    fn from(msg: MenuLayoutMessage) -> Self {
+        Message::MenuLayoutAction(msg)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MenuLayout {
+    pub mode: MenuBarMode,
+    /// Names of hidden menus and Controls entries
+    pub hidden: Vec<String>,
+    /// Whether Alt brought the bar up in hidden mode
+    revealed: bool,
+    /// Alt is held and no other key was pressed since, so its release is a tap
+    alt_alone: bool,
+}
+
+impl MenuLayout {
+    pub fn new(settings: &UserSettings) -> Self {
+        Self {
+            mode: settings.menu_bar_mode,
+            hidden: settings.hidden_menus.clone(),
+            ..Self::default()
+        }
+    }
+
+    pub fn is_hidden(&self, name: &str) -> bool {
+        self.hidden.iter().any(|hidden| hidden == name)
+    }
+
+    /// Whether the bar is drawn; in fullscreen it also shows while the cursor is at the top
+    pub fn shows_bar(&self) -> bool {
+        self.mode != MenuBarMode::Hidden || self.revealed
+    }
+
+    /// Height the bar takes at the top of the window
+    pub fn bar_height(&self) -> f32 {
+        if self.shows_bar() { MENU_BAR_HEIGHT } else { 0.0 }
+    }
+
+    pub fn on_key_pressed(&mut self, key: &Key) {
+        match key.as_ref() {
+            Key::Named(Named::Alt) => self.alt_alone = true,
+            Key::Named(Named::Escape) => {
+                self.alt_alone = false;
+                self.revealed = false;
+            }
+            _ => self.alt_alone = false,
+        }
+    }
+
+    /// Shows or hides the bar in hidden mode when Alt is tapped on its own
+    pub fn on_key_released(&mut self, key: &Key) {
+        if let Key::Named(Named::Alt) = key.as_ref() {
+            if std::mem::take(&mut self.alt_alone) && self.mode == MenuBarMode::Hidden {
+                self.revealed = !self.revealed;
+            }
+        }
+    }
+
+    /// Takes the mode and hidden entries of settings read while the app runs
+    pub fn apply(&mut self, settings: &UserSettings) {
+        self.mode = settings.menu_bar_mode;
+        self.hidden = settings.hidden_menus.clone();
+    }
+}
+
+pub fn handle_menu_layout_message(app: &mut DataViewer, msg: MenuLayoutMessage) -> Task<Message> {
+    let layout = &mut app.menu_layout;
+    match msg {
+        MenuLayoutMessage::SetMode(mode) => {
+            layout.mode = mode;
+            layout.revealed = false;
+        }
+        MenuLayoutMessage::ToggleHidden(name) => {
+            if layout.is_hidden(&name) {
+                layout.hidden.retain(|hidden| *hidden != name);
+            } else {
+                layout.hidden.push(name);
+            }
+        }
+    }
+
+    let mut settings = UserSettings::load(None);
+    settings.menu_bar_mode = layout.mode;
+    settings.hidden_menus = layout.hidden.clone();
+    if let Err(e) = settings.save() {
+        error!("Failed to save the menu bar layout: {e}");
+    }
+    Task::none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alt_tap_reveals_hidden_bar() {
+        let mut layout = MenuLayout { mode: MenuBarMode::Hidden, ..MenuLayout::default() };
+        assert!(!layout.shows_bar());
+        assert_eq!(layout.bar_height(), 0.0);
+
+        let alt = Key::Named(Named::Alt);
+        layout.on_key_pressed(&alt);
+        layout.on_key_released(&alt);
+        assert!(layout.shows_bar());
+
+        // Alt as a modifier of another key leaves the bar as it is
+        layout.on_key_pressed(&alt);
+        layout.on_key_pressed(&Key::Character("1".into()));
+        layout.on_key_released(&alt);
+        assert!(layout.shows_bar());
+
+        layout.on_key_pressed(&Key::Named(Named::Escape));
+        assert!(!layout.shows_bar());
+
+        // Outside hidden mode the bar always shows
+        let mut layout = MenuLayout::default();
+        layout.on_key_pressed(&alt);
+        layout.on_key_released(&alt);
+        assert!(layout.shows_bar());
+        assert_eq!(layout.bar_height(), MENU_BAR_HEIGHT);
+    }
+}
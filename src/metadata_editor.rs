@@ -0,0 +1,314 @@
+/// Metadata editor dialog
+///
+/// Edits the rating, color label, copyright and description of the active pane's image and
+/// writes them to its XMP sidecar with `utils::xmp`. With the `selection` feature the same
+/// values can be written to every image marked as selected in the folder. Image files
+/// themselves are never modified, and images inside archives can't be edited.
+use std::path::PathBuf;
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::font::Font;
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, column, container, text, button, text_input, Space};
+use log::{info, error};
+
+use crate::app::{Message, DataViewer};
+use crate::cache::img_cache::PathSource;
+use crate::utils::xmp::{self, XmpFields, LABELS};
+
+#[derive(Debug, Clone, Default)]
+pub struct MetadataEditorState {
+    pub visible: bool,
+    pub pane_index: usize,
+    /// Image the fields were loaded from
+    pub image: Option<PathBuf>,
+    pub rating: Option<u8>,
+    pub label: Option<String>,
+    pub copyright: String,
+    pub description: String,
+    pub apply_to_selected: bool,
+    pub saving: bool,
+    pub status: Option<String>,
+}
+
+impl MetadataEditorState {
+    fn fields(&self) -> XmpFields {
+        let non_empty = |value: &str| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        XmpFields {
+            rating: self.rating,
+            label: self.label.clone(),
+            copyright: non_empty(&self.copyright),
+            description: non_empty(&self.description),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum MetadataEditorMessage {
+    Show,
+    Hide,
+    SetRating(u8),
+    SetLabel(Option<String>),
+    SetCopyright(String),
+    SetDescription(String),
+    SetApplyToSelected(bool),
+    Save,
+    Saved(Result<usize, String>),
+}
+
+impl From<MetadataEditorMessage> for Message {
+    fn from(msg: MetadataEditorMessage) -> Self {
+        Message::MetadataEditorAction(msg)
+    }
+}
+
+/// Image files the dialog writes to: the image it was opened on, or the selected ones
+fn targets(app: &DataViewer) -> Vec<PathBuf> {
+    let state = &app.metadata_editor;
+
+    #[cfg(feature = "selection")]
+    if state.apply_to_selected {
+        use crate::selection_manager::ImageMark;
+        let Some(pane) = app.panes.get(state.pane_index).filter(|p| p.dir_loaded) else {
+            return Vec::new();
+        };
+        return pane.img_cache.image_paths.iter()
+            .filter(|source| app.selection_manager.get_mark(&source.file_name()) == ImageMark::Selected)
+            .filter_map(|source| match source {
+                PathSource::Filesystem(path) => Some(path.clone()),
+                _ => None,
+            })
+            .collect();
+    }
+
+    state.image.iter().cloned().collect()
+}
+
+pub fn handle_metadata_editor_message(app: &mut DataViewer, msg: MetadataEditorMessage) -> Task<Message> {
+    match msg {
+        MetadataEditorMessage::Show => {
+            let pane_index = app.active_pane_index();
+            let Some(pane) = app.panes.get(pane_index).filter(|p| p.dir_loaded) else {
+                return Task::none();
+            };
+            let image = match pane.img_cache.image_paths.get(pane.img_cache.current_index) {
+                Some(PathSource::Filesystem(path)) => Some(path.clone()),
+                _ => None,
+            };
+            let fields = image.as_deref().map(xmp::read_sidecar).unwrap_or_default();
+            let apply_to_selected = app.metadata_editor.apply_to_selected;
+            app.metadata_editor = MetadataEditorState {
+                visible: true,
+                pane_index,
+                status: image.is_none().then(|| "Error: images inside archives can't be edited".to_string()),
+                image,
+                rating: fields.rating,
+                label: fields.label,
+                copyright: fields.copyright.unwrap_or_default(),
+                description: fields.description.unwrap_or_default(),
+                apply_to_selected,
+                saving: false,
+            };
+            Task::none()
+        }
+        MetadataEditorMessage::Hide => {
+            app.metadata_editor.visible = false;
+            Task::none()
+        }
+        MetadataEditorMessage::SetRating(rating) => {
+            app.metadata_editor.rating = (rating > 0).then_some(rating);
+            Task::none()
+        }
+        MetadataEditorMessage::SetLabel(label) => {
+            app.metadata_editor.label = label;
+            Task::none()
+        }
+        MetadataEditorMessage::SetCopyright(copyright) => {
+            app.metadata_editor.copyright = copyright;
+            Task::none()
+        }
+        MetadataEditorMessage::SetDescription(description) => {
+            app.metadata_editor.description = description;
+            Task::none()
+        }
+        MetadataEditorMessage::SetApplyToSelected(enabled) => {
+            app.metadata_editor.apply_to_selected = enabled;
+            Task::none()
+        }
+        MetadataEditorMessage::Save => {
+            if app.metadata_editor.saving {
+                return Task::none();
+            }
+            let images = targets(app);
+            let state = &mut app.metadata_editor;
+            if images.is_empty() {
+                state.status = Some("Error: no images to write to".to_string());
+                return Task::none();
+            }
+            state.saving = true;
+            state.status = None;
+            let fields = state.fields();
+            Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || -> Result<usize, String> {
+                        for image in &images {
+                            let sidecar = xmp::write_sidecar(image, &fields)?;
+                            info!("Wrote metadata to {}", sidecar.display());
+                        }
+                        Ok(images.len())
+                    })
+                    .await
+                    .unwrap_or_else(|e| Err(e.to_string()))
+                },
+                |result| MetadataEditorMessage::Saved(result).into()
+            )
+        }
+        MetadataEditorMessage::Saved(result) => {
+            let state = &mut app.metadata_editor;
+            state.saving = false;
+            state.status = Some(match result {
+                Ok(1) => "Saved to the XMP sidecar".to_string(),
+                Ok(count) => format!("Saved to {} XMP sidecars", count),
+                Err(e) => {
+                    error!("Failed to write metadata: {}", e);
+                    format!("Error: {}", e)
+                }
+            });
+            Task::none()
+        }
+    }
+}
+
+/// Builds the metadata dialog
+pub fn view_metadata_editor(app: &DataViewer) -> Element<'_, Message, WinitTheme, Renderer> {
+    let state = &app.metadata_editor;
+    let label_style = |_theme: &WinitTheme| container::Style {
+        text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+        ..container::Style::default()
+    };
+    let field_label = |label: &'static str| text(label).size(13).width(Length::Fixed(90.0));
+
+    let ratings = (0..=5u8).fold(row![].spacing(12), |ratings, rating| {
+        let name = if rating == 0 { "None".to_string() } else { rating.to_string() };
+        ratings.push(iced_widget::Radio::new(
+            name,
+            rating,
+            Some(state.rating.unwrap_or(0)),
+            |rating| MetadataEditorMessage::SetRating(rating).into(),
+        ).size(14).text_size(13))
+    });
+
+    // A label other tools wrote that isn't one of these is kept, with no option checked
+    let current_label = match state.label.as_deref() {
+        None => Some(None),
+        Some(label) => LABELS.iter().copied().find(|l| *l == label).map(Some),
+    };
+    let labels = std::iter::once(None)
+        .chain(LABELS.iter().copied().map(Some))
+        .fold(row![].spacing(12), |labels, label: Option<&'static str>| {
+            labels.push(iced_widget::Radio::new(
+                label.unwrap_or("None"),
+                label,
+                current_label,
+                |label| MetadataEditorMessage::SetLabel(label.map(str::to_string)).into(),
+            ).size(14).text_size(13))
+        });
+
+    let fields = column![
+        row![field_label("Rating"), ratings].spacing(10).align_y(Alignment::Center),
+        row![field_label("Label"), labels].spacing(10).align_y(Alignment::Center),
+        row![
+            field_label("Copyright"),
+            text_input("", &state.copyright)
+                .size(13)
+                .on_input(|value| MetadataEditorMessage::SetCopyright(value).into()),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        row![
+            field_label("Description"),
+            text_input("", &state.description)
+                .size(13)
+                .on_input(|value| MetadataEditorMessage::SetDescription(value).into()),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    ]
+    .spacing(8);
+
+    #[cfg(feature = "selection")]
+    let apply_to_selected: Element<'_, Message, WinitTheme, Renderer> = container(
+        crate::widgets::toggler::Toggler::new(
+            Some("Write to all images marked as selected".into()),
+            state.apply_to_selected,
+            |enabled| MetadataEditorMessage::SetApplyToSelected(enabled).into(),
+        ).width(Length::Fill)
+    ).style(label_style).into();
+    #[cfg(not(feature = "selection"))]
+    let apply_to_selected: Element<'_, Message, WinitTheme, Renderer> = Space::with_height(0).into();
+
+    let file_name = state.image.as_ref()
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let status_text = match (state.saving, state.status.as_deref()) {
+        (true, _) => "Saving...".to_string(),
+        (false, Some(status)) => status.to_string(),
+        (false, None) => " ".to_string(),
+    };
+    let is_error = status_text.starts_with("Error");
+
+    let content = column![
+        text("Edit Metadata").size(18)
+            .font(Font {
+                family: iced_winit::core::font::Family::Name("Roboto"),
+                weight: iced_winit::core::font::Weight::Bold,
+                stretch: iced_winit::core::font::Stretch::Normal,
+                style: iced_winit::core::font::Style::Normal,
+            }),
+        text(file_name).size(12),
+        container(fields).style(label_style),
+        apply_to_selected,
+        container(text(status_text).size(14))
+            .style(move |theme: &WinitTheme| container::Style {
+                text_color: Some(if is_error {
+                    theme.extended_palette().danger.strong.color
+                } else {
+                    theme.extended_palette().success.strong.color
+                }),
+                ..container::Style::default()
+            })
+            .height(Length::Fixed(18.0)),
+        row![
+            button(text("Save"))
+                .padding([3, 10])
+                .on_press_maybe((!state.saving && state.image.is_some())
+                    .then_some(MetadataEditorMessage::Save.into())),
+            Space::with_width(Length::Fill),
+            button(text("Close"))
+                .padding([3, 10])
+                .on_press(MetadataEditorMessage::Hide.into()),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    ]
+    .spacing(10)
+    .width(Length::Fixed(520.0));
+
+    container(content)
+        .padding(15)
+        .style(|theme: &WinitTheme| {
+            iced_widget::container::Style {
+                background: Some(theme.extended_palette().background.base.color.into()),
+                text_color: Some(theme.extended_palette().primary.weak.text),
+                border: iced_winit::core::Border {
+                    color: theme.extended_palette().background.strong.color,
+                    width: 1.0,
+                    radius: iced_winit::core::border::Radius::from(8.0),
+                },
+                ..Default::default()
+            }
+        })
+        .into()
+}
@@ -0,0 +1,134 @@
+/// Back/forward history of navigation jumps
+///
+/// Jumps that skip over images (slider releases, first/last, overview, similarity, metrics
+/// plot, script and trigger jumps) push the position left behind, and Alt+Left/Alt+Right or
+/// the mouse back/forward buttons walk the stack like a browser. Stepping one image at a time
+/// is not recorded. Entries remember their folder and are skipped once the pane shows
+/// another one.
+use iced_winit::runtime::Task;
+use log::debug;
+
+use crate::app::{DataViewer, Message};
+
+const MAX_ENTRIES: usize = 200;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    pane_index: usize,
+    directory: Option<String>,
+    pos: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct NavigationHistory {
+    back: Vec<Entry>,
+    forward: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum HistoryMessage {
+    Back,
+    Forward,
+}
+
+impl From<HistoryMessage> for Message {
+    fn from(msg: HistoryMessage) -> Self {
+        Message::HistoryAction(msg)
+    }
+}
+
+fn push(stack: &mut Vec<Entry>, entry: Entry) {
+    if stack.last() != Some(&entry) {
+        stack.push(entry);
+    }
+    if stack.len() > MAX_ENTRIES {
+        stack.remove(0);
+    }
+}
+
+/// Top entry of `stack` still valid for the folder each pane shows
+fn pop_valid(stack: &mut Vec<Entry>, directory_of: impl Fn(usize) -> Option<String>) -> Option<Entry> {
+    while let Some(entry) = stack.pop() {
+        if directory_of(entry.pane_index) == entry.directory {
+            return Some(entry);
+        }
+    }
+    None
+}
+
+impl NavigationHistory {
+    /// Remember leaving `from` for `to` in a pane showing `directory`
+    pub fn record(&mut self, pane_index: usize, directory: Option<String>, from: usize, to: usize) {
+        if from == to {
+            return;
+        }
+        push(&mut self.back, Entry { pane_index, directory, pos: from });
+        self.forward.clear();
+    }
+
+    fn step(&mut self, forward: bool, directory_of: impl Fn(usize) -> Option<String>, current_of: impl Fn(usize) -> usize) -> Option<Entry> {
+        let (from, to) = if forward {
+            (&mut self.forward, &mut self.back)
+        } else {
+            (&mut self.back, &mut self.forward)
+        };
+        let entry = pop_valid(from, &directory_of)?;
+        push(to, Entry { pos: current_of(entry.pane_index), ..entry.clone() });
+        Some(entry)
+    }
+}
+
+/// Record a jump of `pane_index` to `to`, before it is carried out
+pub fn record_jump(app: &mut DataViewer, pane_index: usize, to: usize) {
+    let Some(pane) = app.panes.get(pane_index).filter(|pane| pane.dir_loaded) else {
+        return;
+    };
+    let directory = pane.directory_path.clone();
+    let from = pane.img_cache.current_index;
+    app.navigation_history.record(pane_index, directory, from, to);
+}
+
+pub fn handle_history_message(app: &mut DataViewer, msg: HistoryMessage) -> Task<Message> {
+    let panes = &app.panes;
+    let entry = app.navigation_history.step(
+        matches!(msg, HistoryMessage::Forward),
+        |pane_index| panes.get(pane_index).and_then(|pane| pane.directory_path.clone()),
+        |pane_index| panes.get(pane_index).map_or(0, |pane| pane.img_cache.current_index),
+    );
+    match entry {
+        Some(entry) => {
+            debug!("History {:?}: pane {} to {}", msg, entry.pane_index, entry.pos);
+            app.jump_to_image(entry.pane_index, entry.pos)
+        }
+        None => Task::none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_back_and_forward_like_a_browser() {
+        let dir = || Some("/a".to_string());
+        let mut history = NavigationHistory::default();
+        history.record(0, dir(), 0, 50);
+        history.record(0, dir(), 50, 90);
+
+        let back = history.step(false, |_| dir(), |_| 90).unwrap();
+        assert_eq!(back.pos, 50);
+        let back = history.step(false, |_| dir(), |_| 50).unwrap();
+        assert_eq!(back.pos, 0);
+        assert!(history.step(false, |_| dir(), |_| 0).is_none());
+
+        let forward = history.step(true, |_| dir(), |_| 0).unwrap();
+        assert_eq!(forward.pos, 50);
+
+        // A new jump drops the forward entries
+        history.record(0, dir(), 50, 10);
+        assert!(history.step(true, |_| dir(), |_| 10).is_none());
+
+        // Entries of another folder are skipped
+        assert!(history.step(false, |_| Some("/b".to_string()), |_| 10).is_none());
+    }
+}
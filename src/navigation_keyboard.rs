@@ -25,6 +25,7 @@ use crate::menu::PaneLayout;
 use crate::cache::img_cache::{CacheStrategy, LoadOperation, LoadOperationType, load_images_by_operation};
 use crate::loading_status::LoadingStatus;
 use crate::pane::{IMAGE_RENDER_TIMES, IMAGE_RENDER_FPS};
+use crate::utils::frame_stats;
 use iced_wgpu::engine::CompressionStrategy;
 
 
@@ -100,6 +101,8 @@ pub fn render_next_image_all(panes: &mut Vec<&mut Pane>, _pane_layout: &PaneLayo
 
     // Only record rendering time if any pane actually rendered something
     if did_render_happen {
+        frame_stats::navigation_rendered();
+
         // Record image rendering time
         if let Ok(mut render_times) = IMAGE_RENDER_TIMES.lock() {
             let now = Instant::now();
@@ -160,6 +163,8 @@ pub fn render_prev_image_all(panes: &mut Vec<&mut Pane>, _pane_layout: &PaneLayo
 
     // Only record rendering time if any pane actually rendered something
     if did_render_happen {
+        frame_stats::navigation_rendered();
+
         // Record image rendering time
         if let Ok(mut render_times) = IMAGE_RENDER_TIMES.lock() {
             let now = Instant::now();
@@ -439,6 +444,18 @@ fn get_target_indices_for_previous(panes: &mut Vec<&mut Pane>) -> Vec<Option<isi
     }).collect()
 }
 
+/// With the shared slider in dual-pane mode, a pane that has no image at the slider's
+/// position or the one it moves to stays where it is, so it doesn't fall out of step with
+/// the other pane
+fn is_out_of_step(pane_idx: usize, pane: &Pane, target: usize, current: usize, pane_layout: &PaneLayout, is_slider_dual: bool) -> bool {
+    if is_slider_dual || *pane_layout != PaneLayout::DualPane {
+        return false;
+    }
+    let num_images = pane.img_cache.image_paths.len();
+    crate::navigation_slider::missing_pair(pane_idx, current, num_images).is_some()
+        || crate::navigation_slider::missing_pair(pane_idx, target, num_images).is_some()
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn move_right_all(
     device: &Arc<wgpu::Device>,
@@ -447,7 +464,7 @@ pub fn move_right_all(
     compression_strategy: CompressionStrategy,
     panes: &mut [pane::Pane],
     loading_status: &mut LoadingStatus,
-    slider_value: &mut u16,
+    slider_value: &mut u32,
     pane_layout: &PaneLayout,
     is_slider_dual: bool,
     last_opened_pane: usize
@@ -460,6 +477,7 @@ pub fn move_right_all(
         debug!("move_right_all() - LoadPos operation in queue, skipping move_right_all()");
         return Task::none();
     }
+    frame_stats::navigation_started();
 
     for pane in panes.iter_mut() {
         pane.print_state();
@@ -472,7 +490,9 @@ pub fn move_right_all(
     let mut panes_to_load: Vec<&mut pane::Pane> = vec![];
     let mut indices_to_load: Vec<usize> = vec![];
     for (index, pane) in panes.iter_mut().enumerate() {
-        if pane.is_selected && pane.dir_loaded && pane.img_cache.current_index < pane.img_cache.image_paths.len() - 1 {
+        if pane.is_selected && pane.dir_loaded && pane.img_cache.current_index < pane.img_cache.image_paths.len() - 1
+            && !is_out_of_step(index, pane, *slider_value as usize + 1, *slider_value as usize, pane_layout, is_slider_dual)
+        {
             panes_to_load.push(pane);
             indices_to_load.push(index);
         }
@@ -535,6 +555,7 @@ pub fn move_right_all(
         debug!("move_right_all() - did_render_happen = {}", did_render_happen);
 
         if did_render_happen {
+            crate::usage_stats::record_navigation_step();
             loading_status.is_next_image_loaded = true;
             for pane in panes_to_load.iter_mut() {
                 pane.is_next_image_loaded = true;
@@ -554,6 +575,10 @@ pub fn move_right_all(
         } else {
             // Render failed because image not in cache - start loading timer for spinner
             debug!("SPINNER: move_right - render failed (not cached), setting timer");
+            // Count the step as a miss once, not on every retry while it loads
+            if panes_to_load.iter().all(|pane| pane.loading_started_at.is_none()) {
+                crate::usage_stats::record_cache_miss();
+            }
             for pane in panes_to_load.iter_mut() {
                 if pane.loading_started_at.is_none() {
                     pane.loading_started_at = Some(Instant::now());
@@ -568,7 +593,7 @@ pub fn move_right_all(
     // Update master slider when !is_slider_dual
     if did_new_render_happen && !is_slider_dual || *pane_layout == PaneLayout::SinglePane {
         // Use the current_index of the pane with largest dir size
-        *slider_value = (get_master_slider_value(&panes_to_load, pane_layout, is_slider_dual, last_opened_pane)) as u16;
+        *slider_value = (get_master_slider_value(&panes_to_load, pane_layout, is_slider_dual, last_opened_pane)) as u32;
     }
 
     // print tasks
@@ -586,7 +611,7 @@ pub fn move_left_all(
     compression_strategy: CompressionStrategy,
     panes: &mut [pane::Pane],
     loading_status: &mut LoadingStatus,
-    slider_value: &mut u16,
+    slider_value: &mut u32,
     pane_layout: &PaneLayout,
     is_slider_dual: bool,
     last_opened_pane: usize
@@ -598,13 +623,16 @@ pub fn move_left_all(
         debug!("move_left_all() - LoadPos operation in queue, skipping move_right_all()");
         return Task::none();
     }
+    frame_stats::navigation_started();
 
     // Collect mutable references to the panes that haven't reached the edge
     let mut panes_to_load: Vec<&mut pane::Pane> = vec![];
     let mut indices_to_load: Vec<usize> = vec![];
 
     for (index, pane) in panes.iter_mut().enumerate() {
-        if pane.is_selected && pane.dir_loaded && pane.img_cache.current_index > 0 {
+        if pane.is_selected && pane.dir_loaded && pane.img_cache.current_index > 0
+            && !is_out_of_step(index, pane, (*slider_value as usize).saturating_sub(1), *slider_value as usize, pane_layout, is_slider_dual)
+        {
             panes_to_load.push(pane);
             indices_to_load.push(index);
         }
@@ -668,6 +696,7 @@ pub fn move_left_all(
         }
 
         if did_render_happen {
+            crate::usage_stats::record_navigation_step();
             loading_status.is_prev_image_loaded = true;
 
             debug!("move_left_all() - loading prev images...");
@@ -683,6 +712,10 @@ pub fn move_left_all(
         } else {
             // Render failed because image not in cache - start loading timer for spinner
             debug!("SPINNER: move_left - render failed (not cached), setting timer");
+            // Count the step as a miss once, not on every retry while it loads
+            if panes_to_load.iter().all(|pane| pane.loading_started_at.is_none()) {
+                crate::usage_stats::record_cache_miss();
+            }
             for pane in panes_to_load.iter_mut() {
                 if pane.loading_started_at.is_none() {
                     pane.loading_started_at = Some(Instant::now());
@@ -694,7 +727,7 @@ pub fn move_left_all(
     let did_new_render_happen = are_all_prev_images_loaded(&panes_to_load, is_slider_dual, loading_status);
     // Update master slider when !is_slider_dual
     if did_new_render_happen && !is_slider_dual || *pane_layout == PaneLayout::SinglePane {
-        *slider_value = (get_master_slider_value(&panes_to_load, pane_layout, is_slider_dual, last_opened_pane) ) as u16;
+        *slider_value = (get_master_slider_value(&panes_to_load, pane_layout, is_slider_dual, last_opened_pane) ) as u32;
     }
 
     Task::batch(tasks)
@@ -21,7 +21,7 @@ use log::{Level, trace, debug, info, warn, error};
 use image::codecs::png::PngEncoder;
 use image::ImageEncoder;
 use image::ExtendedColorType;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicIsize, AtomicUsize, Ordering};
 #[allow(unused_imports)]
 use std::time::{Instant, Duration};
 use once_cell::sync::Lazy;
@@ -36,7 +36,7 @@ use crate::pane;
 use crate::cache::img_cache::{LoadOperation, load_all_images_in_queue};
 use crate::widgets::shader::scene::Scene;
 use crate::loading_status::LoadingStatus;
-use crate::app::Message;
+use crate::app::{Message, IoMessage};
 use crate::cache::img_cache::{CachedData, CacheStrategy, ImageMetadata};
 use crate::cache::cache_utils::{load_image_resized_sync, create_gpu_texture};
 use crate::pane::IMAGE_RENDER_TIMES;
@@ -45,6 +45,76 @@ use iced_wgpu::engine::CompressionStrategy;
 
 pub static LATEST_SLIDER_POS: AtomicUsize = AtomicUsize::new(0);
 
+/// Frames pane B is ahead of (or behind, if negative) the shared slider
+static PANE_B_OFFSET: AtomicIsize = AtomicIsize::new(0);
+
+pub fn pane_b_offset() -> isize {
+    PANE_B_OFFSET.load(Ordering::Relaxed)
+}
+
+pub fn set_pane_b_offset(offset: isize) {
+    PANE_B_OFFSET.store(offset, Ordering::Relaxed);
+}
+
+/// Index a pane shows for shared slider position `pos`; pane B's offset position is
+/// clamped to its `num_images`
+pub fn pane_position(pane_idx: usize, pos: usize, num_images: usize) -> usize {
+    offset_position(pane_idx, pos, num_images, pane_b_offset())
+}
+
+fn offset_position(pane_idx: usize, pos: usize, num_images: usize, offset: isize) -> usize {
+    if pane_idx != 1 || offset == 0 {
+        return pos;
+    }
+    (pos as isize + offset).clamp(0, num_images.saturating_sub(1) as isize) as usize
+}
+
+/// Why a pane has no image at a shared slider position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingPair {
+    /// Pane B's offset puts the position before its first image
+    BeforeFirst { offset: isize },
+    /// The pane's list ends before the position
+    PastLast { index: usize, num_images: usize },
+}
+
+impl MissingPair {
+    pub fn reason(&self) -> String {
+        match *self {
+            Self::BeforeFirst { offset } => format!("Offset {:+} puts this position before the first image", offset),
+            Self::PastLast { index, num_images } => format!("No image {}: this pane has {} images", index + 1, num_images),
+        }
+    }
+}
+
+/// Why a pane has no counterpart for shared slider position `pos`, if it has none
+pub fn missing_pair(pane_idx: usize, pos: usize, num_images: usize) -> Option<MissingPair> {
+    missing_pair_at(pane_idx, pos, num_images, pane_b_offset())
+}
+
+fn missing_pair_at(pane_idx: usize, pos: usize, num_images: usize, offset: isize) -> Option<MissingPair> {
+    let offset = if pane_idx == 1 { offset } else { 0 };
+    let index = pos as isize + offset;
+    if index < 0 {
+        Some(MissingPair::BeforeFirst { offset })
+    } else if index as usize >= num_images {
+        Some(MissingPair::PastLast { index: index as usize, num_images })
+    } else {
+        None
+    }
+}
+
+/// Slider positions where both panes have an image, and all positions of the shared slider
+pub fn pair_counts(num_images: [usize; 2]) -> (usize, usize) {
+    pair_counts_at(num_images, pane_b_offset())
+}
+
+fn pair_counts_at([count_a, count_b]: [usize; 2], offset: isize) -> (usize, usize) {
+    let first = (-offset).max(0);
+    let end = (count_a as isize).min(count_b as isize - offset);
+    ((end - first).max(0) as usize, count_a.max(count_b))
+}
+
 #[allow(dead_code)]
 static LAST_SLIDER_LOAD: Lazy<Mutex<Instant>> = Lazy::new(|| Mutex::new(Instant::now()));
 
@@ -75,6 +145,7 @@ fn load_full_res_image(
     // Process each pane in the list
     for idx in pane_indices {
         if let Some(pane) = panes.get_mut(idx) {
+            let pos = if pane_index == -1 { pane_position(idx, pos, pane.img_cache.image_paths.len()) } else { pos };
             let img_cache = &mut pane.img_cache;
             let img_path = match img_cache.image_paths.get(pos) {
                 Some(path) => path.clone(),
@@ -99,6 +170,13 @@ fn load_full_res_image(
                 img_cache.current_offset = 0;
             }
 
+            // Filtered navigation usually lands on an image prefetched by loading_handler
+            if let Some((cached_data, metadata)) = pane.filtered_prefetch.get(pos, img_path.path()) {
+                debug!("load_full_res_image: Using prefetched image at pos {} for pane {}", pos, idx);
+                crate::loading_handler::apply_prefetched(pane, pos, target_index, cached_data, metadata);
+                continue;
+            }
+
             // Check if this pane has GPU support by checking if device and queue are available
             let has_gpu_support = is_gpu_supported && pane.device.is_some() && pane.queue.is_some();
 
@@ -142,8 +220,8 @@ fn load_full_res_image(
                 debug!("load_full_res_image: Set current_index = {} for pane {}", pos, idx);
 
                 // Get dimensions from the loaded texture
-                let tex_size = texture.size();
-                let metadata = ImageMetadata::new(tex_size.width, tex_size.height, file_size);
+                let (width, height) = crate::cache::texture_tiles::image_size(&texture);
+                let metadata = ImageMetadata::new(width, height, file_size);
                 img_cache.cached_metadata[target_index] = Some(metadata.clone());
 
                 // Update the currently displayed image
@@ -273,6 +351,8 @@ fn get_loading_tasks_slider(
             }
         }
 
+        crate::bulk_load::add_images(target_indices_and_cache.len());
+
         // Enqueue the batched LoadPos operation with (image index, cache position) pairs
         let load_operation = LoadOperation::LoadPos((pane_index, target_indices_and_cache));
         loading_status.enqueue_image_load(load_operation);
@@ -317,6 +397,7 @@ pub fn load_remaining_images(
     // Clear the global loading queue
     loading_status.reset_image_load_queue();
     loading_status.reset_image_being_loaded_queue();
+    crate::bulk_load::begin();
 
     let mut tasks = Vec::new();
 
@@ -340,6 +421,7 @@ pub fn load_remaining_images(
             .collect();
 
         for cache_index in cache_indices {
+            let pane_pos = pane_position(cache_index, pos, panes[cache_index].img_cache.image_paths.len());
             let local_tasks = get_loading_tasks_slider(
                 device,
                 queue,
@@ -349,7 +431,7 @@ pub fn load_remaining_images(
                 panes,
                 loading_status,
                 cache_index,
-                pos
+                pane_pos
             );
             debug!("load_remaining_images - local_tasks.len(): {}", local_tasks.len());
             tasks.extend(local_tasks);
@@ -435,7 +517,8 @@ pub async fn create_async_image_widget_task(
     let bytes_result = match &img_path {
         crate::cache::img_cache::PathSource::Filesystem(path) => {
             // Direct filesystem reading - no archive cache needed
-            std::fs::read(path)
+            crate::cache::warm_cache::get(path).map_or_else(|| std::fs::read(path), Ok)
+                .and_then(|bytes| crate::plugins::source::convert(&path.to_string_lossy(), bytes))
         },
         crate::cache::img_cache::PathSource::Archive(_) | crate::cache::img_cache::PathSource::Preloaded(_) => {
             // Archive content requires archive cache
@@ -567,6 +650,7 @@ pub fn update_pos(
         // Create async image loading task for each pane
         for idx in pane_indices {
             if let Some(pane) = panes.get(idx) {
+                let pos = if pane_index == -1 { pane_position(idx, pos, pane.img_cache.image_paths.len()) } else { pos };
                 if pane.dir_loaded && !pane.img_cache.image_paths.is_empty() && pos < pane.img_cache.image_paths.len() {
                     debug!("#####################update_pos - Creating async image loading task for pane {}", idx);
 
@@ -583,7 +667,7 @@ pub fn update_pos(
                     // Create task for this pane
                     let pane_task = Task::perform(
                         create_async_image_widget_task(img_path, pos, idx, archive_cache),
-                        Message::SliderImageWidgetLoaded
+                        |result| Message::Io(IoMessage::SliderImageWidgetLoaded(result))
                     );
 
                     tasks.push(pane_task);
@@ -604,6 +688,7 @@ pub fn update_pos(
         let mut tasks = Vec::new();
         for (cache_index, pane) in panes.iter_mut().enumerate() {
             if pane.dir_loaded {
+                let pos = pane_position(cache_index, pos, pane.img_cache.image_paths.len());
                 //match load_current_slider_image(pane, pos) {
                 match load_current_slider_image_widget(pane, pos) {
                     Ok(()) => {
@@ -826,7 +911,8 @@ fn load_current_slider_image_widget(pane: &mut pane::Pane, pos: usize ) -> Resul
                         let bytes_result = match img_path {
                             crate::cache::img_cache::PathSource::Filesystem(path) => {
                                 // Direct filesystem reading - no archive cache needed
-                                std::fs::read(path)
+                                crate::cache::warm_cache::get(path).map_or_else(|| std::fs::read(path), Ok)
+                                    .and_then(|bytes| crate::plugins::source::convert(&path.to_string_lossy(), bytes))
                             },
                             crate::cache::img_cache::PathSource::Archive(_) | crate::cache::img_cache::PathSource::Preloaded(_) => {
                                 // Archive content requires archive cache
@@ -886,3 +972,34 @@ fn load_current_slider_image_widget(pane: &mut pane::Pane, pos: usize ) -> Resul
         }
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_position_only_moves_pane_b() {
+        assert_eq!(offset_position(0, 5, 10, 3), 5);
+        assert_eq!(offset_position(1, 5, 10, 3), 8);
+        assert_eq!(offset_position(1, 5, 10, -7), 0);
+        assert_eq!(offset_position(1, 8, 10, 3), 9);
+        // Without an offset positions are passed through unchanged, as before
+        assert_eq!(offset_position(1, 12, 10, 0), 12);
+    }
+
+    #[test]
+    fn test_missing_pair() {
+        assert_eq!(missing_pair_at(1, 39, 40, 0), None);
+        assert_eq!(missing_pair_at(1, 40, 40, 0), Some(MissingPair::PastLast { index: 40, num_images: 40 }));
+        assert_eq!(missing_pair_at(1, 2, 40, -3), Some(MissingPair::BeforeFirst { offset: -3 }));
+        assert_eq!(missing_pair_at(1, 37, 40, 3), Some(MissingPair::PastLast { index: 40, num_images: 40 }));
+        // The offset only applies to pane B
+        assert_eq!(missing_pair_at(0, 2, 40, -3), None);
+
+        assert_eq!(pair_counts_at([52, 40], 0), (40, 52));
+        assert_eq!(pair_counts_at([40, 40], 3), (37, 40));
+        assert_eq!(pair_counts_at([40, 40], -3), (37, 40));
+        assert_eq!(pair_counts_at([5, 40], -10), (0, 40));
+    }
+}
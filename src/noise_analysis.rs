@@ -0,0 +1,290 @@
+/// Noise measurement over the visible region
+///
+/// Zoom into a flat patch (sky, a gray card, a smooth wall) and press N: the part of the image
+/// each pane is showing is cropped from a full-resolution decode and its per-channel noise
+/// sigma is shown in a bar under the image, one entry per pane, so two denoisers can be
+/// compared on the same patch. With the zoom lock on, both panes show the same crop.
+///
+/// Sigma comes from differences between horizontally adjacent pixels, which cancels smooth
+/// gradients and vignetting across the patch. Texture and edges still count as noise, hence
+/// the flat patch.
+use std::sync::{Arc, Mutex};
+use iced_winit::core::{Element, Length, Alignment, Color, Rectangle};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, column, container, text, button, Column};
+use image::DynamicImage;
+use once_cell::sync::Lazy;
+
+use crate::app::{DataViewer, Message};
+
+const MAX_PANES: usize = 2;
+/// Crops narrower or shorter than this give unreliable numbers and are refused
+const MIN_REGION_SIZE: u32 = 8;
+
+/// Noise statistics of one crop, in 8-bit levels (0-255) whatever the source bit depth
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseReport {
+    pub width: u32,
+    pub height: u32,
+    pub mean: [f32; 3],
+    pub sigma: [f32; 3],
+}
+
+/// Part of the image each pane's shader drew last, as fractions of the image size
+/// (left, top, right, bottom)
+static VISIBLE: Lazy<Mutex<[Option<[f32; 4]>; MAX_PANES]>> = Lazy::new(|| Mutex::new([None; MAX_PANES]));
+
+#[derive(Debug, Clone)]
+enum Status {
+    Measuring,
+    Done(Vec<(usize, Result<NoiseReport, String>)>),
+}
+
+static STATUS: Lazy<Mutex<Option<Status>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone)]
+pub enum NoiseMessage {
+    Measure,
+    Measured(Vec<(usize, Result<NoiseReport, String>)>),
+    Close,
+}
+
+impl From<NoiseMessage> for Message {
+    fn from(msg: NoiseMessage) -> Self {
+        Message::NoiseAction(msg)
+    }
+}
+
+/// Fraction of the image inside `bounds` when it is drawn at `content`
+pub fn visible_fraction(bounds: Rectangle, content: Rectangle) -> [f32; 4] {
+    let fraction = |value: f32, start: f32, length: f32| ((value - start) / length.max(1.0)).clamp(0.0, 1.0);
+    [
+        fraction(bounds.x, content.x, content.width),
+        fraction(bounds.y, content.y, content.height),
+        fraction(bounds.x + bounds.width, content.x, content.width),
+        fraction(bounds.y + bounds.height, content.y, content.height),
+    ]
+}
+
+/// Called by the image shader on each draw
+pub fn set_visible_region(pane_index: usize, bounds: Rectangle, content: Rectangle) {
+    if let Ok(mut visible) = VISIBLE.lock() {
+        if let Some(slot) = visible.get_mut(pane_index) {
+            *slot = Some(visible_fraction(bounds, content));
+        }
+    }
+}
+
+/// Part of the image a pane showed in its last frame, as fractions of the image size
+pub fn visible_region(pane_index: usize) -> Option<[f32; 4]> {
+    VISIBLE.lock().ok()?.get(pane_index).copied().flatten()
+}
+
+/// Per-channel mean and noise sigma of row-major RGB pixels in 0..1
+pub fn measure(pixels: &[[f32; 3]], width: usize) -> NoiseReport {
+    let height = if width == 0 { 0 } else { pixels.len() / width };
+    let mut sum = [0.0f64; 3];
+    let mut diff_sum = [0.0f64; 3];
+    let mut diff_squares = [0.0f64; 3];
+    let mut diff_count = 0usize;
+    for row in pixels.chunks_exact(width.max(1)).take(height) {
+        for p in row {
+            for c in 0..3 {
+                sum[c] += p[c] as f64;
+            }
+        }
+        for pair in row.windows(2) {
+            for c in 0..3 {
+                let d = (pair[1][c] - pair[0][c]) as f64;
+                diff_sum[c] += d;
+                diff_squares[c] += d * d;
+            }
+            diff_count += 1;
+        }
+    }
+
+    let count = (width * height).max(1) as f64;
+    let n = diff_count.max(1) as f64;
+    let mut mean = [0.0f32; 3];
+    let mut sigma = [0.0f32; 3];
+    for c in 0..3 {
+        mean[c] = (sum[c] / count * 255.0) as f32;
+        // Differences of independent samples have twice the variance of the samples
+        let variance = (diff_squares[c] / n - (diff_sum[c] / n).powi(2)).max(0.0) / 2.0;
+        sigma[c] = (variance.sqrt() * 255.0) as f32;
+    }
+    NoiseReport { width: width as u32, height: height as u32, mean, sigma }
+}
+
+fn measure_region(image: &DynamicImage, region: [f32; 4]) -> Result<NoiseReport, String> {
+    let (width, height) = (image.width(), image.height());
+    let x0 = (region[0] * width as f32).floor() as u32;
+    let y0 = (region[1] * height as f32).floor() as u32;
+    let x1 = ((region[2] * width as f32).ceil() as u32).min(width);
+    let y1 = ((region[3] * height as f32).ceil() as u32).min(height);
+    if x1.saturating_sub(x0) < MIN_REGION_SIZE || y1.saturating_sub(y0) < MIN_REGION_SIZE {
+        return Err("visible region too small".to_string());
+    }
+    let crop = image.crop_imm(x0, y0, x1 - x0, y1 - y0).to_rgb32f();
+    let pixels: Vec<[f32; 3]> = crop.pixels().map(|p| p.0).collect();
+    Ok(measure(&pixels, crop.width() as usize))
+}
+
+pub fn handle_noise_message(app: &mut DataViewer, msg: NoiseMessage) -> Task<Message> {
+    match msg {
+        NoiseMessage::Measure => {
+            let visible = VISIBLE.lock().map(|visible| *visible).unwrap_or([None; MAX_PANES]);
+            let jobs: Vec<_> = app.panes.iter()
+                .enumerate()
+                .filter(|(_, pane)| pane.dir_loaded)
+                .filter_map(|(index, pane)| {
+                    let source = pane.img_cache.image_paths.get(pane.img_cache.current_index)?.clone();
+                    let archive_cache = pane.has_compressed_file.then(|| Arc::clone(&pane.archive_cache));
+                    let region = visible.get(index).copied().flatten().unwrap_or([0.0, 0.0, 1.0, 1.0]);
+                    Some((index, source, archive_cache, region))
+                })
+                .collect();
+            if jobs.is_empty() {
+                return Task::none();
+            }
+            if let Ok(mut status) = STATUS.lock() {
+                *status = Some(Status::Measuring);
+            }
+            Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || {
+                        jobs.into_iter()
+                            .map(|(index, source, archive_cache, region)| {
+                                let result = crate::file_io::decode_path_source(&source, archive_cache.as_deref())
+                                    .and_then(|image| measure_region(&image, region));
+                                (index, result)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .await
+                    .unwrap_or_else(|e| vec![(0, Err(e.to_string()))])
+                },
+                |results| NoiseMessage::Measured(results).into()
+            )
+        }
+        NoiseMessage::Measured(results) => {
+            if let Ok(mut status) = STATUS.lock() {
+                // Dropped if the bar was closed while measuring
+                if status.is_some() {
+                    *status = Some(Status::Done(results));
+                }
+            }
+            Task::none()
+        }
+        NoiseMessage::Close => {
+            if let Ok(mut status) = STATUS.lock() {
+                *status = None;
+            }
+            Task::none()
+        }
+    }
+}
+
+fn report_text(report: &NoiseReport) -> String {
+    format!(
+        "sigma R {:.2}  G {:.2}  B {:.2}   mean {:.0}/{:.0}/{:.0}   {}x{} px",
+        report.sigma[0], report.sigma[1], report.sigma[2],
+        report.mean[0], report.mean[1], report.mean[2],
+        report.width, report.height,
+    )
+}
+
+/// Measurements per pane while the noise bar is open, or nothing
+pub fn view<'a>() -> Element<'a, Message, WinitTheme, Renderer> {
+    let Some(status) = STATUS.lock().ok().and_then(|status| status.clone()) else {
+        return container(text("")).height(0).into();
+    };
+
+    let lines = match status {
+        Status::Measuring => column![text("Measuring noise...").size(13)],
+        Status::Done(results) => {
+            let labeled = results.len() > 1;
+            Column::with_children(results.into_iter().map(|(index, result)| {
+                let prefix = if labeled { format!("Pane {}: ", index + 1) } else { String::new() };
+                let line = match result {
+                    Ok(report) => format!("{}{}", prefix, report_text(&report)),
+                    Err(e) => format!("{}{}", prefix, e),
+                };
+                text(line).size(13).into()
+            }))
+            .spacing(2)
+        }
+    };
+
+    container(
+        row![
+            text("Noise (0-255)").size(13),
+            container(lines).width(Length::Fill),
+            button(text("Measure Again").size(11))
+                .padding([1, 6])
+                .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+                .on_press(NoiseMessage::Measure.into()),
+            button(text("Close").size(11))
+                .padding([1, 6])
+                .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+                .on_press(NoiseMessage::Close.into()),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center)
+    )
+    .padding([4, 8])
+    .width(Length::Fill)
+    .style(|_theme: &WinitTheme| container::Style {
+        text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+        ..container::Style::default()
+    })
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_ignores_gradient() {
+        let width = 64;
+        let pixels: Vec<[f32; 3]> = (0..width * 16)
+            .map(|i| {
+                let v = (i % width) as f32 / width as f32;
+                [v, v * 0.5, 0.25]
+            })
+            .collect();
+        let report = measure(&pixels, width);
+        assert_eq!((report.width, report.height), (64, 16));
+        assert!(report.sigma.iter().all(|&s| s < 1e-3));
+        assert!((report.mean[2] - 63.75).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_measure_uniform_noise() {
+        // Uniform noise of half-width h has sigma h / sqrt(3)
+        let mut seed: u32 = 12345;
+        let mut next = || {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            (seed >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0
+        };
+        let half_width = 0.04;
+        let pixels: Vec<[f32; 3]> = (0..256 * 256)
+            .map(|_| [0.5 + next() * half_width, 0.5, 0.5])
+            .collect();
+        let report = measure(&pixels, 256);
+        let expected = half_width / 3.0f32.sqrt() * 255.0;
+        assert!((report.sigma[0] - expected).abs() / expected < 0.05);
+        assert_eq!(report.sigma[1], 0.0);
+    }
+
+    #[test]
+    fn test_visible_fraction() {
+        // Zoomed 2x and panned so the right half of the image fills the view
+        let bounds = Rectangle { x: 0.0, y: 0.0, width: 100.0, height: 100.0 };
+        let content = Rectangle { x: -100.0, y: -50.0, width: 200.0, height: 200.0 };
+        assert_eq!(visible_fraction(bounds, content), [0.5, 0.25, 1.0, 0.75]);
+    }
+}
@@ -0,0 +1,262 @@
+//! Offscreen frame timing for `--render-offscreen`
+//!
+//! Opens a folder and steps through it one image per frame, turning around at either end,
+//! rendering every frame into a texture instead of a window so no display server is needed.
+//! Rendering happens on the software adapter (lavapipe, WARP or SwiftShader) so results from
+//! different CI machines stay comparable, and tasks run in order on a single-threaded runtime
+//! so every run loads the same images at the same frames.
+//!
+//! Frame and present times also go to `frame_stats`, so `--frame-stats FILE` writes the usual
+//! per-stage report alongside the summary printed on exit.
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use futures::StreamExt;
+use iced_core::clipboard;
+use iced_core::mouse::{self, ScrollDelta};
+use iced_core::{renderer, Color, Event, Font, Pixels, Size, Theme};
+use iced_runtime::task::into_stream;
+use iced_runtime::Action;
+use iced_wgpu::engine::{CompressionStrategy, ImageConfig};
+use iced_wgpu::graphics::Viewport;
+use iced_wgpu::{wgpu, Engine, Renderer};
+use iced_winit::runtime::{program, Debug};
+use log::info;
+
+use crate::app::{DataViewer, IoMessage, Message};
+use crate::config::CONFIG;
+use crate::utils::frame_stats::{self, Stage, StageSummary};
+
+/// Fixed target size, so frame times don't depend on the machine's display
+const WIDTH: u32 = 1280;
+const HEIGHT: u32 = 720;
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+/// Updates one frame may take before its messages are taken to be looping
+const MAX_UPDATES_PER_FRAME: usize = 10_000;
+/// Longest a single task may run before the session is abandoned
+const TASK_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct OffscreenRun {
+    pub directory: PathBuf,
+    pub frames: u32,
+    pub settings_path: Option<String>,
+}
+
+pub struct OffscreenReport {
+    pub adapter: String,
+    /// Opening the folder and loading the first cache window
+    pub open_ms: f64,
+    /// Navigation message to rendered pixels, per frame
+    pub frame: StageSummary,
+    pub present: StageSummary,
+}
+
+fn software_device() -> Result<(wgpu::Adapter, wgpu::Device, wgpu::Queue), String> {
+    futures::executor::block_on(async {
+        let instance = wgpu::Instance::default();
+        let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            force_fallback_adapter: true,
+            compatible_surface: None,
+        }).await.ok_or_else(|| "No software adapter available (lavapipe on Linux, WARP on Windows)".to_string())?;
+        let (device, queue) = adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("Offscreen Device"),
+                required_features: adapter.features() & wgpu::Features::TEXTURE_COMPRESSION_BC,
+                required_limits: adapter.limits(),
+            },
+            None,
+        ).await.map_err(|e| e.to_string())?;
+        Ok((adapter, device, queue))
+    })
+}
+
+struct Offscreen {
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    engine: Engine,
+    renderer: Renderer,
+    state: program::State<DataViewer>,
+    viewport: Viewport,
+    theme: Theme,
+    debug: Debug,
+    target: wgpu::TextureView,
+    runtime: tokio::runtime::Runtime,
+    // Held so the app's channels stay open
+    _renderer_requests: mpsc::Receiver<crate::RendererRequest>,
+    _file_sender: mpsc::Sender<String>,
+}
+
+impl Offscreen {
+    fn new(settings_path: Option<&str>) -> Result<(Self, String), String> {
+        let (adapter, device, queue) = software_device()?;
+        let adapter_info = adapter.get_info();
+        info!("Offscreen rendering on {} ({:?})", adapter_info.name, adapter_info.backend);
+        let device = Arc::new(device);
+        let queue = Arc::new(queue);
+
+        // BC1 is left out since software adapters rarely support it
+        let config = ImageConfig {
+            atlas_size: CONFIG.atlas_size,
+            compression_strategy: CompressionStrategy::None,
+        };
+        let engine = Engine::new(&adapter, &device, &queue, FORMAT, None, Some(config));
+        engine.create_image_cache(&device);
+        crate::register_fonts();
+        let mut renderer = Renderer::new(&device, &engine, Font::with_name("Roboto"), Pixels::from(16));
+
+        let (renderer_sender, renderer_requests) = mpsc::channel();
+        let (file_sender, file_receiver) = mpsc::channel();
+        let app = DataViewer::new(
+            Arc::clone(&device),
+            Arc::clone(&queue),
+            adapter_info.backend,
+            renderer_sender,
+            file_receiver,
+            settings_path,
+            None,
+        );
+        let viewport = Viewport::with_physical_size(Size::new(WIDTH, HEIGHT), 1.0);
+        let mut debug = Debug::new();
+        let state = program::State::new(app, viewport.logical_size(), &mut renderer, &mut debug);
+
+        let target = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Target"),
+            size: wgpu::Extent3d { width: WIDTH, height: HEIGHT, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        }).create_view(&wgpu::TextureViewDescriptor::default());
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        let offscreen = Self {
+            device,
+            queue,
+            engine,
+            renderer,
+            state,
+            viewport,
+            theme: crate::app_theme(),
+            debug,
+            target,
+            runtime,
+            _renderer_requests: renderer_requests,
+            _file_sender: file_sender,
+        };
+        Ok((offscreen, adapter_info.name))
+    }
+
+    /// Updates the UI until no messages are queued, running every task to completion first,
+    /// which a window spreads over several frames
+    fn settle(&mut self) -> Result<(), String> {
+        for _ in 0..MAX_UPDATES_PER_FRAME {
+            let (_, task) = self.state.update(
+                self.viewport.logical_size(),
+                mouse::Cursor::Unavailable,
+                &mut self.renderer,
+                &self.theme,
+                &renderer::Style { text_color: Color::WHITE },
+                &mut clipboard::Null,
+                &mut self.debug,
+            );
+            if let Some(mut stream) = task.and_then(into_stream) {
+                let state = &mut self.state;
+                self.runtime.block_on(async {
+                    loop {
+                        match tokio::time::timeout(TASK_TIMEOUT, stream.next()).await {
+                            Ok(Some(Action::Output(message))) => state.queue_message(message),
+                            // Window and clipboard actions have nothing to act on offscreen
+                            Ok(Some(_)) => {}
+                            Ok(None) => return Ok(()),
+                            Err(_) => return Err(format!("A task did not finish within {:?}", TASK_TIMEOUT)),
+                        }
+                    }
+                })?;
+            }
+            if self.state.is_queue_empty() {
+                return Ok(());
+            }
+        }
+        Err(format!("Messages kept coming after {} updates", MAX_UPDATES_PER_FRAME))
+    }
+
+    /// Presents the current UI into the target and waits for the GPU to finish it
+    fn render(&mut self) {
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Offscreen Encoder"),
+        });
+        self.renderer.present(
+            &mut self.engine,
+            &self.device,
+            &self.queue,
+            &mut encoder,
+            None,
+            FORMAT,
+            &self.target,
+            &self.viewport,
+            &self.debug.overlay(),
+        );
+        self.engine.submit(&self.queue, encoder);
+        self.device.poll(wgpu::Maintain::Wait);
+    }
+
+    fn current_index(&self) -> usize {
+        self.state.program().panes[0].img_cache.current_index
+    }
+}
+
+fn wheel(y: f32) -> Message {
+    Message::Event(Event::Mouse(mouse::Event::WheelScrolled {
+        delta: ScrollDelta::Lines { x: 0.0, y },
+    }))
+}
+
+pub fn run(session: &OffscreenRun) -> Result<OffscreenReport, String> {
+    let (mut offscreen, adapter) = Offscreen::new(session.settings_path.as_deref())?;
+
+    let open_start = Instant::now();
+    offscreen.state.queue_message(Message::Io(IoMessage::FileDropped(0, session.directory.to_string_lossy().to_string())));
+    offscreen.settle()?;
+    offscreen.render();
+    let open_ms = open_start.elapsed().as_secs_f64() * 1000.0;
+    if !offscreen.state.program().panes[0].dir_loaded {
+        return Err(format!("No images could be opened from {}", session.directory.display()));
+    }
+
+    let mut frame_times = Vec::with_capacity(session.frames as usize);
+    let mut present_times = Vec::with_capacity(session.frames as usize);
+    // Wheel down is the next image; the direction flips when an end is reached
+    let mut direction = -1.0;
+    for _ in 0..session.frames {
+        let before = offscreen.current_index();
+        let frame_start = Instant::now();
+        offscreen.state.queue_message(wheel(direction));
+        offscreen.settle()?;
+        let present_start = Instant::now();
+        offscreen.render();
+        let present_time = present_start.elapsed();
+        let frame_time = frame_start.elapsed();
+
+        frame_stats::record(Stage::Present, present_time);
+        frame_stats::record(Stage::Frame, frame_time);
+        present_times.push(present_time.as_secs_f64() * 1000.0);
+        frame_times.push(frame_time.as_secs_f64() * 1000.0);
+        if offscreen.current_index() == before {
+            direction = -direction;
+        }
+    }
+
+    Ok(OffscreenReport {
+        adapter,
+        open_ms,
+        frame: frame_stats::summarize(&frame_times),
+        present: frame_stats::summarize(&present_times),
+    })
+}
@@ -17,7 +17,7 @@ use iced_core::image::{Handle, FilterMethod};
 use iced_core::Vector;
 use iced_widget::center;
 
-use crate::cache::img_cache::PathSource;
+use crate::cache::img_cache::{PathIndex, PathSource};
 use crate::config::CONFIG;
 use crate::app::Message;
 use crate::cache::img_cache::{CachedData, CacheStrategy, ImageCache, ImageMetadata};
@@ -31,6 +31,9 @@ use crate::widgets::viewer;
 use crate::widgets::shader::{image_shader::ImageShader, scene::Scene, cpu_scene::CpuScene};
 use crate::file_io::{self, is_file, is_directory, get_file_index, ImageError};
 use crate::utils::mem;
+use crate::cache::dir_cache;
+use crate::cache::progressive::{self, ProgressiveUpload};
+use crate::loading_handler::FilteredPrefetch;
 use iced_wgpu::engine::CompressionStrategy;
 #[allow(unused_imports)]
 use log::{Level, debug, info, warn, error};
@@ -51,8 +54,8 @@ pub struct Pane {
     pub current_image_metadata: Option<ImageMetadata>, // Metadata for current image (resolution, file size)
     pub is_next_image_loaded: bool, // whether the next image in cache is loaded
     pub is_prev_image_loaded: bool, // whether the previous image in cache is loaded
-    pub slider_value: u16,
-    pub prev_slider_value: u16,
+    pub slider_value: u32,
+    pub prev_slider_value: u32,
     pub is_selected: bool,
     pub is_selected_cache: bool,
     pub scene: Option<Scene>,
@@ -79,7 +82,17 @@ pub struct Pane {
     pub zoom_scale: f32,  // Current zoom scale for bbox rendering
     #[cfg(feature = "coco")]
     pub zoom_offset: Vector,  // Current pan offset for bbox rendering
+    #[cfg(feature = "coco")]
+    pub selected_annotation: Option<u64>,  // Annotation picked by clicking in the overlay
+    #[cfg(feature = "parquet")]
+    pub parquet_labels: std::collections::HashMap<String, Vec<(String, String)>>,  // Per-row label columns of an opened parquet file
     pub loading_started_at: Option<Instant>,  // When loading started (for spinner delay)
+    pub filtered_prefetch: FilteredPrefetch,  // Filter matches loaded ahead of filtered navigation
+    pub progressive: Option<ProgressiveUpload>,  // Initial image still being decoded into its texture
+    pub raw_pairs: std::collections::HashMap<PathBuf, PathBuf>,  // Left-out member of each RAW+JPEG pair, keyed by the listed one
+    pub listing_id: u64,  // Background directory listing whose results this pane takes, 0 for none
+    pub indexing: Option<usize>,  // Images found so far while a large folder is still being listed
+    pub playlist: Option<crate::playlist::PlaylistSource>,  // Origin of a list that isn't a folder, for reordering it
 }
 
 impl Default for Pane {
@@ -121,7 +134,17 @@ impl Default for Pane {
             zoom_scale: 1.0,
             #[cfg(feature = "coco")]
             zoom_offset: Vector::default(),
+            #[cfg(feature = "coco")]
+            selected_annotation: None,
+            #[cfg(feature = "parquet")]
+            parquet_labels: std::collections::HashMap::new(),
             loading_started_at: None,
+            filtered_prefetch: FilteredPrefetch::default(),
+            progressive: None,
+            raw_pairs: std::collections::HashMap::new(),
+            listing_id: 0,
+            indexing: None,
+            playlist: None,
         }
     }
 }
@@ -175,7 +198,17 @@ impl Pane {
             zoom_scale: 1.0,
             #[cfg(feature = "coco")]
             zoom_offset: Vector::default(),
+            #[cfg(feature = "coco")]
+            selected_annotation: None,
+            #[cfg(feature = "parquet")]
+            parquet_labels: std::collections::HashMap::new(),
             loading_started_at: None,
+            filtered_prefetch: FilteredPrefetch::default(),
+            progressive: None,
+            raw_pairs: std::collections::HashMap::new(),
+            listing_id: 0,
+            indexing: None,
+            playlist: None,
         }
     }
 
@@ -200,6 +233,12 @@ impl Pane {
         self.slider_image = None;
         self.slider_image_position = None;
 
+        // Keep the decoded window around in case this directory is opened again,
+        // unless the current image is still half decoded
+        if let (true, Some(directory), None) = (self.dir_loaded, &self.directory_path, &self.progressive) {
+            dir_cache::remember(directory, &self.img_cache);
+        }
+
         // Explicitly reset the image cache
         self.img_cache.clear_cache();
         self.img_cache = ImageCache::default();
@@ -211,6 +250,14 @@ impl Pane {
         // Reset archive cache to a fresh instance
         self.archive_cache = Arc::new(Mutex::new(ArchiveCache::new()));
         self.has_compressed_file = false;
+        self.filtered_prefetch.clear();
+        self.progressive = None;
+        self.raw_pairs.clear();
+        self.listing_id = 0;
+        self.indexing = None;
+        self.playlist = None;
+        #[cfg(feature = "parquet")]
+        self.parquet_labels.clear();
 
         // Reset other state
         self.directory_path = None;
@@ -307,113 +354,62 @@ impl Pane {
     }
 
     pub fn render_next_image(&mut self, pane_layout: &PaneLayout, is_slider_dual: bool) -> bool {
-        let mut did_render_happen = false;
-
         self.img_cache.print_cache();
+        debug!("BEGINE RENDERING NEXT: current_index: {}, current_offset: {}",
+            self.img_cache.current_index, self.img_cache.current_offset);
 
-        // Safely compute target index as isize
-        let target_index_isize = self.img_cache.cache_count as isize + self.img_cache.current_offset + 1;
-        if target_index_isize >= 0 {
-            let next_image_index_to_render = (self.img_cache.cache_count as isize + self.img_cache.current_offset + 1) as usize;
-            debug!("BEGINE RENDERING NEXT: next_image_index_to_render: {} current_index: {}, current_offset: {}",
-                next_image_index_to_render, self.img_cache.current_index, self.img_cache.current_offset);
-
-            // Clone the cached image to release the borrow before calling setup_scene_for_image
-            let cached_image = self.img_cache.get_image_by_index(next_image_index_to_render)
-                .cloned();
-
-            match cached_image {
-                Ok(data) => {
-                    self.setup_scene_for_image(&data);
-                }
-                Err(_) => {
-                    debug!("Failed to retrieve next cached image.");
-                    return false;
-                }
-            }
-
-            self.img_cache.current_offset += 1;
-
-            // Since the next image is loaded and rendered, mark the is_next_image_loaded flag
-            self.is_next_image_loaded = true;
-            did_render_happen = true;
+        let Some(data) = self.img_cache.step_next() else {
+            debug!("Failed to retrieve next cached image.");
+            return false;
+        };
+        self.setup_scene_for_image(&data);
 
-            // Handle current_index
-            if self.img_cache.current_index < self.img_cache.image_paths.len() - 1 {
-                self.img_cache.current_index += 1;
-            }
+        // Since the next image is loaded and rendered, mark the is_next_image_loaded flag
+        self.is_next_image_loaded = true;
 
-            // Track which index current_image contains (after current_index is updated)
-            self.current_image_index = Some(self.img_cache.current_index);
+        // Track which index current_image contains (after current_index is updated)
+        self.current_image_index = Some(self.img_cache.current_index);
+        record_view(&self.img_cache);
 
-            // Update metadata from cache
-            self.current_image_metadata = self.img_cache.get_initial_metadata().cloned();
+        // Update metadata from cache
+        self.current_image_metadata = self.img_cache.get_initial_metadata().cloned();
 
-            if *pane_layout == PaneLayout::DualPane && is_slider_dual {
-                self.slider_value = self.img_cache.current_index as u16;
-            }
-            debug!("END RENDERING NEXT: current_index: {}, current_offset: {}", self.img_cache.current_index, self.img_cache.current_offset);
+        if *pane_layout == PaneLayout::DualPane && is_slider_dual {
+            self.slider_value = self.img_cache.current_index as u32;
         }
+        debug!("END RENDERING NEXT: current_index: {}, current_offset: {}", self.img_cache.current_index, self.img_cache.current_offset);
 
-        did_render_happen
+        true
     }
 
     pub fn render_prev_image(&mut self, pane_layout: &PaneLayout, is_slider_dual: bool) -> bool {
-        let mut did_render_happen = false;
-
-        // Render the previous one right away
-        // Avoid loading around the edges
-        if self.img_cache.cache_count as isize + self.img_cache.current_offset > 0 &&
-            self.img_cache.is_some_at_index((self.img_cache.cache_count as isize + self.img_cache.current_offset) as usize) {
-
-            let next_image_index_to_render = self.img_cache.cache_count as isize + (self.img_cache.current_offset - 1);
-            debug!("RENDERING PREV: next_image_index_to_render: {} current_index: {}, current_offset: {}",
-                next_image_index_to_render, self.img_cache.current_index, self.img_cache.current_offset);
-
-            if self.img_cache.is_image_index_within_bounds(next_image_index_to_render) {
-                // Clone the cached image to release the borrow before calling setup_scene_for_image
-                let cached_image = self.img_cache.get_image_by_index(next_image_index_to_render as usize)
-                    .cloned();
+        debug!("RENDERING PREV: current_index: {}, current_offset: {}",
+            self.img_cache.current_index, self.img_cache.current_offset);
 
-                match cached_image {
-                    Ok(data) => {
-                        self.setup_scene_for_image(&data);
-                    }
-                    Err(_) => {
-                        debug!("Failed to retrieve prev cached image.");
-                        return false;
-                    }
-                }
-
-                self.img_cache.current_offset -= 1;
-
-                assert!(self.img_cache.current_offset >= -(self.img_cache.cache_count as isize)); // Check against actual cache size, not static CONFIG
-
-                // Since the prev image is loaded and rendered, mark the is_prev_image_loaded flag
-                self.is_prev_image_loaded = true;
-
-                if self.img_cache.current_index > 0 {
-                    self.img_cache.current_index -= 1;
-                }
+        let Some(data) = self.img_cache.step_prev() else {
+            debug!("Failed to retrieve prev cached image.");
+            return false;
+        };
+        self.setup_scene_for_image(&data);
 
-                // Track which index current_image contains (after current_index is updated)
-                self.current_image_index = Some(self.img_cache.current_index);
+        // Since the prev image is loaded and rendered, mark the is_prev_image_loaded flag
+        self.is_prev_image_loaded = true;
 
-                // Update metadata from cache
-                self.current_image_metadata = self.img_cache.get_initial_metadata().cloned();
+        // Track which index current_image contains (after current_index is updated)
+        self.current_image_index = Some(self.img_cache.current_index);
+        record_view(&self.img_cache);
 
-                debug!("RENDERED PREV: current_index: {}, current_offset: {}",
-                self.img_cache.current_index, self.img_cache.current_offset);
+        // Update metadata from cache
+        self.current_image_metadata = self.img_cache.get_initial_metadata().cloned();
 
-                if *pane_layout == PaneLayout::DualPane && is_slider_dual {
-                    self.slider_value = self.img_cache.current_index as u16;
-                }
+        debug!("RENDERED PREV: current_index: {}, current_offset: {}",
+        self.img_cache.current_index, self.img_cache.current_offset);
 
-                did_render_happen = true;
-            }
+        if *pane_layout == PaneLayout::DualPane && is_slider_dual {
+            self.slider_value = self.img_cache.current_index as u32;
         }
 
-        did_render_happen
+        true
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -430,7 +426,7 @@ impl Pane {
         _pane_index: usize,
         path: &PathBuf,
         is_slider_dual: bool,
-        slider_value: &mut u16,
+        slider_value: &mut u32,
         cache_size: usize,
         archive_cache_size: u64,
         archive_warning_threshold_mb: u64,
@@ -482,6 +478,20 @@ impl Pane {
                         },
                     }
                 }
+                #[cfg(feature = "parquet")]
+                Some("parquet") => {
+                    let mut archive_cache = self.archive_cache.lock().unwrap();
+                    match read_parquet_path(path, &mut file_paths, &mut archive_cache, archive_warning_threshold_mb) {
+                        Ok(labels) => {
+                            archive = ArchiveType::Parquet;
+                            self.parquet_labels = labels;
+                        },
+                        Err(e) => {
+                            error!("Failed to read parquet file: {e}");
+                            return Task::none();
+                        },
+                    }
+                }
                 _ => {
                     error!("File extension not supported");
                     return Task::none();
@@ -518,6 +528,8 @@ impl Pane {
                         // Parent directory access failed (likely sandboxed), create a single-file list
                         debug!("❌ Parent directory access denied (error: {}), creating single-file cache", e);
                         debug!("This is likely due to App Store sandboxing - only the selected file is accessible");
+                        #[cfg(target_os = "macos")]
+                        crate::access_prompt::report(path, directory, _pane_index);
                         let file_path = path.to_string_lossy().to_string();
                         let single_file_list = vec![path.clone()];
                         debug!("Single-file cache created with 1 image: {}", file_path);
@@ -542,10 +554,14 @@ impl Pane {
 
             // Handle the result from get_image_paths
             file_paths = match paths_result {
-                Ok(paths) => paths.iter().map(|item| {
-                    // Regular filesystem files - use Filesystem variant
-                    PathSource::Filesystem(item.to_path_buf())
-                }).collect::<Vec<_>>(),
+                Ok(paths) => {
+                    let (paths, raw_pairs) = crate::raw_pairs::apply(paths, is_file(path).then_some(path.as_path()));
+                    self.raw_pairs = raw_pairs;
+                    paths.iter().map(|item| {
+                        // Regular filesystem files - use Filesystem variant
+                        PathSource::Filesystem(item.to_path_buf())
+                    }).collect::<Vec<_>>()
+                }
                 Err(ImageError::NoImagesFound) => {
                     error!("No supported images found in directory");
                     // TODO: Show a message to the user that no images were found
@@ -553,7 +569,10 @@ impl Pane {
                 }
                 Err(e) => {
                     error!("Error reading directory: {e}");
-                    // TODO: Show error message to user
+                    #[cfg(target_os = "macos")]
+                    if let ImageError::DirectoryError(_) = e {
+                        crate::access_prompt::report(path, path, _pane_index);
+                    }
                     return Task::none();
                 }
             };
@@ -598,7 +617,7 @@ impl Pane {
 
         // Instantiate a new image cache based on GPU support
         let mut img_cache = ImageCache::new(
-            &file_paths,
+            file_paths,
             cache_size,
             cache_strategy,
             compression_strategy,
@@ -610,20 +629,28 @@ impl Pane {
         // Track memory before loading initial images
         mem::log_memory("Pane::initialize_dir_path: Before loading initial image");
 
-        // Load only the first/dropped image synchronously for immediate display
-        let mut archive_guard = self.archive_cache.lock().unwrap();
-        let archive_cache = if self.has_compressed_file {
-            Some(&mut *archive_guard)
-        } else {
-            None
-        };
+        // Load only the first/dropped image synchronously for immediate display,
+        // unless it is still cached from a recent visit or large enough to show progressively
+        let restored = self.directory_path.as_deref()
+            .is_some_and(|directory| dir_cache::restore(directory, &mut img_cache, cache_strategy));
+        if !restored && !self.has_compressed_file {
+            self.progressive = progressive::begin(&mut img_cache, cache_strategy, device);
+        }
+        if !restored && self.progressive.is_none() {
+            let mut archive_guard = self.archive_cache.lock().unwrap();
+            let archive_cache = if self.has_compressed_file {
+                Some(&mut *archive_guard)
+            } else {
+                None
+            };
 
-        if let Err(e) = img_cache.load_single_image(archive_cache) {
-            error!("Failed to load initial image: {}", e);
-            drop(archive_guard); // Release the lock before returning
-            return Task::none();
+            if let Err(e) = img_cache.load_single_image(archive_cache) {
+                error!("Failed to load initial image: {}", e);
+                drop(archive_guard); // Release the lock before returning
+                return Task::none();
+            }
+            drop(archive_guard); // Release the lock after loading
         }
-        drop(archive_guard); // Release the lock after loading
 
         mem::log_memory("Pane::initialize_dir_path: After loading initial image");
 
@@ -644,6 +671,7 @@ impl Pane {
         if let Ok(initial_image) = img_cache.get_initial_image() {
             // Track which index this initial image represents
             self.current_image_index = Some(img_cache.current_index);
+            record_view(&img_cache);
 
             // Set metadata for the initial image
             self.current_image_metadata = img_cache.get_initial_metadata().cloned();
@@ -654,7 +682,7 @@ impl Pane {
         }
 
         // Update slider value
-        let current_slider_value = initial_index as u16;
+        let current_slider_value = initial_index as u32;
         debug!("current_slider_value: {:?}", current_slider_value);
         if is_slider_dual {
             self.slider_value = current_slider_value;
@@ -663,8 +691,7 @@ impl Pane {
         }
         debug!("slider_value: {:?}", *slider_value);
 
-        let file_paths = img_cache.image_paths.clone();
-        debug!("file_paths.len() {:?}", file_paths.len());
+        debug!("file_paths.len() {:?}", img_cache.image_paths.len());
 
         self.img_cache = img_cache;
         debug!("img_cache.cache_count {:?}", self.img_cache.cache_count);
@@ -686,27 +713,23 @@ impl Pane {
         pane_layout: &PaneLayout,
         pane_file_lengths: &[usize],
         _pane_index: usize,
-        image_paths: Vec<PathBuf>,
+        file_paths: PathIndex,
         directory_path: String,
         initial_index: usize,
         is_slider_dual: bool,
-        slider_value: &mut u16,
+        slider_value: &mut u32,
         cache_size: usize,
     ) {
         mem::log_memory("Before pane initialization with paths");
 
-        // Convert PathBuf to PathSource
-        let file_paths: Vec<PathSource> = image_paths.iter()
-            .map(|p| PathSource::Filesystem(p.clone()))
-            .collect();
-
         if file_paths.is_empty() {
             error!("No images in enumerated paths");
             return;
         }
 
         self.directory_path = Some(directory_path);
-        self.has_compressed_file = false;
+        // Preloaded content (an in-memory source) is read from the pane's archive cache
+        self.has_compressed_file = matches!(file_paths.get(0), Some(PathSource::Preloaded(_)));
 
         let longest_file_length = pane_file_lengths.iter().max().unwrap_or(&0);
 
@@ -727,7 +750,7 @@ impl Pane {
 
         // Instantiate a new image cache with pre-enumerated paths
         let mut img_cache = ImageCache::new(
-            &file_paths,
+            file_paths,
             cache_size,
             cache_strategy,
             compression_strategy,
@@ -738,11 +761,24 @@ impl Pane {
 
         mem::log_memory("Pane::initialize_with_paths: Before loading initial image");
 
-        // Load only the first/dropped image synchronously for immediate display
-        // No archive cache since this is for regular directories
-        if let Err(e) = img_cache.load_single_image(None) {
-            error!("Failed to load initial image: {}", e);
-            return;
+        // Load only the first/dropped image synchronously for immediate display,
+        // unless it is still cached from a recent visit or large enough to show progressively
+        let restored = self.directory_path.as_deref()
+            .is_some_and(|directory| dir_cache::restore(directory, &mut img_cache, cache_strategy));
+        if !restored && !self.has_compressed_file {
+            self.progressive = progressive::begin(&mut img_cache, cache_strategy, device);
+        }
+        if !restored && self.progressive.is_none() {
+            let mut archive_guard = self.archive_cache.lock().unwrap();
+            let archive_cache = if self.has_compressed_file {
+                Some(&mut *archive_guard)
+            } else {
+                None
+            };
+            if let Err(e) = img_cache.load_single_image(archive_cache) {
+                error!("Failed to load initial image: {}", e);
+                return;
+            }
         }
 
         mem::log_memory("Pane::initialize_with_paths: After loading initial image");
@@ -750,6 +786,7 @@ impl Pane {
         // Set up scene with initial image
         if let Ok(initial_image) = img_cache.get_initial_image() {
             self.current_image_index = Some(img_cache.current_index);
+            record_view(&img_cache);
             self.current_image_metadata = img_cache.get_initial_metadata().cloned();
             self.setup_scene_for_image(initial_image);
         } else {
@@ -757,7 +794,7 @@ impl Pane {
         }
 
         // Update slider value
-        let current_slider_value = initial_index as u16;
+        let current_slider_value = initial_index as u32;
         debug!("current_slider_value: {:?}", current_slider_value);
         if is_slider_dual {
             self.slider_value = current_slider_value;
@@ -769,6 +806,14 @@ impl Pane {
         debug!("img_cache.cache_count {:?}", self.img_cache.cache_count);
     }
 
+    /// Image count for the footer, marked with "+" while the folder is still being listed
+    pub fn file_count_label(&self) -> String {
+        match self.indexing {
+            Some(found) => format!("{}+", found.max(self.img_cache.num_files)),
+            None => self.img_cache.num_files.to_string(),
+        }
+    }
+
     pub fn build_ui_container(&self, use_slider_image_for_render: bool, is_horizontal_split: bool, double_click_threshold_ms: u16, use_nearest_filter: bool) -> iced_winit::core::Element<'_, Message, WinitTheme, Renderer> {
         if self.dir_loaded {
             if use_slider_image_for_render && self.slider_image.is_some() {
@@ -798,7 +843,8 @@ impl Pane {
                         .horizontal_split(is_horizontal_split)
                         .with_interaction_state(self.mouse_wheel_zoom, self.ctrl_pressed)
                         .double_click_threshold_ms(double_click_threshold_ms)
-                        .use_nearest_filter(use_nearest_filter);
+                        .use_nearest_filter(use_nearest_filter)
+                        .zoom_lock_pane(self.pane_id);
 
                 #[cfg(not(feature = "coco"))]
                 let shader_widget = ImageShader::new(Some(scene))
@@ -808,7 +854,8 @@ impl Pane {
                         .horizontal_split(is_horizontal_split)
                         .with_interaction_state(self.mouse_wheel_zoom, self.ctrl_pressed)
                         .double_click_threshold_ms(double_click_threshold_ms)
-                        .use_nearest_filter(use_nearest_filter);
+                        .use_nearest_filter(use_nearest_filter)
+                        .zoom_lock_pane(self.pane_id);
 
                 // Set up zoom change callback for COCO bbox rendering
                 #[cfg(feature = "coco")]
@@ -843,6 +890,13 @@ impl Pane {
     }
 }
 
+/// Count the image at the current index in the usage statistics
+fn record_view(img_cache: &ImageCache) {
+    if let Some(source) = img_cache.image_paths.get(img_cache.current_index) {
+        crate::usage_stats::record_view(source.path());
+    }
+}
+
 #[allow(dead_code)]
 pub fn get_pane_with_largest_dir_size(panes: &Vec<&mut Pane>) -> isize {
     let mut max_dir_size = 0;
@@ -873,20 +927,12 @@ pub fn get_master_slider_value(panes: &[&mut Pane],
 
 fn read_zip_path(path: &PathBuf, file_paths: &mut Vec<PathSource>, archive_cache: &mut ArchiveCache, archive_cache_size: u64) -> Result<(), Box<dyn Error>> {
     use std::io::Read;
-    let mut files = Vec::new();
     let mut archive = zip::ZipArchive::new(std::io::BufReader::new(
-        File::open(path)?))?;
-    let mut image_names = Vec::new();
+        File::open(crate::file_io::long_path(path))?))?;
 
     // First pass: collect all image files and their sizes
-    for i in 0..archive.len() {
-        let file = archive.by_index(i)?;
-        if file.is_file() && supported_image(file.name()) {
-            let filename = file.name().to_string();
-            image_names.push(filename);
-            files.push(file.size());
-        }
-    }
+    let (image_names, files): (Vec<String>, Vec<u64>) =
+        viewskater_core::archive_index::zip_entries(&mut archive, supported_image)?.into_iter().unzip();
 
     // Set up the archive cache for this ZIP file
     archive_cache.set_current_archive(path.clone(), ArchiveType::Zip);
@@ -914,7 +960,8 @@ fn read_zip_path(path: &PathBuf, file_paths: &mut Vec<PathSource>, archive_cache
 }
 
 fn read_rar_path(path: &PathBuf, file_paths: &mut Vec<PathSource>, archive_cache: &mut ArchiveCache, archive_cache_size: u64) -> Result<(), Box<dyn Error>> {
-    let archive = unrar::Archive::new(path)
+    let archive_path = crate::file_io::long_path(path).into_owned();
+    let archive = unrar::Archive::new(&archive_path)
         .open_for_listing()?;
     let mut files = Vec::new();
     let mut image_names = Vec::new();
@@ -942,7 +989,7 @@ fn read_rar_path(path: &PathBuf, file_paths: &mut Vec<PathSource>, archive_cache
 
         if will_preload {
             // Small archive - preload the data and use Preloaded variant
-            let mut archive = unrar::Archive::new(path)
+            let mut archive = unrar::Archive::new(&archive_path)
                 .open_for_processing()?;
             while let Some(process) = archive.read_header()? {
                 archive = if *name == process.entry().filename.as_os_str().to_string_lossy() {
@@ -968,21 +1015,16 @@ fn read_7z_path(path: &PathBuf, file_paths: &mut Vec<PathSource>, archive_cache:
     use std::thread;
     use std::io::Read;
     let password = sevenz_rust2::Password::empty();
-    let mut file = File::open(path)?;
+    let archive_path = crate::file_io::long_path(path);
+    let mut file = File::open(&archive_path)?;
     let archive = sevenz_rust2::Archive::read(&mut file, &password)?;
     let is_solid = archive.is_solid;
-    let mut files = Vec::new();
-    let mut image_names = Vec::new();
     // Set up the archive cache for this 7Z file
     archive_cache.set_current_archive(path.clone(), ArchiveType::SevenZ);
 
     // First pass: collect all image files and their sizes
-    for entry in archive.files.iter() {
-        if !entry.is_directory && supported_image(entry.name()) {
-            files.push(entry.size());
-            image_names.push(entry.name());
-        }
-    }
+    let (image_names, files): (Vec<String>, Vec<u64>) =
+        viewskater_core::archive_index::sevenz_entries(&archive, supported_image).into_iter().unzip();
 
     let image_size = files.iter().sum::<u64>();
     debug!("Total image size: {}mb", image_size / 1_000_000);
@@ -1017,7 +1059,7 @@ fn read_7z_path(path: &PathBuf, file_paths: &mut Vec<PathSource>, archive_cache:
         for block_index in 0..block_count {
             thread::scope(|s| {
                 s.spawn(||{
-                    let mut source = File::open(path).unwrap();
+                    let mut source = File::open(&archive_path).unwrap();
                     // 2. For decoders that supports it, we can set the thread_count on the block decoder
                     //    so that it uses multiple threads to decode the block. Currently only LZMA2 is
                     //    supporting this. We try to use all threads report from std::thread.
@@ -1055,4 +1097,32 @@ fn read_7z_path(path: &PathBuf, file_paths: &mut Vec<PathSource>, archive_cache:
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Open a parquet dataset: every image row is preloaded, label columns are returned per image
+#[cfg(feature = "parquet")]
+fn read_parquet_path(
+    path: &Path,
+    file_paths: &mut Vec<PathSource>,
+    archive_cache: &mut ArchiveCache,
+    archive_warning_threshold_mb: u64,
+) -> Result<std::collections::HashMap<String, Vec<(String, String)>>, Box<dyn Error>> {
+    // Rows are decoded into memory up front, so warn before loading large files
+    let size_mb = crate::parquet_dataset::estimated_size(path)? / 1_000_000;
+    if size_mb > archive_warning_threshold_mb {
+        let (available_gb, is_recommended) = mem::check_memory_for_archive(size_mb);
+        if !file_io::show_memory_warning_sync(size_mb, available_gb, is_recommended) {
+            return Err("User cancelled loading large parquet file".into());
+        }
+    }
+
+    archive_cache.set_current_archive(path.to_path_buf(), ArchiveType::Parquet);
+
+    let dataset = crate::parquet_dataset::read_parquet_dataset(path)?;
+    for (name, data) in dataset.images {
+        file_paths.push(PathSource::Preloaded(PathBuf::from(&name)));
+        archive_cache.add_preloaded_data(name, data);
+    }
+
+    Ok(dataset.labels)
+}
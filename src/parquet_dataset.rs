@@ -0,0 +1,139 @@
+/// Parquet image datasets
+///
+/// Reads parquet files where each row holds an encoded image (a binary column, or a
+/// Hugging Face style `{bytes, path}` struct) next to label columns. Rows are preloaded
+/// like a small archive and named `<row>_<name>` so the usual natural sort keeps the
+/// row order; the remaining scalar columns are kept as per-image metadata.
+use std::collections::HashMap;
+use std::path::Path;
+use log::{debug, info};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::Field;
+
+use crate::file_io::supported_image;
+
+/// Rows of a parquet dataset, ready to be preloaded into an archive cache
+#[derive(Default)]
+pub struct ParquetDataset {
+    /// (image name, encoded bytes) in row order
+    pub images: Vec<(String, Vec<u8>)>,
+    /// Image name -> (column, value) pairs for the metadata panel
+    pub labels: HashMap<String, Vec<(String, String)>>,
+}
+
+fn open(path: &Path) -> Result<SerializedFileReader<std::fs::File>, String> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    SerializedFileReader::new(file)
+        .map_err(|e| format!("Failed to read parquet {}: {}", path.display(), e))
+}
+
+/// Uncompressed size of all row groups, used for the large-file warning before loading
+pub fn estimated_size(path: &Path) -> Result<u64, String> {
+    let reader = open(path)?;
+    Ok(reader.metadata().row_groups().iter()
+        .map(|rg| rg.total_byte_size().max(0) as u64)
+        .sum())
+}
+
+/// Read every row that contains a decodable image
+pub fn read_parquet_dataset(path: &Path) -> Result<ParquetDataset, String> {
+    let reader = open(path)?;
+    let num_rows = reader.metadata().file_metadata().num_rows().max(0) as usize;
+    let width = num_rows.max(1).to_string().len();
+
+    let mut dataset = ParquetDataset::default();
+    let rows = reader.get_row_iter(None).map_err(|e| e.to_string())?;
+    for (row_index, row) in rows.enumerate() {
+        let row = row.map_err(|e| e.to_string())?;
+
+        let mut image: Option<(Vec<u8>, Option<String>)> = None;
+        let mut labels = Vec::new();
+        for (column, field) in row.get_column_iter() {
+            match field {
+                Field::Bytes(bytes) if image.is_none() && looks_like_image(bytes.data()) => {
+                    image = Some((bytes.data().to_vec(), None));
+                }
+                // Hugging Face `datasets` stores images as struct { bytes, path }
+                Field::Group(group) if image.is_none() && image_from_group(group).is_some() => {
+                    image = image_from_group(group);
+                }
+                Field::Bytes(_) | Field::Group(_) | Field::ListInternal(_) | Field::MapInternal(_) => {}
+                _ => labels.push((column.clone(), field_to_string(field))),
+            }
+        }
+
+        let Some((bytes, path_hint)) = image else {
+            debug!("Parquet row {} has no image column, skipping", row_index);
+            continue;
+        };
+
+        let name = image_name(row_index, width, path_hint.as_deref(), &bytes);
+        if !labels.is_empty() {
+            dataset.labels.insert(name.clone(), labels);
+        }
+        dataset.images.push((name, bytes));
+    }
+
+    info!("Read {} image rows from {}", dataset.images.len(), path.display());
+    Ok(dataset)
+}
+
+fn looks_like_image(bytes: &[u8]) -> bool {
+    image::guess_format(bytes).is_ok()
+}
+
+fn image_from_group(group: &parquet::record::Row) -> Option<(Vec<u8>, Option<String>)> {
+    let mut bytes = None;
+    let mut path = None;
+    for (name, field) in group.get_column_iter() {
+        match (name.as_str(), field) {
+            ("bytes", Field::Bytes(b)) if looks_like_image(b.data()) => bytes = Some(b.data().to_vec()),
+            ("path", Field::Str(p)) => path = Some(p.clone()),
+            _ => {}
+        }
+    }
+    bytes.map(|b| (b, path))
+}
+
+/// Unique, sortable name for a row: `<zero-padded row>_<file name>`
+///
+/// Falls back to `row.<ext>` with the extension guessed from the bytes when the
+/// row carries no usable path.
+fn image_name(row_index: usize, width: usize, path_hint: Option<&str>, bytes: &[u8]) -> String {
+    let base = path_hint
+        .and_then(|p| Path::new(p).file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .filter(|n| supported_image(n))
+        .unwrap_or_else(|| {
+            let extension = image::guess_format(bytes).ok()
+                .and_then(|f| f.extensions_str().first().copied())
+                .unwrap_or("png");
+            format!("row.{}", extension)
+        });
+    format!("{:0width$}_{}", row_index, base, width = width)
+}
+
+fn field_to_string(field: &Field) -> String {
+    match field {
+        // Display wraps strings in quotes
+        Field::Str(s) => s.clone(),
+        Field::Null => "null".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_name_keeps_row_order() {
+        let png = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        assert_eq!(image_name(7, 4, Some("train/cat.jpg"), &png), "0007_cat.jpg");
+        assert_eq!(image_name(7, 4, None, &png), "0007_row.png");
+        // Paths without an image extension fall back to the guessed format
+        assert_eq!(image_name(12, 3, Some("sample"), &png), "012_row.png");
+        assert!(image_name(9, 2, None, &png) < image_name(10, 2, None, &png));
+    }
+}
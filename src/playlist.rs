@@ -0,0 +1,186 @@
+/// Reordering of image lists that aren't a folder
+///
+/// A pane opened from an explicit path list (`--stdin-list`) or from favorites shows its
+/// images in the order they were given rather than by name, so that order can be changed:
+/// the Playlist section of the sidebar lists the pane's images with a drag handle each, and
+/// dropping one on another row moves it there. Favorites keep the new order in
+/// `favorites.yaml`; a path list is written to `<config dir>/viewskater/playlist.txt`, one
+/// path per line, which `viewskater --stdin-list < playlist.txt` opens again.
+use std::path::PathBuf;
+use iced_winit::core::{Element, Length, Alignment, Color, Font};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{column, container, mouse_area, row, text};
+use log::{info, warn};
+
+use crate::app::{DataViewer, Message};
+use crate::pane::Pane;
+
+const PLAYLIST_FILE_NAME: &str = "playlist.txt";
+/// Longer lists are too slow to lay out as rows
+const MAX_ROWS: usize = 2_000;
+
+/// Where the list of a pane came from, so a new order can be kept there
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistSource {
+    PathList,
+    Favorites,
+}
+
+/// Row being dragged and the row under the cursor
+#[derive(Debug, Default)]
+pub struct PlaylistDrag {
+    pane_index: usize,
+    from: Option<usize>,
+    over: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub enum PlaylistMessage {
+    Grab(usize, usize),
+    Hover(usize),
+    Drop,
+}
+
+impl From<PlaylistMessage> for Message {
+    fn from(msg: PlaylistMessage) -> Self {
+        Message::PlaylistAction(msg)
+    }
+}
+
+fn playlist_path() -> PathBuf {
+    crate::app_dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("viewskater")
+        .join(PLAYLIST_FILE_NAME)
+}
+
+fn save_path_list(paths: &[PathBuf]) {
+    let path = playlist_path();
+    let lines: String = paths.iter().map(|p| format!("{}\n", p.display())).collect();
+    let result = path.parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, lines));
+    match result {
+        Ok(()) => info!("Saved playlist order to {}", path.display()),
+        Err(e) => warn!("Failed to save playlist to {}: {}", path.display(), e),
+    }
+}
+
+/// Move the entry at `from` so it ends up at `to`
+fn move_entry<T>(entries: &mut Vec<T>, from: usize, to: usize) {
+    let entry = entries.remove(from);
+    entries.insert(to, entry);
+}
+
+/// Position of the entry that was at `index` after `move_entry(from, to)`
+fn moved_index(index: usize, from: usize, to: usize) -> usize {
+    if index == from {
+        to
+    } else if from < index && index <= to {
+        index - 1
+    } else if to <= index && index < from {
+        index + 1
+    } else {
+        index
+    }
+}
+
+pub fn handle_playlist_message(app: &mut DataViewer, msg: PlaylistMessage) -> Task<Message> {
+    let drag = &mut app.playlist_drag;
+    match msg {
+        PlaylistMessage::Grab(pane_index, index) => {
+            *drag = PlaylistDrag { pane_index, from: Some(index), over: Some(index) };
+            Task::none()
+        }
+        PlaylistMessage::Hover(index) => {
+            if drag.from.is_some() {
+                drag.over = Some(index);
+            }
+            Task::none()
+        }
+        PlaylistMessage::Drop => {
+            let pane_index = drag.pane_index;
+            let (Some(from), Some(to)) = (drag.from.take(), drag.over.take()) else {
+                return Task::none();
+            };
+            let Some(pane) = app.panes.get(pane_index) else {
+                return Task::none();
+            };
+            let (Some(source), true) = (pane.playlist, from != to && to < pane.img_cache.image_paths.len()) else {
+                return Task::none();
+            };
+
+            let mut paths: Vec<PathBuf> = pane.img_cache.image_paths.iter().map(|p| p.path().clone()).collect();
+            move_entry(&mut paths, from, to);
+            let current_index = moved_index(pane.img_cache.current_index, from, to);
+            match source {
+                PlaylistSource::Favorites => app.favorites.reorder(&paths),
+                PlaylistSource::PathList => save_path_list(&paths),
+            }
+            app.open_path_list(paths, pane_index, current_index, source)
+        }
+    }
+}
+
+/// Rows with drag handles for the list of `pane`, or None when it is a folder
+pub fn section<'a>(drag: &PlaylistDrag, pane: &'a Pane, pane_index: usize) -> Option<Element<'a, Message, WinitTheme, Renderer>> {
+    pane.playlist?;
+    let paths = &pane.img_cache.image_paths;
+    if paths.len() > MAX_ROWS {
+        return Some(text(format!("{} images, too many to reorder here", paths.len())).size(12).into());
+    }
+
+    let dragging = drag.from.filter(|_| drag.pane_index == pane_index);
+    let handle_style = |theme: &WinitTheme| iced_widget::text::Style {
+        color: Some(theme.extended_palette().background.strong.color),
+    };
+    let rows = paths.iter().enumerate().fold(column![].spacing(1), |rows, (index, source)| {
+        let is_current = index == pane.img_cache.current_index;
+        let is_target = dragging.is_some() && drag.over == Some(index);
+        let handle = mouse_area(text("::").size(12).font(Font::MONOSPACE).style(handle_style))
+            .on_press(PlaylistMessage::Grab(pane_index, index).into());
+        let label = text(source.file_name().to_string()).size(12);
+        let entry = container(row![handle, label].spacing(6).align_y(Alignment::Center))
+            .padding([1, 4])
+            .width(Length::Fill)
+            .style(move |theme: &WinitTheme| {
+                let background = if is_target {
+                    Some(theme.extended_palette().primary.weak.color.into())
+                } else if is_current {
+                    Some(Color::from_rgba(1.0, 1.0, 1.0, 0.08).into())
+                } else {
+                    None
+                };
+                container::Style { background, ..container::Style::default() }
+            });
+        rows.push(mouse_area(entry).on_enter(PlaylistMessage::Hover(index).into()))
+    });
+
+    Some(mouse_area(rows).on_release(PlaylistMessage::Drop.into()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_entry_and_current_index() {
+        let mut entries = vec!['a', 'b', 'c', 'd', 'e'];
+        move_entry(&mut entries, 1, 3);
+        assert_eq!(entries, vec!['a', 'c', 'd', 'b', 'e']);
+        move_entry(&mut entries, 3, 0);
+        assert_eq!(entries, vec!['b', 'a', 'c', 'd', 'e']);
+
+        // Each original index follows its entry
+        let original = vec!['a', 'b', 'c', 'd', 'e'];
+        for (from, to) in [(1, 3), (3, 0), (0, 4), (2, 2)] {
+            let mut moved = original.clone();
+            move_entry(&mut moved, from, to);
+            for (index, entry) in original.iter().enumerate() {
+                assert_eq!(moved[moved_index(index, from, to)], *entry);
+            }
+        }
+    }
+}
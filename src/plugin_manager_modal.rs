@@ -0,0 +1,100 @@
+/// Plugin manager dialog
+///
+/// Lists the installed plugins with their kind and runtime and lets them be switched on and
+/// off, and shows the plugin directories that could not be loaded with the reason.
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::font::Font;
+use iced_winit::core::Theme as WinitTheme;
+use iced_wgpu::Renderer;
+use iced_widget::{row, column, container, text, button, scrollable, Space};
+
+use crate::app::{Message, DataViewer};
+use crate::plugins::PluginMessage;
+
+/// Builds the plugin manager dialog
+pub fn view_plugin_manager_modal(app: &DataViewer) -> Element<'_, Message, WinitTheme, Renderer> {
+    let manager = &app.plugins;
+    let label_style = |_theme: &WinitTheme| container::Style {
+        text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+        ..container::Style::default()
+    };
+    let detail_style = |theme: &WinitTheme| iced_widget::text::Style {
+        color: Some(theme.extended_palette().background.strong.color),
+    };
+
+    let mut list = column![].spacing(8);
+    for plugin in manager.plugins() {
+        let name = plugin.name.clone();
+        list = list.push(
+            row![
+                column![
+                    text(plugin.name.clone()).size(14),
+                    text(format!("{}, {}", plugin.kind.label(), plugin.runtime_label())).size(11).style(detail_style),
+                ]
+                .width(Length::Fill),
+                crate::widgets::toggler::Toggler::new(
+                    None::<String>,
+                    manager.is_enabled(&plugin.name),
+                    move |_| PluginMessage::Toggle(name.clone()).into(),
+                ),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        );
+    }
+    for (dir, error) in manager.failed() {
+        let dir_name = dir.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        list = list.push(column![
+            text(dir_name).size(14),
+            text(format!("Not loaded: {}", error)).size(11).style(|theme: &WinitTheme| iced_widget::text::Style {
+                color: Some(theme.extended_palette().danger.strong.color),
+            }),
+        ]);
+    }
+    if manager.plugins().is_empty() && manager.failed().is_empty() {
+        list = list.push(text(format!("No plugins installed in {}", crate::plugins::plugins_dir().display())).size(13));
+    }
+
+    let content = column![
+        text("Plugins").size(18)
+            .font(Font {
+                family: iced_winit::core::font::Family::Name("Roboto"),
+                weight: iced_winit::core::font::Weight::Bold,
+                stretch: iced_winit::core::font::Stretch::Normal,
+                style: iced_winit::core::font::Style::Normal,
+            }),
+        container(scrollable(list).height(Length::Shrink)).style(label_style).max_height(360),
+        row![
+            button(text("Open Plugins Folder"))
+                .padding([3, 10])
+                .on_press(PluginMessage::OpenFolder.into()),
+            button(text("Reload"))
+                .padding([3, 10])
+                .on_press(PluginMessage::Reload.into()),
+            Space::with_width(Length::Fill),
+            button(text("Close"))
+                .padding([3, 10])
+                .on_press(PluginMessage::HideManager.into()),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    ]
+    .spacing(10)
+    .width(Length::Fixed(480.0));
+
+    container(content)
+        .padding(15)
+        .style(|theme: &WinitTheme| {
+            iced_widget::container::Style {
+                background: Some(theme.extended_palette().background.base.color.into()),
+                text_color: Some(theme.extended_palette().primary.weak.text),
+                border: iced_winit::core::Border {
+                    color: theme.extended_palette().background.strong.color,
+                    width: 1.0,
+                    radius: iced_winit::core::border::Radius::from(8.0),
+                },
+                ..Default::default()
+            }
+        })
+        .into()
+}
@@ -0,0 +1,629 @@
+/// Plugins: metadata panels, overlays, image sources and analyzers
+///
+/// A plugin is a directory under `<config dir>/viewskater/plugins/` with a `plugin.yaml`:
+///
+/// ```yaml
+/// name: EXIF dump
+/// kind: metadata
+/// command: ["exiftool", "-s", "{path}"]
+/// timeout_ms: 2000
+/// ```
+///
+/// For each image shown, enabled `metadata` plugins are run and their output lines of the
+/// form `key: value` are listed in a side panel, and `overlay` plugins are run and their
+/// shapes drawn over the image (see [`overlay`]). `source` plugins open files with the
+/// `extensions` they list by converting them to an image the viewer reads (see [`source`]).
+/// `analyzer` plugins are for captioning or classification services and only run when asked
+/// to, from the Plugins menu or the side panel; their reply is shown as text, pretty-printed
+/// when it is JSON.
+///
+/// A plugin is either a `command`, run as a separate process without a shell, with a cleared
+/// environment (only `PATH` is kept), its own directory as working directory and a hard
+/// timeout, or a WebAssembly `module` (builds with the `wasm-plugins` feature), which runs
+/// sandboxed with no access to the system (see [`wasm`]). Commands get `{path}` replaced by
+/// the image path, except source commands, which read the file on stdin and write the image
+/// to stdout. Analyzer commands also get the image path on stdin, and an analyzer with `url:`
+/// instead of `command:` POSTs the image bytes to that endpoint (builds with the
+/// `http-analyzers` feature). Modules get the file's bytes. Either way a misbehaving plugin
+/// cannot hang or crash the viewer.
+pub mod overlay;
+pub mod source;
+#[cfg(feature = "wasm-plugins")]
+pub mod wasm;
+
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use serde::Deserialize;
+use iced_winit::runtime::Task;
+use log::{debug, info, warn};
+
+use crate::app::Message;
+use overlay::OverlayShape;
+
+const MANIFEST_NAME: &str = "plugin.yaml";
+const DISABLED_LIST_NAME: &str = "disabled.yaml";
+const DEFAULT_TIMEOUT_MS: u64 = 2000;
+/// Captioning models and remote services take longer than metadata tools
+const DEFAULT_ANALYZER_TIMEOUT_MS: u64 = 30_000;
+/// Output beyond this is dropped
+const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Deserialize)]
+struct PluginManifest {
+    name: String,
+    kind: String,
+    #[serde(default)]
+    command: Vec<String>,
+    /// WebAssembly module, relative to the plugin directory
+    #[serde(default)]
+    module: Option<String>,
+    /// HTTP endpoint of an analyzer
+    #[serde(default)]
+    url: Option<String>,
+    /// File extensions opened by a source plugin
+    #[serde(default)]
+    extensions: Vec<String>,
+    #[serde(default)]
+    timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluginKind {
+    Metadata,
+    Overlay,
+    Source,
+    /// Runs on request
+    Analyzer,
+}
+
+impl PluginKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PluginKind::Metadata => "Metadata",
+            PluginKind::Overlay => "Overlay",
+            PluginKind::Source => "Source",
+            PluginKind::Analyzer => "Analyzer",
+        }
+    }
+
+    /// Function a WASM module exports for this kind
+    #[cfg(feature = "wasm-plugins")]
+    fn entry_point(&self) -> &'static str {
+        match self {
+            PluginKind::Metadata => "metadata",
+            PluginKind::Overlay => "overlay",
+            PluginKind::Source => "source",
+            PluginKind::Analyzer => "analyzer",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Runtime {
+    Process(Vec<String>),
+    /// Analyzer endpoint the image is POSTed to
+    Http(String),
+    #[cfg(feature = "wasm-plugins")]
+    Wasm(wasm::WasmModule),
+}
+
+#[derive(Debug, Clone)]
+pub struct Plugin {
+    pub name: String,
+    pub kind: PluginKind,
+    dir: PathBuf,
+    runtime: Runtime,
+    /// Lowercase, without the dot
+    extensions: Vec<String>,
+    timeout: Duration,
+}
+
+impl Plugin {
+    pub fn runtime_label(&self) -> &'static str {
+        match self.runtime {
+            Runtime::Process(_) => "command",
+            Runtime::Http(_) => "HTTP",
+            #[cfg(feature = "wasm-plugins")]
+            Runtime::Wasm(_) => "WASM",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PluginOutput {
+    /// (key, value) pairs in output order; lines without a colon have an empty key
+    Fields(Vec<(String, String)>),
+    /// Shapes of an overlay plugin
+    Shapes(Vec<OverlayShape>),
+    /// Reply of an analyzer
+    Text(String),
+    Error(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum PluginMessage {
+    /// Scan the plugins directory again
+    Reload,
+    /// Enable or disable a plugin by name
+    Toggle(String),
+    ShowManager,
+    HideManager,
+    OpenFolder,
+    /// Run an analyzer by name on an image
+    Analyze(String, PathBuf),
+    Finished(String, PathBuf, PluginOutput),
+}
+
+impl From<PluginMessage> for Message {
+    fn from(msg: PluginMessage) -> Self {
+        Message::PluginAction(msg)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PluginManager {
+    plugins: Vec<Plugin>,
+    /// Plugin directories that could not be loaded, with the reason
+    failed: Vec<(PathBuf, String)>,
+    disabled: HashSet<String>,
+    /// Output per (plugin name, image path) for the image currently shown
+    results: HashMap<(String, PathBuf), PluginOutput>,
+    /// Analyzers started and not finished, as (plugin name, image path)
+    running: HashSet<(String, PathBuf)>,
+    requested: Option<PathBuf>,
+    pub show_manager: bool,
+}
+
+fn run_task(plugin: Plugin, path: PathBuf) -> Task<Message> {
+    Task::perform(
+        async move {
+            let name = plugin.name.clone();
+            let run_path = path.clone();
+            let output = tokio::task::spawn_blocking(move || run_plugin(&plugin, &run_path))
+                .await
+                .unwrap_or_else(|e| PluginOutput::Error(format!("Plugin task failed: {}", e)));
+            (name, path, output)
+        },
+        |(name, path, output)| PluginMessage::Finished(name, path, output).into()
+    )
+}
+
+pub fn plugins_dir() -> PathBuf {
+    crate::app_dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("viewskater")
+        .join("plugins")
+}
+
+fn parse_manifest(dir: &Path, yaml: &str) -> Result<Plugin, String> {
+    let manifest: PluginManifest = serde_yaml::from_str(yaml).map_err(|e| e.to_string())?;
+    let (kind, default_timeout_ms) = match manifest.kind.as_str() {
+        "metadata" => (PluginKind::Metadata, DEFAULT_TIMEOUT_MS),
+        "overlay" => (PluginKind::Overlay, DEFAULT_TIMEOUT_MS),
+        "source" => (PluginKind::Source, DEFAULT_TIMEOUT_MS),
+        "analyzer" => (PluginKind::Analyzer, DEFAULT_ANALYZER_TIMEOUT_MS),
+        other => return Err(format!("unsupported plugin kind '{}'", other)),
+    };
+    if kind == PluginKind::Source && manifest.extensions.is_empty() {
+        return Err("source plugins need a list of extensions".to_string());
+    }
+    let runtime = match (manifest.command.is_empty(), manifest.module, manifest.url) {
+        (false, None, None) => Runtime::Process(manifest.command),
+        (true, Some(module), None) => load_module(&dir.join(module), kind)?,
+        (true, None, Some(url)) if kind == PluginKind::Analyzer => Runtime::Http(url),
+        (true, None, Some(_)) => return Err("only analyzer plugins can use a url".to_string()),
+        (true, None, None) => return Err("command is empty".to_string()),
+        _ => return Err("set only one of command, module or url".to_string()),
+    };
+    Ok(Plugin {
+        name: manifest.name,
+        kind,
+        dir: dir.to_path_buf(),
+        runtime,
+        extensions: manifest.extensions.iter()
+            .map(|ext| ext.trim_start_matches('.').to_lowercase())
+            .collect(),
+        timeout: Duration::from_millis(manifest.timeout_ms.unwrap_or(default_timeout_ms)),
+    })
+}
+
+#[cfg(feature = "wasm-plugins")]
+fn load_module(path: &Path, kind: PluginKind) -> Result<Runtime, String> {
+    let module = wasm::WasmModule::load(path)?;
+    if !module.exports(kind.entry_point()) {
+        return Err(format!("module does not export '{}'", kind.entry_point()));
+    }
+    Ok(Runtime::Wasm(module))
+}
+
+#[cfg(not(feature = "wasm-plugins"))]
+fn load_module(_path: &Path, _kind: PluginKind) -> Result<Runtime, String> {
+    Err("WASM plugins need a build with the wasm-plugins feature".to_string())
+}
+
+/// Command line with `{path}` replaced in every argument
+fn command_args(command: &[String], image_path: &Path) -> Vec<String> {
+    let path = image_path.to_string_lossy();
+    command.iter().map(|arg| arg.replace("{path}", &path)).collect()
+}
+
+/// Split plugin output into `key: value` fields
+pub fn parse_output(output: &str) -> Vec<(String, String)> {
+    output.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| match line.split_once(':') {
+            Some((key, value)) => (key.trim().to_string(), value.trim().to_string()),
+            None => (String::new(), line.trim().to_string()),
+        })
+        .collect()
+}
+
+/// Analyzer reply as shown: JSON pretty-printed, anything else trimmed
+pub fn format_reply(reply: &str) -> String {
+    match serde_json::from_str::<serde_json::Value>(reply) {
+        Ok(value) => serde_json::to_string_pretty(&value).unwrap_or_else(|_| reply.trim().to_string()),
+        Err(_) => reply.trim().to_string(),
+    }
+}
+
+/// Run a plugin command, killing it when it exceeds its timeout
+///
+/// Returns stdout cut at `max_output` bytes, and whether it was cut.
+fn run_process(plugin: &Plugin, args: &[String], input: Option<Vec<u8>>, max_output: usize) -> Result<(Vec<u8>, bool), String> {
+    let mut command = Command::new(&args[0]);
+    command.args(&args[1..])
+        .current_dir(&plugin.dir)
+        .env_clear()
+        .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    if let Some(path) = std::env::var_os("PATH") {
+        command.env("PATH", path);
+    }
+    let mut child = command.spawn().map_err(|e| format!("Failed to start: {}", e))?;
+
+    // Write stdin and read stdout on separate threads so full pipes cannot stall the child
+    // until the timeout
+    if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        });
+    }
+    let stdout = child.stdout.take();
+    let reader = std::thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut stdout) = stdout {
+            let _ = (&mut stdout).take(max_output as u64 + 1).read_to_end(&mut bytes);
+            let _ = std::io::copy(&mut stdout, &mut std::io::sink());
+        }
+        let truncated = bytes.len() > max_output;
+        bytes.truncate(max_output);
+        (bytes, truncated)
+    });
+
+    let start = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Ok(status),
+            Ok(None) if start.elapsed() > plugin.timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break Err(format!("Timed out after {} ms", plugin.timeout.as_millis()));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(10)),
+            Err(e) => break Err(e.to_string()),
+        }
+    };
+    let output = reader.join().unwrap_or_default();
+
+    match status {
+        Ok(status) if status.success() => Ok(output),
+        Ok(status) => Err(format!("Exited with {}", status)),
+        Err(e) => Err(e),
+    }
+}
+
+/// Run a metadata, overlay or analyzer plugin on one image
+fn run_plugin(plugin: &Plugin, image_path: &Path) -> PluginOutput {
+    let output = match &plugin.runtime {
+        Runtime::Process(command) => {
+            // Analyzers also get the image path on stdin
+            let input = (plugin.kind == PluginKind::Analyzer).then(|| format!("{}\n", image_path.display()).into_bytes());
+            run_process(plugin, &command_args(command, image_path), input, MAX_OUTPUT_BYTES).map(|(bytes, _)| bytes)
+        }
+        Runtime::Http(url) => post_image(url, image_path, plugin.timeout),
+        #[cfg(feature = "wasm-plugins")]
+        Runtime::Wasm(module) => std::fs::read(image_path)
+            .map_err(|e| format!("Failed to read image: {}", e))
+            .and_then(|bytes| module.call(plugin.kind.entry_point(), &bytes, plugin.timeout, MAX_OUTPUT_BYTES)),
+    };
+    match output {
+        Ok(bytes) => {
+            let text = String::from_utf8_lossy(&bytes);
+            match plugin.kind {
+                PluginKind::Overlay => PluginOutput::Shapes(overlay::parse_shapes(&text)),
+                PluginKind::Analyzer => PluginOutput::Text(format_reply(&text)),
+                _ => PluginOutput::Fields(parse_output(&text)),
+            }
+        }
+        Err(e) => PluginOutput::Error(e),
+    }
+}
+
+/// Send the image file to an analyzer endpoint and return its reply
+#[cfg(feature = "http-analyzers")]
+fn post_image(url: &str, image_path: &Path, timeout: Duration) -> Result<Vec<u8>, String> {
+    let bytes = std::fs::read(image_path).map_err(|e| format!("Failed to read image: {}", e))?;
+    let file_name = image_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let response = ureq::post(url)
+        .set("User-Agent", &format!("viewskater/{}", env!("CARGO_PKG_VERSION")))
+        .set("Content-Type", "application/octet-stream")
+        .set("X-Image-Name", &file_name)
+        .timeout(timeout)
+        .send_bytes(&bytes)
+        .map_err(|e| e.to_string())?;
+    let mut reply = Vec::new();
+    response.into_reader()
+        .take(MAX_OUTPUT_BYTES as u64)
+        .read_to_end(&mut reply)
+        .map_err(|e| e.to_string())?;
+    Ok(reply)
+}
+
+#[cfg(not(feature = "http-analyzers"))]
+fn post_image(_url: &str, _image_path: &Path, _timeout: Duration) -> Result<Vec<u8>, String> {
+    Err("URL analyzers need a build with the http-analyzers feature".to_string())
+}
+
+/// Convert a file's bytes with a source plugin
+fn run_source(plugin: &Plugin, bytes: Vec<u8>, max_output: usize) -> Result<Vec<u8>, String> {
+    match &plugin.runtime {
+        Runtime::Process(command) => match run_process(plugin, command, Some(bytes), max_output)? {
+            (_, true) => Err(format!("Output is over the {} byte limit", max_output)),
+            (output, false) => Ok(output),
+        },
+        Runtime::Http(_) => Err("source plugins cannot use a url".to_string()),
+        #[cfg(feature = "wasm-plugins")]
+        Runtime::Wasm(module) => module.call(plugin.kind.entry_point(), &bytes, plugin.timeout, max_output),
+    }
+}
+
+impl PluginManager {
+    /// Scan the plugins directory; plugins listed in `disabled.yaml` start disabled
+    pub fn load() -> Self {
+        if crate::safe_mode::is_enabled() {
+            info!("Safe mode: plugins are not loaded");
+            return Self::default();
+        }
+        let dir = plugins_dir();
+        let disabled = std::fs::read_to_string(dir.join(DISABLED_LIST_NAME))
+            .ok()
+            .and_then(|yaml| serde_yaml::from_str::<Vec<String>>(&yaml).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+
+        let mut plugins = Vec::new();
+        let mut failed = Vec::new();
+        if let Ok(entries) = std::fs::read_dir(&dir) {
+            for entry in entries.filter_map(Result::ok) {
+                let plugin_dir = entry.path();
+                let Ok(yaml) = std::fs::read_to_string(plugin_dir.join(MANIFEST_NAME)) else {
+                    continue;
+                };
+                match parse_manifest(&plugin_dir, &yaml) {
+                    Ok(plugin) => plugins.push(plugin),
+                    Err(e) => {
+                        warn!("Skipping plugin {}: {}", plugin_dir.display(), e);
+                        failed.push((plugin_dir, e));
+                    }
+                }
+            }
+        }
+        plugins.sort_by(|a, b| a.name.cmp(&b.name));
+        if !plugins.is_empty() {
+            info!("Found {} plugin(s) in {}", plugins.len(), dir.display());
+        }
+        let manager = Self { plugins, failed, disabled, ..Self::default() };
+        manager.publish_sources();
+        manager
+    }
+
+    /// Make the enabled source plugins available to the file readers
+    fn publish_sources(&self) {
+        source::publish(self.plugins.iter()
+            .filter(|plugin| plugin.kind == PluginKind::Source && self.is_enabled(&plugin.name))
+            .cloned()
+            .collect());
+    }
+
+    pub fn plugins(&self) -> &[Plugin] {
+        &self.plugins
+    }
+
+    pub fn failed(&self) -> &[(PathBuf, String)] {
+        &self.failed
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        !self.disabled.contains(name)
+    }
+
+    fn save_disabled(&self) {
+        let mut names: Vec<&String> = self.disabled.iter().collect();
+        names.sort();
+        let dir = plugins_dir();
+        let result = std::fs::create_dir_all(&dir)
+            .and_then(|_| std::fs::write(dir.join(DISABLED_LIST_NAME), serde_yaml::to_string(&names).unwrap_or_default()));
+        if let Err(e) = result {
+            warn!("Failed to save plugin state: {}", e);
+        }
+    }
+
+    /// Output of each enabled metadata plugin and analyzer for an image, in plugin order. An
+    /// analyzer without output has not been run on it, or is still running when `is_running`
+    /// says so.
+    pub fn results_for(&self, image_path: &Path) -> Vec<(&Plugin, Option<&PluginOutput>)> {
+        self.plugins.iter()
+            .filter(|plugin| matches!(plugin.kind, PluginKind::Metadata | PluginKind::Analyzer) && self.is_enabled(&plugin.name))
+            .map(|plugin| (plugin, self.results.get(&(plugin.name.clone(), image_path.to_path_buf()))))
+            .collect()
+    }
+
+    pub fn is_running(&self, name: &str, image_path: &Path) -> bool {
+        self.running.contains(&(name.to_string(), image_path.to_path_buf()))
+    }
+
+    /// Enabled analyzers, for the menu
+    pub fn analyzers(&self) -> impl Iterator<Item = &Plugin> {
+        self.plugins.iter().filter(|plugin| plugin.kind == PluginKind::Analyzer && self.is_enabled(&plugin.name))
+    }
+
+    pub fn has_overlays(&self) -> bool {
+        self.plugins.iter().any(|plugin| plugin.kind == PluginKind::Overlay && self.is_enabled(&plugin.name))
+    }
+
+    /// Shapes of the enabled overlay plugins that finished on an image
+    pub fn overlay_shapes(&self, image_path: &Path) -> Vec<&OverlayShape> {
+        self.plugins.iter()
+            .filter(|plugin| plugin.kind == PluginKind::Overlay && self.is_enabled(&plugin.name))
+            .filter_map(|plugin| match self.results.get(&(plugin.name.clone(), image_path.to_path_buf())) {
+                Some(PluginOutput::Shapes(shapes)) => Some(shapes),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// Run the enabled metadata and overlay plugins on the image now shown, unless that was
+    /// already done
+    pub fn request(&mut self, image_path: Option<&Path>) -> Option<Task<Message>> {
+        let image_path = image_path?;
+        if self.plugins.is_empty() || self.requested.as_deref() == Some(image_path) {
+            return None;
+        }
+        self.requested = Some(image_path.to_path_buf());
+        self.results.retain(|(_, path), _| path == image_path);
+        self.running.retain(|(_, path)| path == image_path);
+
+        let tasks: Vec<Task<Message>> = self.plugins.iter()
+            .filter(|plugin| matches!(plugin.kind, PluginKind::Metadata | PluginKind::Overlay) && self.is_enabled(&plugin.name))
+            .map(|plugin| run_task(plugin.clone(), image_path.to_path_buf()))
+            .collect();
+        Some(Task::batch(tasks))
+    }
+}
+
+pub fn handle_plugin_message(manager: &mut PluginManager, msg: PluginMessage) -> Task<Message> {
+    match msg {
+        PluginMessage::Reload => {
+            let show_manager = manager.show_manager;
+            *manager = PluginManager::load();
+            manager.show_manager = show_manager;
+            Task::none()
+        }
+        PluginMessage::Toggle(name) => {
+            if !manager.disabled.remove(&name) {
+                manager.disabled.insert(name);
+            }
+            manager.save_disabled();
+            manager.publish_sources();
+            // Run newly enabled plugins on the current image
+            manager.requested = None;
+            Task::none()
+        }
+        PluginMessage::ShowManager => {
+            manager.show_manager = true;
+            Task::none()
+        }
+        PluginMessage::HideManager => {
+            manager.show_manager = false;
+            Task::none()
+        }
+        PluginMessage::OpenFolder => {
+            let dir = plugins_dir();
+            let _ = std::fs::create_dir_all(&dir);
+            crate::logging::open_in_file_explorer(dir.to_string_lossy().as_ref());
+            Task::none()
+        }
+        PluginMessage::Analyze(name, path) => {
+            let Some(plugin) = manager.plugins.iter().find(|plugin| plugin.name == name).cloned() else {
+                return Task::none();
+            };
+            if !manager.running.insert((name.clone(), path.clone())) {
+                return Task::none();
+            }
+            manager.results.remove(&(name, path.clone()));
+            run_task(plugin, path)
+        }
+        PluginMessage::Finished(name, path, output) => {
+            manager.running.remove(&(name.clone(), path.clone()));
+            if manager.requested.as_deref() == Some(path.as_path()) {
+                if let PluginOutput::Error(e) = &output {
+                    debug!("Plugin {} failed on {}: {}", name, path.display(), e);
+                }
+                manager.results.insert((name, path), output);
+            }
+            Task::none()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_and_command_substitution() {
+        let yaml = "name: Sizes\nkind: metadata\ncommand: [\"identify\", \"-format\", \"size: %wx%h\", \"{path}\"]\n";
+        let plugin = parse_manifest(Path::new("/plugins/sizes"), yaml).unwrap();
+        assert_eq!(plugin.timeout, Duration::from_millis(DEFAULT_TIMEOUT_MS));
+        let Runtime::Process(command) = &plugin.runtime else {
+            panic!("expected a command plugin");
+        };
+        assert_eq!(
+            command_args(command, Path::new("/data/a b.png")),
+            vec!["identify", "-format", "size: %wx%h", "/data/a b.png"]
+        );
+
+        let source = parse_manifest(Path::new("."), "name: X\nkind: source\nextensions: [\".RAW\"]\ncommand: [\"x\"]\n").unwrap();
+        assert_eq!(source.extensions, vec!["raw"]);
+        assert!(parse_manifest(Path::new("."), "name: X\nkind: source\ncommand: [\"x\"]\n").is_err());
+        assert!(parse_manifest(Path::new("."), "name: X\nkind: thumbnail\ncommand: [\"x\"]\n").is_err());
+        assert!(parse_manifest(Path::new("."), "name: X\nkind: metadata\ncommand: []\n").is_err());
+        assert!(parse_manifest(Path::new("."), "name: X\nkind: overlay\ncommand: [\"x\"]\nmodule: x.wasm\n").is_err());
+    }
+
+    #[test]
+    fn test_analyzer_manifest_and_reply() {
+        let plugin = parse_manifest(Path::new("."), "name: Caption\nkind: analyzer\nurl: http://localhost:8000/caption\n").unwrap();
+        assert_eq!(plugin.kind, PluginKind::Analyzer);
+        assert_eq!(plugin.timeout, Duration::from_millis(DEFAULT_ANALYZER_TIMEOUT_MS));
+        assert!(parse_manifest(Path::new("."), "name: X\nkind: metadata\nurl: http://localhost\n").is_err());
+        assert!(parse_manifest(Path::new("."), "name: X\nkind: analyzer\ncommand: [\"x\"]\nurl: http://localhost\n").is_err());
+
+        assert_eq!(format_reply("{\"label\":\"cat\"}"), "{\n  \"label\": \"cat\"\n}");
+        assert_eq!(format_reply("  a cat on a sofa\n"), "a cat on a sofa");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_source_command_converts_stdin() {
+        let yaml = "name: Copy\nkind: source\nextensions: [raw]\ncommand: [\"cat\"]\n";
+        let plugin = parse_manifest(&std::env::temp_dir(), yaml).unwrap();
+        assert_eq!(run_source(&plugin, b"P6 1 1 255 abc".to_vec(), 1024).unwrap(), b"P6 1 1 255 abc");
+        assert!(run_source(&plugin, vec![0; 64], 16).is_err());
+    }
+
+    #[test]
+    fn test_output_lines_become_fields() {
+        assert_eq!(parse_output("Model: X100\n\nTime: 12:30:01\nfree text\n"), vec![
+            ("Model".to_string(), "X100".to_string()),
+            ("Time".to_string(), "12:30:01".to_string()),
+            (String::new(), "free text".to_string()),
+        ]);
+    }
+}
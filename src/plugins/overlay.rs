@@ -0,0 +1,183 @@
+/// Shapes drawn over the image by overlay plugins
+///
+/// An overlay plugin prints one shape per line, in image pixel coordinates, optionally
+/// followed by a `#rrggbb` color and a label:
+///
+/// ```text
+/// rect <x> <y> <width> <height> [#rrggbb] [label]
+/// line <x1> <y1> <x2> <y2> [#rrggbb] [label]
+/// point <x> <y> [#rrggbb] [label]
+/// ```
+///
+/// Lines that don't parse are skipped. The image shader records where it drew the image, and
+/// the overlay layer is drawn right after it in the same frame, so the shapes follow zoom and
+/// pan without the app tracking the zoom state.
+use std::sync::Mutex;
+use iced_winit::core::{mouse, Color, Element, Length, Point, Rectangle, Size};
+use iced_winit::core::Theme as WinitTheme;
+use iced_wgpu::Renderer;
+use iced_widget::canvas::{self, Path as CanvasPath, Stroke};
+
+use crate::app::Message;
+
+const DEFAULT_COLOR: Color = Color::from_rgb(1.0, 0.85, 0.1);
+const LABEL_SIZE: f32 = 13.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShapeGeometry {
+    Rect { x: f32, y: f32, width: f32, height: f32 },
+    Line { x1: f32, y1: f32, x2: f32, y2: f32 },
+    Point { x: f32, y: f32 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverlayShape {
+    pub geometry: ShapeGeometry,
+    pub color: Color,
+    pub label: Option<String>,
+}
+
+/// Screen rectangle the image was last drawn to by a shader reporting its bounds
+static CONTENT_BOUNDS: Mutex<Option<Rectangle>> = Mutex::new(None);
+
+pub fn set_content_bounds(bounds: Rectangle) {
+    *CONTENT_BOUNDS.lock().unwrap() = Some(bounds);
+}
+
+fn parse_color(token: &str) -> Option<Color> {
+    let hex = token.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    Some(Color::from_rgb8((value >> 16) as u8, (value >> 8) as u8, value as u8))
+}
+
+fn parse_shape(line: &str) -> Option<OverlayShape> {
+    let mut tokens = line.split_whitespace();
+    let (kind, count) = match tokens.next()? {
+        "rect" => ("rect", 4),
+        "line" => ("line", 4),
+        "point" => ("point", 2),
+        _ => return None,
+    };
+    let numbers: Vec<f32> = tokens.by_ref().take(count).map(|t| t.parse().ok()).collect::<Option<_>>()?;
+    if numbers.len() != count || numbers.iter().any(|n| !n.is_finite()) {
+        return None;
+    }
+    let geometry = match kind {
+        "rect" => ShapeGeometry::Rect { x: numbers[0], y: numbers[1], width: numbers[2], height: numbers[3] },
+        "line" => ShapeGeometry::Line { x1: numbers[0], y1: numbers[1], x2: numbers[2], y2: numbers[3] },
+        _ => ShapeGeometry::Point { x: numbers[0], y: numbers[1] },
+    };
+
+    let mut rest: Vec<&str> = tokens.collect();
+    let color = match rest.first().and_then(|token| parse_color(token)) {
+        Some(color) => {
+            rest.remove(0);
+            color
+        }
+        None => DEFAULT_COLOR,
+    };
+    let label = (!rest.is_empty()).then(|| rest.join(" "));
+    Some(OverlayShape { geometry, color, label })
+}
+
+/// Parse overlay plugin output, skipping lines that are not shapes
+pub fn parse_shapes(output: &str) -> Vec<OverlayShape> {
+    output.lines().filter_map(parse_shape).collect()
+}
+
+struct OverlayLayer<'a> {
+    shapes: Vec<&'a OverlayShape>,
+    image_size: (u32, u32),
+}
+
+impl canvas::Program<Message, WinitTheme, Renderer> for OverlayLayer<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &WinitTheme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry<Renderer>> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let Some(content) = *CONTENT_BOUNDS.lock().unwrap() else {
+            return vec![frame.into_geometry()];
+        };
+        let scale_x = content.width / self.image_size.0.max(1) as f32;
+        let scale_y = content.height / self.image_size.1.max(1) as f32;
+        let to_frame = |x: f32, y: f32| Point::new(content.x - bounds.x + x * scale_x, content.y - bounds.y + y * scale_y);
+
+        // Shapes can reach outside the pane when zoomed in
+        frame.with_clip(Rectangle::new(Point::ORIGIN, bounds.size()), |frame| {
+            for shape in &self.shapes {
+                let stroke = Stroke::default().with_color(shape.color).with_width(2.0);
+                let anchor = match shape.geometry {
+                    ShapeGeometry::Rect { x, y, width, height } => {
+                        let top_left = to_frame(x, y);
+                        let size = Size::new(width * scale_x, height * scale_y);
+                        frame.stroke(&CanvasPath::rectangle(top_left, size), stroke);
+                        top_left
+                    }
+                    ShapeGeometry::Line { x1, y1, x2, y2 } => {
+                        let start = to_frame(x1, y1);
+                        frame.stroke(&CanvasPath::line(start, to_frame(x2, y2)), stroke);
+                        start
+                    }
+                    ShapeGeometry::Point { x, y } => {
+                        let center = to_frame(x, y);
+                        frame.fill(&CanvasPath::circle(center, 4.0), shape.color);
+                        Point::new(center.x + 6.0, center.y)
+                    }
+                };
+                if let Some(label) = &shape.label {
+                    frame.fill_text(canvas::Text {
+                        content: label.clone(),
+                        position: Point::new(anchor.x, anchor.y - LABEL_SIZE - 2.0),
+                        color: shape.color,
+                        size: LABEL_SIZE.into(),
+                        ..canvas::Text::default()
+                    });
+                }
+            }
+        });
+
+        vec![frame.into_geometry()]
+    }
+}
+
+/// Layer drawing overlay shapes for an image of `image_size` pixels
+pub fn overlay_layer<'a>(shapes: Vec<&'a OverlayShape>, image_size: (u32, u32)) -> Element<'a, Message, WinitTheme, Renderer> {
+    canvas::Canvas::new(OverlayLayer { shapes, image_size })
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_shapes() {
+        let shapes = parse_shapes("rect 10 20 30 40 #ff0000 cat 0.92\nline 0 0 5 5\npoint 1.5 2\ncircle 1 2 3\nrect 1 2 x 4\n");
+        assert_eq!(shapes, vec![
+            OverlayShape {
+                geometry: ShapeGeometry::Rect { x: 10.0, y: 20.0, width: 30.0, height: 40.0 },
+                color: Color::from_rgb8(255, 0, 0),
+                label: Some("cat 0.92".to_string()),
+            },
+            OverlayShape {
+                geometry: ShapeGeometry::Line { x1: 0.0, y1: 0.0, x2: 5.0, y2: 5.0 },
+                color: DEFAULT_COLOR,
+                label: None,
+            },
+            OverlayShape {
+                geometry: ShapeGeometry::Point { x: 1.5, y: 2.0 },
+                color: DEFAULT_COLOR,
+                label: None,
+            },
+        ]);
+    }
+}
@@ -0,0 +1,48 @@
+/// Source plugins: open files the viewer cannot decode by converting them with a plugin
+///
+/// A source plugin lists the extensions it opens. Files with those extensions are listed
+/// with the other images, and their bytes are handed to the plugin whenever they are read,
+/// which returns an image in a format the viewer reads (PNG, PPM, ...). The enabled source
+/// plugins live in a static because the file readers run on worker threads without access
+/// to the app.
+use std::io;
+use std::path::Path;
+use std::sync::RwLock;
+use log::debug;
+
+use super::Plugin;
+
+/// Converted images are far larger than text output
+const MAX_IMAGE_BYTES: usize = 512 * 1024 * 1024;
+
+static SOURCES: RwLock<Vec<Plugin>> = RwLock::new(Vec::new());
+
+/// Replace the enabled source plugins
+pub(super) fn publish(plugins: Vec<Plugin>) {
+    *SOURCES.write().unwrap() = plugins;
+}
+
+fn find(extension: &str) -> Option<Plugin> {
+    let extension = extension.to_lowercase();
+    SOURCES.read().unwrap().iter()
+        .find(|plugin| plugin.extensions.contains(&extension))
+        .cloned()
+}
+
+/// Whether an enabled source plugin opens files with this extension
+pub fn handles_extension(extension: &str) -> bool {
+    find(extension).is_some()
+}
+
+/// Convert a file with the source plugin for its extension; other files pass through
+pub fn convert(file_name: &str, bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+    let Some(extension) = Path::new(file_name).extension().and_then(|ext| ext.to_str()) else {
+        return Ok(bytes);
+    };
+    let Some(plugin) = find(extension) else {
+        return Ok(bytes);
+    };
+    debug!("Converting {} with source plugin {}", file_name, plugin.name);
+    super::run_source(&plugin, bytes, MAX_IMAGE_BYTES)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{} plugin: {}", plugin.name, e)))
+}
@@ -0,0 +1,167 @@
+/// Sandboxed WebAssembly plugin runtime
+///
+/// Modules are compiled with wasmtime and instantiated with no imports, so a plugin cannot
+/// reach files, the network, the clock or the environment; it only sees the bytes it is
+/// given. Modules that import anything are rejected when loaded. Every call runs in a fresh
+/// instance whose memory is capped, and an epoch deadline interrupts it after the plugin's
+/// timeout.
+///
+/// A module exports `memory`, `alloc(len: i32) -> i32` and the entry point of its kind
+/// (`metadata`, `overlay` or `source`), which takes the input as `(ptr: i32, len: i32)` and
+/// returns an `i64` holding the output pointer in the high 32 bits and its length in the
+/// low 32 bits.
+use std::path::Path;
+use std::time::Duration;
+use once_cell::sync::Lazy;
+use wasmtime::{Config, Engine, Instance, Module, Store, StoreLimits, StoreLimitsBuilder, Trap};
+use log::warn;
+
+/// Linear memory one plugin instance may grow to
+const MAX_MEMORY_BYTES: usize = 256 * 1024 * 1024;
+/// Interval of the epoch ticks that time out running plugins
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// One engine for all plugins, with a thread advancing its epoch
+static ENGINE: Lazy<Engine> = Lazy::new(|| {
+    let mut config = Config::new();
+    config.epoch_interruption(true);
+    let engine = Engine::new(&config).expect("wasmtime config with epoch interruption is valid");
+
+    let ticker = engine.clone();
+    let spawned = std::thread::Builder::new()
+        .name("wasm-plugin-epoch".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(EPOCH_TICK);
+            ticker.increment_epoch();
+        });
+    if let Err(e) = spawned {
+        warn!("Failed to start the WASM plugin timer, plugins will not time out: {}", e);
+    }
+    engine
+});
+
+/// A compiled plugin module
+#[derive(Clone)]
+pub struct WasmModule(Module);
+
+impl std::fmt::Debug for WasmModule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("WasmModule").field(&self.0.name()).finish()
+    }
+}
+
+impl WasmModule {
+    /// Compile a `.wasm` or `.wat` file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::new(&bytes)
+    }
+
+    pub fn new(bytes: &[u8]) -> Result<Self, String> {
+        let module = Module::new(&ENGINE, bytes).map_err(|e| format!("{:#}", e))?;
+        if let Some(import) = module.imports().next() {
+            return Err(format!(
+                "imports are not allowed, the module imports {}::{}",
+                import.module(), import.name()
+            ));
+        }
+        Ok(Self(module))
+    }
+
+    pub fn exports(&self, name: &str) -> bool {
+        self.0.get_export(name).is_some()
+    }
+
+    /// Run `entry` on `input` and return the bytes it points to
+    pub fn call(&self, entry: &str, input: &[u8], timeout: Duration, max_output: usize) -> Result<Vec<u8>, String> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MAX_MEMORY_BYTES)
+            .instances(1)
+            .build();
+        let mut store: Store<StoreLimits> = Store::new(&ENGINE, limits);
+        store.limiter(|limits| limits);
+        let ticks = (timeout.as_millis() / EPOCH_TICK.as_millis()).max(1) as u64;
+        store.set_epoch_deadline(ticks);
+
+        let describe = |e: wasmtime::Error| match e.downcast_ref::<Trap>() {
+            Some(Trap::Interrupt) => format!("Timed out after {} ms", timeout.as_millis()),
+            _ => format!("{:#}", e),
+        };
+
+        let instance = Instance::new(&mut store, &self.0, &[]).map_err(describe)?;
+        let memory = instance.get_memory(&mut store, "memory")
+            .ok_or_else(|| "module does not export memory".to_string())?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc").map_err(describe)?;
+        let run = instance.get_typed_func::<(i32, i32), i64>(&mut store, entry).map_err(describe)?;
+
+        let len = i32::try_from(input.len()).map_err(|_| "input is too large".to_string())?;
+        let ptr = alloc.call(&mut store, len).map_err(describe)?;
+        memory.write(&mut store, ptr as u32 as usize, input).map_err(|e| e.to_string())?;
+
+        let packed = run.call(&mut store, (ptr, len)).map_err(describe)? as u64;
+        let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+        if out_len > max_output {
+            return Err(format!("Output of {} bytes is over the {} byte limit", out_len, max_output));
+        }
+        let mut output = vec![0; out_len];
+        memory.read(&store, out_ptr, &mut output).map_err(|e| e.to_string())?;
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_millis(500);
+
+    /// Echoes its input from `source`, returns fixed text from `metadata` and never returns
+    /// from `overlay`
+    const TEST_MODULE: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (data (i32.const 0) "size: 3")
+          (func (export "alloc") (param $len i32) (result i32)
+            i32.const 1024)
+          (func (export "metadata") (param $ptr i32) (param $len i32) (result i64)
+            i64.const 7)
+          (func (export "source") (param $ptr i32) (param $len i32) (result i64)
+            (i64.or
+              (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+              (i64.extend_i32_u (local.get $len))))
+          (func (export "overlay") (param $ptr i32) (param $len i32) (result i64)
+            (loop $spin (br $spin))
+            i64.const 0))
+    "#;
+
+    #[test]
+    fn test_calls_return_module_output() {
+        let module = WasmModule::new(TEST_MODULE.as_bytes()).unwrap();
+        assert!(module.exports("source"));
+        assert_eq!(module.call("metadata", b"abc", TIMEOUT, 1024).unwrap(), b"size: 3");
+        assert_eq!(module.call("source", b"image bytes", TIMEOUT, 1024).unwrap(), b"image bytes");
+        assert!(module.call("source", b"image bytes", TIMEOUT, 4).is_err());
+    }
+
+    #[test]
+    fn test_sandbox_limits() {
+        let module = WasmModule::new(TEST_MODULE.as_bytes()).unwrap();
+        let error = module.call("overlay", b"", Duration::from_millis(50), 1024).unwrap_err();
+        assert!(error.starts_with("Timed out"), "{}", error);
+
+        let importing = r#"(module (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32))))"#;
+        assert!(WasmModule::new(importing.as_bytes()).unwrap_err().contains("imports are not allowed"));
+
+        let greedy = r#"
+            (module
+              (memory (export "memory") 1)
+              (func (export "alloc") (param i32) (result i32) i32.const 0)
+              (func (export "metadata") (param i32 i32) (result i64)
+                (drop (memory.grow (i32.const 8192)))
+                (i64.extend_i32_s (memory.size))))
+        "#;
+        let module = WasmModule::new(greedy.as_bytes()).unwrap();
+        // The output length is the page count: growing to 512 MiB failed, one page is left
+        assert_eq!(module.call("metadata", b"", TIMEOUT, 1024).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,161 @@
+/// Named setting presets
+///
+/// Each `<config dir>/viewskater/profiles/<name>.yaml` holds any subset of the keys of
+/// `settings.yaml`, plus an optional `pane_layout: single | dual`. Selecting a profile from
+/// the Controls menu, or starting with `--profile <name>`, lays its keys over the settings
+/// file; "Default" goes back to the file alone. The profile is not written to
+/// `settings.yaml`, so saving from the settings dialog keeps the base settings separate.
+use std::path::PathBuf;
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use serde_yaml::{Mapping, Value};
+use log::{info, warn};
+
+use crate::app::Message;
+use crate::menu::PaneLayout;
+use crate::settings::UserSettings;
+
+const PANE_LAYOUT_KEY: &str = "pane_layout";
+
+static ACTIVE: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+static REQUESTED: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone)]
+pub enum ProfileMessage {
+    Select(String),
+    /// Back to the settings file without a profile
+    Clear,
+}
+
+impl From<ProfileMessage> for Message {
+    fn from(msg: ProfileMessage) -> Self {
+        Message::ProfileAction(msg)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Profile {
+    pub name: String,
+    settings: Mapping,
+    pub pane_layout: Option<PaneLayout>,
+}
+
+pub fn profiles_dir() -> PathBuf {
+    crate::app_dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("viewskater")
+        .join("profiles")
+}
+
+/// Names of the profiles in the profiles folder, sorted
+pub fn available() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(profiles_dir()) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml"))
+        .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().to_string()))
+        .collect();
+    names.sort();
+    names
+}
+
+pub fn active_name() -> Option<String> {
+    ACTIVE.lock().ok()?.clone()
+}
+
+pub fn set_active(name: Option<String>) {
+    if let Ok(mut active) = ACTIVE.lock() {
+        *active = name;
+    }
+}
+
+/// Select a profile once the app is running, for `--profile`
+pub fn request(name: String) {
+    if let Ok(mut requested) = REQUESTED.lock() {
+        *requested = Some(name);
+    }
+}
+
+pub fn take_request() -> Option<String> {
+    REQUESTED.lock().ok()?.take()
+}
+
+fn parse(name: &str, yaml: &str) -> Result<Profile, String> {
+    let mut settings: Mapping = serde_yaml::from_str(yaml).map_err(|e| e.to_string())?;
+    let pane_layout = match settings.remove(PANE_LAYOUT_KEY) {
+        None => None,
+        Some(Value::String(layout)) if layout == "single" => Some(PaneLayout::SinglePane),
+        Some(Value::String(layout)) if layout == "dual" => Some(PaneLayout::DualPane),
+        Some(other) => return Err(format!("{} must be single or dual, not {:?}", PANE_LAYOUT_KEY, other)),
+    };
+    Ok(Profile { name: name.to_string(), settings, pane_layout })
+}
+
+pub fn load(name: &str) -> Option<Profile> {
+    let path = profiles_dir().join(format!("{}.yaml", name));
+    let path = if path.is_file() { path } else { path.with_extension("yml") };
+    let yaml = match std::fs::read_to_string(&path) {
+        Ok(yaml) => yaml,
+        Err(e) => {
+            warn!("Failed to read profile {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    match parse(name, &yaml) {
+        Ok(profile) => Some(profile),
+        Err(e) => {
+            warn!("Invalid profile {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+impl Profile {
+    /// `base` with this profile's keys replacing its own
+    pub fn apply_to(&self, base: &UserSettings) -> Option<UserSettings> {
+        let mut value = serde_yaml::to_value(base).ok()?;
+        if let Value::Mapping(map) = &mut value {
+            for (key, profile_value) in &self.settings {
+                map.insert(key.clone(), profile_value.clone());
+            }
+        }
+        match serde_yaml::from_value(value) {
+            Ok(settings) => Some(settings),
+            Err(e) => {
+                warn!("Profile {} does not fit the settings: {}", self.name, e);
+                None
+            }
+        }
+    }
+}
+
+/// `base` with the active profile laid over it, e.g. after the settings file is reloaded
+pub fn with_active_profile(base: UserSettings) -> UserSettings {
+    let Some(profile) = active_name().and_then(|name| load(&name)) else {
+        return base;
+    };
+    info!("Keeping profile {} over the reloaded settings", profile.name);
+    profile.apply_to(&base).unwrap_or(base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_overrides_only_its_keys() {
+        let profile = parse("presentation", "show_footer: false\ncache_size: 9\npane_layout: dual\n").unwrap();
+        assert_eq!(profile.pane_layout, Some(PaneLayout::DualPane));
+
+        let base = UserSettings { show_fps: true, ..UserSettings::default() };
+        let settings = profile.apply_to(&base).unwrap();
+        assert!(!settings.show_footer);
+        assert_eq!(settings.cache_size, 9);
+        assert!(settings.show_fps);
+
+        assert!(parse("bad", "pane_layout: triple\n").is_err());
+        assert!(parse("bad", "cache_size: lots\n").unwrap().apply_to(&base).is_none());
+    }
+}
@@ -0,0 +1,139 @@
+/// RAW+JPEG pairs
+///
+/// Cameras shooting RAW+JPEG write two files per shot under the same name. While pairing is
+/// on, a folder listing keeps one entry per shot, the JPEG unless the RAW was the file
+/// opened, and the pane remembers the other member so R can switch the current shot to it.
+/// Turning pairing off lists both files again.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::utils::raw_preview::is_raw;
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+fn is_jpeg(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("jpg") || ext.eq_ignore_ascii_case("jpeg"))
+}
+
+/// `paths` with each RAW+JPEG pair reduced to one entry, and the left-out member of each
+/// pair keyed by the entry kept. `preferred` is kept when it belongs to a pair.
+///
+/// Only shots with exactly one RAW and one JPEG are paired; anything else sharing a name,
+/// such as edited exports, stays in the list.
+pub fn collapse(paths: Vec<PathBuf>, preferred: Option<&Path>) -> (Vec<PathBuf>, HashMap<PathBuf, PathBuf>) {
+    let mut shots: HashMap<(Option<&Path>, String), Vec<usize>> = HashMap::new();
+    for (index, path) in paths.iter().enumerate() {
+        let stem = path.file_stem().map(|s| s.to_string_lossy().to_lowercase()).unwrap_or_default();
+        shots.entry((path.parent(), stem)).or_default().push(index);
+    }
+
+    let mut hidden = vec![false; paths.len()];
+    let mut pairs = HashMap::new();
+    for members in shots.values() {
+        let [a, b] = members[..] else {
+            continue;
+        };
+        let (raw, jpeg) = match (is_raw(&paths[a]), is_raw(&paths[b])) {
+            (true, false) if is_jpeg(&paths[b]) => (a, b),
+            (false, true) if is_jpeg(&paths[a]) => (b, a),
+            _ => continue,
+        };
+        let (kept, left_out) = if preferred == Some(paths[raw].as_path()) { (raw, jpeg) } else { (jpeg, raw) };
+        hidden[left_out] = true;
+        pairs.insert(paths[kept].clone(), paths[left_out].clone());
+    }
+
+    let paths = paths.into_iter()
+        .zip(hidden)
+        .filter_map(|(path, hidden)| (!hidden).then_some(path))
+        .collect();
+    (paths, pairs)
+}
+
+/// Like `collapse`, leaving the list as it is while pairing is off
+pub fn apply(paths: Vec<PathBuf>, preferred: Option<&Path>) -> (Vec<PathBuf>, HashMap<PathBuf, PathBuf>) {
+    if is_enabled() {
+        collapse(paths, preferred)
+    } else {
+        (paths, HashMap::new())
+    }
+}
+
+/// `apply` for a list streamed in natural order instead of held at once, handing each path
+/// kept to `keep`
+///
+/// Members of a shot are only paired when listed next to each other, which is where the sort
+/// puts the RAW and JPEG a camera writes.
+pub fn apply_sorted(
+    paths: impl Iterator<Item = PathBuf>,
+    preferred: Option<&Path>,
+    mut keep: impl FnMut(PathBuf),
+) -> HashMap<PathBuf, PathBuf> {
+    let mut pairs = HashMap::new();
+    if !is_enabled() {
+        paths.for_each(keep);
+        return pairs;
+    }
+    let shot = |path: &Path| (path.parent().map(Path::to_path_buf), path.file_stem().map(|s| s.to_string_lossy().to_lowercase()));
+    let mut group: Vec<PathBuf> = Vec::new();
+    for path in paths {
+        if group.last().is_some_and(|last| shot(last) != shot(&path)) {
+            let (kept, found) = collapse(std::mem::take(&mut group), preferred);
+            kept.into_iter().for_each(&mut keep);
+            pairs.extend(found);
+        }
+        group.push(path);
+    }
+    let (kept, found) = collapse(group, preferred);
+    kept.into_iter().for_each(keep);
+    pairs.extend(found);
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collapse() {
+        let paths: Vec<PathBuf> = ["a/IMG_1.CR2", "a/IMG_1.JPG", "a/IMG_2.jpg", "a/IMG_3.nef", "a/IMG_3.jpg", "a/IMG_3.png"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+
+        let (listed, pairs) = collapse(paths.clone(), None);
+        // IMG_3 has three members, so it isn't treated as a pair
+        assert_eq!(listed, paths[1..].to_vec());
+        assert_eq!(pairs.get(&paths[1]), Some(&paths[0]));
+        assert_eq!(pairs.len(), 1);
+
+        // Opening the RAW keeps it in place of the JPEG
+        let (listed, pairs) = collapse(paths.clone(), Some(&paths[0]));
+        assert_eq!(listed[0], paths[0]);
+        assert_eq!(listed.len(), 5);
+        assert_eq!(pairs.get(&paths[0]), Some(&paths[1]));
+    }
+
+    #[test]
+    fn test_apply_sorted() {
+        let paths: Vec<PathBuf> = ["a/IMG_1.CR2", "a/IMG_1.JPG", "a/IMG_2.jpg", "a/IMG_3.nef", "a/IMG_3.jpg", "a/IMG_3.png"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let mut listed = Vec::new();
+        let pairs = apply_sorted(paths.clone().into_iter(), None, |path| listed.push(path));
+        // Same result as collapsing the whole list
+        assert_eq!((listed, pairs), collapse(paths, None));
+    }
+}
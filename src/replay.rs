@@ -190,9 +190,9 @@ pub struct ReplayController {
     /// Count of navigations in current direction (reset when direction/directory changes)
     pub navigation_count: usize,
     /// Current slider position for slider navigation mode (0-indexed)
-    pub current_slider_position: u16,
+    pub current_slider_position: u32,
     /// Maximum slider position (image_count - 1, set when directory loads)
-    pub max_slider_position: u16,
+    pub max_slider_position: u32,
 }
 
 impl ReplayController {
@@ -314,7 +314,7 @@ impl ReplayController {
     /// Set the total image count for slider navigation mode
     /// Called after directory loads to enable proper slider position tracking
     pub fn set_image_count(&mut self, count: usize) {
-        self.max_slider_position = count.saturating_sub(1) as u16;
+        self.max_slider_position = count.saturating_sub(1) as u32;
         // Reset position to start for right navigation, or end for left-only navigation
         let direction = self.config.directions.first().unwrap_or(&ReplayDirection::Right);
         if matches!(direction, ReplayDirection::Left) {
@@ -443,7 +443,7 @@ impl ReplayController {
         }
 
         // Increment position by step, clamping to max
-        let new_pos = (self.current_slider_position + self.config.slider_step)
+        let new_pos = (self.current_slider_position + self.config.slider_step as u32)
             .min(self.max_slider_position);
         self.current_slider_position = new_pos;
         self.on_navigation_performed();
@@ -461,7 +461,7 @@ impl ReplayController {
         }
 
         // Decrement position by step, clamping to 0
-        let new_pos = self.current_slider_position.saturating_sub(self.config.slider_step);
+        let new_pos = self.current_slider_position.saturating_sub(self.config.slider_step as u32);
         self.current_slider_position = new_pos;
         self.on_navigation_performed();
 
@@ -669,7 +669,7 @@ pub enum ReplayAction {
     NavigateLeft,
     StartNavigatingLeft,
     /// Slider navigation: move to specific position (used in slider mode)
-    SliderNavigate { position: u16 },
+    SliderNavigate { position: u32 },
     /// Switch to left navigation in slider mode (resets position to max)
     SliderStartNavigatingLeft,
     Finish,
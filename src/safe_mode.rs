@@ -0,0 +1,17 @@
+/// `--safe-mode`: start with conservative defaults
+///
+/// For when a setting or driver combination keeps the app from rendering. The settings file
+/// is still read, so it can be fixed from the settings dialog, but BC1 compression, the saved
+/// window size, position and state, the atlas size, the `WGPU_ADAPTER_NAME` adapter choice
+/// and plugins are ignored for the session.
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
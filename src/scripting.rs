@@ -0,0 +1,364 @@
+/// Rhai scripts for custom actions bound to hotkeys
+///
+/// This module is only compiled when the "scripting" feature is enabled.
+/// Scripts are `.rhai` files in `<config dir>/viewskater/scripts/`. A `// key: ctrl+k`
+/// comment in the first lines binds a script to a key; every script can also be run from
+/// the "Scripts" menu. For example, to copy the current file aside and move on:
+///
+/// ```rhai
+/// // key: k
+/// copy_to("~/keep");
+/// next();
+/// ```
+///
+/// Functions available to scripts:
+/// - `current_path()`, `current_name()`, `current_index()`, `image_count()`
+/// - `next()`, `prev()`, `jump(index)`
+/// - `copy_to(dir)`: copy the current file into `dir` (created if missing), returns success
+/// - `toggle_selected()`, `toggle_excluded()`, `clear_mark()`, `export_selection()`
+///   (when built with the "selection" feature)
+/// - `print(text)` writes to the log
+///
+/// Navigation and marks are applied after the script finishes, in call order.
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use iced_core::keyboard::{self, Key};
+use iced_winit::runtime::Task;
+use log::{error, info, warn};
+use rhai::{Engine, AST};
+
+use crate::app::{DataViewer, Message, NavigationMessage};
+
+/// Upper bound on script operations, so a runaway loop cannot freeze the UI
+const MAX_OPERATIONS: u64 = 5_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct KeyBinding {
+    pub key: String,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyBinding {
+    /// Parse `ctrl+shift+k` style bindings; `ctrl` means Cmd on macOS
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut binding = KeyBinding::default();
+        for part in text.split('+').map(|p| p.trim().to_lowercase()) {
+            match part.as_str() {
+                "ctrl" | "cmd" => binding.ctrl = true,
+                "shift" => binding.shift = true,
+                "alt" => binding.alt = true,
+                key if key.chars().count() == 1 && binding.key.is_empty() => binding.key = key.to_string(),
+                _ => return None,
+            }
+        }
+        (!binding.key.is_empty()).then_some(binding)
+    }
+
+    fn matches(&self, key: &keyboard::Key, modifiers: keyboard::Modifiers) -> bool {
+        #[cfg(target_os = "macos")]
+        let ctrl = modifiers.logo();
+        #[cfg(not(target_os = "macos"))]
+        let ctrl = modifiers.control();
+
+        let Key::Character(c) = key.as_ref() else {
+            return false;
+        };
+        c.to_lowercase() == self.key && ctrl == self.ctrl && modifiers.shift() == self.shift && modifiers.alt() == self.alt
+    }
+}
+
+impl std::fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.ctrl { write!(f, "Ctrl+")?; }
+        if self.alt { write!(f, "Alt+")?; }
+        if self.shift { write!(f, "Shift+")?; }
+        write!(f, "{}", self.key.to_uppercase())
+    }
+}
+
+/// Binding declared in the leading comment lines of a script
+pub fn binding_from_source(source: &str) -> Option<KeyBinding> {
+    source.lines()
+        .map(str::trim)
+        .take_while(|line| line.starts_with("//") || line.is_empty())
+        .find_map(|line| line.trim_start_matches('/').trim().strip_prefix("key:").map(str::trim))
+        .and_then(KeyBinding::parse)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptAction {
+    Next,
+    Prev,
+    Jump(usize),
+    #[cfg_attr(not(feature = "selection"), allow(dead_code))]
+    ToggleSelected,
+    #[cfg_attr(not(feature = "selection"), allow(dead_code))]
+    ToggleExcluded,
+    #[cfg_attr(not(feature = "selection"), allow(dead_code))]
+    ClearMark,
+    #[cfg_attr(not(feature = "selection"), allow(dead_code))]
+    ExportSelection,
+}
+
+/// What a script can see of the viewer when it starts
+#[derive(Debug, Clone, Default)]
+pub struct ScriptContext {
+    pub current_path: Option<PathBuf>,
+    pub current_index: usize,
+    pub image_count: usize,
+}
+
+pub struct Script {
+    pub name: String,
+    pub binding: Option<KeyBinding>,
+    ast: AST,
+}
+
+#[derive(Default)]
+pub struct ScriptManager {
+    scripts: Vec<Script>,
+}
+
+impl std::fmt::Debug for ScriptManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScriptManager").field("scripts", &self.scripts.len()).finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ScriptMessage {
+    Run(usize),
+    Reload,
+}
+
+impl From<ScriptMessage> for Message {
+    fn from(msg: ScriptMessage) -> Self {
+        Message::ScriptAction(msg)
+    }
+}
+
+pub fn scripts_dir() -> PathBuf {
+    crate::app_dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("viewskater")
+        .join("scripts")
+}
+
+fn expand_home(dir: &str) -> PathBuf {
+    match dir.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map(|home| home.join(rest)).unwrap_or_else(|| PathBuf::from(dir)),
+        None => PathBuf::from(dir),
+    }
+}
+
+fn copy_into(source: &Path, dir: &str) -> Result<PathBuf, String> {
+    let dir = expand_home(dir);
+    let name = source.file_name().ok_or("current image has no file name")?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let target = dir.join(name);
+    std::fs::copy(source, &target).map_err(|e| format!("{}: {}", target.display(), e))?;
+    Ok(target)
+}
+
+/// Engine with the viewer API bound to `context`, recording actions into `actions`
+fn build_engine(context: ScriptContext, actions: Arc<Mutex<Vec<ScriptAction>>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.on_print(|text| info!("script: {}", text));
+
+    let push = move |action: ScriptAction| {
+        if let Ok(mut actions) = actions.lock() {
+            actions.push(action);
+        }
+    };
+
+    let path = context.current_path.clone();
+    engine.register_fn("current_path", move || path.as_ref().map(|p| p.to_string_lossy().to_string()).unwrap_or_default());
+    let path = context.current_path.clone();
+    engine.register_fn("current_name", move || {
+        path.as_ref().and_then(|p| p.file_name()).map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+    });
+    let (index, count) = (context.current_index as i64, context.image_count as i64);
+    engine.register_fn("current_index", move || index);
+    engine.register_fn("image_count", move || count);
+
+    let path = context.current_path.clone();
+    engine.register_fn("copy_to", move |dir: &str| {
+        let Some(path) = &path else {
+            return false;
+        };
+        match copy_into(path, dir) {
+            Ok(target) => {
+                info!("script: copied {} to {}", path.display(), target.display());
+                true
+            }
+            Err(e) => {
+                warn!("script: copy_to failed: {}", e);
+                false
+            }
+        }
+    });
+
+    let p = push.clone();
+    engine.register_fn("next", move || p(ScriptAction::Next));
+    let p = push.clone();
+    engine.register_fn("prev", move || p(ScriptAction::Prev));
+    let p = push.clone();
+    engine.register_fn("jump", move |index: i64| p(ScriptAction::Jump(index.max(0) as usize)));
+
+    #[cfg(feature = "selection")]
+    {
+        let p = push.clone();
+        engine.register_fn("toggle_selected", move || p(ScriptAction::ToggleSelected));
+        let p = push.clone();
+        engine.register_fn("toggle_excluded", move || p(ScriptAction::ToggleExcluded));
+        let p = push.clone();
+        engine.register_fn("clear_mark", move || p(ScriptAction::ClearMark));
+        let p = push;
+        engine.register_fn("export_selection", move || p(ScriptAction::ExportSelection));
+    }
+
+    engine
+}
+
+/// Run a compiled script and return the actions it asked for
+pub fn run_script(ast: &AST, context: ScriptContext) -> Result<Vec<ScriptAction>, String> {
+    let actions = Arc::new(Mutex::new(Vec::new()));
+    let engine = build_engine(context, Arc::clone(&actions));
+    engine.run_ast(ast).map_err(|e| e.to_string())?;
+    let actions = actions.lock().map(|actions| actions.clone()).unwrap_or_default();
+    Ok(actions)
+}
+
+impl ScriptManager {
+    /// Compile every `.rhai` file in the scripts directory
+    pub fn load() -> Self {
+        let dir = scripts_dir();
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            return Self::default();
+        };
+        let engine = build_engine(ScriptContext::default(), Arc::new(Mutex::new(Vec::new())));
+
+        let mut scripts = Vec::new();
+        for path in entries.filter_map(Result::ok).map(|entry| entry.path()) {
+            if path.extension().and_then(|e| e.to_str()) != Some("rhai") {
+                continue;
+            }
+            let Ok(source) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            match engine.compile(&source) {
+                Ok(ast) => scripts.push(Script {
+                    name: path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+                    binding: binding_from_source(&source),
+                    ast,
+                }),
+                Err(e) => warn!("Skipping script {}: {}", path.display(), e),
+            }
+        }
+        scripts.sort_by(|a, b| a.name.cmp(&b.name));
+        if !scripts.is_empty() {
+            info!("Loaded {} script(s) from {}", scripts.len(), dir.display());
+        }
+        Self { scripts }
+    }
+
+    pub fn scripts(&self) -> &[Script] {
+        &self.scripts
+    }
+}
+
+pub fn handle_script_message(app: &mut DataViewer, msg: ScriptMessage) -> Task<Message> {
+    match msg {
+        ScriptMessage::Reload => {
+            app.script_manager = ScriptManager::load();
+            Task::none()
+        }
+        ScriptMessage::Run(script_index) => {
+            let pane_index = app.active_pane_index();
+            let Some(pane) = app.panes.get(pane_index).filter(|p| p.dir_loaded) else {
+                return Task::none();
+            };
+            let context = ScriptContext {
+                current_path: match pane.img_cache.image_paths.get(pane.img_cache.current_index) {
+                    Some(crate::cache::img_cache::PathSource::Filesystem(path)) => Some(path.clone()),
+                    _ => None,
+                },
+                current_index: pane.img_cache.current_index,
+                image_count: pane.img_cache.image_paths.len(),
+            };
+            let Some(script) = app.script_manager.scripts.get(script_index) else {
+                return Task::none();
+            };
+            match run_script(&script.ast, context) {
+                Ok(actions) => apply_actions(app, pane_index, actions),
+                Err(e) => {
+                    error!("Script {} failed: {}", script.name, e);
+                    Task::none()
+                }
+            }
+        }
+    }
+}
+
+fn apply_actions(app: &mut DataViewer, pane_index: usize, actions: Vec<ScriptAction>) -> Task<Message> {
+    let mut tasks = Vec::new();
+    for action in actions {
+        let task = match action {
+            ScriptAction::Next => app.step_all(true),
+            ScriptAction::Prev => app.step_all(false),
+            ScriptAction::Jump(index) => Task::done(Message::Navigation(NavigationMessage::JumpToImage(pane_index, index))),
+            #[cfg(feature = "selection")]
+            ScriptAction::ToggleSelected => Task::done(crate::widgets::selection_widget::SelectionMessage::MarkImageSelected(pane_index).into()),
+            #[cfg(feature = "selection")]
+            ScriptAction::ToggleExcluded => Task::done(crate::widgets::selection_widget::SelectionMessage::MarkImageExcluded(pane_index).into()),
+            #[cfg(feature = "selection")]
+            ScriptAction::ClearMark => Task::done(crate::widgets::selection_widget::SelectionMessage::ClearImageMark(pane_index).into()),
+            #[cfg(feature = "selection")]
+            ScriptAction::ExportSelection => Task::done(crate::widgets::selection_widget::SelectionMessage::ExportSelectionJson.into()),
+            #[cfg(not(feature = "selection"))]
+            _ => Task::none(),
+        };
+        tasks.push(task);
+    }
+    Task::batch(tasks)
+}
+
+/// Run the script bound to a key, if any
+pub fn handle_keyboard_event(
+    script_manager: &ScriptManager,
+    key: &keyboard::Key,
+    modifiers: keyboard::Modifiers,
+) -> Option<Task<Message>> {
+    script_manager.scripts.iter()
+        .position(|script| script.binding.as_ref().is_some_and(|binding| binding.matches(key, modifiers)))
+        .map(|index| Task::done(ScriptMessage::Run(index).into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binding_from_leading_comment() {
+        let source = "// Keep the current image\n// key: Ctrl+Shift+K\ncopy_to(\"~/keep\");\n// key: j\n";
+        assert_eq!(binding_from_source(source), Some(KeyBinding { key: "k".to_string(), ctrl: true, shift: true, alt: false }));
+        assert_eq!(binding_from_source("next();\n// key: j\n"), None);
+        assert_eq!(KeyBinding::parse("ctrl+f5"), None);
+        assert_eq!(KeyBinding::parse("alt+x").unwrap().to_string(), "Alt+X");
+    }
+
+    #[test]
+    fn test_script_actions_in_call_order() {
+        let engine = Engine::new();
+        let ast = engine.compile("if current_index() + 1 < image_count() { next(); } else { jump(0); } prev();").unwrap();
+        let context = ScriptContext { current_path: None, current_index: 4, image_count: 5 };
+        assert_eq!(run_script(&ast, context).unwrap(), vec![ScriptAction::Jump(0), ScriptAction::Prev]);
+
+        // Runaway loops are stopped instead of freezing the viewer
+        let ast = engine.compile("loop { }").unwrap();
+        assert!(run_script(&ast, ScriptContext::default()).is_err());
+    }
+}
@@ -125,7 +125,7 @@ impl SelectionManager {
 
     /// Get the platform-specific directory for storing selection data
     fn get_selections_dir() -> PathBuf {
-        let data_dir = dirs::data_local_dir()
+        let data_dir = crate::app_dirs::data_local_dir()
             .unwrap_or_else(|| PathBuf::from("."));
         data_dir.join("viewskater").join("selections")
     }
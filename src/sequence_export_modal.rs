@@ -0,0 +1,344 @@
+/// Animation export dialog
+///
+/// Takes a frame range of the active pane (or only the frames marked as selected)
+/// and encodes it with `utils::sequence_export` on a worker thread, streaming
+/// progress back into the dialog.
+use std::path::PathBuf;
+use std::sync::Arc;
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::font::Font;
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, column, container, text, button, Space};
+use log::{info, error};
+
+use crate::app::{Message, DataViewer};
+use crate::utils::sequence_export::{
+    export_sequence, SequenceExportEvent, SequenceExportOptions, SequenceFormat,
+};
+
+/// Dialog state; the range is kept as 0-based inclusive indices
+#[derive(Debug, Clone)]
+pub struct SequenceExportState {
+    pub visible: bool,
+    pub pane_index: usize,
+    pub start: usize,
+    pub end: usize,
+    pub marked_only: bool,
+    pub format: SequenceFormat,
+    pub fps: u32,
+    pub max_size: u32,                         // 0 = original size
+    pub progress: Option<(usize, usize)>,      // (frames done, total) while encoding
+    pub status: Option<String>,
+}
+
+impl Default for SequenceExportState {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            pane_index: 0,
+            start: 0,
+            end: 0,
+            marked_only: false,
+            format: SequenceFormat::Gif,
+            fps: 10,
+            max_size: 512,
+            progress: None,
+            status: None,
+        }
+    }
+}
+
+impl SequenceExportState {
+    fn options(&self) -> SequenceExportOptions {
+        SequenceExportOptions {
+            format: self.format,
+            fps: self.fps,
+            max_size: (self.max_size > 0).then_some(self.max_size),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SequenceExportMessage {
+    Show,
+    Hide,
+    SetStart(u32),
+    SetEnd(u32),
+    SetMarkedOnly(bool),
+    SetFormat(SequenceFormat),
+    SetFps(u32),
+    SetMaxSize(u32),
+    /// Ask for the output file, then start encoding
+    Export,
+    ExportTo(PathBuf),
+    Event(SequenceExportEvent),
+}
+
+impl From<SequenceExportMessage> for Message {
+    fn from(msg: SequenceExportMessage) -> Self {
+        Message::SequenceExportAction(msg)
+    }
+}
+
+pub fn handle_sequence_export_message(app: &mut DataViewer, msg: SequenceExportMessage) -> Task<Message> {
+    let state = &mut app.sequence_export;
+    match msg {
+        SequenceExportMessage::Show => {
+            let pane_index = app.active_pane_index();
+            let Some(pane) = app.panes.get(pane_index).filter(|p| p.dir_loaded) else {
+                return Task::none();
+            };
+            // Keep the previous range when reopening on the same list
+            let last = pane.img_cache.image_paths.len().saturating_sub(1);
+            if state.pane_index != pane_index || state.end > last || state.end == 0 {
+                state.start = 0;
+                state.end = last;
+            }
+            state.pane_index = pane_index;
+            state.visible = true;
+            Task::none()
+        }
+        SequenceExportMessage::Hide => {
+            state.visible = false;
+            Task::none()
+        }
+        SequenceExportMessage::SetStart(start) => {
+            state.start = start as usize;
+            state.end = state.end.max(state.start);
+            Task::none()
+        }
+        SequenceExportMessage::SetEnd(end) => {
+            state.end = end as usize;
+            state.start = state.start.min(state.end);
+            Task::none()
+        }
+        SequenceExportMessage::SetMarkedOnly(enabled) => {
+            state.marked_only = enabled;
+            Task::none()
+        }
+        SequenceExportMessage::SetFormat(format) => {
+            state.format = format;
+            Task::none()
+        }
+        SequenceExportMessage::SetFps(fps) => {
+            state.fps = fps.max(1);
+            Task::none()
+        }
+        SequenceExportMessage::SetMaxSize(max_size) => {
+            state.max_size = max_size;
+            Task::none()
+        }
+        SequenceExportMessage::Export => {
+            if state.progress.is_some() {
+                return Task::none();
+            }
+            let extension = state.format.extension();
+            let label = state.format.label();
+            Task::perform(
+                async move {
+                    rfd::AsyncFileDialog::new()
+                        .set_title("Export animation")
+                        .set_file_name(format!("sequence.{}", extension))
+                        .add_filter(label, &[extension])
+                        .save_file()
+                        .await
+                },
+                |file_handle| match file_handle {
+                    Some(file) => Message::SequenceExportAction(SequenceExportMessage::ExportTo(file.path().to_path_buf())),
+                    None => Message::Nothing,
+                }
+            )
+        }
+        SequenceExportMessage::ExportTo(output) => {
+            let Some(pane) = app.panes.get(state.pane_index).filter(|p| p.dir_loaded) else {
+                return Task::none();
+            };
+            let paths = &pane.img_cache.image_paths;
+            let end = state.end.min(paths.len().saturating_sub(1));
+
+            #[cfg_attr(not(feature = "selection"), allow(unused_mut))]
+            let mut frames: Vec<_> = (state.start..=end).filter_map(|i| paths.get(i).cloned()).collect();
+            #[cfg(feature = "selection")]
+            if state.marked_only {
+                use crate::selection_manager::ImageMark;
+                frames.retain(|p| app.selection_manager.get_mark(&p.file_name()) == ImageMark::Selected);
+            }
+
+            if frames.is_empty() {
+                state.status = Some("Error: no frames in the selected range".to_string());
+                return Task::none();
+            }
+
+            info!("Exporting {} frame(s) as {} to {}", frames.len(), state.format.label(), output.display());
+            state.progress = Some((0, frames.len()));
+            state.status = None;
+
+            let options = state.options();
+            let archive_cache = Arc::clone(&pane.archive_cache);
+            let (sender, receiver) = futures::channel::mpsc::unbounded();
+            std::thread::spawn(move || {
+                let progress_sender = sender.clone();
+                let result = export_sequence(&frames, &archive_cache, &output, options, |done, total| {
+                    let _ = progress_sender.unbounded_send(SequenceExportEvent::Progress(done, total));
+                });
+                let _ = sender.unbounded_send(SequenceExportEvent::Finished(result));
+            });
+
+            Task::run(receiver, |event| Message::SequenceExportAction(SequenceExportMessage::Event(event)))
+        }
+        SequenceExportMessage::Event(SequenceExportEvent::Progress(done, total)) => {
+            state.progress = Some((done, total));
+            Task::none()
+        }
+        SequenceExportMessage::Event(SequenceExportEvent::Finished(result)) => {
+            state.progress = None;
+            state.status = Some(match result {
+                Ok(path) => format!("Saved {}", path.display()),
+                Err(e) => {
+                    error!("Animation export failed: {}", e);
+                    format!("Error: {}", e)
+                }
+            });
+            Task::none()
+        }
+    }
+}
+
+/// Builds the export dialog
+pub fn view_sequence_export_modal(app: &DataViewer) -> Element<'_, Message, WinitTheme, Renderer> {
+    let state = &app.sequence_export;
+    let num_files = app.panes.get(state.pane_index)
+        .map(|p| p.img_cache.image_paths.len())
+        .unwrap_or(0);
+    let last = num_files.saturating_sub(1) as u32;
+    let label_style = |_theme: &WinitTheme| container::Style {
+        text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+        ..container::Style::default()
+    };
+
+    let range = column![
+        row![
+            text(format!("First frame: {}", state.start + 1)).size(13).width(Length::Fixed(130.0)),
+            iced_widget::slider(0..=last, state.start as u32, |v| SequenceExportMessage::SetStart(v).into())
+                .width(Length::Fill),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        row![
+            text(format!("Last frame: {}", state.end + 1)).size(13).width(Length::Fixed(130.0)),
+            iced_widget::slider(0..=last, state.end as u32, |v| SequenceExportMessage::SetEnd(v).into())
+                .width(Length::Fill),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        text(format!("{} of {} frames", state.end + 1 - state.start.min(state.end), num_files)).size(12),
+    ]
+    .spacing(5);
+
+    #[cfg(feature = "selection")]
+    let marked_only: Element<'_, Message, WinitTheme, Renderer> = container(
+        crate::widgets::toggler::Toggler::new(
+            Some("Only frames marked as selected".into()),
+            state.marked_only,
+            |enabled| SequenceExportMessage::SetMarkedOnly(enabled).into(),
+        ).width(Length::Fill)
+    ).style(label_style).into();
+    #[cfg(not(feature = "selection"))]
+    let marked_only: Element<'_, Message, WinitTheme, Renderer> = Space::with_height(0).into();
+
+    let formats = SequenceFormat::ALL.iter().fold(row![].spacing(15), |formats, format| {
+        formats.push(iced_widget::Radio::new(
+            format.label(),
+            *format,
+            Some(state.format),
+            |f| SequenceExportMessage::SetFormat(f).into(),
+        ))
+    });
+
+    let size_label = if state.max_size == 0 {
+        "Max size: original".to_string()
+    } else {
+        format!("Max size: {}px", state.max_size)
+    };
+    let encoding = column![
+        row![
+            text(format!("FPS: {}", state.fps)).size(13).width(Length::Fixed(130.0)),
+            iced_widget::slider(1..=60u32, state.fps, |v| SequenceExportMessage::SetFps(v).into())
+                .width(Length::Fill),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        row![
+            text(size_label).size(13).width(Length::Fixed(130.0)),
+            iced_widget::slider(0..=1920u32, state.max_size, |v| SequenceExportMessage::SetMaxSize(v).into())
+                .step(32u32)
+                .width(Length::Fill),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    ]
+    .spacing(5);
+
+    let status_text = match (state.progress, state.status.as_deref()) {
+        (Some((done, total)), _) => format!("Encoding frame {} / {}", done, total),
+        (None, Some(status)) => status.to_string(),
+        (None, None) => " ".to_string(),
+    };
+    let is_error = status_text.starts_with("Error");
+
+    let content = column![
+        text("Export Animation").size(18)
+            .font(Font {
+                family: iced_winit::core::font::Family::Name("Roboto"),
+                weight: iced_winit::core::font::Weight::Bold,
+                stretch: iced_winit::core::font::Stretch::Normal,
+                style: iced_winit::core::font::Style::Normal,
+            }),
+        container(range).style(label_style),
+        marked_only,
+        container(formats).style(label_style),
+        container(encoding).style(label_style),
+        container(text(status_text).size(14))
+            .style(move |theme: &WinitTheme| container::Style {
+                text_color: Some(if is_error {
+                    theme.extended_palette().danger.strong.color
+                } else {
+                    theme.extended_palette().success.strong.color
+                }),
+                ..container::Style::default()
+            })
+            .height(Length::Fixed(18.0)),
+        row![
+            button(text("Export..."))
+                .padding([3, 10])
+                .on_press_maybe((state.progress.is_none() && num_files > 0)
+                    .then_some(SequenceExportMessage::Export.into())),
+            Space::with_width(Length::Fill),
+            button(text("Close"))
+                .padding([3, 10])
+                .on_press(SequenceExportMessage::Hide.into()),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    ]
+    .spacing(10)
+    .width(Length::Fixed(480.0));
+
+    container(content)
+        .padding(15)
+        .style(|theme: &WinitTheme| {
+            iced_widget::container::Style {
+                background: Some(theme.extended_palette().background.base.color.into()),
+                text_color: Some(theme.extended_palette().primary.weak.text),
+                border: iced_winit::core::Border {
+                    color: theme.extended_palette().background.strong.color,
+                    width: 1.0,
+                    radius: iced_winit::core::border::Radius::from(8.0),
+                },
+                ..Default::default()
+            }
+        })
+        .into()
+}
@@ -0,0 +1,340 @@
+/// Review sessions saved to a file and shared
+///
+/// "Save Session..." writes what the panes show to a `.vsession` file (YAML): each pane's
+/// folder, archive or image list with the image shown and the part of it in view, the pane
+/// layout, the favorites among the shown images and, with the coco feature, the annotation
+/// file loaded for the folder. Opening the file with "Open Session...", by dropping it on the
+/// window or by passing it on the command line brings all of that back, so a reviewer can
+/// hand the exact context of a finding to someone else. Paths are stored as they are, so the
+/// files need to be at the same place on both machines, such as a shared drive.
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use iced_winit::runtime::Task;
+use log::{info, warn};
+
+use crate::app::{DataViewer, Message, NavigationMessage};
+use crate::favorites::Favorite;
+use crate::menu::PaneLayout;
+use crate::playlist::PlaylistSource;
+
+const SESSION_EXTENSION: &str = "vsession";
+const SESSION_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+struct Layout {
+    dual_pane: bool,
+    horizontal_split: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    divider_position: Option<u16>,
+    /// Each pane has a slider of its own
+    dual_slider: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct PaneSession {
+    /// Folder or archive the pane shows; None for an image list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source: Option<PathBuf>,
+    /// Images of a list that isn't a folder, in their order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    images: Vec<PathBuf>,
+    /// Image shown, inside the archive for an archive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    image: Option<PathBuf>,
+    index: usize,
+    /// Part of the image in view as fractions of its size (left, top, right, bottom), when
+    /// zoomed in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    view: Option<[f32; 4]>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct AnnotationLink {
+    file: PathBuf,
+    image_directory: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Session {
+    version: u32,
+    #[serde(default)]
+    layout: Layout,
+    panes: Vec<PaneSession>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    favorites: Vec<Favorite>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    annotations: Option<AnnotationLink>,
+}
+
+/// Image and view of a pane still to be brought back after its folder is listed
+#[derive(Debug)]
+struct PendingPane {
+    pane_index: usize,
+    index: usize,
+    view: Option<[f32; 4]>,
+    jumped: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct SessionRestore {
+    pending: Vec<PendingPane>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SessionMessage {
+    Save,
+    SaveTo(PathBuf),
+    Open,
+    OpenFrom(PathBuf),
+}
+
+impl From<SessionMessage> for Message {
+    fn from(msg: SessionMessage) -> Self {
+        Message::SessionAction(msg)
+    }
+}
+
+pub fn is_session_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case(SESSION_EXTENSION))
+}
+
+/// The part of the image a pane shows, unless it shows all of it
+fn zoomed_view(pane_index: usize) -> Option<[f32; 4]> {
+    crate::noise_analysis::visible_region(pane_index)
+        .filter(|&[left, top, right, bottom]| left > 0.0 || top > 0.0 || right < 1.0 || bottom < 1.0)
+}
+
+fn pane_session(app: &DataViewer, pane_index: usize) -> Option<PaneSession> {
+    let pane = app.panes.get(pane_index).filter(|pane| pane.dir_loaded)?;
+    let index = pane.img_cache.current_index;
+    let image = pane.img_cache.image_paths.get(index).map(|source| source.path().clone());
+    let (source, images) = match pane.playlist {
+        Some(_) => (None, pane.img_cache.image_paths.iter().map(|source| source.path().clone()).collect()),
+        None => (Some(PathBuf::from(pane.directory_path.as_ref()?)), Vec::new()),
+    };
+    Some(PaneSession { source, images, image, index, view: zoomed_view(pane_index) })
+}
+
+impl Session {
+    fn capture(app: &DataViewer) -> Self {
+        let panes: Vec<PaneSession> = (0..app.panes.len()).filter_map(|pane_index| pane_session(app, pane_index)).collect();
+
+        // Favorites in the shown folders and lists
+        let is_shown = |path: &Path| panes.iter().any(|pane| {
+            pane.source.as_deref().is_some_and(|source| path.parent() == Some(source)) || pane.images.iter().any(|image| image == path)
+        });
+        let favorites = app.favorites.among(is_shown);
+
+        #[cfg(feature = "coco")]
+        let annotations = match (app.annotation_manager.get_json_path(), app.annotation_manager.get_image_directory()) {
+            (Some(file), Some(image_directory)) => Some(AnnotationLink { file: file.clone(), image_directory: image_directory.clone() }),
+            _ => None,
+        };
+        #[cfg(not(feature = "coco"))]
+        let annotations = None;
+
+        Self {
+            version: SESSION_VERSION,
+            layout: Layout {
+                dual_pane: app.pane_layout == PaneLayout::DualPane,
+                horizontal_split: app.is_horizontal_split,
+                divider_position: app.divider_position,
+                dual_slider: app.is_slider_dual,
+            },
+            panes,
+            favorites,
+            annotations,
+        }
+    }
+}
+
+fn save(app: &DataViewer, path: &Path) -> Result<(), String> {
+    let session = Session::capture(app);
+    if session.panes.is_empty() {
+        return Err("no folder is open".to_string());
+    }
+    let yaml = serde_yaml::to_string(&session).map_err(|e| e.to_string())?;
+    std::fs::write(path, yaml).map_err(|e| e.to_string())
+}
+
+fn read(path: &Path) -> Result<Session, String> {
+    let yaml = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let session: Session = serde_yaml::from_str(&yaml).map_err(|e| e.to_string())?;
+    if session.version > SESSION_VERSION {
+        warn!("Session {} is from a newer version; parts of it may be ignored", path.display());
+    }
+    Ok(session)
+}
+
+/// Open the source of a saved pane, at its image when the listing can find it
+fn open_pane(app: &mut DataViewer, pane_index: usize, pane: &PaneSession) -> Task<Message> {
+    if !pane.images.is_empty() {
+        return app.open_path_list(pane.images.clone(), pane_index, pane.index, PlaylistSource::PathList);
+    }
+    let Some(source) = &pane.source else {
+        return Task::none();
+    };
+    match &pane.image {
+        Some(image) if source.is_dir() && image.is_file() => app.initialize_dir_path(image, pane_index),
+        _ => app.initialize_dir_path(source, pane_index),
+    }
+}
+
+fn open(app: &mut DataViewer, session: Session) -> Task<Message> {
+    app.reset_state(-1);
+    let layout = if session.layout.dual_pane && session.panes.len() > 1 { PaneLayout::DualPane } else { PaneLayout::SinglePane };
+    app.toggle_pane_layout(layout);
+    app.is_horizontal_split = session.layout.horizontal_split;
+    app.divider_position = session.layout.divider_position;
+    if app.is_slider_dual != session.layout.dual_slider {
+        app.toggle_slider_type();
+    }
+    app.favorites.merge(session.favorites);
+
+    let pane_count = if layout == PaneLayout::DualPane { 2 } else { 1 };
+    let mut tasks = Vec::new();
+    app.session_restore.pending.clear();
+    for (pane_index, pane) in session.panes.iter().enumerate().take(pane_count) {
+        tasks.push(open_pane(app, pane_index, pane));
+        app.session_restore.pending.push(PendingPane { pane_index, index: pane.index, view: pane.view, jumped: false });
+    }
+
+    #[cfg(feature = "coco")]
+    if let Some(link) = session.annotations.filter(|link| link.file.is_file()) {
+        tasks.push(Task::done(crate::coco::widget::CocoMessage::LoadCocoFileFor(link.file, link.image_directory).into()));
+    }
+    Task::batch(tasks)
+}
+
+/// Moves panes of an opened session to their saved image once listed, then brings the
+/// saved part of it into view
+pub fn restore_pending(app: &mut DataViewer) -> Option<Task<Message>> {
+    if app.session_restore.pending.is_empty() {
+        return None;
+    }
+    let mut tasks = Vec::new();
+    let panes = &app.panes;
+    app.session_restore.pending.retain_mut(|pending| {
+        let Some(pane) = panes.get(pending.pane_index) else {
+            return false;
+        };
+        if !pane.dir_loaded || pane.indexing.is_some() {
+            return true;
+        }
+        if pending.index >= pane.img_cache.image_paths.len() {
+            warn!("Session image {} is past the end of pane {}", pending.index, pending.pane_index);
+            return false;
+        }
+        if pane.img_cache.current_index != pending.index {
+            if !pending.jumped {
+                pending.jumped = true;
+                tasks.push(Task::done(Message::Navigation(NavigationMessage::JumpToImage(pending.pane_index, pending.index))));
+            }
+            return true;
+        }
+        if pane.scene.as_ref().and_then(|scene| scene.get_texture()).is_none() {
+            return true;
+        }
+        if let Some(view) = pending.view {
+            crate::zoom_lock::request_view(pending.pane_index, view);
+        }
+        false
+    });
+    (!tasks.is_empty()).then(|| Task::batch(tasks))
+}
+
+pub fn handle_session_message(app: &mut DataViewer, msg: SessionMessage) -> Task<Message> {
+    match msg {
+        SessionMessage::Save => Task::perform(
+            async {
+                rfd::AsyncFileDialog::new()
+                    .set_title("Save session")
+                    .set_file_name(format!("review.{}", SESSION_EXTENSION))
+                    .add_filter("ViewSkater session", &[SESSION_EXTENSION])
+                    .save_file()
+                    .await
+            },
+            |file_handle| match file_handle {
+                Some(file) => SessionMessage::SaveTo(file.path().to_path_buf()).into(),
+                None => Message::Nothing,
+            }
+        ),
+        SessionMessage::SaveTo(path) => {
+            match save(app, &path) {
+                Ok(()) => info!("Saved session to {}", path.display()),
+                Err(e) => {
+                    warn!("Failed to save session to {}: {}", path.display(), e);
+                    app.set_failure_save_modal(Some(e));
+                }
+            }
+            Task::none()
+        }
+        SessionMessage::Open => Task::perform(
+            async {
+                rfd::AsyncFileDialog::new()
+                    .set_title("Open session")
+                    .add_filter("ViewSkater session", &[SESSION_EXTENSION])
+                    .pick_file()
+                    .await
+            },
+            |file_handle| match file_handle {
+                Some(file) => SessionMessage::OpenFrom(file.path().to_path_buf()).into(),
+                None => Message::Nothing,
+            }
+        ),
+        SessionMessage::OpenFrom(path) => match read(&path) {
+            Ok(session) => {
+                info!("Opening session {}", path.display());
+                open(app, session)
+            }
+            Err(e) => {
+                warn!("Failed to open session {}: {}", path.display(), e);
+                Task::none()
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_round_trip() {
+        let session = Session {
+            version: SESSION_VERSION,
+            layout: Layout { dual_pane: true, horizontal_split: false, divider_position: Some(640), dual_slider: false },
+            panes: vec![
+                PaneSession {
+                    source: Some(PathBuf::from("/data/run1")),
+                    images: Vec::new(),
+                    image: Some(PathBuf::from("/data/run1/0042.png")),
+                    index: 41,
+                    view: Some([0.25, 0.25, 0.5, 0.5]),
+                },
+                PaneSession {
+                    source: None,
+                    images: vec![PathBuf::from("/data/b.png"), PathBuf::from("/data/a.png")],
+                    image: Some(PathBuf::from("/data/a.png")),
+                    index: 1,
+                    view: None,
+                },
+            ],
+            favorites: Vec::new(),
+            annotations: Some(AnnotationLink {
+                file: PathBuf::from("/data/annotations.json"),
+                image_directory: PathBuf::from("/data/run1"),
+            }),
+        };
+        let yaml = serde_yaml::to_string(&session).unwrap();
+        assert_eq!(serde_yaml::from_str::<Session>(&yaml).unwrap(), session);
+
+        // Only the version and panes are required
+        let minimal: Session = serde_yaml::from_str("version: 1\npanes:\n  - source: /data/run1\n    index: 3\n").unwrap();
+        assert_eq!(minimal.layout, Layout::default());
+        assert_eq!(minimal.panes[0].index, 3);
+
+        assert!(is_session_file(Path::new("review.VSession")));
+        assert!(!is_session_file(Path::new("review.yaml")));
+    }
+}
@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use log::{debug, info, warn, error};
 use iced_wgpu::engine::CompressionStrategy;
 use crate::cache::img_cache::CacheStrategy;
+use crate::cache::source_strategy::{self, SourceStrategies, StrategyOverride};
 use crate::config;
 
 /// User-specific settings that persist across app sessions
@@ -25,7 +26,7 @@ pub struct UserSettings {
     #[serde(default = "default_synced_zoom")]
     pub synced_zoom: bool,
 
-    /// Enable mouse wheel zoom
+    /// Enable mouse wheel zoom; Ctrl + wheel then navigates instead of zooming
     #[serde(default)]
     pub mouse_wheel_zoom: bool,
 
@@ -37,6 +38,17 @@ pub struct UserSettings {
     #[serde(default = "default_compression_strategy")]
     pub compression_strategy: String,
 
+    /// Cache and compression strategies for archives and network folders; empty keeps the
+    /// local folder ones
+    #[serde(default)]
+    pub archive_cache_strategy: String,
+    #[serde(default)]
+    pub archive_compression_strategy: String,
+    #[serde(default)]
+    pub remote_cache_strategy: String,
+    #[serde(default)]
+    pub remote_compression_strategy: String,
+
     /// Slider type: dual (true) or single (false)
     #[serde(default)]
     pub is_slider_dual: bool,
@@ -90,6 +102,46 @@ pub struct UserSettings {
     #[serde(default = "default_archive_warning_threshold_mb")]
     pub archive_warning_threshold_mb: u64,
 
+    /// Memory kept for decoded images of recently closed directories (MB)
+    #[serde(default = "default_directory_cache_mb")]
+    pub directory_cache_mb: u64,
+
+    /// Number of images decoded in parallel
+    #[serde(default = "default_decode_threads")]
+    pub decode_threads: usize,
+
+    /// Number of files read from disk at the same time
+    #[serde(default = "default_max_concurrent_reads")]
+    pub max_concurrent_reads: usize,
+
+    /// Seconds an image load may take before it is retried, then replaced by an error placeholder (0 = no limit)
+    #[serde(default = "default_load_timeout_secs")]
+    pub load_timeout_secs: u64,
+
+    /// List symlinked images when reading a directory (broken links are always skipped)
+    #[serde(default = "default_follow_symlinks")]
+    pub follow_symlinks: bool,
+
+    /// Look for a newer release on GitHub at startup (builds with the `update-check` feature)
+    #[serde(default)]
+    pub check_for_updates: bool,
+
+    /// Ask before moving files to the trash (permanent deletion always asks)
+    #[serde(default = "default_confirm_delete")]
+    pub confirm_delete: bool,
+
+    /// Keep the images viewed in each folder across sessions
+    #[serde(default)]
+    pub persist_viewed: bool,
+
+    /// Decode large JPEGs at a reduced scale that still covers the window, in full once zoomed past 1:1
+    #[serde(default)]
+    pub load_for_display: bool,
+
+    /// Convert images with an embedded ICC profile (Display P3, Adobe RGB, ...) to sRGB when decoding
+    #[serde(default = "default_convert_icc_profiles")]
+    pub convert_icc_profiles: bool,
+
     /// COCO: Disable polygon simplification for segmentation masks
     #[serde(default)]
     pub coco_disable_simplification: bool,
@@ -98,6 +150,22 @@ pub struct UserSettings {
     #[serde(default)]
     pub coco_mask_render_mode: CocoMaskRenderMode,
 
+    /// COCO: Show category/score labels on bounding boxes
+    #[serde(default = "default_coco_show_labels")]
+    pub coco_show_labels: bool,
+
+    /// COCO: Label font size in pixels
+    #[serde(default = "default_coco_label_size")]
+    pub coco_label_size: f32,
+
+    /// COCO: Padding added around each bbox when exporting crops, in percent of the bbox size
+    #[serde(default)]
+    pub coco_crop_padding: f32,
+
+    /// COCO: Longest side of exported crops in pixels (0 keeps the original size)
+    #[serde(default)]
+    pub coco_crop_size: u32,
+
     /// Use binary file size units (KiB/MiB with 1024 divisor) instead of decimal (KB/MB with 1000)
     /// - true: Binary units like `ls -lh` (1 KiB = 1024 bytes)
     /// - false: Decimal units like GNOME/macOS/Windows (1 KB = 1000 bytes)
@@ -108,6 +176,14 @@ pub struct UserSettings {
     #[serde(default)]
     pub spinner_location: SpinnerLocation,
 
+    /// How the menu bar is drawn
+    #[serde(default)]
+    pub menu_bar_mode: MenuBarMode,
+
+    /// Menus and Controls entries left out of the menu bar (see `menu_layout`)
+    #[serde(default)]
+    pub hidden_menus: Vec<String>,
+
     // Window position and state
     #[serde(default)]
     pub window_position_x: i32,
@@ -148,6 +224,18 @@ impl Default for SpinnerLocation {
     }
 }
 
+/// How the menu bar is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum MenuBarMode {
+    /// Menu names (default)
+    #[default]
+    Full,
+    /// One glyph per menu
+    Compact,
+    /// Not drawn until Alt is tapped
+    Hidden,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum WindowState {
     #[default]
@@ -218,6 +306,42 @@ fn default_archive_warning_threshold_mb() -> u64 {
     config::DEFAULT_ARCHIVE_WARNING_THRESHOLD_MB
 }
 
+fn default_directory_cache_mb() -> u64 {
+    config::DEFAULT_DIRECTORY_CACHE_MB
+}
+
+fn default_decode_threads() -> usize {
+    config::DEFAULT_DECODE_THREADS
+}
+
+fn default_max_concurrent_reads() -> usize {
+    config::DEFAULT_MAX_CONCURRENT_READS
+}
+
+fn default_load_timeout_secs() -> u64 {
+    config::DEFAULT_LOAD_TIMEOUT_SECS
+}
+
+fn default_follow_symlinks() -> bool {
+    true
+}
+
+fn default_confirm_delete() -> bool {
+    true
+}
+
+fn default_convert_icc_profiles() -> bool {
+    true
+}
+
+fn default_coco_show_labels() -> bool {
+    true
+}
+
+fn default_coco_label_size() -> f32 {
+    13.0
+}
+
 impl Default for UserSettings {
     fn default() -> Self {
         Self {
@@ -228,6 +352,10 @@ impl Default for UserSettings {
             mouse_wheel_zoom: false,
             cache_strategy: "gpu".to_string(),
             compression_strategy: "none".to_string(),
+            archive_cache_strategy: String::new(),
+            archive_compression_strategy: String::new(),
+            remote_cache_strategy: String::new(),
+            remote_compression_strategy: String::new(),
             is_slider_dual: false,
             show_copy_buttons: true,
             show_metadata: true,
@@ -241,10 +369,26 @@ impl Default for UserSettings {
             double_click_threshold_ms: config::DEFAULT_DOUBLE_CLICK_THRESHOLD_MS,
             archive_cache_size: config::DEFAULT_ARCHIVE_CACHE_SIZE,
             archive_warning_threshold_mb: config::DEFAULT_ARCHIVE_WARNING_THRESHOLD_MB,
+            directory_cache_mb: config::DEFAULT_DIRECTORY_CACHE_MB,
+            decode_threads: config::DEFAULT_DECODE_THREADS,
+            max_concurrent_reads: config::DEFAULT_MAX_CONCURRENT_READS,
+            load_timeout_secs: config::DEFAULT_LOAD_TIMEOUT_SECS,
+            follow_symlinks: true,
+            check_for_updates: false,
+            confirm_delete: true,
+            persist_viewed: false,
+            load_for_display: false,
+            convert_icc_profiles: true,
             coco_disable_simplification: false,
             coco_mask_render_mode: CocoMaskRenderMode::default(),
+            coco_show_labels: true,
+            coco_label_size: 13.0,
+            coco_crop_padding: 0.0,
+            coco_crop_size: 0,
             use_binary_size: false,  // Default to decimal (GNOME/macOS/Windows style)
             spinner_location: SpinnerLocation::default(),
+            menu_bar_mode: MenuBarMode::default(),
+            hidden_menus: Vec::new(),
             window_position_x: 0,
             window_position_y: 0,
             window_state: WindowState::Window,
@@ -257,8 +401,9 @@ impl UserSettings {
     /// On macOS: ~/Library/Application Support/viewskater/settings.yaml
     /// On Linux: ~/.config/viewskater/settings.yaml
     /// On Windows: C:\Users\<user>\AppData\Roaming\viewskater\settings.yaml
+    /// In portable mode: <executable dir>/viewskater-data/config/viewskater/settings.yaml
     pub fn settings_path() -> PathBuf {
-        let config_dir = dirs::config_dir()
+        let config_dir = crate::app_dirs::config_dir()
             .unwrap_or_else(|| PathBuf::from("."));
 
         let app_config_dir = config_dir.join("viewskater");
@@ -359,6 +504,10 @@ impl UserSettings {
         result = Self::replace_yaml_value_or_track(&result, "mouse_wheel_zoom", &self.mouse_wheel_zoom.to_string(), &mut missing_keys);
         result = Self::replace_yaml_value_or_track(&result, "cache_strategy", &format!("\"{}\"", self.cache_strategy), &mut missing_keys);
         result = Self::replace_yaml_value_or_track(&result, "compression_strategy", &format!("\"{}\"", self.compression_strategy), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "archive_cache_strategy", &format!("\"{}\"", self.archive_cache_strategy), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "archive_compression_strategy", &format!("\"{}\"", self.archive_compression_strategy), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "remote_cache_strategy", &format!("\"{}\"", self.remote_cache_strategy), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "remote_compression_strategy", &format!("\"{}\"", self.remote_compression_strategy), &mut missing_keys);
         result = Self::replace_yaml_value_or_track(&result, "is_slider_dual", &self.is_slider_dual.to_string(), &mut missing_keys);
         result = Self::replace_yaml_value_or_track(&result, "show_copy_buttons", &self.show_copy_buttons.to_string(), &mut missing_keys);
         result = Self::replace_yaml_value_or_track(&result, "show_metadata", &self.show_metadata.to_string(), &mut missing_keys);
@@ -374,6 +523,16 @@ impl UserSettings {
         result = Self::replace_yaml_value_or_track(&result, "double_click_threshold_ms", &self.double_click_threshold_ms.to_string(), &mut missing_keys);
         result = Self::replace_yaml_value_or_track(&result, "archive_cache_size", &self.archive_cache_size.to_string(), &mut missing_keys);
         result = Self::replace_yaml_value_or_track(&result, "archive_warning_threshold_mb", &self.archive_warning_threshold_mb.to_string(), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "directory_cache_mb", &self.directory_cache_mb.to_string(), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "decode_threads", &self.decode_threads.to_string(), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "max_concurrent_reads", &self.max_concurrent_reads.to_string(), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "load_timeout_secs", &self.load_timeout_secs.to_string(), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "follow_symlinks", &self.follow_symlinks.to_string(), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "check_for_updates", &self.check_for_updates.to_string(), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "confirm_delete", &self.confirm_delete.to_string(), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "persist_viewed", &self.persist_viewed.to_string(), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "load_for_display", &self.load_for_display.to_string(), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "convert_icc_profiles", &self.convert_icc_profiles.to_string(), &mut missing_keys);
 
         // Update COCO settings
         result = Self::replace_yaml_value_or_track(&result, "coco_disable_simplification", &self.coco_disable_simplification.to_string(), &mut missing_keys);
@@ -381,6 +540,10 @@ impl UserSettings {
             CocoMaskRenderMode::Polygon => "Polygon",
             CocoMaskRenderMode::Pixel => "Pixel",
         }), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "coco_show_labels", &self.coco_show_labels.to_string(), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "coco_label_size", &self.coco_label_size.to_string(), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "coco_crop_padding", &self.coco_crop_padding.to_string(), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "coco_crop_size", &self.coco_crop_size.to_string(), &mut missing_keys);
 
         // Update display settings
         result = Self::replace_yaml_value_or_track(&result, "use_binary_size", &self.use_binary_size.to_string(), &mut missing_keys);
@@ -389,6 +552,8 @@ impl UserSettings {
             SpinnerLocation::MenuBar => "MenuBar",
             SpinnerLocation::None => "None",
         }), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "menu_bar_mode", &format!("\"{}\"", self.menu_bar_mode_name()), &mut missing_keys);
+        result = Self::replace_yaml_value_or_track(&result, "hidden_menus", &self.hidden_menus_yaml(), &mut missing_keys);
 
         result = Self::replace_yaml_value_or_track(&result, "window_position_x", &self.window_position_x.to_string(), &mut missing_keys);
         result = Self::replace_yaml_value_or_track(&result, "window_position_y", &self.window_position_y.to_string(), &mut missing_keys);
@@ -404,7 +569,9 @@ impl UserSettings {
                 matches!(k.0.as_str(),
                     "cache_size" | "max_loading_queue_size" | "max_being_loaded_queue_size" |
                     "window_width" | "window_height" | "atlas_size" |
-                    "double_click_threshold_ms" | "archive_cache_size" | "archive_warning_threshold_mb")
+                    "double_click_threshold_ms" | "archive_cache_size" | "archive_warning_threshold_mb" |
+                    "directory_cache_mb" | "decode_threads" | "max_concurrent_reads" | "load_timeout_secs" | "follow_symlinks" | "check_for_updates" |
+                    "confirm_delete" | "persist_viewed" | "load_for_display" | "convert_icc_profiles")
             });
 
             if needs_header && !result.contains("# --- Advanced Settings ---") {
@@ -438,11 +605,31 @@ impl UserSettings {
             "double_click_threshold_ms" => "# Double-click detection threshold (milliseconds)".to_string(),
             "archive_cache_size" => "# Max size for compressed file cache (bytes)".to_string(),
             "archive_warning_threshold_mb" => "# Warning threshold for solid archives (megabytes)".to_string(),
+            "directory_cache_mb" => "# Memory for decoded images of recently closed directories (megabytes, 0 = disabled)".to_string(),
+            "decode_threads" => "# Number of images decoded in parallel".to_string(),
+            "max_concurrent_reads" => "# Number of files read from disk at the same time (lower for HDDs and network storage)".to_string(),
+            "load_timeout_secs" => "# Seconds before a stuck image load is retried once, then shown as an error (0 = no limit)".to_string(),
+            "follow_symlinks" => "# List symlinked images when opening a folder (false = skip all links)".to_string(),
+            "check_for_updates" => "# Check GitHub for a newer release at startup (off by default, no other data is sent)".to_string(),
+            "confirm_delete" => "# Ask before moving files to the trash (permanent deletion always asks)".to_string(),
+            "persist_viewed" => "# Remember which images of each folder were viewed across sessions".to_string(),
+            "load_for_display" => "# Decode large JPEGs at the window's size, in full only when zoomed past 1:1".to_string(),
+            "convert_icc_profiles" => "# Convert images with an embedded ICC profile to sRGB when decoding (false = faster, restart required)".to_string(),
             "coco_disable_simplification" => "# COCO: Disable polygon simplification (more accurate but slower)".to_string(),
             "coco_mask_render_mode" => "# COCO: Mask rendering mode (Polygon or Pixel)".to_string(),
+            "coco_show_labels" => "# COCO: Show category/score labels on bounding boxes".to_string(),
+            "coco_label_size" => "# COCO: Bounding box label font size (pixels)".to_string(),
+            "coco_crop_padding" => "# COCO: Crop export padding around each bbox (percent of bbox size)".to_string(),
+            "coco_crop_size" => "# COCO: Crop export longest side in pixels (0 = original size)".to_string(),
+            "archive_cache_strategy" => "# Cache strategy for archives: \"cpu\", \"gpu\" or \"\" (same as cache_strategy)".to_string(),
+            "archive_compression_strategy" => "# Compression strategy for archives: \"none\", \"bc1\" or \"\" (same as compression_strategy)".to_string(),
+            "remote_cache_strategy" => "# Cache strategy for network folders: \"cpu\", \"gpu\" or \"\" (same as cache_strategy)".to_string(),
+            "remote_compression_strategy" => "# Compression strategy for network folders: \"none\", \"bc1\" or \"\" (same as compression_strategy)".to_string(),
             "use_binary_size" => "# Use binary file size units (true = KiB/MiB like ls -lh, false = KB/MB like GNOME)".to_string(),
             "show_metadata" => "# Show image metadata (resolution, file size) in footer".to_string(),
             "spinner_location" => "# Loading spinner location: Footer, MenuBar, or None".to_string(),
+            "menu_bar_mode" => "# Menu bar: Full, Compact (one glyph per menu), or Hidden (tap Alt to show)".to_string(),
+            "hidden_menus" => "# Menus and Controls entries left out of the menu bar, e.g. [\"Plugins\", \"Stereo\"]".to_string(),
             _ => String::new(),
         }
     }
@@ -491,6 +678,7 @@ is_horizontal_split: {}
 synced_zoom: {}
 
 # Enable mouse wheel zoom (false = mouse wheel navigates images)
+# Ctrl + wheel does the other one
 mouse_wheel_zoom: {}
 
 # Cache strategy: "cpu" or "gpu"
@@ -503,6 +691,15 @@ cache_strategy: "{}"
 # - "bc1": BC1/DXT1 compression (lower quality, less VRAM usage, faster for large images)
 compression_strategy: "{}"
 
+# Cache and compression strategies for archives (zip/rar/7z) and folders on network shares
+# - "": Same as cache_strategy / compression_strategy above
+# - "cpu" / "gpu" and "none" / "bc1": As above, for that kind of source only
+# Archives are slow to seek and often do best with "cpu"
+archive_cache_strategy: "{}"
+archive_compression_strategy: "{}"
+remote_cache_strategy: "{}"
+remote_compression_strategy: "{}"
+
 # Slider type for navigation
 # - true: Dual slider (independent sliders for each pane)
 # - false: Single slider (shared across panes)
@@ -548,6 +745,50 @@ archive_cache_size: {}
 # Warning threshold for solid archives (megabytes)
 archive_warning_threshold_mb: {}
 
+# Memory for decoded images of recently closed directories, so switching back is instant
+# (megabytes, 0 = disabled)
+directory_cache_mb: {}
+
+# Number of images decoded in parallel (higher uses more CPU cores)
+decode_threads: {}
+
+# Number of files read from disk at the same time
+# Use 1-2 for hard drives and network storage, more for NVMe drives
+max_concurrent_reads: {}
+
+# Seconds an image load may take before it is cancelled and retried once, e.g. on a stalled
+# network mount; if the retry also runs over, an error placeholder is shown (0 = no limit)
+load_timeout_secs: {}
+
+# List images that are symbolic links (or junctions) when opening a folder
+# - true: Links to image files are listed; broken links are skipped
+# - false: All links are skipped
+follow_symlinks: {}
+
+# Check GitHub for a newer release at startup and show a banner if one exists
+# Sends one request to the GitHub releases API; nothing else is collected or sent
+check_for_updates: {}
+
+# Ask for confirmation before moving files to the trash (File > Move to Trash, Delete key)
+# Files are never deleted permanently without asking, even when this is false
+confirm_delete: {}
+
+# Remember which images of each folder have been viewed across sessions
+# - true: Kept per folder in the app's data directory, for resuming long reviews
+# - false: Viewed images are only tracked until the app is closed
+persist_viewed: {}
+
+# Decode JPEGs much larger than the window at 1/2, 1/4 or 1/8 scale for quick browsing
+# - true: Faster decodes and less cache memory; the full image is decoded when zoomed past 1:1
+# - false: Every image is decoded at full resolution
+load_for_display: {}
+
+# Convert images with an embedded ICC profile (Display P3, Adobe RGB, ...) to sRGB when decoding
+# - true: Wide-gamut photos show their intended colors (a display profile then applies on top)
+# - false: Values are shown as if they were sRGB, which skips the conversion for speed
+# Requires restart
+convert_icc_profiles: {}
+
 # --- COCO Settings ---
 
 # Disable polygon simplification for segmentation masks (more accurate but slower)
@@ -556,6 +797,18 @@ coco_disable_simplification: {}
 # Mask rendering mode: "Polygon" (vector, scalable) or "Pixel" (raster, exact)
 coco_mask_render_mode: "{}"
 
+# Show category name (and score, if present) labels on bounding boxes
+coco_show_labels: {}
+
+# Bounding box label font size (pixels)
+coco_label_size: {}
+
+# Padding added around each bbox when exporting annotation crops (percent of bbox width/height)
+coco_crop_padding: {}
+
+# Resize exported crops so the longest side is this many pixels (0 = keep original size)
+coco_crop_size: {}
+
 # --- Display Settings ---
 
 # Use binary file size units (KiB/MiB with 1024 divisor) instead of decimal (KB/MB with 1000)
@@ -568,6 +821,18 @@ use_binary_size: {}
 # - "MenuBar": Show spinner in the menu bar (overlays in fullscreen mode)
 # - "None": Don't show loading spinner
 spinner_location: "{}"
+
+# Menu bar
+# - "Full": Menu names (default)
+# - "Compact": One glyph per menu
+# - "Hidden": Not shown until Alt is tapped; tap Alt again or press Escape to hide it
+menu_bar_mode: "{}"
+
+# Menus and Controls entries left out of the menu bar, e.g. ["Plugins", "Stereo"]
+# Hideable menus: Annotations, Similar, Model, Plugins, Scripts, Help
+# Hideable Controls entries: Stereo, Shader, LUT, Display Profile, Soft Proof, White Balance,
+# Profiles, Viewed Images
+hidden_menus: {}
 "#,
             self.show_fps,
             self.show_footer,
@@ -576,6 +841,10 @@ spinner_location: "{}"
             self.mouse_wheel_zoom,
             self.cache_strategy,
             self.compression_strategy,
+            self.archive_cache_strategy,
+            self.archive_compression_strategy,
+            self.remote_cache_strategy,
+            self.remote_compression_strategy,
             self.is_slider_dual,
             self.show_copy_buttons,
             self.show_metadata,
@@ -589,20 +858,50 @@ spinner_location: "{}"
             self.double_click_threshold_ms,
             self.archive_cache_size,
             self.archive_warning_threshold_mb,
+            self.directory_cache_mb,
+            self.decode_threads,
+            self.max_concurrent_reads,
+            self.load_timeout_secs,
+            self.follow_symlinks,
+            self.check_for_updates,
+            self.confirm_delete,
+            self.persist_viewed,
+            self.load_for_display,
+            self.convert_icc_profiles,
             self.coco_disable_simplification,
             match self.coco_mask_render_mode {
                 CocoMaskRenderMode::Polygon => "Polygon",
                 CocoMaskRenderMode::Pixel => "Pixel",
             },
+            self.coco_show_labels,
+            self.coco_label_size,
+            self.coco_crop_padding,
+            self.coco_crop_size,
             self.use_binary_size,
             match self.spinner_location {
                 SpinnerLocation::Footer => "Footer",
                 SpinnerLocation::MenuBar => "MenuBar",
                 SpinnerLocation::None => "None",
-            }
+            },
+            self.menu_bar_mode_name(),
+            self.hidden_menus_yaml(),
         )
     }
 
+    fn menu_bar_mode_name(&self) -> &'static str {
+        match self.menu_bar_mode {
+            MenuBarMode::Full => "Full",
+            MenuBarMode::Compact => "Compact",
+            MenuBarMode::Hidden => "Hidden",
+        }
+    }
+
+    /// `hidden_menus` as a one-line YAML list
+    fn hidden_menus_yaml(&self) -> String {
+        let names: Vec<String> = self.hidden_menus.iter().map(|name| format!("{:?}", name)).collect();
+        format!("[{}]", names.join(", "))
+    }
+
     /// Convert cache_strategy string to CacheStrategy enum
     pub fn get_cache_strategy(&self) -> CacheStrategy {
         match self.cache_strategy.to_lowercase().as_str() {
@@ -615,6 +914,22 @@ spinner_location: "{}"
         }
     }
 
+    /// Per-source strategies, with `local` (the possibly safe-mode adjusted strategies of
+    /// local folders) as the base
+    pub fn get_source_strategies(&self, local: (CacheStrategy, CompressionStrategy)) -> SourceStrategies {
+        SourceStrategies {
+            local,
+            archive: StrategyOverride {
+                cache: source_strategy::parse_cache_strategy(&self.archive_cache_strategy),
+                compression: source_strategy::parse_compression_strategy(&self.archive_compression_strategy),
+            },
+            remote: StrategyOverride {
+                cache: source_strategy::parse_cache_strategy(&self.remote_cache_strategy),
+                compression: source_strategy::parse_compression_strategy(&self.remote_compression_strategy),
+            },
+        }
+    }
+
     /// Convert compression_strategy string to CompressionStrategy enum
     pub fn get_compression_strategy(&self) -> CompressionStrategy {
         match self.compression_strategy.to_lowercase().as_str() {
@@ -7,7 +7,7 @@ use iced_wgpu::engine::CompressionStrategy;
 use iced_aw::widget::tab_bar::tab_label::TabLabel;
 use iced_aw::tabs::Tabs;
 
-use crate::app::{Message, DataViewer};
+use crate::app::{Message, OverlayMessage, PaneMessage, SettingsMessage, DataViewer};
 use crate::cache::img_cache::CacheStrategy;
 use crate::widgets;
 use crate::settings::SpinnerLocation;
@@ -15,8 +15,8 @@ use crate::settings::SpinnerLocation;
 /// Builds the settings modal dialog with tabs
 pub fn view_settings_modal<'a>(viewer: &'a DataViewer) -> Element<'a, Message, WinitTheme, Renderer> {
     // Create the tabs with compact styling
-    #[cfg_attr(not(feature = "coco"), allow(unused_mut))]
-    let mut tabs = Tabs::new(Message::SettingsTabSelected)
+    #[cfg_attr(not(any(feature = "coco", target_os = "macos")), allow(unused_mut))]
+    let mut tabs = Tabs::new(|index| Message::Settings(SettingsMessage::SettingsTabSelected(index)))
         .push(
             0,  // Tab ID
             TabLabel::Text("General".to_string()),  // Label
@@ -38,6 +38,16 @@ pub fn view_settings_modal<'a>(viewer: &'a DataViewer) -> Element<'a, Message, W
         );
     }
 
+    // Stored folder grants of the macOS sandbox
+    #[cfg(target_os = "macos")]
+    {
+        tabs = tabs.push(
+            crate::bookmark_permissions::PERMISSIONS_TAB,
+            TabLabel::Text("Permissions".to_string()),
+            crate::bookmark_permissions::view_permissions_tab(viewer)
+        );
+    }
+
     let tabs = tabs.set_active_tab(&viewer.settings.active_tab)
         .tab_bar_style(|theme: &WinitTheme, status| {
             use iced_aw::style::status::Status;
@@ -116,17 +126,17 @@ pub fn view_settings_modal<'a>(viewer: &'a DataViewer) -> Element<'a, Message, W
         row![
             button(text("Reset to Defaults"))
                 .padding([3, 10])
-                .on_press(Message::ResetAdvancedSettings),
+                .on_press(Message::Settings(SettingsMessage::ResetAdvancedSettings)),
             button(text("Save"))
                 .padding([3, 10])
-                .on_press(Message::SaveSettings),
+                .on_press(Message::Settings(SettingsMessage::SaveSettings)),
             button(text("Close"))
                 .padding([3, 10])
-                .on_press(Message::HideOptions),
+                .on_press(Message::Overlay(OverlayMessage::HideOptions)),
             Space::with_width(Length::Fill),
             button(text("Open Settings Dir"))
                 .padding([3, 10])
-                .on_press(Message::OpenSettingsDir),
+                .on_press(Message::Overlay(OverlayMessage::OpenSettingsDir)),
         ]
         .spacing(10)
         .align_y(Alignment::Center)
@@ -169,7 +179,7 @@ fn view_general_tab<'a>(viewer: &'a DataViewer) -> Element<'a, Message, WinitThe
             widgets::toggler::Toggler::new(
                 Some("Show FPS Display".into()),
                 viewer.show_fps,
-                Message::ToggleFpsDisplay,
+                |enabled| Message::Pane(PaneMessage::ToggleFpsDisplay(enabled)),
             ).width(Length::Fill)
         ).style(|_theme: &WinitTheme| container::Style {
             text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
@@ -180,7 +190,7 @@ fn view_general_tab<'a>(viewer: &'a DataViewer) -> Element<'a, Message, WinitThe
             widgets::toggler::Toggler::new(
                 Some("Show Footer".into()),
                 viewer.show_footer,
-                Message::ToggleFooter,
+                |enabled| Message::Pane(PaneMessage::ToggleFooter(enabled)),
             ).width(Length::Fill)
         ).style(|_theme: &WinitTheme| container::Style {
             text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
@@ -191,7 +201,7 @@ fn view_general_tab<'a>(viewer: &'a DataViewer) -> Element<'a, Message, WinitThe
             widgets::toggler::Toggler::new(
                 Some("Show Copy Buttons".into()),
                 viewer.show_copy_buttons,
-                Message::ToggleCopyButtons,
+                |enabled| Message::Pane(PaneMessage::ToggleCopyButtons(enabled)),
             ).width(Length::Fill)
         ).style(|_theme: &WinitTheme| container::Style {
             text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
@@ -202,7 +212,7 @@ fn view_general_tab<'a>(viewer: &'a DataViewer) -> Element<'a, Message, WinitThe
             widgets::toggler::Toggler::new(
                 Some("Show Image Metadata".into()),
                 viewer.show_metadata,
-                Message::ToggleMetadataDisplay,
+                |enabled| Message::Pane(PaneMessage::ToggleMetadataDisplay(enabled)),
             ).width(Length::Fill)
         ).style(|_theme: &WinitTheme| container::Style {
             text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
@@ -213,7 +223,7 @@ fn view_general_tab<'a>(viewer: &'a DataViewer) -> Element<'a, Message, WinitThe
             widgets::toggler::Toggler::new(
                 Some("Nearest-Neighbor Filter (for pixel art)".into()),
                 viewer.nearest_neighbor_filter,
-                Message::ToggleNearestNeighborFilter,
+                |enabled| Message::Pane(PaneMessage::ToggleNearestNeighborFilter(enabled)),
             ).width(Length::Fill)
         ).style(|_theme: &WinitTheme| container::Style {
             text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
@@ -235,19 +245,19 @@ fn view_general_tab<'a>(viewer: &'a DataViewer) -> Element<'a, Message, WinitThe
                     "Footer",
                     SpinnerLocation::Footer,
                     Some(viewer.spinner_location),
-                    Message::SetSpinnerLocation,
+                    |location| Message::Pane(PaneMessage::SetSpinnerLocation(location)),
                 ),
                 iced_widget::Radio::new(
                     "Menu Bar",
                     SpinnerLocation::MenuBar,
                     Some(viewer.spinner_location),
-                    Message::SetSpinnerLocation,
+                    |location| Message::Pane(PaneMessage::SetSpinnerLocation(location)),
                 ),
                 iced_widget::Radio::new(
                     "None",
                     SpinnerLocation::None,
                     Some(viewer.spinner_location),
-                    Message::SetSpinnerLocation,
+                    |location| Message::Pane(PaneMessage::SetSpinnerLocation(location)),
                 ),
             ]
             .spacing(15)
@@ -257,7 +267,7 @@ fn view_general_tab<'a>(viewer: &'a DataViewer) -> Element<'a, Message, WinitThe
             widgets::toggler::Toggler::new(
                 Some("Horizontal Split".into()),
                 viewer.is_horizontal_split,
-                Message::ToggleSplitOrientation,
+                |enabled| Message::Pane(PaneMessage::ToggleSplitOrientation(enabled)),
             ).width(Length::Fill)
         ).style(|_theme: &WinitTheme| container::Style {
             text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
@@ -280,9 +290,9 @@ fn view_general_tab<'a>(viewer: &'a DataViewer) -> Element<'a, Message, WinitThe
                 viewer.compression_strategy == CompressionStrategy::Bc1,
                 |enabled| {
                     if enabled {
-                        Message::SetCompressionStrategy(CompressionStrategy::Bc1)
+                        Message::Pane(PaneMessage::SetCompressionStrategy(CompressionStrategy::Bc1))
                     } else {
-                        Message::SetCompressionStrategy(CompressionStrategy::None)
+                        Message::Pane(PaneMessage::SetCompressionStrategy(CompressionStrategy::None))
                     }
                 },
             ).width(Length::Fill)
@@ -297,9 +307,9 @@ fn view_general_tab<'a>(viewer: &'a DataViewer) -> Element<'a, Message, WinitThe
                 viewer.cache_strategy == CacheStrategy::Gpu,
                 |enabled| {
                     if enabled {
-                        Message::SetCacheStrategy(CacheStrategy::Gpu)
+                        Message::Pane(PaneMessage::SetCacheStrategy(CacheStrategy::Gpu))
                     } else {
-                        Message::SetCacheStrategy(CacheStrategy::Cpu)
+                        Message::Pane(PaneMessage::SetCacheStrategy(CacheStrategy::Cpu))
                     }
                 },
             ).width(Length::Fill)
@@ -325,7 +335,7 @@ fn view_general_tab<'a>(viewer: &'a DataViewer) -> Element<'a, Message, WinitThe
             widgets::toggler::Toggler::new(
                 Some("Sync Zoom/Pan".into()),
                 viewer.synced_zoom,
-                Message::ToggleSyncedZoom,
+                |enabled| Message::Pane(PaneMessage::ToggleSyncedZoom(enabled)),
             ).width(Length::Fill)
         ).style(|_theme: &WinitTheme| container::Style {
             text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
@@ -336,7 +346,7 @@ fn view_general_tab<'a>(viewer: &'a DataViewer) -> Element<'a, Message, WinitThe
             widgets::toggler::Toggler::new(
                 Some("Mouse Wheel Zoom".into()),
                 viewer.mouse_wheel_zoom,
-                Message::ToggleMouseWheelZoom,
+                |enabled| Message::Pane(PaneMessage::ToggleMouseWheelZoom(enabled)),
             ).width(Length::Fill)
         ).style(|_theme: &WinitTheme| container::Style {
             text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
@@ -347,7 +357,7 @@ fn view_general_tab<'a>(viewer: &'a DataViewer) -> Element<'a, Message, WinitThe
             widgets::toggler::Toggler::new(
                 Some("Dual Slider".into()),
                 viewer.is_slider_dual,
-                Message::ToggleSliderType,
+                |enabled| Message::Pane(PaneMessage::ToggleSliderType(enabled)),
             ).width(Length::Fill)
         ).style(|_theme: &WinitTheme| container::Style {
             text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
@@ -382,7 +392,7 @@ fn labeled_text_input_row<'a>(
             .size(14)
             .width(Length::Fixed(150.0))
             .on_input(move |new_value| {
-                Message::AdvancedSettingChanged(field_name_owned.clone(), new_value)
+                Message::Settings(SettingsMessage::AdvancedSettingChanged(field_name_owned.clone(), new_value))
             }),
     ]
     .spacing(10)
@@ -453,6 +463,10 @@ fn view_advanced_tab<'a>(viewer: &'a DataViewer) -> Element<'a, Message, WinitTh
         labeled_text_input_row("Double-Click Threshold (ms):", "double_click_threshold_ms", get_value("double_click_threshold_ms")),
         labeled_text_input_row("Archive Cache Size (MB):", "archive_cache_size", get_value("archive_cache_size")),
         labeled_text_input_row("Archive Warning Threshold (MB):", "archive_warning_threshold_mb", get_value("archive_warning_threshold_mb")),
+        labeled_text_input_row("Recent Directories Cache (MB):", "directory_cache_mb", get_value("directory_cache_mb")),
+        labeled_text_input_row("Decode Threads:", "decode_threads", get_value("decode_threads")),
+        labeled_text_input_row("Max Concurrent File Reads:", "max_concurrent_reads", get_value("max_concurrent_reads")),
+        labeled_text_input_row("Load Timeout (s, 0 = none):", "load_timeout_secs", get_value("load_timeout_secs")),
     ]
     .spacing(3);
 
@@ -508,14 +522,14 @@ fn view_coco_tab<'a>(viewer: &'a DataViewer) -> Element<'a, Message, WinitTheme,
                     "Polygon (Vector)",
                     CocoMaskRenderMode::Polygon,
                     Some(viewer.coco_mask_render_mode),
-                    Message::SetCocoMaskRenderMode,
+                    |mode| Message::Pane(PaneMessage::SetCocoMaskRenderMode(mode)),
                 ),
                 iced_widget::horizontal_space(),
                 iced_widget::Radio::new(
                     "Pixel (Raster)",
                     CocoMaskRenderMode::Pixel,
                     Some(viewer.coco_mask_render_mode),
-                    Message::SetCocoMaskRenderMode,
+                    |mode| Message::Pane(PaneMessage::SetCocoMaskRenderMode(mode)),
                 ),
             ]
             .spacing(20)
@@ -544,7 +558,7 @@ fn view_coco_tab<'a>(viewer: &'a DataViewer) -> Element<'a, Message, WinitTheme,
                 widgets::toggler::Toggler::new(
                     Some("Disable Polygon Simplification".into()),
                     viewer.coco_disable_simplification,
-                    Message::ToggleCocoSimplification,
+                    |enabled| Message::Pane(PaneMessage::ToggleCocoSimplification(enabled)),
                 ).width(Length::Fill)
             ).style(|_theme: &WinitTheme| container::Style {
                 text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
@@ -567,6 +581,94 @@ fn view_coco_tab<'a>(viewer: &'a DataViewer) -> Element<'a, Message, WinitTheme,
         );
     }
 
+    content = content.push(Space::with_height(10));
+
+    content = content.push(
+        text("Bounding Box Labels").size(14)
+            .font(Font {
+                family: iced_winit::core::font::Family::Name("Roboto"),
+                weight: iced_winit::core::font::Weight::Medium,
+                stretch: iced_winit::core::font::Stretch::Normal,
+                style: iced_winit::core::font::Style::Normal,
+            })
+    );
+
+    content = content.push(
+        container(
+            widgets::toggler::Toggler::new(
+                Some("Show Labels".into()),
+                viewer.coco_show_labels,
+                |enabled| Message::Pane(PaneMessage::ToggleCocoLabels(enabled)),
+            ).width(Length::Fill)
+        ).style(|_theme: &WinitTheme| container::Style {
+            text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+            ..container::Style::default()
+        })
+    );
+
+    if viewer.coco_show_labels {
+        content = content.push(
+            container(
+                row![
+                    text(format!("Label Size: {:.0}px", viewer.coco_label_size)).size(13).width(Length::Fixed(120.0)),
+                    iced_widget::slider(8.0..=32.0, viewer.coco_label_size, |value| Message::Pane(PaneMessage::SetCocoLabelSize(value)))
+                        .step(1.0)
+                        .width(Length::Fixed(200.0)),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center)
+            ).style(|_theme: &WinitTheme| container::Style {
+                text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+                ..container::Style::default()
+            })
+        );
+    }
+
+    content = content.push(Space::with_height(10));
+
+    content = content.push(
+        text("Crop Export").size(14)
+            .font(Font {
+                family: iced_winit::core::font::Family::Name("Roboto"),
+                weight: iced_winit::core::font::Weight::Medium,
+                stretch: iced_winit::core::font::Stretch::Normal,
+                style: iced_winit::core::font::Style::Normal,
+            })
+    );
+
+    let crop_size_label = if viewer.coco_crop_size == 0 {
+        "Resize: original".to_string()
+    } else {
+        format!("Resize: {}px", viewer.coco_crop_size)
+    };
+
+    content = content.push(
+        container(
+            column![
+                row![
+                    text(format!("Padding: {:.0}%", viewer.coco_crop_padding)).size(13).width(Length::Fixed(120.0)),
+                    iced_widget::slider(0.0..=50.0, viewer.coco_crop_padding, |value| Message::Pane(PaneMessage::SetCocoCropPadding(value)))
+                        .step(1.0)
+                        .width(Length::Fixed(200.0)),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+                row![
+                    text(crop_size_label).size(13).width(Length::Fixed(120.0)),
+                    iced_widget::slider(0..=1024u32, viewer.coco_crop_size, |value| Message::Pane(PaneMessage::SetCocoCropSize(value)))
+                        .step(32u32)
+                        .width(Length::Fixed(200.0)),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            ]
+            .spacing(5)
+        ).style(|_theme: &WinitTheme| container::Style {
+            text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+            ..container::Style::default()
+        })
+    );
+
     let content = content;
 
     scrollable(
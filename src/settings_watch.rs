@@ -0,0 +1,77 @@
+/// Live reload of the settings file
+///
+/// A worker thread polls the modification time of the settings file, the same way
+/// `--follow` polls its directory, and asks the app to re-read it after an edit. What can
+/// change at runtime is applied in `handle_settings_file_changed`; cache and compression
+/// strategy, atlas size and window geometry still take effect on the next start.
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use once_cell::sync::Lazy;
+use iced_winit::runtime::Task;
+use log::{info, warn};
+
+use crate::app::{Message, SettingsMessage};
+use crate::settings::UserSettings;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Wait after a change so an editor has finished writing the file
+const SETTLE_DELAY: Duration = Duration::from_millis(200);
+
+static REQUESTED: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(None));
+static WATCHED: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(None));
+
+/// Watch the settings file in use: the `--settings` file, or the default one
+pub fn request(custom_path: Option<&str>) {
+    let path = custom_path.map(PathBuf::from).unwrap_or_else(UserSettings::settings_path);
+    if let Ok(mut requested) = REQUESTED.lock() {
+        *requested = Some(path);
+    }
+}
+
+pub fn take_request() -> Option<PathBuf> {
+    REQUESTED.lock().ok()?.take()
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+pub fn start(path: PathBuf) -> Task<Message> {
+    if let Ok(mut watched) = WATCHED.lock() {
+        *watched = Some(path.clone());
+    }
+    let (sender, receiver) = futures::channel::mpsc::unbounded();
+    std::thread::spawn(move || {
+        let mut last = modified(&path);
+        while !sender.is_closed() {
+            std::thread::sleep(POLL_INTERVAL);
+            let current = modified(&path);
+            if current != last && current.is_some() {
+                std::thread::sleep(SETTLE_DELAY);
+                last = modified(&path);
+                let _ = sender.unbounded_send(());
+            }
+        }
+    });
+    Task::run(receiver, |_| Message::Settings(SettingsMessage::SettingsFileChanged))
+}
+
+/// Settings from the watched file, or None while it is missing or does not parse
+///
+/// Unlike `UserSettings::load`, a broken file is not replaced by defaults, so a
+/// half-saved edit cannot reset the running app.
+pub fn read_watched() -> Option<UserSettings> {
+    let path = WATCHED.lock().ok()?.clone()?;
+    let yaml = std::fs::read_to_string(&path).ok()?;
+    match serde_yaml::from_str(&yaml) {
+        Ok(settings) => {
+            info!("Reloading settings from {}", path.display());
+            Some(settings)
+        }
+        Err(e) => {
+            warn!("Not reloading {}: {}", path.display(), e);
+            None
+        }
+    }
+}
@@ -0,0 +1,156 @@
+/// Soft proofing against an output ICC profile
+///
+/// Profiles in `<config dir>/viewskater/icc/proof/` are listed in "Controls -> Soft Proof";
+/// any other profile can be opened from there too. The chosen profile is reduced to a 3D
+/// table on a worker thread and shared with the image pipeline through a static, like the
+/// LUT. "Proof Colors" (Ctrl+Y) shows images as the profile would reproduce them and "Gamut
+/// Warning" (Ctrl+Shift+Y) paints colors it cannot reproduce in gray. Proofing is relative
+/// colorimetric without paper simulation, and like the LUT it is not remembered across
+/// restarts.
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use once_cell::sync::Lazy;
+use iced_winit::runtime::Task;
+use log::{error, info};
+
+use crate::app::Message;
+use crate::utils::icc_proof::{parse_proof_profile, ProofTable, PROOF_TABLE_SIZE};
+
+#[derive(Debug, Default)]
+struct State {
+    path: Option<PathBuf>,
+    table: Option<Arc<ProofTable>>,
+    proof_colors: bool,
+    gamut_warning: bool,
+}
+
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
+/// Bumped when the proofing table changes, so pipelines re-upload it
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone)]
+pub enum SoftProofMessage {
+    Open,
+    Load(PathBuf),
+    Loaded(PathBuf, Result<Arc<ProofTable>, String>),
+    ToggleProofColors,
+    ToggleGamutWarning,
+    Clear,
+}
+
+impl From<SoftProofMessage> for Message {
+    fn from(msg: SoftProofMessage) -> Self {
+        Message::SoftProofAction(msg)
+    }
+}
+
+pub fn proof_dir() -> PathBuf {
+    crate::display_profile::icc_dir().join("proof")
+}
+
+/// ICC profiles in the proofing directory, sorted by name
+pub fn available_profiles() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(proof_dir()) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e.eq_ignore_ascii_case("icc") || e.eq_ignore_ascii_case("icm")))
+        .collect();
+    paths.sort();
+    paths
+}
+
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+/// Proofing table of the chosen profile
+pub fn table() -> Option<Arc<ProofTable>> {
+    STATE.lock().ok()?.table.clone()
+}
+
+pub fn active_path() -> Option<PathBuf> {
+    STATE.lock().ok()?.path.clone()
+}
+
+/// Whether proofed colors and the gamut warning are shown, once a profile is chosen
+pub fn modes() -> (bool, bool) {
+    STATE.lock()
+        .map(|state| (state.proof_colors, state.gamut_warning))
+        .unwrap_or((false, false))
+}
+
+fn read_proof_table(path: &Path) -> Result<Arc<ProofTable>, String> {
+    let data = std::fs::read(path).map_err(|e| e.to_string())?;
+    let profile = parse_proof_profile(&data)?;
+    Ok(Arc::new(profile.build_table(PROOF_TABLE_SIZE)))
+}
+
+pub fn handle_soft_proof_message(msg: SoftProofMessage) -> Task<Message> {
+    match msg {
+        SoftProofMessage::Open => Task::perform(
+            async {
+                rfd::AsyncFileDialog::new()
+                    .set_title("Select output ICC profile")
+                    .add_filter("ICC profile", &["icc", "icm"])
+                    .pick_file()
+                    .await
+            },
+            |file_handle| match file_handle {
+                Some(file) => SoftProofMessage::Load(file.path().to_path_buf()).into(),
+                None => Message::Nothing,
+            }
+        ),
+        SoftProofMessage::Load(path) => Task::perform(
+            async move {
+                let read_path = path.clone();
+                let result = tokio::task::spawn_blocking(move || read_proof_table(&read_path))
+                    .await
+                    .unwrap_or_else(|e| Err(e.to_string()));
+                (path, result)
+            },
+            |(path, result)| SoftProofMessage::Loaded(path, result).into()
+        ),
+        SoftProofMessage::Loaded(path, result) => {
+            match result {
+                Ok(table) => {
+                    info!("Soft proofing with {}", path.display());
+                    if let Ok(mut state) = STATE.lock() {
+                        state.path = Some(path);
+                        state.table = Some(table);
+                        state.proof_colors = true;
+                    }
+                    GENERATION.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(e) => error!("Failed to load proof profile {}: {}", path.display(), e),
+            }
+            Task::none()
+        }
+        SoftProofMessage::ToggleProofColors => {
+            if let Ok(mut state) = STATE.lock() {
+                if state.table.is_some() {
+                    state.proof_colors = !state.proof_colors;
+                }
+            }
+            Task::none()
+        }
+        SoftProofMessage::ToggleGamutWarning => {
+            if let Ok(mut state) = STATE.lock() {
+                if state.table.is_some() {
+                    state.gamut_warning = !state.gamut_warning;
+                }
+            }
+            Task::none()
+        }
+        SoftProofMessage::Clear => {
+            if let Ok(mut state) = STATE.lock() {
+                *state = State::default();
+            }
+            GENERATION.fetch_add(1, Ordering::Relaxed);
+            Task::none()
+        }
+    }
+}
@@ -0,0 +1,96 @@
+/// Early decode of the file the app was opened with
+///
+/// With "Open With" or a file argument, the image is decoded on a worker thread while the
+/// window and GPU are still being set up, and the GPU cache picks the result up instead of
+/// decoding it again. Frames are held back until the image is shown, or for at most
+/// `FIRST_FRAME_TIMEOUT`, so the window does not flash an empty view first.
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+use image::DynamicImage;
+use once_cell::sync::Lazy;
+use log::{debug, warn};
+
+use crate::app::DataViewer;
+use crate::cache::img_cache::PathSource;
+
+const FIRST_FRAME_TIMEOUT: Duration = Duration::from_millis(1500);
+/// Longest wait for a decode still in progress when the cache asks for it
+const TAKE_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Default)]
+struct Prefetch {
+    path: Option<PathBuf>,
+    image: Option<DynamicImage>,
+    done: bool,
+}
+
+static PREFETCH: Lazy<(Mutex<Prefetch>, Condvar)> = Lazy::new(|| (Mutex::new(Prefetch::default()), Condvar::new()));
+static FIRST_FRAME_DEADLINE: Lazy<Mutex<Option<Instant>>> = Lazy::new(|| Mutex::new(None));
+
+/// Start decoding `path` if it is an image file
+pub fn prefetch(path: &Path) {
+    if !path.is_file() || !crate::file_io::supported_image(&path.to_string_lossy()) {
+        return;
+    }
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if let Ok(mut prefetch) = PREFETCH.0.lock() {
+        *prefetch = Prefetch { path: Some(path.clone()), image: None, done: false };
+    }
+    if let Ok(mut deadline) = FIRST_FRAME_DEADLINE.lock() {
+        *deadline = Some(Instant::now() + FIRST_FRAME_TIMEOUT);
+    }
+
+    std::thread::spawn(move || {
+        let start = Instant::now();
+        let image = crate::cache::cache_utils::decode_original_image(&PathSource::Filesystem(path.clone()), None);
+        match &image {
+            Ok(_) => debug!("Decoded startup image {} in {:?}", path.display(), start.elapsed()),
+            Err(e) => warn!("Failed to decode startup image {}: {}", path.display(), e),
+        }
+        let (lock, ready) = &*PREFETCH;
+        if let Ok(mut prefetch) = lock.lock() {
+            if prefetch.path.as_ref() == Some(&path) {
+                prefetch.image = image.ok();
+                prefetch.done = true;
+            }
+        }
+        ready.notify_all();
+    });
+}
+
+/// The decoded startup image for `path_source`, waiting for it if the decode is still running
+pub fn take(path_source: &PathSource) -> Option<DynamicImage> {
+    let PathSource::Filesystem(path) = path_source else {
+        return None;
+    };
+    let (lock, ready) = &*PREFETCH;
+    let prefetch = lock.lock().ok()?;
+    // The directory listing may spell the path differently from the command line
+    if prefetch.path.is_none() || prefetch.path != std::fs::canonicalize(path).ok() {
+        return None;
+    }
+    let (mut prefetch, _) = ready.wait_timeout_while(prefetch, TAKE_TIMEOUT, |p| !p.done).ok()?;
+    let image = prefetch.image.take();
+    *prefetch = Prefetch::default();
+    image
+}
+
+/// Whether to skip drawing because the startup image is not shown yet
+pub fn holds_first_frame(app: &DataViewer) -> bool {
+    let Ok(mut deadline) = FIRST_FRAME_DEADLINE.lock() else {
+        return false;
+    };
+    match *deadline {
+        Some(until) if Instant::now() < until && !app.panes.first().is_some_and(|pane| pane.dir_loaded) => true,
+        Some(_) => {
+            *deadline = None;
+            // Drop a decode the cache never asked for, e.g. after a timeout
+            if let Ok(mut prefetch) = PREFETCH.0.lock() {
+                *prefetch = Prefetch::default();
+            }
+            false
+        }
+        None => false,
+    }
+}
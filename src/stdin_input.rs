@@ -0,0 +1,228 @@
+/// Images and path lists piped in on standard input
+///
+/// `viewskater -` reads one encoded image from stdin and opens it from a private temp
+/// directory. `viewskater --stdin-list` reads newline-separated paths and opens them as an
+/// ad-hoc list, so `fd -e png | viewskater --stdin-list` browses just those files. The list
+/// is read on a worker thread: it opens on the first path and grows as more arrive.
+use std::collections::HashSet;
+use std::io::{BufRead, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::{Duration, Instant};
+use iced_winit::runtime::Task;
+use once_cell::sync::Lazy;
+use log::{debug, error, info, warn};
+
+use crate::app::{DataViewer, DirectoryEnumResult, IoMessage, Message};
+use crate::playlist::PlaylistSource;
+use viewskater_core::path_index::PathIndexBuilder;
+
+/// Paths read within this long of the last batch are sent together
+const BATCH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Directory relative paths of a requested `--stdin-list` resolve against
+static REQUESTED: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone)]
+pub enum StdinListEvent {
+    /// The first paths, which open the list
+    Opened(Vec<PathBuf>),
+    /// Paths read since the last batch
+    Added(Vec<PathBuf>),
+    /// Stdin was closed after this many paths
+    Finished(usize),
+}
+
+fn temp_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("viewskater_stdin_{}", std::process::id()))
+}
+
+/// Save an encoded image from `reader` to a temp file and return its path
+///
+/// The file gets its own directory so opening it does not pull in unrelated images.
+pub fn read_image(mut reader: impl Read) -> Result<PathBuf, String> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).map_err(|e| format!("Failed to read stdin: {}", e))?;
+    if bytes.is_empty() {
+        return Err("No image data on stdin".to_string());
+    }
+    let format = image::guess_format(&bytes).map_err(|_| "Unrecognized image data on stdin".to_string())?;
+    let extension = format.extensions_str().first().copied().unwrap_or("png");
+
+    let dir = temp_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!("stdin.{}", extension));
+    std::fs::write(&path, &bytes).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Remove the temp directory written by `read_image`, if any
+pub fn cleanup() {
+    let dir = temp_dir();
+    if dir.exists() {
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            warn!("Failed to remove {}: {}", dir.display(), e);
+        }
+    }
+}
+
+/// Supported image files named one per line, in order and without duplicates, passed to
+/// `found` as they are read
+///
+/// Relative paths are resolved against `base`; blank lines, missing files and
+/// unsupported formats are skipped.
+pub fn read_path_list(reader: impl BufRead, base: &Path, mut found: impl FnMut(PathBuf)) {
+    let mut seen = HashSet::new();
+    for line in reader.lines().map_while(Result::ok) {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+        let path = base.join(line);
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        if !path.is_file() || !crate::file_io::supported_image(&name) {
+            continue;
+        }
+        if seen.insert(path.clone()) {
+            found(path);
+        }
+    }
+}
+
+/// Read the path list from stdin once the app has started, resolving against `base`
+pub fn request_list(base: PathBuf) {
+    if let Ok(mut requested) = REQUESTED.lock() {
+        *requested = Some(base);
+    }
+}
+
+pub fn take_list_request() -> Option<PathBuf> {
+    REQUESTED.lock().ok()?.take()
+}
+
+/// Read stdin on a worker thread, sending the paths in batches as they arrive
+///
+/// A second thread does the batching, so paths are sent within `BATCH_INTERVAL` even while
+/// the reader waits on a slow producer.
+pub fn start(base: PathBuf) -> Task<Message> {
+    let (path_sender, paths) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        read_path_list(std::io::stdin().lock(), &base, |path| {
+            let _ = path_sender.send(path);
+        });
+    });
+
+    let (sender, receiver) = futures::channel::mpsc::unbounded();
+    std::thread::spawn(move || {
+        let mut batch = Vec::new();
+        let mut count = 0;
+        let mut last_sent: Option<Instant> = None;
+        loop {
+            let closed = match paths.recv_timeout(BATCH_INTERVAL) {
+                Ok(path) => {
+                    batch.push(path);
+                    if last_sent.is_some_and(|sent| sent.elapsed() < BATCH_INTERVAL) {
+                        continue;
+                    }
+                    false
+                }
+                Err(RecvTimeoutError::Timeout) => false,
+                Err(RecvTimeoutError::Disconnected) => true,
+            };
+            if !batch.is_empty() {
+                count += batch.len();
+                let batch = std::mem::take(&mut batch);
+                let event = if last_sent.is_none() { StdinListEvent::Opened(batch) } else { StdinListEvent::Added(batch) };
+                if sender.unbounded_send(event).is_err() {
+                    return;
+                }
+                last_sent = Some(Instant::now());
+            }
+            if closed {
+                break;
+            }
+        }
+        let _ = sender.unbounded_send(StdinListEvent::Finished(count));
+    });
+    Task::run(receiver, |event| Message::Io(IoMessage::StdinList(event)))
+}
+
+pub fn handle_event(app: &mut DataViewer, event: StdinListEvent) -> Task<Message> {
+    match event {
+        StdinListEvent::Opened(paths) => {
+            info!("Opening {} image(s) from stdin", paths.len());
+            let found = paths.len();
+            app.reset_state(-1);
+            let task = app.open_path_list(paths, 0, 0, PlaylistSource::PathList);
+            if let Some(pane) = app.panes.first_mut() {
+                pane.indexing = Some(found);
+            }
+            task
+        }
+        StdinListEvent::Added(paths) => append(app, paths),
+        StdinListEvent::Finished(0) => {
+            error!("No supported image paths on stdin");
+            Task::none()
+        }
+        StdinListEvent::Finished(count) => {
+            info!("Read {} image path(s) from stdin", count);
+            if let Some(pane) = app.panes.first_mut().filter(|pane| pane.playlist == Some(PlaylistSource::PathList)) {
+                pane.indexing = None;
+            }
+            Task::none()
+        }
+    }
+}
+
+/// Add paths read since the list was opened to the end of the first pane
+fn append(app: &mut DataViewer, paths: Vec<PathBuf>) -> Task<Message> {
+    let Some(pane) = app.panes.first_mut().filter(|pane| pane.dir_loaded && pane.playlist == Some(PlaylistSource::PathList)) else {
+        debug!("Dropping {} path(s) from stdin: the list is no longer open", paths.len());
+        return Task::none();
+    };
+    let listed = &pane.img_cache.image_paths;
+    let mut file_paths = PathIndexBuilder::new();
+    listed.range(0..listed.len()).for_each(|source| file_paths.push(source.path().clone()));
+    paths.into_iter().for_each(|path| file_paths.push(path));
+    let file_paths = file_paths.finish();
+    pane.indexing = Some(file_paths.len());
+
+    // A cache window that ends before the old end stays as it is
+    let img_cache = &mut pane.img_cache;
+    if img_cache.current_index + img_cache.cache_count < img_cache.image_paths.len() {
+        img_cache.num_files = file_paths.len();
+        img_cache.image_paths = file_paths;
+        return Task::none();
+    }
+    let result = DirectoryEnumResult {
+        initial_index: img_cache.current_index,
+        file_paths,
+        directory_path: pane.directory_path.clone().unwrap_or_default(),
+        skipped: Default::default(),
+        raw_pairs: Default::default(),
+    };
+    app.complete_dir_initialization(result, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_path_list_skips_missing_unsupported_and_duplicates() {
+        let dir = std::env::temp_dir().join(format!("viewskater_stdin_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["b.png", "a.jpg", "notes.txt"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+
+        let input = format!("b.png\r\n\n{}\nmissing.png\nnotes.txt\nb.png\n", dir.join("a.jpg").display());
+        let mut paths = Vec::new();
+        read_path_list(Cursor::new(input), &dir, |path| paths.push(path));
+        assert_eq!(paths, vec![dir.join("b.png"), dir.join("a.jpg")]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
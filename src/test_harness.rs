@@ -0,0 +1,352 @@
+//! Headless harness that drives `DataViewer::update` from tests
+//!
+//! Images are small PNGs whose pixels encode their own index, so any cached image can be
+//! checked against the slot it sits in. `SyntheticSource` keeps them in memory and opens as
+//! preloaded content, the way a small archive does; `SyntheticFolder` writes them to a
+//! temporary folder for scenarios that go through the filesystem. `Harness` owns a
+//! `DataViewer` on a headless wgpu device and feeds it scripted messages, running every task
+//! they return to completion before the next message is sent. Tasks run one after another on
+//! a single-threaded runtime, which keeps the order of loads the same from run to run.
+//!
+//! The device comes from the software adapter when there is one (lavapipe on Linux, WARP on
+//! Windows), otherwise from the default one. With neither, `Harness::new` returns `None` and
+//! the scenarios end early.
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::time::Duration;
+use futures::StreamExt;
+use iced_core::Event;
+use iced_core::mouse::{self, ScrollDelta};
+use iced_runtime::task::into_stream;
+use iced_runtime::Action;
+use iced_wgpu::wgpu;
+use iced_winit::runtime::{Program, Task};
+use image::{ImageFormat, Rgb, RgbImage};
+
+use crate::app::{DataViewer, IoMessage, Message, NavigationMessage};
+use crate::app::message::DirectoryEnumResult;
+use crate::cache::img_cache::{CachedData, PathIndex, PathSource};
+use crate::RendererRequest;
+
+/// Messages one `send` may produce before the loop is taken to be stuck
+const MAX_MESSAGES: usize = 10_000;
+/// Longest a single task may run; generous since debug builds decode slowly
+const TASK_TIMEOUT: Duration = Duration::from_secs(10);
+const IMAGE_WIDTH: u32 = 16;
+const IMAGE_HEIGHT: u32 = 12;
+/// Blue channel of every synthetic pixel, so a blank or foreign image never decodes to an index
+const MARKER: u8 = 0x5a;
+
+/// Synthetic image `index`
+fn synthetic_image(index: usize) -> RgbImage {
+    RgbImage::from_pixel(IMAGE_WIDTH, IMAGE_HEIGHT, Rgb([(index & 0xff) as u8, (index >> 8) as u8, MARKER]))
+}
+
+/// Numbered images kept in memory, opened as preloaded content
+pub struct SyntheticSource {
+    pub name: String,
+    pub count: usize,
+}
+
+impl SyntheticSource {
+    pub fn new(name: &str, count: usize) -> Self {
+        Self { name: name.to_string(), count }
+    }
+
+    pub fn entry_name(index: usize) -> String {
+        format!("img_{:04}.png", index)
+    }
+
+    /// Encoded bytes of image `index`
+    pub fn bytes(index: usize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        synthetic_image(index)
+            .write_to(&mut std::io::Cursor::new(&mut bytes), ImageFormat::Png)
+            .expect("Encode synthetic image");
+        bytes
+    }
+}
+
+/// Numbered images in a fresh temporary folder, removed on drop
+pub struct SyntheticFolder {
+    pub path: PathBuf,
+    pub count: usize,
+}
+
+impl SyntheticFolder {
+    pub fn new(name: &str, count: usize) -> Self {
+        let path = std::env::temp_dir().join(format!("viewskater_harness_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).expect("Create synthetic folder");
+        for index in 0..count {
+            synthetic_image(index)
+                .save(path.join(SyntheticSource::entry_name(index)))
+                .expect("Write synthetic image");
+        }
+        Self { path, count }
+    }
+}
+
+impl Drop for SyntheticFolder {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Index a synthetic image was written with, from its encoded bytes
+pub fn image_index(bytes: &[u8]) -> Option<usize> {
+    let image = image::load_from_memory(bytes).ok()?.to_rgb8();
+    let Rgb([low, high, marker]) = *image.get_pixel(0, 0);
+    (marker == MARKER).then_some(low as usize | (high as usize) << 8)
+}
+
+fn request_device(force_fallback_adapter: bool) -> Option<(Arc<wgpu::Device>, Arc<wgpu::Queue>, wgpu::Backend)> {
+    futures::executor::block_on(async {
+        let instance = wgpu::Instance::default();
+        let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            force_fallback_adapter,
+            compatible_surface: None,
+        }).await?;
+        let (device, queue) = adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("Test Device"),
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::downlevel_defaults(),
+            },
+            None,
+        ).await.ok()?;
+        Some((Arc::new(device), Arc::new(queue), adapter.get_info().backend))
+    })
+}
+
+/// Device on the software adapter, or the default one where there is no software adapter
+fn headless_device() -> Option<(Arc<wgpu::Device>, Arc<wgpu::Queue>, wgpu::Backend)> {
+    request_device(true).or_else(|| request_device(false))
+}
+
+pub struct Harness {
+    pub app: DataViewer,
+    runtime: tokio::runtime::Runtime,
+    settings_path: PathBuf,
+    // Held so the app's channels stay open
+    _renderer_requests: Receiver<RendererRequest>,
+    _file_sender: Sender<String>,
+}
+
+impl Harness {
+    /// App with default settings apart from a CPU cache of `cache_size` images each side
+    pub fn new(name: &str, cache_size: usize) -> Option<Self> {
+        let Some((device, queue, backend)) = headless_device() else {
+            eprintln!("No wgpu adapter available, skipping the {} scenario", name);
+            return None;
+        };
+        let settings_path = std::env::temp_dir()
+            .join(format!("viewskater_harness_{}_{}.yaml", name, std::process::id()));
+        std::fs::write(
+            &settings_path,
+            format!("cache_strategy: cpu\ncompression_strategy: none\ncache_size: {}\n", cache_size),
+        ).expect("Write harness settings");
+
+        let (renderer_sender, renderer_requests) = mpsc::channel();
+        let (file_sender, file_receiver) = mpsc::channel();
+        let app = DataViewer::new(
+            device,
+            queue,
+            backend,
+            renderer_sender,
+            file_receiver,
+            settings_path.to_str(),
+            None,
+        );
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("Build test runtime");
+        Some(Self {
+            app,
+            runtime,
+            settings_path,
+            _renderer_requests: renderer_requests,
+            _file_sender: file_sender,
+        })
+    }
+
+    /// Sends `message`, then every message its tasks produce, until nothing is left to run
+    pub fn send(&mut self, message: Message) {
+        let task = self.app.update(message);
+        self.run(task);
+    }
+
+    /// Runs `task`, then every message it produces, until nothing is left to run
+    fn run(&mut self, task: Task<Message>) {
+        let mut pending = VecDeque::new();
+        self.drain(task, &mut pending);
+        let mut sent = 0;
+        while let Some(message) = pending.pop_front() {
+            sent += 1;
+            assert!(sent <= MAX_MESSAGES, "Messages kept coming after {} were handled", MAX_MESSAGES);
+            let task = self.app.update(message);
+            self.drain(task, &mut pending);
+        }
+    }
+
+    /// Runs `task` to completion, queueing the messages it produces
+    fn drain(&mut self, task: Task<Message>, pending: &mut VecDeque<Message>) {
+        let Some(mut stream) = into_stream(task) else {
+            return;
+        };
+        self.runtime.block_on(async {
+            loop {
+                match tokio::time::timeout(TASK_TIMEOUT, stream.next()).await {
+                    Ok(Some(Action::Output(message))) => pending.push_back(message),
+                    // Window, clipboard and widget actions have nothing to act on here
+                    Ok(Some(_)) => {}
+                    Ok(None) => break,
+                    Err(_) => panic!("A task did not finish within {:?}", TASK_TIMEOUT),
+                }
+            }
+        });
+    }
+
+    /// Opens `source` in the first pane, as a listed small archive is opened
+    pub fn open_in_memory(&mut self, source: &SyntheticSource) {
+        let pane = &mut self.app.panes[0];
+        {
+            let mut archive_cache = pane.archive_cache.lock().expect("Lock archive cache");
+            archive_cache.clear_preloaded_data();
+            for index in 0..source.count {
+                archive_cache.add_preloaded_data(SyntheticSource::entry_name(index), SyntheticSource::bytes(index));
+            }
+        }
+        let paths = (0..source.count)
+            .map(|index| PathSource::Preloaded(PathBuf::from(SyntheticSource::entry_name(index))))
+            .collect::<Vec<_>>();
+        let result = DirectoryEnumResult {
+            file_paths: PathIndex::new(paths),
+            directory_path: format!("synthetic:{}", source.name),
+            initial_index: 0,
+            skipped: Default::default(),
+            raw_pairs: Default::default(),
+        };
+        let task = self.app.complete_dir_initialization(result, 0);
+        self.run(task);
+    }
+
+    pub fn open(&mut self, folder: &SyntheticFolder) {
+        self.send(Message::Io(IoMessage::FileDropped(0, folder.path.to_string_lossy().to_string())));
+    }
+
+    /// One wheel notch, the same path as the arrow keys
+    pub fn next(&mut self) {
+        self.send(Message::Event(Event::Mouse(mouse::Event::WheelScrolled {
+            delta: ScrollDelta::Lines { x: 0.0, y: -1.0 },
+        })));
+    }
+
+    pub fn prev(&mut self) {
+        self.send(Message::Event(Event::Mouse(mouse::Event::WheelScrolled {
+            delta: ScrollDelta::Lines { x: 0.0, y: 1.0 },
+        })));
+    }
+
+    pub fn jump(&mut self, index: usize) {
+        self.send(Message::Navigation(NavigationMessage::JumpToImage(0, index)));
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.app.panes[0].img_cache.current_index
+    }
+
+    /// Checks the first pane's cache once loading has settled: slots hold consecutive
+    /// indices, every cached image is the one its slot says, and the current slot holds the
+    /// current image
+    pub fn assert_cache_consistent(&self) {
+        let cache = &self.app.panes[0].img_cache;
+        let slots = cache.cache_count * 2 + 1;
+        assert_eq!(cache.cached_data.len(), slots);
+        assert_eq!(cache.cached_image_indices.len(), slots);
+        assert!(cache.current_index < cache.num_files, "current_index {} of {}", cache.current_index, cache.num_files);
+        assert!(cache.loading_queue.is_empty() && cache.being_loaded_queue.is_empty(), "Loads left in the queues");
+        assert!(cache.cached_image_indices.windows(2).all(|pair| pair[1] == pair[0] + 1),
+            "Slots are not consecutive: {:?}", cache.cached_image_indices);
+
+        for (slot, (&index, data)) in cache.cached_image_indices.iter().zip(&cache.cached_data).enumerate() {
+            let in_folder = index >= 0 && (index as usize) < cache.num_files;
+            match data {
+                Some(CachedData::Cpu(bytes)) => {
+                    assert!(in_folder, "Slot {} holds an image but points at {}", slot, index);
+                    assert_eq!(image_index(bytes), Some(index as usize), "Slot {} holds the wrong image", slot);
+                }
+                Some(_) => panic!("Slot {} holds a GPU texture with the CPU cache", slot),
+                None => assert!(!in_folder, "Slot {} is empty but points at image {}", slot, index),
+            }
+        }
+
+        let current_slot = (cache.cache_count as isize + cache.current_offset) as usize;
+        assert_eq!(cache.cached_image_indices.get(current_slot), Some(&(cache.current_index as isize)),
+            "Current slot {} does not hold image {}", current_slot, cache.current_index);
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.settings_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_images_encode_index() {
+        let folder = SyntheticFolder::new("encode", 300);
+        let bytes = std::fs::read(folder.path.join("img_0299.png")).unwrap();
+        assert_eq!(image_index(&bytes), Some(299));
+        assert_eq!(image_index(&SyntheticSource::bytes(299)), Some(299));
+        assert_eq!(image_index(b"not an image"), None);
+    }
+
+    #[test]
+    fn test_step_through_folder_and_past_both_edges() {
+        let Some(mut harness) = Harness::new("edges", 3) else {
+            return;
+        };
+        let folder = SyntheticFolder::new("edges", 12);
+        harness.open(&folder);
+        assert_eq!(harness.current_index(), 0);
+        harness.assert_cache_consistent();
+
+        // One past the last image stays on it, which is what move_next_edge guards
+        for step in 1..=folder.count {
+            harness.next();
+            assert_eq!(harness.current_index(), step.min(folder.count - 1));
+            harness.assert_cache_consistent();
+        }
+        for step in 1..=folder.count {
+            harness.prev();
+            assert_eq!(harness.current_index(), (folder.count - 1).saturating_sub(step));
+            harness.assert_cache_consistent();
+        }
+    }
+
+    #[test]
+    fn test_jump_then_step() {
+        let Some(mut harness) = Harness::new("jump", 3) else {
+            return;
+        };
+        harness.open_in_memory(&SyntheticSource::new("jump", 20));
+
+        for (target, after_next) in [(10, 11), (19, 19), (1, 2)] {
+            harness.jump(target);
+            assert_eq!(harness.current_index(), target);
+            harness.assert_cache_consistent();
+            harness.next();
+            assert_eq!(harness.current_index(), after_next);
+            harness.assert_cache_consistent();
+        }
+    }
+}
@@ -0,0 +1,333 @@
+/// Navigation from external controllers: OSC over UDP and, with the "midi" feature, MIDI
+///
+/// Configured in `<config dir>/viewskater/triggers.yaml`:
+///
+/// ```yaml
+/// osc_port: 9000            # or `--osc-port 9000`
+/// osc_host: 127.0.0.1       # 0.0.0.0 accepts messages from other machines
+/// midi_port: "nanoKONTROL"  # part of the MIDI input name
+/// midi:
+///   - { note: 60, action: prev }
+///   - { note: 62, action: next }
+///   - { cc: 16, action: jog }   # relative encoder: 1-63 forward, 65-127 back
+/// ```
+///
+/// OSC addresses are `/viewskater/next`, `/prev`, `/first`, `/last` and `/jump <index>`;
+/// a numeric 0 argument (button release) is ignored. Foot pedals that act as keyboards
+/// need no setup, they already send arrow keys.
+use std::net::UdpSocket;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use iced_winit::runtime::Task;
+use log::{debug, info, warn};
+
+use crate::app::{DataViewer, Message, NavigationMessage};
+
+const CONFIG_NAME: &str = "triggers.yaml";
+const OSC_PREFIX: &str = "/viewskater";
+
+/// Settings read at startup, waiting for the app to start listening
+static REQUESTED: Lazy<Mutex<Option<TriggerConfig>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriggerAction {
+    Next,
+    Prev,
+    First,
+    Last,
+    Jump(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum MidiAction {
+    Next,
+    Prev,
+    First,
+    Last,
+    /// Relative encoder on a CC: direction from the value
+    Jog,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct MidiBinding {
+    #[serde(default)]
+    note: Option<u8>,
+    #[serde(default)]
+    cc: Option<u8>,
+    action: MidiAction,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TriggerConfig {
+    #[serde(default)]
+    osc_port: Option<u16>,
+    #[serde(default)]
+    osc_host: Option<String>,
+    #[serde(default)]
+    midi_port: Option<String>,
+    #[serde(default)]
+    midi: Vec<MidiBinding>,
+}
+
+impl TriggerConfig {
+    fn is_enabled(&self) -> bool {
+        self.osc_port.is_some() || self.midi_port.is_some()
+    }
+}
+
+fn config_path() -> PathBuf {
+    crate::app_dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("viewskater")
+        .join(CONFIG_NAME)
+}
+
+/// Read `triggers.yaml`, with `osc_port` overriding its port, for the app to pick up
+pub fn request(osc_port: Option<u16>) {
+    let mut config = match std::fs::read_to_string(config_path()) {
+        Ok(yaml) => serde_yaml::from_str::<TriggerConfig>(&yaml).unwrap_or_else(|e| {
+            warn!("Ignoring {}: {}", CONFIG_NAME, e);
+            TriggerConfig::default()
+        }),
+        Err(_) => TriggerConfig::default(),
+    };
+    if osc_port.is_some() {
+        config.osc_port = osc_port;
+    }
+    if config.is_enabled() {
+        if let Ok(mut requested) = REQUESTED.lock() {
+            *requested = Some(config);
+        }
+    }
+}
+
+pub fn take_request() -> Option<TriggerConfig> {
+    REQUESTED.lock().ok()?.take()
+}
+
+/// Null-terminated string padded to a multiple of 4 bytes, and the offset after it
+fn read_osc_string(data: &[u8], offset: usize) -> Option<(&str, usize)> {
+    let rest = data.get(offset..)?;
+    let len = rest.iter().position(|&b| b == 0)?;
+    let text = std::str::from_utf8(&rest[..len]).ok()?;
+    Some((text, offset + (len + 4) / 4 * 4))
+}
+
+/// Address and first numeric argument of each message in an OSC packet or bundle
+pub fn parse_osc(data: &[u8]) -> Vec<(String, Option<f32>)> {
+    let mut messages = Vec::new();
+    if data.starts_with(b"#bundle\0") {
+        // Bundle: 8-byte tag, 8-byte time tag, then size-prefixed elements
+        let mut offset = 16;
+        while let Some(size) = data.get(offset..offset + 4).and_then(|b| b.try_into().ok()).map(i32::from_be_bytes) {
+            let start = offset + 4;
+            let Some(element) = data.get(start..start + size.max(0) as usize) else {
+                break;
+            };
+            messages.extend(parse_osc(element));
+            offset = start + size.max(0) as usize;
+        }
+        return messages;
+    }
+
+    let Some((address, offset)) = read_osc_string(data, 0) else {
+        return messages;
+    };
+    let (tags, offset) = read_osc_string(data, offset).unwrap_or((",", offset));
+    let word = data.get(offset..offset + 4).and_then(|b| <[u8; 4]>::try_from(b).ok());
+    let argument = match (tags.chars().nth(1), word) {
+        (Some('i'), Some(bytes)) => Some(i32::from_be_bytes(bytes) as f32),
+        (Some('f'), Some(bytes)) => Some(f32::from_be_bytes(bytes)),
+        (Some('T'), _) => Some(1.0),
+        (Some('F'), _) => Some(0.0),
+        _ => None,
+    };
+    messages.push((address.to_string(), argument));
+    messages
+}
+
+pub fn osc_action(address: &str, argument: Option<f32>) -> Option<TriggerAction> {
+    let command = address.strip_prefix(OSC_PREFIX).unwrap_or(address);
+    match command {
+        "/jump" => argument.filter(|v| *v >= 0.0).map(|v| TriggerAction::Jump(v as usize)),
+        // Buttons send 1 on press and 0 on release
+        _ if argument == Some(0.0) => None,
+        "/next" => Some(TriggerAction::Next),
+        "/prev" => Some(TriggerAction::Prev),
+        "/first" => Some(TriggerAction::First),
+        "/last" => Some(TriggerAction::Last),
+        _ => None,
+    }
+}
+
+#[cfg_attr(not(feature = "midi"), allow(dead_code))]
+fn midi_action(bindings: &[MidiBinding], message: &[u8]) -> Option<TriggerAction> {
+    let (&status, data) = message.split_first()?;
+    let (&number, &value) = (data.first()?, data.get(1)?);
+    let (is_note, is_cc) = (status & 0xf0 == 0x90 && value > 0, status & 0xf0 == 0xb0);
+    let binding = bindings.iter().find(|b| {
+        (is_note && b.note == Some(number)) || (is_cc && b.cc == Some(number))
+    })?;
+    match binding.action {
+        MidiAction::Jog if is_cc => match value {
+            1..=63 => Some(TriggerAction::Next),
+            65..=127 => Some(TriggerAction::Prev),
+            _ => None,
+        },
+        MidiAction::Jog => None,
+        // CCs from buttons send 127 on press and 0 on release
+        _ if is_cc && value == 0 => None,
+        MidiAction::Next => Some(TriggerAction::Next),
+        MidiAction::Prev => Some(TriggerAction::Prev),
+        MidiAction::First => Some(TriggerAction::First),
+        MidiAction::Last => Some(TriggerAction::Last),
+    }
+}
+
+fn listen_osc(config: &TriggerConfig, sender: futures::channel::mpsc::UnboundedSender<TriggerAction>) {
+    let Some(port) = config.osc_port else {
+        return;
+    };
+    let host = config.osc_host.clone().unwrap_or_else(|| "127.0.0.1".to_string());
+    let socket = match UdpSocket::bind((host.as_str(), port)) {
+        Ok(socket) => socket,
+        Err(e) => {
+            warn!("Failed to listen for OSC on {}:{}: {}", host, port, e);
+            return;
+        }
+    };
+    info!("Listening for OSC on {}:{}", host, port);
+    let _ = socket.set_read_timeout(Some(Duration::from_millis(500)));
+    std::thread::spawn(move || {
+        let mut buffer = [0u8; 1536];
+        while !sender.is_closed() {
+            let Ok(len) = socket.recv(&mut buffer) else {
+                continue;
+            };
+            for (address, argument) in parse_osc(&buffer[..len]) {
+                debug!("OSC {} {:?}", address, argument);
+                if let Some(action) = osc_action(&address, argument) {
+                    let _ = sender.unbounded_send(action);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(feature = "midi")]
+fn listen_midi(config: &TriggerConfig, sender: futures::channel::mpsc::UnboundedSender<TriggerAction>) {
+    let Some(port_name) = config.midi_port.clone() else {
+        return;
+    };
+    let bindings = config.midi.clone();
+    // The connection lives on its own thread and closes when the app stops listening
+    std::thread::spawn(move || {
+        let input = match midir::MidiInput::new("viewskater") {
+            Ok(input) => input,
+            Err(e) => {
+                warn!("MIDI is unavailable: {}", e);
+                return;
+            }
+        };
+        let ports = input.ports();
+        let Some(port) = ports.iter().find(|port| input.port_name(port).is_ok_and(|name| name.contains(&port_name))) else {
+            warn!("No MIDI input matching '{}'", port_name);
+            return;
+        };
+        let callback_sender = sender.clone();
+        let connection = input.connect(port, "viewskater-triggers", move |_stamp, message, _| {
+            if let Some(action) = midi_action(&bindings, message) {
+                let _ = callback_sender.unbounded_send(action);
+            }
+        }, ());
+        match connection {
+            Ok(_connection) => {
+                info!("Listening for MIDI on '{}'", port_name);
+                while !sender.is_closed() {
+                    std::thread::sleep(Duration::from_millis(500));
+                }
+            }
+            Err(e) => warn!("Failed to open MIDI input '{}': {}", port_name, e),
+        }
+    });
+}
+
+#[cfg(not(feature = "midi"))]
+fn listen_midi(config: &TriggerConfig, _sender: futures::channel::mpsc::UnboundedSender<TriggerAction>) {
+    if config.midi_port.is_some() || !config.midi.is_empty() {
+        warn!("MIDI triggers are configured but this build has no MIDI support (build with --features midi)");
+    }
+}
+
+/// Start the configured listeners
+pub fn start(config: TriggerConfig) -> Task<Message> {
+    let (sender, receiver) = futures::channel::mpsc::unbounded();
+    listen_osc(&config, sender.clone());
+    listen_midi(&config, sender);
+    Task::run(receiver, Message::TriggerAction)
+}
+
+pub fn handle_trigger_action(app: &mut DataViewer, action: TriggerAction) -> Task<Message> {
+    let pane_index = app.active_pane_index();
+    let Some(pane) = app.panes.get(pane_index).filter(|pane| pane.dir_loaded) else {
+        return Task::none();
+    };
+    let last = pane.img_cache.image_paths.len().saturating_sub(1);
+    match action {
+        TriggerAction::Next => app.step_all(true),
+        TriggerAction::Prev => app.step_all(false),
+        TriggerAction::First => Task::done(Message::Navigation(NavigationMessage::JumpToImage(pane_index, 0))),
+        TriggerAction::Last => Task::done(Message::Navigation(NavigationMessage::JumpToImage(pane_index, last))),
+        TriggerAction::Jump(index) => Task::done(Message::Navigation(NavigationMessage::JumpToImage(pane_index, index.min(last)))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn osc_message(address: &str, tags: &str, argument: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for text in [address, tags] {
+            data.extend(text.as_bytes());
+            data.extend(std::iter::repeat(0).take(4 - text.len() % 4));
+        }
+        data.extend(argument);
+        data
+    }
+
+    #[test]
+    fn test_osc_messages_and_bundles() {
+        let next = osc_message("/viewskater/next", ",f", &1.0f32.to_be_bytes());
+        let jump = osc_message("/viewskater/jump", ",i", &42i32.to_be_bytes());
+        assert_eq!(parse_osc(&next), vec![("/viewskater/next".to_string(), Some(1.0))]);
+
+        let mut bundle = b"#bundle\0\0\0\0\0\0\0\0\x01".to_vec();
+        for element in [&next, &jump] {
+            bundle.extend((element.len() as i32).to_be_bytes());
+            bundle.extend(element);
+        }
+        let actions: Vec<_> = parse_osc(&bundle).into_iter().filter_map(|(a, v)| osc_action(&a, v)).collect();
+        assert_eq!(actions, vec![TriggerAction::Next, TriggerAction::Jump(42)]);
+
+        // Button release
+        assert_eq!(osc_action("/viewskater/next", Some(0.0)), None);
+        assert_eq!(osc_action("/prev", None), Some(TriggerAction::Prev));
+    }
+
+    #[test]
+    fn test_midi_bindings() {
+        let config: TriggerConfig = serde_yaml::from_str(
+            "midi_port: pedal\nmidi:\n  - { note: 62, action: next }\n  - { cc: 16, action: jog }\n"
+        ).unwrap();
+        assert_eq!(midi_action(&config.midi, &[0x90, 62, 100]), Some(TriggerAction::Next));
+        assert_eq!(midi_action(&config.midi, &[0x90, 62, 0]), None); // note-on with velocity 0 is a release
+        assert_eq!(midi_action(&config.midi, &[0xb0, 16, 1]), Some(TriggerAction::Next));
+        assert_eq!(midi_action(&config.midi, &[0xb3, 16, 127]), Some(TriggerAction::Prev));
+        assert_eq!(midi_action(&config.midi, &[0x90, 61, 100]), None);
+    }
+}
@@ -18,8 +18,8 @@ use macos::*;
 #[allow(unused_imports)]
 use log::{Level, debug, info, warn, error};
 
+use std::sync::Arc;
 use iced_widget::{container, Container, row, column, horizontal_space, text, button, center};
-#[cfg(feature = "coco")]
 use iced_widget::Stack;
 use iced_winit::core::{Color, Element, Length, Alignment};
 use iced_winit::core::alignment;
@@ -33,12 +33,18 @@ use iced_winit::core::text::Shaping;
 use crate::pane::Pane;
 use crate::{menu as app_menu};
 use app_menu::button_style;
+use crate::cache::texture_tiles::grid_of;
 use crate::menu::PaneLayout;
-use crate::{app::Message, DataViewer};
-use crate::widgets::shader::image_shader::ImageShader;
-use crate::widgets::{split::Axis, viewer, dualslider::DualSlider};
+use crate::plugins::{PluginKind, PluginMessage, PluginOutput};
+use crate::{app::{Message, IoMessage, NavigationMessage, PaneMessage}, DataViewer};
+use crate::widgets::shader::image_shader::{ImageShader, wheel_zooms};
+use crate::widgets::shader::stereo_scene::{StereoMode, StereoScene};
+use crate::widgets::shader::texture_scene::BlendScene;
+use iced_widget::shader::Shader;
+use crate::widgets::{split::Axis, viewer, dualslider::DualSlider, overview_strip, timeline_strip};
+use crate::widgets::sidebar::{self, SidebarSection};
 use crate::{CURRENT_FPS, CURRENT_MEMORY_USAGE, pane::IMAGE_RENDER_FPS};
-use crate::menu::MENU_BAR_HEIGHT;
+use crate::navigation_slider::MissingPair;
 use iced_widget::tooltip;
 use crate::widgets::synced_image_split::SyncedImageSplit;
 use crate::widgets::circular::mini_circular;
@@ -69,10 +75,11 @@ fn image_copy_icon<'a, Message>() -> Element<'a, Message, WinitTheme, Renderer>
 }
 
 
-/// Helper struct to pass ML mark badge and COCO badge into footer function
+/// Helper struct to pass ML mark badge, COCO badge and window/level controls into footer function
 pub struct FooterOptions {
     pub mark_badge: Option<Element<'static, Message, WinitTheme, Renderer>>,
     pub coco_badge: Option<Element<'static, Message, WinitTheme, Renderer>>,
+    pub window_level: Option<Element<'static, Message, WinitTheme, Renderer>>,
 }
 
 impl FooterOptions {
@@ -80,9 +87,18 @@ impl FooterOptions {
         Self {
             mark_badge: None,
             coco_badge: None,
+            window_level: None,
         }
     }
 
+    /// Window/level sliders when the pane shows a 16-bit grayscale image
+    pub fn with_window_level(mut self, pane: &Pane) -> Self {
+        self.window_level = pane.scene.as_ref()
+            .and_then(|scene| scene.get_texture())
+            .and_then(|texture| crate::widgets::window_level::view(texture));
+        self
+    }
+
     #[cfg(feature = "selection")]
     pub fn with_mark(mut self, mark: crate::selection_manager::ImageMark) -> Self {
         self.mark_badge = Some(crate::widgets::selection_widget::mark_badge(mark));
@@ -361,7 +377,7 @@ pub fn get_footer(
     });
 
     // Left side: metadata (resolution and file size) - EoG style
-    let left_content: Element<'_, Message, WinitTheme, Renderer> = if let Some(meta) = state.metadata {
+    let metadata: Element<'_, Message, WinitTheme, Renderer> = if let Some(meta) = state.metadata {
         text(meta)
             .font(Font::MONOSPACE)
             .style(|_theme| iced::widget::text::Style {
@@ -374,6 +390,10 @@ pub fn get_footer(
             .size(14)
             .into()
     };
+    let left_content: Element<'_, Message, WinitTheme, Renderer> = match options.window_level {
+        Some(window_level) => row![metadata, window_level].spacing(12).align_y(Alignment::Center).into(),
+        None => metadata,
+    };
 
     // Optional loading spinner (shown during background loading, hidden when footer is narrow)
     let spinner_element: Element<'_, Message, WinitTheme, Renderer> = if state.show_spinner {
@@ -389,7 +409,7 @@ pub fn get_footer(
             button(file_copy_icon())
                 .padding(iced::padding::all(2))
                 .style(|_theme: &WinitTheme, _status: button::Status| button_style(_theme, _status, "labeled"))
-                .on_press(Message::CopyFilename(pane_index)),
+                .on_press(Message::Io(IoMessage::CopyFilename(pane_index))),
             container(text("Copy filename").size(14))
                 .padding(5)
                 .style(|theme: &WinitTheme| container::Style {
@@ -409,7 +429,7 @@ pub fn get_footer(
             button(folder_copy_icon())
                 .padding(iced::padding::all(2))
                 .style(|_theme: &WinitTheme, _status: button::Status| button_style(_theme, _status, "labeled"))
-                .on_press(Message::CopyFilePath(pane_index)),
+                .on_press(Message::Io(IoMessage::CopyFilePath(pane_index))),
             container(text("Copy file path").size(14))
                 .padding(5)
                 .style(|theme: &WinitTheme| container::Style {
@@ -429,7 +449,7 @@ pub fn get_footer(
             button(image_copy_icon())
                 .padding(iced::padding::all(2))
                 .style(|_theme: &WinitTheme, _status: button::Status| button_style(_theme, _status, "labeled"))
-                .on_press(Message::CopyImage(pane_index)),
+                .on_press(Message::Io(IoMessage::CopyImage(pane_index))),
             container(text("Copy image").size(14))
                 .padding(5)
                 .style(|theme: &WinitTheme| container::Style {
@@ -510,6 +530,110 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
 
     let mb = app_menu::build_menu(app);
 
+    // Nearest-neighbor results from the last "find similar" query
+    #[cfg(feature = "embeddings")]
+    let similar_strip = crate::widgets::similarity_widget::results_strip(&app.embedding_manager);
+    #[cfg(not(feature = "embeddings"))]
+    let similar_strip: Element<'_, Message, WinitTheme, Renderer> = container(text("")).height(0).into();
+
+    // Proxies around the current index, only when a single slider drives navigation
+    let overview_strip: Element<'_, Message, WinitTheme, Renderer> =
+        match overview_strip::active_pane(&app.pane_layout, app.is_slider_dual) {
+            Some(pane_index) => {
+                let pane = &app.panes[pane_index];
+                let current = overview_strip::display_index(pane, app.use_slider_image_for_render);
+                overview_strip::view(&app.overview, pane, pane_index, current)
+            }
+            None => container(text("")).height(0).into(),
+        };
+
+    // Capture-date groups of the same pane
+    let timeline_strip: Element<'_, Message, WinitTheme, Renderer> =
+        match overview_strip::active_pane(&app.pane_layout, app.is_slider_dual) {
+            Some(pane_index) => {
+                let current = overview_strip::display_index(&app.panes[pane_index], app.use_slider_image_for_render);
+                timeline_strip::view(&app.timeline, pane_index, current)
+            }
+            None => container(text("")).height(0).into(),
+        };
+
+    // Burst stack of the current image
+    let burst_bar = crate::burst_stacks::view(app);
+
+    // Progress of "Preload Folder into Memory"
+    let preload_bar = crate::folder_preload::view(&app.folder_preload);
+
+    // Progress of "Move to Trash"
+    let delete_bar = crate::file_delete::view(&app.file_delete);
+
+    // Progress of the window reload after a slider jump
+    let bulk_load_bar = crate::bulk_load::view();
+
+    // Playback controls of a shown animated image
+    let animation_bar = crate::animation::view();
+
+    // New release banner
+    #[cfg(feature = "update-check")]
+    let update_bar = crate::update_check::view(&app.update_check);
+    #[cfg(not(feature = "update-check"))]
+    let update_bar: Element<'_, Message, WinitTheme, Renderer> = container(text("")).height(0).into();
+
+    // Files skipped when the last folder was opened
+    let folder_stats_bar = crate::folder_stats::view(&app.folder_stats);
+
+    // Progress of "Export Comparison Report"
+    let report_bar = crate::comparison_report::view(&app.comparison_report);
+
+    // Metrics CSV plot for the active pane
+    let metrics_pane = app.active_pane_index();
+    let metrics_plot: Element<'_, Message, WinitTheme, Renderer> = match app.panes.get(metrics_pane) {
+        Some(pane) => crate::widgets::metrics_panel::view(&app.metrics_panel, pane, metrics_pane),
+        None => container(text("")).height(0).into(),
+    };
+
+    // Status of `--follow`
+    let follow_bar = crate::follow_mode::view(&app.follow_mode);
+
+    // Active LUT and its intensity
+    let lut_bar = crate::widgets::lut_panel::view();
+
+    // Exposure and gamma while an HDR image is shown
+    let shows_hdr = app.panes.iter().any(|pane| pane.scene.as_ref()
+        .and_then(|scene| scene.get_texture())
+        .is_some_and(|texture| crate::cache::hdr_textures::is_hdr(texture)));
+    let exposure_bar = crate::widgets::exposure_panel::view(shows_hdr);
+
+    // White balance preview mode and split
+    let white_balance_bar = crate::white_balance::view();
+
+    // Noise measured over the visible region
+    let noise_bar = crate::noise_analysis::view();
+    let rotate_bar = crate::auto_rotate::view();
+
+    // Annotation files found near the shown folder
+    let annotation_bar: Element<'_, Message, WinitTheme, Renderer> = {
+        #[cfg(feature = "coco")]
+        {
+            crate::coco::discovery::view(&app.coco_discovery)
+        }
+        #[cfg(not(feature = "coco"))]
+        {
+            container(text("")).height(0).into()
+        }
+    };
+
+    // Model loading, running or error
+    let inference_bar: Element<'_, Message, WinitTheme, Renderer> = {
+        #[cfg(feature = "inference")]
+        {
+            crate::inference::view(&app.inference)
+        }
+        #[cfg(not(feature = "inference"))]
+        {
+            container(text("")).height(0).into()
+        }
+    };
+
     let is_fullscreen = app.window_state == WindowState::FullScreen;
     let cursor_on_top = app.cursor_on_top;
     let cursor_on_menu = app.cursor_on_menu;
@@ -530,7 +654,12 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
         container(text("")).width(0).height(0).into()
     };
 
-    let top_bar = container(
+    // In hidden mode the bar only takes space once Alt reveals it; fullscreen still shows it
+    // while the cursor is at the top
+    let top_bar = if !app.menu_layout.shows_bar() && !is_fullscreen {
+        container(text("")).width(Length::Fill).height(0)
+    } else {
+        container(
         row![
             mb,
             horizontal_space(),
@@ -544,7 +673,8 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
             .align_y(alignment::Vertical::Center)
     )
     .align_y(alignment::Vertical::Center)
-    .width(Length::Fill);
+    .width(Length::Fill)
+    };
 
     // Menu bar spinner for fullscreen mode (same logic)
     let fullscreen_menu_bar_spinner: Element<'_, Message, WinitTheme, Renderer> = if show_menu_bar_spinner {
@@ -611,7 +741,8 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                         .horizontal_split(false)
                         .with_interaction_state(app.panes[0].mouse_wheel_zoom, app.panes[0].ctrl_pressed)
                         .double_click_threshold_ms(app.double_click_threshold_ms)
-                        .use_nearest_filter(app.nearest_neighbor_filter);
+                        .use_nearest_filter(app.nearest_neighbor_filter)
+                        .zoom_lock_pane(0);
 
                     #[cfg(not(feature = "coco"))]
                     let shader = ImageShader::new(Some(scene))
@@ -621,7 +752,8 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                         .horizontal_split(false)
                         .with_interaction_state(app.panes[0].mouse_wheel_zoom, app.panes[0].ctrl_pressed)
                         .double_click_threshold_ms(app.double_click_threshold_ms)
-                        .use_nearest_filter(app.nearest_neighbor_filter);
+                        .use_nearest_filter(app.nearest_neighbor_filter)
+                        .zoom_lock_pane(0);
 
                     #[cfg(feature = "coco")]
                     {
@@ -641,6 +773,7 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                             });
                     }
 
+                    let shader = shader.report_content_bounds(app.plugins.has_overlays());
                     center(shader)
                 } else {
                     return container(text("No image loaded"));
@@ -695,6 +828,13 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                                     has_invalid,
                                     app.coco_mask_render_mode,
                                     app.coco_disable_simplification,
+                                    crate::coco::overlay::LabelOptions {
+                                        visible: app.coco_show_labels,
+                                        size: app.coco_label_size,
+                                    },
+                                    0,
+                                    app.panes[0].selected_annotation,
+                                    app.annotation_manager.overlay_filter(),
                                 );
 
                                 // Stack image and annotations
@@ -734,6 +874,20 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                     .height(Length::Fill)
                     .padding(0);
 
+                // Model predictions go over the dataset's own annotations
+                #[cfg(feature = "inference")]
+                let with_annotations = match crate::inference::overlay(app) {
+                    Some(predictions) => container(
+                        Stack::new()
+                            .push(with_annotations)
+                            .push(predictions)
+                    )
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .padding(0),
+                    None => with_annotations,
+                };
+
                 with_annotations.into()
             } else {
                 // Use build_ui_container even when dir not loaded to show loading spinner
@@ -745,6 +899,14 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                 )
             };
 
+            // Shapes of overlay plugins, drawn over the full-resolution image only
+            let first_img: Element<'_, Message, WinitTheme, Renderer> = match plugin_overlay_layer(app) {
+                Some(overlay) => Stack::new().push(first_img).push(overlay).into(),
+                None => first_img,
+            };
+
+            let first_img = sidebar::attach(&app.sidebar, first_img, sidebar_sections(app));
+
             let footer = if app.show_footer && app.panes[0].dir_loaded {
                 // Use slider position during slider movement, otherwise use current_image_index
                 let display_index = if app.use_slider_image_for_render && app.panes[0].slider_image_position.is_some() {
@@ -752,7 +914,7 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                 } else {
                     app.panes[0].current_image_index.unwrap_or(app.panes[0].img_cache.current_index)
                 };
-                let footer_text = format!("{}/{}", display_index + 1, app.panes[0].img_cache.num_files);
+                let footer_text = format!("{}/{}", display_index + 1, app.panes[0].file_count_label());
 
                 // Generate metadata text for footer (EoG style: "1920x1080 pixels  2.5 MB")
                 let metadata_text = if app.show_metadata {
@@ -776,7 +938,7 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                     {
                         FooterOptions::new()
                     }
-                };
+                }.with_window_level(&app.panes[0]);
                 get_footer(footer_text, metadata_text, 0, app.show_copy_buttons, show_spinner, app.spinner_location, options, app.window_width)
             } else {
                 container(text("")).height(0)
@@ -784,11 +946,11 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
 
             let slider = if app.panes[0].dir_loaded && app.panes[0].img_cache.num_files > 1 {
                 container(DualSlider::new(
-                    0..=(app.panes[0].img_cache.num_files - 1) as u16,
+                    0..=(app.panes[0].img_cache.num_files - 1) as u32,
                     app.slider_value,
                     -1,
-                    Message::SliderChanged,
-                    Message::SliderReleased,
+                    |pane_index, value| Message::Navigation(NavigationMessage::SliderChanged(pane_index, value)),
+                    |pane_index, value| Message::Navigation(NavigationMessage::SliderReleased(pane_index, value)),
                 )
                 .width(Length::Fill))
             } else {
@@ -800,6 +962,8 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                 .height(Length::Shrink)
                 .align_x(Horizontal::Center);
 
+            let viewed_strip = crate::viewed_images::view(&app.viewed_images, 0, &app.panes[0]);
+
             // Create the column WITHOUT converting to Element first
             center(
                 container(
@@ -811,7 +975,27 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                         column![fps_bar, first_img]
                     } else {column![
                         top_bar,
+                        update_bar,
                         first_img,
+                        similar_strip,
+                        overview_strip,
+                        timeline_strip,
+                        burst_bar,
+                        metrics_plot,
+                        preload_bar,
+                        delete_bar,
+                        bulk_load_bar,
+                        animation_bar,
+                        folder_stats_bar,
+                        follow_bar,
+                        lut_bar,
+                        exposure_bar,
+                        white_balance_bar,
+                        noise_bar,
+                        rotate_bar,
+                        annotation_bar,
+                        inference_bar,
+                        viewed_strip,
                         slider_controls,
                         footer
                     ]}
@@ -837,7 +1021,7 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                         {
                             FooterOptions::new()
                         }
-                    },
+                    }.with_window_level(&app.panes[0]),
                     {
                         #[cfg(feature = "selection")]
                         {
@@ -847,7 +1031,7 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                         {
                             FooterOptions::new()
                         }
-                    },
+                    }.with_window_level(&app.panes[1]),
                 ];
 
                 debug!("build_ui (dual_pane_slider2): app.nearest_neighbor_filter = {}", app.nearest_neighbor_filter);
@@ -866,12 +1050,31 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                     app.use_binary_size,
                     app.spinner_location,
                     app.window_width,
+                    app.menu_layout.bar_height(),
                 );
+                let panes = sidebar::attach(&app.sidebar, panes.into(), sidebar_sections(app));
 
                 container(
                     column![
                         top_bar,
-                        panes
+                        update_bar,
+                        panes,
+                        similar_strip,
+                        metrics_plot,
+                        preload_bar,
+                        delete_bar,
+                        bulk_load_bar,
+                        animation_bar,
+                        folder_stats_bar,
+                        report_bar,
+                        follow_bar,
+                        lut_bar,
+                        exposure_bar,
+                        white_balance_bar,
+                        noise_bar,
+                        rotate_bar,
+                        annotation_bar,
+                        inference_bar
                     ]
                 )
                 .style(|theme| container::Style {
@@ -883,15 +1086,28 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
             } else {
                 // Pass synced_zoom parameter
                 debug!("build_ui (dual_pane_slider1): app.nearest_neighbor_filter = {}", app.nearest_neighbor_filter);
-                let panes = build_ui_dual_pane_slider1(
+                // Panes without an image at the shared position, when the lists differ
+                let both_loaded = app.panes[0].dir_loaded && app.panes[1].dir_loaded;
+                let missing_pairs: [Option<MissingPair>; 2] = std::array::from_fn(|idx| {
+                    let pane = &app.panes[idx];
+                    if !both_loaded || pane.indexing.is_some() {
+                        return None;
+                    }
+                    crate::navigation_slider::missing_pair(idx, app.slider_value as usize, pane.img_cache.image_paths.len())
+                });
+
+                let panes = build_stereo_view(app).or_else(|| build_blend_view(app)).unwrap_or_else(|| build_ui_dual_pane_slider1(
                     &app.panes,
+                    missing_pairs,
                     app.divider_position,
                     app.use_slider_image_for_render,
                     app.is_horizontal_split,
                     app.synced_zoom,
                     app.double_click_threshold_ms,
                     app.nearest_neighbor_filter,
-                );
+                    app.menu_layout.bar_height(),
+                ));
+                let panes = sidebar::attach(&app.sidebar, panes, sidebar_sections(app));
 
                 // Use slider position during slider movement, otherwise use current_image_index
                 let display_index_0 = if app.use_slider_image_for_render && app.panes[0].slider_image_position.is_some() {
@@ -904,9 +1120,13 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                 } else {
                     app.panes[1].current_image_index.unwrap_or(app.panes[1].img_cache.current_index)
                 };
+                let footer_index = |missing: Option<MissingPair>, index: usize| match missing {
+                    Some(_) => "-".to_string(),
+                    None => (index + 1).to_string(),
+                };
                 let footer_texts = [
-                    format!("{}/{}", display_index_0 + 1, app.panes[0].img_cache.num_files),
-                    format!("{}/{}", display_index_1 + 1, app.panes[1].img_cache.num_files)
+                    format!("{}/{}", footer_index(missing_pairs[0], display_index_0), app.panes[0].file_count_label()),
+                    format!("{}/{}", footer_index(missing_pairs[1], display_index_1), app.panes[1].file_count_label())
                 ];
 
                 // Generate metadata text for each pane (EoG style)
@@ -939,7 +1159,7 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                         {
                             FooterOptions::new()
                         }
-                    };
+                    }.with_window_level(&app.panes[0]);
                     let options1 = {
                         #[cfg(feature = "selection")]
                         {
@@ -949,13 +1169,21 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                         {
                             FooterOptions::new()
                         }
-                    };
+                    }.with_window_level(&app.panes[1]);
                     // Each pane gets half the window width in dual mode
                     let pane_width = app.window_width / 2.0;
+                    let offset_control: Element<'_, Message, WinitTheme, Renderer> = if both_loaded {
+                        let counts = [0, 1].map(|idx| app.panes[idx].img_cache.image_paths.len());
+                        pane_offset_control(crate::navigation_slider::pane_b_offset(), crate::navigation_slider::pair_counts(counts))
+                    } else {
+                        container(text("")).width(0).into()
+                    };
                     row![
-                        get_footer(footer_texts[0].clone(), metadata_texts[0].clone(), 0, app.show_copy_buttons, show_spinner_0, app.spinner_location, options0, pane_width),
-                        get_footer(footer_texts[1].clone(), metadata_texts[1].clone(), 1, app.show_copy_buttons, show_spinner_1, app.spinner_location, options1, pane_width)
+                        get_footer(footer_texts[0].clone(), crate::channel_diff::with_footer_text(metadata_texts[0].clone(), 0), 0, app.show_copy_buttons, show_spinner_0, app.spinner_location, options0, pane_width),
+                        offset_control,
+                        get_footer(footer_texts[1].clone(), crate::channel_diff::with_footer_text(metadata_texts[1].clone(), 1), 1, app.show_copy_buttons, show_spinner_1, app.spinner_location, options1, pane_width)
                     ]
+                    .align_y(Alignment::Center)
                 } else {
                     row![]
                 };
@@ -965,11 +1193,11 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                 let slider = if app.panes.iter().any(|p| p.dir_loaded) && max_num_files > 1 {
                     container(
                         DualSlider::new(
-                            0..=(max_num_files - 1) as u16,
+                            0..=(max_num_files - 1) as u32,
                             app.slider_value,
                             -1,
-                            Message::SliderChanged,
-                            Message::SliderReleased,
+                            |pane_index, value| Message::Navigation(NavigationMessage::SliderChanged(pane_index, value)),
+                            |pane_index, value| Message::Navigation(NavigationMessage::SliderReleased(pane_index, value)),
                         ).width(Length::Fill)
                     )
                     .width(Length::Fill)
@@ -978,6 +1206,15 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                     container(text("")).height(0)
                 };
 
+                // The shared slider spans the longer folder, so only that pane's marks line up
+                let viewed_pane = app.last_opened_pane.max(0) as usize;
+                let viewed_strip: Element<'_, Message, WinitTheme, Renderer> = match app.panes.get(viewed_pane) {
+                    Some(pane) if pane.img_cache.num_files == max_num_files => {
+                        crate::viewed_images::view(&app.viewed_images, viewed_pane, pane)
+                    }
+                    _ => container(text("")).height(0).into(),
+                };
+
                 container(
                     if is_fullscreen && !show_option &&(cursor_on_top || cursor_on_menu) {
                         column![top_bar, fps_bar, panes]
@@ -988,7 +1225,28 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
                     } else {
                         column![
                             top_bar,
+                            update_bar,
                             panes,
+                            similar_strip,
+                            overview_strip,
+                            timeline_strip,
+                            burst_bar,
+                            metrics_plot,
+                            preload_bar,
+                            delete_bar,
+                            bulk_load_bar,
+                            animation_bar,
+                            folder_stats_bar,
+                            report_bar,
+                            follow_bar,
+                            lut_bar,
+                            exposure_bar,
+                            white_balance_bar,
+                            noise_bar,
+                            rotate_bar,
+                            annotation_bar,
+                            inference_bar,
+                            viewed_strip,
                             slider,
                             footer
                         ]
@@ -1006,17 +1264,153 @@ pub fn build_ui(app: &DataViewer) -> Container<'_, Message, WinitTheme, Renderer
 
 
 
+/// "- offset +" buttons shifting pane B against the shared slider; the label resets it
+/// Pane B's frame offset buttons, with how many slider positions have an image in both
+/// panes when some don't
+fn pane_offset_control<'a>(offset: isize, (paired, positions): (usize, usize)) -> Element<'a, Message, WinitTheme, Renderer> {
+    let small_button = |label: String, msg: Message| {
+        button(text(label).size(12).font(Font::MONOSPACE))
+            .padding([1, 5])
+            .style(|theme: &WinitTheme, status| button_style(theme, status, "labeled"))
+            .on_press(msg)
+    };
+    let pair_stats: Element<'a, Message, WinitTheme, Renderer> = if paired < positions {
+        text(format!("{} of {} paired", paired, positions)).size(12).font(Font::MONOSPACE).into()
+    } else {
+        container(text("")).width(0).into()
+    };
+    tooltip(
+        row![
+            pair_stats,
+            small_button("-".to_string(), Message::Navigation(NavigationMessage::SetPaneOffset(offset - 1))),
+            small_button(format!("B {:+}", offset), Message::Navigation(NavigationMessage::SetPaneOffset(0))),
+            small_button("+".to_string(), Message::Navigation(NavigationMessage::SetPaneOffset(offset + 1))),
+        ]
+        .spacing(2)
+        .align_y(Alignment::Center),
+        container(text("Frame offset of the right pane; click the number to reset").size(14))
+            .padding(5)
+            .style(|theme: &WinitTheme| container::Style {
+                text_color: Some(Color::from([1.0, 1.0, 1.0])),
+                background: Some(theme.extended_palette().background.strong.color.into()),
+                ..container::Style::default()
+            }),
+        tooltip::Position::Top,
+    )
+    .into()
+}
+
+/// Shown instead of a pane's image when it has none at the shared slider position, rather
+/// than leaving its last image up as if it were the pair
+fn missing_pair_placeholder<'a>(missing: MissingPair) -> Element<'a, Message, WinitTheme, Renderer> {
+    container(
+        column![
+            text("No pair").size(18),
+            text(missing.reason()).size(13),
+        ]
+        .spacing(6)
+        .align_x(Alignment::Center)
+    )
+    .center_x(Length::Fill)
+    .center_y(Length::Fill)
+    .style(|theme: &WinitTheme| container::Style {
+        text_color: Some(Color::from_rgb(0.6, 0.6, 0.6)),
+        background: Some(theme.extended_palette().background.weak.color.scale_alpha(0.25).into()),
+        border: iced_winit::core::Border {
+            color: Color::from_rgb(0.35, 0.35, 0.35),
+            width: 1.0,
+            radius: 0.0.into(),
+        },
+        ..container::Style::default()
+    })
+    .into()
+}
+
+/// Both panes' current images composed as a stereo pair
+///
+/// Returns None when stereo mode is off, a pane has no texture to sample (nothing loaded,
+/// or the slider preview is showing) or a texture is tiled, so the split view is used.
+fn build_stereo_view(app: &DataViewer) -> Option<Element<'_, Message, WinitTheme, Renderer>> {
+    if app.stereo_mode == StereoMode::Off || app.use_slider_image_for_render
+        || !app.panes.iter().all(|pane| pane.dir_loaded)
+    {
+        return None;
+    }
+    let left = app.panes.first()?.scene.as_ref()?.get_texture()?;
+    let right = app.panes.get(1)?.scene.as_ref()?.get_texture()?;
+    if grid_of(left).is_some() || grid_of(right).is_some() {
+        return None;
+    }
+
+    Some(
+        Shader::new(StereoScene::new(Arc::clone(left), Arc::clone(right), app.stereo_mode))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    )
+}
+
+/// Pane 2 drawn over pane 1 with an opacity slider above the view
+///
+/// Falls back to the split view under the same conditions as `build_stereo_view`.
+fn build_blend_view(app: &DataViewer) -> Option<Element<'_, Message, WinitTheme, Renderer>> {
+    if !app.blend_compare || app.use_slider_image_for_render
+        || !app.panes.iter().all(|pane| pane.dir_loaded)
+    {
+        return None;
+    }
+    let base = app.panes.first()?.scene.as_ref()?.get_texture()?;
+    let over = app.panes.get(1)?.scene.as_ref()?.get_texture()?;
+    if grid_of(base).is_some() || grid_of(over).is_some() {
+        return None;
+    }
+
+    let opacity_percent = app.blend_opacity * 100.0;
+    let opacity_row = container(
+        row![
+            text(format!("Pane 2 opacity: {:.0}%", opacity_percent)).size(13).width(Length::Fixed(150.0)),
+            iced_widget::slider(0.0..=100.0, opacity_percent, |value| Message::Pane(PaneMessage::SetBlendOpacity(value / 100.0)))
+                .step(1.0)
+                .width(Length::Fixed(200.0)),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center)
+    )
+    .padding([4, 8])
+    .style(|_theme: &WinitTheme| container::Style {
+        text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+        ..container::Style::default()
+    });
+
+    Some(
+        column![
+            opacity_row,
+            Shader::new(BlendScene::new(Arc::clone(base), Arc::clone(over), app.blend_opacity))
+                .width(Length::Fill)
+                .height(Length::Fill),
+        ]
+        .into()
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn build_ui_dual_pane_slider1(
     panes: &[Pane],
+    missing_pairs: [Option<MissingPair>; 2],
     divider_position: Option<u16>,
     use_slider_image_for_render: bool,
     is_horizontal_split: bool,
     synced_zoom: bool,
     double_click_threshold_ms: u16,
     use_nearest_filter: bool,
+    menu_bar_height: f32,
 ) -> Element<'_, Message, WinitTheme, Renderer> {
-    let first_img = panes[0].build_ui_container(use_slider_image_for_render, is_horizontal_split, double_click_threshold_ms, use_nearest_filter);
-    let second_img = panes[1].build_ui_container(use_slider_image_for_render, is_horizontal_split, double_click_threshold_ms, use_nearest_filter);
+    let pane_ui = |idx: usize| match missing_pairs[idx] {
+        Some(missing) => missing_pair_placeholder(missing),
+        None => panes[idx].build_ui_container(use_slider_image_for_render, is_horizontal_split, double_click_threshold_ms, use_nearest_filter),
+    };
+    let first_img = pane_ui(0);
+    let second_img = pane_ui(1);
 
     let is_selected: Vec<bool> = panes.iter().map(|pane| pane.is_selected).collect();
 
@@ -1027,14 +1421,15 @@ pub fn build_ui_dual_pane_slider1(
         is_selected,
         divider_position,
         if is_horizontal_split { Axis::Horizontal } else { Axis::Vertical },
-        Message::OnSplitResize,
-        Message::ResetSplit,
-        Message::FileDropped,
-        Message::PaneSelected,
-        MENU_BAR_HEIGHT,
+        |position| Message::Pane(PaneMessage::OnSplitResize(position)),
+        |position| Message::Pane(PaneMessage::ResetSplit(position)),
+        |pane_index, path| Message::Io(IoMessage::FileDropped(pane_index, path)),
+        |pane_index, selected| Message::Pane(PaneMessage::PaneSelected(pane_index, selected)),
+        menu_bar_height,
         true,
     )
     .synced_zoom(synced_zoom)
+    .wheel_zooms(wheel_zooms(panes[0].mouse_wheel_zoom, panes[0].ctrl_pressed))
     .min_scale(0.25)
     .max_scale(10.0)
     .scale_step(0.10)
@@ -1058,6 +1453,7 @@ pub fn build_ui_dual_pane_slider2<'a>(
     use_binary_size: bool,
     spinner_location: SpinnerLocation,
     window_width: f32,
+    menu_bar_height: f32,
 ) -> Element<'a, Message, WinitTheme, Renderer> {
     // Each pane gets roughly half the window width
     let pane_width = window_width / 2.0;
@@ -1103,24 +1499,24 @@ pub fn build_ui_dual_pane_slider2<'a>(
                 column![
                     panes[0].build_ui_container(use_slider_image_for_render, is_horizontal_split, double_click_threshold_ms, use_nearest_filter),
                     DualSlider::new(
-                        0..=(panes[0].img_cache.num_files - 1) as u16,
+                        0..=(panes[0].img_cache.num_files - 1) as u32,
                         panes[0].slider_value,
                         0,
-                        Message::SliderChanged,
-                        Message::SliderReleased
+                        |pane_index, value| Message::Navigation(NavigationMessage::SliderChanged(pane_index, value)),
+                        |pane_index, value| Message::Navigation(NavigationMessage::SliderReleased(pane_index, value))
                     )
                     .width(Length::Fill),
-                    get_footer(footer_texts[0].clone(), metadata_texts[0].clone(), 0, show_copy_buttons, show_spinner_0, spinner_location, footer_opt0, pane_width)
+                    get_footer(footer_texts[0].clone(), crate::channel_diff::with_footer_text(metadata_texts[0].clone(), 0), 0, show_copy_buttons, show_spinner_0, spinner_location, footer_opt0, pane_width)
                 ]
             } else {
                 column![
                     panes[0].build_ui_container(use_slider_image_for_render, is_horizontal_split, double_click_threshold_ms, use_nearest_filter),
                     DualSlider::new(
-                        0..=(panes[0].img_cache.num_files - 1) as u16,
+                        0..=(panes[0].img_cache.num_files - 1) as u32,
                         panes[0].slider_value,
                         0,
-                        Message::SliderChanged,
-                        Message::SliderReleased
+                        |pane_index, value| Message::Navigation(NavigationMessage::SliderChanged(pane_index, value)),
+                        |pane_index, value| Message::Navigation(NavigationMessage::SliderReleased(pane_index, value))
                     )
                     .width(Length::Fill),
                 ]
@@ -1139,24 +1535,24 @@ pub fn build_ui_dual_pane_slider2<'a>(
                 column![
                     panes[1].build_ui_container(use_slider_image_for_render, is_horizontal_split, double_click_threshold_ms, use_nearest_filter),
                     DualSlider::new(
-                        0..=(panes[1].img_cache.num_files - 1) as u16,
+                        0..=(panes[1].img_cache.num_files - 1) as u32,
                         panes[1].slider_value,
                         1,
-                        Message::SliderChanged,
-                        Message::SliderReleased
+                        |pane_index, value| Message::Navigation(NavigationMessage::SliderChanged(pane_index, value)),
+                        |pane_index, value| Message::Navigation(NavigationMessage::SliderReleased(pane_index, value))
                     )
                     .width(Length::Fill),
-                    get_footer(footer_texts[1].clone(), metadata_texts[1].clone(), 1, show_copy_buttons, show_spinner_1, spinner_location, footer_opt1, pane_width)
+                    get_footer(footer_texts[1].clone(), crate::channel_diff::with_footer_text(metadata_texts[1].clone(), 1), 1, show_copy_buttons, show_spinner_1, spinner_location, footer_opt1, pane_width)
                 ]
             } else {
                 column![
                     panes[1].build_ui_container(use_slider_image_for_render, is_horizontal_split, double_click_threshold_ms, use_nearest_filter),
                     DualSlider::new(
-                        0..=(panes[1].img_cache.num_files - 1) as u16,
+                        0..=(panes[1].img_cache.num_files - 1) as u32,
                         panes[1].slider_value,
                         1,
-                        Message::SliderChanged,
-                        Message::SliderReleased
+                        |pane_index, value| Message::Navigation(NavigationMessage::SliderChanged(pane_index, value)),
+                        |pane_index, value| Message::Navigation(NavigationMessage::SliderReleased(pane_index, value))
                     )
                     .width(Length::Fill),
                 ]
@@ -1178,14 +1574,15 @@ pub fn build_ui_dual_pane_slider2<'a>(
         is_selected,
         divider_position,
         if is_horizontal_split { Axis::Horizontal } else { Axis::Vertical },
-        Message::OnSplitResize,
-        Message::ResetSplit,
-        Message::FileDropped,
-        Message::PaneSelected,
-        MENU_BAR_HEIGHT,
+        |position| Message::Pane(PaneMessage::OnSplitResize(position)),
+        |position| Message::Pane(PaneMessage::ResetSplit(position)),
+        |pane_index, path| Message::Io(IoMessage::FileDropped(pane_index, path)),
+        |pane_index, selected| Message::Pane(PaneMessage::PaneSelected(pane_index, selected)),
+        menu_bar_height,
         true,
     )
     .synced_zoom(false)
+    .wheel_zooms(wheel_zooms(panes[0].mouse_wheel_zoom, panes[0].ctrl_pressed))
     .min_scale(0.25)
     .max_scale(10.0)
     .scale_step(0.10)
@@ -1235,8 +1632,9 @@ fn get_fps_container(app: &DataViewer) -> Container<'_, Message, WinitTheme, Ren
         };
 
         container(
-            text(format!("UI: {:5.1} FPS | Image: {:5.1} FPS | {}",
-                         ui_fps, image_fps, memory_text))
+            text(format!("UI: {:5.1} FPS | Image: {:5.1} FPS | {}{}",
+                         ui_fps, image_fps, memory_text,
+                         if crate::utils::color::gamma_space_scaling() { " | Gamma-space scaling" } else { "" }))
                 .size(14)
                 .font(Font::MONOSPACE)
                 .style(|_theme| iced::widget::text::Style {
@@ -1247,4 +1645,145 @@ fn get_fps_container(app: &DataViewer) -> Container<'_, Message, WinitTheme, Ren
     } else {
         container(text("")).width(0).height(0)
     }
-}
\ No newline at end of file
+}
+
+/// Shapes of enabled overlay plugins for the image in the first pane
+fn plugin_overlay_layer(app: &DataViewer) -> Option<Element<'_, Message, WinitTheme, Renderer>> {
+    if !app.plugins.has_overlays() || (app.use_slider_image_for_render && app.panes[0].slider_image.is_some()) {
+        return None;
+    }
+    let path = app.plugin_image_path()?;
+    let shapes = app.plugins.overlay_shapes(&path);
+    if shapes.is_empty() {
+        return None;
+    }
+    let image = &app.panes[0].current_image;
+    Some(crate::plugins::overlay::overlay_layer(shapes, (image.width(), image.height())))
+}
+
+/// Sidebar sections with content for the active pane, in display order
+fn sidebar_sections(app: &DataViewer) -> Vec<(SidebarSection, Element<'_, Message, WinitTheme, Renderer>)> {
+    let mut sections = Vec::new();
+    if !app.sidebar.is_visible() {
+        return sections;
+    }
+    let pane_index = app.active_pane_index();
+    if let Some(pane) = app.panes.get(pane_index).filter(|pane| pane.dir_loaded) {
+        sections.push((SidebarSection::Info, image_info_section(pane, &app.favorites, &app.file_hashes, app.use_slider_image_for_render, app.use_binary_size)));
+        #[cfg(feature = "parquet")]
+        {
+            if let Some(labels) = parquet_labels_section(pane, app.use_slider_image_for_render) {
+                sections.push((SidebarSection::Labels, labels));
+            }
+        }
+        #[cfg(feature = "gps-map")]
+        {
+            if let Some(map) = crate::gps_map::section(&app.gps_map, pane) {
+                sections.push((SidebarSection::Map, map));
+            }
+        }
+        if let Some(playlist) = crate::playlist::section(&app.playlist_drag, pane, pane_index) {
+            sections.push((SidebarSection::Playlist, playlist));
+        }
+    }
+    if let Some(plugins) = plugins_section(app) {
+        sections.push((SidebarSection::Plugins, plugins));
+    }
+    sections.push((SidebarSection::Log, sidebar::log_section()));
+    sections
+}
+
+/// Name, location, position and size of the image shown in a pane
+fn image_info_section<'a>(
+    pane: &'a Pane,
+    favorites: &crate::favorites::Favorites,
+    file_hashes: &crate::file_hashes::FileHashes,
+    use_slider_image_for_render: bool,
+    use_binary_size: bool,
+) -> Element<'a, Message, WinitTheme, Renderer> {
+    let index = if use_slider_image_for_render && pane.slider_image_position.is_some() {
+        pane.slider_image_position.unwrap()
+    } else {
+        pane.current_image_index.unwrap_or(pane.img_cache.current_index)
+    };
+    let mut fields = vec![("Position".to_string(), format!("{}/{}", index + 1, pane.img_cache.num_files))];
+    if let Some(path) = pane.img_cache.image_paths.get(index) {
+        fields.insert(0, ("Name".to_string(), path.file_name().to_string()));
+        if let Some(folder) = path.path().parent() {
+            fields.push(("Folder".to_string(), folder.display().to_string()));
+        }
+        if std::fs::symlink_metadata(path.path()).is_ok_and(|m| m.file_type().is_symlink()) {
+            let target = std::fs::canonicalize(path.path())
+                .map(|target| target.display().to_string())
+                .unwrap_or_else(|_| "(broken link)".to_string());
+            fields.push(("Link target".to_string(), target));
+        }
+        if let Some(favorite) = favorites.get(path.path()) {
+            fields.push(("Favorite".to_string(), favorite.note.clone().unwrap_or_else(|| "Yes".to_string())));
+        }
+        fields.extend(file_hashes.fields(path.path()));
+    }
+    // Metadata follows the loaded image, not the slider preview
+    if let Some(metadata) = pane.current_image_metadata.as_ref().filter(|_| pane.slider_image_position.is_none()) {
+        fields.push(("Resolution".to_string(), format!("{} pixels", metadata.resolution_string())));
+        fields.push(("File size".to_string(), metadata.file_size_string(use_binary_size)));
+    }
+    sidebar::field_list(fields).into()
+}
+
+/// Output of enabled metadata plugins and analyzers for the current image
+fn plugins_section(app: &DataViewer) -> Option<Element<'_, Message, WinitTheme, Renderer>> {
+    let path = app.plugin_image_path()?;
+    let results = app.plugins.results_for(&path);
+    if results.is_empty() {
+        return None;
+    }
+
+    let mut content = column![].spacing(6);
+    for (plugin, output) in results {
+        let name = text(plugin.name.clone()).size(13).font(Font::MONOSPACE);
+        let running = plugin.kind != PluginKind::Analyzer || app.plugins.is_running(&plugin.name, &path);
+        let header: Element<'_, Message, WinitTheme, Renderer> = match plugin.kind {
+            PluginKind::Analyzer => row![
+                container(name).width(Length::Fill),
+                button(text("Analyze").size(11))
+                    .padding([1, 6])
+                    .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+                    .on_press_maybe((!running).then(|| PluginMessage::Analyze(plugin.name.clone(), path.clone()).into())),
+            ]
+            .align_y(Alignment::Center)
+            .into(),
+            _ => name.into(),
+        };
+        content = content.push(header);
+        match output {
+            None if running => content = content.push(text("Running...").size(12)),
+            None => {}
+            Some(PluginOutput::Error(e)) => content = content.push(text(e.clone()).size(12)),
+            Some(PluginOutput::Fields(fields)) => content = content.push(sidebar::field_list(fields.iter().cloned())),
+            Some(PluginOutput::Text(reply)) => content = content.push(text(reply.clone()).size(12).font(Font::MONOSPACE)),
+            // Overlay shapes are drawn over the image instead
+            Some(PluginOutput::Shapes(_)) => {}
+        }
+    }
+    Some(content.into())
+}
+
+/// Label columns of the parquet row being displayed
+#[cfg(feature = "parquet")]
+fn parquet_labels_section(pane: &Pane, use_slider_image_for_render: bool) -> Option<Element<'_, Message, WinitTheme, Renderer>> {
+    if pane.parquet_labels.is_empty() {
+        return None;
+    }
+
+    let index = if use_slider_image_for_render && pane.slider_image_position.is_some() {
+        pane.slider_image_position.unwrap()
+    } else {
+        pane.current_image_index.unwrap_or(pane.img_cache.current_index)
+    };
+    let name = pane.img_cache.image_paths.get(index)?.file_name();
+    Some(match pane.parquet_labels.get(name.as_ref()) {
+        Some(labels) => sidebar::field_list(labels.iter().cloned()).into(),
+        None => text("No label columns").size(12).into(),
+    })
+}
@@ -0,0 +1,226 @@
+/// Optional check for a newer release
+///
+/// With `check_for_updates` enabled, one request is sent at startup to the GitHub releases API
+/// for the latest release, without cookies or any identifier beyond the user agent. A newer
+/// version is announced in a banner with its release notes and a download link; failures
+/// (offline, rate limits) are only logged. "Check for Updates" in the Help menu runs the same
+/// check on demand.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, column, container, text, button, scrollable};
+use log::{info, warn};
+use serde::Deserialize;
+
+use crate::app::{DataViewer, Message, OverlayMessage};
+
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/ggand0/viewskater/releases/latest";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const NOTES_HEIGHT: f32 = 160.0;
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    tag_name: String,
+    html_url: String,
+    #[serde(default)]
+    body: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct UpdateCheck {
+    available: Option<Release>,
+    show_notes: bool,
+    /// Result of a check started from the menu, shown until dismissed
+    status: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum UpdateMessage {
+    /// Check now and report the result even when up to date
+    CheckNow,
+    Checked { manual: bool, result: Result<Option<Release>, String> },
+    ToggleNotes,
+    Download,
+    Dismiss,
+}
+
+impl From<UpdateMessage> for Message {
+    fn from(msg: UpdateMessage) -> Self {
+        Message::UpdateAction(msg)
+    }
+}
+
+/// Check once the app is running (the `check_for_updates` setting)
+pub fn request() {
+    REQUESTED.store(true, Ordering::Relaxed);
+}
+
+pub fn take_request() -> bool {
+    REQUESTED.swap(false, Ordering::Relaxed)
+}
+
+/// `major.minor.patch` of a tag such as `v0.3.1`, ignoring any pre-release suffix
+fn parse_version(tag: &str) -> Option<(u64, u64, u64)> {
+    let version = tag.trim().trim_start_matches('v');
+    let version = version.split(['-', '+']).next()?;
+    let mut parts = version.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().flatten().unwrap_or(0);
+    let patch = parts.next().flatten().unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+fn is_newer(tag: &str, current: &str) -> bool {
+    match (parse_version(tag), parse_version(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+/// The latest release if it is newer than this build
+fn fetch_newer_release() -> Result<Option<Release>, String> {
+    let current = env!("CARGO_PKG_VERSION");
+    let response = ureq::get(LATEST_RELEASE_URL)
+        .set("User-Agent", &format!("viewskater/{}", current))
+        .set("Accept", "application/vnd.github+json")
+        .timeout(REQUEST_TIMEOUT)
+        .call()
+        .map_err(|e| e.to_string())?;
+    let body = response.into_string().map_err(|e| e.to_string())?;
+    let release: Release = serde_json::from_str(&body).map_err(|e| e.to_string())?;
+    Ok(is_newer(&release.tag_name, current).then_some(release))
+}
+
+fn check(manual: bool) -> Task<Message> {
+    Task::perform(
+        async move {
+            tokio::task::spawn_blocking(fetch_newer_release)
+                .await
+                .unwrap_or_else(|e| Err(e.to_string()))
+        },
+        move |result| UpdateMessage::Checked { manual, result }.into(),
+    )
+}
+
+/// Startup check, when requested by the setting
+pub fn start() -> Task<Message> {
+    check(false)
+}
+
+pub fn handle_update_message(app: &mut DataViewer, msg: UpdateMessage) -> Task<Message> {
+    let state = &mut app.update_check;
+    match msg {
+        UpdateMessage::CheckNow => {
+            state.status = Some("Checking for updates...".to_string());
+            return check(true);
+        }
+        UpdateMessage::Checked { manual, result } => {
+            state.status = None;
+            match result {
+                Ok(Some(release)) => {
+                    info!("Update available: {}", release.tag_name);
+                    state.available = Some(release);
+                    state.show_notes = false;
+                }
+                Ok(None) => {
+                    info!("No newer release than {}", env!("CARGO_PKG_VERSION"));
+                    if manual {
+                        state.status = Some(format!("ViewSkater {} is up to date", env!("CARGO_PKG_VERSION")));
+                    }
+                }
+                Err(e) => {
+                    warn!("Update check failed: {}", e);
+                    if manual {
+                        state.status = Some(format!("Update check failed: {}", e));
+                    }
+                }
+            }
+        }
+        UpdateMessage::ToggleNotes => state.show_notes = !state.show_notes,
+        UpdateMessage::Download => {
+            if let Some(release) = &state.available {
+                return Task::done(Message::Overlay(OverlayMessage::OpenWebLink(release.html_url.clone())));
+            }
+        }
+        UpdateMessage::Dismiss => {
+            state.available = None;
+            state.status = None;
+        }
+    }
+    Task::none()
+}
+
+fn small_button(label: &str, message: UpdateMessage) -> Element<'_, Message, WinitTheme, Renderer> {
+    button(text(label).size(11))
+        .padding([1, 6])
+        .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+        .on_press(message.into())
+        .into()
+}
+
+/// Banner for an available update or the result of a manual check, or nothing
+pub fn view(state: &UpdateCheck) -> Element<'_, Message, WinitTheme, Renderer> {
+    let label_style = |_theme: &WinitTheme| iced_widget::text::Style {
+        color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+    };
+
+    let content: Element<'_, Message, WinitTheme, Renderer> = match (&state.available, &state.status) {
+        (Some(release), _) => {
+            let banner = row![
+                text(format!("ViewSkater {} is available", release.tag_name))
+                    .size(11).style(label_style).width(Length::Fill),
+                small_button(if state.show_notes { "Hide notes" } else { "Release notes" }, UpdateMessage::ToggleNotes),
+                small_button("Download", UpdateMessage::Download),
+                small_button("Dismiss", UpdateMessage::Dismiss),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center);
+
+            if state.show_notes {
+                let notes = release.body.as_deref().filter(|body| !body.trim().is_empty()).unwrap_or("No release notes");
+                column![
+                    banner,
+                    scrollable(text(notes).size(11).style(label_style)).height(Length::Fixed(NOTES_HEIGHT)),
+                ]
+                .spacing(4)
+                .into()
+            } else {
+                banner.into()
+            }
+        }
+        (None, Some(status)) => row![
+            text(status).size(11).style(label_style).width(Length::Fill),
+            small_button("OK", UpdateMessage::Dismiss),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .into(),
+        (None, None) => return container(text("")).height(0).into(),
+    };
+
+    container(content)
+        .padding([2, 8])
+        .width(Length::Fill)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_comparison() {
+        assert_eq!(parse_version("v0.3.1"), Some((0, 3, 1)));
+        assert_eq!(parse_version("1.2"), Some((1, 2, 0)));
+        assert_eq!(parse_version("v0.4.0-beta.1"), Some((0, 4, 0)));
+        assert!(is_newer("v0.3.2", "0.3.1"));
+        assert!(is_newer("v0.10.0", "0.9.9"));
+        assert!(!is_newer("v0.3.1", "0.3.1"));
+        assert!(!is_newer("nightly", "0.3.1"));
+    }
+}
@@ -0,0 +1,240 @@
+/// Local usage statistics for the current session
+///
+/// Counts images viewed, how often navigation found the next image already cached, the
+/// average decode time and the folders viewed most. Nothing is written to disk or sent
+/// anywhere; the numbers live in memory and are shown in the Help > Usage Statistics panel.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::font::Font;
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, column, container, text, button, Space};
+use once_cell::sync::Lazy;
+
+use crate::app::{DataViewer, Message};
+
+/// Folders listed in the panel
+const TOP_FOLDERS: usize = 5;
+
+#[derive(Debug, Default)]
+struct Counters {
+    views: u64,
+    /// Images reached with the arrow keys, and how many of those had to be loaded first
+    navigation_steps: u64,
+    cache_misses: u64,
+    decode_total: Duration,
+    decode_count: u64,
+    views_by_folder: HashMap<PathBuf, u64>,
+}
+
+impl Counters {
+    fn record_view(&mut self, path: &Path) {
+        self.views += 1;
+        if let Some(folder) = path.parent() {
+            *self.views_by_folder.entry(folder.to_path_buf()).or_insert(0) += 1;
+        }
+    }
+
+    fn snapshot(&self) -> UsageSnapshot {
+        let mut top_folders: Vec<(PathBuf, u64)> = self.views_by_folder.iter()
+            .map(|(folder, views)| (folder.clone(), *views))
+            .collect();
+        top_folders.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        top_folders.truncate(TOP_FOLDERS);
+
+        UsageSnapshot {
+            views: self.views,
+            cache_hit_rate: (self.navigation_steps > 0).then(|| {
+                self.navigation_steps.saturating_sub(self.cache_misses) as f64 / self.navigation_steps as f64
+            }),
+            average_decode_ms: (self.decode_count > 0)
+                .then(|| self.decode_total.as_secs_f64() * 1000.0 / self.decode_count as f64),
+            top_folders,
+        }
+    }
+}
+
+static COUNTERS: Lazy<Mutex<Counters>> = Lazy::new(|| Mutex::new(Counters::default()));
+
+/// Count an image shown in a pane
+pub fn record_view(path: &Path) {
+    if let Ok(mut counters) = COUNTERS.lock() {
+        counters.record_view(path);
+    }
+}
+
+/// Count a rendered keyboard navigation step
+pub fn record_navigation_step() {
+    if let Ok(mut counters) = COUNTERS.lock() {
+        counters.navigation_steps += 1;
+    }
+}
+
+/// Count a navigation step that found the next image not yet cached
+pub fn record_cache_miss() {
+    if let Ok(mut counters) = COUNTERS.lock() {
+        counters.cache_misses += 1;
+    }
+}
+
+pub fn record_decode(duration: Duration) {
+    if let Ok(mut counters) = COUNTERS.lock() {
+        counters.decode_total += duration;
+        counters.decode_count += 1;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageSnapshot {
+    pub views: u64,
+    pub cache_hit_rate: Option<f64>,
+    pub average_decode_ms: Option<f64>,
+    pub top_folders: Vec<(PathBuf, u64)>,
+}
+
+pub fn snapshot() -> UsageSnapshot {
+    COUNTERS.lock()
+        .map(|counters| counters.snapshot())
+        .unwrap_or_else(|_| Counters::default().snapshot())
+}
+
+#[derive(Debug, Clone)]
+pub enum UsageStatsMessage {
+    Show,
+    Hide,
+    Reset,
+}
+
+impl From<UsageStatsMessage> for Message {
+    fn from(msg: UsageStatsMessage) -> Self {
+        Message::UsageStatsAction(msg)
+    }
+}
+
+pub fn handle_usage_stats_message(app: &mut DataViewer, msg: UsageStatsMessage) -> Task<Message> {
+    match msg {
+        UsageStatsMessage::Show => app.show_usage_stats = true,
+        UsageStatsMessage::Hide => app.show_usage_stats = false,
+        UsageStatsMessage::Reset => {
+            if let Ok(mut counters) = COUNTERS.lock() {
+                *counters = Counters::default();
+            }
+        }
+    }
+    Task::none()
+}
+
+fn stat_row<'a>(label: &'a str, value: String) -> Element<'a, Message, WinitTheme, Renderer> {
+    row![
+        text(label).size(13).width(Length::Fixed(170.0)),
+        text(value).size(13),
+    ]
+    .spacing(10)
+    .into()
+}
+
+/// Builds the statistics panel
+pub fn view_usage_stats(_app: &DataViewer) -> Element<'_, Message, WinitTheme, Renderer> {
+    let stats = snapshot();
+    let label_style = |_theme: &WinitTheme| container::Style {
+        text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+        ..container::Style::default()
+    };
+
+    let mut folders = column![text("Most viewed folders").size(13)].spacing(3);
+    if stats.top_folders.is_empty() {
+        folders = folders.push(text("None yet").size(12));
+    }
+    for (folder, views) in &stats.top_folders {
+        folders = folders.push(
+            row![
+                text(views.to_string()).size(12).width(Length::Fixed(50.0)),
+                text(folder.display().to_string()).size(12),
+            ]
+            .spacing(10)
+        );
+    }
+
+    let summary = column![
+        stat_row("Images viewed", stats.views.to_string()),
+        stat_row("Cache hit rate", stats.cache_hit_rate
+            .map(|rate| format!("{:.1}%", rate * 100.0))
+            .unwrap_or_else(|| "-".to_string())),
+        stat_row("Average decode time", stats.average_decode_ms
+            .map(|ms| format!("{:.1} ms", ms))
+            .unwrap_or_else(|| "-".to_string())),
+    ]
+    .spacing(5);
+
+    let content = column![
+        text("Usage Statistics").size(18)
+            .font(Font {
+                family: iced_winit::core::font::Family::Name("Roboto"),
+                weight: iced_winit::core::font::Weight::Bold,
+                stretch: iced_winit::core::font::Stretch::Normal,
+                style: iced_winit::core::font::Style::Normal,
+            }),
+        text("This session only. Kept in memory, never saved or sent.").size(12),
+        container(summary).style(label_style),
+        container(folders).style(label_style),
+        row![
+            button(text("Reset"))
+                .padding([3, 10])
+                .on_press(UsageStatsMessage::Reset.into()),
+            Space::with_width(Length::Fill),
+            button(text("Close"))
+                .padding([3, 10])
+                .on_press(UsageStatsMessage::Hide.into()),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    ]
+    .spacing(10)
+    .width(Length::Fixed(480.0));
+
+    container(content)
+        .padding(15)
+        .style(|theme: &WinitTheme| {
+            iced_widget::container::Style {
+                background: Some(theme.extended_palette().background.base.color.into()),
+                text_color: Some(theme.extended_palette().primary.weak.text),
+                border: iced_winit::core::Border {
+                    color: theme.extended_palette().background.strong.color,
+                    width: 1.0,
+                    radius: iced_winit::core::border::Radius::from(8.0),
+                },
+                ..Default::default()
+            }
+        })
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot() {
+        let mut counters = Counters::default();
+        assert_eq!(counters.snapshot().cache_hit_rate, None);
+
+        for path in ["/a/1.png", "/a/2.png", "/b/1.png", "/a/3.png"] {
+            counters.record_view(Path::new(path));
+        }
+        counters.navigation_steps = 4;
+        counters.cache_misses = 1;
+        counters.decode_total = Duration::from_millis(30);
+        counters.decode_count = 2;
+
+        let stats = counters.snapshot();
+        assert_eq!(stats.views, 4);
+        assert_eq!(stats.cache_hit_rate, Some(0.75));
+        assert_eq!(stats.average_decode_ms, Some(15.0));
+        assert_eq!(stats.top_folders[0], (PathBuf::from("/a"), 3));
+        assert_eq!(stats.top_folders.len(), 2);
+    }
+}
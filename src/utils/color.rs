@@ -0,0 +1,116 @@
+/// sRGB-aware image scaling
+///
+/// Averaging sRGB-encoded bytes darkens edges and fine detail when an image is shrunk.
+/// `resize` converts to 16-bit linear light with premultiplied alpha, filters there and
+/// encodes back. The gamma-space path is kept behind a debug toggle (F4) so the two can
+/// be compared; the toggle also switches the image shader's minification filter.
+use std::sync::atomic::{AtomicBool, Ordering};
+use image::{DynamicImage, ImageBuffer, Rgba, RgbaImage};
+use image::imageops::FilterType;
+use once_cell::sync::Lazy;
+
+static GAMMA_SPACE_SCALING: AtomicBool = AtomicBool::new(false);
+
+static SRGB_TO_LINEAR: Lazy<[u16; 256]> = Lazy::new(|| {
+    let mut table = [0u16; 256];
+    for (i, value) in table.iter_mut().enumerate() {
+        *value = (srgb_to_linear(i as f32 / 255.0) * 65535.0).round() as u16;
+    }
+    table
+});
+
+static LINEAR_TO_SRGB: Lazy<Vec<u8>> = Lazy::new(|| {
+    (0..=u16::MAX)
+        .map(|v| (linear_to_srgb(v as f32 / 65535.0) * 255.0).round() as u8)
+        .collect()
+});
+
+pub(crate) fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+pub(crate) fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 }
+}
+
+/// sRGB-encoded byte of a linear value, clamped to 0..=1
+pub fn encode_srgb(linear: f32) -> u8 {
+    LINEAR_TO_SRGB[(linear.clamp(0.0, 1.0) * 65535.0).round() as usize]
+}
+
+/// Whether scaling is done on sRGB-encoded values (debug comparison mode)
+pub fn gamma_space_scaling() -> bool {
+    GAMMA_SPACE_SCALING.load(Ordering::Relaxed)
+}
+
+/// Flip the debug comparison mode, returning the new value
+pub fn toggle_gamma_space_scaling() -> bool {
+    !GAMMA_SPACE_SCALING.fetch_xor(true, Ordering::Relaxed)
+}
+
+/// Premultiplied linear-light copy of an RGBA image
+fn to_linear(image: &RgbaImage) -> ImageBuffer<Rgba<u16>, Vec<u16>> {
+    ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        let alpha = a as u32 * 257;
+        let premultiply = |c: u8| ((SRGB_TO_LINEAR[c as usize] as u32 * alpha) / 65535) as u16;
+        Rgba([premultiply(r), premultiply(g), premultiply(b), alpha as u16])
+    })
+}
+
+fn to_srgb(image: &ImageBuffer<Rgba<u16>, Vec<u16>>) -> RgbaImage {
+    ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        if a == 0 {
+            return Rgba([0, 0, 0, 0]);
+        }
+        let unpremultiply = |c: u16| LINEAR_TO_SRGB[((c as u32 * 65535) / a as u32).min(65535) as usize];
+        Rgba([unpremultiply(r), unpremultiply(g), unpremultiply(b), (a / 257) as u8])
+    })
+}
+
+/// Resize to exactly `width` x `height`, in linear light unless the debug toggle is on
+pub fn resize(image: &DynamicImage, width: u32, height: u32, filter: FilterType) -> DynamicImage {
+    if gamma_space_scaling() {
+        return image.resize_exact(width, height, filter);
+    }
+    let linear = image::imageops::resize(&to_linear(&image.to_rgba8()), width, height, filter);
+    DynamicImage::ImageRgba8(to_srgb(&linear))
+}
+
+/// Shrink to fit within `max_width` x `max_height`, keeping the aspect ratio
+pub fn thumbnail(image: &DynamicImage, max_width: u32, max_height: u32) -> DynamicImage {
+    let scale = (max_width as f64 / image.width() as f64)
+        .min(max_height as f64 / image.height() as f64)
+        .min(1.0);
+    let width = ((image.width() as f64 * scale).round() as u32).max(1);
+    let height = ((image.height() as f64 * scale).round() as u32).max(1);
+    resize(image, width, height, FilterType::Triangle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer_tables_round_trip() {
+        for value in 0..=255u8 {
+            assert_eq!(LINEAR_TO_SRGB[SRGB_TO_LINEAR[value as usize] as usize], value);
+        }
+    }
+
+    #[test]
+    fn test_linear_downscale_keeps_brightness() {
+        // Alternating black and white columns average to mid grey in light, which is
+        // about 188 in sRGB; gamma-space averaging gives the too-dark 128
+        let stripes = RgbaImage::from_fn(4, 2, |x, _| if x % 2 == 0 { Rgba([0, 0, 0, 255]) } else { Rgba([255, 255, 255, 255]) });
+        let shrunk = resize(&DynamicImage::ImageRgba8(stripes), 1, 1, FilterType::Triangle).to_rgba8();
+        let grey = shrunk.get_pixel(0, 0).0[0];
+        assert!((186..=190).contains(&grey), "got {}", grey);
+
+        // Fully transparent pixels do not darken their opaque neighbours
+        let edge = RgbaImage::from_fn(2, 1, |x, _| if x == 0 { Rgba([255, 255, 255, 255]) } else { Rgba([0, 0, 0, 0]) });
+        let shrunk = resize(&DynamicImage::ImageRgba8(edge), 1, 1, FilterType::Triangle).to_rgba8();
+        assert_eq!(shrunk.get_pixel(0, 0).0[0], 255);
+    }
+}
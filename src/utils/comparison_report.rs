@@ -0,0 +1,233 @@
+/// Static HTML report comparing the paired images of two panes
+///
+/// Each pair is decoded, compared at the first image's size (the second is resized if the
+/// sizes differ) and written as JPEG thumbnails of both images and their amplified
+/// difference into a `<report name>_files` folder next to the HTML page, so the report can
+/// be zipped and opened anywhere without the app.
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use image::{DynamicImage, RgbImage};
+use image::imageops::FilterType;
+use log::info;
+
+use crate::archive_cache::ArchiveCache;
+use crate::cache::img_cache::PathSource;
+use crate::exif_utils::decode_with_exif_orientation;
+use crate::file_io::read_image_bytes;
+
+const THUMBNAIL_SIZE: u32 = 320;
+/// Scale applied to the difference thumbnail so small changes are visible
+const DIFF_GAIN: u16 = 4;
+
+/// Difference between two images of the same size, on 8-bit RGB values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffMetrics {
+    /// Mean absolute difference, 0-255
+    pub mae: f64,
+    pub rmse: f64,
+    /// Infinite for identical images
+    pub psnr: f64,
+    /// Share of pixels with any channel differing, 0-1
+    pub changed: f64,
+}
+
+pub fn diff_metrics(a: &RgbImage, b: &RgbImage) -> DiffMetrics {
+    let mut abs_sum = 0u64;
+    let mut square_sum = 0u64;
+    let mut changed = 0u64;
+    for (pa, pb) in a.pixels().zip(b.pixels()) {
+        let mut pixel_changed = false;
+        for c in 0..3 {
+            let d = (pa[c] as i32 - pb[c] as i32).unsigned_abs() as u64;
+            abs_sum += d;
+            square_sum += d * d;
+            pixel_changed |= d > 0;
+        }
+        changed += pixel_changed as u64;
+    }
+    let pixels = (a.width() as u64 * a.height() as u64).max(1);
+    let samples = (pixels * 3) as f64;
+    let mse = square_sum as f64 / samples;
+    DiffMetrics {
+        mae: abs_sum as f64 / samples,
+        rmse: mse.sqrt(),
+        psnr: if mse == 0.0 { f64::INFINITY } else { 10.0 * (255.0 * 255.0 / mse).log10() },
+        changed: changed as f64 / pixels as f64,
+    }
+}
+
+fn diff_image(a: &RgbImage, b: &RgbImage) -> RgbImage {
+    RgbImage::from_fn(a.width(), a.height(), |x, y| {
+        let (pa, pb) = (a.get_pixel(x, y), b.get_pixel(x, y));
+        image::Rgb(std::array::from_fn(|c| {
+            ((pa[c] as i16 - pb[c] as i16).unsigned_abs() * DIFF_GAIN).min(255) as u8
+        }))
+    })
+}
+
+pub fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn format_psnr(psnr: f64) -> String {
+    if psnr.is_finite() { format!("{:.2} dB", psnr) } else { "identical".to_string() }
+}
+
+struct PairRow {
+    index: usize,
+    names: [String; 2],
+    thumbnails: [String; 3],
+    metrics: Result<DiffMetrics, String>,
+    resized: bool,
+}
+
+/// The folder the thumbnails of `output` are written to
+pub fn files_dir(output: &Path) -> PathBuf {
+    let stem = output.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| "report".to_string());
+    output.with_file_name(format!("{}_files", stem))
+}
+
+/// Compare each pair and write the report to `output`, reporting progress after each pair
+pub fn export_report(
+    pairs: &[(usize, PathSource, PathSource)],
+    archive_caches: [Arc<Mutex<ArchiveCache>>; 2],
+    titles: [String; 2],
+    output: &Path,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<PathBuf, String> {
+    if pairs.is_empty() {
+        return Err("No image pairs to compare".to_string());
+    }
+    let files_dir = files_dir(output);
+    std::fs::create_dir_all(&files_dir)
+        .map_err(|e| format!("Failed to create {}: {}", files_dir.display(), e))?;
+    let files_dir_name = files_dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+
+    let decode = |side: usize, path_source: &PathSource| -> Result<DynamicImage, String> {
+        let bytes = {
+            let mut cache = archive_caches[side].lock().map_err(|_| "Archive cache lock poisoned".to_string())?;
+            read_image_bytes(path_source, Some(&mut *cache))
+                .map_err(|e| format!("Failed to read {}: {}", path_source.file_name(), e))?
+        };
+        decode_with_exif_orientation(&bytes)
+            .map_err(|e| format!("Failed to decode {}: {:?}", path_source.file_name(), e))
+    };
+    let save_thumbnail = |image: &DynamicImage, name: String| -> Result<String, String> {
+        let path = files_dir.join(&name);
+        crate::utils::color::thumbnail(image, THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+            .to_rgb8()
+            .save_with_format(&path, image::ImageFormat::Jpeg)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(format!("{}/{}", files_dir_name, name))
+    };
+
+    let mut rows = Vec::with_capacity(pairs.len());
+    for (i, (index, a, b)) in pairs.iter().enumerate() {
+        let names = [a.file_name().to_string(), b.file_name().to_string()];
+        let row = match (decode(0, a), decode(1, b)) {
+            (Ok(image_a), Ok(image_b)) => {
+                let rgb_a = image_a.to_rgb8();
+                let resized = (image_a.width(), image_a.height()) != (image_b.width(), image_b.height());
+                let rgb_b = if resized {
+                    image_b.resize_exact(rgb_a.width(), rgb_a.height(), FilterType::Triangle).to_rgb8()
+                } else {
+                    image_b.to_rgb8()
+                };
+                let diff = DynamicImage::ImageRgb8(diff_image(&rgb_a, &rgb_b));
+                PairRow {
+                    index: *index,
+                    names,
+                    thumbnails: [
+                        save_thumbnail(&image_a, format!("{:05}_a.jpg", index))?,
+                        save_thumbnail(&image_b, format!("{:05}_b.jpg", index))?,
+                        save_thumbnail(&diff, format!("{:05}_diff.jpg", index))?,
+                    ],
+                    metrics: Ok(diff_metrics(&rgb_a, &rgb_b)),
+                    resized,
+                }
+            }
+            (Err(e), _) | (_, Err(e)) => PairRow {
+                index: *index,
+                names,
+                thumbnails: Default::default(),
+                metrics: Err(e),
+                resized: false,
+            },
+        };
+        rows.push(row);
+        progress(i + 1, pairs.len());
+    }
+
+    std::fs::write(output, render_html(&titles, &rows))
+        .map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+    info!("Wrote comparison report of {} pair(s) to {}", rows.len(), output.display());
+    Ok(output.to_path_buf())
+}
+
+fn render_html(titles: &[String; 2], rows: &[PairRow]) -> String {
+    let compared: Vec<&DiffMetrics> = rows.iter().filter_map(|row| row.metrics.as_ref().ok()).collect();
+    let identical = compared.iter().filter(|m| m.psnr.is_infinite()).count();
+    let mean_mae = compared.iter().map(|m| m.mae).sum::<f64>() / compared.len().max(1) as f64;
+
+    let mut html = String::new();
+    let _ = write!(html, "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>ViewSkater comparison</title>\n\
+        <style>body{{font-family:sans-serif;background:#1e1e1e;color:#e0e0e0}}\
+        table{{border-collapse:collapse}}td,th{{border:1px solid #444;padding:4px;vertical-align:top}}\
+        img{{max-width:{size}px;max-height:{size}px}}.err{{color:#e57373}}</style></head><body>\n",
+        size = THUMBNAIL_SIZE);
+    let _ = writeln!(html, "<h1>Comparison report</h1>\n<p>A: {}<br>B: {}</p>",
+        html_escape(&titles[0]), html_escape(&titles[1]));
+    let _ = writeln!(html, "<p>{} pairs, {} compared, {} identical, mean absolute difference {:.3}. \
+        Generated {}.</p>",
+        rows.len(), compared.len(), identical, mean_mae, chrono::Local::now().format("%Y-%m-%d %H:%M"));
+    html.push_str("<table>\n<tr><th>#</th><th>A</th><th>B</th><th>Difference</th><th>Metrics</th></tr>\n");
+    for row in rows {
+        let images: String = row.thumbnails.iter()
+            .map(|src| if src.is_empty() {
+                "<td></td>".to_string()
+            } else {
+                format!("<td><img src=\"{}\"></td>", html_escape(src))
+            })
+            .collect();
+        let metrics = match &row.metrics {
+            Ok(m) => format!("MAE {:.3}<br>RMSE {:.3}<br>PSNR {}<br>Changed {:.2}%{}",
+                m.mae, m.rmse, format_psnr(m.psnr), m.changed * 100.0,
+                if row.resized { "<br>B resized to A" } else { "" }),
+            Err(e) => format!("<span class=\"err\">{}</span>", html_escape(e)),
+        };
+        let _ = writeln!(html, "<tr><td>{}<br>{}<br>{}</td>{}<td>{}</td></tr>",
+            row.index + 1, html_escape(&row.names[0]), html_escape(&row.names[1]), images, metrics);
+    }
+    html.push_str("</table>\n</body></html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_metrics() {
+        let a = RgbImage::from_pixel(4, 4, image::Rgb([100, 100, 100]));
+        let same = diff_metrics(&a, &a);
+        assert_eq!(same.mae, 0.0);
+        assert!(same.psnr.is_infinite());
+
+        let mut b = a.clone();
+        b.put_pixel(0, 0, image::Rgb([110, 100, 100]));
+        let metrics = diff_metrics(&a, &b);
+        assert!((metrics.mae - 10.0 / 48.0).abs() < 1e-9);
+        assert!((metrics.changed - 1.0 / 16.0).abs() < 1e-9);
+        assert!(metrics.psnr.is_finite() && metrics.psnr > 30.0);
+    }
+
+    #[test]
+    fn test_html_escape_and_files_dir() {
+        assert_eq!(html_escape("a<b>&\"c\""), "a&lt;b&gt;&amp;&quot;c&quot;");
+        assert_eq!(files_dir(Path::new("/tmp/review.html")), PathBuf::from("/tmp/review_files"));
+    }
+}
@@ -0,0 +1,257 @@
+/// Capture date and GPS position from an EXIF block
+///
+/// The image crate hands back the raw TIFF structure of a file's EXIF data but doesn't parse
+/// it, so this reads just enough of it to find the few tags the viewer uses.
+use std::path::Path;
+use chrono::{Duration, NaiveDateTime};
+use image::{ImageDecoder, ImageReader};
+
+const DATE_TIME: u16 = 0x0132;
+const EXIF_IFD_POINTER: u16 = 0x8769;
+const DATE_TIME_ORIGINAL: u16 = 0x9003;
+const SUB_SEC_TIME_ORIGINAL: u16 = 0x9291;
+#[cfg(feature = "gps-map")]
+const GPS_IFD_POINTER: u16 = 0x8825;
+#[cfg(feature = "gps-map")]
+const GPS_LATITUDE_REF: u16 = 1;
+#[cfg(feature = "gps-map")]
+const GPS_LATITUDE: u16 = 2;
+#[cfg(feature = "gps-map")]
+const GPS_LONGITUDE_REF: u16 = 3;
+#[cfg(feature = "gps-map")]
+const GPS_LONGITUDE: u16 = 4;
+
+pub(crate) struct Tiff<'a> {
+    pub(crate) data: &'a [u8],
+    pub(crate) little_endian: bool,
+}
+
+impl Tiff<'_> {
+    pub(crate) fn u16_at(&self, offset: usize) -> Option<u16> {
+        let bytes: [u8; 2] = self.data.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if self.little_endian { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) })
+    }
+
+    pub(crate) fn u32_at(&self, offset: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if self.little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    }
+
+    /// Offset of the 12-byte entry for `tag` in the IFD at `ifd`
+    pub(crate) fn find_entry(&self, ifd: usize, tag: u16) -> Option<usize> {
+        let count = self.u16_at(ifd)? as usize;
+        (0..count)
+            .map(|i| ifd + 2 + i * 12)
+            .find(|&entry| self.u16_at(entry) == Some(tag))
+    }
+
+    /// Offset of the IFD a pointer tag in `ifd` refers to
+    pub(crate) fn sub_ifd(&self, ifd: usize, tag: u16) -> Option<usize> {
+        let entry = self.find_entry(ifd, tag)?;
+        Some(self.u32_at(entry + 8)? as usize)
+    }
+
+    /// ASCII value without its terminating NUL, stored inline when it fits in four bytes
+    fn ascii(&self, ifd: usize, tag: u16) -> Option<&str> {
+        let entry = self.find_entry(ifd, tag)?;
+        if self.u16_at(entry + 2)? != 2 {
+            return None;
+        }
+        let count = self.u32_at(entry + 4)? as usize;
+        let start = if count <= 4 { entry + 8 } else { self.u32_at(entry + 8)? as usize };
+        let bytes = self.data.get(start..start + count)?;
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        std::str::from_utf8(&bytes[..end]).ok()
+    }
+
+    /// First character of an inline ASCII value, such as the N/S and E/W references
+    #[cfg(feature = "gps-map")]
+    fn reference(&self, ifd: usize, tag: u16) -> Option<u8> {
+        let entry = self.find_entry(ifd, tag)?;
+        self.data.get(entry + 8).copied()
+    }
+
+    /// Degrees, minutes and seconds stored as three unsigned rationals
+    #[cfg(feature = "gps-map")]
+    fn degrees(&self, ifd: usize, tag: u16) -> Option<f64> {
+        let entry = self.find_entry(ifd, tag)?;
+        if self.u16_at(entry + 2)? != 5 || self.u32_at(entry + 4)? < 3 {
+            return None;
+        }
+        let values = self.u32_at(entry + 8)? as usize;
+        let rational = |i: usize| -> Option<f64> {
+            let numerator = self.u32_at(values + i * 8)? as f64;
+            let denominator = self.u32_at(values + i * 8 + 4)? as f64;
+            (denominator != 0.0).then(|| numerator / denominator)
+        };
+        Some(rational(0)? + rational(1).unwrap_or(0.0) / 60.0 + rational(2).unwrap_or(0.0) / 3600.0)
+    }
+}
+
+/// The TIFF header's byte order and the offset of IFD0
+fn open(exif: &[u8]) -> Option<(Tiff<'_>, usize)> {
+    let little_endian = match exif.get(0..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let tiff = Tiff { data: exif, little_endian };
+    if tiff.u16_at(2)? != 42 {
+        return None;
+    }
+    let ifd0 = tiff.u32_at(4)? as usize;
+    Some((tiff, ifd0))
+}
+
+fn read_exif(path: &Path) -> Option<Vec<u8>> {
+    let mut decoder = ImageReader::open(crate::file_io::long_path(path))
+        .ok()?
+        .with_guessed_format()
+        .ok()?
+        .into_decoder()
+        .ok()?;
+    decoder.exif_metadata().ok()?
+}
+
+/// When the picture was taken, falling back to when the file was last written by the camera
+/// or an editor (IFD0 DateTime). Fractions of a second are added when the camera wrote them,
+/// which tells apart the frames of a burst.
+pub fn parse_capture_date(exif: &[u8]) -> Option<NaiveDateTime> {
+    let (tiff, ifd0) = open(exif)?;
+    // Unknown dates are written as spaces or zeros, which fail to parse
+    let parse = |value: &str| NaiveDateTime::parse_from_str(value.trim(), "%Y:%m:%d %H:%M:%S").ok();
+    let exif_ifd = tiff.sub_ifd(ifd0, EXIF_IFD_POINTER);
+    let original = exif_ifd.and_then(|exif_ifd| tiff.ascii(exif_ifd, DATE_TIME_ORIGINAL)).and_then(parse);
+    let Some(original) = original else {
+        return tiff.ascii(ifd0, DATE_TIME).and_then(parse);
+    };
+    // Digits after the decimal point, so "5" is half a second
+    let fraction = exif_ifd
+        .and_then(|exif_ifd| tiff.ascii(exif_ifd, SUB_SEC_TIME_ORIGINAL))
+        .map(str::trim)
+        .filter(|digits| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()))
+        .and_then(|digits| format!("0.{}", digits).parse::<f64>().ok())
+        .unwrap_or(0.0);
+    Some(original + Duration::milliseconds((fraction * 1000.0) as i64))
+}
+
+/// Capture date of an image file, reading only its headers
+pub fn read_capture_date(path: &Path) -> Option<NaiveDateTime> {
+    parse_capture_date(&read_exif(path)?)
+}
+
+/// Latitude and longitude in signed decimal degrees from a TIFF-structured EXIF block
+#[cfg(feature = "gps-map")]
+pub fn parse_gps(exif: &[u8]) -> Option<(f64, f64)> {
+    let (tiff, ifd0) = open(exif)?;
+    let gps = tiff.sub_ifd(ifd0, GPS_IFD_POINTER)?;
+
+    let mut latitude = tiff.degrees(gps, GPS_LATITUDE)?;
+    let mut longitude = tiff.degrees(gps, GPS_LONGITUDE)?;
+    if tiff.reference(gps, GPS_LATITUDE_REF) == Some(b'S') {
+        latitude = -latitude;
+    }
+    if tiff.reference(gps, GPS_LONGITUDE_REF) == Some(b'W') {
+        longitude = -longitude;
+    }
+    // Cameras without a fix sometimes write zeros
+    let valid = latitude.abs() <= 90.0 && longitude.abs() <= 180.0 && (latitude, longitude) != (0.0, 0.0);
+    valid.then_some((latitude, longitude))
+}
+
+/// GPS position of an image file, reading only its headers
+#[cfg(feature = "gps-map")]
+pub fn read_gps(path: &Path) -> Option<(f64, f64)> {
+    parse_gps(&read_exif(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Little-endian TIFF with IFD0 holding only the GPS pointer
+    #[cfg(feature = "gps-map")]
+    fn exif_block(latitude_ref: u8, longitude_ref: u8) -> Vec<u8> {
+        let mut data = b"II".to_vec();
+        data.extend(42u16.to_le_bytes());
+        data.extend(8u32.to_le_bytes());
+        // IFD0 at 8: one entry, then the next-IFD offset
+        data.extend(1u16.to_le_bytes());
+        data.extend(GPS_IFD_POINTER.to_le_bytes());
+        data.extend(4u16.to_le_bytes());
+        data.extend(1u32.to_le_bytes());
+        data.extend(26u32.to_le_bytes());
+        data.extend(0u32.to_le_bytes());
+        // GPS IFD at 26: four entries, values after it at 26 + 2 + 48 + 4 = 80
+        data.extend(4u16.to_le_bytes());
+        let entries: [(u16, u16, u32, [u8; 4]); 4] = [
+            (GPS_LATITUDE_REF, 2, 2, [latitude_ref, 0, 0, 0]),
+            (GPS_LATITUDE, 5, 3, 80u32.to_le_bytes()),
+            (GPS_LONGITUDE_REF, 2, 2, [longitude_ref, 0, 0, 0]),
+            (GPS_LONGITUDE, 5, 3, 104u32.to_le_bytes()),
+        ];
+        for (tag, kind, count, value) in entries {
+            data.extend(tag.to_le_bytes());
+            data.extend(kind.to_le_bytes());
+            data.extend(count.to_le_bytes());
+            data.extend(value);
+        }
+        data.extend(0u32.to_le_bytes());
+        // 35° 30' 36", 139° 45' 0"
+        for (numerator, denominator) in [(35u32, 1u32), (30, 1), (3600, 100), (139, 1), (45, 1), (0, 1)] {
+            data.extend(numerator.to_le_bytes());
+            data.extend(denominator.to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    #[cfg(feature = "gps-map")]
+    fn test_parse_gps() {
+        let (latitude, longitude) = parse_gps(&exif_block(b'N', b'E')).unwrap();
+        assert!((latitude - 35.51).abs() < 1e-9);
+        assert!((longitude - 139.75).abs() < 1e-9);
+
+        let (latitude, longitude) = parse_gps(&exif_block(b'S', b'W')).unwrap();
+        assert!(latitude < 0.0 && longitude < 0.0);
+
+        assert_eq!(parse_gps(b"II*\0\x08\0\0\0\0\0"), None);
+        assert_eq!(parse_gps(b"not exif"), None);
+    }
+
+    /// Big-endian TIFF with IFD0 DateTime and an Exif IFD holding DateTimeOriginal
+    fn date_block(original: &[u8; 20]) -> Vec<u8> {
+        let mut data = b"MM".to_vec();
+        data.extend(42u16.to_be_bytes());
+        data.extend(8u32.to_be_bytes());
+        // IFD0 at 8: two entries, values after the Exif IFD
+        data.extend(2u16.to_be_bytes());
+        for (tag, kind, count, value) in [(DATE_TIME, 2u16, 20u32, 56u32), (EXIF_IFD_POINTER, 4, 1, 38)] {
+            data.extend(tag.to_be_bytes());
+            data.extend(kind.to_be_bytes());
+            data.extend(count.to_be_bytes());
+            data.extend(value.to_be_bytes());
+        }
+        data.extend(0u32.to_be_bytes());
+        // Exif IFD at 38: one entry
+        data.extend(1u16.to_be_bytes());
+        data.extend(DATE_TIME_ORIGINAL.to_be_bytes());
+        data.extend(2u16.to_be_bytes());
+        data.extend(20u32.to_be_bytes());
+        data.extend(76u32.to_be_bytes());
+        data.extend(0u32.to_be_bytes());
+        data.extend(b"2024:01:02 03:04:05\0");
+        data.extend(original);
+        data
+    }
+
+    #[test]
+    fn test_parse_capture_date() {
+        let date = parse_capture_date(&date_block(b"2023:06:12 18:30:00\0")).unwrap();
+        assert_eq!(date.to_string(), "2023-06-12 18:30:00");
+        // Blank DateTimeOriginal falls back to IFD0 DateTime
+        let date = parse_capture_date(&date_block(b"    :  :     :  :  \0")).unwrap();
+        assert_eq!(date.to_string(), "2024-01-02 03:04:05");
+        assert_eq!(parse_capture_date(b"not exif"), None);
+    }
+}
@@ -0,0 +1,191 @@
+/// Frame timing capture for `--frame-stats`
+///
+/// When enabled, the existing timing hooks (image load, GPU upload, renderer present and
+/// keyboard navigation) also feed their samples here, and a JSON report with per-stage
+/// percentiles is written on exit so CI can compare runs between commits.
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use log::{info, warn};
+
+use crate::build_info::BuildInfo;
+
+/// Samples kept per stage; later samples are dropped so long sessions stay bounded
+const MAX_SAMPLES: usize = 100_000;
+
+static FRAME_STATS: Lazy<Mutex<Option<FrameStatsRecorder>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Reading and decoding an image on the loader
+    Decode,
+    /// Uploading decoded pixels to a GPU texture
+    Upload,
+    /// Renderer present and queue submit for one frame
+    Present,
+    /// Whole redraw, from acquiring the surface texture to presenting it
+    Frame,
+    /// Key press to the next image being rendered
+    Navigation,
+}
+
+#[derive(Default)]
+struct FrameStatsRecorder {
+    output: PathBuf,
+    decode: Vec<f64>,
+    upload: Vec<f64>,
+    present: Vec<f64>,
+    frame: Vec<f64>,
+    navigation: Vec<f64>,
+    navigation_start: Option<Instant>,
+}
+
+impl FrameStatsRecorder {
+    fn samples_mut(&mut self, stage: Stage) -> &mut Vec<f64> {
+        match stage {
+            Stage::Decode => &mut self.decode,
+            Stage::Upload => &mut self.upload,
+            Stage::Present => &mut self.present,
+            Stage::Frame => &mut self.frame,
+            Stage::Navigation => &mut self.navigation,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct StageSummary {
+    pub count: usize,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Serialize)]
+struct FrameStatsReport<'a> {
+    version: &'static str,
+    commit: &'static str,
+    profile: &'static str,
+    platform: &'static str,
+    decode: StageSummary,
+    upload: StageSummary,
+    present: StageSummary,
+    frame: StageSummary,
+    navigation_latency: StageSummary,
+    /// Raw whole-frame times in milliseconds, in order
+    frame_times_ms: &'a [f64],
+}
+
+/// Nearest-rank percentile of sorted samples
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+pub fn summarize(samples: &[f64]) -> StageSummary {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mean_ms = if sorted.is_empty() { 0.0 } else { sorted.iter().sum::<f64>() / sorted.len() as f64 };
+    StageSummary {
+        count: sorted.len(),
+        mean_ms,
+        p50_ms: percentile(&sorted, 50.0),
+        p90_ms: percentile(&sorted, 90.0),
+        p99_ms: percentile(&sorted, 99.0),
+        max_ms: sorted.last().copied().unwrap_or(0.0),
+    }
+}
+
+/// Start recording; the report goes to `output` when `write_report` is called
+pub fn enable(output: PathBuf) {
+    info!("Recording frame timing to {}", output.display());
+    if let Ok(mut stats) = FRAME_STATS.lock() {
+        *stats = Some(FrameStatsRecorder { output, ..FrameStatsRecorder::default() });
+    }
+}
+
+pub fn record(stage: Stage, duration: Duration) {
+    if let Ok(mut stats) = FRAME_STATS.lock() {
+        if let Some(recorder) = stats.as_mut() {
+            let samples = recorder.samples_mut(stage);
+            if samples.len() < MAX_SAMPLES {
+                samples.push(duration.as_secs_f64() * 1000.0);
+            }
+        }
+    }
+}
+
+/// A keyboard navigation was requested; the first one since the last render is kept
+pub fn navigation_started() {
+    if let Ok(mut stats) = FRAME_STATS.lock() {
+        if let Some(recorder) = stats.as_mut() {
+            recorder.navigation_start.get_or_insert_with(Instant::now);
+        }
+    }
+}
+
+/// The image a navigation asked for was rendered
+pub fn navigation_rendered() {
+    let start = match FRAME_STATS.lock() {
+        Ok(mut stats) => stats.as_mut().and_then(|recorder| recorder.navigation_start.take()),
+        Err(_) => None,
+    };
+    if let Some(start) = start {
+        record(Stage::Navigation, start.elapsed());
+    }
+}
+
+/// Write the JSON report and stop recording; does nothing when recording is off
+pub fn write_report() {
+    let Some(recorder) = FRAME_STATS.lock().ok().and_then(|mut stats| stats.take()) else {
+        return;
+    };
+
+    let report = FrameStatsReport {
+        version: BuildInfo::version(),
+        commit: BuildInfo::git_hash_short(),
+        profile: BuildInfo::build_profile(),
+        platform: BuildInfo::target_platform(),
+        decode: summarize(&recorder.decode),
+        upload: summarize(&recorder.upload),
+        present: summarize(&recorder.present),
+        frame: summarize(&recorder.frame),
+        navigation_latency: summarize(&recorder.navigation),
+        frame_times_ms: &recorder.frame,
+    };
+    let result = serde_json::to_string_pretty(&report)
+        .map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(&recorder.output, json).map_err(|e| e.to_string()));
+    match result {
+        Ok(()) => info!("Frame timing written to {}", recorder.output.display()),
+        Err(e) => warn!("Failed to write frame timing to {}: {}", recorder.output.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_use_nearest_rank() {
+        let samples: Vec<f64> = (1..=100).map(f64::from).collect();
+        let summary = summarize(&samples);
+        assert_eq!(summary.count, 100);
+        assert_eq!(summary.p50_ms, 50.0);
+        assert_eq!(summary.p90_ms, 90.0);
+        assert_eq!(summary.p99_ms, 99.0);
+        assert_eq!(summary.max_ms, 100.0);
+        assert_eq!(summary.mean_ms, 50.5);
+
+        // Unsorted input and a single sample
+        assert_eq!(summarize(&[3.0, 1.0, 2.0]).p50_ms, 2.0);
+        assert_eq!(percentile(&[7.0], 99.0), 7.0);
+        assert_eq!(summarize(&[]).p99_ms, 0.0);
+    }
+}
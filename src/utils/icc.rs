@@ -0,0 +1,237 @@
+/// Matrix/TRC ICC display profiles
+///
+/// Reads the colorant (`rXYZ`/`gXYZ`/`bXYZ`) and tone curve (`rTRC`/`gTRC`/`bTRC`) tags that
+/// describe most monitor profiles, and reduces them to what the image shader applies: a matrix
+/// from linear sRGB to linear display RGB and one effective gamma per channel. LUT-based
+/// profiles (`A2B0` only) are not supported.
+
+/// Linear sRGB to PCS XYZ, Bradford-adapted to D50 as in the ICC sRGB profile
+pub(super) const SRGB_TO_XYZ_D50: [[f32; 3]; 3] = [
+    [0.436_074_7, 0.385_064_9, 0.143_080_4],
+    [0.222_504_5, 0.716_878_6, 0.060_616_9],
+    [0.013_932_2, 0.097_104_5, 0.714_173_3],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DisplayProfile {
+    /// Row-major matrix from linear sRGB to linear display RGB
+    pub from_srgb: [[f32; 3]; 3],
+    /// Effective gamma of each channel's tone curve
+    pub gamma: [f32; 3],
+}
+
+pub(super) fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+pub(super) fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+pub(super) fn read_s15_fixed16(data: &[u8], offset: usize) -> Option<f32> {
+    Some(read_u32(data, offset)? as i32 as f32 / 65536.0)
+}
+
+/// Tag data by signature, from the tag table after the 128-byte header
+pub(super) fn find_tag<'a>(data: &'a [u8], signature: &[u8; 4]) -> Option<&'a [u8]> {
+    let count = read_u32(data, 128)? as usize;
+    (0..count.min(1024)).find_map(|i| {
+        let entry = 132 + i * 12;
+        if data.get(entry..entry + 4)? != signature {
+            return None;
+        }
+        let offset = read_u32(data, entry + 4)? as usize;
+        let size = read_u32(data, entry + 8)? as usize;
+        data.get(offset..offset.checked_add(size)?)
+    })
+}
+
+pub(super) fn read_xyz(data: &[u8], signature: &[u8; 4]) -> Result<[f32; 3], String> {
+    let tag = find_tag(data, signature).ok_or(format!("missing {} tag", String::from_utf8_lossy(signature)))?;
+    if tag.get(0..4) != Some(&b"XYZ "[..]) {
+        return Err(format!("{} is not an XYZ tag", String::from_utf8_lossy(signature)));
+    }
+    Ok([
+        read_s15_fixed16(tag, 8).ok_or("truncated XYZ tag")?,
+        read_s15_fixed16(tag, 12).ok_or("truncated XYZ tag")?,
+        read_s15_fixed16(tag, 16).ok_or("truncated XYZ tag")?,
+    ])
+}
+
+/// Value of a `parametricCurveType` function at `x`
+pub(super) fn parametric_curve(function: u16, p: &[f32], x: f32) -> Option<f32> {
+    let param = |i: usize| p.get(i).copied();
+    let g = param(0)?;
+    Some(match function {
+        0 => x.powf(g),
+        1 => {
+            let (a, b) = (param(1)?, param(2)?);
+            if x >= -b / a { (a * x + b).powf(g) } else { 0.0 }
+        }
+        2 => {
+            let (a, b, c) = (param(1)?, param(2)?, param(3)?);
+            if x >= -b / a { (a * x + b).powf(g) + c } else { c }
+        }
+        3 => {
+            let (a, b, c, d) = (param(1)?, param(2)?, param(3)?, param(4)?);
+            if x >= d { (a * x + b).powf(g) } else { c * x }
+        }
+        4 => {
+            let (a, b, c, d, e, f) = (param(1)?, param(2)?, param(3)?, param(4)?, param(5)?, param(6)?);
+            if x >= d { (a * x + b).powf(g) + e } else { c * x + f }
+        }
+        _ => return None,
+    })
+}
+
+/// Gamma that matches a tone curve at mid-gray
+fn read_gamma(data: &[u8], signature: &[u8; 4]) -> Result<f32, String> {
+    let name = String::from_utf8_lossy(signature).to_string();
+    let tag = find_tag(data, signature).ok_or(format!("missing {} tag", name))?;
+    let mid = match tag.get(0..4) {
+        Some(b"curv") => {
+            let count = read_u32(tag, 8).ok_or("truncated curve")? as usize;
+            match count {
+                0 => return Ok(1.0),
+                1 => return Ok(read_u16(tag, 12).ok_or("truncated curve")? as f32 / 256.0),
+                _ => {
+                    let position = (count - 1) as f32 * 0.5;
+                    let (low, high) = (position.floor() as usize, position.ceil() as usize);
+                    let entry = |i: usize| read_u16(tag, 12 + i * 2).map(|v| v as f32 / 65535.0);
+                    let (y0, y1) = (entry(low).ok_or("truncated curve")?, entry(high).ok_or("truncated curve")?);
+                    y0 + (y1 - y0) * (position - low as f32)
+                }
+            }
+        }
+        Some(b"para") => {
+            let function = read_u16(tag, 8).ok_or("truncated curve")?;
+            let params: Vec<f32> = (0..7).map_while(|i| read_s15_fixed16(tag, 12 + i * 4)).collect();
+            parametric_curve(function, &params, 0.5).ok_or(format!("unsupported parametric curve in {}", name))?
+        }
+        _ => return Err(format!("{} has an unsupported curve type", name)),
+    };
+    if !(mid > 0.0 && mid < 1.0) {
+        return Err(format!("{} is not a usable tone curve", name));
+    }
+    Ok((mid.ln() / 0.5f32.ln()).clamp(0.5, 5.0))
+}
+
+pub(super) fn multiply(a: &[[f32; 3]; 3], b: &[[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    let mut out = [[0.0; 3]; 3];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    out
+}
+
+pub(super) fn invert(m: &[[f32; 3]; 3]) -> Option<[[f32; 3]; 3]> {
+    let cofactor = |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+    let det = m[0][0] * cofactor(1, 2, 1, 2) - m[0][1] * cofactor(1, 2, 0, 2) + m[0][2] * cofactor(1, 2, 0, 1);
+    if det.abs() < 1e-8 {
+        return None;
+    }
+    Some([
+        [cofactor(1, 2, 1, 2) / det, -cofactor(0, 2, 1, 2) / det, cofactor(0, 1, 1, 2) / det],
+        [-cofactor(1, 2, 0, 2) / det, cofactor(0, 2, 0, 2) / det, -cofactor(0, 1, 0, 2) / det],
+        [cofactor(1, 2, 0, 1) / det, -cofactor(0, 2, 0, 1) / det, cofactor(0, 1, 0, 1) / det],
+    ])
+}
+
+pub fn parse_display_profile(data: &[u8]) -> Result<DisplayProfile, String> {
+    if data.get(36..40) != Some(&b"acsp"[..]) {
+        return Err("not an ICC profile".to_string());
+    }
+    if data.get(16..20) != Some(&b"RGB "[..]) {
+        return Err("only RGB display profiles are supported".to_string());
+    }
+    let (r, g, b) = (read_xyz(data, b"rXYZ")?, read_xyz(data, b"gXYZ")?, read_xyz(data, b"bXYZ")?);
+    let to_xyz = [[r[0], g[0], b[0]], [r[1], g[1], b[1]], [r[2], g[2], b[2]]];
+    let from_xyz = invert(&to_xyz).ok_or("colorant matrix is singular")?;
+    Ok(DisplayProfile {
+        from_srgb: multiply(&from_xyz, &SRGB_TO_XYZ_D50),
+        gamma: [read_gamma(data, b"rTRC")?, read_gamma(data, b"gTRC")?, read_gamma(data, b"bTRC")?],
+    })
+}
+
+/// Profiles for the tests of the ICC parsers
+#[cfg(test)]
+pub(super) mod test_profiles {
+    fn fixed(v: f32) -> [u8; 4] {
+        ((v * 65536.0).round() as i32).to_be_bytes()
+    }
+
+    /// `curv` tone curve with a single gamma
+    pub fn curv(gamma: f32) -> Vec<u8> {
+        let mut tag = b"curv\0\0\0\0".to_vec();
+        tag.extend(1u32.to_be_bytes());
+        tag.extend(((gamma * 256.0) as u16).to_be_bytes());
+        tag
+    }
+
+    /// `para` tone curve of the given function type and parameters
+    pub fn para(function: u16, params: &[f32]) -> Vec<u8> {
+        let mut tag = b"para\0\0\0\0".to_vec();
+        tag.extend(function.to_be_bytes());
+        tag.extend([0u8; 2]);
+        params.iter().for_each(|&v| tag.extend(fixed(v)));
+        tag
+    }
+
+    /// Minimal RGB profile with the given colorant columns and `curve` for every channel
+    pub fn matrix_trc(columns: [[f32; 3]; 3], curve: &[u8]) -> Vec<u8> {
+        let mut tags: Vec<(&[u8; 4], Vec<u8>)> = Vec::new();
+        for (signature, column) in [(b"rXYZ", columns[0]), (b"gXYZ", columns[1]), (b"bXYZ", columns[2])] {
+            let mut tag = b"XYZ \0\0\0\0".to_vec();
+            column.iter().for_each(|&v| tag.extend(fixed(v)));
+            tags.push((signature, tag));
+        }
+        for signature in [b"rTRC", b"gTRC", b"bTRC"] {
+            tags.push((signature, curve.to_vec()));
+        }
+
+        let mut data = vec![0u8; 128];
+        data[16..20].copy_from_slice(b"RGB ");
+        data[36..40].copy_from_slice(b"acsp");
+        data.extend((tags.len() as u32).to_be_bytes());
+        let mut offset = 132 + tags.len() * 12;
+        let mut body: Vec<u8> = Vec::new();
+        for (signature, tag) in &tags {
+            data.extend(*signature);
+            data.extend((offset as u32).to_be_bytes());
+            data.extend((tag.len() as u32).to_be_bytes());
+            offset += tag.len();
+            body.extend(tag);
+        }
+        data.extend(body);
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::test_profiles::{curv, matrix_trc};
+
+    #[test]
+    fn test_srgb_like_profile_is_identity() {
+        let m = SRGB_TO_XYZ_D50;
+        let columns = [[m[0][0], m[1][0], m[2][0]], [m[0][1], m[1][1], m[2][1]], [m[0][2], m[1][2], m[2][2]]];
+        let profile = parse_display_profile(&matrix_trc(columns, &curv(2.2))).unwrap();
+        for (i, row) in profile.from_srgb.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                assert!((value - if i == j { 1.0 } else { 0.0 }).abs() < 1e-3, "{:?}", profile.from_srgb);
+            }
+        }
+        assert!((profile.gamma[0] - 2.2).abs() < 0.01);
+        assert!(parse_display_profile(&[0u8; 64]).is_err());
+    }
+
+    #[test]
+    fn test_parametric_curve_effective_gamma() {
+        // The sRGB curve behaves like gamma 2.2 at mid-gray
+        let y = parametric_curve(3, &[2.4, 1.0 / 1.055, 0.055 / 1.055, 1.0 / 12.92, 0.04045], 0.5).unwrap();
+        assert!((y.ln() / 0.5f32.ln() - 2.2).abs() < 0.05);
+    }
+}
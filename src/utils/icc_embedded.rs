@@ -0,0 +1,173 @@
+/// Embedded ICC profiles of decoded images
+///
+/// Phone photos in Display P3, camera JPEGs in Adobe RGB and exports from wide-gamut editing
+/// carry the profile their values are in, and look washed out when those values are shown as
+/// sRGB. Images with a matrix/TRC RGB profile are converted to sRGB when they are decoded; a
+/// display profile, if one is set, takes over from there in the image shader. Profiles that
+/// already describe sRGB are recognized and skipped, and LUT-based, CMYK and gray profiles are
+/// left alone. `convert_icc_profiles: false` in the settings file turns conversion off.
+use std::io::Cursor;
+use image::{DynamicImage, ImageDecoder, ImageReader};
+use log::debug;
+use rayon::prelude::*;
+
+use super::icc::{find_tag, invert, multiply, parametric_curve, read_s15_fixed16, read_u16, read_u32, read_xyz, SRGB_TO_XYZ_D50};
+use crate::config::CONFIG;
+use crate::utils::color::encode_srgb;
+
+/// Conversion from an image's RGB profile to sRGB
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceProfile {
+    /// Linear light of each 8-bit value, per channel
+    to_linear: [[f32; 256]; 3],
+    /// Row-major matrix from linear profile RGB to linear sRGB
+    to_srgb: [[f32; 3]; 3],
+}
+
+/// Tone curve of a channel, as the linear light of each 8-bit value
+fn read_curve(data: &[u8], signature: &[u8; 4]) -> Result<[f32; 256], String> {
+    let name = String::from_utf8_lossy(signature).to_string();
+    let tag = find_tag(data, signature).ok_or(format!("missing {} tag", name))?;
+    let mut table = [0.0; 256];
+    match tag.get(0..4) {
+        Some(b"curv") => {
+            let count = read_u32(tag, 8).ok_or("truncated curve")? as usize;
+            let entry = |i: usize| read_u16(tag, 12 + i * 2).map(|v| v as f32 / 65535.0);
+            if count > 0 && entry(count - 1).is_none() {
+                return Err(format!("{} is truncated", name));
+            }
+            let gamma = if count == 1 { read_u16(tag, 12).unwrap_or(256) as f32 / 256.0 } else { 1.0 };
+            for (i, value) in table.iter_mut().enumerate() {
+                let x = i as f32 / 255.0;
+                *value = match count {
+                    0 => x,
+                    1 => x.powf(gamma),
+                    _ => {
+                        let position = x * (count - 1) as f32;
+                        let low = position.floor() as usize;
+                        let (y0, y1) = (entry(low).unwrap_or(0.0), entry((low + 1).min(count - 1)).unwrap_or(0.0));
+                        y0 + (y1 - y0) * (position - low as f32)
+                    }
+                };
+            }
+        }
+        Some(b"para") => {
+            let function = read_u16(tag, 8).ok_or("truncated curve")?;
+            let params: Vec<f32> = (0..7).map_while(|i| read_s15_fixed16(tag, 12 + i * 4)).collect();
+            for (i, value) in table.iter_mut().enumerate() {
+                *value = parametric_curve(function, &params, i as f32 / 255.0)
+                    .ok_or(format!("unsupported parametric curve in {}", name))?;
+            }
+        }
+        _ => return Err(format!("{} has an unsupported curve type", name)),
+    }
+    Ok(table)
+}
+
+pub fn parse_source_profile(data: &[u8]) -> Result<SourceProfile, String> {
+    if data.get(36..40) != Some(&b"acsp"[..]) {
+        return Err("not an ICC profile".to_string());
+    }
+    if data.get(16..20) != Some(&b"RGB "[..]) {
+        return Err("only RGB profiles are converted".to_string());
+    }
+    let (r, g, b) = (read_xyz(data, b"rXYZ")?, read_xyz(data, b"gXYZ")?, read_xyz(data, b"bXYZ")?);
+    let to_xyz = [[r[0], g[0], b[0]], [r[1], g[1], b[1]], [r[2], g[2], b[2]]];
+    let xyz_to_srgb = invert(&SRGB_TO_XYZ_D50).ok_or("sRGB matrix is singular")?;
+    Ok(SourceProfile {
+        to_linear: [read_curve(data, b"rTRC")?, read_curve(data, b"gTRC")?, read_curve(data, b"bTRC")?],
+        to_srgb: multiply(&xyz_to_srgb, &to_xyz),
+    })
+}
+
+impl SourceProfile {
+    /// Whether converting would leave every value within one step of where it is
+    pub fn is_srgb(&self) -> bool {
+        let identity = self.to_srgb.iter().enumerate()
+            .all(|(i, row)| row.iter().enumerate().all(|(j, &value)| (value - if i == j { 1.0 } else { 0.0 }).abs() < 0.01));
+        identity && self.to_linear.iter()
+            .all(|curve| curve.iter().enumerate().all(|(i, &linear)| (encode_srgb(linear) as i32 - i as i32).abs() <= 1))
+    }
+
+    /// Converts the RGB of a pixel, leaving any alpha after it as it is
+    fn convert_pixel(&self, pixel: &mut [u8]) {
+        let linear = [0, 1, 2].map(|channel| self.to_linear[channel][pixel[channel] as usize]);
+        for (value, row) in pixel.iter_mut().zip(&self.to_srgb) {
+            *value = encode_srgb(row[0] * linear[0] + row[1] * linear[1] + row[2] * linear[2]);
+        }
+    }
+
+    /// Converts an image to sRGB; 16-bit images are reduced to 8 bits, as they are shown anyway.
+    /// Gray and floating-point images are left as they are.
+    pub fn convert(&self, image: &mut DynamicImage) {
+        match image {
+            DynamicImage::ImageRgb16(_) => *image = DynamicImage::ImageRgb8(image.to_rgb8()),
+            DynamicImage::ImageRgba16(_) => *image = DynamicImage::ImageRgba8(image.to_rgba8()),
+            _ => {}
+        }
+        match image {
+            DynamicImage::ImageRgb8(rgb) => rgb.par_chunks_exact_mut(3).for_each(|pixel| self.convert_pixel(pixel)),
+            DynamicImage::ImageRgba8(rgba) => rgba.par_chunks_exact_mut(4).for_each(|pixel| self.convert_pixel(pixel)),
+            _ => {}
+        }
+    }
+}
+
+/// ICC profile embedded in an encoded image, for the formats whose decoder reads one
+fn embedded_profile(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut decoder = ImageReader::new(Cursor::new(bytes)).with_guessed_format().ok()?.into_decoder().ok()?;
+    decoder.icc_profile().ok().flatten()
+}
+
+/// Converts `image`, decoded from `bytes`, from its embedded profile to sRGB
+pub fn convert_to_srgb(bytes: &[u8], image: &mut DynamicImage) {
+    if !CONFIG.convert_icc_profiles {
+        return;
+    }
+    let Some(data) = embedded_profile(bytes) else {
+        return;
+    };
+    match parse_source_profile(&data) {
+        Ok(profile) if !profile.is_srgb() => profile.convert(image),
+        Ok(_) => {}
+        Err(e) => debug!("Embedded ICC profile not converted: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::icc::test_profiles::{matrix_trc, para};
+
+    #[test]
+    fn test_wide_gamut_red_converts_out_of_srgb() {
+        // Display P3 primaries, adapted to D50
+        let p3 = [[0.5151, 0.2412, -0.0011], [0.2920, 0.6922, 0.0419], [0.1571, 0.0666, 0.7841]];
+        let profile = parse_source_profile(&matrix_trc(p3, &para(0, &[2.2]))).unwrap();
+        assert!(!profile.is_srgb());
+
+        // P3 red is more saturated than sRGB can show, so it clips to pure red
+        let mut red = [255, 0, 0];
+        profile.convert_pixel(&mut red);
+        assert_eq!(red, [255, 0, 0]);
+        // A less saturated P3 red gets more red and less green in sRGB
+        let mut muted = [200, 100, 100, 77];
+        profile.convert_pixel(&mut muted);
+        assert!(muted[0] > 200 && muted[1] < 100, "{:?}", muted);
+        assert_eq!(muted[3], 77);
+        // Neutral grays stay neutral
+        let mut gray = [128, 128, 128];
+        profile.convert_pixel(&mut gray);
+        assert!(gray.iter().all(|&v| (v as i32 - gray[0] as i32).abs() <= 1), "{:?}", gray);
+    }
+
+    #[test]
+    fn test_srgb_profile_is_skipped() {
+        let m = SRGB_TO_XYZ_D50;
+        let columns = [[m[0][0], m[1][0], m[2][0]], [m[0][1], m[1][1], m[2][1]], [m[0][2], m[1][2], m[2][2]]];
+        let srgb_curve = para(3, &[2.4, 1.0 / 1.055, 0.055 / 1.055, 1.0 / 12.92, 0.04045]);
+        let data = matrix_trc(columns, &srgb_curve);
+        assert!(parse_source_profile(&data).unwrap().is_srgb());
+        assert!(parse_source_profile(&[0u8; 64]).is_err());
+    }
+}
@@ -0,0 +1,383 @@
+/// Soft proofing through ICC output profiles
+///
+/// Reduces an output profile to a 3D table from sRGB-encoded color to the color it would
+/// reproduce as (sRGB -> PCS -> device -> PCS -> sRGB), with a fourth channel marking colors
+/// outside the profile's gamut. Output profiles are read from their v2 `lut8`/`lut16` tables
+/// (`B2A1`/`A2B1`, falling back to the perceptual `B2A0`/`A2B0`), which covers most CMYK
+/// press profiles; v4 `mAB`/`mBA` tables are not supported. RGB profiles without tables are
+/// proofed through their primaries. The gamut comes from the `gamt` tag when present, and
+/// otherwise from how far a color moves in the round trip.
+use super::color::{linear_to_srgb, srgb_to_linear};
+use super::icc::{find_tag, invert, multiply, read_s15_fixed16, read_u16, read_xyz, SRGB_TO_XYZ_D50};
+use super::lut::f32_to_f16;
+
+/// Grid points per axis of the proofing table
+pub const PROOF_TABLE_SIZE: usize = 33;
+/// D50 white of the PCS
+const D50: [f32; 3] = [0.9642, 1.0, 0.8249];
+/// Round-trip ΔE*ab above which a color counts as out of gamut when there is no `gamt` tag
+const ROUND_TRIP_DELTA_E: f32 = 4.0;
+/// Slack for matrix profiles, so colors on the gamut boundary are not flagged
+const MATRIX_GAMUT_EPSILON: f32 = 1e-3;
+/// Keeps a table from a corrupt header from allocating gigabytes
+const MAX_CLUT_ENTRIES: usize = 1 << 24;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Pcs {
+    Lab,
+    Xyz,
+}
+
+/// A `lut8Type` or `lut16Type` tag
+#[derive(Debug, Clone)]
+struct LutTable {
+    inputs: usize,
+    outputs: usize,
+    grid: usize,
+    /// Only applied when the input is PCS XYZ
+    matrix: [[f32; 3]; 3],
+    input_curves: Vec<Vec<f32>>,
+    clut: Vec<f32>,
+    output_curves: Vec<Vec<f32>>,
+    /// 16-bit table, which uses the legacy 16-bit Lab encoding
+    wide: bool,
+}
+
+fn apply(m: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    [0, 1, 2].map(|i| m[i][0] * v[0] + m[i][1] * v[1] + m[i][2] * v[2])
+}
+
+fn xyz_to_lab(xyz: [f32; 3]) -> [f32; 3] {
+    let f = |t: f32| if t > 216.0 / 24389.0 { t.cbrt() } else { (24389.0 / 27.0 * t + 16.0) / 116.0 };
+    let [fx, fy, fz] = [0, 1, 2].map(|i| f(xyz[i] / D50[i]));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+fn lab_to_xyz(lab: [f32; 3]) -> [f32; 3] {
+    let fy = (lab[0] + 16.0) / 116.0;
+    let f = [fy + lab[1] / 500.0, fy, fy - lab[2] / 200.0];
+    let inverse = |t: f32| if t > 6.0 / 29.0 { t * t * t } else { (116.0 * t - 16.0) * 27.0 / 24389.0 };
+    [0, 1, 2].map(|i| inverse(f[i]) * D50[i])
+}
+
+fn delta_e(a: [f32; 3], b: [f32; 3]) -> f32 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+impl Pcs {
+    /// PCS color as normalized table values
+    fn encode(self, xyz: [f32; 3], wide: bool) -> [f32; 3] {
+        match self {
+            Pcs::Lab => {
+                // lut16 puts L* 100 and a*/b* 127 at 0xFF00 rather than 0xFFFF
+                let scale = if wide { 65280.0 / 65535.0 } else { 1.0 };
+                let [l, a, b] = xyz_to_lab(xyz);
+                [l / 100.0 * scale, (a + 128.0) / 255.0 * scale, (b + 128.0) / 255.0 * scale]
+            }
+            Pcs::Xyz => xyz.map(|v| v * 32768.0 / 65535.0),
+        }
+    }
+
+    fn decode(self, values: &[f32], wide: bool) -> [f32; 3] {
+        let v = [0, 1, 2].map(|i| values.get(i).copied().unwrap_or(0.0));
+        match self {
+            Pcs::Lab => {
+                let scale = if wide { 65535.0 / 65280.0 } else { 1.0 };
+                lab_to_xyz([v[0] * scale * 100.0, v[1] * scale * 255.0 - 128.0, v[2] * scale * 255.0 - 128.0])
+            }
+            Pcs::Xyz => v.map(|v| v * 65535.0 / 32768.0),
+        }
+    }
+}
+
+/// Linear interpolation in a 1D table over 0..=1
+fn curve(table: &[f32], x: f32) -> f32 {
+    let position = x.clamp(0.0, 1.0) * (table.len() - 1) as f32;
+    let low = (position.floor() as usize).min(table.len() - 2);
+    let t = position - low as f32;
+    table[low] + (table[low + 1] - table[low]) * t
+}
+
+fn parse_lut_table(tag: &[u8], name: &str) -> Result<LutTable, String> {
+    let wide = match tag.get(0..4) {
+        Some(b"mft2") => true,
+        Some(b"mft1") => false,
+        Some(b"mAB ") | Some(b"mBA ") => return Err(format!("{} is a v4 table, which is not supported", name)),
+        _ => return Err(format!("{} has an unsupported table type", name)),
+    };
+    let truncated = || format!("{} is truncated", name);
+    let inputs = *tag.get(8).ok_or_else(truncated)? as usize;
+    let outputs = *tag.get(9).ok_or_else(truncated)? as usize;
+    let grid = *tag.get(10).ok_or_else(truncated)? as usize;
+    if !(1..=8).contains(&inputs) || !(1..=8).contains(&outputs) || grid < 2 {
+        return Err(format!("{} has invalid dimensions", name));
+    }
+
+    let mut matrix = [[0.0; 3]; 3];
+    for (i, value) in matrix.iter_mut().flatten().enumerate() {
+        *value = read_s15_fixed16(tag, 12 + i * 4).ok_or_else(truncated)?;
+    }
+    let (input_entries, output_entries, mut offset) = if wide {
+        (read_u16(tag, 48).ok_or_else(truncated)? as usize, read_u16(tag, 50).ok_or_else(truncated)? as usize, 52)
+    } else {
+        (256, 256, 48)
+    };
+    if input_entries < 2 || output_entries < 2 {
+        return Err(format!("{} has invalid curves", name));
+    }
+    let clut_entries = grid.checked_pow(inputs as u32)
+        .and_then(|points| points.checked_mul(outputs))
+        .filter(|&entries| entries <= MAX_CLUT_ENTRIES)
+        .ok_or(format!("{} is too large", name))?;
+
+    let mut read_values = |count: usize| -> Result<Vec<f32>, String> {
+        let width = if wide { 2 } else { 1 };
+        let bytes = tag.get(offset..offset + count * width).ok_or_else(truncated)?;
+        offset += count * width;
+        Ok(if wide {
+            bytes.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]]) as f32 / 65535.0).collect()
+        } else {
+            bytes.iter().map(|&byte| byte as f32 / 255.0).collect()
+        })
+    };
+    let input_curves = (0..inputs).map(|_| read_values(input_entries)).collect::<Result<Vec<_>, _>>()?;
+    let clut = read_values(clut_entries)?;
+    let output_curves = (0..outputs).map(|_| read_values(output_entries)).collect::<Result<Vec<_>, _>>()?;
+
+    Ok(LutTable { inputs, outputs, grid, matrix, input_curves, clut, output_curves, wide })
+}
+
+impl LutTable {
+    fn eval(&self, input: &[f32], xyz_input: bool) -> Vec<f32> {
+        let mut values: Vec<f32> = input.iter().take(self.inputs).copied().collect();
+        values.resize(self.inputs, 0.0);
+        if xyz_input && self.inputs == 3 {
+            values = apply(&self.matrix, [values[0], values[1], values[2]]).to_vec();
+        }
+        for (value, table) in values.iter_mut().zip(&self.input_curves) {
+            *value = curve(table, *value);
+        }
+        let mut out = self.clut_lookup(&values);
+        for (value, table) in out.iter_mut().zip(&self.output_curves) {
+            *value = curve(table, *value);
+        }
+        out
+    }
+
+    /// Multilinear interpolation in the grid; the first input varies slowest
+    fn clut_lookup(&self, input: &[f32]) -> Vec<f32> {
+        let max = (self.grid - 1) as f32;
+        let mut cells = Vec::with_capacity(self.inputs);
+        let mut stride = self.outputs;
+        for &x in input.iter().rev() {
+            let position = x.clamp(0.0, 1.0) * max;
+            let index = (position.floor() as usize).min(self.grid - 2);
+            cells.push((index, position - index as f32, stride));
+            stride *= self.grid;
+        }
+
+        let mut out = vec![0.0; self.outputs];
+        for corner in 0..(1usize << self.inputs) {
+            let mut weight = 1.0;
+            let mut offset = 0;
+            for (bit, &(index, t, stride)) in cells.iter().enumerate() {
+                if corner & (1 << bit) != 0 {
+                    weight *= t;
+                    offset += (index + 1) * stride;
+                } else {
+                    weight *= 1.0 - t;
+                    offset += index * stride;
+                }
+            }
+            if weight == 0.0 {
+                continue;
+            }
+            for (value, entry) in out.iter_mut().zip(&self.clut[offset..offset + self.outputs]) {
+                *value += weight * entry;
+            }
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ProofTransform {
+    /// Linear sRGB to and from the linear device RGB of a matrix profile
+    Matrix { to_device: [[f32; 3]; 3], from_device: [[f32; 3]; 3] },
+    Tables { pcs: Pcs, to_device: LutTable, from_device: LutTable, gamut: Option<LutTable> },
+}
+
+#[derive(Debug, Clone)]
+pub struct ProofProfile {
+    transform: ProofTransform,
+    xyz_to_srgb: [[f32; 3]; 3],
+}
+
+/// 3D table of proofed colors, red fastest like `Lut3d`: sRGB-encoded RGB, and 1.0 in the
+/// fourth channel where the color is out of gamut
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProofTable {
+    pub size: usize,
+    pub table: Vec<[f32; 4]>,
+}
+
+impl ProofTable {
+    pub fn to_rgba16f_bytes(&self) -> Vec<u8> {
+        self.table.iter()
+            .flatten()
+            .flat_map(|&value| f32_to_f16(value).to_le_bytes())
+            .collect()
+    }
+}
+
+pub fn parse_proof_profile(data: &[u8]) -> Result<ProofProfile, String> {
+    if data.get(36..40) != Some(&b"acsp"[..]) {
+        return Err("not an ICC profile".to_string());
+    }
+    let xyz_to_srgb = invert(&SRGB_TO_XYZ_D50).ok_or("sRGB matrix is singular")?;
+    let table = |signatures: &[&[u8; 4]]| -> Result<Option<LutTable>, String> {
+        for signature in signatures {
+            if let Some(tag) = find_tag(data, signature) {
+                return parse_lut_table(tag, &String::from_utf8_lossy(*signature)).map(Some);
+            }
+        }
+        Ok(None)
+    };
+
+    let transform = match (table(&[b"B2A1", b"B2A0"])?, table(&[b"A2B1", b"A2B0"])?) {
+        (Some(to_device), Some(from_device)) => {
+            let pcs = match data.get(20..24) {
+                Some(b"Lab ") => Pcs::Lab,
+                Some(b"XYZ ") => Pcs::Xyz,
+                _ => return Err("unknown profile connection space".to_string()),
+            };
+            if to_device.inputs != 3 || from_device.outputs != 3 || to_device.outputs != from_device.inputs {
+                return Err("B2A and A2B tables do not match".to_string());
+            }
+            let gamut = table(&[b"gamt"])?.filter(|gamut| gamut.inputs == 3);
+            ProofTransform::Tables { pcs, to_device, from_device, gamut }
+        }
+        _ if data.get(16..20) == Some(&b"RGB "[..]) => {
+            let (r, g, b) = (read_xyz(data, b"rXYZ")?, read_xyz(data, b"gXYZ")?, read_xyz(data, b"bXYZ")?);
+            let to_xyz = [[r[0], g[0], b[0]], [r[1], g[1], b[1]], [r[2], g[2], b[2]]];
+            let to_device = multiply(&invert(&to_xyz).ok_or("colorant matrix is singular")?, &SRGB_TO_XYZ_D50);
+            let from_device = invert(&to_device).ok_or("colorant matrix is singular")?;
+            ProofTransform::Matrix { to_device, from_device }
+        }
+        _ => return Err("no B2A/A2B tables to proof with".to_string()),
+    };
+    Ok(ProofProfile { transform, xyz_to_srgb })
+}
+
+impl ProofProfile {
+    /// Linear sRGB color as reproduced through the profile, and whether it is out of gamut
+    pub fn proof(&self, rgb: [f32; 3]) -> ([f32; 3], bool) {
+        match &self.transform {
+            ProofTransform::Matrix { to_device, from_device } => {
+                let device = apply(to_device, rgb);
+                let range = -MATRIX_GAMUT_EPSILON..=1.0 + MATRIX_GAMUT_EPSILON;
+                let out_of_gamut = device.iter().any(|value| !range.contains(value));
+                (apply(from_device, device.map(|value| value.clamp(0.0, 1.0))), out_of_gamut)
+            }
+            ProofTransform::Tables { pcs, to_device, from_device, gamut } => {
+                let xyz = apply(&SRGB_TO_XYZ_D50, rgb);
+                let xyz_input = *pcs == Pcs::Xyz;
+                let device = to_device.eval(&pcs.encode(xyz, to_device.wide), xyz_input);
+                let proofed = pcs.decode(&from_device.eval(&device, false), from_device.wide);
+                let out_of_gamut = match gamut {
+                    Some(gamut) => gamut.eval(&pcs.encode(xyz, gamut.wide), xyz_input)[0] > 0.5,
+                    None => delta_e(xyz_to_lab(xyz), xyz_to_lab(proofed)) > ROUND_TRIP_DELTA_E,
+                };
+                (apply(&self.xyz_to_srgb, proofed), out_of_gamut)
+            }
+        }
+    }
+
+    pub fn build_table(&self, size: usize) -> ProofTable {
+        let step = |i: usize| i as f32 / (size - 1) as f32;
+        let mut table = Vec::with_capacity(size * size * size);
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    let rgb = [step(r), step(g), step(b)].map(srgb_to_linear);
+                    let (proofed, out_of_gamut) = self.proof(rgb);
+                    let [pr, pg, pb] = proofed.map(|c| linear_to_srgb(c.clamp(0.0, 1.0)));
+                    table.push([pr, pg, pb, if out_of_gamut { 1.0 } else { 0.0 }]);
+                }
+            }
+        }
+        ProofTable { size, table }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// lut16 tag passing three channels through unchanged
+    fn identity_lut16() -> Vec<u8> {
+        let mut tag = b"mft2\0\0\0\0".to_vec();
+        tag.extend([3, 3, 2, 0]);
+        for i in 0..9 {
+            let value: i32 = if i % 4 == 0 { 0x10000 } else { 0 };
+            tag.extend(value.to_be_bytes());
+        }
+        tag.extend(2u16.to_be_bytes());
+        tag.extend(2u16.to_be_bytes());
+        let ramp = [0u16, 0xFFFF];
+        (0..3).for_each(|_| ramp.iter().for_each(|v| tag.extend(v.to_be_bytes())));
+        for index in 0..8usize {
+            // First input varies slowest
+            for channel in 0..3 {
+                let on = index & (4 >> channel) != 0;
+                tag.extend(if on { 0xFFFFu16 } else { 0 }.to_be_bytes());
+            }
+        }
+        (0..3).for_each(|_| ramp.iter().for_each(|v| tag.extend(v.to_be_bytes())));
+        tag
+    }
+
+    /// Lab output profile whose device values are the PCS values themselves
+    fn passthrough_profile() -> Vec<u8> {
+        let tags = [(b"B2A1", identity_lut16()), (b"A2B1", identity_lut16())];
+        let mut data = vec![0u8; 128];
+        data[16..20].copy_from_slice(b"RGB ");
+        data[20..24].copy_from_slice(b"Lab ");
+        data[36..40].copy_from_slice(b"acsp");
+        data.extend((tags.len() as u32).to_be_bytes());
+        let mut offset = 132 + tags.len() * 12;
+        let mut body: Vec<u8> = Vec::new();
+        for (signature, tag) in &tags {
+            data.extend(*signature);
+            data.extend((offset as u32).to_be_bytes());
+            data.extend((tag.len() as u32).to_be_bytes());
+            offset += tag.len();
+            body.extend(tag);
+        }
+        data.extend(body);
+        data
+    }
+
+    #[test]
+    fn test_lab_round_trip() {
+        for xyz in [[0.2, 0.3, 0.1], D50, [0.05, 0.02, 0.4]] {
+            let back = lab_to_xyz(xyz_to_lab(xyz));
+            assert!((0..3).all(|i| (back[i] - xyz[i]).abs() < 1e-4), "{:?} -> {:?}", xyz, back);
+            let decoded = Pcs::Lab.decode(&Pcs::Lab.encode(xyz, true), true);
+            assert!((0..3).all(|i| (decoded[i] - xyz[i]).abs() < 1e-4));
+        }
+    }
+
+    #[test]
+    fn test_passthrough_profile_proofs_to_itself() {
+        let profile = parse_proof_profile(&passthrough_profile()).unwrap();
+        let (proofed, out_of_gamut) = profile.proof([0.2, 0.5, 0.1]);
+        assert!(!out_of_gamut);
+        assert!((0..3).all(|i| (proofed[i] - [0.2, 0.5, 0.1][i]).abs() < 1e-3), "{:?}", proofed);
+
+        let table = profile.build_table(3);
+        assert_eq!(table.table.len(), 27);
+        assert_eq!(table.to_rgba16f_bytes().len(), 27 * 8);
+        assert!(parse_proof_profile(&[0u8; 64]).is_err());
+    }
+}
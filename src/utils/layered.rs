@@ -0,0 +1,528 @@
+/// Flattened previews of Photoshop PSD/PSB and GIMP XCF files
+///
+/// Design assets are often kept in the same folder as their exports, and used to show up
+/// there as load errors. A PSD stores a flattened copy of the document after its layers,
+/// which is what is shown here; a file saved with "Maximize Compatibility" off has only a
+/// blank one. An XCF has no such copy, so its visible layers are drawn over each other at
+/// their offsets and opacities. Blend modes, layer masks and layer effects are not applied,
+/// so a document that relies on them looks different here than in its editor.
+use image::RgbaImage;
+use crate::utils::color::encode_srgb;
+
+pub const LAYERED_EXTENSIONS: [&str; 3] = ["psd", "psb", "xcf"];
+
+/// Longest side of a document that is decoded, Photoshop's own limit for PSD files
+const MAX_SIDE: u32 = 30000;
+
+// XCF property types
+const PROP_END: u32 = 0;
+const PROP_COLORMAP: u32 = 1;
+const PROP_OPACITY: u32 = 6;
+const PROP_VISIBLE: u32 = 8;
+const PROP_OFFSETS: u32 = 15;
+const PROP_COMPRESSION: u32 = 17;
+const PROP_GROUP_ITEM: u32 = 29;
+const PROP_ITEM_PATH: u32 = 30;
+const PROP_FLOAT_OPACITY: u32 = 33;
+
+const XCF_TILE_SIDE: u32 = 64;
+
+pub fn is_psd(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"8BPS")
+}
+
+pub fn is_xcf(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"gimp xcf ")
+}
+
+pub fn is_layered(bytes: &[u8]) -> bool {
+    is_psd(bytes) || is_xcf(bytes)
+}
+
+/// Width and height from the header, without decoding anything
+pub fn dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if is_psd(bytes) {
+        let mut reader = Reader { bytes, pos: 14 };
+        let height = reader.u32().ok()?;
+        return Some((reader.u32().ok()?, height));
+    }
+    if is_xcf(bytes) {
+        let mut reader = Reader { bytes, pos: 14 };
+        return Some((reader.u32().ok()?, reader.u32().ok()?));
+    }
+    None
+}
+
+/// Flattened image of a PSD, PSB or XCF file
+pub fn decode(bytes: &[u8]) -> Result<RgbaImage, String> {
+    if is_psd(bytes) {
+        decode_psd(bytes)
+    } else if is_xcf(bytes) {
+        decode_xcf(bytes)
+    } else {
+        Err("Not a PSD or XCF file".to_string())
+    }
+}
+
+/// Big-endian reads that fail at the end of the data
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| "File is truncated".to_string())?;
+        let data = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(data)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// A length or file offset, 8 bytes wide in PSB and recent XCF files and 4 otherwise
+    fn offset(&mut self, wide: bool) -> Result<usize, String> {
+        let value = if wide { self.u64()? } else { self.u32()? as u64 };
+        usize::try_from(value).map_err(|_| "Offset out of range".to_string())
+    }
+}
+
+fn check_size(width: u32, height: u32) -> Result<(), String> {
+    if width == 0 || height == 0 || width > MAX_SIDE || height > MAX_SIDE {
+        return Err(format!("Unsupported image size {}x{}", width, height));
+    }
+    Ok(())
+}
+
+/// Appends `len` bytes of PackBits-compressed `data`, padding or cutting a damaged row to length
+fn unpack_bits(data: &[u8], len: usize, out: &mut Vec<u8>) {
+    let start = out.len();
+    let mut pos = 0;
+    while out.len() - start < len && pos < data.len() {
+        let header = data[pos] as i8;
+        pos += 1;
+        if header >= 0 {
+            let end = (pos + header as usize + 1).min(data.len());
+            out.extend_from_slice(&data[pos..end]);
+            pos = end;
+        } else if header != -128 {
+            if let Some(&value) = data.get(pos) {
+                out.resize(out.len() + 1 + header.unsigned_abs() as usize, value);
+            }
+            pos += 1;
+        }
+    }
+    out.resize(start + len, 0);
+}
+
+/// 8-bit samples of a PSD channel stored `depth` bits per sample
+fn psd_samples(plane: &[u8], width: usize, depth: u16) -> Vec<u8> {
+    let row_len = (width * depth as usize).div_ceil(8);
+    plane.chunks(row_len)
+        .flat_map(|row| (0..width).map(move |x| match depth {
+            // Bitmap mode: a set bit is black
+            1 => if (row[x / 8] >> (7 - x % 8)) & 1 == 1 { 0 } else { 255 },
+            16 => row[x * 2],
+            // 32-bit documents are linear
+            32 => encode_srgb(f32::from_be_bytes(row[x * 4..x * 4 + 4].try_into().unwrap())),
+            _ => row[x],
+        }))
+        .collect()
+}
+
+fn decode_psd(bytes: &[u8]) -> Result<RgbaImage, String> {
+    let mut reader = Reader { bytes, pos: 4 };
+    let large = match reader.u16()? {
+        1 => false,
+        2 => true,
+        version => return Err(format!("Unknown PSD version {}", version)),
+    };
+    reader.take(6)?;
+    let channels = reader.u16()? as usize;
+    let height = reader.u32()?;
+    let width = reader.u32()?;
+    let depth = reader.u16()?;
+    let mode = reader.u16()?;
+    check_size(width, height)?;
+    if !matches!(depth, 1 | 8 | 16 | 32) {
+        return Err(format!("Unsupported PSD bit depth {}", depth));
+    }
+
+    let len = reader.u32()? as usize;
+    let color_data = reader.take(len)?;
+    let len = reader.u32()? as usize;
+    reader.take(len)?;
+    let len = reader.offset(large)?;
+    let layers = reader.take(len)?;
+    // A negative layer count means the first extra channel is the flattened image's alpha
+    let mut layer_info = Reader { bytes: layers, pos: if large { 8 } else { 4 } };
+    let has_alpha = layer_info.u16().is_ok_and(|count| (count as i16) < 0);
+
+    // Grayscale, bitmap and duotone documents keep the gray levels in their first channel
+    let (colors, with_alpha) = match mode {
+        0 | 1 | 8 => (1, has_alpha),
+        2 => (1, false),
+        3 => (3, has_alpha),
+        4 => (4, has_alpha),
+        _ => return Err(format!("Unsupported PSD color mode {}", mode)),
+    };
+    let used = (colors + with_alpha as usize).min(channels);
+    if used < colors {
+        return Err(format!("PSD has {} channels, {} needed", channels, colors));
+    }
+
+    let (width, height) = (width as usize, height as usize);
+    let row_len = (width * depth as usize).div_ceil(8);
+    let planes: Vec<Vec<u8>> = match reader.u16()? {
+        0 => (0..used)
+            .map(|_| reader.take(row_len * height).map(|plane| psd_samples(plane, width, depth)))
+            .collect::<Result<_, _>>()?,
+        1 => {
+            let row_lengths = (0..channels * height)
+                .map(|_| if large { reader.u32() } else { reader.u16().map(u32::from) })
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut planes = Vec::with_capacity(used);
+            for lengths in row_lengths.chunks(height).take(used) {
+                let mut plane = Vec::with_capacity(row_len * height);
+                for &len in lengths {
+                    unpack_bits(reader.take(len as usize)?, row_len, &mut plane);
+                }
+                planes.push(psd_samples(&plane, width, depth));
+            }
+            planes
+        }
+        compression => return Err(format!("Unsupported PSD compression {}", compression)),
+    };
+
+    let palette = |index: u8, channel: usize| color_data.get(channel * 256 + index as usize).copied().unwrap_or(0);
+    let pixels = (0..width * height).flat_map(|i| {
+        let alpha = if with_alpha && planes.len() > colors { planes[colors][i] } else { 255 };
+        match mode {
+            2 => [palette(planes[0][i], 0), palette(planes[0][i], 1), palette(planes[0][i], 2), 255],
+            3 => [planes[0][i], planes[1][i], planes[2][i], alpha],
+            // CMYK samples are stored inverted, 255 for no ink
+            4 => {
+                let black = planes[3][i] as u16;
+                let ink = |channel: usize| (planes[channel][i] as u16 * black / 255) as u8;
+                [ink(0), ink(1), ink(2), alpha]
+            }
+            _ => [planes[0][i], planes[0][i], planes[0][i], alpha],
+        }
+    });
+    RgbaImage::from_raw(width as u32, height as u32, pixels.collect())
+        .ok_or_else(|| "PSD pixel data does not match its size".to_string())
+}
+
+/// Properties of an XCF image or layer, up to the end marker
+fn xcf_properties<'a>(reader: &mut Reader<'a>) -> Result<Vec<(u32, &'a [u8])>, String> {
+    let mut properties = Vec::new();
+    loop {
+        let kind = reader.u32()?;
+        let len = reader.u32()? as usize;
+        if kind == PROP_END {
+            return Ok(properties);
+        }
+        properties.push((kind, reader.take(len)?));
+    }
+}
+
+/// Offsets up to the terminating zero
+fn xcf_pointers(reader: &mut Reader, wide: bool) -> Result<Vec<usize>, String> {
+    let mut pointers = Vec::new();
+    loop {
+        match reader.offset(wide)? {
+            0 => return Ok(pointers),
+            pointer => pointers.push(pointer),
+        }
+    }
+}
+
+/// Bytes per channel and whether values are linear, for the integer precisions
+fn xcf_precision(version: u32, precision: u32) -> Result<(usize, bool), String> {
+    match (version, precision) {
+        (0..=3, _) | (4..=5, 0) | (6.., 150) => Ok((1, false)),
+        (4..=5, 1) | (6.., 250) => Ok((2, false)),
+        (6.., 100) => Ok((1, true)),
+        (6.., 200) => Ok((2, true)),
+        _ => Err(format!("Unsupported XCF precision {}", precision)),
+    }
+}
+
+/// Pixels of one XCF tile, decompressing GIMP's per-channel RLE
+fn xcf_tile(reader: &mut Reader, compression: u8, pixels: usize, bpp: usize) -> Result<Vec<u8>, String> {
+    if compression == 0 {
+        return Ok(reader.take(pixels * bpp)?.to_vec());
+    }
+    let mut tile = vec![0; pixels * bpp];
+    for channel in 0..bpp {
+        let mut pixel = 0;
+        while pixel < pixels {
+            let header = reader.u8()?;
+            let (count, literal) = match header {
+                0..=126 => (header as usize + 1, false),
+                127 => (reader.u16()? as usize, false),
+                128 => (reader.u16()? as usize, true),
+                _ => (256 - header as usize, true),
+            };
+            if pixel + count > pixels {
+                return Err("Corrupt XCF tile".to_string());
+            }
+            let run = reader.take(if literal { count } else { 1 })?;
+            for i in 0..count {
+                tile[(pixel + i) * bpp + channel] = run[if literal { i } else { 0 }];
+            }
+            pixel += count;
+        }
+    }
+    Ok(tile)
+}
+
+struct XcfLayer {
+    offset: usize,
+    /// Layer type: RGB, gray or indexed, each with or without alpha
+    kind: u32,
+    opacity: f32,
+    position: (i64, i64),
+}
+
+fn decode_xcf(bytes: &[u8]) -> Result<RgbaImage, String> {
+    let version = match bytes.get(9..13) {
+        Some(b"file") => 0,
+        Some([b'v', digits @ ..]) => std::str::from_utf8(digits).ok()
+            .and_then(|digits| digits.parse().ok())
+            .ok_or_else(|| "Unknown XCF version".to_string())?,
+        _ => return Err("Unknown XCF version".to_string()),
+    };
+    let wide = version >= 11;
+    let mut reader = Reader { bytes, pos: 14 };
+    let width = reader.u32()?;
+    let height = reader.u32()?;
+    reader.u32()?;
+    check_size(width, height)?;
+    let precision = if version >= 4 { reader.u32()? } else { 0 };
+    let (channel_bytes, linear) = xcf_precision(version, precision)?;
+
+    let mut colormap: &[u8] = &[];
+    let mut compression = 0;
+    for (kind, data) in xcf_properties(&mut reader)? {
+        match kind {
+            PROP_COLORMAP => colormap = data.get(4..).unwrap_or(&[]),
+            PROP_COMPRESSION => compression = data.first().copied().unwrap_or(0),
+            _ => {}
+        }
+    }
+    if compression > 1 {
+        return Err(format!("Unsupported XCF compression {}", compression));
+    }
+
+    // Layers are listed top first, each group before the layers in it. Group layers are
+    // left out; their visibility and opacity are carried down to their layers.
+    let mut layers = Vec::new();
+    let mut groups: Vec<(bool, f32)> = Vec::new();
+    for pointer in xcf_pointers(&mut reader, wide)? {
+        let mut layer = Reader { bytes, pos: pointer };
+        layer.take(8)?;
+        let kind = layer.u32()?;
+        let name_len = layer.u32()? as usize;
+        layer.take(name_len)?;
+
+        let (mut visible, mut opacity, mut position, mut is_group, mut depth) = (true, 1.0, (0, 0), false, 0);
+        for (property, data) in xcf_properties(&mut layer)? {
+            let mut data = Reader { bytes: data, pos: 0 };
+            match property {
+                PROP_VISIBLE => visible = data.u32()? != 0,
+                PROP_OPACITY => opacity = data.u32()?.min(255) as f32 / 255.0,
+                PROP_FLOAT_OPACITY => opacity = f32::from_bits(data.u32()?).clamp(0.0, 1.0),
+                PROP_OFFSETS => position = (data.u32()? as i32 as i64, data.u32()? as i32 as i64),
+                PROP_GROUP_ITEM => is_group = true,
+                PROP_ITEM_PATH => depth = (data.bytes.len() / 4).saturating_sub(1),
+                _ => {}
+            }
+        }
+
+        groups.truncate(depth);
+        let shown = visible && groups.iter().all(|&(visible, _)| visible);
+        let total_opacity = groups.iter().fold(opacity, |total, &(_, group)| total * group);
+        if is_group {
+            groups.push((visible, opacity));
+        } else if shown && total_opacity > 0.0 {
+            layers.push(XcfLayer { offset: layer.offset(wide)?, kind, opacity: total_opacity, position });
+        }
+    }
+
+    let mut canvas = vec![[0.0f32; 4]; width as usize * height as usize];
+    for layer in layers.iter().rev() {
+        draw_xcf_layer(bytes, wide, layer, compression, channel_bytes, linear, colormap, (width, height), &mut canvas)?;
+    }
+    let pixels = canvas.iter()
+        .flat_map(|pixel| pixel.map(|value| (value * 255.0).round() as u8))
+        .collect();
+    RgbaImage::from_raw(width, height, pixels).ok_or_else(|| "XCF canvas does not match its size".to_string())
+}
+
+/// Draws a layer's pixels over the canvas, non-premultiplied RGBA from 0 to 1
+#[allow(clippy::too_many_arguments)]
+fn draw_xcf_layer(
+    bytes: &[u8],
+    wide: bool,
+    layer: &XcfLayer,
+    compression: u8,
+    channel_bytes: usize,
+    linear: bool,
+    colormap: &[u8],
+    (canvas_width, canvas_height): (u32, u32),
+    canvas: &mut [[f32; 4]],
+) -> Result<(), String> {
+    let mut hierarchy = Reader { bytes, pos: layer.offset };
+    let (width, height) = (hierarchy.u32()?, hierarchy.u32()?);
+    check_size(width, height)?;
+    let bpp = hierarchy.u32()? as usize;
+    let channels = [3usize, 1, 1].get(layer.kind as usize / 2).ok_or_else(|| format!("Unknown XCF layer type {}", layer.kind))?
+        + (layer.kind % 2) as usize;
+    if bpp != channels * channel_bytes {
+        return Err(format!("XCF layer has {} bytes per pixel, {} expected", bpp, channels * channel_bytes));
+    }
+    let level = *xcf_pointers(&mut hierarchy, wide)?.first().ok_or_else(|| "XCF layer has no pixels".to_string())?;
+    let mut level = Reader { bytes, pos: level };
+    level.take(8)?;
+    let tiles = xcf_pointers(&mut level, wide)?;
+
+    let encode = |value: u8| if linear { encode_srgb(value as f32 / 255.0) } else { value };
+    let tiles_across = width.div_ceil(XCF_TILE_SIDE);
+    for (index, &pointer) in tiles.iter().enumerate().take((tiles_across * height.div_ceil(XCF_TILE_SIDE)) as usize) {
+        let (tile_x, tile_y) = (index as u32 % tiles_across * XCF_TILE_SIDE, index as u32 / tiles_across * XCF_TILE_SIDE);
+        let tile_width = XCF_TILE_SIDE.min(width - tile_x);
+        let tile_height = XCF_TILE_SIDE.min(height - tile_y);
+        let tile = xcf_tile(&mut Reader { bytes, pos: pointer }, compression, (tile_width * tile_height) as usize, bpp)?;
+
+        for (i, pixel) in tile.chunks_exact(bpp).enumerate() {
+            let x = layer.position.0 + (tile_x + i as u32 % tile_width) as i64;
+            let y = layer.position.1 + (tile_y + i as u32 / tile_width) as i64;
+            if x < 0 || y < 0 || x >= canvas_width as i64 || y >= canvas_height as i64 {
+                continue;
+            }
+            // High byte of each channel
+            let value = |channel: usize| pixel[channel * channel_bytes];
+            let color = match layer.kind / 2 {
+                0 => [encode(value(0)), encode(value(1)), encode(value(2))],
+                1 => [encode(value(0)); 3],
+                _ => {
+                    let entry = value(0) as usize * 3;
+                    match colormap.get(entry..entry + 3) {
+                        Some(rgb) => [rgb[0], rgb[1], rgb[2]],
+                        None => [0, 0, 0],
+                    }
+                }
+            };
+            let alpha = if layer.kind % 2 == 1 { value(channels - 1) as f32 / 255.0 } else { 1.0 } * layer.opacity;
+
+            let below = &mut canvas[y as usize * canvas_width as usize + x as usize];
+            let below_alpha = below[3];
+            let out_alpha = alpha + below_alpha * (1.0 - alpha);
+            if out_alpha > 0.0 {
+                for (channel, &value) in below[..3].iter_mut().zip(&color) {
+                    *channel = (value as f32 / 255.0 * alpha + *channel * below_alpha * (1.0 - alpha)) / out_alpha;
+                }
+            }
+            below[3] = out_alpha;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unpack_bits() {
+        let mut out = Vec::new();
+        // Two literal bytes, then 5 repeated three times
+        unpack_bits(&[1, 7, 8, 0xFE, 5], 5, &mut out);
+        assert_eq!(out, [7, 8, 5, 5, 5]);
+        // A short row is padded
+        unpack_bits(&[0, 9], 3, &mut out);
+        assert_eq!(&out[5..], [9, 0, 0]);
+    }
+
+    #[test]
+    fn test_decode_psd() {
+        // 2x1 RGB with alpha, RLE compressed
+        let mut psd = b"8BPS".to_vec();
+        psd.extend(1u16.to_be_bytes());
+        psd.extend([0; 6]);
+        psd.extend(4u16.to_be_bytes());
+        psd.extend(1u32.to_be_bytes());
+        psd.extend(2u32.to_be_bytes());
+        psd.extend(8u16.to_be_bytes());
+        psd.extend(3u16.to_be_bytes());
+        psd.extend(0u32.to_be_bytes());
+        psd.extend(0u32.to_be_bytes());
+        // Layer section with a layer count of -1, so the fourth channel is alpha
+        psd.extend(6u32.to_be_bytes());
+        psd.extend(2u32.to_be_bytes());
+        psd.extend((-1i16).to_be_bytes());
+        psd.extend(1u16.to_be_bytes());
+        psd.extend([2u16, 3, 3, 2].iter().flat_map(|len| len.to_be_bytes()));
+        psd.extend([0xFF, 200, 1, 10, 20, 1, 30, 255, 0xFF, 128]);
+
+        let image = decode(&psd).unwrap();
+        assert_eq!(image.dimensions(), (2, 1));
+        assert_eq!(dimensions(&psd), Some((2, 1)));
+        assert_eq!(image.get_pixel(0, 0).0, [200, 10, 30, 128]);
+        assert_eq!(image.get_pixel(1, 0).0, [200, 20, 255, 128]);
+    }
+
+    #[test]
+    fn test_decode_xcf() {
+        // 2x1 RGB image, RLE compressed: an opaque red layer under a half-transparent blue
+        // layer moved one pixel to the right
+        let mut xcf = b"gimp xcf v003\0".to_vec();
+        xcf.extend([2u32, 1, 0].iter().flat_map(|value| value.to_be_bytes()));
+        xcf.extend([PROP_COMPRESSION, 1].iter().flat_map(|value| value.to_be_bytes()));
+        xcf.push(1);
+        xcf.extend([PROP_END, 0].iter().flat_map(|value| value.to_be_bytes()));
+        let layer_pointers = xcf.len();
+        xcf.extend([0u32; 4].iter().flat_map(|value| value.to_be_bytes()));
+
+        let add_layer = |xcf: &mut Vec<u8>, width: u32, offset: u32, opacity: u32, rle: &[u8]| {
+            let start = xcf.len() as u32;
+            xcf.extend([width, 1, 0, 1].iter().flat_map(|value| value.to_be_bytes()));
+            xcf.push(0);
+            xcf.extend([PROP_OPACITY, 4, opacity, PROP_OFFSETS, 8, offset, 0, PROP_END, 0]
+                .iter().flat_map(|value| value.to_be_bytes()));
+            let hierarchy = xcf.len() as u32 + 8;
+            xcf.extend([hierarchy, 0].iter().flat_map(|value| value.to_be_bytes()));
+            let level = hierarchy + 20;
+            xcf.extend([width, 1, 3, level, 0].iter().flat_map(|value| value.to_be_bytes()));
+            let tile = level + 16;
+            xcf.extend([width, 1, tile, 0].iter().flat_map(|value| value.to_be_bytes()));
+            xcf.extend(rle);
+            start
+        };
+        let blue = add_layer(&mut xcf, 1, 1, 128, &[0, 0, 0, 0, 0, 255]);
+        let red = add_layer(&mut xcf, 2, 0, 255, &[1, 255, 1, 0, 1, 0]);
+        xcf[layer_pointers..layer_pointers + 8].copy_from_slice(&[blue.to_be_bytes(), red.to_be_bytes()].concat());
+
+        let image = decode(&xcf).unwrap();
+        assert_eq!(dimensions(&xcf), Some((2, 1)));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(1, 0).0, [127, 0, 128, 255]);
+    }
+}
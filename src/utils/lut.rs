@@ -0,0 +1,173 @@
+/// 3D color lookup tables in the Adobe/Resolve `.cube` format
+///
+/// Only 3D tables are read. Entries are stored red-fastest, which is also the x axis of the
+/// 3D texture the image shader samples.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lut3d {
+    pub title: Option<String>,
+    pub size: usize,
+    pub domain_min: [f32; 3],
+    pub domain_max: [f32; 3],
+    pub table: Vec<[f32; 3]>,
+}
+
+fn parse_floats<const N: usize>(values: &[&str], line_number: usize) -> Result<[f32; N], String> {
+    if values.len() != N {
+        return Err(format!("line {}: expected {} values", line_number, N));
+    }
+    let mut out = [0.0; N];
+    for (slot, value) in out.iter_mut().zip(values) {
+        *slot = value.parse().map_err(|_| format!("line {}: invalid number '{}'", line_number, value))?;
+    }
+    Ok(out)
+}
+
+pub fn parse_cube(text: &str) -> Result<Lut3d, String> {
+    let mut lut = Lut3d {
+        title: None,
+        size: 0,
+        domain_min: [0.0; 3],
+        domain_max: [1.0; 3],
+        table: Vec::new(),
+    };
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let keyword = parts.next().unwrap_or_default();
+        let values: Vec<&str> = parts.collect();
+        match keyword {
+            "TITLE" => lut.title = Some(line["TITLE".len()..].trim().trim_matches('"').to_string()),
+            "LUT_3D_SIZE" => {
+                let size: usize = values.first()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or(format!("line {}: invalid LUT_3D_SIZE", line_number))?;
+                if !(2..=256).contains(&size) {
+                    return Err(format!("LUT_3D_SIZE {} is out of range (2-256)", size));
+                }
+                lut.size = size;
+                lut.table.reserve(size * size * size);
+            }
+            "LUT_1D_SIZE" => return Err("1D LUTs are not supported".to_string()),
+            "DOMAIN_MIN" => lut.domain_min = parse_floats(&values, line_number)?,
+            "DOMAIN_MAX" => lut.domain_max = parse_floats(&values, line_number)?,
+            "LUT_3D_INPUT_RANGE" => {
+                let [min, max] = parse_floats(&values, line_number)?;
+                lut.domain_min = [min; 3];
+                lut.domain_max = [max; 3];
+            }
+            // Other keywords (e.g. LUT_1D_INPUT_RANGE from other tools) do not affect a 3D table
+            keyword if keyword.starts_with(|c: char| c.is_ascii_alphabetic()) => {}
+            _ => {
+                let mut entry = vec![keyword];
+                entry.extend(values);
+                lut.table.push(parse_floats(&entry, line_number)?);
+            }
+        }
+    }
+
+    if lut.size == 0 {
+        return Err("missing LUT_3D_SIZE".to_string());
+    }
+    let expected = lut.size * lut.size * lut.size;
+    if lut.table.len() != expected {
+        return Err(format!("expected {} entries, found {}", expected, lut.table.len()));
+    }
+    if (0..3).any(|i| lut.domain_max[i] <= lut.domain_min[i]) {
+        return Err("DOMAIN_MAX must be greater than DOMAIN_MIN".to_string());
+    }
+    Ok(lut)
+}
+
+/// IEEE half-precision bits, rounding to nearest; subnormals below 2^-24 flush to zero
+pub fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    if exponent == 0xff {
+        return sign | 0x7c00 | if mantissa != 0 { 0x200 } else { 0 };
+    }
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if half_exponent <= 0 {
+        if half_exponent < -10 {
+            return sign;
+        }
+        return sign | ((mantissa | 0x80_0000) >> (14 - half_exponent)) as u16;
+    }
+    let half = sign | ((half_exponent as u16) << 10) | (mantissa >> 13) as u16;
+    // A carry out of the mantissa correctly bumps the exponent
+    if mantissa & 0x1000 != 0 { half + 1 } else { half }
+}
+
+/// IEEE half-precision bits to `f32`
+pub fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = (bits >> 10) & 0x1f;
+    let mantissa = (bits & 0x3ff) as f32;
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f32.powi(exponent as i32 - 15),
+    }
+}
+
+impl Lut3d {
+    /// Table as RGBA16F texels for a `size`³ texture
+    pub fn to_rgba16f_bytes(&self) -> Vec<u8> {
+        self.table.iter()
+            .flat_map(|&[r, g, b]| [r, g, b, 1.0])
+            .flat_map(|value| f32_to_f16(value).to_le_bytes())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cube() {
+        let text = "# identity\nTITLE \"Identity\"\nLUT_3D_SIZE 2\nDOMAIN_MIN 0 0 0\nDOMAIN_MAX 1 1 1\n\n\
+                    0 0 0\n1 0 0\n0 1 0\n1 1 0\n0 0 1\n1 0 1\n0 1 1\n1 1 1\n";
+        let lut = parse_cube(text).unwrap();
+        assert_eq!(lut.title.as_deref(), Some("Identity"));
+        assert_eq!(lut.size, 2);
+        assert_eq!(lut.table[1], [1.0, 0.0, 0.0]);
+        assert_eq!(lut.to_rgba16f_bytes().len(), 8 * 8);
+
+        assert!(parse_cube("LUT_3D_SIZE 2\n0 0 0\n").is_err());
+        assert!(parse_cube("LUT_1D_SIZE 4\n").is_err());
+        assert!(parse_cube("LUT_3D_SIZE 2\n0 0\n").is_err());
+    }
+
+    #[test]
+    fn test_f32_to_f16() {
+        assert_eq!(f32_to_f16(0.0), 0x0000);
+        assert_eq!(f32_to_f16(1.0), 0x3c00);
+        assert_eq!(f32_to_f16(0.5), 0x3800);
+        assert_eq!(f32_to_f16(-2.0), 0xc000);
+        assert_eq!(f32_to_f16(65504.0), 0x7bff);
+        assert_eq!(f32_to_f16(1.0e6), 0x7c00);
+        assert_eq!(f32_to_f16(2.0f32.powi(-24)), 0x0001);
+    }
+
+    #[test]
+    fn test_f16_to_f32() {
+        for value in [0.0, 1.0, 0.5, -2.0, 65504.0, 2.0f32.powi(-24), 0.1875, 1234.0] {
+            assert_eq!(f16_to_f32(f32_to_f16(value)), value);
+        }
+        assert_eq!(f16_to_f32(0x7c00), f32::INFINITY);
+        assert!(f16_to_f32(0x7e00).is_nan());
+    }
+}
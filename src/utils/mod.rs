@@ -1,3 +1,16 @@
+pub mod color;
+pub mod comparison_report;
+pub mod exif_tags;
+pub mod frame_stats;
+pub mod icc;
+pub mod icc_embedded;
+pub mod icc_proof;
+pub mod layered;
+pub mod lut;
 pub mod mem;
+pub mod raw_preview;
 pub mod save;
+pub mod sequence_export;
+pub mod svg;
 pub mod timing;
+pub mod xmp;
@@ -0,0 +1,235 @@
+/// Embedded JPEG previews of camera RAW files
+///
+/// There is no RAW decoder in the viewer; instead RAW files are shown through the full-size
+/// JPEG the camera stores alongside the sensor data, which is what the camera's own screen
+/// shows. Most formats are TIFF-based and keep it in one of their IFDs; Fuji RAF points to
+/// it from its header. The RAW's orientation tag is copied onto the preview, since the
+/// embedded JPEG rarely carries its own.
+use std::io;
+use std::path::Path;
+use crate::utils::exif_tags::Tiff;
+
+pub const RAW_EXTENSIONS: [&str; 9] = ["cr2", "nef", "nrw", "arw", "dng", "pef", "raf", "rw2", "srw"];
+
+const COMPRESSION: u16 = 0x0103;
+const STRIP_OFFSETS: u16 = 0x0111;
+const ORIENTATION: u16 = 0x0112;
+const STRIP_BYTE_COUNTS: u16 = 0x0117;
+const SUB_IFDS: u16 = 0x014A;
+const JPEG_OFFSET: u16 = 0x0201;
+const JPEG_LENGTH: u16 = 0x0202;
+/// Panasonic RW2 keeps its preview in a tag of its own
+const RW2_JPEG_FROM_RAW: u16 = 0x002E;
+/// IFD chains of broken files can loop
+const MAX_IFDS: usize = 32;
+
+pub fn is_raw(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| RAW_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// Whether `data` is a JPEG the image crate can show: baseline or progressive, as opposed to
+/// the lossless JPEG some formats use for the sensor data itself
+fn is_displayable_jpeg(data: &[u8]) -> bool {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return false;
+    }
+    let mut pos = 2;
+    while let (Some(&0xFF), Some(&marker)) = (data.get(pos), data.get(pos + 1)) {
+        match marker {
+            0xC0..=0xC2 => return true,
+            0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF | 0xDA => return false,
+            _ => {}
+        }
+        let Some(length) = data.get(pos + 2..pos + 4) else {
+            return false;
+        };
+        pos += 2 + u16::from_be_bytes([length[0], length[1]]) as usize;
+    }
+    false
+}
+
+/// A SHORT or LONG value stored inline
+fn scalar(tiff: &Tiff, ifd: usize, tag: u16) -> Option<u32> {
+    let entry = tiff.find_entry(ifd, tag)?;
+    match tiff.u16_at(entry + 2)? {
+        3 => tiff.u16_at(entry + 8).map(u32::from),
+        4 => tiff.u32_at(entry + 8),
+        _ => None,
+    }
+}
+
+/// (offset, length) of the JPEG an IFD points to, if any
+fn ifd_jpeg(tiff: &Tiff, ifd: usize) -> Option<(usize, usize)> {
+    if let (Some(offset), Some(length)) = (scalar(tiff, ifd, JPEG_OFFSET), scalar(tiff, ifd, JPEG_LENGTH)) {
+        return Some((offset as usize, length as usize));
+    }
+    if let Some(entry) = tiff.find_entry(ifd, RW2_JPEG_FROM_RAW) {
+        return Some((tiff.u32_at(entry + 8)? as usize, tiff.u32_at(entry + 4)? as usize));
+    }
+    // Old-style JPEG compression in a single strip, as in the first IFD of CR2 files
+    if matches!(scalar(tiff, ifd, COMPRESSION), Some(6 | 7)) {
+        let strips = tiff.find_entry(ifd, STRIP_OFFSETS)?;
+        if tiff.u32_at(strips + 4)? == 1 {
+            return Some((scalar(tiff, ifd, STRIP_OFFSETS)? as usize, scalar(tiff, ifd, STRIP_BYTE_COUNTS)? as usize));
+        }
+    }
+    None
+}
+
+/// Every IFD in the chain starting at `first`, and the SubIFDs they point to
+fn ifds(tiff: &Tiff, first: usize) -> Vec<usize> {
+    let mut found = Vec::new();
+    let mut queue = vec![first];
+    while let Some(ifd) = queue.pop() {
+        if ifd == 0 || found.contains(&ifd) || found.len() >= MAX_IFDS {
+            continue;
+        }
+        let Some(count) = tiff.u16_at(ifd) else {
+            continue;
+        };
+        found.push(ifd);
+        if let Some(next) = tiff.u32_at(ifd + 2 + count as usize * 12) {
+            queue.push(next as usize);
+        }
+        if let Some(entry) = tiff.find_entry(ifd, SUB_IFDS) {
+            let sub_count = tiff.u32_at(entry + 4).unwrap_or(0) as usize;
+            match sub_count {
+                1 => queue.extend(tiff.u32_at(entry + 8).map(|o| o as usize)),
+                n if n > 1 => {
+                    let list = tiff.u32_at(entry + 8).unwrap_or(0) as usize;
+                    queue.extend((0..n.min(MAX_IFDS)).filter_map(|i| tiff.u32_at(list + i * 4)).map(|o| o as usize));
+                }
+                _ => {}
+            }
+        }
+    }
+    found
+}
+
+/// Largest displayable JPEG in a TIFF-based RAW, and the file's orientation
+fn tiff_preview(data: &[u8]) -> Option<(&[u8], u16)> {
+    let little_endian = match data.get(0..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let tiff = Tiff { data, little_endian };
+    // Panasonic RW2 has its own magic number in place of 42
+    if !matches!(tiff.u16_at(2)?, 42 | 0x55) {
+        return None;
+    }
+    let ifd0 = tiff.u32_at(4)? as usize;
+    let orientation = scalar(&tiff, ifd0, ORIENTATION).unwrap_or(1) as u16;
+    // The lossless JPEG holding the sensor data in some formats is ruled out by its SOF marker
+    let preview = ifds(&tiff, ifd0).into_iter()
+        .filter_map(|ifd| ifd_jpeg(&tiff, ifd))
+        .filter_map(|(offset, length)| data.get(offset..offset.checked_add(length)?))
+        .filter(|jpeg| is_displayable_jpeg(jpeg))
+        .max_by_key(|jpeg| jpeg.len())?;
+    Some((preview, orientation))
+}
+
+/// Fuji RAF: a header with the preview's offset and length at fixed positions
+fn raf_preview(data: &[u8]) -> Option<&[u8]> {
+    if !data.starts_with(b"FUJIFILMCCD-RAW") {
+        return None;
+    }
+    let read = |at: usize| Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?) as usize);
+    let (offset, length) = (read(84)?, read(88)?);
+    data.get(offset..offset.checked_add(length)?).filter(|jpeg| is_displayable_jpeg(jpeg))
+}
+
+/// `jpeg` with an EXIF segment holding only `orientation` inserted after its SOI marker,
+/// unless it is upright or already has EXIF of its own
+fn with_orientation(jpeg: &[u8], orientation: u16) -> Vec<u8> {
+    let has_exif = jpeg.get(4..12).is_some_and(|header| &header[2..] == b"Exif\0\0")
+        && jpeg.get(2..4) == Some(&[0xFF, 0xE1]);
+    if !(2..=8).contains(&orientation) || has_exif {
+        return jpeg.to_vec();
+    }
+    let mut tiff = b"II*\0".to_vec();
+    tiff.extend(8u32.to_le_bytes());
+    tiff.extend(1u16.to_le_bytes());
+    tiff.extend(ORIENTATION.to_le_bytes());
+    tiff.extend(3u16.to_le_bytes());
+    tiff.extend(1u32.to_le_bytes());
+    tiff.extend((orientation as u32).to_le_bytes());
+    tiff.extend(0u32.to_le_bytes());
+
+    let mut out = Vec::with_capacity(jpeg.len() + tiff.len() + 10);
+    out.extend(&jpeg[..2]);
+    out.extend([0xFF, 0xE1]);
+    out.extend(((2 + 6 + tiff.len()) as u16).to_be_bytes());
+    out.extend(b"Exif\0\0");
+    out.extend(tiff);
+    out.extend(&jpeg[2..]);
+    out
+}
+
+/// The preview JPEG of a RAW file's contents, ready to decode
+fn extract(data: &[u8]) -> Option<Vec<u8>> {
+    if let Some(jpeg) = raf_preview(data) {
+        return Some(jpeg.to_vec());
+    }
+    let (jpeg, orientation) = tiff_preview(data)?;
+    Some(with_orientation(jpeg, orientation))
+}
+
+/// Reads a RAW file and returns its preview JPEG
+pub fn read(path: &Path) -> io::Result<Vec<u8>> {
+    let data = std::fs::read(crate::file_io::long_path(path))?;
+    extract(&data).ok_or_else(|| io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("No embedded preview found in {}", path.display())
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Smallest baseline JPEG header: SOI, then an SOF0 segment
+    const BASELINE: [u8; 8] = [0xFF, 0xD8, 0xFF, 0xC0, 0x00, 0x02, 0xFF, 0xD9];
+
+    #[test]
+    fn test_tiff_preview_skips_lossless_raw_data() {
+        let lossless = [0xFF, 0xD8, 0xFF, 0xC3, 0x00, 0x02, 0xFF, 0xD9, 0x00, 0x00];
+        // IFD0 at 8 with a single-strip JPEG and orientation 6, then an IFD with lossless data
+        let mut data = b"II*\0".to_vec();
+        data.extend(8u32.to_le_bytes());
+        let entries = |data: &mut Vec<u8>, list: &[(u16, u16, u32)], next: u32| {
+            data.extend((list.len() as u16).to_le_bytes());
+            for &(tag, kind, value) in list {
+                data.extend(tag.to_le_bytes());
+                data.extend(kind.to_le_bytes());
+                data.extend(1u32.to_le_bytes());
+                data.extend(value.to_le_bytes());
+            }
+            data.extend(next.to_le_bytes());
+        };
+        // IFD0 at 8 (4 entries, 54 bytes), IFD1 at 62 (3 entries, 42 bytes), data from 104
+        entries(&mut data, &[(COMPRESSION, 3, 6), (STRIP_OFFSETS, 4, 104), (ORIENTATION, 3, 6), (STRIP_BYTE_COUNTS, 4, 8)], 62);
+        entries(&mut data, &[(COMPRESSION, 3, 6), (STRIP_OFFSETS, 4, 112), (STRIP_BYTE_COUNTS, 4, 10)], 0);
+        data.extend(BASELINE);
+        data.extend(lossless);
+
+        let (jpeg, orientation) = tiff_preview(&data).unwrap();
+        assert_eq!(jpeg, BASELINE);
+        assert_eq!(orientation, 6);
+        assert!(!is_displayable_jpeg(&lossless));
+    }
+
+    #[test]
+    fn test_with_orientation() {
+        let rotated = with_orientation(&BASELINE, 6);
+        assert_eq!(&rotated[..4], &[0xFF, 0xD8, 0xFF, 0xE1]);
+        assert_eq!(&rotated[6..12], b"Exif\0\0");
+        assert!(rotated.ends_with(&BASELINE[2..]));
+        assert!(is_displayable_jpeg(&rotated));
+        // Upright or already tagged previews are left alone
+        assert_eq!(with_orientation(&BASELINE, 1), BASELINE);
+        assert_eq!(with_orientation(&rotated, 3), rotated);
+    }
+}
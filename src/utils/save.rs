@@ -5,19 +5,27 @@ use iced_wgpu::wgpu::{self, util::align_to, Texture};
 use crate::app::DataViewer;
 
 pub(crate) fn extract_gpu_image(app: &mut DataViewer, texture: &Arc<Texture>) -> Vec<u8> {
+    read_texture(&app.device, &app.queue, texture)
+}
+
+/// Copies an RGBA texture created with `COPY_SRC` back to tightly packed rows, blocking until
+/// the GPU is done; also reads back offscreen renders such as annotation burn-in. The tiles
+/// of an oversized image are put back together into the whole image.
+pub(crate) fn read_texture(device: &wgpu::Device, queue: &wgpu::Queue, texture: &Texture) -> Vec<u8> {
     let width = texture.width();
     let height = texture.height();
+    let layers = texture.depth_or_array_layers();
+    let bytes_per_texel = crate::cache::compact_textures::bytes_per_texel(texture);
 
-    let bytes_per_row = align_to(width * 4, wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
-    let buffer = app.device.create_buffer(&wgpu::BufferDescriptor {
+    let bytes_per_row = align_to(width * bytes_per_texel, wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
         label: Some("tmp"),
-        size: (bytes_per_row * height) as u64,
+        size: bytes_per_row as u64 * height as u64 * layers as u64,
         usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
         mapped_at_creation: false,
     });
 
-    let mut encoder = app
-        .device
+    let mut encoder = device
         .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
 
     encoder.copy_texture_to_buffer(
@@ -33,7 +41,7 @@ pub(crate) fn extract_gpu_image(app: &mut DataViewer, texture: &Arc<Texture>) ->
         texture.size(),
     );
 
-    app.queue.submit([encoder.finish()]);
+    queue.submit([encoder.finish()]);
 
     let (sender, receiver) = std::sync::mpsc::channel();
     let buffer_slice = buffer.slice(..);
@@ -42,12 +50,12 @@ pub(crate) fn extract_gpu_image(app: &mut DataViewer, texture: &Arc<Texture>) ->
         sender.send(result).unwrap();
     });
 
-    app.device.poll(wgpu::Maintain::Wait);
+    device.poll(wgpu::Maintain::Wait);
 
     receiver.recv().unwrap().unwrap();
 
     let padded_bytes_per_row = bytes_per_row as usize;
-    let unpadded_bytes_per_row = (width * 4) as usize;
+    let unpadded_bytes_per_row = (width * bytes_per_texel) as usize;
 
     let pixels: Vec<u8> = buffer_slice
         .get_mapped_range()
@@ -58,5 +66,8 @@ pub(crate) fn extract_gpu_image(app: &mut DataViewer, texture: &Arc<Texture>) ->
 
     buffer.unmap();
 
-    pixels
+    match crate::cache::texture_tiles::grid_of(texture) {
+        Some(grid) => grid.assemble(&pixels),
+        None => crate::cache::compact_textures::to_rgba(texture, pixels),
+    }
 }
@@ -0,0 +1,213 @@
+/// Animated export of an image sequence
+///
+/// GIF is encoded in-process with the `image` crate. Animated WebP and MP4 are not
+/// supported by `image`, so the frames are written to a temporary directory and
+/// handed to an `ffmpeg` found on PATH.
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use image::{DynamicImage, Frame, Delay};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::FilterType;
+use log::{info, warn};
+
+use crate::archive_cache::ArchiveCache;
+use crate::cache::img_cache::PathSource;
+use crate::exif_utils::decode_with_exif_orientation;
+use crate::file_io::read_image_bytes;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SequenceFormat {
+    #[default]
+    Gif,
+    WebP,
+    Mp4,
+}
+
+impl SequenceFormat {
+    pub const ALL: [SequenceFormat; 3] = [SequenceFormat::Gif, SequenceFormat::WebP, SequenceFormat::Mp4];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SequenceFormat::Gif => "gif",
+            SequenceFormat::WebP => "webp",
+            SequenceFormat::Mp4 => "mp4",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SequenceFormat::Gif => "GIF",
+            SequenceFormat::WebP => "WebP",
+            SequenceFormat::Mp4 => "MP4 (ffmpeg)",
+        }
+    }
+}
+
+impl std::fmt::Display for SequenceFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SequenceExportOptions {
+    pub format: SequenceFormat,
+    pub fps: u32,
+    /// Longest side of the output frames (None keeps the first frame's size)
+    pub max_size: Option<u32>,
+}
+
+/// Updates sent from the export worker
+#[derive(Debug, Clone)]
+pub enum SequenceExportEvent {
+    /// (frames done, total frames)
+    Progress(usize, usize),
+    Finished(Result<PathBuf, String>),
+}
+
+/// Frame size for the whole animation, derived from the first frame
+///
+/// Dimensions are rounded down to even numbers since yuv420p video requires it.
+pub fn output_size(first: (u32, u32), max_size: Option<u32>) -> (u32, u32) {
+    let (width, height) = first;
+    let scale = match max_size {
+        Some(max) if width.max(height) > max => max as f64 / width.max(height) as f64,
+        _ => 1.0,
+    };
+    let even = |v: f64| ((v.round() as u32) & !1).max(2);
+    (even(width as f64 * scale), even(height as f64 * scale))
+}
+
+/// Decode and encode `frames` into `output`, reporting progress after each frame
+pub fn export_sequence(
+    frames: &[PathSource],
+    archive_cache: &Arc<Mutex<ArchiveCache>>,
+    output: &Path,
+    options: SequenceExportOptions,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<PathBuf, String> {
+    if frames.is_empty() {
+        return Err("No frames to export".to_string());
+    }
+    let fps = options.fps.max(1);
+    let total = frames.len();
+
+    let mut size = None;
+    let mut decode = |path_source: &PathSource| -> Result<DynamicImage, String> {
+        let bytes = {
+            let mut cache = archive_cache.lock().map_err(|_| "Archive cache lock poisoned".to_string())?;
+            read_image_bytes(path_source, Some(&mut *cache))
+                .map_err(|e| format!("Failed to read {}: {}", path_source.file_name(), e))?
+        };
+        let image = decode_with_exif_orientation(&bytes)
+            .map_err(|e| format!("Failed to decode {}: {:?}", path_source.file_name(), e))?;
+        let (width, height) = *size.get_or_insert_with(|| output_size((image.width(), image.height()), options.max_size));
+        Ok(crate::utils::color::resize(&image, width, height, FilterType::Triangle))
+    };
+
+    match options.format {
+        SequenceFormat::Gif => {
+            let file = File::create(output)
+                .map_err(|e| format!("Failed to create {}: {}", output.display(), e))?;
+            let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), 10);
+            encoder.set_repeat(Repeat::Infinite).map_err(|e| e.to_string())?;
+
+            for (i, path_source) in frames.iter().enumerate() {
+                let image = decode(path_source)?;
+                let frame = Frame::from_parts(image.to_rgba8(), 0, 0, Delay::from_numer_denom_ms(1000, fps));
+                encoder.encode_frame(frame).map_err(|e| format!("Failed to encode frame {}: {}", i, e))?;
+                progress(i + 1, total);
+            }
+        }
+        SequenceFormat::WebP | SequenceFormat::Mp4 => {
+            check_ffmpeg()?;
+            let frame_dir = std::env::temp_dir().join(format!("viewskater_export_{}", std::process::id()));
+            std::fs::create_dir_all(&frame_dir)
+                .map_err(|e| format!("Failed to create {}: {}", frame_dir.display(), e))?;
+
+            let result = (|| {
+                for (i, path_source) in frames.iter().enumerate() {
+                    let image = decode(path_source)?;
+                    let path = frame_dir.join(format!("frame_{:06}.png", i));
+                    image.save_with_format(&path, image::ImageFormat::Png)
+                        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+                    progress(i + 1, total);
+                }
+                run_ffmpeg(&ffmpeg_args(options.format, fps, &frame_dir.join("frame_%06d.png"), output))
+            })();
+
+            if let Err(e) = std::fs::remove_dir_all(&frame_dir) {
+                warn!("Failed to remove temporary frames in {}: {}", frame_dir.display(), e);
+            }
+            result?;
+        }
+    }
+
+    info!("Exported {} frame(s) to {}", total, output.display());
+    Ok(output.to_path_buf())
+}
+
+fn ffmpeg_args(format: SequenceFormat, fps: u32, input_pattern: &Path, output: &Path) -> Vec<String> {
+    let mut args: Vec<String> = vec![
+        "-y".into(),
+        "-loglevel".into(), "error".into(),
+        "-framerate".into(), fps.to_string(),
+        "-i".into(), input_pattern.to_string_lossy().into_owned(),
+    ];
+    match format {
+        SequenceFormat::WebP => args.extend([
+            "-c:v", "libwebp", "-lossless", "0", "-quality", "85", "-loop", "0",
+        ].map(String::from)),
+        SequenceFormat::Mp4 | SequenceFormat::Gif => args.extend([
+            "-c:v", "libx264", "-pix_fmt", "yuv420p", "-movflags", "+faststart",
+        ].map(String::from)),
+    }
+    args.push(output.to_string_lossy().into_owned());
+    args
+}
+
+fn check_ffmpeg() -> Result<(), String> {
+    Command::new("ffmpeg")
+        .arg("-version")
+        .output()
+        .map(|_| ())
+        .map_err(|_| "ffmpeg was not found on PATH; it is required for WebP and MP4 export".to_string())
+}
+
+fn run_ffmpeg(args: &[String]) -> Result<(), String> {
+    let output = Command::new("ffmpeg")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("ffmpeg failed: {}", String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_size() {
+        assert_eq!(output_size((1920, 1080), None), (1920, 1080));
+        assert_eq!(output_size((1920, 1080), Some(480)), (480, 270));
+        // Odd sizes are rounded down to even for video encoders
+        assert_eq!(output_size((641, 481), None), (640, 480));
+        // Never upscaled
+        assert_eq!(output_size((320, 240), Some(1024)), (320, 240));
+    }
+
+    #[test]
+    fn test_ffmpeg_args_end_with_output() {
+        let args = ffmpeg_args(SequenceFormat::WebP, 12, Path::new("f_%06d.png"), Path::new("out.webp"));
+        assert_eq!(args.last().map(String::as_str), Some("out.webp"));
+        assert!(args.windows(2).any(|w| w[0] == "-framerate" && w[1] == "12"));
+        assert!(args.iter().any(|a| a == "libwebp"));
+    }
+}
@@ -0,0 +1,87 @@
+/// Rasterizing SVG files
+///
+/// A vector image has no pixel size of its own, so it is rendered with resvg to fit the
+/// window, and rendered again at the size it is drawn at once it is zoomed past that (see
+/// `display_decode`). Text in the SVG is set with the system's fonts.
+use std::sync::Arc;
+use image::RgbaImage;
+use once_cell::sync::Lazy;
+use resvg::{tiny_skia, usvg};
+
+pub const SVG_EXTENSIONS: [&str; 1] = ["svg"];
+
+/// Longest side an SVG is rendered at, however far it is zoomed in
+pub const MAX_RASTER_SIDE: u32 = 16384;
+
+/// Loading the system's fonts takes a while, so it is done once
+static FONTS: Lazy<Arc<usvg::fontdb::Database>> = Lazy::new(|| {
+    let mut fonts = usvg::fontdb::Database::new();
+    fonts.load_system_fonts();
+    Arc::new(fonts)
+});
+
+/// Whether `bytes` look like an SVG document: XML whose root element is `<svg>`
+pub fn is_svg(bytes: &[u8]) -> bool {
+    let head = &bytes[..bytes.len().min(1024)];
+    let head = head.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(head);
+    let start = head.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(head.len());
+    head[start..].starts_with(b"<") && head.windows(4).any(|window| window == b"<svg")
+}
+
+/// Scale that fits a `width` x `height` image into `max_width` x `max_height`, enlarging it
+/// if needed, without going past `MAX_RASTER_SIDE`
+fn fit_scale(width: f32, height: f32, max_width: u32, max_height: u32) -> f32 {
+    let scale = (max_width as f32 / width).min(max_height as f32 / height);
+    scale.min(MAX_RASTER_SIDE as f32 / width.max(height))
+}
+
+/// Renders an SVG to fit `max_width` x `max_height`, keeping its aspect ratio
+pub fn rasterize(bytes: &[u8], max_width: u32, max_height: u32) -> Result<RgbaImage, String> {
+    let mut options = usvg::Options::default();
+    options.fontdb = Arc::clone(&FONTS);
+    let tree = usvg::Tree::from_data(bytes, &options).map_err(|e| e.to_string())?;
+
+    let size = tree.size();
+    let scale = fit_scale(size.width(), size.height(), max_width, max_height);
+    let width = ((size.width() * scale).round() as u32).max(1);
+    let height = ((size.height() * scale).round() as u32).max(1);
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| format!("Cannot render an SVG at {}x{}", width, height))?;
+    resvg::render(&tree, tiny_skia::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+
+    // tiny-skia works in premultiplied alpha
+    let pixels = pixmap.pixels().iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+    RgbaImage::from_raw(width, height, pixels).ok_or_else(|| "SVG pixel buffer size mismatch".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CIRCLE: &str = r##"<?xml version="1.0"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="20">
+  <rect width="40" height="20" fill="#ff0000"/>
+  <circle cx="30" cy="10" r="5" fill="#0000ff" fill-opacity="0.5"/>
+</svg>"##;
+
+    #[test]
+    fn test_rasterize_to_fit() {
+        assert!(is_svg(CIRCLE.as_bytes()));
+        assert!(is_svg(b"\xEF\xBB\xBF  <svg/>"));
+        assert!(!is_svg(b"<?xml version=\"1.0\"?><rss/>"));
+        assert!(!is_svg(&[0xFF, 0xD8, 0xFF]));
+
+        // Enlarged to fit the box, keeping the 2:1 aspect ratio
+        let image = rasterize(CIRCLE.as_bytes(), 400, 400).unwrap();
+        assert_eq!(image.dimensions(), (400, 200));
+        assert_eq!(image.get_pixel(10, 100).0, [255, 0, 0, 255]);
+        // Never past the largest side
+        assert_eq!(fit_scale(40.0, 20.0, 100_000, 100_000), MAX_RASTER_SIDE as f32 / 40.0);
+        assert!(rasterize(b"<svg", 10, 10).is_err());
+    }
+}
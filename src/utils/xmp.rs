@@ -0,0 +1,184 @@
+/// XMP sidecar reading and writing for the metadata editor
+///
+/// Only rating, color label, copyright and description are handled. Sidecars follow the
+/// Adobe naming (`IMG_0001.jpg` -> `IMG_0001.xmp`), so Lightroom, Bridge, digiKam and
+/// Capture One pick them up. An existing sidecar is edited in place: the four properties are
+/// removed wherever they appear and written again in a description block of their own, and
+/// everything else in the file is kept as it was.
+use std::path::{Path, PathBuf};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Color labels as Lightroom names them
+pub const LABELS: [&str; 5] = ["Red", "Yellow", "Green", "Blue", "Purple"];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XmpFields {
+    /// 0-5 stars
+    pub rating: Option<u8>,
+    pub label: Option<String>,
+    pub copyright: Option<String>,
+    pub description: Option<String>,
+}
+
+static RATING: Lazy<Regex> = Lazy::new(|| Regex::new(r#"xmp:Rating(?:="(-?\d+)"|>\s*(-?\d+)\s*<)"#).unwrap());
+static LABEL: Lazy<Regex> = Lazy::new(|| Regex::new(r#"xmp:Label(?:="([^"]*)"|>([^<]*)<)"#).unwrap());
+static RIGHTS: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<dc:rights>.*?<rdf:li[^>]*>(.*?)</rdf:li>").unwrap());
+static DESCRIPTION: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<dc:description>.*?<rdf:li[^>]*>(.*?)</rdf:li>").unwrap());
+
+/// The properties this module owns, as attributes or as elements
+static OWNED: Lazy<[Regex; 3]> = Lazy::new(|| [
+    Regex::new(r#"\s+xmp:(?:Rating|Label)="[^"]*""#).unwrap(),
+    Regex::new(r"(?s)\s*<xmp:(?:Rating|Label)>.*?</xmp:(?:Rating|Label)>").unwrap(),
+    Regex::new(r"(?s)\s*<dc:(?:rights|description)>.*?</dc:(?:rights|description)>").unwrap(),
+]);
+
+pub fn sidecar_path(image: &Path) -> PathBuf {
+    image.with_extension("xmp")
+}
+
+fn escape(value: &str) -> String {
+    value.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn unescape(value: &str) -> String {
+    value.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn first_group(regex: &Regex, xmp: &str) -> Option<String> {
+    let captures = regex.captures(xmp)?;
+    let value = captures.iter().skip(1).flatten().next()?.as_str();
+    Some(unescape(value.trim())).filter(|v| !v.is_empty())
+}
+
+/// The fields found in a sidecar; anything missing or malformed is left `None`
+pub fn parse(xmp: &str) -> XmpFields {
+    XmpFields {
+        rating: first_group(&RATING, xmp).and_then(|r| r.parse::<u8>().ok()).filter(|r| *r <= 5),
+        label: first_group(&LABEL, xmp),
+        copyright: first_group(&RIGHTS, xmp),
+        description: first_group(&DESCRIPTION, xmp),
+    }
+}
+
+fn alt_text(element: &str, value: &str) -> String {
+    format!(
+        "   <dc:{element}>\n    <rdf:Alt>\n     <rdf:li xml:lang=\"x-default\">{}</rdf:li>\n    </rdf:Alt>\n   </dc:{element}>\n",
+        escape(value)
+    )
+}
+
+/// `rdf:Description` block holding the fields, or nothing when all are empty
+fn description_block(fields: &XmpFields) -> String {
+    let mut attributes = String::new();
+    if let Some(rating) = fields.rating {
+        attributes.push_str(&format!("\n    xmp:Rating=\"{}\"", rating));
+    }
+    if let Some(label) = &fields.label {
+        attributes.push_str(&format!("\n    xmp:Label=\"{}\"", escape(label)));
+    }
+    let mut elements = String::new();
+    if let Some(copyright) = &fields.copyright {
+        elements.push_str(&alt_text("rights", copyright));
+    }
+    if let Some(description) = &fields.description {
+        elements.push_str(&alt_text("description", description));
+    }
+    if attributes.is_empty() && elements.is_empty() {
+        return String::new();
+    }
+    format!(
+        "  <rdf:Description rdf:about=\"\"\n    xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"\n    xmlns:dc=\"http://purl.org/dc/elements/1.1/\"{}>\n{}  </rdf:Description>\n",
+        attributes, elements
+    )
+}
+
+/// Sidecar contents with `fields` written into `existing`, or into a new packet
+pub fn update(existing: Option<&str>, fields: &XmpFields) -> Result<String, String> {
+    let Some(existing) = existing else {
+        return Ok(format!(
+            "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\" x:xmptk=\"viewskater\">\n <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n{} </rdf:RDF>\n</x:xmpmeta>\n",
+            description_block(fields)
+        ));
+    };
+    let end = existing.rfind("</rdf:RDF>").ok_or("existing sidecar is not an XMP packet")?;
+    let mut head = existing[..end].to_string();
+    for regex in OWNED.iter() {
+        head = regex.replace_all(&head, "").into_owned();
+    }
+    if !head.ends_with('\n') {
+        head.push('\n');
+    }
+    Ok(format!("{}{} {}", head, description_block(fields), &existing[end..]))
+}
+
+/// Writes `fields` to the sidecar of `image`, keeping the rest of an existing sidecar
+pub fn write_sidecar(image: &Path, fields: &XmpFields) -> Result<PathBuf, String> {
+    let path = sidecar_path(image);
+    let existing = match std::fs::read_to_string(&path) {
+        Ok(contents) => Some(contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    };
+    let contents = update(existing.as_deref(), fields)?;
+    std::fs::write(&path, contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Fields in the sidecar of `image`, empty when it has none
+pub fn read_sidecar(image: &Path) -> XmpFields {
+    std::fs::read_to_string(sidecar_path(image))
+        .map(|contents| parse(&contents))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields() -> XmpFields {
+        XmpFields {
+            rating: Some(4),
+            label: Some("Green".to_string()),
+            copyright: Some("© 2024 A & B".to_string()),
+            description: Some("Scan <draft>".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_new_sidecar_round_trip() {
+        let xmp = update(None, &fields()).unwrap();
+        assert!(xmp.contains("A &amp; B"));
+        assert_eq!(parse(&xmp), fields());
+        assert_eq!(parse(&update(None, &XmpFields::default()).unwrap()), XmpFields::default());
+    }
+
+    #[test]
+    fn test_update_keeps_other_properties() {
+        let existing = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about="" xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+    xmlns:crs="http://ns.adobe.com/camera-raw-settings/1.0/"
+    xmp:Rating="2" crs:Exposure2012="+0.50">
+   <xmp:Label>Red</xmp:Label>
+   <dc:description><rdf:Alt><rdf:li xml:lang="x-default">old</rdf:li></rdf:Alt></dc:description>
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>
+"#;
+        let updated = update(Some(existing), &fields()).unwrap();
+        assert!(updated.contains(r#"crs:Exposure2012="+0.50""#));
+        assert!(!updated.contains("old") && !updated.contains(r#"xmp:Rating="2""#));
+        assert_eq!(parse(&updated), fields());
+        assert!(updated.trim_end().ends_with("</x:xmpmeta>"));
+
+        assert!(update(Some("not xmp"), &fields()).is_err());
+    }
+}
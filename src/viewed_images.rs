@@ -0,0 +1,261 @@
+/// Viewed-image tracking for reviewing large folders
+///
+/// Every image shown in a pane is recorded as viewed for its folder. A thin strip above the
+/// slider marks the viewed stretches of the folder, and "View -> Viewed Images -> Jump to First
+/// Unviewed" (**J**) goes to the first image not seen yet. With `persist_viewed` on, the viewed names are
+/// kept in `<data dir>/viewskater/viewed/` per folder, so a review spread over several days
+/// resumes where it left off; otherwise they only last for the session.
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use iced_winit::core::{Element, Length, Color};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{container, text, Row};
+use log::{debug, warn};
+
+use crate::app::{DataViewer, Message, NavigationMessage};
+use crate::pane::Pane;
+
+/// Most segments drawn in the marker strip; larger folders are binned
+const MAX_SEGMENTS: usize = 400;
+/// Newly viewed images are written at most this often while persisting
+const SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+pub enum ViewedMessage {
+    JumpToFirstUnviewed,
+    /// Forget the viewed images of the active pane's folder
+    ClearFolder,
+}
+
+impl From<ViewedMessage> for Message {
+    fn from(msg: ViewedMessage) -> Self {
+        Message::ViewedAction(msg)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FolderViews {
+    directory: String,
+    names: HashSet<String>,
+    #[serde(skip)]
+    dirty: AtomicBool,
+}
+
+/// Viewed flags by image index for what a pane shows
+#[derive(Debug, Default)]
+struct PaneMarks {
+    directory: String,
+    flags: Vec<bool>,
+}
+
+#[derive(Debug)]
+pub struct ViewedImages {
+    pub persist: bool,
+    folders: HashMap<String, FolderViews>,
+    panes: Vec<PaneMarks>,
+    last_save: Instant,
+}
+
+impl Default for ViewedImages {
+    fn default() -> Self {
+        Self {
+            persist: false,
+            folders: HashMap::new(),
+            panes: Vec::new(),
+            last_save: Instant::now(),
+        }
+    }
+}
+
+fn viewed_dir() -> PathBuf {
+    crate::app_dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("viewskater")
+        .join("viewed")
+}
+
+/// FNV-1a of the folder path, so the file name stays the same across builds
+fn folder_file(directory: &str) -> PathBuf {
+    let hash = directory.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    viewed_dir().join(format!("{:016x}.json", hash))
+}
+
+fn load_folder(directory: &str) -> FolderViews {
+    let path = folder_file(directory);
+    let loaded = std::fs::read_to_string(&path).ok()
+        .and_then(|json| match serde_json::from_str::<FolderViews>(&json) {
+            Ok(views) => Some(views),
+            Err(e) => {
+                warn!("Failed to parse {}: {}", path.display(), e);
+                None
+            }
+        })
+        .filter(|views| views.directory == directory);
+    loaded.unwrap_or_else(|| FolderViews { directory: directory.to_string(), ..Default::default() })
+}
+
+/// Viewed (true) and unviewed runs of `flags` as (viewed, length), binned to at most
+/// `max_segments` bins first; a bin counts as viewed only when all of its images are
+pub fn marker_runs(flags: &[bool], max_segments: usize) -> Vec<(bool, usize)> {
+    let bins: Vec<(bool, usize)> = if flags.len() > max_segments {
+        (0..max_segments).map(|i| {
+            let bin = &flags[i * flags.len() / max_segments..(i + 1) * flags.len() / max_segments];
+            (bin.iter().all(|&viewed| viewed), bin.len())
+        }).collect()
+    } else {
+        flags.iter().map(|&viewed| (viewed, 1)).collect()
+    };
+
+    let mut runs: Vec<(bool, usize)> = Vec::new();
+    for (viewed, len) in bins {
+        match runs.last_mut() {
+            Some((last, run_len)) if *last == viewed => *run_len += len,
+            _ => runs.push((viewed, len)),
+        }
+    }
+    runs
+}
+
+impl ViewedImages {
+    /// Records the image each loaded pane shows
+    pub fn record(&mut self, panes: &[Pane]) {
+        self.panes.resize_with(panes.len(), Default::default);
+        for (pane, marks) in panes.iter().zip(self.panes.iter_mut()) {
+            let (true, Some(directory)) = (pane.dir_loaded, pane.directory_path.as_ref()) else {
+                continue;
+            };
+            let Some(source) = pane.img_cache.image_paths.get(pane.img_cache.current_index) else {
+                continue;
+            };
+            let persist = self.persist;
+            let folder = self.folders.entry(directory.clone())
+                .or_insert_with(|| if persist { load_folder(directory) } else {
+                    FolderViews { directory: directory.clone(), ..Default::default() }
+                });
+            if folder.names.insert(source.file_name().to_string()) {
+                folder.dirty.store(true, Ordering::Relaxed);
+            }
+
+            let paths = &pane.img_cache.image_paths;
+            if marks.directory != *directory || marks.flags.len() != paths.len() {
+                marks.directory = directory.clone();
+                marks.flags = paths.iter().map(|path| folder.names.contains(path.file_name().as_ref())).collect();
+            } else {
+                marks.flags[pane.img_cache.current_index] = true;
+            }
+        }
+
+        if self.persist && self.last_save.elapsed() >= SAVE_INTERVAL {
+            self.last_save = Instant::now();
+            self.save();
+        }
+    }
+
+    /// Writes the folders with newly viewed images, when persisting
+    pub fn save(&self) {
+        if !self.persist {
+            return;
+        }
+        for folder in self.folders.values().filter(|folder| folder.dirty.swap(false, Ordering::Relaxed)) {
+            let path = folder_file(&folder.directory);
+            let result = std::fs::create_dir_all(viewed_dir())
+                .and_then(|_| serde_json::to_string(folder).map_err(std::io::Error::other))
+                .and_then(|json| std::fs::write(&path, json));
+            match result {
+                Ok(()) => debug!("Saved {} viewed image(s) to {}", folder.names.len(), path.display()),
+                Err(e) => warn!("Failed to save viewed images to {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    /// Viewed flags by image index for `pane`, when they are up to date
+    fn flags(&self, pane_index: usize, pane: &Pane) -> Option<&[bool]> {
+        let marks = self.panes.get(pane_index)?;
+        (pane.dir_loaded && pane.directory_path.as_ref() == Some(&marks.directory)
+            && marks.flags.len() == pane.img_cache.image_paths.len())
+            .then_some(marks.flags.as_slice())
+    }
+
+    /// (viewed, total) images of the pane's folder
+    pub fn progress(&self, pane_index: usize, pane: &Pane) -> Option<(usize, usize)> {
+        let flags = self.flags(pane_index, pane)?;
+        Some((flags.iter().filter(|&&viewed| viewed).count(), flags.len()))
+    }
+
+    pub fn first_unviewed(&self, pane_index: usize, pane: &Pane) -> Option<usize> {
+        self.flags(pane_index, pane)?.iter().position(|&viewed| !viewed)
+    }
+}
+
+pub fn handle_viewed_message(app: &mut DataViewer, msg: ViewedMessage) -> Task<Message> {
+    let pane_index = app.active_pane_index();
+    let Some(pane) = app.panes.get(pane_index) else {
+        return Task::none();
+    };
+    match msg {
+        ViewedMessage::JumpToFirstUnviewed => match app.viewed_images.first_unviewed(pane_index, pane) {
+            Some(index) => Task::done(Message::Navigation(NavigationMessage::JumpToImage(pane_index, index))),
+            None => Task::none(),
+        },
+        ViewedMessage::ClearFolder => {
+            let Some(directory) = pane.directory_path.clone() else {
+                return Task::none();
+            };
+            let viewed = &mut app.viewed_images;
+            if let Some(folder) = viewed.folders.get_mut(&directory) {
+                folder.names.clear();
+                folder.dirty.store(true, Ordering::Relaxed);
+            }
+            if let Some(marks) = viewed.panes.iter_mut().find(|marks| marks.directory == directory) {
+                marks.flags.clear();
+            }
+            viewed.save();
+            // The image still shown counts as viewed again
+            viewed.record(&app.panes);
+            Task::none()
+        }
+    }
+}
+
+/// Strip over the slider with the viewed stretches of the pane's folder highlighted
+pub fn view<'a>(viewed: &'a ViewedImages, pane_index: usize, pane: &'a Pane) -> Element<'a, Message, WinitTheme, Renderer> {
+    let Some(flags) = viewed.flags(pane_index, pane).filter(|flags| flags.len() > 1) else {
+        return container(text("")).height(0).into();
+    };
+    let segments = marker_runs(flags, MAX_SEGMENTS).into_iter().map(|(seen, len)| {
+        container(text(""))
+            .width(Length::FillPortion(len.min(u16::MAX as usize) as u16))
+            .height(Length::Fixed(3.0))
+            .style(move |_theme: &WinitTheme| container::Style {
+                background: seen.then(|| Color::from_rgba(0.45, 0.75, 0.55, 0.7).into()),
+                ..container::Style::default()
+            })
+            .into()
+    });
+    container(Row::with_children(segments))
+        .padding([0, 8])
+        .width(Length::Fill)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marker_runs() {
+        let flags = [true, true, false, true, false, false];
+        assert_eq!(marker_runs(&flags, 10), vec![(true, 2), (false, 1), (true, 1), (false, 2)]);
+        // Binned in threes: only a fully viewed bin is marked
+        assert_eq!(marker_runs(&flags, 2), vec![(false, 6)]);
+        assert_eq!(marker_runs(&[true; 6], 3), vec![(true, 6)]);
+        assert!(marker_runs(&[], 3).is_empty());
+    }
+}
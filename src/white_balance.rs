@@ -0,0 +1,283 @@
+/// Auto white balance preview
+///
+/// A quick check for color casts in scans and renders. Gray world assumes the scene averages
+/// to gray; white patch assumes its brightest pixels are white. Per-channel gains are
+/// estimated from a small decode of the file on a worker thread, then the image shader
+/// applies them right of a draggable split and leaves the original on the left. The pixels
+/// and the file are never changed.
+///
+/// Estimates are matched to pipelines by the texture they were made for, since pipelines
+/// are not tied to a pane, and are dropped once that texture is no longer shown.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::{wgpu, Renderer};
+use iced_widget::{row, container, text, button};
+use once_cell::sync::Lazy;
+use log::warn;
+
+use crate::app::Message;
+use crate::archive_cache::ArchiveCache;
+use crate::cache::img_cache::PathSource;
+use crate::pane::Pane;
+use crate::utils::color::srgb_to_linear;
+
+/// Longest side of the decode the gains are estimated from
+const ESTIMATE_SIZE: u32 = 256;
+/// Share of the brightest pixels averaged by the white-patch estimate
+const WHITE_PATCH_FRACTION: f32 = 0.01;
+/// Gains are clamped so near-monochrome images don't blow up a channel
+const MAX_GAIN: f32 = 4.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WhiteBalanceMode {
+    GrayWorld,
+    WhitePatch,
+}
+
+impl WhiteBalanceMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            WhiteBalanceMode::GrayWorld => "Gray World",
+            WhiteBalanceMode::WhitePatch => "White Patch",
+        }
+    }
+}
+
+/// Gains for both modes, so switching between them doesn't need a new estimate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub gray_world: [f32; 3],
+    pub white_patch: [f32; 3],
+}
+
+/// Texture address and size
+type TextureKey = (usize, u32, u32);
+
+#[derive(Debug)]
+struct State {
+    mode: Option<WhiteBalanceMode>,
+    /// Horizontal split position across the image, 0 = all corrected, 1 = all original
+    split: f32,
+    /// `None` while the estimate is being computed
+    estimates: HashMap<TextureKey, Option<Estimate>>,
+}
+
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State {
+    mode: None,
+    split: 0.5,
+    estimates: HashMap::new(),
+}));
+
+#[derive(Debug, Clone)]
+pub enum WhiteBalanceMessage {
+    SetMode(Option<WhiteBalanceMode>),
+    SetSplit(f32),
+    Estimated(TextureKey, Result<Estimate, String>),
+}
+
+impl From<WhiteBalanceMessage> for Message {
+    fn from(msg: WhiteBalanceMessage) -> Self {
+        Message::WhiteBalanceAction(msg)
+    }
+}
+
+fn texture_key(texture: &wgpu::Texture) -> TextureKey {
+    (texture as *const wgpu::Texture as usize, texture.width(), texture.height())
+}
+
+pub fn mode() -> Option<WhiteBalanceMode> {
+    STATE.lock().ok()?.mode
+}
+
+/// Gains and split position for a texture while the preview is on and its estimate is ready
+pub fn gains_for(texture: &wgpu::Texture) -> Option<([f32; 3], f32)> {
+    let state = STATE.lock().ok()?;
+    let estimate = (*state.estimates.get(&texture_key(texture))?)?;
+    let gains = match state.mode? {
+        WhiteBalanceMode::GrayWorld => estimate.gray_world,
+        WhiteBalanceMode::WhitePatch => estimate.white_patch,
+    };
+    Some((gains, state.split))
+}
+
+/// Gains that make `reference` neutral, scaled so the channel that is already brightest
+/// (white patch) or the average (gray world) stays put
+fn gains_towards(reference: [f32; 3], target: f32) -> [f32; 3] {
+    reference.map(|c| if c > 0.0 { (target / c).clamp(1.0 / MAX_GAIN, MAX_GAIN) } else { 1.0 })
+}
+
+/// Estimates both modes' gains from 8-bit sRGB pixels, working in linear light
+pub fn estimate(pixels: &[[u8; 3]]) -> Estimate {
+    if pixels.is_empty() {
+        return Estimate { gray_world: [1.0; 3], white_patch: [1.0; 3] };
+    }
+    let table: Vec<f32> = (0..=255u8).map(|v| srgb_to_linear(v as f32 / 255.0)).collect();
+    let linear: Vec<[f32; 3]> = pixels.iter()
+        .map(|p| [table[p[0] as usize], table[p[1] as usize], table[p[2] as usize]])
+        .collect();
+
+    let mut sum = [0.0f64; 3];
+    for p in &linear {
+        for c in 0..3 {
+            sum[c] += p[c] as f64;
+        }
+    }
+    let mean = sum.map(|s| (s / linear.len() as f64) as f32);
+    let gray_world = gains_towards(mean, (mean[0] + mean[1] + mean[2]) / 3.0);
+
+    // Clipped pixels read as white whatever the cast, so they are left out when possible
+    let mut candidates: Vec<(usize, f32)> = pixels.iter()
+        .enumerate()
+        .filter(|(_, p)| p.iter().all(|&c| c < 255))
+        .map(|(i, _)| (i, linear[i][0] + linear[i][1] + linear[i][2]))
+        .collect();
+    if candidates.is_empty() {
+        candidates = linear.iter().enumerate().map(|(i, p)| (i, p[0] + p[1] + p[2])).collect();
+    }
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let count = ((candidates.len() as f32 * WHITE_PATCH_FRACTION).ceil() as usize).max(1);
+    let mut white = [0.0f32; 3];
+    for &(i, _) in &candidates[..count] {
+        for c in 0..3 {
+            white[c] += linear[i][c] / count as f32;
+        }
+    }
+    let white_patch = gains_towards(white, white[0].max(white[1]).max(white[2]));
+
+    Estimate { gray_world, white_patch }
+}
+
+fn estimate_file(source: &PathSource, archive_cache: Option<&Mutex<ArchiveCache>>) -> Result<Estimate, String> {
+    let image = crate::file_io::decode_path_source(source, archive_cache)?
+        .thumbnail(ESTIMATE_SIZE, ESTIMATE_SIZE)
+        .to_rgb8();
+    let pixels: Vec<[u8; 3]> = image.pixels().map(|p| p.0).collect();
+    Ok(estimate(&pixels))
+}
+
+/// Starts estimates for the images shown in the panes while the preview is on, and forgets
+/// the ones no longer shown
+pub fn request(panes: &[Pane]) -> Option<Task<Message>> {
+    let mut state = STATE.lock().ok()?;
+    if state.mode.is_none() {
+        return None;
+    }
+
+    let shown: Vec<(TextureKey, &Pane)> = panes.iter()
+        .filter(|pane| pane.dir_loaded)
+        .filter_map(|pane| Some((texture_key(pane.scene.as_ref()?.get_texture()?), pane)))
+        .collect();
+    state.estimates.retain(|key, _| shown.iter().any(|(shown_key, _)| shown_key == key));
+
+    let mut tasks = Vec::new();
+    for (key, pane) in shown {
+        if state.estimates.contains_key(&key) {
+            continue;
+        }
+        let Some(source) = pane.img_cache.image_paths.get(pane.img_cache.current_index).cloned() else {
+            continue;
+        };
+        let archive_cache = pane.has_compressed_file.then(|| Arc::clone(&pane.archive_cache));
+        state.estimates.insert(key, None);
+        tasks.push(Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || estimate_file(&source, archive_cache.as_deref()))
+                    .await
+                    .unwrap_or_else(|e| Err(e.to_string()))
+            },
+            move |result| WhiteBalanceMessage::Estimated(key, result).into()
+        ));
+    }
+    (!tasks.is_empty()).then(|| Task::batch(tasks))
+}
+
+pub fn handle_white_balance_message(msg: WhiteBalanceMessage) -> Task<Message> {
+    if let Ok(mut state) = STATE.lock() {
+        match msg {
+            WhiteBalanceMessage::SetMode(mode) => {
+                state.mode = mode;
+                if mode.is_none() {
+                    state.estimates.clear();
+                }
+            }
+            WhiteBalanceMessage::SetSplit(split) => state.split = split.clamp(0.0, 1.0),
+            WhiteBalanceMessage::Estimated(key, result) => match result {
+                // Ignore estimates for textures that went away while they were computed
+                Ok(estimate) => if let Some(entry) = state.estimates.get_mut(&key) {
+                    *entry = Some(estimate);
+                },
+                Err(e) => warn!("White balance estimate failed: {}", e),
+            },
+        }
+    }
+    Task::none()
+}
+
+/// Mode buttons and split slider while the preview is on, or nothing
+pub fn view<'a>() -> Element<'a, Message, WinitTheme, Renderer> {
+    let Some((mode, split)) = STATE.lock().ok().and_then(|state| state.mode.map(|mode| (mode, state.split))) else {
+        return container(text("")).height(0).into();
+    };
+
+    let mode_button = |option: WhiteBalanceMode| {
+        let checked = if option == mode { "[x]" } else { "[  ]" };
+        button(text(format!("{} {}", checked, option.label())).size(11))
+            .padding([1, 6])
+            .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+            .on_press(WhiteBalanceMessage::SetMode(Some(option)).into())
+    };
+
+    container(
+        row![
+            text("Auto white balance (preview)").size(13).width(Length::Fill),
+            mode_button(WhiteBalanceMode::GrayWorld),
+            mode_button(WhiteBalanceMode::WhitePatch),
+            text("Original | Corrected").size(13),
+            iced_widget::slider(0.0..=100.0, split * 100.0, |value| WhiteBalanceMessage::SetSplit(value / 100.0).into())
+                .step(1.0)
+                .width(Length::Fixed(200.0)),
+            button(text("Close").size(11))
+                .padding([1, 6])
+                .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+                .on_press(WhiteBalanceMessage::SetMode(None).into()),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center)
+    )
+    .padding([4, 8])
+    .width(Length::Fill)
+    .style(|_theme: &WinitTheme| container::Style {
+        text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+        ..container::Style::default()
+    })
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_removes_cast() {
+        // A warm cast: every pixel has more red than blue
+        let pixels: Vec<[u8; 3]> = (0..100u8)
+            .map(|i| [(i as u16 * 2 + 50).min(254) as u8, i * 2 + 30, i * 2 + 10])
+            .collect();
+        let estimate = estimate(&pixels);
+        assert!(estimate.gray_world[0] < 1.0 && estimate.gray_world[2] > 1.0);
+        assert!(estimate.white_patch[2] > estimate.white_patch[1]);
+        assert!((estimate.white_patch[0] - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_estimate_neutral_image() {
+        let pixels: Vec<[u8; 3]> = (0..=254u8).map(|v| [v, v, v]).collect();
+        let estimate = estimate(&pixels);
+        for gain in estimate.gray_world.iter().chain(estimate.white_patch.iter()) {
+            assert!((gain - 1.0).abs() < 1e-4);
+        }
+    }
+}
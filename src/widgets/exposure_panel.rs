@@ -0,0 +1,81 @@
+/// Exposure and gamma of HDR images
+///
+/// While a pane shows an OpenEXR or Radiance HDR image (see `cache::hdr_textures`), a bar
+/// with exposure and gamma sliders is shown above the slider. Like the LUT, the values are
+/// kept in a static the image pipeline reads when it renders, and apply to every HDR image.
+use std::sync::Mutex;
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, container, text, button};
+
+use crate::app::Message;
+use crate::cache::hdr_textures::DISPLAY_GAMMA;
+
+/// Exposure in stops and gamma
+static EXPOSURE: Mutex<(f32, f32)> = Mutex::new((0.0, DISPLAY_GAMMA));
+
+#[derive(Debug, Clone)]
+pub enum ExposureMessage {
+    SetExposure(f32),
+    SetGamma(f32),
+    Reset,
+}
+
+impl From<ExposureMessage> for Message {
+    fn from(msg: ExposureMessage) -> Self {
+        Message::ExposureAction(msg)
+    }
+}
+
+/// Exposure in stops and gamma
+pub fn current() -> (f32, f32) {
+    EXPOSURE.lock().map_or((0.0, DISPLAY_GAMMA), |exposure| *exposure)
+}
+
+pub fn handle_exposure_message(msg: ExposureMessage) -> Task<Message> {
+    if let Ok(mut exposure) = EXPOSURE.lock() {
+        match msg {
+            ExposureMessage::SetExposure(stops) => exposure.0 = stops.clamp(-10.0, 10.0),
+            ExposureMessage::SetGamma(gamma) => exposure.1 = gamma.clamp(1.0, 4.0),
+            ExposureMessage::Reset => *exposure = (0.0, DISPLAY_GAMMA),
+        }
+    }
+    Task::none()
+}
+
+/// Exposure and gamma sliders while an HDR image is shown, or nothing
+pub fn view<'a>(shows_hdr: bool) -> Element<'a, Message, WinitTheme, Renderer> {
+    if !shows_hdr {
+        return container(text("")).height(0).into();
+    }
+    let (stops, gamma) = current();
+
+    container(
+        row![
+            text("HDR").size(13).width(Length::Fill),
+            text(format!("Exposure: {:+.1} EV", stops)).size(13),
+            iced_widget::slider(-10.0..=10.0, stops, |value| ExposureMessage::SetExposure(value).into())
+                .step(0.1)
+                .width(Length::Fixed(200.0)),
+            text(format!("Gamma: {:.2}", gamma)).size(13),
+            iced_widget::slider(1.0..=4.0, gamma, |value| ExposureMessage::SetGamma(value).into())
+                .step(0.05)
+                .width(Length::Fixed(120.0)),
+            button(text("Reset").size(11))
+                .padding([1, 6])
+                .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+                .on_press(ExposureMessage::Reset.into()),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center)
+    )
+    .padding([4, 8])
+    .width(Length::Fill)
+    .style(|_theme: &WinitTheme| container::Style {
+        text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+        ..container::Style::default()
+    })
+    .into()
+}
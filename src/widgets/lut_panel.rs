@@ -0,0 +1,178 @@
+/// Color grading preview with a 3D LUT
+///
+/// The active LUT is shared with the image pipelines through a static, like the user
+/// shader, since they are built on the render side without access to the app state. LUTs
+/// in `<config dir>/viewskater/luts/` are listed in "Controls -> LUT"; any other `.cube`
+/// file can be opened from there too. While a LUT is active, a bar with its name and an
+/// intensity slider is shown above the slider.
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use once_cell::sync::Lazy;
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{row, container, text, button};
+use log::{error, info};
+
+use crate::app::Message;
+use crate::utils::lut::{parse_cube, Lut3d};
+
+#[derive(Debug)]
+struct ActiveLut {
+    path: PathBuf,
+    lut: Arc<Lut3d>,
+    intensity: f32,
+}
+
+static ACTIVE_LUT: Lazy<Mutex<Option<ActiveLut>>> = Lazy::new(|| Mutex::new(None));
+/// Bumped when the LUT table changes (not on intensity changes), so pipelines re-upload it
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone)]
+pub enum LutMessage {
+    Open,
+    Load(PathBuf),
+    Loaded(PathBuf, Result<Arc<Lut3d>, String>),
+    SetIntensity(f32),
+    Clear,
+}
+
+impl From<LutMessage> for Message {
+    fn from(msg: LutMessage) -> Self {
+        Message::LutAction(msg)
+    }
+}
+
+pub fn luts_dir() -> PathBuf {
+    crate::app_dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("viewskater")
+        .join("luts")
+}
+
+/// `.cube` files in the LUT directory, sorted by name
+pub fn available_luts() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(luts_dir()) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e.eq_ignore_ascii_case("cube")))
+        .collect();
+    paths.sort();
+    paths
+}
+
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+/// Table and intensity of the active LUT
+pub fn active() -> Option<(Arc<Lut3d>, f32)> {
+    let state = ACTIVE_LUT.lock().ok()?;
+    state.as_ref().map(|active| (Arc::clone(&active.lut), active.intensity))
+}
+
+pub fn active_path() -> Option<PathBuf> {
+    let state = ACTIVE_LUT.lock().ok()?;
+    state.as_ref().map(|active| active.path.clone())
+}
+
+fn read_lut(path: &Path) -> Result<Arc<Lut3d>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    parse_cube(&text).map(Arc::new)
+}
+
+pub fn handle_lut_message(msg: LutMessage) -> Task<Message> {
+    match msg {
+        LutMessage::Open => Task::perform(
+            async {
+                rfd::AsyncFileDialog::new()
+                    .set_title("Select LUT")
+                    .add_filter("Cube LUT", &["cube"])
+                    .pick_file()
+                    .await
+            },
+            |file_handle| match file_handle {
+                Some(file) => LutMessage::Load(file.path().to_path_buf()).into(),
+                None => Message::Nothing,
+            }
+        ),
+        LutMessage::Load(path) => Task::perform(
+            async move {
+                let read_path = path.clone();
+                let result = tokio::task::spawn_blocking(move || read_lut(&read_path))
+                    .await
+                    .unwrap_or_else(|e| Err(e.to_string()));
+                (path, result)
+            },
+            |(path, result)| LutMessage::Loaded(path, result).into()
+        ),
+        LutMessage::Loaded(path, result) => {
+            match result {
+                Ok(lut) => {
+                    info!("Loaded {}³ LUT {}", lut.size, path.display());
+                    if let Ok(mut state) = ACTIVE_LUT.lock() {
+                        let intensity = state.as_ref().map_or(1.0, |active| active.intensity);
+                        *state = Some(ActiveLut { path, lut, intensity });
+                    }
+                    GENERATION.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(e) => error!("Failed to load LUT {}: {}", path.display(), e),
+            }
+            Task::none()
+        }
+        LutMessage::SetIntensity(intensity) => {
+            if let Ok(mut state) = ACTIVE_LUT.lock() {
+                if let Some(active) = state.as_mut() {
+                    active.intensity = intensity.clamp(0.0, 1.0);
+                }
+            }
+            Task::none()
+        }
+        LutMessage::Clear => {
+            if let Ok(mut state) = ACTIVE_LUT.lock() {
+                *state = None;
+            }
+            GENERATION.fetch_add(1, Ordering::Relaxed);
+            Task::none()
+        }
+    }
+}
+
+/// LUT name and intensity slider while a LUT is active, or nothing
+pub fn view<'a>() -> Element<'a, Message, WinitTheme, Renderer> {
+    let Some((path, intensity)) = ACTIVE_LUT.lock().ok()
+        .and_then(|state| state.as_ref().map(|active| (active.path.clone(), active.intensity)))
+    else {
+        return container(text("")).height(0).into();
+    };
+    let name = path.file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let intensity_percent = intensity * 100.0;
+
+    container(
+        row![
+            text(format!("LUT: {}", name)).size(13).width(Length::Fill),
+            text(format!("Intensity: {:.0}%", intensity_percent)).size(13),
+            iced_widget::slider(0.0..=100.0, intensity_percent, |value| LutMessage::SetIntensity(value / 100.0).into())
+                .step(1.0)
+                .width(Length::Fixed(200.0)),
+            button(text("Clear").size(11))
+                .padding([1, 6])
+                .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+                .on_press(LutMessage::Clear.into()),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center)
+    )
+    .padding([4, 8])
+    .width(Length::Fill)
+    .style(|_theme: &WinitTheme| container::Style {
+        text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+        ..container::Style::default()
+    })
+    .into()
+}
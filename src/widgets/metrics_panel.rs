@@ -0,0 +1,369 @@
+/// Plot of per-step training metrics linked to numbered images
+///
+/// Loads a CSV with a step column (`step`, `global_step`, `iteration` or `epoch`, else the
+/// first column) and one or more numeric columns, and plots one column above the slider.
+/// Images are matched to steps by the last number in their file name, so `step_001000.png`
+/// is step 1000; clicking the plot jumps to the image closest to the clicked step.
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use iced_winit::core::{mouse, Color, Element, Length, Point, Rectangle, Size};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::canvas::{self, Path as CanvasPath, Stroke};
+use iced_widget::{button, container, row, text};
+use iced_core::alignment;
+use log::{error, info};
+
+use crate::app::{Message, NavigationMessage};
+use crate::pane::Pane;
+
+const PLOT_HEIGHT: f32 = 72.0;
+const STEP_COLUMNS: [&str; 4] = ["step", "global_step", "iteration", "epoch"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricsTable {
+    pub source: PathBuf,
+    /// Ascending steps, one per row
+    pub steps: Vec<u64>,
+    pub columns: Vec<String>,
+    /// `values[column][row]`; NaN where a cell is empty or not a number
+    pub values: Vec<Vec<f64>>,
+}
+
+impl MetricsTable {
+    /// Row whose step is closest to `step`
+    pub fn nearest_row(&self, step: f64) -> Option<usize> {
+        let after = self.steps.partition_point(|&s| (s as f64) < step);
+        [after.checked_sub(1), (after < self.steps.len()).then_some(after)]
+            .into_iter()
+            .flatten()
+            .min_by(|&a, &b| (self.steps[a] as f64 - step).abs().total_cmp(&(self.steps[b] as f64 - step).abs()))
+    }
+
+    /// Smallest and largest finite value of a column
+    pub fn value_range(&self, column: usize) -> Option<(f64, f64)> {
+        self.values.get(column)?.iter()
+            .filter(|v| v.is_finite())
+            .fold(None, |range, &v| match range {
+                None => Some((v, v)),
+                Some((lo, hi)) => Some((lo.min(v), hi.max(v))),
+            })
+    }
+}
+
+/// Parse a metrics CSV; rows whose step is not a whole number are skipped
+pub fn parse_csv(source: &Path, content: &str) -> Result<MetricsTable, String> {
+    let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+    let header: Vec<String> = lines.next()
+        .ok_or("CSV is empty")?
+        .split(',')
+        .map(|cell| cell.trim().trim_matches('"').to_string())
+        .collect();
+    let step_column = header.iter()
+        .position(|name| STEP_COLUMNS.contains(&name.to_lowercase().as_str()))
+        .unwrap_or(0);
+    let value_columns: Vec<usize> = (0..header.len()).filter(|&i| i != step_column).collect();
+    if value_columns.is_empty() {
+        return Err("CSV needs a step column and at least one metric column".to_string());
+    }
+
+    let mut rows: Vec<(u64, Vec<f64>)> = Vec::new();
+    for line in lines {
+        let cells: Vec<&str> = line.split(',').map(|cell| cell.trim().trim_matches('"')).collect();
+        let Some(step) = cells.get(step_column).and_then(|cell| cell.parse::<f64>().ok())
+            .filter(|s| *s >= 0.0 && s.fract() == 0.0) else {
+            continue;
+        };
+        let values = value_columns.iter()
+            .map(|&i| cells.get(i).and_then(|cell| cell.parse::<f64>().ok()).unwrap_or(f64::NAN))
+            .collect();
+        rows.push((step as u64, values));
+    }
+    if rows.is_empty() {
+        return Err("CSV has no rows with a numeric step".to_string());
+    }
+    rows.sort_by_key(|(step, _)| *step);
+
+    Ok(MetricsTable {
+        source: source.to_path_buf(),
+        steps: rows.iter().map(|(step, _)| *step).collect(),
+        columns: value_columns.iter().map(|&i| header[i].clone()).collect(),
+        values: (0..value_columns.len())
+            .map(|c| rows.iter().map(|(_, values)| values[c]).collect())
+            .collect(),
+    })
+}
+
+/// Last run of digits in a file name's stem, e.g. 1000 for `step_001000.png`
+pub fn step_from_name(name: &str) -> Option<u64> {
+    let stem = Path::new(name).file_stem()?.to_str()?;
+    let end = stem.rfind(|c: char| c.is_ascii_digit())? + 1;
+    let start = stem[..end].rfind(|c: char| !c.is_ascii_digit()).map_or(0, |i| i + 1);
+    stem[start..end].parse().ok()
+}
+
+/// Index of the image whose step is closest to `step`
+fn image_for_step(pane: &Pane, step: u64) -> Option<usize> {
+    pane.img_cache.image_paths.iter()
+        .enumerate()
+        .filter_map(|(i, path)| step_from_name(&path.file_name()).map(|s| (i, s.abs_diff(step))))
+        .min_by_key(|&(_, distance)| distance)
+        .map(|(i, _)| i)
+}
+
+#[derive(Debug, Default)]
+pub struct MetricsPanel {
+    table: Option<Arc<MetricsTable>>,
+    column: usize,
+}
+
+impl MetricsPanel {
+    pub fn is_loaded(&self) -> bool {
+        self.table.is_some()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum MetricsMessage {
+    Load,
+    LoadFromPath(PathBuf),
+    Loaded(Result<Arc<MetricsTable>, String>),
+    /// Plot the next metric column
+    NextColumn,
+    /// Jump a pane to the image closest to a step (pane_index, step)
+    JumpToStep(usize, u64),
+    Close,
+}
+
+impl From<MetricsMessage> for Message {
+    fn from(msg: MetricsMessage) -> Self {
+        Message::MetricsAction(msg)
+    }
+}
+
+pub fn handle_metrics_message(msg: MetricsMessage, panes: &[Pane], panel: &mut MetricsPanel) -> Task<Message> {
+    match msg {
+        MetricsMessage::Load => Task::perform(
+            async {
+                rfd::AsyncFileDialog::new()
+                    .set_title("Select metrics CSV")
+                    .add_filter("CSV", &["csv"])
+                    .pick_file()
+                    .await
+            },
+            |file_handle| match file_handle {
+                Some(file) => MetricsMessage::LoadFromPath(file.path().to_path_buf()).into(),
+                None => Message::Nothing,
+            }
+        ),
+        MetricsMessage::LoadFromPath(path) => Task::perform(
+            async move {
+                let content = tokio::fs::read_to_string(&path).await
+                    .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                parse_csv(&path, &content).map(Arc::new)
+            },
+            |result| MetricsMessage::Loaded(result).into()
+        ),
+        MetricsMessage::Loaded(Ok(table)) => {
+            info!("Loaded {} metric rows ({}) from {}", table.steps.len(), table.columns.join(", "), table.source.display());
+            panel.table = Some(table);
+            panel.column = 0;
+            Task::none()
+        }
+        MetricsMessage::Loaded(Err(e)) => {
+            error!("Failed to load metrics: {}", e);
+            Task::none()
+        }
+        MetricsMessage::NextColumn => {
+            if let Some(table) = &panel.table {
+                panel.column = (panel.column + 1) % table.columns.len();
+            }
+            Task::none()
+        }
+        MetricsMessage::JumpToStep(pane_index, step) => {
+            match panes.get(pane_index).filter(|p| p.dir_loaded).and_then(|pane| image_for_step(pane, step)) {
+                Some(image_index) => Task::done(Message::Navigation(NavigationMessage::JumpToImage(pane_index, image_index))),
+                None => Task::none(),
+            }
+        }
+        MetricsMessage::Close => {
+            *panel = MetricsPanel::default();
+            Task::none()
+        }
+    }
+}
+
+/// Line plot of one column; clicking picks the nearest step
+struct MetricsPlot<'a> {
+    table: &'a MetricsTable,
+    column: usize,
+    current_step: Option<u64>,
+    pane_index: usize,
+}
+
+impl MetricsPlot<'_> {
+    fn step_range(&self) -> (f64, f64) {
+        let first = self.table.steps[0] as f64;
+        let last = *self.table.steps.last().unwrap_or(&0) as f64;
+        (first, if last > first { last } else { first + 1.0 })
+    }
+
+    fn x_for_step(&self, step: f64, width: f32) -> f32 {
+        let (first, last) = self.step_range();
+        ((step - first) / (last - first)) as f32 * width
+    }
+}
+
+impl canvas::Program<Message, WinitTheme, Renderer> for MetricsPlot<'_> {
+    type State = ();
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<Message>) {
+        let canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) = event else {
+            return (canvas::event::Status::Ignored, None);
+        };
+        let Some(position) = cursor.position_in(bounds) else {
+            return (canvas::event::Status::Ignored, None);
+        };
+        let (first, last) = self.step_range();
+        let step = first + (position.x / bounds.width) as f64 * (last - first);
+        let message = self.table.nearest_row(step)
+            .map(|row| MetricsMessage::JumpToStep(self.pane_index, self.table.steps[row]).into());
+        (canvas::event::Status::Captured, message)
+    }
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &WinitTheme,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry<Renderer>> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        frame.fill_rectangle(Point::ORIGIN, bounds.size(), Color::from_rgb(0.12, 0.12, 0.12));
+
+        let Some((lo, hi)) = self.table.value_range(self.column) else {
+            return vec![frame.into_geometry()];
+        };
+        let span = if hi > lo { hi - lo } else { 1.0 };
+        let (width, height) = (bounds.width, bounds.height - 4.0);
+        let y_for = |v: f64| 2.0 + height - ((v - lo) / span) as f32 * height;
+
+        // Break the line at missing values instead of drawing through them
+        let line = CanvasPath::new(|builder| {
+            let mut pen_down = false;
+            for (step, value) in self.table.steps.iter().zip(&self.table.values[self.column]) {
+                if !value.is_finite() {
+                    pen_down = false;
+                    continue;
+                }
+                let point = Point::new(self.x_for_step(*step as f64, width), y_for(*value));
+                if pen_down { builder.line_to(point) } else { builder.move_to(point) }
+                pen_down = true;
+            }
+        });
+        frame.stroke(&line, Stroke::default().with_color(Color::from_rgb(0.35, 0.65, 1.0)).with_width(1.5));
+
+        let marker = |frame: &mut canvas::Frame<Renderer>, x: f32, color: Color| {
+            frame.fill_rectangle(Point::new(x - 0.5, 0.0), Size::new(1.0, bounds.height), color);
+        };
+        if let Some(step) = self.current_step {
+            marker(&mut frame, self.x_for_step(step as f64, width), Color::from_rgb(1.0, 0.8, 0.2));
+        }
+        if let Some(position) = cursor.position_in(bounds) {
+            marker(&mut frame, position.x, Color::from_rgba(1.0, 1.0, 1.0, 0.35));
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(&self, _state: &Self::State, bounds: Rectangle, cursor: mouse::Cursor) -> mouse::Interaction {
+        if cursor.is_over(bounds) { mouse::Interaction::Pointer } else { mouse::Interaction::default() }
+    }
+}
+
+/// Plot of the loaded metrics for a pane, or nothing
+pub fn view<'a>(panel: &'a MetricsPanel, pane: &Pane, pane_index: usize) -> Element<'a, Message, WinitTheme, Renderer> {
+    let Some(table) = panel.table.as_deref() else {
+        return container(text("")).height(0).into();
+    };
+    let label_style = |_theme: &WinitTheme| iced_widget::text::Style {
+        color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+    };
+    let small_button = |label: String, msg: MetricsMessage| {
+        button(text(label).size(11))
+            .padding([1, 6])
+            .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+            .on_press(msg.into())
+    };
+
+    let current_step = pane.img_cache.image_paths.get(pane.img_cache.current_index)
+        .and_then(|path| step_from_name(&path.file_name()));
+    let current_value = current_step
+        .and_then(|step| table.nearest_row(step as f64))
+        .map(|row| table.values[panel.column][row]);
+    let label = match (current_step, current_value) {
+        (Some(step), Some(value)) => format!("{} @ {}: {:.4}", table.columns[panel.column], step, value),
+        _ => table.columns[panel.column].clone(),
+    };
+
+    let plot = canvas::Canvas::new(MetricsPlot { table, column: panel.column, current_step, pane_index })
+        .width(Length::Fill)
+        .height(Length::Fixed(PLOT_HEIGHT));
+
+    container(
+        row![
+            iced_widget::column![
+                text(label).size(11).style(label_style),
+                small_button("Next metric".to_string(), MetricsMessage::NextColumn),
+            ]
+            .spacing(4)
+            .width(Length::Fixed(160.0)),
+            plot,
+            small_button("x".to_string(), MetricsMessage::Close),
+        ]
+        .spacing(8)
+        .align_y(alignment::Vertical::Center)
+    )
+    .padding([4, 8])
+    .width(Length::Fill)
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_finds_step_column_and_sorts() {
+        let csv = "loss,\"step\",acc\n0.5,200,0.7\n0.9,100,\nbad,x,1\n0.4,300,0.8\n";
+        let table = parse_csv(Path::new("m.csv"), csv).unwrap();
+        assert_eq!(table.steps, vec![100, 200, 300]);
+        assert_eq!(table.columns, vec!["loss".to_string(), "acc".to_string()]);
+        assert_eq!(table.values[0], vec![0.9, 0.5, 0.4]);
+        assert!(table.values[1][0].is_nan());
+        assert_eq!(table.value_range(1), Some((0.7, 0.8)));
+
+        assert_eq!(table.nearest_row(0.0), Some(0));
+        assert_eq!(table.nearest_row(240.0), Some(1));
+        assert_eq!(table.nearest_row(260.0), Some(2));
+        assert_eq!(table.nearest_row(1e9), Some(2));
+
+        assert!(parse_csv(Path::new("m.csv"), "step\n1\n").is_err());
+    }
+
+    #[test]
+    fn test_step_from_name_uses_last_number() {
+        assert_eq!(step_from_name("step_001000.png"), Some(1000));
+        assert_eq!(step_from_name("run2_sample_450.jpg"), Some(450));
+        assert_eq!(step_from_name("00042.png"), Some(42));
+        assert_eq!(step_from_name("final.png"), None);
+    }
+}
@@ -7,5 +7,14 @@ pub mod shader;
 pub mod synced_image_split;
 pub mod easing;
 pub mod circular;
+pub mod overview_strip;
+pub mod timeline_strip;
+pub mod metrics_panel;
+pub mod lut_panel;
+pub mod exposure_panel;
+pub mod window_level;
+pub mod sidebar;
 #[cfg(feature = "selection")]
-pub mod selection_widget;
\ No newline at end of file
+pub mod selection_widget;
+#[cfg(feature = "embeddings")]
+pub mod similarity_widget;
\ No newline at end of file
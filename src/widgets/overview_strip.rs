@@ -0,0 +1,227 @@
+/// Contact-sheet style overview strip
+///
+/// Shows small proxies of the images around the current index above the slider.
+/// Proxies are decoded off the UI thread and cached per (pane, path), so the strip
+/// fills in once navigation settles; until then each slot shows its index.
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+use iced_winit::core::{Element, Color, Length};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::core::image::Handle;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{button, center, column, container, text, Row};
+use iced_core::alignment;
+use log::debug;
+
+use crate::app::{Message, NavigationMessage};
+use crate::cache::img_cache::PathSource;
+use crate::file_io::{decode_image_from_bytes, read_image_bytes};
+use crate::menu::PaneLayout;
+use crate::pane::Pane;
+
+/// Number of proxies shown, centered on the current index
+pub const OVERVIEW_COUNT: usize = 9;
+const THUMBNAIL_SIZE: u32 = 96;
+const THUMBNAIL_HEIGHT: f32 = 56.0;
+const MAX_CACHED_THUMBNAILS: usize = 512;
+
+pub type ThumbnailKey = (usize, PathBuf);    // (pane_index, image path)
+
+#[derive(Debug, Clone)]
+pub enum OverviewMessage {
+    /// Expand or collapse the strip
+    SetExpanded(bool),
+    /// Decoded proxies; None marks images that failed to decode
+    ThumbnailsLoaded(Vec<(ThumbnailKey, Option<Handle>)>),
+}
+
+impl From<OverviewMessage> for Message {
+    fn from(msg: OverviewMessage) -> Self {
+        Message::OverviewAction(msg)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct OverviewStrip {
+    pub expanded: bool,
+    thumbnails: HashMap<ThumbnailKey, Option<Handle>>,
+    pending: HashSet<ThumbnailKey>,
+}
+
+/// Indices of the proxies around `current`, shifted to stay inside `0..len`
+pub fn window(current: usize, len: usize) -> Range<usize> {
+    let count = OVERVIEW_COUNT.min(len);
+    let start = current.saturating_sub(OVERVIEW_COUNT / 2).min(len - count);
+    start..start + count
+}
+
+/// Pane the strip follows, or None when each pane has its own slider
+pub fn active_pane(pane_layout: &PaneLayout, is_slider_dual: bool) -> Option<usize> {
+    match pane_layout {
+        PaneLayout::SinglePane => Some(0),
+        PaneLayout::DualPane if !is_slider_dual => Some(0),
+        PaneLayout::DualPane => None,
+    }
+}
+
+/// Index shown by a pane, following the slider preview while it is dragged
+pub fn display_index(pane: &Pane, use_slider_image_for_render: bool) -> usize {
+    match pane.slider_image_position {
+        Some(position) if use_slider_image_for_render => position,
+        _ => pane.img_cache.current_index,
+    }
+}
+
+impl OverviewStrip {
+    /// Start decoding proxies in the current window that are neither cached nor in flight
+    pub fn request_thumbnails(&mut self, pane: &Pane, pane_index: usize, current: usize) -> Task<Message> {
+        if !self.expanded || !pane.dir_loaded || pane.img_cache.image_paths.is_empty() {
+            return Task::none();
+        }
+
+        let paths = &pane.img_cache.image_paths;
+        let missing: Vec<(ThumbnailKey, PathSource)> = window(current, paths.len())
+            .map(|i| ((pane_index, paths[i].path().clone()), paths[i].clone()))
+            .filter(|(key, _)| !self.thumbnails.contains_key(key) && !self.pending.contains(key))
+            .collect();
+        if missing.is_empty() {
+            return Task::none();
+        }
+
+        self.pending.extend(missing.iter().map(|(key, _)| key.clone()));
+        let archive_cache = Arc::clone(&pane.archive_cache);
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    missing.into_iter()
+                        .map(|(key, path_source)| {
+                            let handle = archive_cache.lock().ok()
+                                .and_then(|mut cache| read_image_bytes(&path_source, Some(&mut *cache)).ok())
+                                .and_then(|bytes| decode_image_from_bytes(&bytes).ok())
+                                .map(|image| {
+                                    let proxy = crate::utils::color::thumbnail(&image, THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgba8();
+                                    Handle::from_rgba(proxy.width(), proxy.height(), proxy.into_raw())
+                                });
+                            (key, handle)
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .await
+                .unwrap_or_default()
+            },
+            |thumbnails| Message::OverviewAction(OverviewMessage::ThumbnailsLoaded(thumbnails))
+        )
+    }
+
+    fn insert(&mut self, thumbnails: Vec<(ThumbnailKey, Option<Handle>)>) {
+        // Proxies are cheap to rebuild, so the cache is simply reset when it grows too large
+        if self.thumbnails.len() + thumbnails.len() > MAX_CACHED_THUMBNAILS {
+            debug!("Overview thumbnail cache full, clearing {} entries", self.thumbnails.len());
+            self.thumbnails.clear();
+        }
+        for (key, handle) in thumbnails {
+            self.pending.remove(&key);
+            self.thumbnails.insert(key, handle);
+        }
+    }
+
+    fn thumbnail(&self, pane_index: usize, path: &PathBuf) -> Option<&Handle> {
+        self.thumbnails.get(&(pane_index, path.clone())).and_then(Option::as_ref)
+    }
+}
+
+pub fn handle_overview_message(msg: OverviewMessage, overview: &mut OverviewStrip) -> Task<Message> {
+    match msg {
+        OverviewMessage::SetExpanded(expanded) => {
+            overview.expanded = expanded;
+            if !expanded {
+                overview.thumbnails.clear();
+                overview.pending.clear();
+            }
+        }
+        OverviewMessage::ThumbnailsLoaded(thumbnails) => overview.insert(thumbnails),
+    }
+    Task::none()
+}
+
+/// The strip, or a thin bar with an expand button when collapsed
+///
+/// Returns a zero-height container when the pane has nothing to navigate.
+pub fn view<'a>(
+    overview: &'a OverviewStrip,
+    pane: &'a Pane,
+    pane_index: usize,
+    current: usize,
+) -> Element<'a, Message, WinitTheme, Renderer> {
+    let toggle = button(text(if overview.expanded { "Overview -" } else { "Overview +" }).size(11))
+        .padding([1, 6])
+        .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+        .on_press(Message::OverviewAction(OverviewMessage::SetExpanded(!overview.expanded)));
+
+    let paths = &pane.img_cache.image_paths;
+    if !pane.dir_loaded || paths.len() < 2 {
+        return container(text("")).height(0).into();
+    }
+    if !overview.expanded {
+        return container(toggle).padding([0, 8]).into();
+    }
+
+    let label_style = |_theme: &WinitTheme| iced_widget::text::Style {
+        color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+    };
+
+    let proxies = window(current, paths.len()).fold(Row::new().spacing(4), |strip, index| {
+        let proxy: Element<'a, Message, WinitTheme, Renderer> = match overview.thumbnail(pane_index, paths[index].path()) {
+            Some(handle) => iced_widget::image(handle.clone())
+                .width(Length::Fill)
+                .height(Length::Fixed(THUMBNAIL_HEIGHT))
+                .into(),
+            None => center(text(format!("{}", index + 1)).size(11).style(label_style))
+                .height(Length::Fixed(THUMBNAIL_HEIGHT))
+                .into(),
+        };
+
+        let is_current = index == current;
+        strip.push(
+            button(
+                column![proxy, text(format!("{}", index + 1)).size(10).style(label_style)]
+                    .align_x(alignment::Horizontal::Center)
+            )
+            .padding(2)
+            .width(Length::FillPortion(1))
+            .style(move |theme: &WinitTheme, status| {
+                let mut style = crate::menu::button_style(theme, status, "labeled");
+                if is_current {
+                    style.border.color = theme.extended_palette().primary.strong.color;
+                }
+                style
+            })
+            .on_press(Message::Navigation(NavigationMessage::JumpToImage(pane_index, index)))
+        )
+    });
+
+    container(
+        column![toggle, proxies].spacing(2)
+    )
+    .padding([2, 8])
+    .width(Length::Fill)
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_centers_and_clamps() {
+        assert_eq!(window(50, 100), 46..55);
+        // Shifted to stay inside the list at both ends
+        assert_eq!(window(0, 100), 0..9);
+        assert_eq!(window(99, 100), 91..100);
+        // Fewer images than slots
+        assert_eq!(window(2, 4), 0..4);
+    }
+}
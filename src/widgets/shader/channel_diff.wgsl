@@ -0,0 +1,47 @@
+// Absolute difference of two textures over one region, on sRGB-encoded values.
+// Drawn as a single full-target triangle into a small Rgba8Unorm target.
+
+@group(0) @binding(0)
+var first_texture: texture_2d<f32>;
+
+@group(0) @binding(1)
+var second_texture: texture_2d<f32>;
+
+@group(0) @binding(2)
+var point_sampler: sampler;
+
+struct Region {
+    // left, top, right, bottom as fractions of the texture size
+    bounds: vec4<f32>,
+};
+
+@group(0) @binding(3)
+var<uniform> region: Region;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let corner = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(corner.x * 2.0 - 1.0, 1.0 - corner.y * 2.0, 0.0, 1.0);
+    out.uv = corner;
+    return out;
+}
+
+fn encode_srgb(linear: vec3<f32>) -> vec3<f32> {
+    let low = linear * 12.92;
+    let high = 1.055 * pow(linear, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, linear <= vec3<f32>(0.0031308));
+}
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let point = mix(region.bounds.xy, region.bounds.zw, uv);
+    let first = encode_srgb(textureSampleLevel(first_texture, point_sampler, point, 0.0).rgb);
+    let second = encode_srgb(textureSampleLevel(second_texture, point_sampler, point, 0.0).rgb);
+    return vec4<f32>(abs(first - second), 1.0);
+}
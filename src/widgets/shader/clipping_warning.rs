@@ -0,0 +1,30 @@
+/// Exposure clipping warning ("zebra stripes")
+///
+/// Pure-white and pure-black pixels of the displayed image are covered with diagonal stripes
+/// that move over time, so clipped highlights and crushed shadows stand out from image
+/// content. The test runs in the image shader on the sampled color, before any LUT or
+/// proofing. The toggle is read by the pipeline through a static, and while it is on the
+/// window keeps redrawing so the stripes animate.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use once_cell::sync::Lazy;
+
+/// The stripe phase wraps after this long; the shader's stripe speed and period divide it
+/// evenly so the wrap is seamless
+const PHASE_PERIOD_SECS: f32 = 60.0;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static START: Lazy<Instant> = Lazy::new(Instant::now);
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Seconds driving the stripe animation
+pub fn phase() -> f32 {
+    START.elapsed().as_secs_f32() % PHASE_PERIOD_SECS
+}
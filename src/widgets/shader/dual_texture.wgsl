@@ -0,0 +1,64 @@
+// Composes two pane textures into a single quad.
+// mode 0: side-by-side (first | second)
+// mode 1: cross-eye (second | first)
+// mode 2: red/cyan anaglyph (red from first, green/blue from second)
+// mode 3: second drawn over first at `opacity`
+
+@group(0) @binding(0)
+var first_texture: texture_2d<f32>;
+
+@group(0) @binding(1)
+var second_texture: texture_2d<f32>;
+
+@group(0) @binding(2)
+var my_sampler: sampler;
+
+struct Params {
+    mode: u32,
+    opacity: f32,
+    _padding: vec2<f32>,
+};
+
+@group(0) @binding(3)
+var<uniform> params: Params;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coords: vec2<f32>,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.tex_coords = tex_coords;
+    return out;
+}
+
+@fragment
+fn fs_main(@location(0) tex_coords: vec2<f32>) -> @location(0) vec4<f32> {
+    // Both halves are sampled unconditionally to keep control flow uniform
+    let left_uv = vec2<f32>(tex_coords.x * 2.0, tex_coords.y);
+    let right_uv = vec2<f32>(tex_coords.x * 2.0 - 1.0, tex_coords.y);
+    let first_left = textureSample(first_texture, my_sampler, left_uv);
+    let second_left = textureSample(second_texture, my_sampler, left_uv);
+    let first_right = textureSample(first_texture, my_sampler, right_uv);
+    let second_right = textureSample(second_texture, my_sampler, right_uv);
+    let first = textureSample(first_texture, my_sampler, tex_coords);
+    let second = textureSample(second_texture, my_sampler, tex_coords);
+
+    let on_left = tex_coords.x < 0.5;
+    if (params.mode == 0u) {
+        return select(second_right, first_left, on_left);
+    }
+    if (params.mode == 1u) {
+        return select(first_right, second_left, on_left);
+    }
+    if (params.mode == 2u) {
+        return vec4<f32>(first.r, second.g, second.b, 1.0);
+    }
+    return mix(first, second, params.opacity);
+}
@@ -0,0 +1,253 @@
+use std::sync::Arc;
+use iced_core::Rectangle;
+use iced_wgpu::wgpu::{self, util::DeviceExt};
+
+/// Uniforms for `dual_texture.wgsl` (padded to 16 bytes)
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct DualTextureParams {
+    pub mode: u32,
+    pub opacity: f32,
+    pub _padding: [f32; 2],
+}
+
+/// Pipeline that composes the textures of both panes into one quad
+///
+/// Unlike `TexturePipeline`, the quad is rewritten on every prepare since the
+/// composed image changes aspect ratio when the mode or the pair changes.
+#[derive(Debug)]
+pub struct DualTexturePipeline {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    sampler: wgpu::Sampler,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    params_buffer: wgpu::Buffer,
+    textures: (Arc<wgpu::Texture>, Arc<wgpu::Texture>),
+}
+
+impl DualTexturePipeline {
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        textures: (Arc<wgpu::Texture>, Arc<wgpu::Texture>),
+    ) -> Self {
+        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Dual Texture Vertex Buffer"),
+            size: 16 * std::mem::size_of::<f32>() as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let indices: &[u16] = &[0, 1, 2, 2, 3, 0];
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Dual Texture Index Buffer"),
+            contents: bytemuck::cast_slice(indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Dual Texture Params Buffer"),
+            contents: bytemuck::bytes_of(&DualTextureParams { mode: 0, opacity: 1.0, _padding: [0.0; 2] }),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Dual Texture Bind Group Layout"),
+            entries: &[
+                texture_entry(0),
+                texture_entry(1),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let bind_group = Self::create_bind_group(device, &bind_group_layout, &sampler, &params_buffer, &textures);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Dual Texture Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./dual_texture.wgsl").into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Dual Texture Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Dual Texture Render Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: 4 * std::mem::size_of::<f32>() as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2],
+                }],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+            bind_group,
+            sampler,
+            vertex_buffer,
+            index_buffer,
+            params_buffer,
+            textures,
+        }
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        params_buffer: &wgpu::Buffer,
+        textures: &(Arc<wgpu::Texture>, Arc<wgpu::Texture>),
+    ) -> wgpu::BindGroup {
+        let first_view = crate::cache::texture_tiles::first_layer_view(&textures.0);
+        let second_view = crate::cache::texture_tiles::first_layer_view(&textures.1);
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Dual Texture Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&first_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&second_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: params_buffer.as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    /// Rebind the textures if either pane moved to a different image
+    pub fn update_textures(&mut self, device: &wgpu::Device, textures: (Arc<wgpu::Texture>, Arc<wgpu::Texture>)) {
+        if Arc::ptr_eq(&self.textures.0, &textures.0) && Arc::ptr_eq(&self.textures.1, &textures.1) {
+            return;
+        }
+        self.textures = textures;
+        self.bind_group = Self::create_bind_group(
+            device, &self.bind_group_layout, &self.sampler, &self.params_buffer, &self.textures
+        );
+    }
+
+    /// Write the quad position (relative to the viewport) and the shader parameters
+    pub fn update(&self, queue: &wgpu::Queue, bounds_relative: (f32, f32, f32, f32), params: DualTextureParams) {
+        let (x, y, width, height) = bounds_relative;
+
+        // Convert to NDC coordinates (-1 to 1)
+        let left = 2.0 * x - 1.0;
+        let right = 2.0 * (x + width) - 1.0;
+        let top = 1.0 - 2.0 * y;
+        let bottom = 1.0 - 2.0 * (y + height);
+
+        // Format: [position.x, position.y, texcoord.x, texcoord.y]
+        let vertices: [f32; 16] = [
+            left, bottom, 0.0, 1.0,   // Bottom-left
+            right, bottom, 1.0, 1.0,  // Bottom-right
+            right, top, 1.0, 0.0,     // Top-right
+            left, top, 0.0, 0.0,      // Top-left
+        ];
+
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+    }
+
+    pub fn render(
+        &self,
+        target: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+        clip_bounds: &Rectangle<u32>,
+    ) {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Dual Texture Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+
+        pass.set_scissor_rect(
+            clip_bounds.x,
+            clip_bounds.y,
+            clip_bounds.width,
+            clip_bounds.height,
+        );
+
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        pass.draw_indexed(0..6, 0, 0..1);
+    }
+}
@@ -8,7 +8,7 @@ use iced_core::{Vector, Point};
 use iced_core::layout::Layout;
 use iced_core::clipboard::Clipboard;
 use iced_core::event;
-use iced_winit::core::{self, layout, mouse, renderer, widget::{self, tree::{self, Tree}}, Element, Length, Rectangle, Shell, Size};
+use iced_winit::core::{self, keyboard, layout, mouse, renderer, widget::{self, tree::{self, Tree}}, Element, Length, Rectangle, Shell, Size};
 use iced_widget::shader::{self, Viewport, Storage};
 use iced_wgpu::{wgpu, primitive};
 use crate::widgets::shader::texture_pipeline::TexturePipeline;
@@ -18,6 +18,12 @@ use std::collections::VecDeque;
 use crate::widgets::split::DIVIDER_HITBOX_EXPANSION;
 use crate::CONFIG;
 
+/// Size of the image in a texture as a layout size; a tiled texture's own size is one tile
+fn image_size(texture: &wgpu::Texture) -> Size {
+    let (width, height) = crate::cache::texture_tiles::image_size(texture);
+    Size::new(width as f32, height as f32)
+}
+
 /// A specialized shader widget for displaying images with proper aspect ratio.
 pub struct ImageShader<Message> {
     width: Length,
@@ -33,6 +39,7 @@ pub struct ImageShader<Message> {
     is_horizontal_split: bool,
     mouse_wheel_zoom: bool,
     ctrl_pressed: bool,
+    report_content_bounds: bool,
     #[cfg(feature = "coco")]
     pane_index: usize,
     #[cfg(feature = "coco")]
@@ -42,6 +49,7 @@ pub struct ImageShader<Message> {
     initial_scale: Option<f32>,
     initial_offset: Option<Vector>,
     use_nearest_filter: bool,
+    zoom_lock_pane: Option<usize>,
 }
 
 impl<Message> ImageShader<Message> {
@@ -79,6 +87,7 @@ impl<Message> ImageShader<Message> {
             is_horizontal_split: false,
             mouse_wheel_zoom: false,
             ctrl_pressed: false,
+            report_content_bounds: false,
             #[cfg(feature = "coco")]
             pane_index: 0,
             #[cfg(feature = "coco")]
@@ -88,6 +97,7 @@ impl<Message> ImageShader<Message> {
             initial_scale: None,
             initial_offset: None,
             use_nearest_filter: false,
+            zoom_lock_pane: None,
         }
     }
 
@@ -161,7 +171,7 @@ impl<Message> ImageShader<Message> {
             if let Some(texture) = scene.get_texture() {
                 debug!("ImageShader::calculate_layout - Got texture {}x{}", texture.width(), texture.height());
 
-                let texture_size = Size::new(texture.width() as f32, texture.height() as f32);
+                let texture_size = image_size(texture);
                 let bounds_size = bounds.size();
 
                 // Calculate image size based on content fit
@@ -239,6 +249,8 @@ pub struct ImageShaderState {
     pub last_click_time: Option<std::time::Instant>,
     #[allow(dead_code)]
     pub last_image_index: usize,  // Track image index to detect image changes
+    /// Scale and offset to go back to when the temporary zoom key is released
+    pub held_zoom: Option<(f32, Vector)>,
 }
 
 impl ImageShaderState {
@@ -250,6 +262,7 @@ impl ImageShaderState {
             cursor_grabbed_at: None,
             last_click_time: None,
             last_image_index: 0,
+            held_zoom: None,
         }
     }
 
@@ -311,7 +324,13 @@ impl shader::Primitive for ImagePrimitive {
                 debug!("ImagePrimitive::prepare - Got texture {}x{}", texture.width(), texture.height());
             }
 
-            let texture_size = (texture.width(), texture.height());
+            // Draw the frame that is due when the image is an animation, or the full decode of
+            // a reduced image zoomed past 1:1
+            let texture = &crate::animation::current_frame(texture)
+                .or_else(|| crate::display_decode::full_texture(texture))
+                .unwrap_or_else(|| Arc::clone(texture));
+
+            let texture_size = crate::cache::texture_tiles::image_size(texture);
 
             // Calculate normalized device coordinates for viewport
             let x_rel = self.content_bounds.x * scale_factor / viewport_size.width as f32;
@@ -366,6 +385,9 @@ impl shader::Primitive for ImagePrimitive {
                         debug!("ImagePrimitive::prepare - Updating texture in existing pipeline");
                     }
                     pipeline.update_texture(device, queue, Arc::clone(texture), self.use_nearest_filter);
+                    pipeline.update_filter_options(queue, self.use_nearest_filter);
+                    pipeline.refresh_user_shader(device);
+                    pipeline.refresh_lut(device, queue);
                 }
             }
         } else {
@@ -598,6 +620,31 @@ where
             }
         };
 
+        // Show the part of the image an opened session asks for, once the image is there
+        if let (Some(pane_index), Some(texture)) = (self.zoom_lock_pane, self.scene.as_ref().and_then(|scene| scene.get_texture())) {
+            if let Some(view) = crate::zoom_lock::take_requested_view(pane_index) {
+                let state = tree.state.downcast_mut::<ImageShaderState>();
+                let texture_size = image_size(texture);
+                let base_size = self.calculate_scaled_size(bounds.size(), 1.0);
+                let region = crate::zoom_lock::LockedRegion::fitting(texture_size, bounds.size(), view);
+                (state.scale, state.current_offset) = region.apply(texture_size, base_size, self.min_scale, self.max_scale);
+            }
+        }
+
+        // Follow the zoom lock; zoom and pan input is ignored while it is held
+        let locked = {
+            let state = tree.state.downcast_mut::<ImageShaderState>();
+            match self.locked_zoom(state, bounds) {
+                Some((scale, offset)) => {
+                    state.scale = scale;
+                    state.current_offset = offset;
+                    state.cursor_grabbed_at = None;
+                    true
+                }
+                None => false,
+            }
+        };
+
         // Detect image change and sync zoom state to Pane
         #[cfg(feature = "coco")]
         {
@@ -614,9 +661,20 @@ where
             }
         }
 
+        // A reduced decode shown larger than it is gets decoded in full
+        if let Some(texture) = self.scene.as_ref().and_then(|scene| scene.get_texture()) {
+            let state = tree.state.downcast_ref::<ImageShaderState>();
+            let shown_width = self.calculate_scaled_size(bounds.size(), state.scale).width;
+            crate::display_decode::note_shown_width(texture, shown_width);
+        }
+
+        if locked {
+            return event::Status::Ignored;
+        }
+
         match event {
             core::Event::Mouse(mouse::Event::WheelScrolled { delta }) => {
-                if !self.mouse_wheel_zoom && !self.ctrl_pressed {
+                if !wheel_zooms(self.mouse_wheel_zoom, self.ctrl_pressed) {
                     // log::debug!("image shader mouse scroll ignored");
                     return event::Status::Ignored;
                 }
@@ -634,35 +692,12 @@ where
                         if y < 0.0 && previous_scale > self.min_scale
                             || y > 0.0 && previous_scale < self.max_scale
                         {
-                            state.scale = (if y > 0.0 {
+                            let scale = if y > 0.0 {
                                 state.scale * (1.0 + self.scale_step)
                             } else {
                                 state.scale / (1.0 + self.scale_step)
-                            })
-                            .clamp(self.min_scale, self.max_scale);
-
-                            // Calculate the scaled size
-                            let scaled_size = self.calculate_scaled_size(bounds.size(), state.scale);
-
-                            let factor = state.scale / previous_scale - 1.0;
-
-                            let cursor_to_center = cursor_position - bounds.center();
-
-                            let adjustment = cursor_to_center * factor
-                                + state.current_offset * factor;
-
-                            state.current_offset = Vector::new(
-                                if scaled_size.width > bounds.width {
-                                    state.current_offset.x + adjustment.x
-                                } else {
-                                    0.0
-                                },
-                                if scaled_size.height > bounds.height {
-                                    state.current_offset.y + adjustment.y
-                                } else {
-                                    0.0
-                                },
-                            );
+                            };
+                            self.zoom_at(state, bounds, cursor_position, scale);
 
                             if self.debug {
                                 debug!("ImageShader::on_event - New scale: {}", state.scale);
@@ -796,6 +831,63 @@ where
                     event::Status::Ignored
                 }
             }
+            // Hold G to look at the image at 100% around the cursor; 0 steps through fit,
+            // 100% and 200%
+            core::Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. })
+                if !modifiers.command() && !modifiers.alt() =>
+            {
+                let Some(cursor_position) = cursor.position_over(effective_bounds) else {
+                    return event::Status::Ignored;
+                };
+                let Some(one_to_one) = self.one_to_one_scale(bounds) else {
+                    return event::Status::Ignored;
+                };
+                let state = tree.state.downcast_mut::<ImageShaderState>();
+
+                match key.as_ref() {
+                    keyboard::Key::Character("g") => {
+                        // Key repeat sends more presses while the key is held
+                        if state.held_zoom.is_none() {
+                            state.held_zoom = Some((state.scale, state.current_offset));
+                            self.zoom_at(state, bounds, cursor_position, one_to_one);
+                        }
+                    }
+                    keyboard::Key::Character("0") => {
+                        state.held_zoom = None;
+                        let scale = next_cycle_scale(state.scale, one_to_one);
+                        self.zoom_at(state, bounds, cursor_position, scale);
+                    }
+                    _ => return event::Status::Ignored,
+                }
+
+                #[cfg(feature = "coco")]
+                if let Some(ref callback) = self.on_zoom_change {
+                    let message = callback(self.pane_index, state.scale, state.current_offset);
+                    shell.publish(message);
+                }
+
+                event::Status::Captured
+            }
+            core::Event::Keyboard(keyboard::Event::KeyReleased { key, .. }) => {
+                let state = tree.state.downcast_mut::<ImageShaderState>();
+
+                match (key.as_ref(), state.held_zoom) {
+                    (keyboard::Key::Character("g"), Some((scale, offset))) => {
+                        state.held_zoom = None;
+                        state.scale = scale;
+                        state.current_offset = offset;
+
+                        #[cfg(feature = "coco")]
+                        if let Some(ref callback) = self.on_zoom_change {
+                            let message = callback(self.pane_index, state.scale, state.current_offset);
+                            shell.publish(message);
+                        }
+
+                        event::Status::Captured
+                    }
+                    _ => event::Status::Ignored,
+                }
+            }
             _ => event::Status::Ignored,
         }
     }
@@ -838,7 +930,11 @@ where
         if let Some(scene) = &self.scene {
             let bounds = layout.bounds();
 
-            let state = tree.state.downcast_ref::<ImageShaderState>();
+            let mut state = *tree.state.downcast_ref::<ImageShaderState>();
+            if let Some((scale, offset)) = self.locked_zoom(&state, bounds) {
+                state.scale = scale;
+                state.current_offset = offset;
+            }
 
             // Calculate scaled content bounds with proper aspect ratio
             let scaled_size = self.calculate_scaled_size(bounds.size(), state.scale);
@@ -849,12 +945,20 @@ where
             // Apply content fit with scaling
             let content_bounds = self.calculate_content_bounds(bounds, scaled_size, offset);
 
+            if let Some(pane_index) = self.zoom_lock_pane {
+                crate::noise_analysis::set_visible_region(pane_index, bounds, content_bounds);
+            }
+
             if self.debug {
                 debug!("ImageShader::draw - Scene available");
                 debug!("ImageShader::draw - Layout bounds: {:?}", bounds);
                 debug!("ImageShader::draw - Content bounds: {:?}", content_bounds);
             }
 
+            if self.report_content_bounds {
+                crate::plugins::overlay::set_content_bounds(content_bounds);
+            }
+
             if scene.get_texture().is_some() {
                 debug!("ImageShader::draw - Creating primitive with use_nearest_filter = {}", self.use_nearest_filter);
                 let primitive = ImagePrimitive {
@@ -888,12 +992,68 @@ where
     }
 }
 
+/// Whether the mouse wheel zooms rather than moving to the next or previous image. Plain
+/// wheel moves and Ctrl + wheel zooms, or the other way around with the mouse wheel zoom
+/// setting on. The app's event handler and `SyncedImageSplit` ask the same question, so the
+/// panes never zoom on a scroll that also changes the image.
+pub fn wheel_zooms(mouse_wheel_zoom: bool, ctrl_pressed: bool) -> bool {
+    mouse_wheel_zoom != ctrl_pressed
+}
+
+/// Scale after `scale` when stepping through fit (1.0), 100% (`one_to_one`) and 200%;
+/// stages that look the same as the current one are skipped
+fn next_cycle_scale(scale: f32, one_to_one: f32) -> f32 {
+    let stages = [1.0, one_to_one, one_to_one * 2.0];
+    let near = |a: f32, b: f32| (a - b).abs() <= b * 0.01;
+    match stages.iter().position(|&stage| near(scale, stage)) {
+        Some(i) => stages[i + 1..].iter().copied().find(|&stage| !near(scale, stage)).unwrap_or(1.0),
+        None => 1.0,
+    }
+}
+
 impl<Message> ImageShader<Message> {
+    /// Zooms to `scale`, keeping the point of the image under the cursor where it is
+    fn zoom_at(&self, state: &mut ImageShaderState, bounds: Rectangle, cursor_position: Point, scale: f32) {
+        let previous_scale = state.scale;
+        state.scale = scale.clamp(self.min_scale, self.max_scale);
+
+        // Calculate the scaled size
+        let scaled_size = self.calculate_scaled_size(bounds.size(), state.scale);
+
+        let factor = state.scale / previous_scale - 1.0;
+
+        let cursor_to_center = cursor_position - bounds.center();
+
+        let adjustment = cursor_to_center * factor
+            + state.current_offset * factor;
+
+        state.current_offset = Vector::new(
+            if scaled_size.width > bounds.width {
+                state.current_offset.x + adjustment.x
+            } else {
+                0.0
+            },
+            if scaled_size.height > bounds.height {
+                state.current_offset.y + adjustment.y
+            } else {
+                0.0
+            },
+        );
+    }
+
+    /// Scale at which one pixel of the full image covers one physical screen pixel
+    fn one_to_one_scale(&self, bounds: Rectangle) -> Option<f32> {
+        let texture = self.scene.as_ref()?.get_texture()?;
+        let (full_width, _) = crate::display_decode::full_size(texture);
+        let base_width = self.calculate_scaled_size(bounds.size(), 1.0).width;
+        (base_width > 0.0).then(|| full_width as f32 / crate::display_decode::scale_factor() / base_width)
+    }
+
     // Helper method to calculate scaled size based on content fit
     fn calculate_scaled_size(&self, bounds_size: Size, scale: f32) -> Size {
         if let Some(ref scene) = self.scene {
             if let Some(texture) = scene.get_texture() {
-                let texture_size = Size::new(texture.width() as f32, texture.height() as f32);
+                let texture_size = image_size(texture);
 
                 // Calculate base size according to content fit
                 let base_size = match self.content_fit {
@@ -956,6 +1116,12 @@ impl<Message> ImageShader<Message> {
         self
     }
 
+    /// Record where the image is drawn, for the shapes of overlay plugins
+    pub fn report_content_bounds(mut self, report: bool) -> Self {
+        self.report_content_bounds = report;
+        self
+    }
+
     /// Set the pane index for COCO zoom message emission
     #[cfg(feature = "coco")]
     pub fn pane_index(mut self, pane_index: usize) -> Self {
@@ -984,4 +1150,54 @@ impl<Message> ImageShader<Message> {
         self.use_nearest_filter = use_nearest;
         self
     }
-}
\ No newline at end of file
+
+    /// Follow the zoom lock of the given pane, and report the visible region for noise
+    /// measurements
+    pub fn zoom_lock_pane(mut self, pane_index: usize) -> Self {
+        self.zoom_lock_pane = Some(pane_index);
+        self
+    }
+
+    /// Scale and offset showing the locked region, if the pane's zoom is locked
+    fn locked_zoom(&self, state: &ImageShaderState, bounds: Rectangle) -> Option<(f32, Vector)> {
+        let pane_index = self.zoom_lock_pane?;
+        let texture = self.scene.as_ref()?.get_texture()?;
+        let texture_size = image_size(texture);
+        let region = crate::zoom_lock::region(pane_index, || {
+            let scaled_size = self.calculate_scaled_size(bounds.size(), state.scale);
+            crate::zoom_lock::LockedRegion::capture(texture_size, scaled_size, state.offset(bounds, scaled_size))
+        })?;
+        let base_size = self.calculate_scaled_size(bounds.size(), 1.0);
+        Some(region.apply(texture_size, base_size, self.min_scale, self.max_scale))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wheel_zooms() {
+        assert!(!wheel_zooms(false, false));
+        assert!(wheel_zooms(false, true));
+        // With the setting on, Ctrl + wheel navigates instead
+        assert!(wheel_zooms(true, false));
+        assert!(!wheel_zooms(true, true));
+    }
+
+    #[test]
+    fn test_next_cycle_scale() {
+        // A large image: fit, 100%, 200% and back
+        assert_eq!(next_cycle_scale(1.0, 3.0), 3.0);
+        assert_eq!(next_cycle_scale(3.0, 3.0), 6.0);
+        assert_eq!(next_cycle_scale(6.0, 3.0), 1.0);
+        // Anywhere else goes back to fit
+        assert_eq!(next_cycle_scale(1.7, 3.0), 1.0);
+        // An image shown at 100% when fit skips the repeated stage
+        assert_eq!(next_cycle_scale(1.0, 1.0), 2.0);
+        assert_eq!(next_cycle_scale(2.0, 1.0), 1.0);
+        // A small image enlarged to fit
+        assert_eq!(next_cycle_scale(1.0, 0.5), 0.5);
+        assert_eq!(next_cycle_scale(0.5, 0.5), 1.0);
+    }
+}
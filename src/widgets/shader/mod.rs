@@ -2,4 +2,9 @@ pub mod scene;
 pub mod texture_pipeline;
 pub mod texture_scene;
 pub mod cpu_scene;
-pub mod image_shader;
\ No newline at end of file
+pub mod image_shader;
+pub mod dual_texture_pipeline;
+pub mod stereo_scene;
+pub mod user_shader;
+pub mod clipping_warning;
+pub mod diff_readback;
@@ -210,6 +210,9 @@ impl shader::Primitive for Primitive {
         } else {
             let pipeline = storage.get_mut::<TexturePipeline>().unwrap();
             pipeline.update_texture(device, queue, self.texture.clone(), false);
+            pipeline.refresh_user_shader(device);
+            pipeline.refresh_lut(device, queue);
+            pipeline.update_filter_options(queue, false);
         }
     }
 
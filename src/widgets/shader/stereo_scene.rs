@@ -0,0 +1,151 @@
+use std::sync::Arc;
+use iced_core::{Point, Size, ContentFit};
+use iced_widget::shader::{self, Viewport};
+use iced_winit::core::{Rectangle, mouse};
+use iced_wgpu::wgpu;
+use crate::widgets::shader::dual_texture_pipeline::{DualTexturePipeline, DualTextureParams};
+
+/// How a left/right pair from the two panes is composed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StereoMode {
+    #[default]
+    Off,
+    SideBySide,
+    CrossEye,
+    Anaglyph,
+}
+
+impl StereoMode {
+    pub const ALL: [StereoMode; 4] = [
+        StereoMode::Off,
+        StereoMode::SideBySide,
+        StereoMode::CrossEye,
+        StereoMode::Anaglyph,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            StereoMode::Off => "Off",
+            StereoMode::SideBySide => "Side-by-side",
+            StereoMode::CrossEye => "Cross-eye",
+            StereoMode::Anaglyph => "Red/cyan anaglyph",
+        }
+    }
+
+    /// Mode index understood by `dual_texture.wgsl`
+    fn shader_mode(&self) -> u32 {
+        match self {
+            StereoMode::Off | StereoMode::SideBySide => 0,
+            StereoMode::CrossEye => 1,
+            StereoMode::Anaglyph => 2,
+        }
+    }
+
+    /// Size of the composed image for a pair of the given dimensions
+    fn composed_size(&self, left: (u32, u32), right: (u32, u32)) -> Size {
+        let width = left.0.max(right.0) as f32;
+        let height = left.1.max(right.1) as f32;
+        match self {
+            StereoMode::Anaglyph | StereoMode::Off => Size::new(width, height),
+            StereoMode::SideBySide | StereoMode::CrossEye => Size::new(width * 2.0, height),
+        }
+    }
+}
+
+/// Shader program that draws the current images of both panes as a stereo pair
+#[derive(Debug, Clone)]
+pub struct StereoScene {
+    pub left: Arc<wgpu::Texture>,
+    pub right: Arc<wgpu::Texture>,
+    pub mode: StereoMode,
+}
+
+impl StereoScene {
+    pub fn new(left: Arc<wgpu::Texture>, right: Arc<wgpu::Texture>, mode: StereoMode) -> Self {
+        Self { left, right, mode }
+    }
+}
+
+#[derive(Debug)]
+pub struct StereoPrimitive {
+    left: Arc<wgpu::Texture>,
+    right: Arc<wgpu::Texture>,
+    mode: StereoMode,
+    content_bounds: Rectangle,
+}
+
+impl shader::Primitive for StereoPrimitive {
+    fn prepare(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        storage: &mut shader::Storage,
+        _bounds: &Rectangle,
+        viewport: &Viewport,
+    ) {
+        let scale_factor = viewport.scale_factor() as f32;
+        let viewport_size = viewport.physical_size();
+        let bounds_relative = (
+            self.content_bounds.x * scale_factor / viewport_size.width as f32,
+            self.content_bounds.y * scale_factor / viewport_size.height as f32,
+            self.content_bounds.width * scale_factor / viewport_size.width as f32,
+            self.content_bounds.height * scale_factor / viewport_size.height as f32,
+        );
+
+        let textures = (Arc::clone(&self.left), Arc::clone(&self.right));
+        if !storage.has::<DualTexturePipeline>() {
+            storage.store(DualTexturePipeline::new(device, format, textures));
+        } else {
+            storage.get_mut::<DualTexturePipeline>().unwrap().update_textures(device, textures);
+        }
+
+        let params = DualTextureParams {
+            mode: self.mode.shader_mode(),
+            opacity: 1.0,
+            _padding: [0.0; 2],
+        };
+        storage.get::<DualTexturePipeline>().unwrap().update(queue, bounds_relative, params);
+    }
+
+    fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        storage: &shader::Storage,
+        target: &wgpu::TextureView,
+        clip_bounds: &Rectangle<u32>,
+    ) {
+        if let Some(pipeline) = storage.get::<DualTexturePipeline>() {
+            pipeline.render(target, encoder, clip_bounds);
+        }
+    }
+}
+
+impl<Message> shader::Program<Message> for StereoScene {
+    type State = ();
+    type Primitive = StereoPrimitive;
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        _cursor: mouse::Cursor,
+        bounds: Rectangle,
+    ) -> Self::Primitive {
+        let image_size = self.mode.composed_size(
+            (self.left.width(), self.left.height()),
+            (self.right.width(), self.right.height()),
+        );
+        let fitted_size = ContentFit::Contain.fit(image_size, bounds.size());
+
+        // Centered in the widget bounds
+        let x = bounds.x + (bounds.width - fitted_size.width) / 2.0;
+        let y = bounds.y + (bounds.height - fitted_size.height) / 2.0;
+
+        StereoPrimitive {
+            left: Arc::clone(&self.left),
+            right: Arc::clone(&self.right),
+            mode: self.mode,
+            content_bounds: Rectangle::new(Point::new(x, y), fitted_size),
+        }
+    }
+}
@@ -4,12 +4,55 @@ var my_texture: texture_2d<f32>;
 @group(0) @binding(1)
 var my_sampler: sampler;
 
+struct FilterOptions {
+    gamma_space: u32,   // 1 = average samples on sRGB-encoded values (debug comparison)
+    supersample: u32,   // 0 when nearest-neighbor filtering is selected
+    lut_enabled: u32,   // 1 when a .cube LUT is active
+    lut_intensity: f32, // 0 = original, 1 = fully graded
+    lut_domain_min: vec4<f32>,
+    lut_domain_max: vec4<f32>,
+    display_matrix: array<vec4<f32>, 3>, // rows of linear sRGB -> linear display RGB
+    display_gamma: vec4<f32>,            // display tone curve per channel; w = 1 when a profile is active
+    proof_colors: u32,  // 1 to show colors as the soft-proofing profile reproduces them
+    gamut_warning: u32, // 1 to paint colors outside its gamut gray
+    clipping_warning: u32, // 1 to stripe pure-white and pure-black pixels
+    time: f32,             // seconds, wrapping every 60, for the stripe animation
+    white_balance_gains: vec4<f32>, // linear per-channel gains; w = 1 while the preview is on
+    white_balance_split: f32,       // texture x left of which the original is shown
+    tile_grid: vec4<u32>,   // columns, rows, then image pixels per tile along x and y
+    image_size: vec4<f32>,  // width and height of the whole image, then the tile margin
+    pixel_format: u32,      // 0 = RGBA, 1 = sRGB-encoded gray in r, 2 = palette index in r, 3 = HDR,
+                            // 4 = 16-bit gray, high byte in r and low byte in g
+    exposure_scale: f32,    // 2^stops of exposure for HDR images
+    exposure_power: f32,    // applied after exposure; 1 keeps the sRGB output curve
+    window_low: f32,        // 16-bit gray shown as black, as a fraction of the range
+    window_high: f32,       // 16-bit gray shown as white
+    _padding0: u32,
+    _padding1: u32,
+    _padding2: u32,
+};
+
 @group(0) @binding(2)
-var<uniform> texture_rect: vec4<f32>; // {offset_x, offset_y, scale_x, scale_y}
+var<uniform> options: FilterOptions;
 
 @group(0) @binding(3)
 var<uniform> screen_rect: vec4<f32>; // {scaled_width, scaled_height, offset_x, offset_y}
 
+@group(0) @binding(4)
+var lut_texture: texture_3d<f32>;
+
+@group(0) @binding(5)
+var lut_sampler: sampler;
+
+@group(0) @binding(6)
+var proof_texture: texture_3d<f32>; // rgb = proofed color (sRGB-encoded), a = 1 when out of gamut
+
+@group(0) @binding(7)
+var image_tiles: texture_2d_array<f32>; // same texture as my_texture, one layer per tile
+
+@group(0) @binding(8)
+var palette_texture: texture_2d<f32>; // 256x1 colors of an indexed image
+
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
@@ -29,8 +72,199 @@ fn vs_main(
     return out;
 }
 
-@fragment
-fn fs_main(@location(0) tex_coords: vec2<f32>) -> @location(0) vec4<f32> {
-    let color = textureSample(my_texture, my_sampler, tex_coords);
+fn linear_to_srgb(c: vec3<f32>) -> vec3<f32> {
+    let low = c * 12.92;
+    let high = 1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, c <= vec3<f32>(0.0031308));
+}
+
+fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    let low = c / 12.92;
+    let high = pow((c + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, c <= vec3<f32>(0.04045));
+}
+
+// Grayscale and indexed images are single-channel `R8Unorm` textures, never tiled. Gray
+// levels are stored sRGB-encoded, so they are filtered in encoded space and converted after.
+// Palette indices can't be blended; the nearest texel's index is looked up in the palette.
+fn sample_single_channel(tex_coords: vec2<f32>) -> vec4<f32> {
+    if (options.pixel_format == 1u) {
+        let gray = textureSampleLevel(my_texture, my_sampler, tex_coords, 0.0).r;
+        return vec4<f32>(srgb_to_linear(vec3<f32>(gray)), 1.0);
+    }
+    if (options.pixel_format == 4u) {
+        // Both bytes filter linearly, so recombining them filters the 16-bit value
+        let bytes = textureSampleLevel(my_texture, my_sampler, tex_coords, 0.0).rg;
+        let value = (bytes.r * 65280.0 + bytes.g * 255.0) / 65535.0;
+        let span = max(options.window_high - options.window_low, 1.0 / 65535.0);
+        let gray = clamp((value - options.window_low) / span, 0.0, 1.0);
+        return vec4<f32>(srgb_to_linear(vec3<f32>(gray)), 1.0);
+    }
+    let size = textureDimensions(my_texture);
+    let texel = min(vec2<u32>(clamp(tex_coords, vec2<f32>(0.0), vec2<f32>(1.0)) * vec2<f32>(size)), size - vec2<u32>(1u));
+    let index = u32(textureLoad(my_texture, texel, 0).r * 255.0 + 0.5);
+    return textureLoad(palette_texture, vec2<u32>(index, 0u), 0);
+}
+
+// An image larger than the GPU's texture limit is a grid of tiles, the layers of
+// `image_tiles`, each repeating `image_size.z` texels of its neighbours around its edges so
+// bilinear taps never clamp. The tile holding a coordinate is found from the per-tile stride.
+fn sample_image(tex_coords: vec2<f32>) -> vec4<f32> {
+    let grid = options.tile_grid;
+    if (options.pixel_format == 1u || options.pixel_format == 2u || options.pixel_format == 4u) {
+        return sample_single_channel(tex_coords);
+    }
+    if (grid.x * grid.y <= 1u) {
+        return textureSampleLevel(my_texture, my_sampler, tex_coords, 0.0);
+    }
+    let pixel = clamp(tex_coords, vec2<f32>(0.0), vec2<f32>(1.0)) * options.image_size.xy;
+    let stride = vec2<f32>(grid.zw);
+    let tile = min(vec2<u32>(pixel / stride), grid.xy - vec2<u32>(1u));
+    let tile_size = vec2<f32>(textureDimensions(image_tiles));
+    let local = (pixel - vec2<f32>(tile) * stride + options.image_size.z) / tile_size;
+    return textureSampleLevel(image_tiles, my_sampler, local, tile.y * grid.x + tile.x, 0.0);
+}
+
+// Textures are sRGB, so each sample arrives in linear light. When the image is shrunk,
+// one bilinear tap skips most texels, so up to 4x4 taps across the pixel footprint are
+// averaged with premultiplied alpha.
+fn filtered_sample(tex_coords: vec2<f32>) -> vec4<f32> {
+    let size = options.image_size.xy;
+    let dx = dpdx(tex_coords) * size;
+    let dy = dpdy(tex_coords) * size;
+    let footprint = vec2<f32>(max(abs(dx.x), abs(dy.x)), max(abs(dx.y), abs(dy.y)));
+    let taps = vec2<i32>(clamp(ceil(footprint), vec2<f32>(1.0), vec2<f32>(4.0)));
+
+    if (options.supersample == 0u || (taps.x == 1 && taps.y == 1)) {
+        return sample_image(tex_coords);
+    }
+
+    var sum = vec4<f32>(0.0);
+    for (var y = 0; y < taps.y; y += 1) {
+        for (var x = 0; x < taps.x; x += 1) {
+            let offset = (vec2<f32>(f32(x), f32(y)) + 0.5) / vec2<f32>(taps) - 0.5;
+            var texel = sample_image(tex_coords + offset * footprint / size);
+            if (options.gamma_space == 1u) {
+                texel = vec4<f32>(linear_to_srgb(texel.rgb), texel.a);
+            }
+            sum += vec4<f32>(texel.rgb * texel.a, texel.a);
+        }
+    }
+
+    var color = sum / f32(taps.x * taps.y);
+    if (color.a > 0.0) {
+        color = vec4<f32>(color.rgb / color.a, color.a);
+    }
+    if (options.gamma_space == 1u) {
+        color = vec4<f32>(srgb_to_linear(color.rgb), color.a);
+    }
+    return color;
+}
+
+// HDR images hold scene-linear values past 1.0; the exposure brings a range of them into
+// view before everything else, so clipping warnings show what the exposure clips.
+fn apply_exposure(color: vec4<f32>) -> vec4<f32> {
+    if (options.pixel_format != 3u) {
+        return color;
+    }
+    let exposed = max(color.rgb * options.exposure_scale, vec3<f32>(0.0));
+    return vec4<f32>(pow(exposed, vec3<f32>(options.exposure_power)), color.a);
+}
+
+// .cube LUTs map display-encoded colors, so the lookup is done on sRGB values. Coordinates
+// are inset by half a texel so the table's first and last entries land on the domain edges.
+fn apply_lut(color: vec4<f32>) -> vec4<f32> {
+    if (options.lut_enabled == 0u) {
+        return color;
+    }
+    let encoded = linear_to_srgb(clamp(color.rgb, vec3<f32>(0.0), vec3<f32>(1.0)));
+    let range = options.lut_domain_max.rgb - options.lut_domain_min.rgb;
+    let normalized = clamp((encoded - options.lut_domain_min.rgb) / range, vec3<f32>(0.0), vec3<f32>(1.0));
+    let size = f32(textureDimensions(lut_texture).x);
+    let coords = (normalized * (size - 1.0) + 0.5) / size;
+    let graded = srgb_to_linear(max(textureSampleLevel(lut_texture, lut_sampler, coords, 0.0).rgb, vec3<f32>(0.0)));
+    return vec4<f32>(mix(color.rgb, graded, options.lut_intensity), color.a);
+}
+
+// Soft proofing looks up sRGB-encoded colors like the LUT. The gamut flag is interpolated
+// between grid points, so it is thresholded at half.
+fn apply_proof(color: vec4<f32>) -> vec4<f32> {
+    if (options.proof_colors == 0u && options.gamut_warning == 0u) {
+        return color;
+    }
+    let encoded = linear_to_srgb(clamp(color.rgb, vec3<f32>(0.0), vec3<f32>(1.0)));
+    let size = f32(textureDimensions(proof_texture).x);
+    let coords = (encoded * (size - 1.0) + 0.5) / size;
+    let proofed = textureSampleLevel(proof_texture, lut_sampler, coords, 0.0);
+    if (options.gamut_warning == 1u && proofed.a > 0.5) {
+        return vec4<f32>(srgb_to_linear(vec3<f32>(0.5)), color.a);
+    }
+    if (options.proof_colors == 1u) {
+        return vec4<f32>(srgb_to_linear(clamp(proofed.rgb, vec3<f32>(0.0), vec3<f32>(1.0))), color.a);
+    }
     return color;
-}
\ No newline at end of file
+}
+
+// ICC display compensation, applied last. The sRGB surface encodes the output with the sRGB
+// curve, so the value is pre-decoded to make the display receive its own curve instead.
+fn to_display(color: vec4<f32>) -> vec4<f32> {
+    if (options.display_gamma.w == 0.0) {
+        return color;
+    }
+    let c = clamp(color.rgb, vec3<f32>(0.0), vec3<f32>(1.0));
+    let display_linear = clamp(vec3<f32>(
+        dot(options.display_matrix[0].xyz, c),
+        dot(options.display_matrix[1].xyz, c),
+        dot(options.display_matrix[2].xyz, c),
+    ), vec3<f32>(0.0), vec3<f32>(1.0));
+    let encoded = pow(display_linear, 1.0 / options.display_gamma.rgb);
+    return vec4<f32>(srgb_to_linear(encoded), color.a);
+}
+
+// Zebra stripes over clipped pixels, tested on the sampled color and drawn over the final
+// one: black stripes on pure white, white stripes on pure black. They run diagonally in
+// screen space at 32 px/s with a 16 px period, which divides the 60 s wrap of `time`.
+fn apply_clipping_warning(color: vec4<f32>, source: vec3<f32>, position: vec2<f32>) -> vec4<f32> {
+    if (options.clipping_warning == 0u) {
+        return color;
+    }
+    let encoded = linear_to_srgb(clamp(source, vec3<f32>(0.0), vec3<f32>(1.0)));
+    let white = min(min(encoded.r, encoded.g), encoded.b) >= 254.5 / 255.0;
+    let black = max(max(encoded.r, encoded.g), encoded.b) <= 0.5 / 255.0;
+    let stripe = fract((position.x + position.y + options.time * 32.0) / 16.0) < 0.5;
+    if (!(white || black) || !stripe) {
+        return color;
+    }
+    return vec4<f32>(select(vec3<f32>(1.0), vec3<f32>(0.0), white), color.a);
+}
+
+// Auto white balance preview. Gains are applied to the sampled color, before any grading,
+// right of the split; the left side keeps the original.
+fn apply_white_balance(color: vec4<f32>, tex_coords: vec2<f32>) -> vec4<f32> {
+    if (options.white_balance_gains.w == 0.0 || tex_coords.x < options.white_balance_split) {
+        return color;
+    }
+    return vec4<f32>(color.rgb * options.white_balance_gains.rgb, color.a);
+}
+
+// A one-pixel gray line over the white balance split, drawn last so nothing tints it.
+// `pixel_width` comes from fs_main, where derivatives are taken in uniform control flow.
+fn draw_white_balance_split(color: vec4<f32>, tex_coords: vec2<f32>, pixel_width: f32) -> vec4<f32> {
+    if (options.white_balance_gains.w == 0.0 || abs(tex_coords.x - options.white_balance_split) > pixel_width * 0.5) {
+        return color;
+    }
+    return vec4<f32>(srgb_to_linear(vec3<f32>(0.75)), 1.0);
+}
+
+// `user_shader` is appended at pipeline creation: the user's WGSL snippet, or a pass-through.
+@fragment
+fn fs_main(
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+) -> @location(0) vec4<f32> {
+    let pixel_width = fwidth(tex_coords.x);
+    let source = apply_exposure(filtered_sample(tex_coords));
+    let balanced = apply_white_balance(source, tex_coords);
+    let color = to_display(apply_proof(user_shader(apply_lut(balanced), tex_coords)));
+    return draw_white_balance_split(apply_clipping_warning(color, source.rgb, position.xy), tex_coords, pixel_width);
+}
@@ -3,7 +3,17 @@ use std::sync::Mutex;
 use once_cell::sync::Lazy;
 use iced_core::Rectangle;
 use iced_wgpu::wgpu::{self, util::DeviceExt};
+use log::error;
 use crate::utils::timing::TimingStats;
+use crate::utils::icc_proof::ProofTable;
+use crate::utils::lut::Lut3d;
+use crate::widgets::lut_panel;
+use crate::widgets::exposure_panel;
+use crate::cache::hdr_textures::DISPLAY_GAMMA;
+use crate::widgets::shader::user_shader;
+use crate::widgets::shader::clipping_warning;
+use crate::cache::texture_tiles::{self, TILE_MARGIN};
+use crate::cache::{compact_textures, gray16_textures};
 
 static _TEXTURE_UPDATE_STATS: Lazy<Mutex<TimingStats>> = Lazy::new(|| {
     Mutex::new(TimingStats::new("Texture Update"))
@@ -20,12 +30,300 @@ pub struct TexturePipeline {
     pub index_buffer: wgpu::Buffer,
     pub num_indices: u32,
     pub texture: Arc<wgpu::Texture>,
+    options_buffer: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    lut: LutTexture,
+    proof: ProofTexture,
+    bind_group_layout: wgpu::BindGroupLayout,
+    format: wgpu::TextureFormat,
+    shader_generation: u64,              // user shader generation the pipeline was built with
+}
+
+/// `FilterOptions` uniform of `texture.wgsl`
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct FilterOptions {
+    gamma_space: u32,
+    supersample: u32,
+    lut_enabled: u32,
+    lut_intensity: f32,
+    lut_domain_min: [f32; 4],
+    lut_domain_max: [f32; 4],
+    display_matrix: [[f32; 4]; 3],
+    display_gamma: [f32; 4],
+    proof_colors: u32,
+    gamut_warning: u32,
+    clipping_warning: u32,
+    time: f32,
+    white_balance_gains: [f32; 4],
+    white_balance_split: f32,
+    _padding: [f32; 3],
+    tile_grid: [u32; 4],
+    image_size: [f32; 4],
+    pixel_format: u32,
+    exposure_scale: f32,
+    exposure_power: f32,
+    window_low: f32,
+    window_high: f32,
+    _padding2: [u32; 3],
+}
+
+fn filter_options(use_nearest_filter: bool, texture: &wgpu::Texture) -> FilterOptions {
+    let lut = lut_panel::active();
+    let white_balance = crate::white_balance::gains_for(texture);
+    let display = crate::display_profile::active();
+    let (proof_colors, gamut_warning) = crate::soft_proof::modes();
+    let (domain_min, domain_max) = lut.as_ref()
+        .map_or(([0.0; 3], [1.0; 3]), |(lut, _)| (lut.domain_min, lut.domain_max));
+    let tile_grid = texture_tiles::grid_of(texture);
+    let (width, height) = texture_tiles::image_size(texture);
+    let (stops, gamma) = exposure_panel::current();
+    let [window_low, window_high] = gray16_textures::shader_window(texture);
+    FilterOptions {
+        gamma_space: crate::utils::color::gamma_space_scaling() as u32,
+        supersample: !use_nearest_filter as u32,
+        lut_enabled: lut.is_some() as u32,
+        lut_intensity: lut.map_or(0.0, |(_, intensity)| intensity),
+        lut_domain_min: [domain_min[0], domain_min[1], domain_min[2], 0.0],
+        lut_domain_max: [domain_max[0], domain_max[1], domain_max[2], 0.0],
+        display_matrix: display.map_or([[0.0; 4]; 3], |profile| profile.from_srgb.map(|[a, b, c]| [a, b, c, 0.0])),
+        display_gamma: display.map_or([1.0, 1.0, 1.0, 0.0], |profile| [profile.gamma[0], profile.gamma[1], profile.gamma[2], 1.0]),
+        proof_colors: proof_colors as u32,
+        gamut_warning: gamut_warning as u32,
+        clipping_warning: clipping_warning::is_enabled() as u32,
+        time: clipping_warning::phase(),
+        white_balance_gains: white_balance.map_or([1.0, 1.0, 1.0, 0.0], |([r, g, b], _)| [r, g, b, 1.0]),
+        white_balance_split: white_balance.map_or(0.0, |(_, split)| split),
+        _padding: [0.0; 3],
+        tile_grid: tile_grid.map_or([1, 1, 0, 0], |grid| {
+            let (stride_x, stride_y) = grid.stride();
+            [grid.columns, grid.rows, stride_x, stride_y]
+        }),
+        image_size: [width as f32, height as f32, TILE_MARGIN as f32, 0.0],
+        pixel_format: compact_textures::pixel_format(texture),
+        exposure_scale: stops.exp2(),
+        exposure_power: DISPLAY_GAMMA / gamma,
+        window_low,
+        window_high,
+        _padding2: [0; 3],
+    }
+}
+
+/// 3D texture of the active LUT (an identity table when none is active)
+#[derive(Debug)]
+struct LutTexture {
+    view: wgpu::TextureView,
+    sampler: wgpu::Sampler,
+    generation: u64,
+}
+
+fn create_lut_texture(device: &wgpu::Device, queue: &wgpu::Queue) -> LutTexture {
+    let generation = lut_panel::generation();
+    let lut = lut_panel::active().map(|(lut, _)| lut).unwrap_or_else(|| Arc::new(Lut3d {
+        title: None,
+        size: 2,
+        domain_min: [0.0; 3],
+        domain_max: [1.0; 3],
+        table: (0..8).map(|i| [(i & 1) as f32, ((i >> 1) & 1) as f32, ((i >> 2) & 1) as f32]).collect(),
+    }));
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        label: Some("LUT Sampler"),
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        ..Default::default()
+    });
+
+    LutTexture {
+        view: create_table_texture(device, queue, "LUT Texture", lut.size as u32, &lut.to_rgba16f_bytes()),
+        sampler,
+        generation,
+    }
+}
+
+/// 3D texture of the soft-proofing table (an identity table with no gamut flags when no
+/// profile is chosen); sampled with the LUT sampler
+#[derive(Debug)]
+struct ProofTexture {
+    view: wgpu::TextureView,
+    generation: u64,
+}
+
+fn create_proof_texture(device: &wgpu::Device, queue: &wgpu::Queue) -> ProofTexture {
+    let generation = crate::soft_proof::generation();
+    let table = crate::soft_proof::table().unwrap_or_else(|| Arc::new(ProofTable {
+        size: 2,
+        table: (0..8).map(|i| [(i & 1) as f32, ((i >> 1) & 1) as f32, ((i >> 2) & 1) as f32, 0.0]).collect(),
+    }));
+    ProofTexture {
+        view: create_table_texture(device, queue, "Proof Texture", table.size as u32, &table.to_rgba16f_bytes()),
+        generation,
+    }
+}
+
+/// `size`³ RGBA16F texture from texels stored red-fastest
+fn create_table_texture(device: &wgpu::Device, queue: &wgpu::Queue, label: &str, size: u32, texels: &[u8]) -> wgpu::TextureView {
+    let extent = wgpu::Extent3d { width: size, height: size, depth_or_array_layers: size };
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: extent,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D3,
+        format: wgpu::TextureFormat::Rgba16Float,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        texels,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(size * 8),
+            rows_per_image: Some(size),
+        },
+        extent,
+    );
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+fn create_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    texture: &wgpu::Texture,
+    sampler: &wgpu::Sampler,
+    options_buffer: &wgpu::Buffer,
+    lut: &LutTexture,
+    proof: &ProofTexture,
+) -> wgpu::BindGroup {
+    let texture_view = texture_tiles::first_layer_view(texture);
+    let tiles_view = texture_tiles::tiles_view(texture);
+    let palette_view = compact_textures::palette_view(device, texture);
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&texture_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: options_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 4,
+                resource: wgpu::BindingResource::TextureView(&lut.view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 5,
+                resource: wgpu::BindingResource::Sampler(&lut.sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 6,
+                resource: wgpu::BindingResource::TextureView(&proof.view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 7,
+                resource: wgpu::BindingResource::TextureView(&tiles_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 8,
+                resource: wgpu::BindingResource::TextureView(&palette_view),
+            },
+        ],
+        label: Some("Bind Group"),
+    })
+}
+
+/// Compile `texture.wgsl` with the user shader hook, falling back to the plain pass if the
+/// user snippet does not validate
+fn create_shader_module(device: &wgpu::Device) -> wgpu::ShaderModule {
+    let base = include_str!("./texture.wgsl");
+    if user_shader::current_path().is_some() {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shader Module (user shader)"),
+            source: wgpu::ShaderSource::Wgsl(user_shader::compose(base, true).into()),
+        });
+        match futures::executor::block_on(device.pop_error_scope()) {
+            None => return module,
+            Some(e) => error!("User shader failed to compile, using the plain image pass: {}", e),
+        }
+    }
+    device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Shader Module"),
+        source: wgpu::ShaderSource::Wgsl(user_shader::compose(base, false).into()),
+    })
+}
+
+fn create_render_pipeline(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    bind_group_layout: &wgpu::BindGroupLayout,
+) -> wgpu::RenderPipeline {
+    let shader = create_shader_module(device);
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Pipeline Layout"),
+        bind_group_layouts: &[bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Render Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[wgpu::VertexBufferLayout {
+                array_stride: 4 * std::mem::size_of::<f32>() as u64,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &[
+                    wgpu::VertexAttribute {
+                        offset: 0,
+                        shader_location: 0,
+                        format: wgpu::VertexFormat::Float32x2,
+                    },
+                    wgpu::VertexAttribute {
+                        offset: 2 * std::mem::size_of::<f32>() as u64,
+                        shader_location: 1,
+                        format: wgpu::VertexFormat::Float32x2,
+                    },
+                ],
+            }],
+        },
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        multiview: None,
+    })
 }
 
 impl TexturePipeline {
     pub fn new(
         device: &wgpu::Device,
-        _queue: &wgpu::Queue,
+        queue: &wgpu::Queue,
         format: wgpu::TextureFormat,
         texture: Arc<wgpu::Texture>,
         _render_size: (u32, u32),
@@ -89,7 +387,11 @@ impl TexturePipeline {
             ..Default::default()
         });
 
-        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let options_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Filter Options Buffer"),
+            contents: bytemuck::bytes_of(&filter_options(use_nearest_filter, &texture)),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
         
         // Simplified binding layout - we don't need complex uniform buffers
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -111,72 +413,71 @@ impl TexturePipeline {
                     ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                     count: None,
                 },
-            ],
-        });
-        
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&texture_view),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
                 },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&sampler),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D3,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 5,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 6,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D3,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 7,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 8,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
                 },
             ],
-            label: Some("Bind Group"),
-        });
-
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Shader Module"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("./texture.wgsl").into()),
         });
 
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Pipeline Layout"),
-            bind_group_layouts: &[&bind_group_layout],
-            push_constant_ranges: &[],
-        });
+        let lut = create_lut_texture(device, queue);
+        let proof = create_proof_texture(device, queue);
+        let bind_group = create_bind_group(device, &bind_group_layout, &texture, &sampler, &options_buffer, &lut, &proof);
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[wgpu::VertexBufferLayout {
-                    array_stride: 4 * std::mem::size_of::<f32>() as u64,
-                    step_mode: wgpu::VertexStepMode::Vertex,
-                    attributes: &[
-                        wgpu::VertexAttribute {
-                            offset: 0,
-                            shader_location: 0,
-                            format: wgpu::VertexFormat::Float32x2,
-                        },
-                        wgpu::VertexAttribute {
-                            offset: 2 * std::mem::size_of::<f32>() as u64,
-                            shader_location: 1,
-                            format: wgpu::VertexFormat::Float32x2,
-                        },
-                    ],
-                }],
-            },
-            primitive: wgpu::PrimitiveState::default(),
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            multiview: None,
-        });
+        let shader_generation = crate::widgets::shader::user_shader::generation();
+        let pipeline = create_render_pipeline(device, format, &bind_group_layout);
 
         Self {
             pipeline,
@@ -185,9 +486,47 @@ impl TexturePipeline {
             index_buffer,
             num_indices: indices.len() as u32,
             texture,
+            options_buffer,
+            sampler,
+            lut,
+            proof,
+            bind_group_layout,
+            format,
+            shader_generation,
+        }
+    }
+
+    /// Rebuild the render pipeline if the user shader changed since it was created
+    pub fn refresh_user_shader(&mut self, device: &wgpu::Device) {
+        let generation = crate::widgets::shader::user_shader::generation();
+        if generation != self.shader_generation {
+            self.pipeline = create_render_pipeline(device, self.format, &self.bind_group_layout);
+            self.shader_generation = generation;
+        }
+    }
+
+    /// Upload the active LUT and soft-proofing table if either changed since the last upload
+    pub fn refresh_lut(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let lut_changed = lut_panel::generation() != self.lut.generation;
+        let proof_changed = crate::soft_proof::generation() != self.proof.generation;
+        if lut_changed {
+            self.lut = create_lut_texture(device, queue);
+        }
+        if proof_changed {
+            self.proof = create_proof_texture(device, queue);
+        }
+        if lut_changed || proof_changed {
+            self.bind_group = create_bind_group(
+                device, &self.bind_group_layout, &self.texture, &self.sampler, &self.options_buffer, &self.lut, &self.proof);
         }
     }
 
+    /// Refresh the filter options, picking up the gamma-space debug toggle, LUT intensity,
+    /// the clipping warning's animation and white balance gains estimated for this texture
+    pub fn update_filter_options(&self, queue: &wgpu::Queue, use_nearest_filter: bool) {
+        queue.write_buffer(&self.options_buffer, 0, bytemuck::bytes_of(&filter_options(use_nearest_filter, &self.texture)));
+    }
+
     pub fn update_texture(
         &mut self,
         device: &wgpu::Device,
@@ -216,22 +555,9 @@ impl TexturePipeline {
             ..Default::default()
         });
 
-        let texture_view = self.texture.create_view(&wgpu::TextureViewDescriptor::default());
-        
-        self.bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &self.pipeline.get_bind_group_layout(0),
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&texture_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&sampler),
-                },
-            ],
-            label: Some("Updated Bind Group"),
-        });
+        self.bind_group = create_bind_group(
+            device, &self.bind_group_layout, &self.texture, &sampler, &self.options_buffer, &self.lut, &self.proof);
+        self.sampler = sampler;
     }
 
     pub fn render(
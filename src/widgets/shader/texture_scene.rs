@@ -6,7 +6,9 @@ use iced_widget::shader::{self, Viewport};
 use iced_winit::core::{Rectangle, mouse};
 use iced_wgpu::wgpu;
 use crate::widgets::shader::texture_pipeline::TexturePipeline;
+use crate::widgets::shader::dual_texture_pipeline::{DualTexturePipeline, DualTextureParams};
 use crate::cache::img_cache::CachedData;
+use crate::cache::texture_tiles;
 use crate::utils::timing::TimingStats;
 
 static _SHADER_UPDATE_STATS: Lazy<Mutex<TimingStats>> = Lazy::new(|| {
@@ -26,10 +28,10 @@ impl TextureScene {
     pub fn new(initial_image: Option<&CachedData>) -> Self {
         let (texture, texture_size) = match initial_image {
             Some(CachedData::Gpu(tex)) => (
-                Some(Arc::clone(tex)), (tex.width(), tex.height())
+                Some(Arc::clone(tex)), texture_tiles::image_size(tex)
             ),
             Some(CachedData::BC1(tex)) => (
-                Some(Arc::clone(tex)), (tex.width(), tex.height())
+                Some(Arc::clone(tex)), texture_tiles::image_size(tex)
             ),
             _ => (None, (0, 0)),
         };
@@ -61,11 +63,10 @@ impl TextureScene {
     
     pub fn update_texture(&mut self, new_texture: Arc<wgpu::Texture>) {
         // Get width and height before moving the Arc
-        let width = new_texture.width();
-        let height = new_texture.height();
-        
+        let size = texture_tiles::image_size(&new_texture);
+
         self.texture = Some(new_texture);
-        self.texture_size = (width, height);
+        self.texture_size = size;
     }
 }
 
@@ -177,6 +178,9 @@ impl shader::Primitive for TexturePrimitive {
             // Only update the texture if needed
             let pipeline = registry.pipelines.get_mut(&pipeline_key).unwrap();
             pipeline.update_texture(device, queue, self.texture.clone(), false);
+            pipeline.refresh_user_shader(device);
+            pipeline.refresh_lut(device, queue);
+            pipeline.update_filter_options(queue, false);
         }
     }
 
@@ -245,4 +249,108 @@ impl<Message> shader::Program<Message> for TextureScene {
             TexturePrimitive::placeholder(bounds)
         }
     }
-}
\ No newline at end of file
+}
+
+/// Mode index of the opacity blend in `dual_texture.wgsl`
+const BLEND_SHADER_MODE: u32 = 3;
+
+/// Before/after onion skin: `over` is blended on top of `base` at `opacity` (0.0..=1.0)
+#[derive(Debug, Clone)]
+pub struct BlendScene {
+    pub base: Arc<wgpu::Texture>,
+    pub over: Arc<wgpu::Texture>,
+    pub opacity: f32,
+    pub content_fit: ContentFit,
+}
+
+impl BlendScene {
+    pub fn new(base: Arc<wgpu::Texture>, over: Arc<wgpu::Texture>, opacity: f32) -> Self {
+        Self {
+            base,
+            over,
+            opacity: opacity.clamp(0.0, 1.0),
+            content_fit: ContentFit::Contain,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct BlendPrimitive {
+    base: Arc<wgpu::Texture>,
+    over: Arc<wgpu::Texture>,
+    opacity: f32,
+    content_bounds: Rectangle,
+}
+
+impl shader::Primitive for BlendPrimitive {
+    fn prepare(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        storage: &mut shader::Storage,
+        _bounds: &Rectangle,
+        viewport: &Viewport,
+    ) {
+        let scale_factor = viewport.scale_factor() as f32;
+        let viewport_size = viewport.physical_size();
+        let bounds_relative = (
+            self.content_bounds.x * scale_factor / viewport_size.width as f32,
+            self.content_bounds.y * scale_factor / viewport_size.height as f32,
+            self.content_bounds.width * scale_factor / viewport_size.width as f32,
+            self.content_bounds.height * scale_factor / viewport_size.height as f32,
+        );
+
+        let textures = (Arc::clone(&self.base), Arc::clone(&self.over));
+        if !storage.has::<DualTexturePipeline>() {
+            storage.store(DualTexturePipeline::new(device, format, textures));
+        } else {
+            storage.get_mut::<DualTexturePipeline>().unwrap().update_textures(device, textures);
+        }
+
+        let params = DualTextureParams {
+            mode: BLEND_SHADER_MODE,
+            opacity: self.opacity,
+            _padding: [0.0; 2],
+        };
+        storage.get::<DualTexturePipeline>().unwrap().update(queue, bounds_relative, params);
+    }
+
+    fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        storage: &shader::Storage,
+        target: &wgpu::TextureView,
+        clip_bounds: &Rectangle<u32>,
+    ) {
+        if let Some(pipeline) = storage.get::<DualTexturePipeline>() {
+            pipeline.render(target, encoder, clip_bounds);
+        }
+    }
+}
+
+impl<Message> shader::Program<Message> for BlendScene {
+    type State = ();
+    type Primitive = BlendPrimitive;
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        _cursor: mouse::Cursor,
+        bounds: Rectangle,
+    ) -> Self::Primitive {
+        // The base image decides the layout; the overlay is stretched onto it
+        let (width, height) = texture_tiles::image_size(&self.base);
+        let image_size = Size::new(width as f32, height as f32);
+        let fitted_size = self.content_fit.fit(image_size, bounds.size());
+        let x = bounds.x + (bounds.width - fitted_size.width) / 2.0;
+        let y = bounds.y + (bounds.height - fitted_size.height) / 2.0;
+
+        BlendPrimitive {
+            base: Arc::clone(&self.base),
+            over: Arc::clone(&self.over),
+            opacity: self.opacity,
+            content_bounds: Rectangle::new(Point::new(x, y), fitted_size),
+        }
+    }
+}
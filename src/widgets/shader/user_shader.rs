@@ -0,0 +1,185 @@
+/// User WGSL post-process for the image view, like mpv user shaders
+///
+/// The snippet defines one function that receives each filtered image sample and its
+/// texture coordinate:
+///
+/// ```wgsl
+/// fn user_shader(color: vec4<f32>, uv: vec2<f32>) -> vec4<f32> {
+///     return vec4<f32>(vec3<f32>(dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722))), color.a);
+/// }
+/// ```
+///
+/// It is appended to `texture.wgsl`, so it may also read the image with `sample_image(uv)`
+/// (e.g. for sharpening), but it cannot declare bindings or entry points of its own. The
+/// file is watched and image pipelines pick up each valid change on their next frame; a
+/// snippet that fails to compile leaves the plain image pass in place.
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use once_cell::sync::Lazy;
+use iced_winit::runtime::Task;
+use log::{error, info};
+
+use crate::app::Message;
+
+const IDENTITY: &str = "fn user_shader(color: vec4<f32>, uv: vec2<f32>) -> vec4<f32> { return color; }";
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Attributes that would let a snippet reach beyond the image pass
+const FORBIDDEN: [&str; 6] = ["@group", "@binding", "@vertex", "@fragment", "@compute", "var<storage"];
+
+#[derive(Debug, Default)]
+struct UserShader {
+    path: Option<PathBuf>,
+    source: Option<String>,
+}
+
+static USER_SHADER: Lazy<Mutex<UserShader>> = Lazy::new(|| Mutex::new(UserShader::default()));
+/// Bumped whenever the active snippet changes, so pipelines know to rebuild
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+/// Identifies the current file watcher; older watchers exit when it changes
+static WATCH_ID: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone)]
+pub enum UserShaderMessage {
+    Open,
+    Load(PathBuf),
+    Clear,
+    /// The watched file changed on disk
+    Changed(PathBuf),
+}
+
+impl From<UserShaderMessage> for Message {
+    fn from(msg: UserShaderMessage) -> Self {
+        Message::UserShaderAction(msg)
+    }
+}
+
+/// Check that a snippet only defines the `user_shader` hook and helpers
+pub fn validate_snippet(source: &str) -> Result<(), String> {
+    let code: String = source.lines()
+        .map(|line| line.split("//").next().unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if let Some(attribute) = FORBIDDEN.iter().find(|attribute| code.contains(*attribute)) {
+        return Err(format!("'{}' is not allowed in a user shader", attribute));
+    }
+    if !code.contains("fn user_shader(") {
+        return Err("missing 'fn user_shader(color: vec4<f32>, uv: vec2<f32>) -> vec4<f32>'".to_string());
+    }
+    Ok(())
+}
+
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+/// File of the active snippet, if any
+pub fn current_path() -> Option<PathBuf> {
+    USER_SHADER.lock().ok()?.path.clone()
+}
+
+/// `base` with the active snippet, or a pass-through hook, appended
+pub fn compose(base: &str, with_user_snippet: bool) -> String {
+    let snippet = USER_SHADER.lock().ok()
+        .filter(|_| with_user_snippet)
+        .and_then(|state| state.source.clone())
+        .unwrap_or_else(|| IDENTITY.to_string());
+    format!("{}\n\n{}\n", base, snippet)
+}
+
+fn load(path: &Path) -> Result<(), String> {
+    let source = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    validate_snippet(&source)?;
+    let mut state = USER_SHADER.lock().map_err(|e| e.to_string())?;
+    state.path = Some(path.to_path_buf());
+    state.source = Some(source);
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+    Ok(())
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Poll the file's modification time until another file is loaded or the shader is cleared
+fn watch(path: PathBuf) -> Task<Message> {
+    let (sender, receiver) = futures::channel::mpsc::unbounded();
+    let watch_id = WATCH_ID.fetch_add(1, Ordering::Relaxed) + 1;
+    std::thread::spawn(move || {
+        let mut last_modified = modified(&path);
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            if WATCH_ID.load(Ordering::Relaxed) != watch_id {
+                break;
+            }
+            let now = modified(&path);
+            if now != last_modified {
+                last_modified = now;
+                if sender.unbounded_send(path.clone()).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    Task::run(receiver, |path| UserShaderMessage::Changed(path).into())
+}
+
+pub fn handle_user_shader_message(msg: UserShaderMessage) -> Task<Message> {
+    match msg {
+        UserShaderMessage::Open => Task::perform(
+            async {
+                rfd::AsyncFileDialog::new()
+                    .set_title("Select user shader")
+                    .add_filter("WGSL", &["wgsl"])
+                    .pick_file()
+                    .await
+            },
+            |file_handle| match file_handle {
+                Some(file) => UserShaderMessage::Load(file.path().to_path_buf()).into(),
+                None => Message::Nothing,
+            }
+        ),
+        UserShaderMessage::Load(path) => match load(&path) {
+            Ok(()) => {
+                info!("Loaded user shader {}", path.display());
+                watch(path)
+            }
+            Err(e) => {
+                error!("Failed to load user shader: {}", e);
+                Task::none()
+            }
+        },
+        UserShaderMessage::Changed(path) => {
+            // Keep the previous snippet while the file is mid-edit or invalid
+            match load(&path) {
+                Ok(()) => info!("Reloaded user shader {}", path.display()),
+                Err(e) => error!("User shader not reloaded: {}", e),
+            }
+            Task::none()
+        }
+        UserShaderMessage::Clear => {
+            if let Ok(mut state) = USER_SHADER.lock() {
+                *state = UserShader::default();
+            }
+            WATCH_ID.fetch_add(1, Ordering::Relaxed);
+            GENERATION.fetch_add(1, Ordering::Relaxed);
+            Task::none()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet_validation() {
+        assert!(validate_snippet(IDENTITY).is_ok());
+        assert!(validate_snippet(&format!("// @group(0) in a comment is fine\n{}", IDENTITY)).is_ok());
+        assert!(validate_snippet("fn other(c: vec4<f32>) -> vec4<f32> { return c; }").is_err());
+        assert!(validate_snippet(&format!("@group(1) @binding(0) var<uniform> x: f32;\n{}", IDENTITY)).is_err());
+        assert!(validate_snippet(&format!("@fragment fn main() {{}}\n{}", IDENTITY)).is_err());
+    }
+}
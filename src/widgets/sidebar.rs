@@ -0,0 +1,261 @@
+/// Sidebar with collapsible sections about the current image
+///
+/// Image info, metadata plugin output, parquet labels and the log each get a section in one
+/// sidebar on the right, rather than a panel or bar of their own. A pinned sidebar is docked
+/// next to the image; an unpinned one floats over the image's right edge so the image keeps
+/// its size. Visibility, pinning and collapsed sections are kept in
+/// `<config dir>/viewskater/sidebar.yaml`.
+use std::path::PathBuf;
+use iced_winit::core::{Element, Length, Alignment, Color, Font};
+use iced_winit::core::alignment::Horizontal;
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{button, column, container, horizontal_space, row, scrollable, stack, text, Column};
+use serde::{Deserialize, Serialize};
+use log::warn;
+
+use crate::app::Message;
+
+const LAYOUT_FILE_NAME: &str = "sidebar.yaml";
+const LOG_LINES: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SidebarSection {
+    Info,
+    Plugins,
+    Labels,
+    Map,
+    Playlist,
+    Log,
+}
+
+impl SidebarSection {
+    fn title(self) -> &'static str {
+        match self {
+            SidebarSection::Info => "Image",
+            SidebarSection::Plugins => "Plugins",
+            SidebarSection::Labels => "Labels",
+            SidebarSection::Map => "Map",
+            SidebarSection::Playlist => "Playlist",
+            SidebarSection::Log => "Log",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SidebarLayout {
+    pub visible: bool,
+    pub pinned: bool,
+    pub width: f32,
+    pub collapsed: Vec<SidebarSection>,
+}
+
+impl Default for SidebarLayout {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            pinned: true,
+            width: 280.0,
+            collapsed: vec![SidebarSection::Log],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SidebarMessage {
+    SetVisible(bool),
+    SetPinned(bool),
+    ToggleSection(SidebarSection),
+}
+
+impl From<SidebarMessage> for Message {
+    fn from(msg: SidebarMessage) -> Self {
+        Message::SidebarAction(msg)
+    }
+}
+
+#[derive(Debug)]
+pub struct Sidebar {
+    pub layout: SidebarLayout,
+}
+
+fn layout_path() -> PathBuf {
+    crate::app_dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("viewskater")
+        .join(LAYOUT_FILE_NAME)
+}
+
+impl Sidebar {
+    pub fn load() -> Self {
+        let layout = std::fs::read_to_string(layout_path())
+            .ok()
+            .and_then(|yaml| serde_yaml::from_str(&yaml).ok())
+            .unwrap_or_default();
+        Self { layout }
+    }
+
+    fn save(&self) {
+        let path = layout_path();
+        let result = path.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, serde_yaml::to_string(&self.layout).unwrap_or_default()));
+        if let Err(e) = result {
+            warn!("Failed to save sidebar layout to {}: {}", path.display(), e);
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.layout.visible
+    }
+
+    fn is_collapsed(&self, section: SidebarSection) -> bool {
+        self.layout.collapsed.contains(&section)
+    }
+
+    fn toggle_section(&mut self, section: SidebarSection) {
+        if self.is_collapsed(section) {
+            self.layout.collapsed.retain(|s| *s != section);
+        } else {
+            self.layout.collapsed.push(section);
+        }
+    }
+}
+
+pub fn handle_sidebar_message(msg: SidebarMessage, sidebar: &mut Sidebar) -> Task<Message> {
+    match msg {
+        SidebarMessage::SetVisible(visible) => sidebar.layout.visible = visible,
+        SidebarMessage::SetPinned(pinned) => sidebar.layout.pinned = pinned,
+        SidebarMessage::ToggleSection(section) => sidebar.toggle_section(section),
+    }
+    sidebar.save();
+    Task::none()
+}
+
+/// Name and value rows, the layout shared by the sections
+pub fn field_list<'a>(fields: impl IntoIterator<Item = (String, String)>) -> Column<'a, Message, WinitTheme, Renderer> {
+    let key_style = |theme: &WinitTheme| iced_widget::text::Style {
+        color: Some(theme.extended_palette().background.strong.color),
+    };
+    fields.into_iter().fold(column![].spacing(6), |content, (key, value)| {
+        content.push(column![
+            text(key).size(11).style(key_style),
+            text(value).size(13),
+        ])
+    })
+}
+
+/// Last lines of the app log, newest last
+pub fn log_section<'a>() -> Element<'a, Message, WinitTheme, Renderer> {
+    let lines: Vec<String> = crate::get_shared_log_buffer()
+        .and_then(|buffer| buffer.lock().ok().map(|lines| {
+            lines.iter().rev().take(LOG_LINES).rev().cloned().collect()
+        }))
+        .unwrap_or_default();
+    if lines.is_empty() {
+        return text("No log messages").size(12).into();
+    }
+    text(lines.join("\n")).size(11).font(Font::MONOSPACE).into()
+}
+
+fn section_header<'a>(section: SidebarSection, collapsed: bool) -> Element<'a, Message, WinitTheme, Renderer> {
+    let marker = if collapsed { "+" } else { "-" };
+    button(text(format!("{} {}", marker, section.title())).size(13).font(Font::MONOSPACE))
+        .padding([2, 4])
+        .width(Length::Fill)
+        .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+        .on_press(SidebarMessage::ToggleSection(section).into())
+        .into()
+}
+
+/// Sidebar with the given sections; sections without content are left out by the caller
+fn view<'a>(
+    sidebar: &Sidebar,
+    sections: Vec<(SidebarSection, Element<'a, Message, WinitTheme, Renderer>)>,
+) -> Element<'a, Message, WinitTheme, Renderer> {
+    let pinned = sidebar.layout.pinned;
+    let header = row![
+        horizontal_space(),
+        button(text(if pinned { "Unpin" } else { "Pin" }).size(11))
+            .padding([1, 6])
+            .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+            .on_press(SidebarMessage::SetPinned(!pinned).into()),
+        button(text("Close").size(11))
+            .padding([1, 6])
+            .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+            .on_press(SidebarMessage::SetVisible(false).into()),
+    ]
+    .spacing(6)
+    .align_y(Alignment::Center);
+
+    let mut content = column![].spacing(8);
+    for (section, body) in sections {
+        let collapsed = sidebar.is_collapsed(section);
+        content = content.push(section_header(section, collapsed));
+        if !collapsed {
+            content = content.push(container(body).padding([0, 6]));
+        }
+    }
+
+    container(column![header, scrollable(content).height(Length::Fill)].spacing(6))
+        .padding(8)
+        .width(Length::Fixed(sidebar.layout.width))
+        .height(Length::Fill)
+        .style(move |theme: &WinitTheme| {
+            let mut background = theme.extended_palette().background.weak.color;
+            if !pinned {
+                background.a = 0.9;
+            }
+            container::Style {
+                text_color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+                background: Some(background.into()),
+                ..container::Style::default()
+            }
+        })
+        .into()
+}
+
+/// `content` with the sidebar docked beside it, floating over it, or unchanged when hidden
+pub fn attach<'a>(
+    sidebar: &Sidebar,
+    content: Element<'a, Message, WinitTheme, Renderer>,
+    sections: Vec<(SidebarSection, Element<'a, Message, WinitTheme, Renderer>)>,
+) -> Element<'a, Message, WinitTheme, Renderer> {
+    if !sidebar.is_visible() || sections.is_empty() {
+        return content;
+    }
+    let panel = view(sidebar, sections);
+    if sidebar.layout.pinned {
+        row![content, panel].into()
+    } else {
+        stack![
+            content,
+            container(panel).width(Length::Fill).height(Length::Fill).align_x(Horizontal::Right),
+        ]
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_round_trip_and_defaults() {
+        let mut sidebar = Sidebar { layout: SidebarLayout::default() };
+        sidebar.toggle_section(SidebarSection::Info);
+        sidebar.toggle_section(SidebarSection::Log);
+        assert_eq!(sidebar.layout.collapsed, vec![SidebarSection::Info]);
+
+        let yaml = serde_yaml::to_string(&sidebar.layout).unwrap();
+        assert_eq!(serde_yaml::from_str::<SidebarLayout>(&yaml).unwrap(), sidebar.layout);
+
+        // Fields missing from an older file keep their defaults
+        let layout: SidebarLayout = serde_yaml::from_str("visible: true\n").unwrap();
+        assert!(layout.visible && layout.pinned);
+        assert_eq!(layout.collapsed, vec![SidebarSection::Log]);
+    }
+}
@@ -0,0 +1,201 @@
+/// Similar-image navigation widget
+///
+/// This module is only compiled when the "embeddings" feature is enabled.
+/// It encapsulates the embedding-related messages and the results strip.
+use std::path::PathBuf;
+use std::sync::Arc;
+use iced_winit::core::{Element, Color, Length};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{button, container, row, scrollable, text, Row};
+use iced_core::alignment;
+use iced_core::keyboard::{self, Key};
+use log::{info, error, warn};
+
+use crate::app::{Message, NavigationMessage};
+use crate::embeddings::{EmbeddingIndex, EmbeddingManager, DEFAULT_NEIGHBOR_COUNT};
+use crate::pane::Pane;
+use crate::menu::PaneLayout;
+
+/// Embedding-specific messages grouped into a single enum variant
+#[derive(Debug, Clone)]
+pub enum SimilarityMessage {
+    /// Pick an embedding file for a pane's image list
+    LoadEmbeddings(usize),                 // pane_index
+    LoadEmbeddingsFromPath(usize, PathBuf),
+    EmbeddingsLoaded(usize, Result<Arc<EmbeddingIndex>, String>),
+    /// Find images similar to the current image of a pane
+    FindSimilar(usize),                    // pane_index
+    /// Jump to a neighbor from the results strip (pane_index, image index)
+    JumpToSimilar(usize, usize),
+    CloseResults,
+}
+
+/// Convert SimilarityMessage to the main Message type
+impl From<SimilarityMessage> for Message {
+    fn from(msg: SimilarityMessage) -> Self {
+        Message::SimilarityAction(msg)
+    }
+}
+
+fn image_names(pane: &Pane) -> Vec<String> {
+    pane.img_cache.image_paths.iter()
+        .map(|p| p.file_name().to_string())
+        .collect()
+}
+
+/// Handle embedding messages
+pub fn handle_similarity_message(
+    msg: SimilarityMessage,
+    panes: &[Pane],
+    embedding_manager: &mut EmbeddingManager,
+) -> Task<Message> {
+    match msg {
+        SimilarityMessage::LoadEmbeddings(pane_index) => {
+            Task::perform(
+                async {
+                    rfd::AsyncFileDialog::new()
+                        .set_title("Select embedding file")
+                        .add_filter("Embeddings", &["npy", "parquet"])
+                        .pick_file()
+                        .await
+                },
+                move |file_handle| {
+                    if let Some(file) = file_handle {
+                        let path = file.path().to_path_buf();
+                        Message::SimilarityAction(SimilarityMessage::LoadEmbeddingsFromPath(pane_index, path))
+                    } else {
+                        Message::Nothing
+                    }
+                }
+            )
+        }
+
+        SimilarityMessage::LoadEmbeddingsFromPath(pane_index, path) => {
+            info!("Loading embeddings from: {}", path.display());
+            Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || EmbeddingIndex::load(&path).map(Arc::new))
+                        .await
+                        .unwrap_or_else(|e| Err(format!("Embedding load task failed: {}", e)))
+                },
+                move |result| Message::SimilarityAction(SimilarityMessage::EmbeddingsLoaded(pane_index, result))
+            )
+        }
+
+        SimilarityMessage::EmbeddingsLoaded(pane_index, result) => {
+            let Some(pane) = panes.get(pane_index).filter(|p| p.dir_loaded) else {
+                warn!("Embeddings loaded but pane {} has no images", pane_index);
+                return Task::none();
+            };
+            match result.and_then(|index| {
+                let (rows, dim) = (index.len(), index.dim());
+                embedding_manager.attach(index, &image_names(pane)).map(|matched| (rows, dim, matched))
+            }) {
+                Ok((rows, dim, matched)) => {
+                    info!("Embeddings ready: {} rows x {} dims, {} images matched", rows, dim, matched);
+                }
+                Err(e) => error!("Failed to load embeddings: {}", e),
+            }
+            Task::none()
+        }
+
+        SimilarityMessage::FindSimilar(pane_index) => {
+            if !embedding_manager.has_embeddings() {
+                info!("No embeddings loaded");
+                return Task::none();
+            }
+            let Some(pane) = panes.get(pane_index).filter(|p| p.dir_loaded) else {
+                return Task::none();
+            };
+            let Some(current) = pane.img_cache.image_paths.get(pane.img_cache.current_index) else {
+                return Task::none();
+            };
+            let filename = current.file_name().to_string();
+            match embedding_manager.find_similar(pane_index, &filename, &image_names(pane), DEFAULT_NEIGHBOR_COUNT) {
+                Ok(results) => info!("Found {} images similar to {}", results.neighbors.len(), filename),
+                Err(e) => warn!("Find similar failed: {}", e),
+            }
+            Task::none()
+        }
+
+        SimilarityMessage::JumpToSimilar(pane_index, image_index) => {
+            Task::done(Message::Navigation(NavigationMessage::JumpToImage(pane_index, image_index)))
+        }
+
+        SimilarityMessage::CloseResults => {
+            embedding_manager.clear_results();
+            Task::none()
+        }
+    }
+}
+
+/// Horizontal strip of the nearest neighbors from the last query
+///
+/// Returns a zero-height container when there are no results.
+pub fn results_strip(embedding_manager: &EmbeddingManager) -> Element<'_, Message, WinitTheme, Renderer> {
+    let Some(results) = embedding_manager.results() else {
+        return container(text("")).height(0).into();
+    };
+
+    let label_style = |_theme: &WinitTheme| iced_widget::text::Style {
+        color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+    };
+
+    let neighbors = results.neighbors.iter().fold(Row::new().spacing(4), |strip, neighbor| {
+        strip.push(
+            button(
+                text(format!("{}  {:.3}", neighbor.filename, neighbor.score)).size(12)
+            )
+            .padding([2, 6])
+            .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+            .on_press(Message::SimilarityAction(
+                SimilarityMessage::JumpToSimilar(results.pane_index, neighbor.image_index)
+            ))
+        )
+    });
+
+    container(
+        row![
+            text(format!("Similar to {}:", results.query)).size(12).style(label_style),
+            scrollable(neighbors)
+                .direction(scrollable::Direction::Horizontal(scrollable::Scrollbar::new().width(4).scroller_width(4)))
+                .width(Length::Fill),
+            button(text("x").size(12))
+                .padding([2, 6])
+                .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+                .on_press(Message::SimilarityAction(SimilarityMessage::CloseResults)),
+        ]
+        .spacing(8)
+        .align_y(alignment::Vertical::Center)
+    )
+    .padding([4, 8])
+    .width(Length::Fill)
+    .into()
+}
+
+/// Handle embedding-related keyboard events
+///
+/// Returns Some(Task) if the key was handled, None if not an embedding key
+pub fn handle_keyboard_event(
+    key: &keyboard::Key,
+    modifiers: keyboard::Modifiers,
+    pane_layout: &PaneLayout,
+    last_opened_pane: isize,
+) -> Option<Task<Message>> {
+    let pane_index = if *pane_layout == PaneLayout::SinglePane {
+        0
+    } else {
+        last_opened_pane as usize
+    };
+
+    match key.as_ref() {
+        Key::Character("f") | Key::Character("F") if !modifiers.control() && !modifiers.logo() => {
+            Some(Task::done(Message::SimilarityAction(
+                SimilarityMessage::FindSimilar(pane_index)
+            )))
+        }
+        _ => None
+    }
+}
@@ -151,6 +151,9 @@ where
     // Add a flag to control synced zooming
     synced_zoom: bool,
 
+    // Whether the wheel zooms the panes; when it navigates, wheel events stay out of them
+    wheel_zooms: bool,
+
     // Add zoom control parameters
     min_scale: f32,
     max_scale: f32,
@@ -229,6 +232,7 @@ where
 
             // Initialize zoom settings
             synced_zoom,
+            wheel_zooms: true,
             min_scale: 0.25,
             max_scale: 10.0,
             scale_step: 0.10,
@@ -328,6 +332,12 @@ where
         self.synced_zoom = synced_zoom;
         self
     }
+
+    /// Set whether the mouse wheel zooms, as `image_shader::wheel_zooms` decides it
+    pub fn wheel_zooms(mut self, wheel_zooms: bool) -> Self {
+        self.wheel_zooms = wheel_zooms;
+        self
+    }
 }
 
 impl<'a, Message, Theme, Renderer> Widget<Message, Theme, Renderer>
@@ -407,6 +417,11 @@ where
             debug_log!("SyncedImageSplit: synced_zoom = {}", split_state.synced_zoom);
         }
 
+        // The app moves to the next or previous image on this scroll; neither pane zooms
+        if is_wheel_event && !self.wheel_zooms {
+            return event::Status::Ignored;
+        }
+
         let mut children = layout.children();
         let first_layout = children
             .next()
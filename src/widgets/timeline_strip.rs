@@ -0,0 +1,221 @@
+/// Capture-date navigation strip
+///
+/// Groups the images of the followed pane's folder by the day they were taken and shows one
+/// button per day above the slider; clicking a day jumps to its first image in folder order.
+/// The day comes from EXIF DateTimeOriginal, or the file's modification time when there is
+/// none. Dates are read in the background the first time the strip is shown for a folder,
+/// so they don't slow down opening it.
+use std::collections::{BTreeMap, HashMap};
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+use iced_winit::core::{Element, Color, Length};
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::Renderer;
+use iced_widget::{button, container, row, scrollable, text, Row};
+use iced_core::alignment;
+use rayon::prelude::*;
+
+use crate::app::{Message, NavigationMessage};
+use crate::cache::img_cache::PathSource;
+use crate::pane::Pane;
+
+/// (pane_index, folder, image count) the groups were computed for
+pub type FolderKey = (usize, String, usize);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayGroup {
+    /// None for images whose date couldn't be read, such as those inside archives
+    pub date: Option<NaiveDate>,
+    pub first_index: usize,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone)]
+pub enum TimelineMessage {
+    SetVisible(bool),
+    /// Capture day of each image, by image index
+    Scanned(FolderKey, Vec<Option<NaiveDate>>),
+}
+
+impl From<TimelineMessage> for Message {
+    fn from(msg: TimelineMessage) -> Self {
+        Message::TimelineAction(msg)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TimelineStrip {
+    pub visible: bool,
+    key: Option<FolderKey>,
+    scanning: bool,
+    groups: Vec<DayGroup>,
+    /// Group of each image, by image index
+    group_of: Vec<usize>,
+}
+
+/// Days in chronological order with undated images last, and the group of each image
+pub fn group_by_day(dates: &[Option<NaiveDate>]) -> (Vec<DayGroup>, Vec<usize>) {
+    let mut days: BTreeMap<(bool, Option<NaiveDate>), (usize, usize)> = BTreeMap::new();
+    for (index, date) in dates.iter().enumerate() {
+        days.entry((date.is_none(), *date))
+            .and_modify(|(_, count)| *count += 1)
+            .or_insert((index, 1));
+    }
+    let groups: Vec<DayGroup> = days.into_iter()
+        .map(|((_, date), (first_index, count))| DayGroup { date, first_index, count })
+        .collect();
+    let positions: HashMap<Option<NaiveDate>, usize> = groups.iter()
+        .enumerate()
+        .map(|(position, group)| (group.date, position))
+        .collect();
+    let group_of = dates.iter().map(|date| positions[date]).collect();
+    (groups, group_of)
+}
+
+fn capture_day(source: &PathSource) -> Option<NaiveDate> {
+    let PathSource::Filesystem(path) = source else {
+        return None;
+    };
+    if let Some(date) = crate::utils::exif_tags::read_capture_date(path) {
+        return Some(date.date());
+    }
+    let modified = std::fs::metadata(crate::file_io::long_path(path)).ok()?.modified().ok()?;
+    Some(DateTime::<Local>::from(modified).date_naive())
+}
+
+impl TimelineStrip {
+    /// Start reading capture dates when the strip is shown for a folder it has no groups for
+    pub fn request(&mut self, pane: &Pane, pane_index: usize) -> Option<Task<Message>> {
+        if !self.visible || !pane.dir_loaded || pane.img_cache.image_paths.is_empty() {
+            return None;
+        }
+        let key = (pane_index, pane.directory_path.clone()?, pane.img_cache.image_paths.len());
+        if self.key.as_ref() == Some(&key) {
+            return None;
+        }
+
+        self.key = Some(key.clone());
+        self.scanning = true;
+        self.groups.clear();
+        self.group_of.clear();
+        let paths = pane.img_cache.image_paths.clone();
+        Some(Task::perform(
+            async move {
+                let dates = tokio::task::spawn_blocking(move || {
+                    paths.to_vec().par_iter().map(capture_day).collect::<Vec<_>>()
+                })
+                .await
+                .unwrap_or_default();
+                (key, dates)
+            },
+            |(key, dates)| TimelineMessage::Scanned(key, dates).into()
+        ))
+    }
+}
+
+pub fn handle_timeline_message(msg: TimelineMessage, timeline: &mut TimelineStrip) -> Task<Message> {
+    match msg {
+        TimelineMessage::SetVisible(visible) => {
+            timeline.visible = visible;
+            if !visible {
+                // Rescanned when shown again, in case the folder changed meanwhile
+                *timeline = TimelineStrip::default();
+            }
+        }
+        TimelineMessage::Scanned(key, dates) => {
+            // Results for a folder that is no longer shown
+            if timeline.key.as_ref() != Some(&key) {
+                return Task::none();
+            }
+            let (groups, group_of) = group_by_day(&dates);
+            timeline.groups = groups;
+            timeline.group_of = group_of;
+            timeline.scanning = false;
+        }
+    }
+    Task::none()
+}
+
+/// One button per capture day, or a zero-height container when the strip is hidden
+pub fn view<'a>(
+    timeline: &'a TimelineStrip,
+    pane_index: usize,
+    current: usize,
+) -> Element<'a, Message, WinitTheme, Renderer> {
+    if !timeline.visible || timeline.key.as_ref().map(|key| key.0) != Some(pane_index) {
+        return container(text("")).height(0).into();
+    }
+
+    let label_style = |_theme: &WinitTheme| iced_widget::text::Style {
+        color: Some(Color::from_rgb(0.878, 0.878, 0.878)),
+    };
+
+    let days: Element<'a, Message, WinitTheme, Renderer> = if timeline.scanning {
+        text("Reading capture dates...").size(12).style(label_style).into()
+    } else {
+        // The year is only worth its width when the folder spans more than one
+        let years: Vec<i32> = timeline.groups.iter().filter_map(|group| group.date.map(|d| d.year())).collect();
+        let show_year = years.first() != years.last();
+        let current_group = timeline.group_of.get(current).copied();
+
+        let buttons = timeline.groups.iter().enumerate().fold(Row::new().spacing(4), |strip, (index, group)| {
+            let day = match group.date {
+                Some(date) if show_year => date.format("%b %-d, %Y").to_string(),
+                Some(date) => date.format("%b %-d").to_string(),
+                None => "Undated".to_string(),
+            };
+            let is_current = current_group == Some(index);
+            strip.push(
+                button(text(format!("{} ({})", day, group.count)).size(12))
+                    .padding([2, 6])
+                    .style(move |theme: &WinitTheme, status| {
+                        let mut style = crate::menu::button_style(theme, status, "labeled");
+                        if is_current {
+                            style.border.color = theme.extended_palette().primary.strong.color;
+                            style.border.width = 1.0;
+                        }
+                        style
+                    })
+                    .on_press(Message::Navigation(NavigationMessage::JumpToImage(pane_index, group.first_index)))
+            )
+        });
+        scrollable(buttons)
+            .direction(scrollable::Direction::Horizontal(scrollable::Scrollbar::new().width(4).scroller_width(4)))
+            .width(Length::Fill)
+            .into()
+    };
+
+    container(
+        row![
+            text("Timeline").size(12).style(label_style),
+            days,
+            button(text("x").size(12))
+                .padding([2, 6])
+                .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+                .on_press(TimelineMessage::SetVisible(false).into()),
+        ]
+        .spacing(8)
+        .align_y(alignment::Vertical::Center)
+    )
+    .padding([4, 8])
+    .width(Length::Fill)
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_by_day() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2023, 6, d);
+        let dates = [day(12), None, day(11), day(12), day(11)];
+        let (groups, group_of) = group_by_day(&dates);
+        assert_eq!(groups, vec![
+            DayGroup { date: day(11), first_index: 2, count: 2 },
+            DayGroup { date: day(12), first_index: 0, count: 2 },
+            DayGroup { date: None, first_index: 1, count: 1 },
+        ]);
+        assert_eq!(group_of, vec![1, 2, 0, 1, 0]);
+    }
+}
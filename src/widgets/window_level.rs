@@ -0,0 +1,84 @@
+/// Window/level controls for 16-bit grayscale images
+///
+/// The footer of a pane showing a 16-bit grayscale image (see `cache::gray16_textures`) gets
+/// sliders for the values shown as black and as white, over the range of the image's values,
+/// and a button that widens the window back to that range. The window belongs to the image's
+/// texture, so each image keeps its own while it stays cached.
+use iced_winit::core::{Element, Length, Alignment, Color};
+use iced_winit::core::font::Font;
+use iced_winit::core::Theme as WinitTheme;
+use iced_winit::runtime::Task;
+use iced_wgpu::{wgpu, Renderer};
+use iced_widget::{row, text, button};
+
+use crate::app::Message;
+use crate::cache::gray16_textures;
+
+type TextureKey = wgpu::Id<wgpu::Texture>;
+
+#[derive(Debug, Clone)]
+pub enum WindowLevelMessage {
+    /// Texture and the value shown as black
+    SetLow(TextureKey, u16),
+    /// Texture and the value shown as white
+    SetHigh(TextureKey, u16),
+    /// Window over all of the texture's values
+    Auto(TextureKey),
+}
+
+impl From<WindowLevelMessage> for Message {
+    fn from(msg: WindowLevelMessage) -> Self {
+        Message::WindowLevelAction(msg)
+    }
+}
+
+pub fn handle_window_level_message(msg: WindowLevelMessage) -> Task<Message> {
+    match msg {
+        WindowLevelMessage::SetLow(key, low) => {
+            if let Some(levels) = gray16_textures::levels(key) {
+                gray16_textures::set_window(key, low.min(levels.window.1.saturating_sub(1)), levels.window.1);
+            }
+        }
+        WindowLevelMessage::SetHigh(key, high) => {
+            if let Some(levels) = gray16_textures::levels(key) {
+                gray16_textures::set_window(key, levels.window.0, high.max(levels.window.0.saturating_add(1)));
+            }
+        }
+        WindowLevelMessage::Auto(key) => {
+            if let Some(levels) = gray16_textures::levels(key) {
+                gray16_textures::set_window(key, levels.range.0, levels.range.1);
+            }
+        }
+    }
+    Task::none()
+}
+
+/// Window sliders for a 16-bit grayscale texture, or None for other textures
+pub fn view(texture: &wgpu::Texture) -> Option<Element<'static, Message, WinitTheme, Renderer>> {
+    let key = texture.global_id();
+    let levels = gray16_textures::levels(key)?;
+    let (min, max) = levels.range;
+    let (low, high) = levels.window;
+    let label = |value: String| text(value)
+        .font(Font::MONOSPACE)
+        .size(12)
+        .style(|_theme| iced::widget::text::Style {
+            color: Some(Color::from([0.8, 0.8, 0.8]))
+        });
+
+    Some(row![
+        label("W/L".to_string()),
+        iced_widget::slider(min..=max, low.clamp(min, max), move |value| WindowLevelMessage::SetLow(key, value).into())
+            .width(Length::Fixed(90.0)),
+        iced_widget::slider(min..=max, high.clamp(min, max), move |value| WindowLevelMessage::SetHigh(key, value).into())
+            .width(Length::Fixed(90.0)),
+        label(format!("{}-{}", low, high)),
+        button(text("Auto").size(11))
+            .padding([1, 6])
+            .style(|theme: &WinitTheme, status| crate::menu::button_style(theme, status, "labeled"))
+            .on_press(WindowLevelMessage::Auto(key).into()),
+    ]
+    .spacing(6)
+    .align_y(Alignment::Center)
+    .into())
+}
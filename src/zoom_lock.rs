@@ -0,0 +1,151 @@
+/// Zoom lock: keep showing the same image region while navigating
+///
+/// Locking captures the crop each pane's image shader is showing, as a center point in image
+/// pixels and a magnification in screen pixels per image pixel. Every image drawn afterwards
+/// is zoomed and panned to that crop, so the same patch can be compared across a sequence.
+/// Zooming and panning are disabled until the lock is released.
+///
+/// The shader widgets read this state while drawing, so it lives in a static like the
+/// slider's render-side state rather than on the app.
+use std::sync::Mutex;
+use iced_core::{Size, Vector};
+use once_cell::sync::Lazy;
+use log::info;
+
+const MAX_PANES: usize = 2;
+
+/// A crop in image coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LockedRegion {
+    /// Center of the view in image pixels
+    pub center_x: f32,
+    pub center_y: f32,
+    /// Screen pixels per image pixel
+    pub magnification: f32,
+}
+
+impl LockedRegion {
+    /// The region shown for a texture drawn at `scaled_size` and panned by `offset`
+    pub fn capture(texture_size: Size, scaled_size: Size, offset: Vector) -> Self {
+        let magnification = scaled_size.width / texture_size.width.max(1.0);
+        Self {
+            center_x: (scaled_size.width / 2.0 + offset.x) / magnification,
+            center_y: (scaled_size.height / 2.0 + offset.y) / magnification,
+            magnification,
+        }
+    }
+
+    /// The region showing the `view` fractions of a texture (left, top, right, bottom) as
+    /// large as they fit in `bounds_size`
+    pub fn fitting(texture_size: Size, bounds_size: Size, view: [f32; 4]) -> Self {
+        let [left, top, right, bottom] = view;
+        let width = (right - left).max(f32::EPSILON) * texture_size.width;
+        let height = (bottom - top).max(f32::EPSILON) * texture_size.height;
+        Self {
+            center_x: (left + right) / 2.0 * texture_size.width,
+            center_y: (top + bottom) / 2.0 * texture_size.height,
+            magnification: (bounds_size.width / width).min(bounds_size.height / height),
+        }
+    }
+
+    /// Zoom scale and pan offset showing this region of a texture whose unzoomed fit is
+    /// `base_size`, with the scale kept within `min_scale..=max_scale`
+    pub fn apply(&self, texture_size: Size, base_size: Size, min_scale: f32, max_scale: f32) -> (f32, Vector) {
+        let base_ratio = base_size.width / texture_size.width.max(1.0);
+        let scale = (self.magnification / base_ratio).clamp(min_scale, max_scale);
+        let magnification = base_ratio * scale;
+        let offset = Vector::new(
+            self.center_x * magnification - base_size.width * scale / 2.0,
+            self.center_y * magnification - base_size.height * scale / 2.0,
+        );
+        (scale, offset)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum PaneLock {
+    /// Capture the current view on the next draw
+    Requested,
+    Locked(LockedRegion),
+}
+
+static LOCKS: Lazy<Mutex<[Option<PaneLock>; MAX_PANES]>> = Lazy::new(|| Mutex::new([None; MAX_PANES]));
+
+/// Part of the image each pane shows once on its next draw, from an opened session
+static REQUESTED_VIEWS: Lazy<Mutex<[Option<[f32; 4]>; MAX_PANES]>> = Lazy::new(|| Mutex::new([None; MAX_PANES]));
+
+pub fn is_active() -> bool {
+    LOCKS.lock().map(|locks| locks.iter().any(Option::is_some)).unwrap_or(false)
+}
+
+/// Lock the view of every pane, or release the lock if one is held
+pub fn toggle() {
+    let Ok(mut locks) = LOCKS.lock() else {
+        return;
+    };
+    let locking = locks.iter().all(Option::is_none);
+    *locks = [if locking { Some(PaneLock::Requested) } else { None }; MAX_PANES];
+    info!("Zoom lock {}", if locking { "enabled" } else { "released" });
+}
+
+/// The locked region of `pane_index`, capturing it with `current` on the first call after
+/// locking. Returns `None` when the pane is not locked.
+pub fn region(pane_index: usize, current: impl FnOnce() -> LockedRegion) -> Option<LockedRegion> {
+    let mut locks = LOCKS.lock().ok()?;
+    let lock = locks.get_mut(pane_index)?;
+    match (*lock)? {
+        PaneLock::Locked(region) => Some(region),
+        PaneLock::Requested => {
+            let region = current();
+            *lock = Some(PaneLock::Locked(region));
+            Some(region)
+        }
+    }
+}
+
+/// Zoom and pan `pane_index` to show the `view` fractions of its image (left, top, right,
+/// bottom) on its next draw, without locking it
+pub fn request_view(pane_index: usize, view: [f32; 4]) {
+    if let Some(slot) = REQUESTED_VIEWS.lock().ok().as_mut().and_then(|views| views.get_mut(pane_index)) {
+        *slot = Some(view);
+    }
+}
+
+pub fn take_requested_view(pane_index: usize) -> Option<[f32; 4]> {
+    REQUESTED_VIEWS.lock().ok()?.get_mut(pane_index)?.take()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_round_trip() {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-3;
+        let texture = Size::new(1000.0, 500.0);
+        let base = Size::new(800.0, 400.0);
+        let region = LockedRegion::capture(texture, Size::new(base.width * 3.0, base.height * 3.0), Vector::new(120.0, -40.0));
+        assert!(close(region.magnification, 2.4));
+
+        let (scale, offset) = region.apply(texture, base, 0.25, 10.0);
+        assert!(close(scale, 3.0) && close(offset.x, 120.0) && close(offset.y, -40.0));
+
+        // A texture twice as large shows the same pixels at the same magnification
+        let (scale, _) = region.apply(Size::new(2000.0, 1000.0), base, 0.25, 10.0);
+        assert!(close(base.width / 2000.0 * scale, 2.4));
+    }
+
+    #[test]
+    fn test_fitting_view() {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-3;
+        let texture = Size::new(1000.0, 500.0);
+        let region = LockedRegion::fitting(texture, Size::new(800.0, 400.0), [0.25, 0.25, 0.75, 0.75]);
+        assert!(close(region.center_x, 500.0) && close(region.center_y, 250.0));
+        assert!(close(region.magnification, 1.6));
+
+        // The whole image fits like the unzoomed view
+        let base = Size::new(800.0, 400.0);
+        let (scale, offset) = LockedRegion::fitting(texture, base, [0.0, 0.0, 1.0, 1.0]).apply(texture, base, 0.25, 10.0);
+        assert!(close(scale, 1.0) && close(offset.x, 0.0) && close(offset.y, 0.0));
+    }
+}